@@ -10,40 +10,62 @@ pub use crate::{frame::ConnectionClose, inet::SocketAddress};
 /// to peers. This includes removing `reason` fields and making error codes more general.
 pub trait Formatter: 'static + Send {
     /// Formats a transport error for use in 1-RTT (application data) packets
-    fn format_transport_error(&self, context: &Context, error: transport::Error)
-        -> ConnectionClose;
+    fn format_transport_error<'a>(
+        &'a self,
+        context: &Context<'a>,
+        error: transport::Error,
+    ) -> ConnectionClose<'a>;
 
     /// Formats an application error for use in 1-RTT (application data) packets
-    fn format_application_error(
-        &self,
-        context: &Context,
+    fn format_application_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: application::Error,
-    ) -> ConnectionClose;
+    ) -> ConnectionClose<'a>;
 
     /// Formats a transport error for use in early (initial, handshake) packets
-    fn format_early_transport_error(
-        &self,
-        context: &Context,
+    fn format_early_transport_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: transport::Error,
-    ) -> ConnectionClose;
+    ) -> ConnectionClose<'a>;
 
     /// Formats an application error for use in early (initial, handshake) packets
-    fn format_early_application_error(
-        &self,
-        context: &Context,
+    fn format_early_application_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: application::Error,
-    ) -> ConnectionClose;
+    ) -> ConnectionClose<'a>;
 }
 
 #[non_exhaustive]
 #[derive(Debug)]
 pub struct Context<'a> {
     pub remote_address: &'a SocketAddress,
+    /// The reason phrase the application provided when closing the connection, if any
+    pub reason: Option<&'a [u8]>,
 }
 
 impl<'a> Context<'a> {
     pub fn new(remote_address: &'a SocketAddress) -> Self {
-        Self { remote_address }
+        Self {
+            remote_address,
+            reason: None,
+        }
+    }
+
+    /// Sets the reason phrase provided by the application
+    pub fn with_reason(mut self, reason: Option<&'a [u8]>) -> Self {
+        self.reason = reason;
+        self
+    }
+}
+
+/// Converts an application error into a frame with the reason phrase from the context
+fn application_close<'a>(context: &Context<'a>, error: application::Error) -> ConnectionClose<'a> {
+    ConnectionClose {
+        reason: context.reason,
+        ..error.into()
     }
 }
 
@@ -55,36 +77,36 @@ impl<'a> Context<'a> {
 pub struct Development;
 
 impl Formatter for Development {
-    fn format_transport_error(
-        &self,
-        _context: &Context,
+    fn format_transport_error<'a>(
+        &'a self,
+        _context: &Context<'a>,
         error: transport::Error,
-    ) -> ConnectionClose {
+    ) -> ConnectionClose<'a> {
         error.into()
     }
 
-    fn format_application_error(
-        &self,
-        _context: &Context,
+    fn format_application_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: application::Error,
-    ) -> ConnectionClose {
-        error.into()
+    ) -> ConnectionClose<'a> {
+        application_close(context, error)
     }
 
-    fn format_early_transport_error(
-        &self,
-        _context: &Context,
+    fn format_early_transport_error<'a>(
+        &'a self,
+        _context: &Context<'a>,
         error: transport::Error,
-    ) -> ConnectionClose {
+    ) -> ConnectionClose<'a> {
         error.into()
     }
 
-    fn format_early_application_error(
-        &self,
-        _context: &Context,
+    fn format_early_application_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: application::Error,
-    ) -> ConnectionClose {
-        error.into()
+    ) -> ConnectionClose<'a> {
+        application_close(context, error)
     }
 }
 
@@ -92,19 +114,19 @@ impl Formatter for Development {
 ///
 /// The following is performed:
 ///
-/// * Reasons and frame_types are hidden
+/// * Reasons and frame_types of transport errors are hidden
 /// * INTERNAL_ERROR is transformed into PROTOCOL_VIOLATION
-/// * Application codes are hidden in early (initial, handshake) packets
+/// * Application codes and reasons are hidden in early (initial, handshake) packets
 /// * Crypto (TLS) alerts are transformed into HANDSHAKE_FAILURE
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Production;
 
 impl Formatter for Production {
-    fn format_transport_error(
-        &self,
-        _context: &Context,
+    fn format_transport_error<'a>(
+        &'a self,
+        _context: &Context<'a>,
         error: transport::Error,
-    ) -> ConnectionClose {
+    ) -> ConnectionClose<'a> {
         // rewrite internal errors as PROTOCOL_VIOLATION
         if error.code == transport::Error::INTERNAL_ERROR.code {
             return transport::Error::PROTOCOL_VIOLATION.into();
@@ -124,27 +146,28 @@ impl Formatter for Production {
         transport::Error::new(error.code.as_varint()).into()
     }
 
-    fn format_application_error(
-        &self,
-        _context: &Context,
+    fn format_application_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: application::Error,
-    ) -> ConnectionClose {
-        error.into()
+    ) -> ConnectionClose<'a> {
+        // the application chose to send the reason, so it's not considered sensitive
+        application_close(context, error)
     }
 
-    fn format_early_transport_error(
-        &self,
-        context: &Context,
+    fn format_early_transport_error<'a>(
+        &'a self,
+        context: &Context<'a>,
         error: transport::Error,
-    ) -> ConnectionClose {
-        Self.format_transport_error(context, error)
+    ) -> ConnectionClose<'a> {
+        self.format_transport_error(context, error)
     }
 
-    fn format_early_application_error(
-        &self,
-        _context: &Context,
+    fn format_early_application_error<'a>(
+        &'a self,
+        _context: &Context<'a>,
         _error: application::Error,
-    ) -> ConnectionClose {
+    ) -> ConnectionClose<'a> {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.3
         //# Sending a CONNECTION_CLOSE of type 0x1d in an Initial or Handshake
         //# packet could expose application state or be used to alter application
@@ -158,3 +181,35 @@ impl Formatter for Production {
         transport::Error::APPLICATION_ERROR.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::varint::VarInt;
+
+    #[test]
+    fn application_reason_test() {
+        let remote_address = SocketAddress::default();
+        let context = Context::new(&remote_address).with_reason(Some(b"shutting down"));
+        let error = application::Error::from(7u8);
+
+        let frame = Production.format_application_error(&context, error);
+        assert_eq!(frame.error_code, VarInt::from_u8(7));
+        assert_eq!(frame.reason, Some(&b"shutting down"[..]));
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.3
+        //= type=test
+        //# Endpoints MUST clear the value of the
+        //# Reason Phrase field and SHOULD use the APPLICATION_ERROR code when
+        //# converting to a CONNECTION_CLOSE of type 0x1c.
+        let frame = Production.format_early_application_error(&context, error);
+        assert_eq!(frame.reason, None);
+
+        let frame = Development.format_application_error(&context, error);
+        assert_eq!(frame.reason, Some(&b"shutting down"[..]));
+
+        // transport errors don't include the application's reason
+        let frame = Development.format_transport_error(&context, transport::Error::NO_ERROR);
+        assert_eq!(frame.reason, None);
+    }
+}
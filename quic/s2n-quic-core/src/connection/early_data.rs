@@ -0,0 +1,137 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Controls whether a server processes 0-RTT data sent by a resuming client
+//!
+//! 0-RTT data is not protected against replay. An attacker that captures the first flight of
+//! a client is able to replay it to the server, possibly several times and to several server
+//! instances sharing the same session ticket keys. Only accept early data for applications
+//! where the requests carried in it are idempotent, or where the application implements its
+//! own replay mitigation (for example, single-use tickets or a strike register).
+
+use crate::{
+    event::{api::SocketAddress, IntoEvent},
+    inet,
+};
+
+//= https://www.rfc-editor.org/rfc/rfc9001#section-9.2
+//# Disabling 0-RTT entirely is the most effective defense against replay
+//# attack.
+
+/// Information about a connection that may send 0-RTT data
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct Attempt<'a> {
+    /// The address of the client
+    pub remote_address: SocketAddress<'a>,
+    /// Whether the client proved ownership of its address with a Retry token
+    pub is_address_validated: bool,
+}
+
+impl<'a> Attempt<'a> {
+    #[inline]
+    #[doc(hidden)]
+    pub fn new(remote_address: &'a inet::SocketAddress, is_address_validated: bool) -> Self {
+        Self {
+            remote_address: remote_address.into_event(),
+            is_address_validated,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// 0-RTT packets from the client are decrypted and processed
+    Accept,
+
+    /// 0-RTT packets from the client are dropped
+    ///
+    /// The client retransmits any data sent in the dropped packets once the handshake
+    /// completes.
+    Reject,
+}
+
+impl Outcome {
+    /// Returns `true` if early data is accepted
+    #[inline]
+    pub fn is_accept(self) -> bool {
+        matches!(self, Self::Accept)
+    }
+}
+
+/// Decides if a server accepts early data on a connection
+pub trait Policy: 'static + Send {
+    /// Called for each connection created by the server
+    fn on_early_data(&mut self, attempt: &Attempt) -> Outcome;
+
+    /// Returns `true` if the policy may accept early data on any connection
+    ///
+    /// Servers fail to start if this returns `true` while the TLS provider is not able to
+    /// accept early data.
+    #[inline]
+    fn may_accept(&self) -> bool {
+        true
+    }
+}
+
+/// Errors returned when validating the early data configuration of an endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The policy may accept early data but the TLS provider does not support it
+    UnsupportedTls,
+}
+
+impl Error {
+    fn message(&self) -> &'static str {
+        match self {
+            Error::UnsupportedTls => {
+                "the early data policy may accept early data, but the TLS provider is not configured to support it"
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+pub mod default {
+    use super::*;
+
+    /// Rejects early data on all connections
+    #[derive(Debug, Default)]
+    pub struct Policy;
+
+    impl super::Policy for Policy {
+        fn on_early_data(&mut self, _attempt: &Attempt) -> Outcome {
+            Outcome::Reject
+        }
+
+        fn may_accept(&self) -> bool {
+            false
+        }
+    }
+}
+
+pub mod accept_all {
+    use super::*;
+
+    /// Accepts early data on all connections
+    ///
+    /// This should only be used by applications that tolerate replayed early data.
+    #[derive(Debug, Default)]
+    pub struct Policy;
+
+    impl super::Policy for Policy {
+        fn on_early_data(&mut self, _attempt: &Attempt) -> Outcome {
+            Outcome::Accept
+        }
+    }
+}
@@ -3,16 +3,20 @@
 
 use crate::{
     ack,
+    crypto::application::limited,
     event::{api::SocketAddress, IntoEvent},
-    inet, stream,
+    inet::{self, Unspecified as _},
+    stream,
     transport::parameters::{
         AckDelayExponent, ActiveConnectionIdLimit, InitialFlowControlLimits, InitialMaxData,
         InitialMaxStreamDataBidiLocal, InitialMaxStreamDataBidiRemote, InitialMaxStreamDataUni,
         InitialMaxStreamsBidi, InitialMaxStreamsUni, InitialStreamLimits, MaxAckDelay,
-        MaxIdleTimeout, TransportParameters,
+        MaxDatagramFrameSize, MaxIdleTimeout, TransportParameters,
     },
+    varint::VarInt,
 };
 use core::{convert::TryInto, time::Duration};
+use s2n_codec::DecoderError;
 
 pub use crate::transport::parameters::ValidationError;
 
@@ -28,6 +32,24 @@ const MAX_HANDSHAKE_DURATION_DEFAULT: Duration = Duration::from_secs(10);
 //# middleboxes from losing state for UDP flows [GATEWAY].
 const MAX_KEEP_ALIVE_PERIOD_DEFAULT: Duration = Duration::from_secs(30);
 
+/// The default number of datagrams that can be queued for sending or receiving
+const DATAGRAM_QUEUE_CAPACITY_DEFAULT: usize = 64;
+
+/// The smallest number of packets accepted for the key update thresholds
+///
+/// Each key update takes at least one round trip to complete, so very small thresholds would
+/// leave the connection permanently waiting on the next key.
+const MIN_KEY_UPDATE_THRESHOLD: u64 = 1_000;
+
+const KEY_UPDATE_THRESHOLD_TOO_SMALL: DecoderError =
+    DecoderError::InvariantViolation("key update packet thresholds must be at least 1000");
+
+const UNSPECIFIED_PREFERRED_ADDRESS: DecoderError =
+    DecoderError::InvariantViolation("preferred address must include an IP address and port");
+
+const RECEIVE_WINDOW_TOO_LARGE: DecoderError =
+    DecoderError::InvariantViolation("receive windows must not exceed u32::MAX bytes");
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub struct ConnectionInfo<'a> {
@@ -47,10 +69,17 @@ impl<'a> ConnectionInfo<'a> {
 #[derive(Clone, Copy, Debug)]
 pub struct Limits {
     pub(crate) max_idle_timeout: MaxIdleTimeout,
+    /// The effective idle timeout when the peer's transport parameters were received, which
+    /// bounds any updates to the idle timeout
+    pub(crate) negotiated_max_idle_timeout: MaxIdleTimeout,
     pub(crate) data_window: InitialMaxData,
     pub(crate) bidirectional_local_data_window: InitialMaxStreamDataBidiLocal,
     pub(crate) bidirectional_remote_data_window: InitialMaxStreamDataBidiRemote,
     pub(crate) unidirectional_data_window: InitialMaxStreamDataUni,
+    /// The size the connection receive window can be grown to by auto-tuning
+    pub(crate) max_data_window: u32,
+    /// The size the stream receive windows can be grown to by auto-tuning
+    pub(crate) max_stream_data_window: u32,
     pub(crate) max_open_bidirectional_streams: InitialMaxStreamsBidi,
     pub(crate) max_open_local_unidirectional_streams: InitialMaxStreamsUni,
     pub(crate) max_open_remote_unidirectional_streams: InitialMaxStreamsUni,
@@ -62,6 +91,13 @@ pub struct Limits {
     pub(crate) max_send_buffer_size: u32,
     pub(crate) max_handshake_duration: Duration,
     pub(crate) max_keep_alive_period: Duration,
+    pub(crate) max_datagram_frame_size: MaxDatagramFrameSize,
+    pub(crate) datagram_send_queue_capacity: usize,
+    pub(crate) datagram_receive_queue_capacity: usize,
+    pub(crate) preferred_ipv4_address: Option<inet::SocketAddressV4>,
+    pub(crate) preferred_ipv6_address: Option<inet::SocketAddressV6>,
+    pub(crate) key_update_window: u64,
+    pub(crate) max_packets_per_key: u64,
 }
 
 impl Default for Limits {
@@ -83,10 +119,13 @@ impl Limits {
     pub const fn new() -> Self {
         Self {
             max_idle_timeout: MaxIdleTimeout::RECOMMENDED,
+            negotiated_max_idle_timeout: MaxIdleTimeout::DISABLED,
             data_window: InitialMaxData::RECOMMENDED,
             bidirectional_local_data_window: InitialMaxStreamDataBidiLocal::RECOMMENDED,
             bidirectional_remote_data_window: InitialMaxStreamDataBidiRemote::RECOMMENDED,
             unidirectional_data_window: InitialMaxStreamDataUni::RECOMMENDED,
+            max_data_window: 0,
+            max_stream_data_window: 0,
             max_open_bidirectional_streams: InitialMaxStreamsBidi::RECOMMENDED,
             max_open_local_unidirectional_streams: InitialMaxStreamsUni::RECOMMENDED,
             max_open_remote_unidirectional_streams: InitialMaxStreamsUni::RECOMMENDED,
@@ -98,6 +137,13 @@ impl Limits {
             max_send_buffer_size: stream::Limits::RECOMMENDED.max_send_buffer_size,
             max_handshake_duration: MAX_HANDSHAKE_DURATION_DEFAULT,
             max_keep_alive_period: MAX_KEEP_ALIVE_PERIOD_DEFAULT,
+            max_datagram_frame_size: MaxDatagramFrameSize::DISABLED,
+            datagram_send_queue_capacity: DATAGRAM_QUEUE_CAPACITY_DEFAULT,
+            datagram_receive_queue_capacity: DATAGRAM_QUEUE_CAPACITY_DEFAULT,
+            preferred_ipv4_address: None,
+            preferred_ipv6_address: None,
+            key_update_window: limited::KEY_UPDATE_WINDOW,
+            max_packets_per_key: u64::MAX,
         }
     }
 
@@ -148,6 +194,96 @@ impl Limits {
         Duration
     );
     setter!(with_max_keep_alive_period, max_keep_alive_period, Duration);
+    setter!(with_max_datagram_frame_size, max_datagram_frame_size, u64);
+    setter!(
+        with_datagram_send_queue_capacity,
+        datagram_send_queue_capacity,
+        usize
+    );
+    setter!(
+        with_datagram_receive_queue_capacity,
+        datagram_receive_queue_capacity,
+        usize
+    );
+
+    /// Enables auto-tuning of the connection receive window, up to the given size
+    ///
+    /// The window starts at the `data_window` and is doubled whenever the application reads a
+    /// full window of data within two round trips, which indicates that flow control limits the
+    /// throughput of the peer. Connections that transfer little data keep the initial window.
+    pub fn with_max_data_window(mut self, value: u64) -> Result<Self, ValidationError> {
+        self.max_data_window = value.try_into().map_err(|_| RECEIVE_WINDOW_TOO_LARGE)?;
+        Ok(self)
+    }
+
+    /// Enables auto-tuning of stream receive windows, up to the given size
+    ///
+    /// See [`Self::with_max_data_window`]. The connection receive window is grown along with
+    /// the stream windows, if auto-tuning is enabled for it.
+    pub fn with_max_stream_data_window(mut self, value: u64) -> Result<Self, ValidationError> {
+        self.max_stream_data_window = value.try_into().map_err(|_| RECEIVE_WINDOW_TOO_LARGE)?;
+        Ok(self)
+    }
+
+    /// Sets the IPv4 address that clients are asked to migrate to once the handshake is confirmed
+    ///
+    /// This allows a server to accept connections on an address shared by several hosts and
+    /// move them to an address that only routes to this host. The server must be able to
+    /// receive packets on the preferred address. This value is only used by servers.
+    pub fn with_preferred_ipv4_address<A: Into<inet::SocketAddressV4>>(
+        mut self,
+        address: A,
+    ) -> Result<Self, ValidationError> {
+        let address = address.into();
+        if address.ip().is_unspecified() || address.port() == 0 {
+            return Err(UNSPECIFIED_PREFERRED_ADDRESS.into());
+        }
+        self.preferred_ipv4_address = Some(address);
+        Ok(self)
+    }
+
+    /// Sets the IPv6 address that clients are asked to migrate to once the handshake is confirmed
+    ///
+    /// See [`Self::with_preferred_ipv4_address`]. This value is only used by servers.
+    pub fn with_preferred_ipv6_address<A: Into<inet::SocketAddressV6>>(
+        mut self,
+        address: A,
+    ) -> Result<Self, ValidationError> {
+        let address = address.into();
+        if address.ip().is_unspecified() || address.port() == 0 {
+            return Err(UNSPECIFIED_PREFERRED_ADDRESS.into());
+        }
+        self.preferred_ipv6_address = Some(address);
+        Ok(self)
+    }
+
+    /// Sets the number of packets before the AEAD confidentiality limit at which a 1-RTT key
+    /// update is initiated
+    ///
+    /// The key update must complete before the remaining packets are sent, otherwise the
+    /// connection is closed with an `AEAD_LIMIT_REACHED` error. The window must be at least
+    /// 1000 packets.
+    pub fn with_key_update_window(mut self, packets: u64) -> Result<Self, ValidationError> {
+        if packets < MIN_KEY_UPDATE_THRESHOLD {
+            return Err(KEY_UPDATE_THRESHOLD_TOO_SMALL.into());
+        }
+        self.key_update_window = packets;
+        Ok(self)
+    }
+
+    /// Sets the number of packets that are protected with a single 1-RTT key before a key
+    /// update is initiated
+    ///
+    /// By default, keys are only updated as they approach the confidentiality limit of the
+    /// negotiated AEAD. Lower values cause keys to be rotated periodically on long-lived
+    /// connections. The limit must be at least 1000 packets.
+    pub fn with_max_packets_per_key(mut self, packets: u64) -> Result<Self, ValidationError> {
+        if packets < MIN_KEY_UPDATE_THRESHOLD {
+            return Err(KEY_UPDATE_THRESHOLD_TOO_SMALL.into());
+        }
+        self.max_packets_per_key = packets;
+        Ok(self)
+    }
 
     // internal APIs
 
@@ -155,6 +291,44 @@ impl Limits {
     pub fn load_peer<A, B, C, D>(&mut self, peer_parameters: &TransportParameters<A, B, C, D>) {
         self.max_idle_timeout
             .load_peer(&peer_parameters.max_idle_timeout);
+        self.negotiated_max_idle_timeout = self.max_idle_timeout;
+    }
+
+    /// Applies the idle timeout and keep-alive period of an [`Update`]
+    ///
+    /// The idle timeout can only be reduced below the value negotiated during the handshake. The
+    /// peer expects the connection to be closed once the negotiated idle timeout elapses, so
+    /// keeping it open for longer would leave the connection without a peer. The keep-alive
+    /// period can be raised or lowered, but is bounded by the effective idle timeout.
+    ///
+    /// The remaining values apply to the stream manager of the connection.
+    #[doc(hidden)]
+    pub fn update(&mut self, update: &Update) {
+        if let Some(max_idle_timeout) = update.max_idle_timeout {
+            self.max_idle_timeout = max_idle_timeout;
+            self.max_idle_timeout
+                .load_peer(&self.negotiated_max_idle_timeout);
+        }
+
+        if let Some(max_keep_alive_period) = update.max_keep_alive_period {
+            self.max_keep_alive_period = match self.max_idle_timeout.as_duration() {
+                Some(max_idle_timeout) => max_keep_alive_period.min(max_idle_timeout),
+                None => max_keep_alive_period,
+            };
+        }
+    }
+
+    /// Returns `true` if the idle timeout was reduced below the value negotiated with the peer
+    #[doc(hidden)]
+    pub fn is_idle_timeout_reduced(&self) -> bool {
+        match (
+            self.max_idle_timeout.as_duration(),
+            self.negotiated_max_idle_timeout.as_duration(),
+        ) {
+            (Some(current), Some(negotiated)) => current < negotiated,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
     }
 
     #[doc(hidden)]
@@ -193,9 +367,15 @@ impl Limits {
             max_open_local_unidirectional_streams: self
                 .max_open_local_unidirectional_streams
                 .as_varint(),
+            max_receive_window: self.max_stream_data_window,
         }
     }
 
+    #[doc(hidden)]
+    pub fn max_data_window(&self) -> u32 {
+        self.max_data_window
+    }
+
     #[doc(hidden)]
     pub fn max_idle_timeout(&self) -> Option<Duration> {
         self.max_idle_timeout.as_duration()
@@ -210,6 +390,213 @@ impl Limits {
     pub fn max_keep_alive_period(&self) -> Duration {
         self.max_keep_alive_period
     }
+
+    #[doc(hidden)]
+    pub fn max_datagram_frame_size(&self) -> u64 {
+        self.max_datagram_frame_size.as_varint().as_u64()
+    }
+
+    #[doc(hidden)]
+    pub fn datagram_send_queue_capacity(&self) -> usize {
+        self.datagram_send_queue_capacity
+    }
+
+    #[doc(hidden)]
+    pub fn datagram_receive_queue_capacity(&self) -> usize {
+        self.datagram_receive_queue_capacity
+    }
+
+    #[doc(hidden)]
+    pub fn key_update_window(&self) -> u64 {
+        self.key_update_window
+    }
+
+    #[doc(hidden)]
+    pub fn max_packets_per_key(&self) -> u64 {
+        self.max_packets_per_key
+    }
+
+    #[doc(hidden)]
+    pub fn preferred_ipv4_address(&self) -> Option<inet::SocketAddressV4> {
+        self.preferred_ipv4_address
+    }
+
+    #[doc(hidden)]
+    pub fn preferred_ipv6_address(&self) -> Option<inet::SocketAddressV6> {
+        self.preferred_ipv6_address
+    }
+}
+
+/// Changes to the limits of a connection that has already been established
+///
+/// Only the values which are set are changed. The update is applied with
+/// `Connection::update_limits`.
+///
+/// # Examples
+///
+/// ```rust
+/// use s2n_quic_core::connection::limits::Update;
+///
+/// let update = Update::new()
+///     .with_max_open_remote_bidirectional_streams(1000)
+///     .unwrap()
+///     .with_data_window(16 * 1024 * 1024)
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Update {
+    pub(crate) max_idle_timeout: Option<MaxIdleTimeout>,
+    pub(crate) max_keep_alive_period: Option<Duration>,
+    pub(crate) data_window: Option<u32>,
+    pub(crate) bidirectional_local_data_window: Option<u32>,
+    pub(crate) bidirectional_remote_data_window: Option<u32>,
+    pub(crate) unidirectional_data_window: Option<u32>,
+    pub(crate) max_open_remote_bidirectional_streams: Option<InitialMaxStreamsBidi>,
+    pub(crate) max_open_remote_unidirectional_streams: Option<InitialMaxStreamsUni>,
+}
+
+macro_rules! update_setter {
+    ($(#[doc = $doc:literal])* $name:ident, $field:ident, $inner:ty) => {
+        $(#[doc = $doc])*
+        pub fn $name(mut self, value: $inner) -> Result<Self, ValidationError> {
+            self.$field = Some(value.try_into()?);
+            Ok(self)
+        }
+    };
+}
+
+macro_rules! window_setter {
+    ($(#[doc = $doc:literal])* $name:ident, $field:ident) => {
+        $(#[doc = $doc])*
+        pub fn $name(mut self, value: u64) -> Result<Self, ValidationError> {
+            let value: u32 = value.try_into().map_err(|_| RECEIVE_WINDOW_TOO_LARGE)?;
+            self.$field = Some(value);
+            Ok(self)
+        }
+    };
+}
+
+impl Update {
+    pub const fn new() -> Self {
+        Self {
+            max_idle_timeout: None,
+            max_keep_alive_period: None,
+            data_window: None,
+            bidirectional_local_data_window: None,
+            bidirectional_remote_data_window: None,
+            unidirectional_data_window: None,
+            max_open_remote_bidirectional_streams: None,
+            max_open_remote_unidirectional_streams: None,
+        }
+    }
+
+    update_setter!(
+        /// Sets the idle timeout of the connection
+        ///
+        /// The timeout can only be reduced; it can't exceed the value negotiated during the
+        /// handshake. If the timeout is reduced below that value, the peer is notified with a
+        /// CONNECTION_CLOSE frame when the connection times out.
+        with_max_idle_timeout,
+        max_idle_timeout,
+        Duration
+    );
+
+    update_setter!(
+        /// Sets the maximum period between packets sent to keep the connection alive
+        ///
+        /// The period can't exceed the effective idle timeout of the connection.
+        with_max_keep_alive_period,
+        max_keep_alive_period,
+        Duration
+    );
+
+    window_setter!(
+        /// Sets the number of bytes the peer can send on the connection before the application
+        /// reads them
+        with_data_window,
+        data_window
+    );
+
+    window_setter!(
+        /// Sets the receive window of bidirectional streams opened by the local endpoint
+        ///
+        /// Only streams opened after the update use the new window.
+        with_bidirectional_local_data_window,
+        bidirectional_local_data_window
+    );
+
+    window_setter!(
+        /// Sets the receive window of bidirectional streams opened by the peer
+        ///
+        /// Only streams opened after the update use the new window.
+        with_bidirectional_remote_data_window,
+        bidirectional_remote_data_window
+    );
+
+    window_setter!(
+        /// Sets the receive window of unidirectional streams opened by the peer
+        ///
+        /// Only streams opened after the update use the new window.
+        with_unidirectional_data_window,
+        unidirectional_data_window
+    );
+
+    update_setter!(
+        /// Sets the number of bidirectional streams the peer can have open concurrently
+        ///
+        /// Increases are sent to the peer in a MAX_STREAMS frame. Since the stream limit
+        /// can't be reduced once it is sent, a lower value only takes effect as streams close.
+        with_max_open_remote_bidirectional_streams,
+        max_open_remote_bidirectional_streams,
+        u64
+    );
+
+    update_setter!(
+        /// Sets the number of unidirectional streams the peer can have open concurrently
+        ///
+        /// Increases are sent to the peer in a MAX_STREAMS frame. Since the stream limit
+        /// can't be reduced once it is sent, a lower value only takes effect as streams close.
+        with_max_open_remote_unidirectional_streams,
+        max_open_remote_unidirectional_streams,
+        u64
+    );
+
+    // internal APIs
+
+    #[doc(hidden)]
+    pub fn data_window(&self) -> Option<u32> {
+        self.data_window
+    }
+
+    /// Returns the updated receive windows for streams, if any were set
+    #[doc(hidden)]
+    pub fn stream_receive_windows(&self, current: InitialStreamLimits) -> InitialStreamLimits {
+        let window = |value: Option<u32>, current: VarInt| value.map_or(current, VarInt::from_u32);
+
+        InitialStreamLimits {
+            max_data_bidi_local: window(
+                self.bidirectional_local_data_window,
+                current.max_data_bidi_local,
+            ),
+            max_data_bidi_remote: window(
+                self.bidirectional_remote_data_window,
+                current.max_data_bidi_remote,
+            ),
+            max_data_uni: window(self.unidirectional_data_window, current.max_data_uni),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn max_open_remote_streams(&self, stream_type: stream::StreamType) -> Option<VarInt> {
+        match stream_type {
+            stream::StreamType::Bidirectional => self
+                .max_open_remote_bidirectional_streams
+                .map(|value| value.as_varint()),
+            stream::StreamType::Unidirectional => self
+                .max_open_remote_unidirectional_streams
+                .map(|value| value.as_varint()),
+        }
+    }
 }
 
 /// Creates limits for a given connection
@@ -223,3 +610,110 @@ impl Limiter for Limits {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::parameters::ClientTransportParameters;
+
+    #[test]
+    fn idle_timeout_update_test() {
+        let mut limits = Limits::new()
+            .with_max_idle_timeout(Duration::from_secs(30))
+            .unwrap();
+        let peer_parameters = ClientTransportParameters {
+            max_idle_timeout: Duration::from_secs(60).try_into().unwrap(),
+            ..Default::default()
+        };
+        limits.load_peer(&peer_parameters);
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(30)));
+
+        // the timeout can't exceed the negotiated value, which is the lower of the two
+        let update = Update::new()
+            .with_max_idle_timeout(Duration::from_secs(120))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(30)));
+        assert!(!limits.is_idle_timeout_reduced());
+
+        let update = Update::new()
+            .with_max_idle_timeout(Duration::from_secs(10))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(10)));
+        assert!(limits.is_idle_timeout_reduced());
+
+        // updates without an idle timeout keep the current value
+        limits.update(&Update::new());
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(10)));
+
+        // a reduced timeout can be raised back up to the negotiated value
+        let update = Update::new()
+            .with_max_idle_timeout(Duration::from_secs(45))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(30)));
+        assert!(!limits.is_idle_timeout_reduced());
+
+        // the timeout can't be disabled once negotiated
+        let update = Update::new()
+            .with_max_idle_timeout(Duration::from_secs(0))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_idle_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn keep_alive_update_test() {
+        let mut limits = Limits::new()
+            .with_max_keep_alive_period(Duration::from_secs(10))
+            .unwrap();
+        limits.load_peer(&ClientTransportParameters::default());
+
+        let update = Update::new()
+            .with_max_keep_alive_period(Duration::from_secs(20))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_keep_alive_period(), Duration::from_secs(20));
+
+        let update = Update::new()
+            .with_max_keep_alive_period(Duration::from_secs(5))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_keep_alive_period(), Duration::from_secs(5));
+
+        // the period is bounded by the effective idle timeout
+        let update = Update::new()
+            .with_max_idle_timeout(Duration::from_secs(15))
+            .unwrap()
+            .with_max_keep_alive_period(Duration::from_secs(20))
+            .unwrap();
+        limits.update(&update);
+        assert_eq!(limits.max_keep_alive_period(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn receive_window_validation_test() {
+        assert!(Update::new().with_data_window(u32::MAX as u64).is_ok());
+        assert!(Update::new().with_data_window(u32::MAX as u64 + 1).is_err());
+        assert!(Update::new()
+            .with_unidirectional_data_window(u32::MAX as u64 + 1)
+            .is_err());
+        assert!(Limits::new()
+            .with_max_data_window(u32::MAX as u64 + 1)
+            .is_err());
+        assert!(Limits::new()
+            .with_max_stream_data_window(u32::MAX as u64 + 1)
+            .is_err());
+    }
+
+    #[test]
+    fn key_update_threshold_validation_test() {
+        assert!(Limits::new().with_key_update_window(0).is_err());
+        assert!(Limits::new().with_key_update_window(999).is_err());
+        assert!(Limits::new().with_key_update_window(1_000).is_ok());
+        assert!(Limits::new().with_max_packets_per_key(0).is_err());
+        assert!(Limits::new().with_max_packets_per_key(999).is_err());
+        assert!(Limits::new().with_max_packets_per_key(1_000).is_ok());
+    }
+}
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod close;
+pub mod early_data;
 pub mod error;
 pub mod id;
 pub mod limits;
+pub mod stats;
 
 pub use error::{Error, ProcessingError};
 pub use id::{InitialId, LocalId, PeerId, UnboundedId};
 pub use limits::Limits;
+pub use stats::Stats;
@@ -0,0 +1,66 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{frame::ack::EcnCounts, inet::SocketAddress, recovery::bandwidth::Bandwidth};
+use core::time::Duration;
+
+/// A snapshot of the statistics for a connection
+///
+/// The RTT, congestion control and MTU values describe the currently active path. The packet
+/// and byte counters are totals over every path the connection has used.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The local address of the active path
+    pub local_address: SocketAddress,
+    /// The remote address of the active path
+    pub remote_address: SocketAddress,
+
+    /// The minimum RTT observed on the active path
+    pub min_rtt: Duration,
+    /// The exponentially-weighted moving average RTT of the active path
+    pub smoothed_rtt: Duration,
+    /// The most recent RTT sample of the active path
+    pub latest_rtt: Duration,
+    /// The variation in the RTT samples of the active path
+    pub rtt_variance: Duration,
+    /// The maximum amount of time the peer intends to delay acknowledgements
+    pub max_ack_delay: Duration,
+    /// The number of consecutive probe timeouts (PTOs) that have expired without an
+    /// acknowledgement from the peer
+    pub pto_count: u32,
+
+    /// The congestion window of the active path, in bytes
+    pub congestion_window: u32,
+    /// The number of bytes sent on the active path that have not been acknowledged or
+    /// declared lost
+    pub bytes_in_flight: u32,
+    /// The rate at which the congestion controller is pacing packets, if it reports one
+    pub pacing_rate: Option<Bandwidth>,
+    /// The maximum size of a UDP payload that can be sent on the active path
+    pub max_datagram_size: u16,
+
+    /// The number of packets sent
+    pub packets_sent: u64,
+    /// The number of bytes sent in packets, including QUIC headers and AEAD tags
+    pub bytes_sent: u64,
+    /// The number of packets received and successfully processed
+    pub packets_received: u64,
+    /// The number of UDP payload bytes received
+    pub bytes_received: u64,
+    /// The number of packets declared lost
+    pub packets_lost: u64,
+    /// The number of congestion controlled bytes in packets declared lost
+    pub bytes_lost: u64,
+    /// The number of packets sent that carried stream or crypto data from lost packets
+    ///
+    /// These packets are also included in `packets_sent`.
+    pub packets_retransmitted: u64,
+    /// The number of stream and crypto data bytes sent again after being declared lost
+    pub bytes_retransmitted: u64,
+
+    /// The ECN markings set on packets sent
+    pub ecn_sent: EcnCounts,
+    /// The ECN markings on datagrams received
+    pub ecn_received: EcnCounts,
+}
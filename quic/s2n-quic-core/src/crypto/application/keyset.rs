@@ -35,6 +35,9 @@ pub struct KeySet<K> {
     crypto: KeyArray<K>,
 
     limits: limited::Limits,
+
+    /// Set when the application requests a key update before the limits require one
+    key_update_requested: bool,
 }
 
 impl<K: OneRttKey> KeySet<K> {
@@ -68,6 +71,7 @@ impl<K: OneRttKey> KeySet<K> {
             generation: 0,
             crypto: KeyArray([active_key, next_key]),
             limits,
+            key_update_requested: false,
         }
     }
 
@@ -75,6 +79,16 @@ impl<K: OneRttKey> KeySet<K> {
     fn rotate_phase(&mut self) {
         self.generation += 1;
         self.key_phase = KeyPhase::next_phase(self.key_phase);
+        self.key_update_requested = false;
+    }
+
+    /// Requests that the next packets are encrypted with the next key phase
+    ///
+    /// The caller is responsible for only requesting an update once the handshake is confirmed.
+    /// The update is completed when a packet protected with the next key phase is received
+    /// from the peer.
+    pub fn request_key_update(&mut self) {
+        self.key_update_requested = true;
     }
 
     /// Derive a new key based on the active key, and store it in the non-active slot
@@ -218,6 +232,15 @@ impl<K: OneRttKey> KeySet<K> {
             return KeyPhase::next_phase(self.key_phase());
         }
 
+        // The next key is only available once the previous update has finished and the
+        // derivation timer has fired. Until then, the configured packet limit and any requested
+        // update remain pending, as switching phases would reuse the retired key.
+        if (self.key_update_requested || self.active_key().reached_packet_limit(&self.limits))
+            && !self.key_update_in_progress()
+        {
+            return KeyPhase::next_phase(self.key_phase());
+        }
+
         self.key_phase()
     }
 
@@ -596,4 +619,114 @@ mod tests {
             Err(PacketEncodingError::AeadLimitReached(_))
         ));
     }
+
+    #[test]
+    fn test_max_packets_per_key() {
+        let key = TestKey {
+            confidentiality_limit: 10_000_000,
+            ..Default::default()
+        };
+        let limits = limited::Limits {
+            max_packets_per_key: 3,
+            ..Default::default()
+        };
+        let mut keyset = KeySet::new(key, limits);
+        let mut encoder_bytes = [0; 512];
+
+        for _ in 0..3 {
+            assert_eq!(keyset.encryption_phase(), KeyPhase::Zero);
+            let buffer = EncoderBuffer::new(&mut encoder_bytes);
+            let mut decoder_bytes = [0; 512];
+            assert!(keyset
+                .encrypt_packet(buffer, |buffer, _key, _phase| {
+                    let payload = ProtectedPayload::new(0, &mut decoder_bytes);
+
+                    Ok((payload, buffer))
+                })
+                .is_ok());
+        }
+
+        // The configured packet limit was reached well before the AEAD limit
+        assert!(keyset.active_key().reached_packet_limit(&keyset.limits));
+        assert!(!keyset.active_key().needs_update(&keyset.limits));
+        assert_eq!(keyset.encryption_phase(), KeyPhase::One);
+    }
+
+    #[test]
+    fn test_max_packets_per_key_during_update() {
+        let clock = Clock::default();
+        let key = TestKey {
+            confidentiality_limit: 10_000_000,
+            ..Default::default()
+        };
+        let limits = limited::Limits {
+            max_packets_per_key: 2,
+            ..Default::default()
+        };
+        let mut keyset = KeySet::new(key, limits);
+        let mut encoder_bytes = [0; 512];
+        let mut sent = vec![];
+
+        let pto = clock.get_time() + Duration::from_millis(10);
+        for i in 0..12 {
+            // Send past the limit several times before the derivation timer fires
+            if i == 8 {
+                keyset.on_timeout(pto);
+            }
+
+            let buffer = EncoderBuffer::new(&mut encoder_bytes);
+            let mut decoder_bytes = [0; 512];
+            assert!(keyset
+                .encrypt_packet(buffer, |buffer, key, phase| {
+                    sent.push((phase, key.derivations));
+                    let payload = ProtectedPayload::new(0, &mut decoder_bytes);
+
+                    Ok((payload, buffer))
+                })
+                .is_ok());
+
+            // The peer responds to the update within the same PTO
+            let (phase, _) = sent[i];
+            if phase != keyset.key_phase() {
+                keyset.rotate_phase();
+                keyset.set_derivation_timer(pto);
+            }
+        }
+
+        let mut generation = 0;
+        for (phase, derivations) in sent.iter().copied() {
+            // Every packet is protected with a key derived for the phase it is sent with
+            assert_eq!(derivations % 2, phase as u64);
+            // Retired keys are never used again
+            assert!(derivations >= generation);
+            generation = derivations;
+        }
+
+        // Only the update after the derivation timer fired is started
+        assert_eq!(generation, 2);
+    }
+
+    #[test]
+    fn test_requested_key_update() {
+        let clock = Clock::default();
+        let mut keyset = KeySet::new(TestKey::default(), Default::default());
+
+        assert_eq!(keyset.encryption_phase(), KeyPhase::Zero);
+        keyset.request_key_update();
+        assert_eq!(keyset.encryption_phase(), KeyPhase::One);
+
+        // The peer responds with the next key phase, which completes the update
+        keyset.rotate_phase();
+        keyset.set_derivation_timer(clock.get_time() + Duration::from_millis(10));
+        assert_eq!(keyset.encryption_phase(), KeyPhase::One);
+
+        // A request while the next key is not yet derived is deferred
+        keyset.request_key_update();
+        assert!(keyset.key_update_in_progress());
+        assert_eq!(keyset.encryption_phase(), KeyPhase::One);
+
+        keyset.on_timeout(clock.get_time() + Duration::from_millis(10));
+        assert!(!keyset.key_update_in_progress());
+        assert_eq!(keyset.encryption_phase(), KeyPhase::Zero);
+    }
 }
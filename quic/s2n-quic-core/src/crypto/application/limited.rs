@@ -21,6 +21,9 @@ pub struct Key<K> {
 pub struct Limits {
     /// The number of packets before the limit at which a key update will be scheduled
     pub key_update_window: u64,
+    /// The number of packets encrypted with a single key after which a key update will be
+    /// scheduled, regardless of the AEAD confidentiality limit
+    pub max_packets_per_key: u64,
     /// The number of packets at which the sealer key will be optimized
     pub sealer_optimization_threshold: u64,
     /// The number of packets at which the opener key will be optimized
@@ -33,6 +36,7 @@ impl Default for Limits {
     fn default() -> Self {
         Self {
             key_update_window: KEY_UPDATE_WINDOW,
+            max_packets_per_key: u64::MAX,
             sealer_optimization_threshold: 100,
             opener_optimization_threshold: 100,
             max_mtu: MaxMtu::default(),
@@ -40,16 +44,13 @@ impl Default for Limits {
     }
 }
 
-// TODO: This should be configured through the limit provider.
-// https://github.com/aws/s2n-quic/issues/322
-const KEY_UPDATE_WINDOW: u64 = 10_000;
+/// The default number of packets before the confidentiality limit at which a key update will
+/// be scheduled
+pub const KEY_UPDATE_WINDOW: u64 = 10_000;
 
 impl<K: OneRttKey> Key<K> {
     pub fn new(key: K) -> Self {
         Key {
-            // TODO: This should be configured through the limit provider, the default being the
-            // key's AEAD limit.
-            // https://github.com/aws/s2n-quic/issues/322
             confidentiality_limit: key.aead_confidentiality_limit(),
             key,
             encrypted_packets: 0,
@@ -65,7 +66,7 @@ impl<K: OneRttKey> Key<K> {
         self.encrypted_packets >= self.confidentiality_limit
     }
 
-    /// If the key is within the update window, an update should be initiated.
+    /// If the key is within the update window an update should be initiated.
     #[inline]
     pub fn needs_update(&self, limits: &Limits) -> bool {
         self.encrypted_packets
@@ -74,6 +75,15 @@ impl<K: OneRttKey> Key<K> {
                 .saturating_sub(limits.key_update_window))
     }
 
+    /// Returns true if the key has protected the configured maximum number of packets
+    ///
+    /// Unlike [`Self::needs_update`], reaching this limit does not put the connection at risk,
+    /// so the update can wait until the next key is available.
+    #[inline]
+    pub fn reached_packet_limit(&self, limits: &Limits) -> bool {
+        self.encrypted_packets >= limits.max_packets_per_key
+    }
+
     pub fn derive_next_key(&self) -> K {
         self.key.derive_next_key()
     }
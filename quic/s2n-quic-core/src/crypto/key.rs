@@ -115,11 +115,11 @@ pub mod testing {
     impl InitialKey for Key {
         type HeaderKey = HeaderKey;
 
-        fn new_server(_connection_id: &[u8]) -> (Self, Self::HeaderKey) {
+        fn new_server(_version: u32, _connection_id: &[u8]) -> (Self, Self::HeaderKey) {
             (Key::default(), HeaderKey::default())
         }
 
-        fn new_client(_connection_id: &[u8]) -> (Self, Self::HeaderKey) {
+        fn new_client(_version: u32, _connection_id: &[u8]) -> (Self, Self::HeaderKey) {
             (Key::default(), HeaderKey::default())
         }
     }
@@ -139,10 +139,10 @@ pub mod testing {
     }
     impl ZeroRttKey for Key {}
     impl RetryKey for Key {
-        fn generate_tag(_payload: &[u8]) -> IntegrityTag {
+        fn generate_tag(_version: u32, _payload: &[u8]) -> IntegrityTag {
             [0u8; INTEGRITY_TAG_LEN]
         }
-        fn validate(_payload: &[u8], _tag: IntegrityTag) -> Result<(), CryptoError> {
+        fn validate(_version: u32, _payload: &[u8], _tag: IntegrityTag) -> Result<(), CryptoError> {
             Ok(())
         }
     }
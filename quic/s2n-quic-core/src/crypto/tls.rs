@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{application::ServerName, crypto::CryptoSuite, transport};
+use crate::{application::ServerName, crypto::CryptoSuite, packet, transport};
 pub use bytes::{Bytes, BytesMut};
 use core::{
     convert::TryFrom,
@@ -11,6 +11,10 @@ use core::{
 use s2n_codec::EncoderValue;
 use zerocopy::{AsBytes, FromBytes, Unaligned};
 
+pub mod client_hello;
+pub mod server_hello;
+pub mod session_ticket;
+
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
 
@@ -49,6 +53,14 @@ pub trait Context<Crypto: CryptoSuite> {
         application_parameters: ApplicationParameters,
     ) -> Result<(), transport::Error>;
 
+    //= https://www.rfc-editor.org/rfc/rfc9001#section-4.6.2
+    //# When 0-RTT is rejected, all connection characteristics that the
+    //# client assumed might be incorrect.
+    /// Called on the client when the server rejected the 0-RTT data
+    ///
+    /// This is emitted before the 1-RTT keys are made available.
+    fn on_zero_rtt_rejected(&mut self) -> Result<(), transport::Error>;
+
     fn on_server_name(
         &mut self,
         server_name: crate::application::ServerName,
@@ -59,6 +71,10 @@ pub trait Context<Crypto: CryptoSuite> {
         application_protocol: Bytes,
     ) -> Result<(), transport::Error>;
 
+    /// Called with the details of the negotiated TLS session before the handshake completes
+    #[cfg(feature = "alloc")]
+    fn on_handshake_info(&mut self, info: HandshakeInfo) -> Result<(), transport::Error>;
+
     //= https://www.rfc-editor.org/rfc/rfc9001#section-4.1.1
     //# The TLS handshake is considered complete when the
     //# TLS stack has reported that the handshake is complete.  This happens
@@ -94,11 +110,24 @@ pub trait Context<Crypto: CryptoSuite> {
     fn send_application(&mut self, transmission: Bytes);
 
     fn waker(&self) -> &Waker;
+
+    /// Returns the QUIC version in use for the connection
+    ///
+    /// The version determines the labels used to derive packet protection keys from the
+    /// TLS secrets.
+    fn quic_version(&self) -> u32;
 }
 
 pub trait Endpoint: 'static + Sized + Send {
     type Session: Session;
 
+    /// The QUIC versions that sessions created by the endpoint are able to derive packet
+    /// protection keys for, in order of preference
+    ///
+    /// Connections are only established with these versions, which must each be supported by
+    /// the transport.
+    const QUIC_VERSIONS: &'static [u32] = &[packet::long::VERSION_1, packet::long::VERSION_2];
+
     fn new_server_session<Params: EncoderValue>(
         &mut self,
         transport_parameters: &Params,
@@ -112,13 +141,61 @@ pub trait Endpoint: 'static + Sized + Send {
 
     /// The maximum length of a tag for any algorithm that may be negotiated
     fn max_tag_length(&self) -> usize;
+
+    /// Returns `true` if sessions created by the endpoint are able to send or accept early data
+    #[inline]
+    fn supports_early_data(&self) -> bool {
+        false
+    }
 }
 
 pub trait Session: CryptoSuite + Sized + Send + Debug {
     fn poll<C: Context<Self>>(&mut self, context: &mut C) -> Poll<Result<(), transport::Error>>;
+
+    /// Prevents a server session from accepting early data from the client
+    ///
+    /// This is called before the ClientHello is processed. Sessions that don't support 0-RTT
+    /// may ignore it.
+    #[inline]
+    fn reject_early_data(&mut self) {}
+
+    /// Replaces the transport parameters sent to the peer
+    ///
+    /// This is called on servers before the ClientHello is processed, when compatible version
+    /// negotiation selects a version other than the one the client started with. Sessions that
+    /// can't change their transport parameters return `false`, in which case the connection
+    /// continues with the original version.
+    #[inline]
+    fn set_transport_parameters<Params: EncoderValue>(
+        &mut self,
+        transport_parameters: &Params,
+    ) -> bool {
+        let _ = transport_parameters;
+        false
+    }
+}
+
+/// Details of the TLS session negotiated with the peer
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct HandshakeInfo {
+    /// The cipher suite used to protect 1-RTT packets
+    pub cipher_suite: CipherSuite,
+    /// The DER-encoded certificate chain presented by the peer, starting with its own certificate
+    ///
+    /// This is empty if the peer didn't authenticate with a certificate, which is the case for
+    /// clients unless the server requires client authentication. The chain is shared so the
+    /// info can be cloned cheaply.
+    pub peer_certificate_chain: alloc::sync::Arc<[Bytes]>,
+    /// Whether the session was resumed with a session ticket
+    ///
+    /// The peer doesn't present its certificates when a session is resumed. Providers that
+    /// don't store them with the session ticket, such as s2n-tls, report an empty
+    /// `peer_certificate_chain` for resumed sessions.
+    pub is_resumed: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum CipherSuite {
     TLS_AES_128_GCM_SHA256,
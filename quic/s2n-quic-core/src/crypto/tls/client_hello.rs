@@ -0,0 +1,115 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads extensions from a ClientHello before it is handed to the TLS provider
+
+use s2n_codec::{DecoderBuffer, DecoderError};
+
+//= https://www.rfc-editor.org/rfc/rfc9001#section-8.2
+//# enum {
+//#    quic_transport_parameters(0x39), (65535)
+//# } ExtensionType;
+/// The `quic_transport_parameters` extension type
+pub const QUIC_TRANSPORT_PARAMETERS: u16 = 0x39;
+
+/// The `early_data` extension type, which is included when the client attempts 0-RTT
+pub const EARLY_DATA: u16 = 42;
+
+/// The extensions of a ClientHello message
+#[derive(Clone, Copy, Debug)]
+pub struct ClientHello<'a> {
+    extensions: DecoderBuffer<'a>,
+}
+
+impl<'a> ClientHello<'a> {
+    //= https://www.rfc-editor.org/rfc/rfc8446#section-4.1.2
+    //# struct {
+    //#     ProtocolVersion legacy_version = 0x0303;    /* TLS v1.2 */
+    //#     Random random;
+    //#     opaque legacy_session_id<0..32>;
+    //#     CipherSuite cipher_suites<2..2^16-2>;
+    //#     opaque legacy_compression_methods<1..2^8-1>;
+    //#     Extension extensions<8..2^16-1>;
+    //# } ClientHello;
+    /// Decodes the body of a ClientHello message, without the handshake header
+    pub fn decode(buffer: DecoderBuffer<'a>) -> Result<Self, DecoderError> {
+        let buffer = buffer.skip(2)?; // legacy_version
+        let buffer = buffer.skip(32)?; // random
+        let buffer = buffer.skip_with_len_prefix::<u8>()?; // legacy_session_id
+        let buffer = buffer.skip_with_len_prefix::<u16>()?; // cipher_suites
+        let buffer = buffer.skip_with_len_prefix::<u8>()?; // legacy_compression_methods
+        let (extensions, _) = buffer.decode_slice_with_len_prefix::<u16>()?;
+
+        Ok(Self { extensions })
+    }
+
+    //= https://www.rfc-editor.org/rfc/rfc8446#section-4.2
+    //# struct {
+    //#     ExtensionType extension_type;
+    //#     opaque extension_data<0..2^16-1>;
+    //# } Extension;
+    /// Returns the data of the extension with the given type, if the client included it
+    pub fn extension(&self, extension_type: u16) -> Result<Option<&'a [u8]>, DecoderError> {
+        let mut buffer = self.extensions;
+
+        while !buffer.is_empty() {
+            let (ty, remaining) = buffer.decode::<u16>()?;
+            let (data, remaining) = remaining.decode_slice_with_len_prefix::<u16>()?;
+
+            if ty == extension_type {
+                return Ok(Some(data.into_less_safe_slice()));
+            }
+
+            buffer = remaining;
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_hello(extensions: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut extension_bytes = vec![];
+        for (ty, data) in extensions {
+            extension_bytes.extend_from_slice(&ty.to_be_bytes());
+            extension_bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            extension_bytes.extend_from_slice(data);
+        }
+
+        let mut hello = vec![0x03, 0x03];
+        hello.extend_from_slice(&[0xaa; 32]);
+        hello.extend_from_slice(&[4, 1, 2, 3, 4]);
+        hello.extend_from_slice(&[0, 2, 0x13, 0x01]);
+        hello.extend_from_slice(&[1, 0]);
+        hello.extend_from_slice(&(extension_bytes.len() as u16).to_be_bytes());
+        hello.extend_from_slice(&extension_bytes);
+        hello
+    }
+
+    #[test]
+    fn extension_test() {
+        let hello = client_hello(&[(0, &[1, 2, 3]), (QUIC_TRANSPORT_PARAMETERS, &[4, 5])]);
+        let hello = ClientHello::decode(DecoderBuffer::new(&hello)).unwrap();
+
+        assert_eq!(
+            hello.extension(QUIC_TRANSPORT_PARAMETERS).unwrap(),
+            Some(&[4, 5][..])
+        );
+        assert_eq!(hello.extension(0).unwrap(), Some(&[1, 2, 3][..]));
+        assert_eq!(hello.extension(EARLY_DATA).unwrap(), None);
+    }
+
+    #[test]
+    fn truncated_test() {
+        let hello = client_hello(&[(QUIC_TRANSPORT_PARAMETERS, &[4, 5])]);
+
+        for len in 0..hello.len() {
+            let result = ClientHello::decode(DecoderBuffer::new(&hello[..len]))
+                .and_then(|hello| hello.extension(QUIC_TRANSPORT_PARAMETERS));
+            assert!(result.is_err(), "len {} should fail to decode", len);
+        }
+    }
+}
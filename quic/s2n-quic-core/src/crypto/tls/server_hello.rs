@@ -0,0 +1,126 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads extensions from a ServerHello, for TLS providers that don't expose them
+
+use s2n_codec::{DecoderBuffer, DecoderError};
+
+/// The `pre_shared_key` extension type, which the server includes when it resumes a session
+pub const PRE_SHARED_KEY: u16 = 41;
+
+//= https://www.rfc-editor.org/rfc/rfc8446#section-4.1.3
+//# For reasons of backward compatibility with middleboxes (see
+//# Appendix D.4), the HelloRetryRequest message uses the same structure
+//# as the ServerHello, but with Random set to the special value of the
+//# SHA-256 of "HelloRetryRequest":
+//#
+//#   CF 21 AD 74 E5 9A 61 11 BE 1D 8C 02 1E 65 B8 91
+//#   C2 A2 11 16 7A BB 8C 5E 07 9E 09 E2 C8 A8 33 9C
+const HELLO_RETRY_REQUEST_RANDOM: [u8; 32] = [
+    0xCF, 0x21, 0xAD, 0x74, 0xE5, 0x9A, 0x61, 0x11, 0xBE, 0x1D, 0x8C, 0x02, 0x1E, 0x65, 0xB8, 0x91,
+    0xC2, 0xA2, 0x11, 0x16, 0x7A, 0xBB, 0x8C, 0x5E, 0x07, 0x9E, 0x09, 0xE2, 0xC8, 0xA8, 0x33, 0x9C,
+];
+
+/// The extensions of a ServerHello message
+#[derive(Clone, Copy, Debug)]
+pub struct ServerHello<'a> {
+    is_hello_retry_request: bool,
+    extensions: DecoderBuffer<'a>,
+}
+
+impl<'a> ServerHello<'a> {
+    //= https://www.rfc-editor.org/rfc/rfc8446#section-4.1.3
+    //# struct {
+    //#     ProtocolVersion legacy_version = 0x0303;    /* TLS v1.2 */
+    //#     Random random;
+    //#     opaque legacy_session_id_echo<0..32>;
+    //#     CipherSuite cipher_suite;
+    //#     uint8 legacy_compression_method = 0;
+    //#     Extension extensions<6..2^16-1>;
+    //# } ServerHello;
+    /// Decodes the body of a ServerHello message, without the handshake header
+    pub fn decode(buffer: DecoderBuffer<'a>) -> Result<Self, DecoderError> {
+        let buffer = buffer.skip(2)?; // legacy_version
+        let (random, buffer) = buffer.decode_slice(32)?;
+        let buffer = buffer.skip_with_len_prefix::<u8>()?; // legacy_session_id_echo
+        let buffer = buffer.skip(2)?; // cipher_suite
+        let buffer = buffer.skip(1)?; // legacy_compression_method
+        let (extensions, _) = buffer.decode_slice_with_len_prefix::<u16>()?;
+
+        Ok(Self {
+            is_hello_retry_request: random.into_less_safe_slice() == HELLO_RETRY_REQUEST_RANDOM,
+            extensions,
+        })
+    }
+
+    /// Returns `true` if the message is a HelloRetryRequest rather than a ServerHello
+    pub fn is_hello_retry_request(&self) -> bool {
+        self.is_hello_retry_request
+    }
+
+    /// Returns the data of the extension with the given type, if the server included it
+    pub fn extension(&self, extension_type: u16) -> Result<Option<&'a [u8]>, DecoderError> {
+        let mut buffer = self.extensions;
+
+        while !buffer.is_empty() {
+            let (ty, remaining) = buffer.decode::<u16>()?;
+            let (data, remaining) = remaining.decode_slice_with_len_prefix::<u16>()?;
+
+            if ty == extension_type {
+                return Ok(Some(data.into_less_safe_slice()));
+            }
+
+            buffer = remaining;
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_hello(random: [u8; 32], extensions: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut extension_bytes = vec![];
+        for (ty, data) in extensions {
+            extension_bytes.extend_from_slice(&ty.to_be_bytes());
+            extension_bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            extension_bytes.extend_from_slice(data);
+        }
+
+        let mut hello = vec![0x03, 0x03];
+        hello.extend_from_slice(&random);
+        hello.extend_from_slice(&[0]);
+        hello.extend_from_slice(&[0x13, 0x01]);
+        hello.extend_from_slice(&[0]);
+        hello.extend_from_slice(&(extension_bytes.len() as u16).to_be_bytes());
+        hello.extend_from_slice(&extension_bytes);
+        hello
+    }
+
+    #[test]
+    fn extension_test() {
+        let hello = server_hello([0xaa; 32], &[(43, &[3, 4]), (PRE_SHARED_KEY, &[0, 0])]);
+        let hello = ServerHello::decode(DecoderBuffer::new(&hello)).unwrap();
+
+        assert!(!hello.is_hello_retry_request());
+        assert_eq!(hello.extension(PRE_SHARED_KEY).unwrap(), Some(&[0, 0][..]));
+        assert_eq!(hello.extension(51).unwrap(), None);
+
+        let hello = server_hello(HELLO_RETRY_REQUEST_RANDOM, &[(43, &[3, 4])]);
+        let hello = ServerHello::decode(DecoderBuffer::new(&hello)).unwrap();
+        assert!(hello.is_hello_retry_request());
+    }
+
+    #[test]
+    fn truncated_test() {
+        let hello = server_hello([0xaa; 32], &[(PRE_SHARED_KEY, &[0, 0])]);
+
+        for len in 0..hello.len() {
+            let result = ServerHello::decode(DecoderBuffer::new(&hello[..len]))
+                .and_then(|hello| hello.extension(PRE_SHARED_KEY));
+            assert!(result.is_err(), "len {} should fail to decode", len);
+        }
+    }
+}
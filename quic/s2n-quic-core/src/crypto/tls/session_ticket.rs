@@ -0,0 +1,143 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persists session tickets issued to a client so later connections can resume the session
+
+use crate::application::ServerName;
+use bytes::Bytes;
+
+/// Stores session tickets issued by servers, keyed by the server name
+pub trait Store: 'static + Send + Sync {
+    /// Called when the server identified by `server_name` issues a new session ticket
+    ///
+    /// The ticket is an opaque value that is only meaningful to the TLS provider that produced it.
+    fn insert(&self, server_name: &ServerName, ticket: Bytes);
+
+    /// Removes and returns a ticket to offer to the server identified by `server_name`
+    ///
+    /// Tickets are removed on use since they should not be offered more than once.
+    fn take(&self, server_name: &ServerName) -> Option<Bytes>;
+}
+
+//= https://www.rfc-editor.org/rfc/rfc8446#section-4.6.1
+//# Clients SHOULD attempt to use each
+//# ticket no more than once, with more recent tickets being used
+//# first.
+
+#[cfg(feature = "std")]
+pub mod memory {
+    use super::*;
+    use std::{
+        collections::{HashMap, VecDeque},
+        sync::Mutex,
+    };
+
+    /// The default number of tickets retained for each server
+    const DEFAULT_MAX_TICKETS_PER_SERVER: usize = 4;
+
+    /// Retains session tickets in memory for the lifetime of the store
+    #[derive(Debug)]
+    pub struct Store {
+        tickets: Mutex<HashMap<Bytes, VecDeque<Bytes>>>,
+        max_tickets_per_server: usize,
+    }
+
+    impl Default for Store {
+        fn default() -> Self {
+            Self::new(DEFAULT_MAX_TICKETS_PER_SERVER)
+        }
+    }
+
+    impl Store {
+        /// Creates a store that retains up to `max_tickets_per_server` tickets for each server
+        ///
+        /// Once the limit is reached, the oldest ticket is evicted.
+        pub fn new(max_tickets_per_server: usize) -> Self {
+            Self {
+                tickets: Default::default(),
+                max_tickets_per_server,
+            }
+        }
+    }
+
+    impl super::Store for Store {
+        fn insert(&self, server_name: &ServerName, ticket: Bytes) {
+            if self.max_tickets_per_server == 0 {
+                return;
+            }
+
+            let mut tickets = self.tickets.lock().expect("lock is not poisoned");
+            let entry = tickets.entry(server_name.clone().into_bytes()).or_default();
+
+            if entry.len() == self.max_tickets_per_server {
+                entry.pop_front();
+            }
+
+            entry.push_back(ticket);
+        }
+
+        fn take(&self, server_name: &ServerName) -> Option<Bytes> {
+            let mut tickets = self.tickets.lock().expect("lock is not poisoned");
+            let key: &[u8] = server_name.as_bytes();
+            let entry = tickets.get_mut(key)?;
+            let ticket = entry.pop_back();
+
+            if entry.is_empty() {
+                tickets.remove(key);
+            }
+
+            ticket
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{super::Store as _, *};
+
+        #[test]
+        fn most_recent_ticket_test() {
+            let store = Store::default();
+            let server_name = ServerName::from("example.com");
+
+            store.insert(&server_name, Bytes::from_static(b"first"));
+            store.insert(&server_name, Bytes::from_static(b"second"));
+
+            assert_eq!(
+                store.take(&server_name),
+                Some(Bytes::from_static(b"second"))
+            );
+            assert_eq!(store.take(&server_name), Some(Bytes::from_static(b"first")));
+            assert_eq!(store.take(&server_name), None);
+        }
+
+        #[test]
+        fn server_name_isolation_test() {
+            let store = Store::default();
+            let server_name = ServerName::from("example.com");
+            let other = ServerName::from("example.org");
+
+            store.insert(&server_name, Bytes::from_static(b"ticket"));
+
+            assert_eq!(store.take(&other), None);
+            assert_eq!(
+                store.take(&server_name),
+                Some(Bytes::from_static(b"ticket"))
+            );
+        }
+
+        #[test]
+        fn eviction_test() {
+            let store = Store::new(1);
+            let server_name = ServerName::from("example.com");
+
+            store.insert(&server_name, Bytes::from_static(b"first"));
+            store.insert(&server_name, Bytes::from_static(b"second"));
+
+            assert_eq!(
+                store.take(&server_name),
+                Some(Bytes::from_static(b"second"))
+            );
+            assert_eq!(store.take(&server_name), None);
+        }
+    }
+}
@@ -125,17 +125,19 @@ impl<S: tls::Session, C: tls::Session> Pair<S, C> {
         SE: tls::Endpoint<Session = S>,
         CE: tls::Endpoint<Session = C>,
     {
-        use crate::crypto::InitialKey;
+        use crate::{crypto::InitialKey, packet::long::VERSION_1};
 
         let server = server_endpoint.new_server_session(&TEST_SERVER_TRANSPORT_PARAMS);
         let mut server_context =
             Context::new(endpoint::Type::Server, ServerState::WaitingClientHello);
-        server_context.initial.crypto = Some(S::InitialKey::new_server(server_name.as_bytes()));
+        server_context.initial.crypto =
+            Some(S::InitialKey::new_server(VERSION_1, server_name.as_bytes()));
 
         let client =
             client_endpoint.new_client_session(&TEST_CLIENT_TRANSPORT_PARAMS, server_name.clone());
         let mut client_context = Context::new(endpoint::Type::Client, ClientState::ClientHelloSent);
-        client_context.initial.crypto = Some(C::InitialKey::new_client(server_name.as_bytes()));
+        client_context.initial.crypto =
+            Some(C::InitialKey::new_client(VERSION_1, server_name.as_bytes()));
 
         Self {
             server: TlsEndpoint::new(server, server_context),
@@ -241,6 +243,17 @@ impl<S: tls::Session, C: tls::Session> Pair<S, C> {
             TEST_CLIENT_TRANSPORT_PARAMS,
             "server did not receive the client transport parameters"
         );
+        assert!(
+            !self
+                .client
+                .context
+                .handshake_info
+                .as_ref()
+                .unwrap()
+                .peer_certificate_chain
+                .is_empty(),
+            "client did not receive the server certificate chain"
+        );
         assert_eq!(
             self.client
                 .context
@@ -327,7 +340,9 @@ pub struct Context<C: CryptoSuite, State: Debug> {
     pub handshake_complete: bool,
     pub server_name: Option<Bytes>,
     pub application_protocol: Option<Bytes>,
+    pub handshake_info: Option<tls::HandshakeInfo>,
     pub transport_parameters: Option<Bytes>,
+    pub quic_version: u32,
     endpoint: endpoint::Type,
     pub state: State,
     waker: Waker,
@@ -343,7 +358,9 @@ impl<C: CryptoSuite, State: Debug> fmt::Debug for Context<C, State> {
             .field("handshake_complete", &self.handshake_complete)
             .field("sni", &self.server_name)
             .field("application_protocol", &self.application_protocol)
+            .field("handshake_info", &self.handshake_info)
             .field("transport_parameters", &self.transport_parameters)
+            .field("quic_version", &self.quic_version)
             .field("endpoint", &self.endpoint)
             .finish()
     }
@@ -360,7 +377,9 @@ impl<C: CryptoSuite, State: Debug> Context<C, State> {
             handshake_complete: false,
             server_name: None,
             application_protocol: None,
+            handshake_info: None,
             transport_parameters: None,
+            quic_version: crate::packet::long::VERSION_1,
             endpoint,
             state,
             waker,
@@ -394,6 +413,17 @@ impl<C: CryptoSuite, State: Debug> Context<C, State> {
             "0-rtt keys are not consistent between endpoints"
         );
 
+        let info = self.handshake_info.as_ref().unwrap();
+        let other_info = other.handshake_info.as_ref().unwrap();
+        assert_eq!(
+            info.cipher_suite, other_info.cipher_suite,
+            "cipher_suite is not consistent between endpoints"
+        );
+        assert_eq!(
+            info.is_resumed, other_info.is_resumed,
+            "is_resumed is not consistent between endpoints"
+        );
+
         self.initial.finish(&other.initial);
         self.handshake.finish(&other.handshake);
         self.application.finish(&other.application);
@@ -408,6 +438,7 @@ impl<C: CryptoSuite, State: Debug> Context<C, State> {
         );
         assert!(self.handshake_complete);
         assert!(self.application_protocol.is_some());
+        assert!(self.handshake_info.is_some());
         assert!(self.transport_parameters.is_some());
     }
 
@@ -584,6 +615,16 @@ impl<C: CryptoSuite, State: Debug> tls::Context<C> for Context<C, State> {
         Ok(())
     }
 
+    fn on_zero_rtt_rejected(&mut self) -> Result<(), transport::Error> {
+        assert!(
+            self.zero_rtt_crypto.is_some(),
+            "0-rtt rejected without 0-rtt keys"
+        );
+        self.log("0-rtt rejected");
+        self.zero_rtt_crypto = None;
+        Ok(())
+    }
+
     fn on_one_rtt_keys(
         &mut self,
         key: C::OneRttKey,
@@ -619,6 +660,16 @@ impl<C: CryptoSuite, State: Debug> tls::Context<C> for Context<C, State> {
         Ok(())
     }
 
+    fn on_handshake_info(&mut self, info: tls::HandshakeInfo) -> Result<(), transport::Error> {
+        assert!(
+            !self.handshake_complete,
+            "handshake info emitted after handshake complete"
+        );
+        self.log("handshake info");
+        self.handshake_info = Some(info);
+        Ok(())
+    }
+
     fn on_handshake_complete(&mut self) -> Result<(), transport::Error> {
         assert!(
             !self.handshake_complete,
@@ -686,4 +737,8 @@ impl<C: CryptoSuite, State: Debug> tls::Context<C> for Context<C, State> {
     fn waker(&self) -> &Waker {
         &self.waker
     }
+
+    fn quic_version(&self) -> u32 {
+        self.quic_version
+    }
 }
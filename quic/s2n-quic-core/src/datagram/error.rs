@@ -0,0 +1,143 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{application, connection, frame::ConnectionClose, transport};
+use core::{fmt, panic};
+
+/// Errors that can occur when sending or receiving unreliable datagrams
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub enum DatagramError {
+    /// Datagrams are not enabled on the connection
+    ///
+    /// The local endpoint has not configured a maximum datagram frame size.
+    #[non_exhaustive]
+    Disabled {
+        source: &'static panic::Location<'static>,
+    },
+    /// The peer does not support receiving datagrams
+    ///
+    /// The peer did not advertise a `max_datagram_frame_size` transport parameter, so
+    /// datagrams can't be sent on the connection.
+    #[non_exhaustive]
+    NotSupported {
+        source: &'static panic::Location<'static>,
+    },
+    /// The datagram exceeds the maximum size that can be sent to the peer
+    #[non_exhaustive]
+    TooLarge {
+        /// The maximum datagram payload size, in bytes, the connection can currently send
+        max_size: usize,
+        source: &'static panic::Location<'static>,
+    },
+    /// The datagram could not be processed due to a Connection Error
+    #[non_exhaustive]
+    ConnectionError { error: connection::Error },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DatagramError {}
+
+impl fmt::Display for DatagramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Disabled { .. } => write!(f, "Datagrams are not enabled on the connection"),
+            Self::NotSupported { .. } => write!(f, "The peer does not support datagrams"),
+            Self::TooLarge { max_size, .. } => write!(
+                f,
+                "The datagram exceeds the maximum size of {} bytes",
+                max_size
+            ),
+            Self::ConnectionError { error, .. } => error.fmt(f),
+        }
+    }
+}
+
+impl DatagramError {
+    /// Returns the [`panic::Location`] for the error
+    pub fn source(&self) -> &'static panic::Location<'static> {
+        match self {
+            DatagramError::Disabled { source } => source,
+            DatagramError::NotSupported { source } => source,
+            DatagramError::TooLarge { source, .. } => source,
+            DatagramError::ConnectionError { error } => error.source(),
+        }
+    }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn disabled() -> DatagramError {
+        let source = panic::Location::caller();
+        DatagramError::Disabled { source }
+    }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn not_supported() -> DatagramError {
+        let source = panic::Location::caller();
+        DatagramError::NotSupported { source }
+    }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn too_large(max_size: usize) -> DatagramError {
+        let source = panic::Location::caller();
+        DatagramError::TooLarge { max_size, source }
+    }
+}
+
+impl application::error::TryInto for DatagramError {
+    fn application_error(&self) -> Option<application::Error> {
+        if let DatagramError::ConnectionError { error, .. } = self {
+            error.application_error()
+        } else {
+            None
+        }
+    }
+}
+
+impl From<connection::Error> for DatagramError {
+    fn from(error: connection::Error) -> Self {
+        Self::ConnectionError { error }
+    }
+}
+
+impl From<transport::Error> for DatagramError {
+    #[track_caller]
+    fn from(error: transport::Error) -> Self {
+        let error: connection::Error = error.into();
+        error.into()
+    }
+}
+
+impl<'a> From<ConnectionClose<'a>> for DatagramError {
+    #[track_caller]
+    fn from(error: ConnectionClose) -> Self {
+        let error: connection::Error = error.into();
+        error.into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<DatagramError> for std::io::Error {
+    fn from(error: DatagramError) -> Self {
+        let kind = error.into();
+        std::io::Error::new(kind, error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<DatagramError> for std::io::ErrorKind {
+    fn from(error: DatagramError) -> Self {
+        use std::io::ErrorKind;
+        match error {
+            DatagramError::Disabled { .. } => ErrorKind::Other,
+            DatagramError::NotSupported { .. } => ErrorKind::Other,
+            DatagramError::TooLarge { .. } => ErrorKind::InvalidInput,
+            DatagramError::ConnectionError { error, .. } => error.into(),
+        }
+    }
+}
@@ -46,6 +46,8 @@ pub mod api {
         pub initial_max_streams_bidi: u64,
         pub initial_max_streams_uni: u64,
         pub max_datagram_frame_size: u64,
+        #[doc = " Transport parameters defined by the application"]
+        pub custom_parameters: &'a [crate::transport::parameters::CustomTransportParameter],
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
@@ -1899,6 +1901,8 @@ pub mod builder {
         pub initial_max_streams_bidi: u64,
         pub initial_max_streams_uni: u64,
         pub max_datagram_frame_size: u64,
+        #[doc = " Transport parameters defined by the application"]
+        pub custom_parameters: &'a [crate::transport::parameters::CustomTransportParameter],
     }
     impl<'a> IntoEvent<api::TransportParameters<'a>> for TransportParameters<'a> {
         #[inline]
@@ -1921,6 +1925,7 @@ pub mod builder {
                 initial_max_streams_bidi,
                 initial_max_streams_uni,
                 max_datagram_frame_size,
+                custom_parameters,
             } = self;
             api::TransportParameters {
                 original_destination_connection_id: original_destination_connection_id.into_event(),
@@ -1941,6 +1946,7 @@ pub mod builder {
                 initial_max_streams_bidi: initial_max_streams_bidi.into_event(),
                 initial_max_streams_uni: initial_max_streams_uni.into_event(),
                 max_datagram_frame_size: max_datagram_frame_size.into_event(),
+                custom_parameters: custom_parameters.into_event(),
             }
         }
     }
@@ -34,6 +34,15 @@ impl IpAddress {
             Self::Ipv6(addr) => addr.unmap(),
         }
     }
+
+    /// Returns a socket address with the IP address and the given `port`
+    #[inline]
+    pub fn with_port(self, port: u16) -> SocketAddress {
+        match self {
+            Self::Ipv4(addr) => SocketAddressV4::new(addr, port).into(),
+            Self::Ipv6(addr) => SocketAddressV6::new(addr, port).into(),
+        }
+    }
 }
 
 impl From<IpV4Address> for IpAddress {
@@ -253,6 +262,15 @@ mod std_conversion {
         }
     }
 
+    impl From<net::IpAddr> for IpAddress {
+        fn from(ip: net::IpAddr) -> Self {
+            match ip {
+                net::IpAddr::V4(ip) => Self::Ipv4(ip.into()),
+                net::IpAddr::V6(ip) => Self::Ipv6(ip.into()),
+            }
+        }
+    }
+
     impl From<(net::IpAddr, u16)> for SocketAddress {
         fn from((ip, port): (net::IpAddr, u16)) -> Self {
             match ip {
@@ -12,6 +12,7 @@ pub mod connection;
 pub mod counter;
 pub mod crypto;
 pub mod ct;
+pub mod datagram;
 pub mod endpoint;
 pub mod event;
 pub mod frame;
@@ -7,7 +7,7 @@ use crate::{
         decoding::HeaderDecoder,
         encoding::{PacketEncoder, PacketPayloadEncoder},
         long::{
-            DestinationConnectionIdLen, LongPayloadEncoder, LongPayloadLenCursor,
+            self, DestinationConnectionIdLen, LongPayloadEncoder, LongPayloadLenCursor,
             SourceConnectionIdLen, Version,
         },
         number::{
@@ -223,7 +223,7 @@ impl<DCID: EncoderValue, SCID: EncoderValue, PacketNumber, Payload>
     Handshake<DCID, SCID, PacketNumber, Payload>
 {
     fn encode_header<E: Encoder>(&self, packet_number_len: PacketNumberLen, encoder: &mut E) {
-        let mut tag: u8 = handshake_tag!() << 4;
+        let mut tag: u8 = long::encode_tag(handshake_tag!(), self.version) << 4;
         tag |= packet_number_len.into_packet_tag_mask();
         tag.encode(encoder);
 
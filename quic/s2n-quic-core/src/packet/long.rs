@@ -49,6 +49,39 @@ const PACKET_TYPE_OFFSET: u8 = 4;
 
 pub(crate) type Version = u32;
 
+//= https://www.rfc-editor.org/rfc/rfc9000#section-15
+//# This version of the specification is identified by the number
+//# 0x00000001.
+pub const VERSION_1: u32 = 0x0000_0001;
+
+/// QUIC version 2, as defined in <https://www.rfc-editor.org/rfc/rfc9369#section-3.1>
+pub const VERSION_2: u32 = 0x6b33_43cf;
+
+// Version 2 assigns each long header packet type the next value of the 2-bit field
+// (Initial = 0b01, 0-RTT = 0b10, Handshake = 0b11, Retry = 0b00).
+//
+// See <https://www.rfc-editor.org/rfc/rfc9369#section-3.2>
+
+/// Maps a version 1 long header tag nibble to the value used on the wire for `version`
+#[inline]
+pub(crate) const fn encode_tag(tag: u8, version: Version) -> u8 {
+    if version == VERSION_2 {
+        (tag & !0b11) | (tag.wrapping_add(1) & 0b11)
+    } else {
+        tag
+    }
+}
+
+/// Maps a long header tag nibble received with `version` to its version 1 value
+#[inline]
+pub(crate) const fn decode_tag(tag: u8, version: Version) -> u8 {
+    if version == VERSION_2 {
+        (tag & !0b11) | (tag.wrapping_add(3) & 0b11)
+    } else {
+        tag
+    }
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2
 //# Destination Connection ID Length:  The byte following the version
 //#    contains the length in bytes of the Destination Connection ID
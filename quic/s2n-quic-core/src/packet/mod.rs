@@ -209,15 +209,10 @@ pub trait PacketDecoder<'a> {
         }
 
         macro_rules! long_packet {
-            ($struct:ident, $handler:ident) => {{
-                let (version, _peek) = peek.decode()?;
-                if version == version_negotiation::VERSION {
-                    version_negotiation!(version)
-                } else {
-                    let (packet, buffer) = $struct::decode(tag, version, buffer)?;
-                    let output = self.$handler(packet)?;
-                    Ok((output, buffer))
-                }
+            ($struct:ident, $handler:ident, $version:ident) => {{
+                let (packet, buffer) = $struct::decode(tag, $version, buffer)?;
+                let output = self.$handler(packet)?;
+                Ok((output, buffer))
             }};
         }
 
@@ -240,10 +235,27 @@ pub trait PacketDecoder<'a> {
                 );
                 version_negotiation!(version)
             }
-            initial_tag!() => long_packet!(ProtectedInitial, handle_initial_packet),
-            zero_rtt_tag!() => long_packet!(ProtectedZeroRtt, handle_zero_rtt_packet),
-            handshake_tag!() => long_packet!(ProtectedHandshake, handle_handshake_packet),
-            retry_tag!() => long_packet!(ProtectedRetry, handle_retry_packet),
+            long_tag @ 0b1100u8..=0b1111u8 => {
+                let (version, _peek) = peek.decode()?;
+                if version == version_negotiation::VERSION {
+                    return version_negotiation!(version);
+                }
+
+                // The long packet type bits are assigned differently depending on the version
+                match long::decode_tag(long_tag, version) {
+                    initial_tag!() => {
+                        long_packet!(ProtectedInitial, handle_initial_packet, version)
+                    }
+                    zero_rtt_tag!() => {
+                        long_packet!(ProtectedZeroRtt, handle_zero_rtt_packet, version)
+                    }
+                    handshake_tag!() => {
+                        long_packet!(ProtectedHandshake, handle_handshake_packet, version)
+                    }
+                    retry_tag!() => long_packet!(ProtectedRetry, handle_retry_packet, version),
+                    _ => unreachable!("long packet types are 2 bits"),
+                }
+            }
             _ => Err(DecoderError::InvariantViolation("invalid packet").into()),
         }
     }
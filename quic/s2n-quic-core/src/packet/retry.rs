@@ -12,7 +12,7 @@ use crate::{
     packet::{
         decoding::HeaderDecoder,
         initial::ProtectedInitial,
-        long::{DestinationConnectionIdLen, SourceConnectionIdLen, Version},
+        long::{self, DestinationConnectionIdLen, SourceConnectionIdLen, Version},
         Tag,
     },
     random, token,
@@ -157,7 +157,7 @@ impl<'a> Retry<'a> {
 
         outcome?;
 
-        let tag = C::generate_tag(buffer.as_mut_slice());
+        let tag = C::generate_tag(packet.version, buffer.as_mut_slice());
         buffer.write_slice(&tag);
         let end = buffer.len();
         let start =
@@ -190,7 +190,7 @@ impl<'a> Retry<'a> {
         //# of packets that have accidentally been corrupted by the network, and
         //# only an entity that observes an Initial packet can send a valid Retry
         //# packet.
-        Crypto::validate(buf, *self.retry_integrity_tag)?;
+        Crypto::validate(self.version, buf, *self.retry_integrity_tag)?;
 
         Ok(())
     }
@@ -209,7 +209,7 @@ impl<'a> Retry<'a> {
             // The last 4 bits are unused. They are set to 0x0f here to allow easy testing with
             // example packets provided in the RFC.
             // https://www.rfc-editor.org/rfc/rfc9001#section-A.2
-            tag: (retry_tag!() << 4) | 0x0f,
+            tag: (long::encode_tag(retry_tag!(), initial_packet.version) << 4) | 0x0f,
             version: initial_packet.version,
             destination_connection_id: initial_packet.source_connection_id(),
             source_connection_id: local_connection_id,
@@ -379,6 +379,32 @@ mod tests {
         assert_eq!(packet.version, retry::example::VERSION);
     }
 
+    #[test]
+    fn test_decode_v2() {
+        let mut buf = retry::example::v2::PACKET;
+        let decoder = DecoderBufferMut::new(&mut buf);
+        let remote_address = inet::ip::SocketAddress::default();
+        let connection_info = connection::id::ConnectionInfo::new(&remote_address);
+        let (packet, _) = packet::ProtectedPacket::decode(decoder, &connection_info, &20).unwrap();
+        let packet = match packet {
+            packet::ProtectedPacket::Retry(retry) => retry,
+            _ => panic!("expected retry packet type"),
+        };
+
+        assert_eq!(
+            packet.retry_integrity_tag,
+            &retry::example::v2::EXPECTED_TAG
+        );
+        assert_eq!(packet.retry_token, retry::example::TOKEN);
+        assert_eq!(packet.version, retry::example::v2::VERSION);
+
+        // re-encoding the packet should preserve the version 2 packet type
+        let mut buf = [0; retry::example::PACKET_LEN];
+        let mut encoder = EncoderBuffer::new(&mut buf);
+        packet.encode(&mut encoder);
+        assert_eq!(retry::example::v2::PACKET[..], buf[..]);
+    }
+
     #[test]
     fn test_decode_no_token() {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.2
@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    event,
+    connection, event,
     event::{
         api::{Path, SocketAddress},
         IntoEvent,
     },
     inet,
 };
+use core::fmt;
 
 #[derive(Debug)]
 #[non_exhaustive]
@@ -103,6 +104,61 @@ impl IntoEvent<event::builder::ConnectionMigrationDenied> for DenyReason {
     }
 }
 
+/// Errors that can occur when the application migrates a connection to a new local address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MigrationError {
+    /// The connection is unable to migrate to the requested local address
+    ///
+    /// Only clients are able to initiate connection migration. The IO provider must also be
+    /// able to send packets from the requested local IP address.
+    Unsupported,
+    /// The handshake has not been confirmed
+    HandshakeNotConfirmed,
+    /// The peer sent the `disable_active_migration` transport parameter
+    DisabledByPeer,
+    /// Either endpoint lacks an unused connection ID of the other for the new path
+    InsufficientConnectionIds,
+    /// The connection has reached the maximum number of paths
+    PathLimitExceeded,
+    /// The migration could not be performed due to a Connection Error
+    #[non_exhaustive]
+    ConnectionError { error: connection::Error },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MigrationError {}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(
+                f,
+                "The connection is unable to migrate to the requested local address"
+            ),
+            Self::HandshakeNotConfirmed => write!(
+                f,
+                "The connection can not migrate before the handshake is confirmed"
+            ),
+            Self::DisabledByPeer => write!(f, "The peer disabled active connection migration"),
+            Self::InsufficientConnectionIds => write!(
+                f,
+                "An unused connection ID is not yet available for the new path"
+            ),
+            Self::PathLimitExceeded => {
+                write!(f, "The connection has reached the maximum number of paths")
+            }
+            Self::ConnectionError { error } => error.fmt(f),
+        }
+    }
+}
+
+impl From<connection::Error> for MigrationError {
+    fn from(error: connection::Error) -> Self {
+        Self::ConnectionError { error }
+    }
+}
+
 /// Validates a path migration attempt from an active path to another
 pub trait Validator: 'static + Send {
     /// Called on each connection migration attempt for a connection
@@ -91,6 +91,14 @@ pub trait Handle: 'static + Copy + Send + fmt::Debug {
     /// Returns the local address for the given handle
     fn local_address(&self) -> LocalAddress;
 
+    /// Sets the local address for the given handle
+    ///
+    /// Handles that are unable to represent the local address ignore the value.
+    #[inline]
+    fn set_local_address(&mut self, local_address: LocalAddress) {
+        let _ = local_address;
+    }
+
     /// Returns `true` if the two handles are equal from a network perspective
     ///
     /// This function is used to determine if a connection has migrated to another
@@ -211,6 +219,11 @@ impl Handle for Tuple {
         self.local_address
     }
 
+    #[inline]
+    fn set_local_address(&mut self, local_address: LocalAddress) {
+        self.local_address = local_address;
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         PartialEq::eq(&self.local_address.unmap(), &other.local_address.unmap())
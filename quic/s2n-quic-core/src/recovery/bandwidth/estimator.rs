@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::time::Timestamp;
-use core::{cmp::max, time::Duration};
+use core::{cmp::max, convert::TryInto, time::Duration};
 use num_rational::Ratio;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -24,7 +24,9 @@ pub struct PacketInfo {
     pub is_app_limited: bool,
 }
 
-#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq)]
+const MICRO_BITS_PER_BYTE: u64 = 8 * 1000000;
+
+#[derive(Copy, Clone, Debug, Default, PartialOrd, Ord, PartialEq, Eq)]
 pub struct Bandwidth {
     bits_per_second: u64,
 }
@@ -32,9 +34,11 @@ pub struct Bandwidth {
 impl Bandwidth {
     pub const ZERO: Bandwidth = Bandwidth { bits_per_second: 0 };
 
-    pub fn new(bytes: u64, interval: Duration) -> Self {
-        const MICRO_BITS_PER_BYTE: u64 = 8 * 1000000;
+    pub const INFINITY: Bandwidth = Bandwidth {
+        bits_per_second: u64::MAX,
+    };
 
+    pub fn new(bytes: u64, interval: Duration) -> Self {
         if interval.is_zero() {
             Bandwidth::ZERO
         } else {
@@ -43,6 +47,12 @@ impl Bandwidth {
             }
         }
     }
+
+    /// Returns the bandwidth in bits per second
+    #[inline]
+    pub fn as_bits_per_second(self) -> u64 {
+        self.bits_per_second
+    }
 }
 
 impl core::ops::Mul<Ratio<u64>> for Bandwidth {
@@ -55,6 +65,29 @@ impl core::ops::Mul<Ratio<u64>> for Bandwidth {
     }
 }
 
+/// Calculates the amount of data in bytes that can be delivered over the given `Duration`
+impl core::ops::Mul<Duration> for Bandwidth {
+    type Output = u64;
+
+    fn mul(self, rhs: Duration) -> Self::Output {
+        (self.bits_per_second as u128 * rhs.as_micros() / MICRO_BITS_PER_BYTE as u128)
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+}
+
+/// Calculates the time it takes to deliver the given amount of data in bytes
+///
+/// Panics if the bandwidth is zero.
+impl core::ops::Div<Bandwidth> for u64 {
+    type Output = Duration;
+
+    fn div(self, rhs: Bandwidth) -> Self::Output {
+        let micros = self as u128 * MICRO_BITS_PER_BYTE as u128 / rhs.bits_per_second as u128;
+        Duration::from_micros(micros.try_into().unwrap_or(u64::MAX))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 /// A bandwidth delivery rate estimate with associated metadata
 pub struct RateSample {
@@ -112,6 +145,9 @@ pub struct Estimator {
     /// The send time of the packet that was most recently marked as delivered, or if the connection
     /// was recently idle, the send time of the first packet sent after resuming from idle.
     first_sent_time: Option<Timestamp>,
+    /// The value of `delivered_bytes` that must be exceeded for the path to no longer
+    /// be considered application limited, or `None` if the path is not application limited
+    app_limited_delivered_bytes: Option<u64>,
     rate_sample: RateSample,
 }
 
@@ -122,6 +158,17 @@ impl Estimator {
         self.delivered_bytes
     }
 
+    /// Gets the total amount of data in bytes declared lost so far over the lifetime of the path, not
+    /// including non-congestion-controlled packets such as pure ACK packets.
+    pub fn lost_bytes(&self) -> u64 {
+        self.lost_bytes
+    }
+
+    /// Returns true if packets sent now would be sent while the path is application limited
+    pub fn is_app_limited(&self) -> bool {
+        self.app_limited_delivered_bytes.is_some()
+    }
+
     /// Gets the latest [RateSample]
     pub fn rate_sample(&self) -> RateSample {
         self.rate_sample
@@ -173,12 +220,23 @@ impl Estimator {
         self.delivered_bytes += bytes_acknowledged as u64;
         self.delivered_time = Some(now);
 
+        if self
+            .app_limited_delivered_bytes
+            .map_or(false, |app_limited| self.delivered_bytes > app_limited)
+        {
+            // Clear app limited if bubble is acked and gone
+            self.app_limited_delivered_bytes = None;
+        }
+
         //= https://tools.ietf.org/id/draft-cheng-iccrg-delivery-rate-estimation-02#3.3
         //# UpdateRateSample() is invoked multiple times when a stretched ACK acknowledges
         //# multiple data packets. In this case we use the information from the most recently
         //# sent packet, i.e., the packet with the highest "P.delivered" value.
+        //
+        // Packets sent in the same burst share the same `delivered_bytes`, so a packet with
+        // an equal value acknowledged in a later ACK frame also updates the rate sample.
         if self.rate_sample.prior_delivered_bytes == 0
-            || newest_acked_packet_info.delivered_bytes > self.rate_sample.prior_delivered_bytes
+            || newest_acked_packet_info.delivered_bytes >= self.rate_sample.prior_delivered_bytes
         {
             // Update info using the newest packet
             self.rate_sample.on_ack(newest_acked_packet_info);
@@ -205,6 +263,19 @@ impl Estimator {
         self.lost_bytes += lost_bytes as u64;
         self.rate_sample.lost_bytes = self.lost_bytes - self.rate_sample.prior_lost_bytes;
     }
+
+    /// Called when the path is found to be application limited
+    ///
+    /// Packets sent until the data currently in flight has been delivered are marked as
+    /// application limited, so the delivery rate samples they produce are not mistaken
+    /// for a reduction in the available bandwidth.
+    #[inline]
+    pub fn on_app_limited(&mut self, bytes_in_flight: u32) {
+        // Any rate sample from a packet sent until the current flight is delivered would
+        // include the idle "bubble" in its interval, see
+        // https://tools.ietf.org/id/draft-cheng-iccrg-delivery-rate-estimation-02#section-3.4
+        self.app_limited_delivered_bytes = Some(self.delivered_bytes + bytes_in_flight as u64);
+    }
 }
 
 #[cfg(test)]
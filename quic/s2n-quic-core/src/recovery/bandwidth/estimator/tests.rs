@@ -27,6 +27,23 @@ fn bandwidth_mul_ratio() {
     assert_eq!(result, Bandwidth::new(3000, Duration::from_secs(1)));
 }
 
+#[test]
+fn bandwidth_mul_duration() {
+    let bandwidth = Bandwidth::new(7000, Duration::from_secs(1));
+
+    assert_eq!(3500, bandwidth * Duration::from_millis(500));
+    assert_eq!(0, Bandwidth::ZERO * Duration::from_secs(1));
+    assert_eq!(u64::MAX, Bandwidth::INFINITY * Duration::from_secs(10));
+}
+
+#[test]
+fn bytes_div_bandwidth() {
+    let bandwidth = Bandwidth::new(7000, Duration::from_secs(1));
+
+    assert_eq!(Duration::from_millis(500), 3500 / bandwidth);
+    assert_eq!(Duration::ZERO, 0 / bandwidth);
+}
+
 // first_sent_time and delivered_time typically hold values from recently acknowledged packets. However,
 // when  no packet has been sent yet, or there are no packets currently in flight, these values are initialized
 // with the time when a packet is sent. This test confirms first_sent_time and delivered_time are
@@ -69,6 +86,7 @@ fn on_packet_sent() {
         delivered_time: Some(delivered_time),
         lost_bytes: 100,
         first_sent_time: Some(first_sent_time),
+        app_limited_delivered_bytes: None,
         rate_sample: Default::default(),
     };
 
@@ -186,6 +204,32 @@ fn on_packet_ack_rate_sample() {
     assert_eq!(Some(t2), bw_estimator.first_sent_time);
 }
 
+#[test]
+fn on_packet_ack_same_burst() {
+    let t0 = NoopClock.get_time();
+    let mut bw_estimator = Estimator::default();
+
+    // Two packets are sent in the same burst, so they have the same delivered_bytes
+    let packet_1 = bw_estimator.on_packet_sent(0, false, t0);
+    let packet_2 = bw_estimator.on_packet_sent(1500, false, t0);
+    assert_eq!(packet_1.delivered_bytes, packet_2.delivered_bytes);
+
+    let t1 = t0 + Duration::from_secs(1);
+    bw_estimator.on_ack(1500, t0, packet_1, t1);
+    assert_eq!(t1 - t0, bw_estimator.rate_sample.interval);
+    assert_eq!(1500, bw_estimator.rate_sample.delivered_bytes);
+
+    // The second packet is acknowledged in a later ACK frame, extending the interval
+    let t2 = t0 + Duration::from_secs(2);
+    bw_estimator.on_ack(1500, t0, packet_2, t2);
+    assert_eq!(t2 - t0, bw_estimator.rate_sample.interval);
+    assert_eq!(3000, bw_estimator.rate_sample.delivered_bytes);
+    assert_eq!(
+        Bandwidth::new(1500, Duration::from_secs(1)),
+        bw_estimator.rate_sample.delivery_rate()
+    );
+}
+
 //= https://tools.ietf.org/id/draft-cheng-iccrg-delivery-rate-estimation-02#2.2.4
 //= type=test
 //# Since it is physically impossible to have data delivered faster than it is sent
@@ -237,3 +281,27 @@ fn on_packet_loss() {
     assert_eq!(750, bw_estimator.lost_bytes);
     assert_eq!(750, bw_estimator.rate_sample.lost_bytes);
 }
+
+#[test]
+fn app_limited() {
+    let t0 = NoopClock.get_time();
+    let mut bw_estimator = Estimator::default();
+
+    let packet_info = bw_estimator.on_packet_sent(0, bw_estimator.is_app_limited(), t0);
+    assert!(!packet_info.is_app_limited);
+
+    // The application stops sending with 1500 bytes in flight
+    bw_estimator.on_app_limited(1500);
+    assert!(bw_estimator.is_app_limited());
+    let packet_info_2 = bw_estimator.on_packet_sent(1500, bw_estimator.is_app_limited(), t0);
+    assert!(packet_info_2.is_app_limited);
+
+    // Delivering the data that was in flight is not enough to exit the app limited phase
+    let t1 = t0 + Duration::from_secs(1);
+    bw_estimator.on_ack(1500, t0, packet_info, t1);
+    assert!(bw_estimator.is_app_limited());
+
+    // Delivering the data sent during the app limited phase exits the app limited phase
+    bw_estimator.on_ack(1500, t0, packet_info_2, t1);
+    assert!(!bw_estimator.is_app_limited());
+}
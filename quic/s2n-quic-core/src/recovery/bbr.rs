@@ -3,17 +3,159 @@
 
 use crate::{
     counter::Counter,
-    recovery::{bandwidth, bandwidth::Bandwidth, CongestionController, RttEstimator},
+    recovery::{
+        bandwidth,
+        bandwidth::Bandwidth,
+        congestion_controller::{self, CongestionController},
+        cubic::CubicCongestionController,
+        pacing::Pacer,
+        RttEstimator,
+    },
     time::Timestamp,
 };
+use core::{
+    cmp::{max, min},
+    convert::TryInto,
+    time::Duration,
+};
 use num_rational::Ratio;
 
+mod data_rate;
+mod data_volume;
 mod full_pipe;
+mod probe_bw;
+mod probe_rtt;
 mod recovery;
+mod round;
 
 //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.8
 //# The maximum tolerated per-round-trip packet loss rate when probing for bandwidth (the default is 2%).
-const LOSS_THRESH: Ratio<u32> = Ratio::new_raw(1, 50);
+const LOSS_THRESH: Ratio<u64> = Ratio::new_raw(1, 50);
+
+/// The multiplicative decrease applied to the short-term lower bounds in response to loss
+///
+/// Based on BBRBeta in the BBRv2 draft
+const BETA: Ratio<u64> = Ratio::new_raw(7, 10);
+
+/// The fraction of inflight_hi left unused in Cruise and ProbeRTT so other flows can gain bandwidth
+///
+/// Based on BBRHeadroom in the BBRv2 draft
+const HEADROOM: Ratio<u64> = Ratio::new_raw(15, 100);
+
+/// The minimum congestion window in packets, allowing pipelining with receivers that
+/// acknowledge every other packet
+///
+/// Based on BBRMinPipeCwnd in the BBRv2 draft
+const MIN_PIPE_CWND_PACKETS: u32 = 4;
+
+/// The pacing gain in Startup, allowing the sending rate to double each round
+///
+/// Based on BBRStartupPacingGain in the BBRv2 draft
+const STARTUP_PACING_GAIN: Ratio<u64> = Ratio::new_raw(277, 100);
+
+/// The congestion window gain in Startup and Drain
+///
+/// Based on BBRStartupCwndGain in the BBRv2 draft
+const STARTUP_CWND_GAIN: Ratio<u64> = Ratio::new_raw(2, 1);
+
+/// The pacing gain in Drain, draining the queue created in Startup within one round
+///
+/// Based on bbr_drain_gain in tcp_bbr2.c
+const DRAIN_PACING_GAIN: Ratio<u64> = Ratio::new_raw(35, 100);
+
+/// The congestion window gain in ProbeRTT
+///
+/// Based on BBRProbeRTTCwndGain in the BBRv2 draft
+const PROBE_RTT_CWND_GAIN: Ratio<u64> = Ratio::new_raw(1, 2);
+
+/// Pacing at slightly below the estimated bandwidth reduces queueing at the bottleneck
+///
+/// Based on BBRPacingMarginPercent in the BBRv2 draft
+const PACING_MARGIN: Ratio<u64> = Ratio::new_raw(99, 100);
+
+/// The maximum amount of data sent in a single burst
+///
+/// Based on the 64 KB send quantum limit in the BBRv2 draft
+const MAX_SEND_QUANTUM: u64 = 64 * 1024;
+
+/// The amount of time used to scale the pacing rate into a send quantum
+const SEND_QUANTUM_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The round-trip time used to calculate the initial pacing rate until an RTT is sampled
+const INITIAL_PACING_RTT: Duration = Duration::from_millis(1);
+
+/// The state machine of a BBR flow
+///
+/// ```text
+///              |
+///              V
+///     +---> Startup  ------------+
+///     |        |                 |
+///     |        V                 |
+///     |     Drain  --------------+
+///     |        |                 |
+///     |        V                 |
+///     +---> ProbeBW -------------+
+///     |     ^    |               |
+///     |     |    |               |
+///     |     +----+               |
+///     |                          |
+///     +---- ProbeRTT <-----------+
+/// ```
+#[derive(Clone, Debug)]
+enum State {
+    /// Rapidly probes for bandwidth by doubling the sending rate each round
+    Startup,
+    /// Drains the queue created in Startup
+    Drain,
+    /// Cycles through phases of probing for bandwidth and cruising at the estimated bandwidth
+    ProbeBw(probe_bw::State),
+    /// Reduces the data in flight to refresh the min_rtt estimate
+    ProbeRtt(probe_rtt::State),
+}
+
+impl State {
+    /// The dynamic gain factor used to scale the estimated bandwidth to produce a pacing rate
+    #[inline]
+    fn pacing_gain(&self) -> Ratio<u64> {
+        match self {
+            State::Startup => STARTUP_PACING_GAIN,
+            State::Drain => DRAIN_PACING_GAIN,
+            State::ProbeBw(probe_bw_state) => probe_bw_state.cycle_phase().pacing_gain(),
+            State::ProbeRtt(_) => Ratio::new_raw(1, 1),
+        }
+    }
+
+    /// The dynamic gain factor used to scale the estimated BDP to produce a congestion window
+    #[inline]
+    fn cwnd_gain(&self) -> Ratio<u64> {
+        match self {
+            State::Startup | State::Drain => STARTUP_CWND_GAIN,
+            State::ProbeBw(probe_bw_state) => probe_bw_state.cycle_phase().cwnd_gain(),
+            State::ProbeRtt(_) => PROBE_RTT_CWND_GAIN,
+        }
+    }
+
+    /// Returns the current ProbeBW cycle phase, if in ProbeBW
+    #[inline]
+    fn cycle_phase(&self) -> Option<probe_bw::CyclePhase> {
+        if let State::ProbeBw(probe_bw_state) = self {
+            Some(probe_bw_state.cycle_phase())
+        } else {
+            None
+        }
+    }
+
+    /// True if the flow is currently probing for additional bandwidth
+    #[inline]
+    fn is_probing_bw(&self) -> bool {
+        matches!(self, State::Startup)
+            || matches!(
+                self.cycle_phase(),
+                Some(probe_bw::CyclePhase::Refill) | Some(probe_bw::CyclePhase::Up)
+            )
+    }
+}
 
 /// A congestion controller that implements "Bottleneck Bandwidth and Round-trip propagation time"
 /// version 2 (BBRv2) as specified in <https://datatracker.ietf.org/doc/draft-cardwell-iccrg-bbr-congestion-control/>.
@@ -21,14 +163,32 @@ const LOSS_THRESH: Ratio<u32> = Ratio::new_raw(1, 50);
 /// Based in part on the Chromium BBRv2 implementation, see <https://source.chromium.org/chromium/chromium/src/+/main:net/third_party/quiche/src/quic/core/congestion_control/bbr2_sender.cc>
 /// and the Linux Kernel TCP BBRv2 implementation, see <https://github.com/google/bbr/blob/v2alpha/net/ipv4/tcp_bbr2.c>
 #[derive(Debug, Clone)]
-struct BbrCongestionController {
+pub struct BbrCongestionController {
+    state: State,
+    round_counter: round::Counter,
     bw_estimator: bandwidth::Estimator,
-    //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.9.1
-    //# The windowed maximum recent bandwidth sample - obtained using the BBR delivery rate sampling
-    //# algorithm [draft-cheng-iccrg-delivery-rate-estimation] - measured during the current or
-    //# previous bandwidth probing cycle (or during Startup, if the flow is still in that state).
-    max_bw: Bandwidth,
     full_pipe_estimator: full_pipe::Estimator,
+    data_rate_model: data_rate::Model,
+    data_volume_model: data_volume::Model,
+    /// Counts the rounds used for tracking the latest delivery and congestion signals
+    ///
+    /// Unlike `round_counter`, rounds are not restarted when the state changes
+    congestion_round_counter: round::Counter,
+    /// True if any packets were lost in the current congestion round
+    loss_in_round: bool,
+    /// True if the congestion window was fully utilized in the current round
+    cwnd_limited_in_round: bool,
+    /// True if the rate samples reflect bandwidth probing, and can be used to adjust inflight_hi
+    bw_probe_samples: bool,
+    /// True if sending is restarting after being idle
+    idle_restart: bool,
+    cwnd: u32,
+    /// The congestion window saved before entering recovery or ProbeRTT
+    prior_cwnd: u32,
+    /// The current rate at which data is paced onto the network path
+    pacing_rate: Bandwidth,
+    pacer: Pacer,
+    max_datagram_size: u16,
     //= https://www.rfc-editor.org/rfc/rfc9002#section-B.2
     //# The sum of the size in bytes of all sent packets
     //# that contain at least one ack-eliciting or PADDING frame and have
@@ -40,6 +200,10 @@ struct BbrCongestionController {
     //# congestion feedback.
     bytes_in_flight: BytesInFlight,
     recovery_state: recovery::State,
+    /// The most recent RTT sample, applied to the model on the next acknowledgement
+    rtt_sample: Option<Duration>,
+    /// The state of the generator used for randomizing the time between bandwidth probes
+    random_state: u64,
 }
 
 type BytesInFlight = Counter<u32>;
@@ -47,29 +211,39 @@ type BytesInFlight = Counter<u32>;
 impl CongestionController for BbrCongestionController {
     type PacketInfo = bandwidth::PacketInfo;
 
+    #[inline]
     fn congestion_window(&self) -> u32 {
-        todo!()
+        self.cwnd
     }
 
+    #[inline]
     fn bytes_in_flight(&self) -> u32 {
         *self.bytes_in_flight
     }
 
+    #[inline]
     fn is_congestion_limited(&self) -> bool {
-        todo!()
+        let available_congestion_window = self.cwnd.saturating_sub(*self.bytes_in_flight);
+        available_congestion_window < self.max_datagram_size as u32
     }
 
+    #[inline]
     fn requires_fast_retransmission(&self) -> bool {
         self.recovery_state.requires_fast_retransmission()
     }
 
+    #[inline]
     fn on_packet_sent(
         &mut self,
         time_sent: Timestamp,
         sent_bytes: usize,
-        _rtt_estimator: &RttEstimator,
+        rtt_estimator: &RttEstimator,
     ) -> Self::PacketInfo {
-        let is_app_limited = false; // TODO: determine if app limited
+        if sent_bytes > 0 && *self.bytes_in_flight == 0 && self.bw_estimator.is_app_limited() {
+            self.on_idle_restart(time_sent);
+        }
+
+        let is_app_limited = self.bw_estimator.is_app_limited();
         let packet_info =
             self.bw_estimator
                 .on_packet_sent(*self.bytes_in_flight, is_app_limited, time_sent);
@@ -80,15 +254,34 @@ impl CongestionController for BbrCongestionController {
             self.bytes_in_flight
                 .try_add(sent_bytes)
                 .expect("sent_bytes should not exceed u32::MAX");
+
+            if self.is_congestion_limited() {
+                self.cwnd_limited_in_round = true;
+            }
+
+            self.pacer.on_packet_sent_with_rate(
+                time_sent,
+                sent_bytes,
+                rtt_estimator,
+                self.pacing_rate,
+                self.max_datagram_size,
+            );
         }
 
         packet_info
     }
 
-    fn on_rtt_update(&mut self, _time_sent: Timestamp, _rtt_estimator: &RttEstimator) {
-        todo!()
+    #[inline]
+    fn on_rtt_update(&mut self, _time_sent: Timestamp, rtt_estimator: &RttEstimator) {
+        self.rtt_sample = Some(rtt_estimator.latest_rtt());
+
+        if self.data_rate_model.max_bw() == Bandwidth::ZERO {
+            // There is no bandwidth estimate yet, so base the pacing rate on the smoothed RTT
+            self.init_pacing_rate(rtt_estimator.smoothed_rtt());
+        }
     }
 
+    #[inline]
     fn on_ack(
         &mut self,
         newest_acked_time_sent: Timestamp,
@@ -97,51 +290,913 @@ impl CongestionController for BbrCongestionController {
         _rtt_estimator: &RttEstimator,
         ack_receive_time: Timestamp,
     ) {
+        // Check if the path was application limited before the acknowledged bytes free up the
+        // congestion window
+        self.check_if_app_limited(ack_receive_time);
+
+        self.bytes_in_flight
+            .try_sub(bytes_acknowledged)
+            .expect("bytes_acknowledged should not exceed u32::MAX");
+
         self.bw_estimator.on_ack(
             bytes_acknowledged,
             newest_acked_time_sent,
             newest_acked_packet_info,
             ack_receive_time,
         );
-        let round_start = false; // TODO: track rounds
-        self.recovery_state
-            .on_ack(round_start, newest_acked_time_sent);
+        self.round_counter.on_ack(
+            newest_acked_packet_info,
+            self.bw_estimator.delivered_bytes(),
+        );
 
-        if round_start {
-            self.full_pipe_estimator.on_round_start(
-                self.bw_estimator.rate_sample(),
-                self.max_bw,
-                self.recovery_state.in_recovery(),
-            )
+        if self
+            .recovery_state
+            .on_ack(self.round_counter.round_start(), newest_acked_time_sent)
+        {
+            // Recovery has ended, so restore the congestion window from before recovery
+            self.restore_cwnd();
+        }
+
+        self.update_model_and_state(
+            newest_acked_packet_info,
+            bytes_acknowledged,
+            ack_receive_time,
+        );
+        self.update_control_parameters(bytes_acknowledged);
+
+        if self.round_counter.round_start() {
+            self.cwnd_limited_in_round = self.is_congestion_limited();
         }
     }
 
+    #[inline]
     fn on_packet_lost(
         &mut self,
         lost_bytes: u32,
-        _packet_info: Self::PacketInfo,
-        _persistent_congestion: bool,
+        packet_info: Self::PacketInfo,
+        persistent_congestion: bool,
         new_loss_burst: bool,
         timestamp: Timestamp,
     ) {
+        debug_assert!(lost_bytes > 0);
+
+        self.bytes_in_flight -= lost_bytes;
         self.bw_estimator.on_loss(lost_bytes as usize);
-        self.recovery_state.on_congestion_event(timestamp);
         self.full_pipe_estimator.on_packet_lost(new_loss_burst);
+        self.loss_in_round = true;
+        self.handle_lost_packet(lost_bytes, packet_info, timestamp);
+
+        if self.recovery_state.in_recovery() {
+            // Reduce the congestion window by the newly lost bytes, allowing at least one packet
+            self.cwnd = max(
+                self.cwnd.saturating_sub(lost_bytes),
+                self.max_datagram_size as u32,
+            );
+        }
+
+        self.on_congestion_event(timestamp);
+
+        if persistent_congestion {
+            // Similar to a retransmission timeout in TCP, only allow one packet beyond
+            // what is in flight to be sent
+            self.prior_cwnd = self.save_cwnd();
+            self.cwnd = *self.bytes_in_flight + self.max_datagram_size as u32;
+        }
     }
 
+    #[inline]
     fn on_congestion_event(&mut self, event_time: Timestamp) {
+        if !self.recovery_state.in_recovery() {
+            // Upon entering recovery, set the congestion window to the data still in flight,
+            // allowing at least one packet for a fast retransmit
+            self.prior_cwnd = self.save_cwnd();
+            self.cwnd = *self.bytes_in_flight + self.max_datagram_size as u32;
+        }
+
         self.recovery_state.on_congestion_event(event_time);
     }
 
-    fn on_mtu_update(&mut self, _max_data_size: u16) {
-        todo!()
+    //= https://www.rfc-editor.org/rfc/rfc9002#section-7.2
+    //# If the maximum datagram size changes during the connection, the
+    //# initial congestion window SHOULD be recalculated with the new size.
+    //# If the maximum datagram size is decreased in order to complete the
+    //# handshake, the congestion window SHOULD be set to the new initial
+    //# congestion window.
+    #[inline]
+    fn on_mtu_update(&mut self, max_datagram_size: u16) {
+        let old_max_datagram_size = self.max_datagram_size;
+        self.max_datagram_size = max_datagram_size;
+
+        if max_datagram_size < old_max_datagram_size {
+            self.cwnd = self.initial_window();
+        } else {
+            self.cwnd = (self.cwnd as u64 * max_datagram_size as u64
+                / old_max_datagram_size as u64)
+                .try_into()
+                .unwrap_or(u32::MAX);
+        }
     }
 
-    fn on_packet_discarded(&mut self, _bytes_sent: usize) {
-        todo!()
+    #[inline]
+    fn on_packet_discarded(&mut self, bytes_sent: usize) {
+        self.bytes_in_flight
+            .try_sub(bytes_sent)
+            .expect("bytes sent should not exceed u32::MAX");
+
+        self.recovery_state.on_packet_discarded();
     }
 
+    #[inline]
     fn earliest_departure_time(&self) -> Option<Timestamp> {
-        todo!()
+        self.pacer.earliest_departure_time()
+    }
+
+    #[inline]
+    fn pacing_rate(&self, _rtt_estimator: &RttEstimator) -> Option<Bandwidth> {
+        Some(self.pacing_rate)
+    }
+}
+
+impl BbrCongestionController {
+    /// Constructs a new `BbrCongestionController`
+    ///
+    /// `random_seed` seeds the generator used to randomize the time between bandwidth probes
+    pub fn new(max_datagram_size: u16, random_seed: u64) -> Self {
+        let initial_window = CubicCongestionController::initial_window(max_datagram_size);
+
+        let mut controller = Self {
+            state: State::Startup,
+            round_counter: Default::default(),
+            bw_estimator: Default::default(),
+            full_pipe_estimator: Default::default(),
+            data_rate_model: Default::default(),
+            data_volume_model: Default::default(),
+            congestion_round_counter: Default::default(),
+            loss_in_round: false,
+            cwnd_limited_in_round: false,
+            bw_probe_samples: false,
+            idle_restart: false,
+            cwnd: initial_window,
+            prior_cwnd: 0,
+            pacing_rate: Bandwidth::ZERO,
+            pacer: Pacer::default(),
+            max_datagram_size,
+            bytes_in_flight: Counter::new(0),
+            recovery_state: recovery::State::Recovered,
+            rtt_sample: None,
+            // xorshift requires a non-zero state
+            random_state: random_seed | 1,
+        };
+
+        controller.init_pacing_rate(INITIAL_PACING_RTT);
+
+        controller
+    }
+
+    /// Returns the current pacing rate
+    #[inline]
+    pub fn pacing_rate(&self) -> Bandwidth {
+        self.pacing_rate
+    }
+
+    /// Updates the model of the network path and transitions between states
+    ///
+    /// Based on BBRUpdateModelAndState in the BBRv2 draft
+    #[inline]
+    fn update_model_and_state(
+        &mut self,
+        packet_info: bandwidth::PacketInfo,
+        bytes_acknowledged: usize,
+        now: Timestamp,
+    ) {
+        let rate_sample = self.bw_estimator.rate_sample();
+
+        // Update the latest delivery signals
+        self.congestion_round_counter
+            .on_ack(packet_info, self.bw_estimator.delivered_bytes());
+        let loss_round_start = self.congestion_round_counter.round_start();
+        self.data_rate_model.update_latest_signals(rate_sample);
+        self.data_volume_model
+            .update_latest_signals(rate_sample.delivered_bytes);
+
+        // Update the congestion signals
+        self.data_rate_model.update_max_bw(rate_sample);
+        if loss_round_start {
+            self.adapt_lower_bounds_from_congestion();
+            self.loss_in_round = false;
+        }
+
+        self.data_volume_model.update_ack_aggregation(
+            self.data_rate_model.bw(),
+            bytes_acknowledged,
+            self.cwnd,
+            self.round_counter.round_count(),
+            now,
+        );
+
+        if self.round_counter.round_start() {
+            if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+                probe_bw_state.on_round_start();
+            }
+        }
+
+        self.check_startup_done(rate_sample);
+        self.check_drain(now);
+        self.update_probe_bw_cycle_phase(rate_sample, bytes_acknowledged, now);
+
+        let probe_rtt_expired = self
+            .data_volume_model
+            .update_min_rtt(self.rtt_sample.take(), now);
+        self.check_probe_rtt(probe_rtt_expired, rate_sample, now);
+
+        // Advance the latest delivery signals
+        if loss_round_start {
+            self.data_rate_model
+                .reset_latest_signals(rate_sample.delivery_rate());
+            self.data_volume_model
+                .reset_latest_signals(rate_sample.delivered_bytes);
+        }
+    }
+
+    /// Updates the pacing rate and congestion window based on the model
+    ///
+    /// Based on BBRUpdateControlParameters in the BBRv2 draft
+    #[inline]
+    fn update_control_parameters(&mut self, bytes_acknowledged: usize) {
+        self.set_pacing_rate(self.state.pacing_gain());
+        self.set_cwnd(bytes_acknowledged);
+    }
+
+    /// Marks the path as application limited if neither the congestion window nor the
+    /// pacer are preventing transmission
+    ///
+    /// The congestion controller is not informed when the application has no more data to
+    /// send, so this is inferred when an acknowledgement is received. The transport sends as
+    /// much as it is allowed before waiting for acknowledgements, so any capacity still
+    /// available at this point was left unused by the application.
+    #[inline]
+    fn check_if_app_limited(&mut self, now: Timestamp) {
+        let pacing_limited = self
+            .pacer
+            .earliest_departure_time()
+            .map_or(false, |departure_time| departure_time > now);
+
+        if !pacing_limited
+            && !self.is_congestion_limited()
+            && !self.recovery_state.requires_fast_retransmission()
+        {
+            self.bw_estimator.on_app_limited(*self.bytes_in_flight);
+        }
+    }
+
+    /// Called when a packet is sent with nothing in flight after the path was app limited
+    #[inline]
+    fn on_idle_restart(&mut self, now: Timestamp) {
+        self.idle_restart = true;
+        self.data_volume_model.on_idle_restart(now);
+
+        if matches!(self.state, State::ProbeBw(_)) {
+            // Resume sending at the estimated bandwidth
+            self.set_pacing_rate(Ratio::new_raw(1, 1));
+        }
+    }
+
+    /// Reduces the short-term lower bounds if there was loss in the last round
+    #[inline]
+    fn adapt_lower_bounds_from_congestion(&mut self) {
+        if self.state.is_probing_bw() {
+            // Losses are expected when probing for bandwidth
+            return;
+        }
+
+        if self.loss_in_round {
+            self.data_rate_model.adapt_lower_bound();
+            self.data_volume_model.adapt_lower_bound(self.cwnd);
+        }
+    }
+
+    /// Removes the short-term lower bounds
+    #[inline]
+    fn reset_lower_bounds(&mut self) {
+        self.data_rate_model.reset_lower_bound();
+        self.data_volume_model.reset_lower_bound();
+    }
+
+    /// Resets the signals tracked over the latest round
+    #[inline]
+    fn reset_congestion_signals(&mut self) {
+        self.loss_in_round = false;
+        self.data_rate_model.reset_latest_signals(Bandwidth::ZERO);
+        self.data_volume_model.reset_latest_signals(0);
+    }
+
+    /// Ends the current round once all data sent so far has been delivered
+    #[inline]
+    fn start_round(&mut self) {
+        self.round_counter
+            .set_round_end(self.bw_estimator.delivered_bytes());
+    }
+
+    /// Exits Startup if the pipe has been filled
+    #[inline]
+    fn check_startup_done(&mut self, rate_sample: bandwidth::RateSample) {
+        if self.round_counter.round_start() {
+            self.full_pipe_estimator.on_round_start(
+                rate_sample,
+                self.data_rate_model.max_bw(),
+                self.recovery_state.in_recovery(),
+            );
+        }
+
+        if matches!(self.state, State::Startup) && self.full_pipe_estimator.filled_pipe() {
+            if self.recovery_state.in_recovery() {
+                // Startup ended due to loss, so start with an upper bound on the data in
+                // flight rather than probing for it
+                let inflight_hi = max(
+                    self.bdp_multiple(self.data_rate_model.bw(), Ratio::new_raw(1, 1)),
+                    self.data_volume_model.inflight_latest(),
+                );
+                self.data_volume_model.set_inflight_hi(inflight_hi);
+            }
+
+            self.state = State::Drain;
+        }
+    }
+
+    /// Exits Drain once the queue created in Startup has drained
+    #[inline]
+    fn check_drain(&mut self, now: Timestamp) {
+        if matches!(self.state, State::Drain)
+            && *self.bytes_in_flight as u64
+                <= self.inflight(self.data_rate_model.max_bw(), Ratio::new_raw(1, 1))
+        {
+            self.start_probe_bw_down(now);
+        }
+    }
+
+    /// Starts a new ProbeBW cycle in the Down phase
+    #[inline]
+    fn start_probe_bw_down(&mut self, now: Timestamp) {
+        self.reset_congestion_signals();
+        let random = self.random();
+        self.state = State::ProbeBw(probe_bw::State::new(now, random));
+        self.start_round();
+    }
+
+    #[inline]
+    fn start_probe_bw_cruise(&mut self) {
+        if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+            probe_bw_state.start_cruise();
+        }
+    }
+
+    #[inline]
+    fn start_probe_bw_refill(&mut self) {
+        self.reset_lower_bounds();
+
+        if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+            probe_bw_state.start_refill();
+        }
+
+        self.start_round();
+    }
+
+    #[inline]
+    fn start_probe_bw_up(&mut self, now: Timestamp) {
+        if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+            probe_bw_state.start_up(self.cwnd, self.max_datagram_size, now);
+        }
+
+        self.start_round();
+    }
+
+    /// Transitions between the phases of the ProbeBW cycle
+    ///
+    /// Based on BBRUpdateProbeBWCyclePhase in the BBRv2 draft
+    #[inline]
+    fn update_probe_bw_cycle_phase(
+        &mut self,
+        rate_sample: bandwidth::RateSample,
+        bytes_acknowledged: usize,
+        now: Timestamp,
+    ) {
+        if !self.full_pipe_estimator.filled_pipe() {
+            // Only handle ProbeBW states once the pipe has been filled
+            return;
+        }
+
+        self.adapt_upper_bounds(rate_sample, bytes_acknowledged, now);
+
+        let cycle_phase = if let Some(cycle_phase) = self.state.cycle_phase() {
+            cycle_phase
+        } else {
+            return;
+        };
+
+        match cycle_phase {
+            probe_bw::CyclePhase::Down => {
+                if self.check_time_to_probe_bw(now) {
+                    // Already decided to move to Refill
+                    return;
+                }
+
+                if self.is_time_to_cruise() {
+                    self.start_probe_bw_cruise();
+                }
+            }
+            probe_bw::CyclePhase::Cruise => {
+                self.check_time_to_probe_bw(now);
+            }
+            probe_bw::CyclePhase::Refill => {
+                // After one round of Refill, start Up
+                if self.round_counter.round_start() {
+                    self.bw_probe_samples = true;
+                    self.start_probe_bw_up(now);
+                }
+            }
+            probe_bw::CyclePhase::Up => {
+                let min_rtt = self.data_volume_model.min_rtt().unwrap_or_default();
+                let has_elapsed_min_rtt = matches!(
+                    self.state,
+                    State::ProbeBw(ref probe_bw_state)
+                        if probe_bw_state.has_elapsed_in_phase(min_rtt, now)
+                );
+
+                if has_elapsed_min_rtt
+                    && *self.bytes_in_flight as u64
+                        > self.inflight(
+                            self.data_rate_model.max_bw(),
+                            probe_bw::CyclePhase::Up.pacing_gain(),
+                        )
+                {
+                    self.start_probe_bw_down(now);
+                }
+            }
+        }
+    }
+
+    /// Starts Refill if it is time to probe for bandwidth
+    ///
+    /// Returns true if Refill was started
+    #[inline]
+    fn check_time_to_probe_bw(&mut self, now: Timestamp) -> bool {
+        let target_inflight = self.target_inflight();
+
+        let is_time_to_probe_bw = matches!(
+            self.state,
+            State::ProbeBw(ref probe_bw_state)
+                if probe_bw_state.is_time_to_probe_bw(target_inflight, self.max_datagram_size, now)
+        );
+
+        if is_time_to_probe_bw {
+            self.start_probe_bw_refill();
+        }
+
+        is_time_to_probe_bw
+    }
+
+    /// Returns true if enough data has drained to move from Down to Cruise
+    #[inline]
+    fn is_time_to_cruise(&self) -> bool {
+        let bytes_in_flight = *self.bytes_in_flight as u64;
+
+        if bytes_in_flight > self.inflight_with_headroom() {
+            // Not enough headroom for other flows
+            return false;
+        }
+
+        bytes_in_flight <= self.inflight(self.data_rate_model.max_bw(), Ratio::new_raw(1, 1))
+    }
+
+    /// Adjusts inflight_hi based on the samples from bandwidth probing
+    ///
+    /// Based on BBRAdaptUpperBounds in the BBRv2 draft
+    #[inline]
+    fn adapt_upper_bounds(
+        &mut self,
+        rate_sample: bandwidth::RateSample,
+        bytes_acknowledged: usize,
+        now: Timestamp,
+    ) {
+        if self.round_counter.round_start() {
+            if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+                if probe_bw_state.ack_phase() == probe_bw::AckPhase::ProbeStopping {
+                    // All samples from the previous bandwidth probe have arrived
+                    self.bw_probe_samples = false;
+                    probe_bw_state.on_probe_stopped();
+
+                    // The current bandwidth sample is the best recent estimate, so now is
+                    // the time to forget samples from the previous cycle
+                    if !rate_sample.is_app_limited {
+                        self.data_rate_model.advance_max_bw_filter();
+                    }
+                }
+            }
+        }
+
+        if self.check_inflight_too_high(rate_sample, now) {
+            return;
+        }
+
+        let inflight_hi = self.data_volume_model.inflight_hi();
+        if inflight_hi == u64::MAX {
+            // No upper bound to raise
+            return;
+        }
+
+        let tx_in_flight = rate_sample.bytes_in_flight as u64;
+        if tx_in_flight > inflight_hi {
+            self.data_volume_model.set_inflight_hi(tx_in_flight);
+        }
+
+        if self.state.cycle_phase() == Some(probe_bw::CyclePhase::Up) {
+            self.probe_inflight_hi_upward(bytes_acknowledged);
+        }
+    }
+
+    /// Raises inflight_hi while the congestion window is being fully utilized in Up
+    #[inline]
+    fn probe_inflight_hi_upward(&mut self, bytes_acknowledged: usize) {
+        let inflight_hi = self.data_volume_model.inflight_hi();
+
+        if !self.cwnd_limited_in_round || (self.cwnd as u64) < inflight_hi {
+            // Not fully using inflight_hi, so don't grow it
+            return;
+        }
+
+        let round_start = self.round_counter.round_start();
+
+        if let State::ProbeBw(ref mut probe_bw_state) = self.state {
+            let inflight_hi = probe_bw_state.probe_inflight_hi_upward(
+                bytes_acknowledged,
+                inflight_hi,
+                self.cwnd,
+                self.max_datagram_size,
+                round_start,
+            );
+            self.data_volume_model.set_inflight_hi(inflight_hi);
+        }
+    }
+
+    /// Returns true if the loss rate of the rate sample is too high, reducing inflight_hi
+    /// if bandwidth was being probed
+    #[inline]
+    fn check_inflight_too_high(
+        &mut self,
+        rate_sample: bandwidth::RateSample,
+        now: Timestamp,
+    ) -> bool {
+        let tx_in_flight = rate_sample.bytes_in_flight as u64;
+
+        if Self::is_inflight_too_high(rate_sample.lost_bytes, tx_in_flight) {
+            if self.bw_probe_samples {
+                self.handle_inflight_too_high(rate_sample.is_app_limited, tx_in_flight, now);
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns true if the amount of data lost exceeds `LOSS_THRESH` of the data in flight
+    #[inline]
+    fn is_inflight_too_high(lost_bytes: u64, tx_in_flight: u64) -> bool {
+        lost_bytes > (LOSS_THRESH * tx_in_flight).to_integer()
+    }
+
+    /// Reduces inflight_hi in response to excessive loss while probing for bandwidth
+    #[inline]
+    fn handle_inflight_too_high(
+        &mut self,
+        is_app_limited: bool,
+        tx_in_flight: u64,
+        now: Timestamp,
+    ) {
+        self.bw_probe_samples = false;
+
+        if !is_app_limited {
+            let inflight_hi = max(tx_in_flight, (BETA * self.target_inflight()).to_integer());
+            self.data_volume_model.set_inflight_hi(inflight_hi);
+        }
+
+        if self.state.cycle_phase() == Some(probe_bw::CyclePhase::Up) {
+            self.start_probe_bw_down(now);
+        }
+    }
+
+    /// Checks if the loss of a packet while probing for bandwidth indicates that too much
+    /// data was in flight
+    ///
+    /// This allows for reacting to loss as soon as it is detected, rather than waiting for the
+    /// end of the round.
+    #[inline]
+    fn handle_lost_packet(
+        &mut self,
+        lost_bytes: u32,
+        packet_info: bandwidth::PacketInfo,
+        now: Timestamp,
+    ) {
+        if !self.bw_probe_samples {
+            return;
+        }
+
+        // The bytes in flight when the packet was sent, including the packet itself
+        let tx_in_flight = packet_info.bytes_in_flight as u64 + lost_bytes as u64;
+        // The bytes lost since the packet was sent, including the packet itself
+        let lost = self.bw_estimator.lost_bytes() - packet_info.lost_bytes;
+
+        if Self::is_inflight_too_high(lost, tx_in_flight) {
+            let tx_in_flight = Self::inflight_hi_from_lost_packet(lost_bytes, tx_in_flight, lost);
+            self.handle_inflight_too_high(packet_info.is_app_limited, tx_in_flight, now);
+        }
+    }
+
+    /// Estimates the data in flight at the point the loss rate crossed `LOSS_THRESH`
+    ///
+    /// Based on BBRInflightHiFromLostPacket in the BBRv2 draft
+    #[inline]
+    fn inflight_hi_from_lost_packet(size: u32, tx_in_flight: u64, lost: u64) -> u64 {
+        let size = size as u64;
+        // The data in flight and lost before this packet
+        let inflight_prev = tx_in_flight.saturating_sub(size);
+        let lost_prev = lost.saturating_sub(size);
+        // The amount of this packet that could have been lost before crossing the threshold
+        let loss_budget = (LOSS_THRESH * inflight_prev)
+            .to_integer()
+            .saturating_sub(lost_prev);
+        let lost_prefix = (Ratio::from_integer(loss_budget)
+            / (Ratio::from_integer(1) - LOSS_THRESH))
+            .to_integer();
+
+        inflight_prev + lost_prefix
+    }
+
+    /// Enters ProbeRTT if the min_rtt estimate has expired, and exits once it has been refreshed
+    #[inline]
+    fn check_probe_rtt(
+        &mut self,
+        probe_rtt_expired: bool,
+        rate_sample: bandwidth::RateSample,
+        now: Timestamp,
+    ) {
+        if !matches!(self.state, State::ProbeRtt(_)) && probe_rtt_expired && !self.idle_restart {
+            self.prior_cwnd = self.save_cwnd();
+            self.state = State::ProbeRtt(probe_rtt::State::default());
+            self.start_round();
+        }
+
+        if matches!(self.state, State::ProbeRtt(_)) {
+            self.handle_probe_rtt(now);
+        }
+
+        if rate_sample.delivered_bytes > 0 {
+            self.idle_restart = false;
+        }
+    }
+
+    #[inline]
+    fn handle_probe_rtt(&mut self, now: Timestamp) {
+        // Ignore low rate samples during ProbeRTT
+        self.bw_estimator.on_app_limited(*self.bytes_in_flight);
+
+        let bytes_in_flight = *self.bytes_in_flight;
+        let probe_rtt_cwnd = self.probe_rtt_cwnd();
+        let delivered_bytes = self.bw_estimator.delivered_bytes();
+
+        let probe_rtt_done = if let State::ProbeRtt(ref mut probe_rtt_state) = self.state {
+            probe_rtt_state.on_ack(
+                bytes_in_flight,
+                probe_rtt_cwnd,
+                &mut self.round_counter,
+                delivered_bytes,
+                now,
+            )
+        } else {
+            false
+        };
+
+        if probe_rtt_done {
+            self.data_volume_model.on_probe_rtt_complete(now);
+            self.restore_cwnd();
+            self.reset_lower_bounds();
+
+            if self.full_pipe_estimator.filled_pipe() {
+                self.start_probe_bw_down(now);
+                self.start_probe_bw_cruise();
+            } else {
+                self.state = State::Startup;
+            }
+        }
+    }
+
+    /// Sets the pacing rate to the bandwidth estimate scaled by the given gain
+    #[inline]
+    fn set_pacing_rate(&mut self, pacing_gain: Ratio<u64>) {
+        let rate = self.data_rate_model.bw() * (pacing_gain * PACING_MARGIN);
+
+        // Don't reduce the pacing rate below the initial rate until the pipe has been filled
+        if self.full_pipe_estimator.filled_pipe() || rate > self.pacing_rate {
+            self.pacing_rate = rate;
+        }
+    }
+
+    /// Sets the pacing rate to allow the initial window to be sent over one RTT at the
+    /// Startup pacing gain
+    #[inline]
+    fn init_pacing_rate(&mut self, rtt: Duration) {
+        let nominal_bandwidth = Bandwidth::new(self.initial_window() as u64, rtt);
+        self.pacing_rate = nominal_bandwidth * STARTUP_PACING_GAIN;
+    }
+
+    /// Sets the congestion window based on the model
+    ///
+    /// Based on BBRSetCwnd in the BBRv2 draft
+    #[inline]
+    fn set_cwnd(&mut self, bytes_acknowledged: usize) {
+        let bytes_acknowledged = bytes_acknowledged as u64;
+        let max_inflight = self.max_inflight();
+        let minimum_window = self.minimum_window() as u64;
+        let mut cwnd = self.cwnd as u64;
+
+        if self.recovery_state.packet_conservation() {
+            cwnd = max(cwnd, *self.bytes_in_flight as u64 + bytes_acknowledged);
+        } else {
+            if self.full_pipe_estimator.filled_pipe() {
+                cwnd = min(cwnd + bytes_acknowledged, max_inflight);
+            } else if cwnd < max_inflight
+                || self.bw_estimator.delivered_bytes() < self.initial_window() as u64
+            {
+                cwnd += bytes_acknowledged;
+            }
+            cwnd = max(cwnd, minimum_window);
+        }
+
+        if matches!(self.state, State::ProbeRtt(_)) {
+            cwnd = min(cwnd, self.probe_rtt_cwnd());
+        }
+
+        // Bound the congestion window by the upper and lower bounds of the model
+        let cap = match self.state.cycle_phase() {
+            Some(probe_bw::CyclePhase::Cruise) => self.inflight_with_headroom(),
+            Some(_) => self.data_volume_model.inflight_hi(),
+            None if matches!(self.state, State::ProbeRtt(_)) => self.inflight_with_headroom(),
+            None => u64::MAX,
+        };
+        let cap = max(
+            min(cap, self.data_volume_model.inflight_lo()),
+            minimum_window,
+        );
+        cwnd = min(cwnd, cap);
+
+        self.cwnd = cwnd.try_into().unwrap_or(u32::MAX);
+    }
+
+    /// Saves the congestion window before entering recovery or ProbeRTT
+    #[inline]
+    fn save_cwnd(&self) -> u32 {
+        if !self.recovery_state.in_recovery() && !matches!(self.state, State::ProbeRtt(_)) {
+            self.cwnd
+        } else {
+            max(self.prior_cwnd, self.cwnd)
+        }
+    }
+
+    /// Restores the congestion window saved before entering recovery or ProbeRTT
+    #[inline]
+    fn restore_cwnd(&mut self) {
+        self.cwnd = max(self.cwnd, self.prior_cwnd);
+    }
+
+    /// The estimated bandwidth-delay product scaled by the given gain
+    #[inline]
+    fn bdp_multiple(&self, bw: Bandwidth, gain: Ratio<u64>) -> u64 {
+        if let Some(min_rtt) = self.data_volume_model.min_rtt() {
+            (gain * (bw * min_rtt)).to_integer()
+        } else {
+            // No valid RTT samples yet
+            self.initial_window() as u64
+        }
+    }
+
+    /// The volume of data in flight appropriate for the given bandwidth and gain
+    #[inline]
+    fn inflight(&self, bw: Bandwidth, gain: Ratio<u64>) -> u64 {
+        self.quantization_budget(self.bdp_multiple(bw, gain))
+    }
+
+    /// The maximum volume of data allowed in flight, considering ACK aggregation
+    #[inline]
+    fn max_inflight(&self) -> u64 {
+        let inflight = self.bdp_multiple(self.data_rate_model.bw(), self.state.cwnd_gain())
+            + self.data_volume_model.extra_acked();
+        self.quantization_budget(inflight)
+    }
+
+    /// Increases the given volume of data in flight to allow for offloading of sending to lower
+    /// layers and to allow inflight_hi to grow while probing for bandwidth
+    #[inline]
+    fn quantization_budget(&self, inflight: u64) -> u64 {
+        let offload_budget = 3 * self.send_quantum();
+        let mut inflight = max(inflight, offload_budget);
+        inflight = max(inflight, self.minimum_window() as u64);
+
+        if self.state.cycle_phase() == Some(probe_bw::CyclePhase::Up) {
+            inflight += 2 * self.max_datagram_size as u64;
+        }
+
+        inflight
+    }
+
+    /// The amount of data sent in a single burst at the current pacing rate
+    #[inline]
+    fn send_quantum(&self) -> u64 {
+        let send_quantum = min(self.pacing_rate * SEND_QUANTUM_INTERVAL, MAX_SEND_QUANTUM);
+        max(send_quantum, 2 * self.max_datagram_size as u64)
+    }
+
+    /// The volume of data to aim to have in flight
+    #[inline]
+    fn target_inflight(&self) -> u64 {
+        min(
+            self.bdp_multiple(self.data_rate_model.bw(), Ratio::new_raw(1, 1)),
+            self.cwnd as u64,
+        )
+    }
+
+    /// inflight_hi, reduced to leave headroom for other flows
+    #[inline]
+    fn inflight_with_headroom(&self) -> u64 {
+        let inflight_hi = self.data_volume_model.inflight_hi();
+
+        if inflight_hi == u64::MAX {
+            return u64::MAX;
+        }
+
+        let headroom = max(
+            self.max_datagram_size as u64,
+            (HEADROOM * inflight_hi).to_integer(),
+        );
+        max(
+            inflight_hi.saturating_sub(headroom),
+            self.minimum_window() as u64,
+        )
+    }
+
+    /// The congestion window used while in ProbeRTT
+    #[inline]
+    fn probe_rtt_cwnd(&self) -> u64 {
+        max(
+            self.bdp_multiple(self.data_rate_model.bw(), PROBE_RTT_CWND_GAIN),
+            self.minimum_window() as u64,
+        )
+    }
+
+    #[inline]
+    fn initial_window(&self) -> u32 {
+        CubicCongestionController::initial_window(self.max_datagram_size)
+    }
+
+    #[inline]
+    fn minimum_window(&self) -> u32 {
+        MIN_PIPE_CWND_PACKETS * self.max_datagram_size as u32
+    }
+
+    /// Generates a pseudorandom value using xorshift64*
+    #[inline]
+    fn random(&mut self) -> u64 {
+        let mut x = self.random_state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.random_state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
     }
 }
+
+#[derive(Debug, Default)]
+pub struct Endpoint {
+    /// Seeds each congestion controller differently so flows sharing a bottleneck
+    /// do not probe for bandwidth at the same time
+    random_seed: u64,
+}
+
+impl congestion_controller::Endpoint for Endpoint {
+    type CongestionController = BbrCongestionController;
+
+    fn new_congestion_controller(
+        &mut self,
+        path_info: congestion_controller::PathInfo,
+    ) -> Self::CongestionController {
+        // splitmix64
+        self.random_seed = self.random_seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut seed = self.random_seed;
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        seed ^= seed >> 31;
+
+        BbrCongestionController::new(path_info.max_datagram_size, seed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
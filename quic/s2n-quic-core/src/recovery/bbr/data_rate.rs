@@ -0,0 +1,182 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::recovery::{
+    bandwidth::{Bandwidth, RateSample},
+    bbr,
+};
+use core::cmp::{max, min};
+
+/// The data rate related parameters of the BBR model of the network path
+#[derive(Clone, Debug)]
+pub(crate) struct Model {
+    /// The maximum delivery rate sampled in the previous ([0]) and current ([1]) ProbeBW cycle
+    ///
+    /// Based on bw_hi in tcp_bbr2.c, BBR.max_bw is the windowed max of these two values
+    max_bw_filter: [Bandwidth; 2],
+    /// The long-term lower bound on the delivery rate, reduced in response to loss
+    bw_lo: Bandwidth,
+    /// The maximum delivery rate sampled in the latest round trip
+    bw_latest: Bandwidth,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            max_bw_filter: [Bandwidth::ZERO; 2],
+            bw_lo: Bandwidth::INFINITY,
+            bw_latest: Bandwidth::ZERO,
+        }
+    }
+}
+
+impl Model {
+    //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.9.1
+    //# The windowed maximum recent bandwidth sample - obtained using the BBR delivery rate sampling
+    //# algorithm [draft-cheng-iccrg-delivery-rate-estimation] - measured during the current or
+    //# previous bandwidth probing cycle (or during Startup, if the flow is still in that state).
+    #[inline]
+    pub fn max_bw(&self) -> Bandwidth {
+        max(self.max_bw_filter[0], self.max_bw_filter[1])
+    }
+
+    /// The maximum sending bandwidth that the model estimates is appropriate
+    /// for matching the current network path delivery rate
+    #[inline]
+    pub fn bw(&self) -> Bandwidth {
+        min(self.max_bw(), self.bw_lo)
+    }
+
+    /// Updates the maximum bandwidth filter with a new rate sample
+    #[inline]
+    pub fn update_max_bw(&mut self, rate_sample: RateSample) {
+        let delivery_rate = rate_sample.delivery_rate();
+
+        // Application limited samples are only used if they indicate the bandwidth is higher
+        // than previously estimated, since they may underestimate the available bandwidth
+        if delivery_rate >= self.max_bw() || !rate_sample.is_app_limited {
+            self.max_bw_filter[1] = max(self.max_bw_filter[1], delivery_rate);
+        }
+    }
+
+    /// Moves the maximum bandwidth filter to the next ProbeBW cycle, forgetting
+    /// samples from the oldest cycle
+    #[inline]
+    pub fn advance_max_bw_filter(&mut self) {
+        if self.max_bw_filter[1] == Bandwidth::ZERO {
+            // No samples have been taken in the current cycle yet
+            return;
+        }
+
+        self.max_bw_filter[0] = self.max_bw_filter[1];
+        self.max_bw_filter[1] = Bandwidth::ZERO;
+    }
+
+    /// Updates the latest delivery signals with a new rate sample
+    #[inline]
+    pub fn update_latest_signals(&mut self, rate_sample: RateSample) {
+        self.bw_latest = max(self.bw_latest, rate_sample.delivery_rate());
+    }
+
+    /// Resets the latest delivery signals at the start of a new round
+    #[inline]
+    pub fn reset_latest_signals(&mut self, bw_latest: Bandwidth) {
+        self.bw_latest = bw_latest;
+    }
+
+    /// Reduces the lower bound on the delivery rate in response to loss
+    #[inline]
+    pub fn adapt_lower_bound(&mut self) {
+        if self.bw_lo == Bandwidth::INFINITY {
+            self.bw_lo = self.max_bw();
+        }
+
+        self.bw_lo = max(self.bw_latest, self.bw_lo * bbr::BETA);
+    }
+
+    /// Removes the lower bound on the delivery rate
+    #[inline]
+    pub fn reset_lower_bound(&mut self) {
+        self.bw_lo = Bandwidth::INFINITY;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+
+    fn rate_sample(bytes: u64, is_app_limited: bool) -> RateSample {
+        RateSample {
+            interval: Duration::from_secs(1),
+            delivered_bytes: bytes,
+            is_app_limited,
+            ..Default::default()
+        }
+    }
+
+    fn bandwidth(bytes: u64) -> Bandwidth {
+        Bandwidth::new(bytes, Duration::from_secs(1))
+    }
+
+    #[test]
+    fn max_bw_filter() {
+        let mut model = Model::default();
+        assert_eq!(Bandwidth::ZERO, model.max_bw());
+        assert_eq!(Bandwidth::ZERO, model.bw());
+
+        model.update_max_bw(rate_sample(1000, false));
+        model.update_max_bw(rate_sample(500, false));
+        assert_eq!(bandwidth(1000), model.max_bw());
+
+        // The max is retained through the next cycle
+        model.advance_max_bw_filter();
+        model.update_max_bw(rate_sample(800, false));
+        assert_eq!(bandwidth(1000), model.max_bw());
+
+        // and forgotten after that
+        model.advance_max_bw_filter();
+        assert_eq!(bandwidth(800), model.max_bw());
+
+        // Advancing without a new sample does not forget the previous samples
+        model.advance_max_bw_filter();
+        assert_eq!(bandwidth(800), model.max_bw());
+    }
+
+    #[test]
+    fn max_bw_app_limited() {
+        let mut model = Model::default();
+        model.update_max_bw(rate_sample(1000, false));
+
+        // App limited samples lower than the max are ignored
+        model.advance_max_bw_filter();
+        model.update_max_bw(rate_sample(500, true));
+        model.advance_max_bw_filter();
+        assert_eq!(bandwidth(1000), model.max_bw());
+
+        // App limited samples greater than the max are used
+        model.update_max_bw(rate_sample(2000, true));
+        assert_eq!(bandwidth(2000), model.max_bw());
+    }
+
+    #[test]
+    fn lower_bound() {
+        let mut model = Model::default();
+        model.update_max_bw(rate_sample(1000, false));
+        model.update_latest_signals(rate_sample(500, false));
+        assert_eq!(Bandwidth::INFINITY, model.bw_lo);
+
+        // bw_lo is initialized to max_bw and reduced by BETA
+        model.adapt_lower_bound();
+        assert_eq!(bandwidth(700), model.bw_lo);
+        assert_eq!(bandwidth(700), model.bw());
+
+        // bw_lo is not reduced below the latest delivery rate
+        model.reset_latest_signals(bandwidth(600));
+        model.adapt_lower_bound();
+        assert_eq!(bandwidth(600), model.bw_lo);
+
+        model.reset_lower_bound();
+        assert_eq!(bandwidth(1000), model.bw());
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    recovery::{bandwidth::Bandwidth, bbr},
+    time::Timestamp,
+};
+use core::{
+    cmp::{max, min},
+    time::Duration,
+};
+
+/// The amount of time after which the min_rtt estimate expires if it is not refreshed
+///
+/// Based on BBRMinRTTFilterLen in the BBRv2 draft
+const MIN_RTT_FILTER_LEN: Duration = Duration::from_secs(10);
+
+/// The amount of time between attempts to refresh the min_rtt estimate in ProbeRTT
+///
+/// Based on BBRProbeRTTInterval in the BBRv2 draft
+const PROBE_RTT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The number of rounds each slot of the extra_acked filter covers
+///
+/// Based on bbr_extra_acked_win_rtts in tcp_bbr2.c. With two slots, the filter
+/// covers the BBRExtraAckedFilterLen of 10 round trips in the BBRv2 draft.
+const EXTRA_ACKED_WINDOW_ROUNDS: u64 = 5;
+
+/// The data volume related parameters of the BBR model of the network path
+#[derive(Clone, Debug)]
+pub(crate) struct Model {
+    /// The windowed minimum round-trip time sample
+    min_rtt: Option<Duration>,
+    /// The time `min_rtt` was last updated
+    min_rtt_timestamp: Option<Timestamp>,
+    /// The minimum round-trip time sample since the last ProbeRTT
+    probe_rtt_min_delay: Option<Duration>,
+    /// The time `probe_rtt_min_delay` was last updated
+    probe_rtt_min_timestamp: Option<Timestamp>,
+    /// The maximum volume of data acknowledged beyond the amount expected given the
+    /// current bandwidth estimate, in the previous ([0]) and current ([1]) window of rounds
+    extra_acked_filter: [u64; 2],
+    /// The round count the current extra_acked filter window started
+    extra_acked_window_start: u64,
+    /// The start of the current interval used to estimate the excess volume of data acknowledged
+    extra_acked_interval_start: Option<Timestamp>,
+    /// The volume of data acknowledged since `extra_acked_interval_start`
+    extra_acked_delivered: u64,
+    /// The long-term upper bound on the volume of data in flight, increased while probing for
+    /// bandwidth and reduced in response to loss
+    inflight_hi: u64,
+    /// The short-term lower bound on the volume of data in flight, reduced in response to loss
+    inflight_lo: u64,
+    /// The maximum volume of data delivered in the latest round trip
+    inflight_latest: u64,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            min_rtt: None,
+            min_rtt_timestamp: None,
+            probe_rtt_min_delay: None,
+            probe_rtt_min_timestamp: None,
+            extra_acked_filter: [0; 2],
+            extra_acked_window_start: 0,
+            extra_acked_interval_start: None,
+            extra_acked_delivered: 0,
+            inflight_hi: u64::MAX,
+            inflight_lo: u64::MAX,
+            inflight_latest: 0,
+        }
+    }
+}
+
+impl Model {
+    /// The windowed minimum round-trip time, or `None` if no RTT has been sampled
+    #[inline]
+    pub fn min_rtt(&self) -> Option<Duration> {
+        self.min_rtt
+    }
+
+    /// The estimated volume of data that is acknowledged in excess of the current bandwidth
+    /// estimate due to aggregation of acknowledgements
+    #[inline]
+    pub fn extra_acked(&self) -> u64 {
+        max(self.extra_acked_filter[0], self.extra_acked_filter[1])
+    }
+
+    /// The long-term upper bound on the volume of data in flight
+    #[inline]
+    pub fn inflight_hi(&self) -> u64 {
+        self.inflight_hi
+    }
+
+    /// The short-term lower bound on the volume of data in flight
+    #[inline]
+    pub fn inflight_lo(&self) -> u64 {
+        self.inflight_lo
+    }
+
+    /// The maximum volume of data delivered in the latest round trip
+    #[inline]
+    pub fn inflight_latest(&self) -> u64 {
+        self.inflight_latest
+    }
+
+    /// Updates the min_rtt estimate with the given RTT sample, if any
+    ///
+    /// Returns true if it is time to enter ProbeRTT to refresh the min_rtt estimate
+    #[inline]
+    pub fn update_min_rtt(&mut self, rtt: Option<Duration>, now: Timestamp) -> bool {
+        let probe_rtt_expired = self
+            .probe_rtt_min_timestamp
+            .map_or(false, |timestamp| now > timestamp + PROBE_RTT_INTERVAL);
+
+        if let Some(rtt) = rtt {
+            if self.probe_rtt_min_delay.map_or(true, |delay| rtt < delay) || probe_rtt_expired {
+                self.probe_rtt_min_delay = Some(rtt);
+                self.probe_rtt_min_timestamp = Some(now);
+            }
+        }
+
+        let min_rtt_expired = self
+            .min_rtt_timestamp
+            .map_or(false, |timestamp| now > timestamp + MIN_RTT_FILTER_LEN);
+
+        if let Some(probe_rtt_min_delay) = self.probe_rtt_min_delay {
+            if self
+                .min_rtt
+                .map_or(true, |min_rtt| probe_rtt_min_delay < min_rtt)
+                || min_rtt_expired
+            {
+                self.min_rtt = Some(probe_rtt_min_delay);
+                self.min_rtt_timestamp = self.probe_rtt_min_timestamp;
+            }
+        }
+
+        probe_rtt_expired
+    }
+
+    /// Called when ProbeRTT completes, delaying the next ProbeRTT by `PROBE_RTT_INTERVAL`
+    #[inline]
+    pub fn on_probe_rtt_complete(&mut self, now: Timestamp) {
+        self.probe_rtt_min_timestamp = Some(now);
+    }
+
+    /// Updates the estimate of the volume of data acknowledged in excess of what the
+    /// bandwidth estimate predicts
+    ///
+    /// Based on BBRUpdateACKAggregation in the BBRv2 draft
+    #[inline]
+    pub fn update_ack_aggregation(
+        &mut self,
+        bw: Bandwidth,
+        bytes_acknowledged: usize,
+        cwnd: u32,
+        round_count: u64,
+        now: Timestamp,
+    ) {
+        let interval_start = *self.extra_acked_interval_start.get_or_insert(now);
+        let mut expected_delivered = bw * (now - interval_start);
+
+        // Reset the interval if the ACK rate is below the expected rate
+        if self.extra_acked_delivered <= expected_delivered {
+            self.extra_acked_delivered = 0;
+            self.extra_acked_interval_start = Some(now);
+            expected_delivered = 0;
+        }
+
+        self.extra_acked_delivered += bytes_acknowledged as u64;
+        let extra = self
+            .extra_acked_delivered
+            .saturating_sub(expected_delivered);
+        let extra = min(extra, cwnd as u64);
+
+        if round_count >= self.extra_acked_window_start + EXTRA_ACKED_WINDOW_ROUNDS {
+            self.extra_acked_window_start = round_count;
+            self.extra_acked_filter[0] = self.extra_acked_filter[1];
+            self.extra_acked_filter[1] = 0;
+        }
+
+        self.extra_acked_filter[1] = max(self.extra_acked_filter[1], extra);
+    }
+
+    /// Restarts the ACK aggregation interval after the path was idle
+    #[inline]
+    pub fn on_idle_restart(&mut self, now: Timestamp) {
+        self.extra_acked_interval_start = Some(now);
+        self.extra_acked_delivered = 0;
+    }
+
+    /// Sets the long-term upper bound on the volume of data in flight
+    #[inline]
+    pub fn set_inflight_hi(&mut self, inflight_hi: u64) {
+        self.inflight_hi = inflight_hi;
+    }
+
+    /// Updates the latest delivery signals with the volume of data delivered in a rate sample
+    #[inline]
+    pub fn update_latest_signals(&mut self, delivered_bytes: u64) {
+        self.inflight_latest = max(self.inflight_latest, delivered_bytes);
+    }
+
+    /// Resets the latest delivery signals at the start of a new round
+    #[inline]
+    pub fn reset_latest_signals(&mut self, inflight_latest: u64) {
+        self.inflight_latest = inflight_latest;
+    }
+
+    /// Reduces the lower bound on the volume of data in flight in response to loss
+    #[inline]
+    pub fn adapt_lower_bound(&mut self, cwnd: u32) {
+        if self.inflight_lo == u64::MAX {
+            self.inflight_lo = cwnd as u64;
+        }
+
+        self.inflight_lo = max(
+            self.inflight_latest,
+            (bbr::BETA * self.inflight_lo).to_integer(),
+        );
+    }
+
+    /// Removes the lower bound on the volume of data in flight
+    #[inline]
+    pub fn reset_lower_bound(&mut self) {
+        self.inflight_lo = u64::MAX;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    #[test]
+    fn min_rtt() {
+        let mut model = Model::default();
+        let now = NoopClock.get_time();
+        assert_eq!(None, model.min_rtt());
+
+        assert!(!model.update_min_rtt(Some(Duration::from_millis(100)), now));
+        assert_eq!(Some(Duration::from_millis(100)), model.min_rtt());
+
+        // Larger samples do not change the min_rtt
+        let now = now + Duration::from_secs(1);
+        assert!(!model.update_min_rtt(Some(Duration::from_millis(200)), now));
+        assert_eq!(Some(Duration::from_millis(100)), model.min_rtt());
+
+        // Smaller samples replace the min_rtt
+        assert!(!model.update_min_rtt(Some(Duration::from_millis(50)), now));
+        assert_eq!(Some(Duration::from_millis(50)), model.min_rtt());
+
+        // The ProbeRTT interval expires without a smaller sample
+        let now = now + PROBE_RTT_INTERVAL + Duration::from_millis(1);
+        assert!(model.update_min_rtt(None, now));
+        model.on_probe_rtt_complete(now);
+        assert!(!model.update_min_rtt(None, now));
+
+        // The min_rtt expires after the filter length and is replaced by a larger sample
+        let now = now + MIN_RTT_FILTER_LEN;
+        assert!(model.update_min_rtt(Some(Duration::from_millis(80)), now));
+        assert_eq!(Some(Duration::from_millis(80)), model.min_rtt());
+    }
+
+    #[test]
+    fn ack_aggregation() {
+        let mut model = Model::default();
+        let now = NoopClock.get_time();
+        // 1000 bytes per second
+        let bw = Bandwidth::new(1000, Duration::from_secs(1));
+        let cwnd = 10_000;
+
+        model.update_ack_aggregation(bw, 1000, cwnd, 1, now);
+        assert_eq!(1000, model.extra_acked());
+
+        // 500 bytes are expected after 500ms, so 1500 is 1000 more than expected
+        let now = now + Duration::from_millis(500);
+        model.update_ack_aggregation(bw, 500, cwnd, 1, now);
+        assert_eq!(1000, model.extra_acked());
+
+        // The excess is capped at the congestion window
+        model.update_ack_aggregation(bw, 20_000, cwnd, 1, now);
+        assert_eq!(cwnd as u64, model.extra_acked());
+
+        // The filter forgets samples after two windows of rounds
+        let now = now + Duration::from_secs(60);
+        model.update_ack_aggregation(bw, 100, cwnd, 1 + EXTRA_ACKED_WINDOW_ROUNDS, now);
+        assert_eq!(cwnd as u64, model.extra_acked());
+        model.update_ack_aggregation(bw, 100, cwnd, 1 + 2 * EXTRA_ACKED_WINDOW_ROUNDS, now);
+        assert_eq!(200, model.extra_acked());
+    }
+
+    #[test]
+    fn lower_bound() {
+        let mut model = Model::default();
+        model.update_latest_signals(5000);
+        model.update_latest_signals(2000);
+        assert_eq!(5000, model.inflight_latest());
+
+        // inflight_lo is initialized to cwnd and reduced by BETA
+        model.adapt_lower_bound(10_000);
+        assert_eq!(7000, model.inflight_lo());
+
+        // inflight_lo is not reduced below the latest inflight
+        model.adapt_lower_bound(10_000);
+        assert_eq!(5000, model.inflight_lo());
+
+        model.reset_lower_bound();
+        assert_eq!(u64::MAX, model.inflight_lo());
+    }
+}
@@ -28,7 +28,6 @@ pub(crate) struct Estimator {
 
 impl Estimator {
     /// Returns true if BBR estimates that is has ever fully utilized its available bandwidth
-    #[allow(dead_code)] // TODO: Remove when used
     #[inline]
     pub fn filled_pipe(&self) -> bool {
         self.filled_pipe
@@ -106,7 +105,7 @@ impl Estimator {
         if in_recovery
             && self.in_recovery_last_round
             && rate_sample.lost_bytes
-                > (bbr::LOSS_THRESH * rate_sample.bytes_in_flight).to_integer() as u64
+                > (bbr::LOSS_THRESH * rate_sample.bytes_in_flight as u64).to_integer()
             && self.loss_bursts >= STARTUP_FULL_LOSS_COUNT
         {
             return true;
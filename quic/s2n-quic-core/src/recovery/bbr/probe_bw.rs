@@ -0,0 +1,346 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::time::Timestamp;
+use core::{
+    cmp::{max, min},
+    time::Duration,
+};
+use num_rational::Ratio;
+
+/// The minimum amount of time to wait between bandwidth probes
+const MIN_BW_PROBE_WAIT: Duration = Duration::from_secs(2);
+
+/// The maximum amount of random time added to `MIN_BW_PROBE_WAIT`
+const MAX_BW_PROBE_WAIT_JITTER_MILLIS: u64 = 1000;
+
+/// The maximum number of rounds to wait between bandwidth probes to maintain
+/// fairness with Reno or CUBIC flows
+///
+/// Based on bbr_bw_probe_max_rounds in tcp_bbr2.c
+const MAX_RENO_COEXISTENCE_ROUNDS: u64 = 63;
+
+/// The maximum number of rounds the growth of inflight_hi is doubled in ProbeBW_UP
+const MAX_BW_PROBE_UP_ROUNDS: u8 = 30;
+
+/// The phases of the ProbeBW cycle
+///
+/// The cycle repeats as follows:
+///
+/// ```text
+///   Down -> Cruise -> Refill -> Up -> Down
+/// ```
+///
+/// `Cruise` may be skipped if it is time to probe for bandwidth again
+/// before the data in flight has drained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CyclePhase {
+    /// Decelerating to drain the queue created while probing for bandwidth
+    Down,
+    /// Cruising at the estimated bandwidth, leaving headroom for other flows
+    Cruise,
+    /// Refilling the pipe for one round trip before probing for bandwidth
+    Refill,
+    /// Accelerating to probe for additional bandwidth
+    Up,
+}
+
+impl CyclePhase {
+    /// The dynamic gain factor used to scale the estimated bandwidth to produce a pacing rate
+    #[inline]
+    pub fn pacing_gain(self) -> Ratio<u64> {
+        match self {
+            CyclePhase::Down => Ratio::new_raw(9, 10),
+            CyclePhase::Cruise | CyclePhase::Refill => Ratio::new_raw(1, 1),
+            CyclePhase::Up => Ratio::new_raw(5, 4),
+        }
+    }
+
+    /// The dynamic gain factor used to scale the estimated BDP to produce a congestion window
+    #[inline]
+    pub fn cwnd_gain(self) -> Ratio<u64> {
+        Ratio::new_raw(2, 1)
+    }
+}
+
+/// Tracks which samples are arriving as acknowledgements of the current bandwidth probe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AckPhase {
+    /// Not waiting on any samples from a bandwidth probe
+    Init,
+    /// Acknowledgements of data sent while refilling the pipe
+    Refilling,
+    /// The probe has started, but acknowledgements of the probe have not arrived yet
+    ProbeStarting,
+    /// Acknowledgements of data sent while probing for bandwidth
+    ProbeFeedback,
+    /// The probe has stopped, but acknowledgements of the probe may still arrive
+    ProbeStopping,
+}
+
+/// The state of the ProbeBW cycle
+#[derive(Clone, Debug)]
+pub(crate) struct State {
+    cycle_phase: CyclePhase,
+    ack_phase: AckPhase,
+    /// The time the current cycle phase started
+    cycle_start_timestamp: Timestamp,
+    /// The amount of time to wait in Down and Cruise before probing for bandwidth
+    bw_probe_wait: Duration,
+    /// The number of rounds since bandwidth was last probed
+    rounds_since_bw_probe: u64,
+    /// The number of rounds spent probing for bandwidth in Up, determining
+    /// the rate at which inflight_hi grows
+    bw_probe_up_rounds: u8,
+    /// The number of bytes acknowledged towards the next increase of inflight_hi
+    bw_probe_up_acks: u64,
+    /// The number of bytes that must be acknowledged to increase inflight_hi by one byte
+    probe_up_cnt: u64,
+}
+
+impl State {
+    /// Starts a new ProbeBW cycle in the `Down` phase
+    ///
+    /// `random` is used to randomize the time until bandwidth is probed again, so
+    /// flows sharing a bottleneck do not probe at the same time.
+    #[inline]
+    pub fn new(now: Timestamp, random: u64) -> Self {
+        let jitter = Duration::from_millis(random % MAX_BW_PROBE_WAIT_JITTER_MILLIS);
+
+        Self {
+            cycle_phase: CyclePhase::Down,
+            ack_phase: AckPhase::ProbeStopping,
+            cycle_start_timestamp: now,
+            bw_probe_wait: MIN_BW_PROBE_WAIT + jitter,
+            // Randomly start at either 0 or 1 rounds to vary the Reno coexistence probe time
+            rounds_since_bw_probe: (random >> 32) % 2,
+            bw_probe_up_rounds: 0,
+            bw_probe_up_acks: 0,
+            probe_up_cnt: u64::MAX,
+        }
+    }
+
+    #[inline]
+    pub fn cycle_phase(&self) -> CyclePhase {
+        self.cycle_phase
+    }
+
+    #[inline]
+    pub fn ack_phase(&self) -> AckPhase {
+        self.ack_phase
+    }
+
+    /// Called when a new round starts
+    #[inline]
+    pub fn on_round_start(&mut self) {
+        self.rounds_since_bw_probe = self.rounds_since_bw_probe.saturating_add(1);
+
+        if self.ack_phase == AckPhase::ProbeStarting {
+            // Acknowledgements of the probe have begun arriving
+            self.ack_phase = AckPhase::ProbeFeedback;
+        }
+    }
+
+    /// Called once all acknowledgements of the previous probe have arrived
+    #[inline]
+    pub fn on_probe_stopped(&mut self) {
+        debug_assert_eq!(AckPhase::ProbeStopping, self.ack_phase);
+        self.ack_phase = AckPhase::Init;
+    }
+
+    /// Returns true if `interval` has elapsed since the current phase started
+    #[inline]
+    pub fn has_elapsed_in_phase(&self, interval: Duration, now: Timestamp) -> bool {
+        now > self.cycle_start_timestamp + interval
+    }
+
+    /// Returns true if it is time to start probing for bandwidth
+    ///
+    /// `target_inflight` and `max_datagram_size` are used to estimate how long a Reno flow
+    /// would take to probe for the same amount of bandwidth, so that BBR does not wait longer.
+    #[inline]
+    pub fn is_time_to_probe_bw(
+        &self,
+        target_inflight: u64,
+        max_datagram_size: u16,
+        now: Timestamp,
+    ) -> bool {
+        if self.has_elapsed_in_phase(self.bw_probe_wait, now) {
+            return true;
+        }
+
+        let reno_rounds = target_inflight / max_datagram_size as u64;
+        let rounds = min(reno_rounds, MAX_RENO_COEXISTENCE_ROUNDS);
+        self.rounds_since_bw_probe >= rounds
+    }
+
+    /// Moves to the `Cruise` phase
+    #[inline]
+    pub fn start_cruise(&mut self) {
+        debug_assert_eq!(CyclePhase::Down, self.cycle_phase);
+        self.cycle_phase = CyclePhase::Cruise;
+    }
+
+    /// Moves to the `Refill` phase
+    #[inline]
+    pub fn start_refill(&mut self) {
+        debug_assert!(matches!(
+            self.cycle_phase,
+            CyclePhase::Down | CyclePhase::Cruise
+        ));
+        self.bw_probe_up_rounds = 0;
+        self.bw_probe_up_acks = 0;
+        self.ack_phase = AckPhase::Refilling;
+        self.cycle_phase = CyclePhase::Refill;
+    }
+
+    /// Moves to the `Up` phase
+    #[inline]
+    pub fn start_up(&mut self, cwnd: u32, max_datagram_size: u16, now: Timestamp) {
+        debug_assert_eq!(CyclePhase::Refill, self.cycle_phase);
+        self.ack_phase = AckPhase::ProbeStarting;
+        self.cycle_start_timestamp = now;
+        self.cycle_phase = CyclePhase::Up;
+        self.raise_inflight_hi_slope(cwnd, max_datagram_size);
+    }
+
+    /// Increases inflight_hi as data is acknowledged while probing for bandwidth
+    ///
+    /// Returns the new value for inflight_hi
+    #[inline]
+    pub fn probe_inflight_hi_upward(
+        &mut self,
+        bytes_acknowledged: usize,
+        inflight_hi: u64,
+        cwnd: u32,
+        max_datagram_size: u16,
+        round_start: bool,
+    ) -> u64 {
+        debug_assert_eq!(CyclePhase::Up, self.cycle_phase);
+
+        let mut inflight_hi = inflight_hi;
+        self.bw_probe_up_acks += bytes_acknowledged as u64;
+
+        if self.bw_probe_up_acks >= self.probe_up_cnt {
+            let delta = self.bw_probe_up_acks / self.probe_up_cnt;
+            self.bw_probe_up_acks -= delta * self.probe_up_cnt;
+            inflight_hi = inflight_hi.saturating_add(delta);
+        }
+
+        if round_start {
+            self.raise_inflight_hi_slope(cwnd, max_datagram_size);
+        }
+
+        inflight_hi
+    }
+
+    /// Doubles the amount inflight_hi grows by each round, starting at one packet per round
+    #[inline]
+    fn raise_inflight_hi_slope(&mut self, cwnd: u32, max_datagram_size: u16) {
+        let growth_this_round = (max_datagram_size as u64) << self.bw_probe_up_rounds;
+        self.bw_probe_up_rounds = min(self.bw_probe_up_rounds + 1, MAX_BW_PROBE_UP_ROUNDS);
+        self.probe_up_cnt = max(cwnd as u64 / growth_this_round, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    #[test]
+    fn cycle() {
+        let now = NoopClock.get_time();
+        let mut state = State::new(now, 0);
+        assert_eq!(CyclePhase::Down, state.cycle_phase());
+        assert_eq!(AckPhase::ProbeStopping, state.ack_phase());
+
+        state.on_round_start();
+        state.on_probe_stopped();
+        assert_eq!(AckPhase::Init, state.ack_phase());
+
+        state.start_cruise();
+        assert_eq!(CyclePhase::Cruise, state.cycle_phase());
+
+        state.start_refill();
+        assert_eq!(CyclePhase::Refill, state.cycle_phase());
+        assert_eq!(AckPhase::Refilling, state.ack_phase());
+
+        let now = now + Duration::from_secs(1);
+        state.start_up(12000, 1200, now);
+        assert_eq!(CyclePhase::Up, state.cycle_phase());
+        assert_eq!(AckPhase::ProbeStarting, state.ack_phase());
+        assert!(!state.has_elapsed_in_phase(Duration::from_millis(10), now));
+        assert!(
+            state.has_elapsed_in_phase(Duration::from_millis(10), now + Duration::from_millis(11))
+        );
+
+        state.on_round_start();
+        assert_eq!(AckPhase::ProbeFeedback, state.ack_phase());
+    }
+
+    #[test]
+    fn time_to_probe_bw() {
+        let now = NoopClock.get_time();
+        let random = 500;
+        let state = State::new(now, random);
+        let max_datagram_size = 1200;
+        // A large target inflight means Reno would take many rounds to probe
+        let target_inflight = 1000 * max_datagram_size as u64;
+
+        assert!(!state.is_time_to_probe_bw(target_inflight, max_datagram_size, now));
+
+        // The probe wait is 2 seconds plus the random jitter
+        let now = now + MIN_BW_PROBE_WAIT + Duration::from_millis(random);
+        assert!(!state.is_time_to_probe_bw(target_inflight, max_datagram_size, now));
+        let now = now + Duration::from_millis(1);
+        assert!(state.is_time_to_probe_bw(target_inflight, max_datagram_size, now));
+    }
+
+    #[test]
+    fn reno_coexistence() {
+        let now = NoopClock.get_time();
+        let mut state = State::new(now, 0);
+        let max_datagram_size = 1200;
+        let target_inflight = 10 * max_datagram_size as u64;
+
+        for _ in 0..9 {
+            state.on_round_start();
+            assert!(!state.is_time_to_probe_bw(target_inflight, max_datagram_size, now));
+        }
+
+        // A Reno flow would probe after 10 rounds
+        state.on_round_start();
+        assert!(state.is_time_to_probe_bw(target_inflight, max_datagram_size, now));
+    }
+
+    #[test]
+    fn probe_inflight_hi_upward() {
+        let now = NoopClock.get_time();
+        let mut state = State::new(now, 0);
+        let max_datagram_size = 1000;
+        let cwnd = 10_000;
+        state.start_refill();
+        state.start_up(cwnd, max_datagram_size, now);
+
+        // inflight_hi grows by one packet after a full window is acknowledged
+        let inflight_hi = state.probe_inflight_hi_upward(
+            cwnd as usize,
+            cwnd as u64,
+            cwnd,
+            max_datagram_size,
+            true,
+        );
+        assert_eq!(11_000, inflight_hi);
+
+        // The growth rate doubles each round
+        let inflight_hi = state.probe_inflight_hi_upward(
+            cwnd as usize,
+            inflight_hi,
+            cwnd,
+            max_datagram_size,
+            true,
+        );
+        assert_eq!(13_000, inflight_hi);
+    }
+}
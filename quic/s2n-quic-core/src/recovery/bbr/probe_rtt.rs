@@ -0,0 +1,104 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{recovery::bbr::round, time::Timestamp};
+use core::time::Duration;
+
+/// The minimum amount of time to spend in ProbeRTT once the data in flight has been reduced
+///
+/// Based on BBRProbeRTTDuration in the BBRv2 draft
+const PROBE_RTT_DURATION: Duration = Duration::from_millis(200);
+
+/// The state of ProbeRTT, in which the data in flight is reduced to refresh the min_rtt estimate
+#[derive(Clone, Debug, Default)]
+pub(crate) struct State {
+    /// The time ProbeRTT may complete, set once the data in flight has been reduced
+    probe_rtt_done_timestamp: Option<Timestamp>,
+    /// True if at least one round has elapsed since the data in flight was reduced
+    probe_rtt_round_done: bool,
+}
+
+impl State {
+    /// Called on each acknowledgement while in ProbeRTT
+    ///
+    /// Returns true if ProbeRTT has completed
+    #[inline]
+    pub fn on_ack(
+        &mut self,
+        bytes_in_flight: u32,
+        probe_rtt_cwnd: u64,
+        round_counter: &mut round::Counter,
+        delivered_bytes: u64,
+        now: Timestamp,
+    ) -> bool {
+        match self.probe_rtt_done_timestamp {
+            None if bytes_in_flight as u64 <= probe_rtt_cwnd => {
+                // The data in flight has been reduced, so maintain it for at least
+                // PROBE_RTT_DURATION and one round trip
+                self.probe_rtt_done_timestamp = Some(now + PROBE_RTT_DURATION);
+                self.probe_rtt_round_done = false;
+                round_counter.set_round_end(delivered_bytes);
+                false
+            }
+            None => false,
+            Some(probe_rtt_done_timestamp) => {
+                if round_counter.round_start() {
+                    self.probe_rtt_round_done = true;
+                }
+
+                self.probe_rtt_round_done && now > probe_rtt_done_timestamp
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        recovery::bandwidth::PacketInfo,
+        time::{Clock, NoopClock},
+    };
+
+    #[test]
+    fn probe_rtt() {
+        let mut state = State::default();
+        let mut round_counter = round::Counter::default();
+        let now = NoopClock.get_time();
+        let probe_rtt_cwnd = 4000;
+
+        // Too much data in flight
+        assert!(!state.on_ack(5000, probe_rtt_cwnd, &mut round_counter, 1000, now));
+        assert_eq!(None, state.probe_rtt_done_timestamp);
+
+        // Data in flight is reduced, ending the current round when the data sent so far is delivered
+        assert!(!state.on_ack(4000, probe_rtt_cwnd, &mut round_counter, 1000, now));
+        assert_eq!(
+            Some(now + PROBE_RTT_DURATION),
+            state.probe_rtt_done_timestamp
+        );
+
+        // The duration has elapsed, but not a full round
+        let now = now + PROBE_RTT_DURATION + Duration::from_millis(1);
+        let packet_info = PacketInfo {
+            delivered_bytes: 500,
+            delivered_time: now,
+            lost_bytes: 0,
+            first_sent_time: now,
+            bytes_in_flight: 0,
+            is_app_limited: false,
+        };
+        round_counter.on_ack(packet_info, 2000);
+        assert!(!state.on_ack(4000, probe_rtt_cwnd, &mut round_counter, 2000, now));
+
+        // A new round starts, completing ProbeRTT
+        round_counter.on_ack(
+            PacketInfo {
+                delivered_bytes: 1000,
+                ..packet_info
+            },
+            3000,
+        );
+        assert!(state.on_ack(4000, probe_rtt_cwnd, &mut round_counter, 3000, now));
+    }
+}
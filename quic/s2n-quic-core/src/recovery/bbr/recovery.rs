@@ -46,7 +46,6 @@ pub(crate) enum State {
 
 impl State {
     /// True if packet conservation dynamics should be used to bound cwnd
-    #[allow(dead_code)] // TODO: Remove when used
     #[inline]
     pub fn packet_conservation(&self) -> bool {
         matches!(self, State::Conservation(_, _))
@@ -79,6 +78,19 @@ impl State {
         }
     }
 
+    /// Called when packets are discarded
+    #[inline]
+    pub fn on_packet_discarded(&mut self) {
+        if let State::Conservation(recovery_start_time, FastRetransmission::RequiresTransmission) =
+            self
+        {
+            // If any of the discarded packets were lost, they will no longer be retransmitted
+            // so flip the state back to idle so it is not waiting for a retransmission that
+            // may never come.
+            *self = State::Conservation(*recovery_start_time, FastRetransmission::Idle)
+        }
+    }
+
     /// Called on each ack
     ///
     /// Returns `true` if the ack caused recovery to be exited
@@ -187,6 +199,15 @@ mod tests {
         state.on_packet_sent();
         assert!(!state.requires_fast_retransmission());
 
+        // Discarding packets moves FastRetransmission to Idle
+        let mut discarded_state =
+            State::Conservation(now, FastRetransmission::RequiresTransmission);
+        discarded_state.on_packet_discarded();
+        assert_eq!(
+            discarded_state,
+            State::Conservation(now, FastRetransmission::Idle)
+        );
+
         // Ack received in the same round does not change the state
         assert!(!state.on_ack(false, now));
         assert!(state.packet_conservation());
@@ -0,0 +1,109 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::recovery::bandwidth::PacketInfo;
+
+/// Tracks the progression of round trips, measured in packet-timed round trips
+///
+/// A round trip begins when a packet is sent and ends when that packet is acknowledged.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Counter {
+    /// The number of packet-timed round trips elapsed so far
+    round_count: u64,
+    /// True if the current acknowledgement started a new round
+    round_start: bool,
+    /// The `delivered_bytes` value at which the current round trip ends
+    next_round_delivered: u64,
+}
+
+impl Counter {
+    /// Called for each acknowledgement of one or more packets
+    ///
+    /// `delivered_bytes` is the total amount of data delivered over the lifetime of the path
+    #[inline]
+    pub fn on_ack(&mut self, packet_info: PacketInfo, delivered_bytes: u64) {
+        if packet_info.delivered_bytes >= self.next_round_delivered {
+            // The packet was sent after the previous round ended, so acknowledging
+            // it marks the end of the current round and the start of a new one
+            self.set_round_end(delivered_bytes);
+            self.round_count += 1;
+            self.round_start = true;
+        } else {
+            self.round_start = false;
+        }
+    }
+
+    /// Ends the current round once all data currently sent has been delivered
+    #[inline]
+    pub fn set_round_end(&mut self, delivered_bytes: u64) {
+        self.next_round_delivered = delivered_bytes;
+    }
+
+    /// Returns true if the latest acknowledgement started a new round
+    #[inline]
+    pub fn round_start(&self) -> bool {
+        self.round_start
+    }
+
+    /// Returns the number of rounds that have started
+    #[inline]
+    pub fn round_count(&self) -> u64 {
+        self.round_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    fn packet_info(delivered_bytes: u64) -> PacketInfo {
+        let now = NoopClock.get_time();
+        PacketInfo {
+            delivered_bytes,
+            delivered_time: now,
+            lost_bytes: 0,
+            first_sent_time: now,
+            bytes_in_flight: 0,
+            is_app_limited: false,
+        }
+    }
+
+    #[test]
+    fn round_progression() {
+        let mut counter = Counter::default();
+        assert_eq!(0, counter.round_count());
+        assert!(!counter.round_start());
+
+        // The first packet sent on the path starts the first round
+        counter.on_ack(packet_info(0), 1000);
+        assert!(counter.round_start());
+        assert_eq!(1, counter.round_count());
+
+        // Packets sent before the round ended do not start a new round
+        counter.on_ack(packet_info(500), 2000);
+        assert!(!counter.round_start());
+        assert_eq!(1, counter.round_count());
+
+        // A packet sent after the first round ended starts a new round
+        counter.on_ack(packet_info(1000), 3000);
+        assert!(counter.round_start());
+        assert_eq!(2, counter.round_count());
+    }
+
+    #[test]
+    fn set_round_end() {
+        let mut counter = Counter::default();
+        counter.on_ack(packet_info(0), 1000);
+
+        // Extend the round until all data sent so far is delivered
+        counter.set_round_end(5000);
+
+        counter.on_ack(packet_info(1000), 3000);
+        assert!(!counter.round_start());
+
+        counter.on_ack(packet_info(5000), 6000);
+        assert!(counter.round_start());
+        assert_eq!(2, counter.round_count());
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    packet::number::PacketNumberSpace,
+    time::{Clock, NoopClock},
+};
+use std::collections::VecDeque;
+
+const MAX_DATAGRAM_SIZE: u16 = 1200;
+
+/// A packet in flight over the simulated network path
+struct SentPacket {
+    time_sent: Timestamp,
+    ack_time: Timestamp,
+    packet_info: bandwidth::PacketInfo,
+}
+
+/// A network path with a fixed propagation delay and bottleneck bandwidth, carrying
+/// a flow that always has data to send
+struct Path {
+    cc: BbrCongestionController,
+    rtt_estimator: RttEstimator,
+    now: Timestamp,
+    /// The propagation delay of the path
+    min_rtt: Duration,
+    /// The bandwidth of the bottleneck link
+    bottleneck: Bandwidth,
+    /// The time the bottleneck link finishes transmitting the queued packets
+    bottleneck_available: Timestamp,
+    in_flight: VecDeque<SentPacket>,
+}
+
+impl Path {
+    fn new(min_rtt: Duration, bottleneck: Bandwidth) -> Self {
+        let now = NoopClock.get_time();
+
+        Self {
+            cc: BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0),
+            rtt_estimator: RttEstimator::new(Duration::from_millis(0)),
+            now,
+            min_rtt,
+            bottleneck,
+            bottleneck_available: now,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Advances to the next packet transmission or acknowledgement
+    fn step(&mut self) {
+        let mds = MAX_DATAGRAM_SIZE as u32;
+        let next_send = if self.cc.bytes_in_flight() + mds <= self.cc.congestion_window() {
+            Some(
+                self.cc
+                    .earliest_departure_time()
+                    .map_or(self.now, |time| time.max(self.now)),
+            )
+        } else {
+            None
+        };
+        let next_ack = self.in_flight.front().map(|packet| packet.ack_time);
+
+        match (next_send, next_ack) {
+            (Some(send), Some(ack)) if send <= ack => self.send(send),
+            (Some(send), None) => self.send(send),
+            (_, Some(_)) => self.ack(),
+            (None, None) => panic!("nothing to send or acknowledge"),
+        }
+    }
+
+    fn send(&mut self, now: Timestamp) {
+        self.now = now;
+        let packet_info =
+            self.cc
+                .on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, &self.rtt_estimator);
+
+        // Packets queue at the bottleneck before crossing it at the bottleneck bandwidth
+        let transmission_time = MAX_DATAGRAM_SIZE as u64 / self.bottleneck;
+        self.bottleneck_available = self.bottleneck_available.max(now) + transmission_time;
+
+        self.in_flight.push_back(SentPacket {
+            time_sent: now,
+            ack_time: self.bottleneck_available + self.min_rtt,
+            packet_info,
+        });
+    }
+
+    fn ack(&mut self) {
+        let packet = self.in_flight.pop_front().expect("a packet is in flight");
+        self.now = packet.ack_time;
+
+        self.rtt_estimator.update_rtt(
+            Duration::from_millis(0),
+            self.now - packet.time_sent,
+            self.now,
+            true,
+            PacketNumberSpace::ApplicationData,
+        );
+        self.cc.on_rtt_update(packet.time_sent, &self.rtt_estimator);
+        self.cc.on_ack(
+            packet.time_sent,
+            MAX_DATAGRAM_SIZE as usize,
+            packet.packet_info,
+            &self.rtt_estimator,
+            self.now,
+        );
+    }
+
+    /// Runs the simulation until `f` returns true, panicking if `timeout` elapses first
+    fn run_until<F: Fn(&BbrCongestionController) -> bool>(&mut self, timeout: Duration, f: F) {
+        let end = self.now + timeout;
+
+        while !f(&self.cc) {
+            assert!(self.now < end, "timed out in {:?}", self.cc.state);
+            self.step();
+        }
+    }
+
+    /// Runs the simulation for the given duration
+    fn run_for(&mut self, duration: Duration) {
+        let end = self.now + duration;
+
+        while self.now < end {
+            self.step();
+        }
+    }
+}
+
+#[test]
+fn initial_state() {
+    let cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+
+    assert!(matches!(cc.state, State::Startup));
+    assert_eq!(
+        CubicCongestionController::initial_window(MAX_DATAGRAM_SIZE),
+        cc.congestion_window()
+    );
+    assert_eq!(0, cc.bytes_in_flight());
+    // The initial window is paced over 1ms with the Startup pacing gain
+    assert_eq!(
+        Bandwidth::new(cc.initial_window() as u64, INITIAL_PACING_RTT) * STARTUP_PACING_GAIN,
+        cc.pacing_rate()
+    );
+}
+
+/// 1 MB/s
+fn bottleneck() -> Bandwidth {
+    Bandwidth::new(1_000_000, Duration::from_secs(1))
+}
+
+#[test]
+fn startup_to_probe_bw() {
+    let mut path = Path::new(Duration::from_millis(50), bottleneck());
+
+    path.run_until(Duration::from_secs(2), |cc| {
+        !matches!(cc.state, State::Startup)
+    });
+
+    // Startup exits once the bandwidth estimate plateaus
+    assert!(path.cc.full_pipe_estimator.filled_pipe());
+    assert!(matches!(path.cc.state, State::Drain));
+    let max_bw = path.cc.data_rate_model.max_bw();
+    assert!(max_bw <= bottleneck());
+    assert!(max_bw >= bottleneck() * Ratio::new_raw(9, 10));
+
+    // Drain slows the pacing rate to drain the queue created in Startup
+    assert!(path.cc.pacing_rate() < max_bw);
+
+    path.run_until(Duration::from_secs(1), |cc| {
+        !matches!(cc.state, State::Drain)
+    });
+
+    // Drain exits once the data in flight no longer exceeds the estimated BDP
+    assert!(matches!(path.cc.state, State::ProbeBw(_)));
+    assert!(
+        path.cc.bytes_in_flight() as u64
+            <= path
+                .cc
+                .inflight(path.cc.data_rate_model.max_bw(), Ratio::new_raw(1, 1))
+    );
+}
+
+#[test]
+fn probe_bw_cycle() {
+    let mut path = Path::new(Duration::from_millis(50), bottleneck());
+    path.run_until(Duration::from_secs(2), |cc| {
+        matches!(cc.state, State::ProbeBw(_))
+    });
+
+    let start = path.now;
+    let mut phases = Vec::new();
+
+    // Run for less than the ProbeRTT interval
+    while path.now - start < Duration::from_secs(4) {
+        path.step();
+
+        if let Some(cycle_phase) = path.cc.state.cycle_phase() {
+            if phases.last() != Some(&cycle_phase) {
+                phases.push(cycle_phase);
+            }
+        }
+    }
+
+    // The flow cruises before periodically refilling the pipe and probing for more bandwidth
+    use probe_bw::CyclePhase::*;
+    assert!(
+        phases
+            .windows(4)
+            .any(|window| window == [Cruise, Refill, Up, Down]),
+        "{:?}",
+        phases
+    );
+
+    // The bandwidth estimate tracks the bottleneck
+    let max_bw = path.cc.data_rate_model.max_bw();
+    assert!(max_bw <= bottleneck());
+    assert!(max_bw >= bottleneck() * Ratio::new_raw(9, 10));
+}
+
+#[test]
+fn probe_rtt() {
+    let mut path = Path::new(Duration::from_millis(50), bottleneck());
+    let start = path.now;
+
+    path.run_until(Duration::from_secs(12), |cc| {
+        matches!(cc.state, State::ProbeRtt(_))
+    });
+
+    // ProbeRTT is entered once the min_rtt estimate has not been refreshed for the ProbeRTT
+    // interval, and the congestion window is reduced
+    assert!(path.now - start > Duration::from_secs(5));
+    assert_eq!(path.cc.probe_rtt_cwnd(), path.cc.congestion_window() as u64);
+
+    let probe_rtt_start = path.now;
+    path.run_until(Duration::from_secs(1), |cc| {
+        !matches!(cc.state, State::ProbeRtt(_))
+    });
+
+    // ProbeRTT lasts at least 200ms, and returns to ProbeBW since the pipe is full
+    assert!(path.now - probe_rtt_start > Duration::from_millis(200));
+    assert!(matches!(path.cc.state, State::ProbeBw(_)));
+    // The min_rtt estimate is refreshed
+    assert!(path.cc.data_volume_model.min_rtt().unwrap() < Duration::from_millis(55));
+}
+
+#[test]
+fn on_packet_lost() {
+    let mut cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+    let rtt_estimator = RttEstimator::new(Duration::from_millis(0));
+    let now = NoopClock.get_time();
+
+    let mut packet_info = None;
+    for _ in 0..10 {
+        packet_info = Some(cc.on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, &rtt_estimator));
+    }
+    assert_eq!(12000, cc.bytes_in_flight());
+
+    cc.on_packet_lost(
+        MAX_DATAGRAM_SIZE as u32,
+        packet_info.unwrap(),
+        false,
+        true,
+        now + Duration::from_millis(100),
+    );
+
+    assert_eq!(10800, cc.bytes_in_flight());
+    assert!(cc.recovery_state.in_recovery());
+    assert!(cc.requires_fast_retransmission());
+    assert_eq!(
+        1,
+        cc.bw_estimator.rate_sample().lost_bytes / MAX_DATAGRAM_SIZE as u64
+    );
+    // The congestion window allows one more packet than is in flight
+    assert_eq!(12000, cc.congestion_window());
+    // The congestion window before recovery is saved
+    assert_eq!(12000, cc.prior_cwnd);
+
+    // A second loss in the same recovery period reduces the congestion window
+    cc.on_packet_lost(
+        MAX_DATAGRAM_SIZE as u32,
+        packet_info.unwrap(),
+        false,
+        false,
+        now + Duration::from_millis(100),
+    );
+    assert_eq!(9600, cc.bytes_in_flight());
+    assert_eq!(10800, cc.congestion_window());
+}
+
+#[test]
+fn on_packet_lost_persistent_congestion() {
+    let mut cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+    let rtt_estimator = RttEstimator::new(Duration::from_millis(0));
+    let now = NoopClock.get_time();
+
+    let packet_info = cc.on_packet_sent(now, 2400, &rtt_estimator);
+    cc.on_packet_sent(now, 2400, &rtt_estimator);
+
+    cc.on_packet_lost(2400, packet_info, true, true, now);
+
+    assert_eq!(2400, cc.bytes_in_flight());
+    assert_eq!(2400 + MAX_DATAGRAM_SIZE as u32, cc.congestion_window());
+}
+
+#[test]
+fn inflight_hi_from_lost_packet() {
+    // 2% of the 100_000 bytes in flight before the packet may be lost
+    assert_eq!(
+        100_000 + 1020,
+        BbrCongestionController::inflight_hi_from_lost_packet(1200, 101_200, 2200)
+    );
+
+    // The loss threshold was already exceeded before the packet was lost
+    assert_eq!(
+        100_000,
+        BbrCongestionController::inflight_hi_from_lost_packet(1200, 101_200, 5200)
+    );
+}
+
+#[test]
+fn app_limited() {
+    let mut cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+    let rtt_estimator = RttEstimator::new(Duration::from_millis(0));
+    let now = NoopClock.get_time();
+
+    // Only one packet is sent, leaving most of the congestion window unused
+    let packet_info = cc.on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, &rtt_estimator);
+    cc.on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, &rtt_estimator);
+    assert!(!cc.bw_estimator.is_app_limited());
+
+    let now = now + Duration::from_millis(100);
+    cc.on_ack(
+        now,
+        MAX_DATAGRAM_SIZE as usize,
+        packet_info,
+        &rtt_estimator,
+        now,
+    );
+    assert!(cc.bw_estimator.is_app_limited());
+
+    // Packets sent while app limited are marked as such
+    let packet_info = cc.on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, &rtt_estimator);
+    assert!(packet_info.is_app_limited);
+}
+
+#[test]
+fn on_mtu_update() {
+    let mut cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+    cc.cwnd = 24000;
+
+    // An increase scales the congestion window
+    cc.on_mtu_update(2400);
+    assert_eq!(2400, cc.max_datagram_size);
+    assert_eq!(48000, cc.congestion_window());
+
+    // A decrease resets the congestion window to the initial window
+    cc.on_mtu_update(1200);
+    assert_eq!(
+        CubicCongestionController::initial_window(1200),
+        cc.congestion_window()
+    );
+}
+
+#[test]
+fn on_packet_discarded() {
+    let mut cc = BbrCongestionController::new(MAX_DATAGRAM_SIZE, 0);
+    let rtt_estimator = RttEstimator::new(Duration::from_millis(0));
+    let now = NoopClock.get_time();
+
+    cc.on_packet_sent(now, 1000, &rtt_estimator);
+    cc.on_packet_discarded(1000);
+
+    assert_eq!(0, cc.bytes_in_flight());
+}
+
+#[test]
+fn endpoint_seeds() {
+    use congestion_controller::Endpoint as _;
+
+    let mut endpoint = Endpoint::default();
+    let remote_address = crate::inet::SocketAddress::default();
+    let path_info = congestion_controller::PathInfo::new(&remote_address);
+
+    let first = endpoint.new_congestion_controller(path_info);
+    let path_info = congestion_controller::PathInfo::new(&remote_address);
+    let second = endpoint.new_congestion_controller(path_info);
+
+    // Each congestion controller probes for bandwidth at different times
+    assert_ne!(first.random_state, second.random_state);
+}
@@ -5,14 +5,19 @@ use crate::{
     event::{api::SocketAddress, IntoEvent},
     inet,
     path::MINIMUM_MTU,
-    recovery::RttEstimator,
+    recovery::{bandwidth::Bandwidth, RttEstimator},
     time::Timestamp,
 };
 use core::fmt::Debug;
 
+/// Creates congestion controllers for the paths of an endpoint
 pub trait Endpoint: 'static + Debug + Send {
     type CongestionController: CongestionController;
 
+    /// Returns a new congestion controller for the path described by `path_info`
+    ///
+    /// This is invoked for the initial path of each connection and for each
+    /// new peer address observed during migration.
     fn new_congestion_controller(&mut self, path_info: PathInfo) -> Self::CongestionController;
 }
 
@@ -105,20 +110,35 @@ pub trait CongestionController: 'static + Clone + Send + Debug {
         timestamp: Timestamp,
     );
 
-    /// Invoked from on_packets_lost, but is also directly invoked when
-    /// the Explicit Congestion Notification counter increases.
+    /// Invoked when the peer reports an increase in the Explicit Congestion Notification
+    /// Congestion Experienced (ECN-CE) counter
+    ///
+    /// The transport does not invoke this method for lost packets; implementations
+    /// typically invoke it from `on_packet_lost`.
     fn on_congestion_event(&mut self, event_time: Timestamp);
 
     /// Invoked when the path maximum transmission unit is updated.
     fn on_mtu_update(&mut self, max_data_size: u16);
 
-    /// Invoked for each packet discarded when a packet number space is discarded.
+    /// Invoked for each packet that is no longer in flight but does not indicate congestion
+    ///
+    /// This occurs when a packet number space is discarded, when 0-RTT packets are
+    /// rejected and when an MTU probe is lost.
     fn on_packet_discarded(&mut self, bytes_sent: usize);
 
     /// Returns the earliest time that a packet may be transmitted.
     ///
     /// If the time is in the past or is `None`, the packet should be transmitted immediately.
     fn earliest_departure_time(&self) -> Option<Timestamp>;
+
+    /// Returns the rate at which packets are currently paced, if the congestion
+    /// controller paces transmissions
+    ///
+    /// This is only used to report connection statistics.
+    #[inline]
+    fn pacing_rate(&self, _rtt_estimator: &RttEstimator) -> Option<Bandwidth> {
+        None
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
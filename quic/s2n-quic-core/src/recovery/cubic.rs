@@ -4,6 +4,7 @@
 use crate::{
     counter::Counter,
     recovery::{
+        bandwidth::Bandwidth,
         congestion_controller::{self, CongestionController},
         cubic::{FastRetransmission::*, State::*},
         hybrid_slow_start::HybridSlowStart,
@@ -439,6 +440,12 @@ impl CongestionController for CubicCongestionController {
     fn earliest_departure_time(&self) -> Option<Timestamp> {
         self.pacer.earliest_departure_time()
     }
+
+    #[inline]
+    fn pacing_rate(&self, rtt_estimator: &RttEstimator) -> Option<Bandwidth> {
+        let slow_start = matches!(self.state, State::SlowStart);
+        Pacer::rate(rtt_estimator, self.congestion_window(), slow_start)
+    }
 }
 
 impl CubicCongestionController {
@@ -462,7 +469,7 @@ impl CubicCongestionController {
     //# while limiting the window to the larger of 14,720 bytes or twice the
     //# maximum datagram size.
     #[inline]
-    fn initial_window(max_datagram_size: u16) -> u32 {
+    pub(crate) fn initial_window(max_datagram_size: u16) -> u32 {
         const INITIAL_WINDOW_LIMIT: u32 = 14720;
         min(
             10 * max_datagram_size as u32,
@@ -3,7 +3,7 @@
 
 use crate::{
     counter::{Counter, Saturating},
-    recovery::{RttEstimator, MAX_BURST_PACKETS},
+    recovery::{bandwidth::Bandwidth, RttEstimator, MAX_BURST_PACKETS},
     time::{Duration, Timestamp},
 };
 use core::ops::Div;
@@ -66,20 +66,52 @@ impl Pacer {
             return;
         }
 
+        self.on_burst_packet_sent(now, bytes_sent, max_datagram_size, || {
+            Self::interval(
+                rtt_estimator,
+                congestion_window,
+                max_datagram_size,
+                slow_start,
+            )
+        });
+    }
+
+    /// Called when each packet has been written by a congestion controller that
+    /// determines its own pacing rate
+    #[inline]
+    pub fn on_packet_sent_with_rate(
+        &mut self,
+        now: Timestamp,
+        bytes_sent: usize,
+        rtt_estimator: &RttEstimator,
+        pacing_rate: Bandwidth,
+        max_datagram_size: u16,
+    ) {
+        if rtt_estimator.smoothed_rtt() < MINIMUM_PACING_RTT {
+            return;
+        }
+
+        self.on_burst_packet_sent(now, bytes_sent, max_datagram_size, || {
+            Self::rate_interval(pacing_rate, max_datagram_size)
+        });
+    }
+
+    #[inline]
+    fn on_burst_packet_sent<F: FnOnce() -> Duration>(
+        &mut self,
+        now: Timestamp,
+        bytes_sent: usize,
+        max_datagram_size: u16,
+        interval: F,
+    ) {
         if self.capacity == 0 {
             if let Some(next_packet_departure_time) = self.next_packet_departure_time {
-                let interval = Self::interval(
-                    rtt_estimator,
-                    congestion_window,
-                    max_datagram_size,
-                    slow_start,
-                );
                 self.next_packet_departure_time =
-                    Some((next_packet_departure_time + interval).max(now));
+                    Some((next_packet_departure_time + interval()).max(now));
             } else {
                 self.next_packet_departure_time = Some(now + INITIAL_INTERVAL);
             }
-            self.capacity = Counter::new((MAX_BURST_PACKETS * max_datagram_size) as u32);
+            self.capacity = Counter::new(MAX_BURST_PACKETS as u32 * max_datagram_size as u32);
         }
 
         self.capacity -= bytes_sent as u32;
@@ -92,6 +124,28 @@ impl Pacer {
         self.next_packet_departure_time
     }
 
+    /// Returns the rate at which packets are paced for the given congestion window
+    ///
+    /// Returns `None` if the RTT is too low for pacing to be used.
+    #[inline]
+    pub fn rate(
+        rtt_estimator: &RttEstimator,
+        congestion_window: u32,
+        slow_start: bool,
+    ) -> Option<Bandwidth> {
+        if rtt_estimator.smoothed_rtt() < MINIMUM_PACING_RTT {
+            return None;
+        }
+
+        let n = if slow_start { SLOW_START_N } else { N };
+
+        //= https://www.rfc-editor.org/rfc/rfc9002#section-7.7
+        //# rate = N * congestion_window / smoothed_rtt
+        let bytes = congestion_window as u64 * *n.0.numer() as u64 / *n.0.denom() as u64;
+
+        Some(Bandwidth::new(bytes, rtt_estimator.smoothed_rtt()))
+    }
+
     // Recalculate the interval between bursts of paced packets
     #[inline]
     fn interval(
@@ -106,7 +160,7 @@ impl Pacer {
 
         // `MAX_BURST_PACKETS` is incorporated into the formula since we are trying to spread
         // bursts of packets evenly over time.
-        let packet_size = (MAX_BURST_PACKETS * max_datagram_size) as u32;
+        let packet_size = MAX_BURST_PACKETS as u32 * max_datagram_size as u32;
 
         //= https://www.rfc-editor.org/rfc/rfc9002#section-7.7
         //# A perfectly paced sender spreads packets exactly evenly over time.
@@ -122,6 +176,19 @@ impl Pacer {
         //# interval = ( smoothed_rtt * packet_size / congestion_window ) / N
         (rtt_estimator.smoothed_rtt() * packet_size / congestion_window) / n
     }
+
+    // Calculate the interval between bursts of packets sent at the given pacing rate
+    #[inline]
+    fn rate_interval(pacing_rate: Bandwidth, max_datagram_size: u16) -> Duration {
+        if pacing_rate == Bandwidth::ZERO {
+            // Without a pacing rate, packets are sent without delay
+            return Duration::ZERO;
+        }
+
+        let packet_size = MAX_BURST_PACKETS as u64 * max_datagram_size as u64;
+
+        packet_size / pacing_rate
+    }
 }
 
 #[cfg(test)]
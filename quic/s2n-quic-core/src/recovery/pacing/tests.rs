@@ -5,8 +5,9 @@ use crate::{
     packet::number::PacketNumberSpace,
     path::MINIMUM_MTU,
     recovery::{
+        bandwidth::Bandwidth,
         pacing::{Pacer, INITIAL_INTERVAL, N, SLOW_START_N},
-        RttEstimator,
+        RttEstimator, MAX_BURST_PACKETS,
     },
     time::{Clock, NoopClock, Timestamp},
 };
@@ -216,3 +217,113 @@ fn get_interval(
         }
     }
 }
+
+#[test]
+fn pacing_rate() {
+    let mut pacer = Pacer::default();
+    let now = NoopClock.get_time();
+    let rtt = RttEstimator::new(Duration::default());
+    let burst_size = (MAX_BURST_PACKETS * MINIMUM_MTU) as u64;
+    // One burst every 10ms
+    let pacing_rate = Bandwidth::new(burst_size, Duration::from_millis(10));
+
+    // Send the first burst to move beyond the initial interval
+    for _ in 0..MAX_BURST_PACKETS {
+        pacer.on_packet_sent_with_rate(now, MINIMUM_MTU as usize, &rtt, pacing_rate, MINIMUM_MTU);
+    }
+    assert_eq!(
+        Some(now + INITIAL_INTERVAL),
+        pacer.earliest_departure_time()
+    );
+
+    // The next burst departs one interval later
+    pacer.on_packet_sent_with_rate(now, MINIMUM_MTU as usize, &rtt, pacing_rate, MINIMUM_MTU);
+    assert_eq!(
+        Some(now + INITIAL_INTERVAL + Duration::from_millis(10)),
+        pacer.earliest_departure_time()
+    );
+
+    // Without a pacing rate, bursts are not delayed
+    let mut pacer = Pacer::default();
+    for _ in 0..=MAX_BURST_PACKETS {
+        pacer.on_packet_sent_with_rate(
+            now,
+            MINIMUM_MTU as usize,
+            &rtt,
+            Bandwidth::ZERO,
+            MINIMUM_MTU,
+        );
+    }
+    assert_eq!(
+        Some(now + INITIAL_INTERVAL),
+        pacer.earliest_departure_time()
+    );
+}
+
+#[test]
+fn rate() {
+    let now = NoopClock.get_time();
+    let mut rtt = RttEstimator::new(Duration::default());
+    let cwnd = 12000;
+
+    // N * congestion_window / smoothed_rtt
+    assert_eq!(
+        Some(Bandwidth::new(15000, rtt.smoothed_rtt())),
+        Pacer::rate(&rtt, cwnd, false)
+    );
+    assert_eq!(
+        Some(Bandwidth::new(24000, rtt.smoothed_rtt())),
+        Pacer::rate(&rtt, cwnd, true)
+    );
+
+    // Pacing is not used for low RTTs
+    rtt.update_rtt(
+        Duration::default(),
+        Duration::from_millis(1),
+        now,
+        true,
+        PacketNumberSpace::ApplicationData,
+    );
+    assert_eq!(None, Pacer::rate(&rtt, cwnd, false));
+}
+
+#[test]
+fn jumbo_frames() {
+    let mut pacer = Pacer::default();
+    let now = NoopClock.get_time();
+    let rtt = RttEstimator::new(Duration::default());
+    // a burst of jumbo frames holds more bytes than fit in a `u16`
+    let max_datagram_size = 9000;
+    let cwnd = max_datagram_size as u32 * 100;
+
+    // the whole burst departs together after the initial interval
+    for _ in 0..MAX_BURST_PACKETS {
+        pacer.on_packet_sent(
+            now,
+            max_datagram_size as usize,
+            &rtt,
+            cwnd,
+            max_datagram_size,
+            false,
+        );
+    }
+    assert_eq!(
+        Some(now + INITIAL_INTERVAL),
+        pacer.earliest_departure_time()
+    );
+    assert_eq!(pacer.capacity, 0);
+
+    // bursts are spaced the same as they are for smaller packets in a proportional window
+    assert_eq!(
+        Pacer::interval(&rtt, MINIMUM_MTU as u32 * 100, MINIMUM_MTU, false),
+        Pacer::interval(&rtt, cwnd, max_datagram_size, false)
+    );
+
+    // one burst every 10ms
+    let burst_size = MAX_BURST_PACKETS as u64 * max_datagram_size as u64;
+    let pacing_rate = Bandwidth::new(burst_size, Duration::from_millis(10));
+    assert_eq!(
+        Duration::from_millis(10),
+        Pacer::rate_interval(pacing_rate, max_datagram_size)
+    );
+}
@@ -66,6 +66,13 @@ pub enum StreamError {
     NonEmptyOutput {
         source: &'static panic::Location<'static>,
     },
+    /// The stream was opened while sending 0-RTT data and the server rejected it
+    ///
+    /// The stream is no longer allowed by the limits the server provided for the connection.
+    #[non_exhaustive]
+    ZeroRttRejected {
+        source: &'static panic::Location<'static>,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -98,6 +105,10 @@ impl fmt::Display for StreamError {
                 f,
                 "The stream was provided a non-empty placeholder buffer for receiving data."
             ),
+            Self::ZeroRttRejected { .. } => write!(
+                f,
+                "The stream was opened in 0-RTT data which was rejected by the peer"
+            ),
         }
     }
 }
@@ -115,6 +126,7 @@ impl StreamError {
             StreamError::NonWritable { source } => source,
             StreamError::SendingBlocked { source } => source,
             StreamError::NonEmptyOutput { source } => source,
+            StreamError::ZeroRttRejected { source } => source,
         }
     }
 
@@ -181,6 +193,14 @@ impl StreamError {
         let source = panic::Location::caller();
         StreamError::NonEmptyOutput { source }
     }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn zero_rtt_rejected() -> StreamError {
+        let source = panic::Location::caller();
+        StreamError::ZeroRttRejected { source }
+    }
 }
 
 impl application::error::TryInto for StreamError {
@@ -237,6 +257,7 @@ impl From<StreamError> for std::io::ErrorKind {
             StreamError::NonWritable { .. } => ErrorKind::Other,
             StreamError::SendingBlocked { .. } => ErrorKind::WouldBlock,
             StreamError::NonEmptyOutput { .. } => ErrorKind::InvalidInput,
+            StreamError::ZeroRttRejected { .. } => ErrorKind::ConnectionReset,
         }
     }
 }
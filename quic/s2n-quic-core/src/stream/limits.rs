@@ -17,6 +17,10 @@ pub struct Limits {
     /// is not communicated to the peer, it is only used for limiting
     /// concurrent streams opened locally by the application.
     pub max_open_local_unidirectional_streams: VarInt,
+    /// The size the receive window of a Stream can be grown to by auto-tuning.
+    /// Auto-tuning is disabled for Streams with a receive window of at least
+    /// this size.
+    pub max_receive_window: u32,
 }
 
 impl Default for Limits {
@@ -29,5 +33,6 @@ impl Limits {
     pub const RECOMMENDED: Self = Self {
         max_send_buffer_size: DEFAULT_STREAM_MAX_SEND_BUFFER_SIZE,
         max_open_local_unidirectional_streams: InitialMaxStreamsUni::RECOMMENDED.as_varint(),
+        max_receive_window: 0,
     };
 }
@@ -5,11 +5,13 @@ mod error;
 mod id;
 pub mod limits;
 pub mod ops;
+pub mod priority;
 mod type_;
 
 pub use error::*;
 pub use id::*;
 pub use limits::Limits;
+pub use priority::Priority;
 pub use type_::*;
 
 #[cfg(any(test, feature = "testing"))]
@@ -66,6 +66,12 @@ impl<'a> Request<'a> {
         self
     }
 
+    /// Sets the priority used to schedule the transmission of the tx stream
+    pub fn set_priority(&mut self, priority: stream::Priority) -> &mut Self {
+        self.tx_mut().priority = Some(priority);
+        self
+    }
+
     /// Requests data on the rx stream to be received into the provided slice of chunks
     pub fn receive(&mut self, chunks: &'a mut [bytes::Bytes]) -> &mut Self {
         self.rx_mut().chunks = Some(chunks);
@@ -180,6 +186,9 @@ pub mod tx {
 
         /// Marks the tx stream as finished (e.g. no more data will be sent)
         pub finish: bool,
+
+        /// Optionally changes the transmission priority of the stream
+        pub priority: Option<stream::Priority>,
     }
 
     /// The result of a tx request
@@ -478,6 +487,7 @@ mod tests {
             .finish()
             .flush()
             .reset(application::Error::new(1).unwrap())
+            .set_priority(stream::Priority::new(0, true))
             .receive(&mut receive_chunks)
             .with_watermark(5, 10)
             .stop_sending(application::Error::new(2).unwrap());
@@ -490,6 +500,7 @@ mod tests {
                     finish: true,
                     flush: true,
                     reset: Some(reset),
+                    priority: Some(priority),
                 }),
                 rx: Some(rx::Request {
                     chunks: Some(rx_chunks),
@@ -498,6 +509,7 @@ mod tests {
                     stop_sending: Some(stop_sending)
                 })
             } if reset == application::Error::new(1).unwrap()
+              && priority == stream::Priority::new(0, true)
               && stop_sending == application::Error::new(2).unwrap()
               && tx_chunks.len() == 1
               && rx_chunks.len() == 2
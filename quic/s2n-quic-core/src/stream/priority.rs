@@ -0,0 +1,105 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Controls the order in which streams are scheduled for transmission
+//!
+//! The scheme is modeled after the urgency and incremental parameters defined in
+//! [RFC 9218](https://www.rfc-editor.org/rfc/rfc9218).
+
+/// The number of distinct urgency levels
+pub const URGENCY_LEVELS: usize = 8;
+
+//= https://www.rfc-editor.org/rfc/rfc9218#section-4.1
+//# The urgency (u) parameter value is Integer (see Section 3.3.1 of
+//# [STRUCTURED-FIELDS]), between 0 and 7 inclusive, in descending order
+//# of priority.  The default is 3.
+
+/// The transmission priority of a stream
+///
+/// Streams with a lower urgency value are sent before streams with a higher value. Streams
+/// with the same urgency are either sent one after another (the default), or, if they are
+/// `incremental`, interleaved with each other on each packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Priority {
+    urgency: u8,
+    incremental: bool,
+}
+
+impl Default for Priority {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Priority {
+    /// The most important urgency value
+    pub const MAX_URGENCY: u8 = 0;
+
+    /// The least important urgency value
+    pub const MIN_URGENCY: u8 = (URGENCY_LEVELS - 1) as u8;
+
+    /// The priority assigned to streams that have not been prioritized
+    pub const DEFAULT: Self = Self {
+        urgency: 3,
+        incremental: false,
+    };
+
+    /// Creates a priority with the given urgency and incremental flag
+    ///
+    /// Urgency values greater than [`Self::MIN_URGENCY`] are treated as [`Self::MIN_URGENCY`].
+    #[inline]
+    pub fn new(urgency: u8, incremental: bool) -> Self {
+        Self {
+            urgency: urgency.min(Self::MIN_URGENCY),
+            incremental,
+        }
+    }
+
+    /// Returns a copy of the priority with the given urgency
+    #[inline]
+    pub fn with_urgency(self, urgency: u8) -> Self {
+        Self::new(urgency, self.incremental)
+    }
+
+    /// Returns a copy of the priority with the given incremental flag
+    #[inline]
+    pub fn with_incremental(self, incremental: bool) -> Self {
+        Self::new(self.urgency, incremental)
+    }
+
+    /// Returns the urgency of the stream, where `0` is the most urgent
+    #[inline]
+    pub fn urgency(self) -> u8 {
+        self.urgency
+    }
+
+    /// Returns `true` if the stream shares each packet with other incremental streams of the
+    /// same urgency
+    #[inline]
+    pub fn is_incremental(self) -> bool {
+        self.incremental
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_test() {
+        //= https://www.rfc-editor.org/rfc/rfc9218#section-4.2
+        //# The default value of the incremental parameter is false (0).
+        assert_eq!(Priority::default().urgency(), 3);
+        assert!(!Priority::default().is_incremental());
+    }
+
+    #[test]
+    fn urgency_bounds_test() {
+        for urgency in 0..=u8::MAX {
+            let priority = Priority::new(urgency, false);
+            assert!(priority.urgency() <= Priority::MIN_URGENCY);
+            assert!((priority.urgency() as usize) < URGENCY_LEVELS);
+        }
+    }
+}
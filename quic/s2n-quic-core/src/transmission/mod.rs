@@ -16,6 +16,8 @@ pub struct Outcome {
     pub is_congestion_controlled: bool,
     pub bytes_sent: usize,
     pub bytes_progressed: usize,
+    /// The number of stream and crypto data bytes that were previously declared lost
+    pub bytes_retransmitted: usize,
 }
 
 impl AckElicitable for Outcome {
@@ -30,5 +32,6 @@ impl AddAssign for Outcome {
         self.is_congestion_controlled |= rhs.is_congestion_controlled;
         self.bytes_sent += rhs.bytes_sent;
         self.bytes_progressed += rhs.bytes_progressed;
+        self.bytes_retransmitted += rhs.bytes_retransmitted;
     }
 }
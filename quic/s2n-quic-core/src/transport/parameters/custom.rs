@@ -0,0 +1,265 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transport parameters defined by the application
+//!
+//! Applications can include additional values in the transport parameters exchanged during the
+//! handshake, for example to negotiate protocol extensions without an extra round trip.
+
+use super::{TransportParameterId, TransportParameterLength};
+use bytes::Bytes;
+use s2n_codec::{Encoder, EncoderValue};
+
+#[cfg(feature = "alloc")]
+use super::{ServerTransportParameters, ValidationError};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::convert::TryInto;
+#[cfg(feature = "alloc")]
+use s2n_codec::{decoder_invariant, DecoderBuffer, DecoderError};
+
+/// A transport parameter defined by the application
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomTransportParameter {
+    id: TransportParameterId,
+    value: Bytes,
+}
+
+impl CustomTransportParameter {
+    /// Returns the ID of the transport parameter
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id.as_u64()
+    }
+
+    /// Returns the value of the transport parameter
+    #[inline]
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+}
+
+impl EncoderValue for CustomTransportParameter {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        let value: &[u8] = &self.value;
+        buffer.encode(&self.id);
+        buffer.encode_with_len_prefix::<TransportParameterLength, _>(&value);
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// The transport parameters defined by the application for a connection
+///
+/// # Examples
+///
+/// ```rust
+/// use s2n_quic_core::transport::parameters::CustomTransportParameters;
+///
+/// let parameters = CustomTransportParameters::default()
+///     .with(0x7a3c, &b"feature-x"[..])
+///     .unwrap();
+///
+/// assert_eq!(parameters.get(0x7a3c).map(|v| &v[..]), Some(&b"feature-x"[..]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CustomTransportParameters {
+    parameters: Vec<CustomTransportParameter>,
+}
+
+#[cfg(feature = "alloc")]
+impl CustomTransportParameters {
+    /// Adds a transport parameter with the given ID and value
+    ///
+    /// The ID must not be used by a transport parameter that s2n-quic implements, nor be one of
+    /// the reserved IDs used to exercise the ignoring of unknown parameters. Each ID can only be
+    /// added once.
+    pub fn with<V: Into<Bytes>>(mut self, id: u64, value: V) -> Result<Self, ValidationError> {
+        let id: TransportParameterId = id.try_into()?;
+
+        if ServerTransportParameters::is_known_id(id) {
+            return Err(ValidationError(
+                "transport parameter ID is reserved by the implementation",
+            ));
+        }
+
+        if is_reserved(id) {
+            return Err(ValidationError(
+                "transport parameter ID is reserved for greasing",
+            ));
+        }
+
+        if self.get(id.as_u64()).is_some() {
+            return Err(ValidationError("duplicate transport parameter ID"));
+        }
+
+        self.parameters.push(CustomTransportParameter {
+            id,
+            value: value.into(),
+        });
+
+        Ok(self)
+    }
+
+    /// Returns the value of the transport parameter with the given ID
+    #[inline]
+    pub fn get(&self, id: u64) -> Option<&Bytes> {
+        self.iter()
+            .find(|parameter| parameter.id() == id)
+            .map(CustomTransportParameter::value)
+    }
+
+    /// Returns an iterator over the transport parameters
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, CustomTransportParameter> {
+        self.parameters.iter()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[CustomTransportParameter] {
+        &self.parameters
+    }
+
+    /// Decodes a transport parameter that isn't implemented by s2n-quic
+    pub(super) fn decode_parameter<'a>(
+        &mut self,
+        id: TransportParameterId,
+        buffer: DecoderBuffer<'a>,
+    ) -> Result<DecoderBuffer<'a>, DecoderError> {
+        let (value, buffer) = buffer.decode_slice_with_len_prefix::<TransportParameterLength>()?;
+
+        // Reserved parameters carry no meaning and are only sent to ensure peers ignore
+        // unknown parameters, so there is no reason to retain them.
+        if is_reserved(id) {
+            return Ok(buffer);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4
+        //# An endpoint MUST NOT send a parameter more than once in a given
+        //# transport parameters extension.
+        decoder_invariant!(
+            self.get(id.as_u64()).is_none(),
+            "duplicate value for custom transport parameter"
+        );
+
+        self.parameters.push(CustomTransportParameter {
+            id,
+            value: Bytes::copy_from_slice(value.into_less_safe_slice()),
+        });
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl EncoderValue for CustomTransportParameters {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        for parameter in self.iter() {
+            buffer.encode(parameter);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a CustomTransportParameters {
+    type Item = &'a CustomTransportParameter;
+    type IntoIter = core::slice::Iter<'a, CustomTransportParameter>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "alloc")]
+//= https://www.rfc-editor.org/rfc/rfc9000#section-18.1
+//# Transport parameters with an identifier of the form "31 * N + 27" for
+//# integer values of N are reserved to exercise the requirement that
+//# unknown transport parameters be ignored.
+#[inline]
+fn is_reserved(id: TransportParameterId) -> bool {
+    id.as_u64() >= 27 && (id.as_u64() - 27) % 31 == 0
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use s2n_codec::EncoderBuffer;
+
+    #[test]
+    fn validation_test() {
+        let parameters = CustomTransportParameters::default();
+
+        // IDs used by s2n-quic
+        assert!(parameters.clone().with(0x01, &b""[..]).is_err());
+        assert!(parameters.clone().with(0x20, &b""[..]).is_err());
+        // reserved IDs
+        assert!(parameters.clone().with(27, &b""[..]).is_err());
+        assert!(parameters.clone().with(31 * 1000 + 27, &b""[..]).is_err());
+        // IDs that can't be encoded
+        assert!(parameters.clone().with(u64::MAX, &b""[..]).is_err());
+
+        let parameters = parameters.with(0x7a3c, &b"a"[..]).unwrap();
+        assert!(parameters.clone().with(0x7a3c, &b"b"[..]).is_err());
+
+        let parameters = parameters.with(0x7a3d, &b"b"[..]).unwrap();
+        assert_eq!(parameters.get(0x7a3c).unwrap(), &b"a"[..]);
+        assert_eq!(parameters.get(0x7a3d).unwrap(), &b"b"[..]);
+        assert_eq!(parameters.get(0x7a3e), None);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let parameters = CustomTransportParameters::default()
+            .with(0x7a3c, &b"feature-x"[..])
+            .unwrap()
+            .with(0x7a3d, &b""[..])
+            .unwrap();
+
+        let mut buffer = [0u8; 64];
+        let mut encoder = EncoderBuffer::new(&mut buffer);
+        encoder.encode(&parameters);
+        // include a reserved parameter, which is ignored
+        encoder.encode(&CustomTransportParameter {
+            id: TransportParameterId::from_u8(27),
+            value: Bytes::from_static(b"grease"),
+        });
+        let len = encoder.len();
+
+        let mut decoded = CustomTransportParameters::default();
+        let mut buffer = DecoderBuffer::new(&buffer[..len]);
+        while !buffer.is_empty() {
+            let (id, remaining) = buffer.decode::<TransportParameterId>().unwrap();
+            buffer = decoded.decode_parameter(id, remaining).unwrap();
+        }
+
+        assert_eq!(decoded, parameters);
+    }
+
+    #[test]
+    fn duplicate_decode_test() {
+        let parameter = CustomTransportParameter {
+            id: 0x7a3cu64.try_into().unwrap(),
+            value: Bytes::from_static(b"a"),
+        };
+
+        let mut buffer = [0u8; 16];
+        let mut encoder = EncoderBuffer::new(&mut buffer);
+        encoder.encode(&parameter);
+        encoder.encode(&parameter);
+        let len = encoder.len();
+
+        let mut decoded = CustomTransportParameters::default();
+        let (id, buffer) = DecoderBuffer::new(&buffer[..len])
+            .decode::<TransportParameterId>()
+            .unwrap();
+        let buffer = decoded.decode_parameter(id, buffer).unwrap();
+        let (id, buffer) = buffer.decode::<TransportParameterId>().unwrap();
+        assert!(decoded.decode_parameter(id, buffer).is_err());
+    }
+}
@@ -14,6 +14,12 @@ use core::{
     mem::size_of,
     time::Duration,
 };
+mod custom;
+
+pub use custom::CustomTransportParameter;
+#[cfg(feature = "alloc")]
+pub use custom::CustomTransportParameters;
+
 use s2n_codec::{
     decoder_invariant, decoder_value, DecoderBuffer, DecoderBufferMut, DecoderBufferMutResult,
     DecoderBufferResult, DecoderError, DecoderValue, DecoderValueMut, Encoder, EncoderValue,
@@ -223,6 +229,9 @@ impl core::fmt::Display for ValidationError {
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
 impl From<DecoderError> for ValidationError {
     fn from(error: DecoderError) -> Self {
         ValidationError(error.into())
@@ -467,6 +476,9 @@ impl MaxIdleTimeout {
     /// Defaults to 30 seconds
     pub const RECOMMENDED: Self = Self(VarInt::from_u32(30_000));
 
+    /// The value of an omitted parameter, which disables the idle timeout
+    pub const DISABLED: Self = Self(VarInt::from_u8(0));
+
     /// Loads a value setting from a peer's transport parameter
     pub fn load_peer(&mut self, peer: &Self) {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.1
@@ -786,7 +798,7 @@ impl TransportParameterValidator for InitialMaxStreamsUni {
 //# endpoint does not support DATAGRAM frames.  A value greater than 0
 //# indicates that the endpoint supports the DATAGRAM frame types and is
 //# willing to receive such frames on this connection.
-transport_parameter!(MaxDatagramFrameSize(VarInt), 0x20, VarInt::from_u16(0));
+varint_transport_parameter!(MaxDatagramFrameSize, 0x20, VarInt::from_u16(0));
 
 //= https://www.rfc-editor.org/rfc/rfc9221#section-3
 //# For most uses of DATAGRAM frames, it is RECOMMENDED to send a value of
@@ -794,6 +806,9 @@ transport_parameter!(MaxDatagramFrameSize(VarInt), 0x20, VarInt::from_u16(0));
 //# this endpoint will accept any DATAGRAM frame that fits inside a QUIC packet.
 impl MaxDatagramFrameSize {
     pub const RECOMMENDED: Self = Self(VarInt::from_u16(65535));
+
+    /// Indicates the endpoint does not support receiving DATAGRAM frames
+    pub const DISABLED: Self = Self(VarInt::from_u16(0));
 }
 
 impl TransportParameterValidator for MaxDatagramFrameSize {
@@ -968,6 +983,16 @@ impl TransportParameterValidator for PreferredAddress {
             !self.is_unspecified(),
             "at least one address needs to be specified"
         );
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
+        //# Similarly, a server MUST NOT include a zero-
+        //# length connection ID in this transport parameter.  A client MUST
+        //# treat a violation of these requirements as a connection error of
+        //# type TRANSPORT_PARAMETER_ERROR.
+        decoder_invariant!(
+            !self.connection_id.is_empty(),
+            "the connection ID must not be zero-length"
+        );
         Ok(self)
     }
 }
@@ -1071,6 +1096,161 @@ impl From<connection::id::LocalId> for InitialSourceConnectionId {
 connection_id_parameter!(RetrySourceConnectionId, LocalId, 0x10);
 optional_transport_parameter!(RetrySourceConnectionId);
 
+// See <https://www.rfc-editor.org/rfc/rfc9368#section-3>
+//
+// Version Information {
+//   Chosen Version (32),
+//   Available Versions (32) ...,
+// }
+
+optional_transport_parameter!(VersionInformation);
+
+/// The versions exchanged by endpoints for compatible version negotiation
+///
+/// Reserved versions, which peers include to exercise version negotiation, are not retained.
+/// Any available versions beyond [`VersionInformation::MAX_AVAILABLE_VERSIONS`] are ignored.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct VersionInformation {
+    chosen_version: u32,
+    available_versions: [u32; VersionInformation::MAX_AVAILABLE_VERSIONS],
+    available_versions_len: u8,
+}
+
+impl VersionInformation {
+    /// The maximum number of available versions that are retained
+    pub const MAX_AVAILABLE_VERSIONS: usize = 8;
+
+    /// Creates version information for the `chosen_version` and the `available_versions`, in
+    /// descending order of preference
+    ///
+    /// Returns `None` if any of the versions are zero or too many versions are provided.
+    pub fn new(chosen_version: u32, available_versions: &[u32]) -> Option<Self> {
+        if available_versions.len() > Self::MAX_AVAILABLE_VERSIONS {
+            return None;
+        }
+
+        let mut value = Self {
+            chosen_version,
+            available_versions: [0; Self::MAX_AVAILABLE_VERSIONS],
+            available_versions_len: available_versions.len() as u8,
+        };
+        value.available_versions[..available_versions.len()].copy_from_slice(available_versions);
+
+        value.validate().ok()
+    }
+
+    /// The version the sender selected for the connection
+    #[inline]
+    pub fn chosen_version(&self) -> u32 {
+        self.chosen_version
+    }
+
+    /// The versions the sender is willing to use, in descending order of preference
+    #[inline]
+    pub fn available_versions(&self) -> &[u32] {
+        &self.available_versions[..self.available_versions_len as usize]
+    }
+
+    /// Returns `true` if the version follows the `0x?a?a?a?a` pattern reserved for exercising
+    /// version negotiation
+    #[inline]
+    fn is_reserved(version: u32) -> bool {
+        version & 0x0f0f_0f0f == 0x0a0a_0a0a
+    }
+}
+
+impl core::fmt::Debug for VersionInformation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VersionInformation")
+            .field("chosen_version", &self.chosen_version)
+            .field("available_versions", &self.available_versions())
+            .finish()
+    }
+}
+
+impl TransportParameter for VersionInformation {
+    type CodecValue = Self;
+
+    const ID: TransportParameterId = TransportParameterId::from_u8(0x11);
+
+    fn from_codec_value(value: Self) -> Self {
+        value
+    }
+
+    fn try_into_codec_value(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn default_value() -> Self {
+        unimplemented!(
+            "VersionInformation is an optional transport parameter, so the default is None"
+        )
+    }
+}
+
+impl TransportParameterValidator for VersionInformation {
+    fn validate(self) -> Result<Self, DecoderError> {
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-3>
+        //
+        // If an endpoint receives a Chosen Version equal to zero, or any Available Version
+        // equal to zero, it MUST treat it as a parsing failure
+        decoder_invariant!(
+            self.chosen_version != 0,
+            "the chosen version must not be zero"
+        );
+        decoder_invariant!(
+            !self.available_versions().contains(&0),
+            "the available versions must not contain zero"
+        );
+        Ok(self)
+    }
+}
+
+decoder_value!(
+    impl<'a> VersionInformation {
+        fn decode(buffer: Buffer) -> Result<Self> {
+            let (chosen_version, mut buffer) = buffer.decode::<u32>()?;
+
+            decoder_invariant!(
+                buffer.len() % size_of::<u32>() == 0,
+                "the available versions must be a list of 32-bit versions"
+            );
+
+            let mut value = Self {
+                chosen_version,
+                available_versions: [0; Self::MAX_AVAILABLE_VERSIONS],
+                available_versions_len: 0,
+            };
+
+            while !buffer.is_empty() {
+                let (version, remaining) = buffer.decode::<u32>()?;
+                buffer = remaining;
+
+                decoder_invariant!(version != 0, "the available versions must not contain zero");
+
+                let len = value.available_versions_len as usize;
+                if Self::is_reserved(version) || len == Self::MAX_AVAILABLE_VERSIONS {
+                    continue;
+                }
+
+                value.available_versions[len] = version;
+                value.available_versions_len += 1;
+            }
+
+            Ok((value, buffer))
+        }
+    }
+);
+
+impl EncoderValue for VersionInformation {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(&self.chosen_version);
+        for version in self.available_versions() {
+            buffer.encode(version);
+        }
+    }
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
 //# If present, transport parameters that set initial per-stream flow
 //# control limits (initial_max_stream_data_bidi_local,
@@ -1236,6 +1416,10 @@ impl<'a> IntoEvent<event::builder::TransportParameters<'a>> for &'a ServerTransp
             initial_max_streams_bidi: self.initial_max_streams_bidi.into_event(),
             initial_max_streams_uni: self.initial_max_streams_uni.into_event(),
             max_datagram_frame_size: self.max_datagram_frame_size.into_event(),
+            #[cfg(feature = "alloc")]
+            custom_parameters: self.custom_parameters.as_slice(),
+            #[cfg(not(feature = "alloc"))]
+            custom_parameters: &[],
         }
     }
 }
@@ -1267,6 +1451,10 @@ impl<'a> IntoEvent<event::builder::TransportParameters<'a>> for &'a ClientTransp
             initial_max_streams_bidi: self.initial_max_streams_bidi.into_event(),
             initial_max_streams_uni: self.initial_max_streams_uni.into_event(),
             max_datagram_frame_size: self.max_datagram_frame_size.into_event(),
+            #[cfg(feature = "alloc")]
+            custom_parameters: self.custom_parameters.as_slice(),
+            #[cfg(not(feature = "alloc"))]
+            custom_parameters: &[],
         }
     }
 }
@@ -1277,11 +1465,14 @@ macro_rules! impl_transport_parameters {
         $($server_param:ident),* $(,)? >
         { $($field:ident : $field_ty:ty),* $(,)? }
     ) => {
-        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[derive(Clone, Debug, PartialEq)]
         pub struct TransportParameters<$($server_param),*> {
             $(
-                pub $field: $field_ty
-            ),*
+                pub $field: $field_ty,
+            )*
+            /// Transport parameters defined by the application
+            #[cfg(feature = "alloc")]
+            pub custom_parameters: CustomTransportParameters,
         }
 
         impl<'a, $($server_param),*> Default for TransportParameters<$($server_param),*>
@@ -1295,6 +1486,8 @@ macro_rules! impl_transport_parameters {
                     $(
                         $field: TransportParameter::default_value(),
                     )*
+                    #[cfg(feature = "alloc")]
+                    custom_parameters: CustomTransportParameters::default(),
                 }
             }
         }
@@ -1310,6 +1503,22 @@ macro_rules! impl_transport_parameters {
                 $(
                     buffer.encode(&TransportParameterCodec(&self.$field));
                 )*
+                #[cfg(feature = "alloc")]
+                buffer.encode(&self.custom_parameters);
+            }
+        }
+
+        impl<$($server_param),*> TransportParameters<$($server_param),*>
+        where
+            $(
+                $server_param: TransportParameter,
+            )*
+        {
+            /// Returns `true` if the ID belongs to a transport parameter implemented by s2n-quic
+            pub(crate) fn is_known_id(id: TransportParameterId) -> bool {
+                $(
+                    id == <$field_ty>::ID ||
+                )* false
             }
         }
 
@@ -1372,10 +1581,21 @@ macro_rules! impl_transport_parameters {
                             //# An endpoint MUST ignore transport parameters that it does
                             //# not support.
 
+                            // retain the transport parameters with unknown tags for the
+                            // application
+                            #[cfg(feature = "alloc")]
+                            let inner_buffer = parameters
+                                .custom_parameters
+                                .decode_parameter(tag, inner_buffer)?;
+
                             // ignore transport parameters with unknown tags
                             // We need to skip the actual content of the parameters, which
                             // consists of a VarInt length field plus payload
-                            inner_buffer.skip_with_len_prefix::<TransportParameterLength>()?
+                            #[cfg(not(feature = "alloc"))]
+                            let inner_buffer =
+                                inner_buffer.skip_with_len_prefix::<TransportParameterLength>()?;
+
+                            inner_buffer
                         }
                     }
                 }
@@ -1411,6 +1631,7 @@ impl_transport_parameters!(
         preferred_address: PreferredAddress,
         initial_source_connection_id: Option<InitialSourceConnectionId>,
         retry_source_connection_id: RetrySourceConnectionId,
+        version_information: Option<VersionInformation>,
     }
 );
 
@@ -1452,6 +1673,7 @@ impl<
         );
         load!(max_ack_delay, max_ack_delay);
         load!(max_active_connection_ids, active_connection_id_limit);
+        load!(max_datagram_frame_size, max_datagram_frame_size);
     }
 }
 
@@ -1521,6 +1743,10 @@ mod snapshot_tests {
             }),
             initial_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
             retry_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
+            version_information: VersionInformation::new(0x6b33_43cf, &[0x6b33_43cf, 0x1]),
+            custom_parameters: CustomTransportParameters::default()
+                .with(0x7a3c, &b"server"[..])
+                .unwrap(),
         }
     }
 
@@ -1558,6 +1784,10 @@ mod snapshot_tests {
             preferred_address: Default::default(),
             initial_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
             retry_source_connection_id: Default::default(),
+            version_information: VersionInformation::new(0x1, &[0x6b33_43cf, 0x1]),
+            custom_parameters: CustomTransportParameters::default()
+                .with(0x7a3c, &b"client"[..])
+                .unwrap(),
         }
     }
 
@@ -1600,4 +1830,56 @@ mod snapshot_tests {
         assert_eq!(value, decoded_params);
         assert_eq!(0, remaining.len());
     }
+
+    fn decode_version_information(bytes: &[u8]) -> Result<VersionInformation, DecoderError> {
+        let (value, remaining) = DecoderBuffer::new(bytes).decode::<VersionInformation>()?;
+        assert!(remaining.is_empty());
+        value.validate()
+    }
+
+    #[test]
+    fn version_information_test() {
+        let value = decode_version_information(&[
+            0, 0, 0, 1, // chosen version
+            0x6b, 0x33, 0x43, 0xcf, // available versions
+            0x1a, 0x2a, 0x3a, 0x4a, //
+            0, 0, 0, 1, //
+        ])
+        .unwrap();
+
+        assert_eq!(value.chosen_version(), 1);
+        // the reserved version is not retained
+        assert_eq!(value.available_versions(), &[0x6b33_43cf, 1]);
+
+        assert!(
+            decode_version_information(&[0, 0, 0, 0, 0, 0, 0, 1]).is_err(),
+            "the chosen version must not be zero"
+        );
+        assert!(
+            decode_version_information(&[0, 0, 0, 1, 0, 0, 0, 0]).is_err(),
+            "the available versions must not contain zero"
+        );
+        assert!(
+            decode_version_information(&[0, 0, 0, 1, 0, 0]).is_err(),
+            "the available versions must be 32-bit values"
+        );
+
+        // versions beyond the capacity are ignored
+        let mut bytes = vec![0, 0, 0, 1];
+        for version in 1..=(VersionInformation::MAX_AVAILABLE_VERSIONS as u32 + 2) {
+            bytes.extend_from_slice(&version.to_be_bytes());
+        }
+        let value = decode_version_information(&bytes).unwrap();
+        assert_eq!(
+            value.available_versions().len(),
+            VersionInformation::MAX_AVAILABLE_VERSIONS
+        );
+
+        assert!(VersionInformation::new(0, &[1]).is_none());
+        assert!(VersionInformation::new(1, &[0]).is_none());
+        assert!(
+            VersionInformation::new(1, &[1; VersionInformation::MAX_AVAILABLE_VERSIONS + 1])
+                .is_none()
+        );
+    }
 }
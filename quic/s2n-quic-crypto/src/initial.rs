@@ -7,9 +7,10 @@ use s2n_quic_core::{
     crypto::{
         self,
         label::{CLIENT_IN, SERVER_IN},
-        CryptoError, Key, INITIAL_SALT,
+        CryptoError, Key, INITIAL_SALT, INITIAL_SALT_V2,
     },
     endpoint,
+    packet::long::VERSION_2,
 };
 
 header_key!(InitialHeaderKey);
@@ -25,12 +26,23 @@ pub struct InitialKey {
 lazy_static::lazy_static! {
     /// Compute the Initial salt once, as the seed is constant
     static ref INITIAL_SIGNING_KEY: hkdf::Salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &INITIAL_SALT);
+    static ref INITIAL_SIGNING_KEY_V2: hkdf::Salt =
+        hkdf::Salt::new(hkdf::HKDF_SHA256, &INITIAL_SALT_V2);
 }
 
 impl InitialKey {
-    fn new(endpoint: endpoint::Type, connection_id: &[u8]) -> (Self, InitialHeaderKey) {
-        let initial_secret = INITIAL_SIGNING_KEY.extract(connection_id);
-        let digest = INITIAL_SIGNING_KEY.algorithm();
+    fn new(
+        endpoint: endpoint::Type,
+        version: u32,
+        connection_id: &[u8],
+    ) -> (Self, InitialHeaderKey) {
+        let signing_key: &hkdf::Salt = if version == VERSION_2 {
+            &INITIAL_SIGNING_KEY_V2
+        } else {
+            &INITIAL_SIGNING_KEY
+        };
+        let initial_secret = signing_key.extract(connection_id);
+        let digest = signing_key.algorithm();
 
         let client_secret = initial_secret
             .expand(&[&CLIENT_IN], digest)
@@ -44,12 +56,12 @@ impl InitialKey {
 
         let (sealer, opener) = match endpoint {
             endpoint::Type::Client => (
-                CipherSuite::new(client_secret),
-                CipherSuite::new(server_secret),
+                CipherSuite::new(version, client_secret),
+                CipherSuite::new(version, server_secret),
             ),
             endpoint::Type::Server => (
-                CipherSuite::new(server_secret),
-                CipherSuite::new(client_secret),
+                CipherSuite::new(version, server_secret),
+                CipherSuite::new(version, client_secret),
             ),
         };
 
@@ -71,12 +83,12 @@ impl InitialKey {
 impl crypto::InitialKey for InitialKey {
     type HeaderKey = InitialHeaderKey;
 
-    fn new_server(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Server, connection_id)
+    fn new_server(version: u32, connection_id: &[u8]) -> (Self, Self::HeaderKey) {
+        Self::new(endpoint::Type::Server, version, connection_id)
     }
 
-    fn new_client(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Client, connection_id)
+    fn new_client(version: u32, connection_id: &[u8]) -> (Self, Self::HeaderKey) {
+        Self::new(endpoint::Type::Client, version, connection_id)
     }
 }
 
@@ -130,21 +142,23 @@ mod tests {
         connection::id::ConnectionInfo,
         crypto::{
             initial::{
-                EXAMPLE_CLIENT_INITIAL_PAYLOAD, EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET,
+                v2, EXAMPLE_CLIENT_INITIAL_PAYLOAD, EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET,
                 EXAMPLE_DCID, EXAMPLE_SERVER_INITIAL_PAYLOAD,
                 EXAMPLE_SERVER_INITIAL_PROTECTED_PACKET,
             },
             InitialKey as _,
         },
         inet::SocketAddress,
-        packet::{encoding::PacketEncoder, initial::CleartextInitial, ProtectedPacket},
+        packet::{
+            encoding::PacketEncoder, initial::CleartextInitial, long::VERSION_1, ProtectedPacket,
+        },
     };
 
     #[test]
     fn rfc_example_server_test() {
         test_round_trip(
-            &InitialKey::new_client(&EXAMPLE_DCID),
-            &InitialKey::new_server(&EXAMPLE_DCID),
+            &InitialKey::new_client(VERSION_1, &EXAMPLE_DCID),
+            &InitialKey::new_server(VERSION_1, &EXAMPLE_DCID),
             &EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET,
             &EXAMPLE_CLIENT_INITIAL_PAYLOAD,
         );
@@ -153,13 +167,93 @@ mod tests {
     #[test]
     fn rfc_example_client_test() {
         test_round_trip(
-            &InitialKey::new_server(&EXAMPLE_DCID),
-            &InitialKey::new_client(&EXAMPLE_DCID),
+            &InitialKey::new_server(VERSION_1, &EXAMPLE_DCID),
+            &InitialKey::new_client(VERSION_1, &EXAMPLE_DCID),
             &EXAMPLE_SERVER_INITIAL_PROTECTED_PACKET,
             &EXAMPLE_SERVER_INITIAL_PAYLOAD,
         );
     }
 
+    #[test]
+    fn rfc_9369_example_server_test() {
+        test_round_trip(
+            &InitialKey::new_client(VERSION_2, &EXAMPLE_DCID),
+            &InitialKey::new_server(VERSION_2, &EXAMPLE_DCID),
+            &v2::EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET,
+            &EXAMPLE_CLIENT_INITIAL_PAYLOAD,
+        );
+    }
+
+    #[test]
+    fn rfc_9369_example_client_test() {
+        test_round_trip(
+            &InitialKey::new_server(VERSION_2, &EXAMPLE_DCID),
+            &InitialKey::new_client(VERSION_2, &EXAMPLE_DCID),
+            &v2::EXAMPLE_SERVER_INITIAL_PROTECTED_PACKET,
+            &EXAMPLE_SERVER_INITIAL_PAYLOAD,
+        );
+    }
+
+    #[test]
+    fn version_2_test() {
+        let (v1_key, v1_header_key) = InitialKey::new_server(VERSION_1, &EXAMPLE_DCID);
+        let (client_key, client_header_key) = InitialKey::new_client(VERSION_2, &EXAMPLE_DCID);
+        let (server_key, server_header_key) = InitialKey::new_server(VERSION_2, &EXAMPLE_DCID);
+
+        // seal the example client payload as a version 2 packet
+        let sealed_packet = decrypt(
+            &v1_key,
+            &v1_header_key,
+            EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET.to_vec(),
+            &EXAMPLE_CLIENT_INITIAL_PAYLOAD,
+            |mut packet| {
+                packet.version = VERSION_2;
+
+                let mut output_buffer = vec![0; EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET.len()];
+                packet
+                    .encode_packet(
+                        &client_key,
+                        &client_header_key,
+                        Default::default(),
+                        None,
+                        EncoderBuffer::new(&mut output_buffer),
+                    )
+                    .unwrap();
+                output_buffer
+            },
+        );
+
+        // version 2 Initial packets use a long packet type of 0b01
+        assert_eq!(sealed_packet[0] & 0x30, 0x10);
+
+        // the version 1 keys should not be able to open the packet
+        let mut protected_packet = sealed_packet.clone();
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let decoder = DecoderBufferMut::new(&mut protected_packet);
+        let packet = match ProtectedPacket::decode(decoder, &connection_info, &20)
+            .unwrap()
+            .0
+        {
+            ProtectedPacket::Initial(initial) => initial,
+            _ => panic!("expected initial packet type"),
+        };
+        assert!(packet
+            .unprotect(&v1_header_key, Default::default())
+            .and_then(|packet| packet.decrypt(&v1_key))
+            .is_err());
+
+        decrypt(
+            &server_key,
+            &server_header_key,
+            sealed_packet,
+            &EXAMPLE_CLIENT_INITIAL_PAYLOAD,
+            |packet| {
+                assert_eq!(packet.version, VERSION_2);
+            },
+        );
+    }
+
     fn test_round_trip(
         sealer: &(InitialKey, InitialHeaderKey),
         opener: &(InitialKey, InitialHeaderKey),
@@ -31,7 +31,10 @@ mod tests {
     use crate::cipher_suite::TLS_CHACHA20_POLY1305_SHA256;
     use hex_literal::hex;
     use ring::hkdf;
-    use s2n_quic_core::crypto::Key;
+    use s2n_quic_core::{
+        crypto::Key,
+        packet::long::{VERSION_1, VERSION_2},
+    };
 
     //= https://www.rfc-editor.org/rfc/rfc9001#section-A.5
     //# In this example, TLS produces an application write secret from which
@@ -62,6 +65,12 @@ mod tests {
     const KU_SECRET: [u8; 32] =
         hex!("1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9");
 
+    /// The secret after a key update with the version 2 labels
+    ///
+    /// See <https://www.rfc-editor.org/rfc/rfc9369#appendix-A.5>
+    const KU_SECRET_V2: [u8; 32] =
+        hex!("c69374c49e3d2a9466fa689e49d476db5d0dfbc87d32ceeaa6343fd0ae4c7d88");
+
     // Prevent trivial success
     const INVALID_SECRET: [u8; 32] =
         hex!("0000000000000000000000000000000000000000000000000000000000000000");
@@ -70,26 +79,27 @@ mod tests {
     /// implementations don't have RFC values we can test.
     /// This is not exhaustive, but it does show that we are using the KDF and label correctly.
     fn generate_ciphers(
+        version: u32,
         secret: &[u8],
         next_secret: &[u8],
     ) -> (TLS_CHACHA20_POLY1305_SHA256, TLS_CHACHA20_POLY1305_SHA256) {
         // Create a cipher based on the initial secret
         let key = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, secret);
-        let cipher = TLS_CHACHA20_POLY1305_SHA256::new(key);
+        let cipher = TLS_CHACHA20_POLY1305_SHA256::new(version, key);
 
         // Create the cipher after a Key Update has occurred
         let next_cipher = cipher.0.update();
 
         // Create a cipher based on the expected post-update secret
         let next_key = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, next_secret);
-        let expected_next_cipher = TLS_CHACHA20_POLY1305_SHA256::new(next_key);
+        let expected_next_cipher = TLS_CHACHA20_POLY1305_SHA256::new(version, next_key);
 
         (next_cipher, expected_next_cipher.0)
     }
 
     #[test]
     fn test_key_update() {
-        let (next_cipher, expected_next_cipher) = generate_ciphers(&SECRET, &KU_SECRET);
+        let (next_cipher, expected_next_cipher) = generate_ciphers(VERSION_1, &SECRET, &KU_SECRET);
 
         // Encrypt two empty blocks to verify the ciphers are the same
         let mut next_cipher_output = [0; 32];
@@ -104,9 +114,34 @@ mod tests {
         assert_eq!(next_cipher_output, expected_cipher_output);
     }
 
+    #[test]
+    fn test_key_update_v2() {
+        let (next_cipher, expected_next_cipher) =
+            generate_ciphers(VERSION_2, &SECRET, &KU_SECRET_V2);
+
+        let mut next_cipher_output = [0; 32];
+        let mut expected_cipher_output = [0; 32];
+        next_cipher
+            .encrypt(0, &[], &mut next_cipher_output[..])
+            .unwrap();
+        expected_next_cipher
+            .encrypt(0, &[], &mut expected_cipher_output[..])
+            .unwrap();
+
+        assert_eq!(next_cipher_output, expected_cipher_output);
+
+        // the version 1 labels derive a different secret
+        let (next_cipher, _) = generate_ciphers(VERSION_1, &SECRET, &KU_SECRET_V2);
+        next_cipher
+            .encrypt(0, &[], &mut next_cipher_output[..])
+            .unwrap();
+        assert!(next_cipher_output != expected_cipher_output);
+    }
+
     #[test]
     fn test_key_update_failure() {
-        let (next_cipher, expected_next_cipher) = generate_ciphers(&INVALID_SECRET, &KU_SECRET);
+        let (next_cipher, expected_next_cipher) =
+            generate_ciphers(VERSION_1, &INVALID_SECRET, &KU_SECRET);
 
         // Encrypt two empty blocks to verify the ciphers are the same
         let mut next_cipher_output = [0; 32];
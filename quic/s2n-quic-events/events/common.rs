@@ -40,6 +40,8 @@ struct TransportParameters<'a> {
     initial_max_streams_bidi: u64,
     initial_max_streams_uni: u64,
     max_datagram_frame_size: u64,
+    /// Transport parameters defined by the application
+    custom_parameters: &'a [crate::transport::parameters::CustomTransportParameter],
 }
 
 struct PreferredAddress<'a> {
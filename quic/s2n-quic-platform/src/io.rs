@@ -3,3 +3,9 @@
 
 #[cfg(feature = "tokio")]
 pub mod tokio;
+
+#[cfg(all(feature = "tokio", feature = "io-uring", target_os = "linux"))]
+pub mod io_uring;
+
+#[cfg(feature = "tokio")]
+pub mod testing;
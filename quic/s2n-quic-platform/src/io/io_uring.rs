@@ -0,0 +1,611 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An IO provider which drives the sockets with [io_uring](https://kernel.dk/io_uring.pdf)
+//!
+//! Instead of calling `recvmmsg`/`sendmmsg` each time the sockets become ready, the provider
+//! keeps operations in flight on a ring that is shared with the kernel:
+//!
+//! * A single multishot `recvmsg` operation receives datagrams into a ring of buffers that are
+//!   registered with the kernel. The endpoint reads each datagram in place and the buffer is
+//!   handed back to the kernel once the endpoint is done with it.
+//! * Each batch of transmissions from the endpoint, including GSO segments, is submitted as
+//!   `sendmsg` operations with a single syscall.
+//!
+//! The ring's file descriptor is registered with the Tokio reactor so the endpoint is still
+//! driven by a Tokio runtime.
+//!
+//! Multishot receive operations require Linux 6.0 or later.
+
+use super::tokio::{bind, clock::Clock, configure_sockets};
+use crate::features::gso;
+use core::{future::Future, pin::Pin, task::Poll};
+use futures::future::poll_fn;
+use s2n_quic_core::{
+    endpoint::Endpoint,
+    event::{self, EndpointPublisher as _},
+    inet::{self, SocketAddress},
+    io::rx::Queue as _,
+    path::MaxMtu,
+    time::Clock as ClockTrait,
+};
+use std::{convert::TryInto, io, io::ErrorKind};
+use tokio::{io::unix::AsyncFd, runtime::Handle};
+
+mod driver;
+mod rx;
+mod tx;
+
+use driver::Driver;
+
+pub type PathHandle = crate::message::msg::Handle;
+
+#[derive(Debug, Default)]
+pub struct Io {
+    builder: Builder,
+}
+
+impl Io {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub fn new<A: std::net::ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let address = addr.to_socket_addrs()?.next().expect("missing address");
+        let builder = Builder::default().with_receive_address(address)?;
+        Ok(Self { builder })
+    }
+
+    pub fn start<E: Endpoint<PathHandle = PathHandle>>(
+        self,
+        mut endpoint: E,
+    ) -> io::Result<(tokio::task::JoinHandle<()>, SocketAddress)> {
+        let Builder {
+            handle,
+            rx_socket,
+            tx_socket,
+            recv_addr,
+            send_addr,
+            recv_buffer_size,
+            send_buffer_size,
+            max_mtu,
+            max_segments,
+            reuse_port,
+        } = self.builder;
+
+        endpoint.set_max_mtu(max_mtu);
+
+        let clock = Clock::default();
+
+        let mut publisher = event::EndpointPublisherSubscriber::new(
+            event::builder::EndpointMeta {
+                endpoint_type: E::ENDPOINT_TYPE,
+                timestamp: clock.get_time(),
+            },
+            None,
+            endpoint.subscriber(),
+        );
+
+        publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
+            configuration: event::builder::PlatformFeatureConfiguration::MaxMtu {
+                mtu: max_mtu.into(),
+            },
+        });
+
+        publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
+            configuration: event::builder::PlatformFeatureConfiguration::Gso {
+                max_segments: max_segments.into(),
+            },
+        });
+
+        let handle = if let Some(handle) = handle {
+            handle
+        } else {
+            Handle::try_current().map_err(|err| io::Error::new(ErrorKind::InvalidInput, err))?
+        };
+
+        let guard = handle.enter();
+
+        let rx_socket = if let Some(rx_socket) = rx_socket {
+            // ensure the socket is non-blocking
+            rx_socket.set_nonblocking(true)?;
+            rx_socket
+        } else if let Some(recv_addr) = recv_addr {
+            bind(recv_addr, reuse_port)?
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "missing bind address",
+            ));
+        };
+
+        let tx_socket = if let Some(tx_socket) = tx_socket {
+            // ensure the socket is non-blocking
+            tx_socket.set_nonblocking(true)?;
+            tx_socket
+        } else if let Some(send_addr) = send_addr {
+            bind(send_addr, reuse_port)?
+        } else {
+            // No tx_socket or send address was specified, so the tx socket
+            // will be a handle to the rx socket.
+            rx_socket.try_clone()?
+        };
+
+        if let Some(size) = send_buffer_size {
+            tx_socket.set_send_buffer_size(size)?;
+        }
+
+        if let Some(size) = recv_buffer_size {
+            rx_socket.set_recv_buffer_size(size)?;
+        }
+
+        let rx_addr = configure_sockets(&rx_socket, &tx_socket, &mut publisher)?;
+
+        // tell the queue the local address so it can fill it in on each message
+        let local_address = {
+            let addr: inet::SocketAddress = rx_addr.into();
+            addr.into()
+        };
+
+        let driver = Driver::new(
+            rx_socket.into(),
+            tx_socket.into(),
+            max_segments.into(),
+            local_address,
+        )?;
+
+        let local_addr = driver.local_addr()?.into();
+
+        let instance = Instance {
+            clock,
+            driver,
+            endpoint,
+        };
+
+        let task = handle.spawn(async move {
+            if let Err(err) = instance.event_loop().await {
+                let debug = format!("A fatal IO error occurred ({:?}): {}", err.kind(), err);
+                if cfg!(test) {
+                    panic!("{}", debug);
+                } else {
+                    eprintln!("{}", debug);
+                }
+            }
+        });
+
+        drop(guard);
+
+        Ok((task, local_addr))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    handle: Option<Handle>,
+    rx_socket: Option<socket2::Socket>,
+    tx_socket: Option<socket2::Socket>,
+    recv_addr: Option<std::net::SocketAddr>,
+    send_addr: Option<std::net::SocketAddr>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    max_mtu: MaxMtu,
+    max_segments: gso::MaxSegments,
+    reuse_port: bool,
+}
+
+impl Builder {
+    #[must_use]
+    pub fn with_handle(mut self, handle: Handle) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Sets the local address for the runtime to listen on. If no send address
+    /// or tx socket is specified, this address will also be used for transmitting from.
+    ///
+    /// NOTE: this method is mutually exclusive with `with_rx_socket`
+    pub fn with_receive_address(mut self, addr: std::net::SocketAddr) -> io::Result<Self> {
+        debug_assert!(self.rx_socket.is_none(), "rx socket has already been set");
+        self.recv_addr = Some(addr);
+        Ok(self)
+    }
+
+    /// Sets the local address for the runtime to transmit from. If no send address
+    /// or tx socket is specified, the receive_address will be used for transmitting.
+    ///
+    /// NOTE: this method is mutually exclusive with `with_tx_socket`
+    pub fn with_send_address(mut self, addr: std::net::SocketAddr) -> io::Result<Self> {
+        debug_assert!(self.tx_socket.is_none(), "tx socket has already been set");
+        self.send_addr = Some(addr);
+        Ok(self)
+    }
+
+    /// Sets the socket used for receiving for the runtime. If no tx_socket or send address is
+    /// specified, this socket will be used for transmitting.
+    ///
+    /// NOTE: this method is mutually exclusive with `with_receive_address`
+    pub fn with_rx_socket(mut self, socket: std::net::UdpSocket) -> io::Result<Self> {
+        debug_assert!(
+            self.recv_addr.is_none(),
+            "recv address has already been set"
+        );
+        self.rx_socket = Some(socket.into());
+        Ok(self)
+    }
+
+    /// Sets the socket used for transmitting on for the runtime. If no tx_socket or send address is
+    /// specified, the rx_socket will be used for transmitting.
+    ///
+    /// NOTE: this method is mutually exclusive with `with_send_address`
+    pub fn with_tx_socket(mut self, socket: std::net::UdpSocket) -> io::Result<Self> {
+        debug_assert!(
+            self.send_addr.is_none(),
+            "send address has already been set"
+        );
+        self.tx_socket = Some(socket.into());
+        Ok(self)
+    }
+
+    /// Sets the size of the operating system’s send buffer associated with the tx socket
+    pub fn with_send_buffer_size(mut self, send_buffer_size: usize) -> io::Result<Self> {
+        self.send_buffer_size = Some(send_buffer_size);
+        Ok(self)
+    }
+
+    /// Sets the size of the operating system’s receive buffer associated with the rx socket
+    pub fn with_recv_buffer_size(mut self, recv_buffer_size: usize) -> io::Result<Self> {
+        self.recv_buffer_size = Some(recv_buffer_size);
+        Ok(self)
+    }
+
+    /// Sets the largest maximum transmission unit (MTU) that can be sent on a path
+    pub fn with_max_mtu(mut self, max_mtu: u16) -> io::Result<Self> {
+        self.max_mtu = max_mtu
+            .try_into()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidInput, format!("{}", err)))?;
+        Ok(self)
+    }
+
+    /// Disables Generic Segmentation Offload (GSO)
+    ///
+    /// By default, GSO will be used unless the platform does not support it or an attempt to use
+    /// GSO fails. If it is known that GSO is not available, set this option to explicitly disable it.
+    pub fn with_gso_disabled(mut self) -> io::Result<Self> {
+        self.max_segments = 1.try_into().expect("1 is always a valid MaxSegments value");
+        Ok(self)
+    }
+
+    /// Enables the port reuse (SO_REUSEPORT) socket option
+    pub fn with_reuse_port(mut self) -> io::Result<Self> {
+        self.reuse_port = true;
+        Ok(self)
+    }
+
+    pub fn build(self) -> io::Result<Io> {
+        Ok(Io { builder: self })
+    }
+}
+
+struct Instance<E> {
+    clock: Clock,
+    driver: Driver,
+    endpoint: E,
+}
+
+impl<E: Endpoint<PathHandle = PathHandle>> Instance<E> {
+    async fn event_loop(self) -> io::Result<()> {
+        let Self {
+            clock,
+            driver,
+            mut endpoint,
+        } = self;
+
+        let mut driver = AsyncFd::new(driver)?;
+        let mut timer = clock.timer();
+
+        // arm the receive operation before waiting for anything
+        driver.get_mut().submit()?;
+
+        loop {
+            // move mutable references into the closure so the future only requires `Send`
+            let (driver_ref, endpoint_ref, timer_ref) = (&mut driver, &mut endpoint, &mut timer);
+            let clock_ref = &clock;
+
+            let wakeup = poll_fn(move |cx| {
+                let mut should_wake = false;
+                let mut application_wakeup = false;
+
+                if let Poll::Ready(wakeup) = endpoint_ref.poll_wakeups(cx, clock_ref) {
+                    should_wake = true;
+                    application_wakeup = true;
+                    if wakeup.is_err() {
+                        // The endpoint has shut down
+                        return Poll::Ready(Ok(None));
+                    }
+                }
+
+                let mut completions_ready = false;
+
+                if let Poll::Ready(guard) = driver_ref.poll_read_ready(cx) {
+                    // clear the readiness before draining the completion queue so we don't miss
+                    // any completions that come in afterwards
+                    guard?.clear_ready();
+                    completions_ready = true;
+                    should_wake = true;
+                }
+
+                let mut timeout_expired = false;
+
+                if Pin::new(&mut *timer_ref).poll(cx).is_ready() {
+                    timeout_expired = true;
+                    should_wake = true;
+                }
+
+                if !should_wake {
+                    return Poll::Pending;
+                }
+
+                Poll::Ready(Ok::<_, io::Error>(Some((
+                    completions_ready,
+                    timeout_expired,
+                    application_wakeup,
+                ))))
+            })
+            .await?;
+
+            let (completions_ready, timeout_expired, application_wakeup) =
+                if let Some(wakeup) = wakeup {
+                    wakeup
+                } else {
+                    return Ok(());
+                };
+
+            let driver = driver.get_mut();
+
+            let subscriber = endpoint.subscriber();
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: E::ENDPOINT_TYPE,
+                    timestamp: clock.get_time(),
+                },
+                None,
+                subscriber,
+            );
+
+            publisher.on_platform_event_loop_wakeup(event::builder::PlatformEventLoopWakeup {
+                timeout_expired,
+                rx_ready: completions_ready,
+                tx_ready: completions_ready,
+                application_wakeup,
+            });
+
+            driver.complete(&mut publisher)?;
+
+            let rx = driver.rx_queue();
+            if !rx.is_empty() {
+                endpoint.receive(rx, &clock);
+            }
+
+            endpoint.transmit(&mut driver.tx_queue(), &clock);
+
+            driver.submit()?;
+
+            if let Some(delay) = endpoint.timeout() {
+                timer.update(delay);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{convert::TryInto, task::Context};
+    use s2n_quic_core::{
+        endpoint::{self, CloseError},
+        event,
+        inet::SocketAddress,
+        io::{
+            rx::{self, Entry as _},
+            tx,
+        },
+        path::Handle as _,
+        time::{Clock, Duration, Timestamp},
+    };
+    use std::collections::BTreeMap;
+
+    struct TestEndpoint {
+        addr: SocketAddress,
+        messages: BTreeMap<u32, Option<Timestamp>>,
+        now: Option<Timestamp>,
+        subscriber: NoopSubscriber,
+    }
+
+    impl TestEndpoint {
+        fn new(addr: SocketAddress) -> Self {
+            let messages = (0..1000).map(|id| (id, None)).collect();
+            Self {
+                addr,
+                messages,
+                now: None,
+                subscriber: Default::default(),
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct NoopSubscriber;
+
+    impl event::Subscriber for NoopSubscriber {
+        type ConnectionContext = ();
+
+        fn create_connection_context(
+            &mut self,
+            _meta: &event::api::ConnectionMeta,
+            _info: &event::api::ConnectionInfo,
+        ) -> Self::ConnectionContext {
+        }
+    }
+
+    impl Endpoint for TestEndpoint {
+        type PathHandle = PathHandle;
+        type Subscriber = NoopSubscriber;
+
+        const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Server;
+
+        fn transmit<Tx: tx::Queue<Handle = PathHandle>, C: Clock>(
+            &mut self,
+            queue: &mut Tx,
+            clock: &C,
+        ) {
+            let now = clock.get_time();
+            self.now = Some(now);
+
+            for (id, tx_time) in &mut self.messages {
+                match tx_time {
+                    Some(time)
+                        if now.saturating_duration_since(*time) < Duration::from_millis(50) =>
+                    {
+                        continue
+                    }
+                    _ => {
+                        let payload = id.to_be_bytes();
+                        let addr = PathHandle::from_remote_address(self.addr.into());
+                        let msg = (addr, payload);
+                        if queue.push(msg).is_ok() {
+                            *tx_time = Some(now);
+                        } else {
+                            // no more capacity
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        fn receive<Rx: rx::Queue<Handle = PathHandle>, C: Clock>(
+            &mut self,
+            queue: &mut Rx,
+            clock: &C,
+        ) {
+            let now = clock.get_time();
+            self.now = Some(now);
+            let local_address = queue.local_address();
+            let entries = queue.as_slice_mut();
+            let len = entries.len();
+            for entry in entries {
+                if let Some((_header, payload)) = entry.read(&local_address) {
+                    assert_eq!(payload.len(), 4, "invalid payload {:?}", payload);
+
+                    let id = (&*payload).try_into().unwrap();
+                    let id = u32::from_be_bytes(id);
+                    self.messages.remove(&id);
+                }
+            }
+            queue.finish(len);
+        }
+
+        fn poll_wakeups<C: Clock>(
+            &mut self,
+            _cx: &mut Context<'_>,
+            clock: &C,
+        ) -> Poll<Result<usize, CloseError>> {
+            let now = clock.get_time();
+            self.now = Some(now);
+
+            if self.messages.is_empty() {
+                return Err(CloseError).into();
+            }
+
+            Poll::Pending
+        }
+
+        fn timeout(&self) -> Option<Timestamp> {
+            self.now.map(|now| now + Duration::from_millis(50))
+        }
+
+        fn set_max_mtu(&mut self, _max_mtu: MaxMtu) {
+            // noop
+        }
+
+        fn subscriber(&mut self) -> &mut Self::Subscriber {
+            &mut self.subscriber
+        }
+    }
+
+    async fn test<A: std::net::ToSocketAddrs>(
+        receive_addr: A,
+        send_addr: Option<A>,
+    ) -> io::Result<()> {
+        let rx_socket = bind(receive_addr, false)?;
+        let rx_socket: std::net::UdpSocket = rx_socket.into();
+        let addr = rx_socket.local_addr()?;
+
+        let mut io_builder = Io::builder().with_rx_socket(rx_socket)?;
+
+        if let Some(addr) = send_addr {
+            let tx_socket = bind(addr, false)?;
+            let tx_socket: std::net::UdpSocket = tx_socket.into();
+            io_builder = io_builder.with_tx_socket(tx_socket)?
+        }
+
+        let io = io_builder.build()?;
+
+        let endpoint = TestEndpoint::new(addr.into());
+
+        let (task, local_addr) = match io.start(endpoint) {
+            Ok(v) => v,
+            // io_uring may be disabled in the current environment
+            Err(err)
+                if matches!(
+                    err.raw_os_error(),
+                    Some(libc::ENOSYS) | Some(libc::EPERM) | Some(libc::EINVAL)
+                ) =>
+            {
+                eprintln!("The current environment does not support io_uring; skipping");
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let local_addr: std::net::SocketAddr = local_addr.into();
+
+        assert_eq!(local_addr, addr);
+
+        task.await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ipv4_test() -> io::Result<()> {
+        test("127.0.0.1:0", None).await
+    }
+
+    #[tokio::test]
+    async fn ipv4_two_socket_test() -> io::Result<()> {
+        test("127.0.0.1:0", Some("127.0.0.1:0")).await
+    }
+
+    #[tokio::test]
+    async fn ipv6_test() -> io::Result<()> {
+        match test(("::1", 0), None).await {
+            Err(err) if err.kind() == io::ErrorKind::AddrNotAvailable => {
+                eprintln!("The current environment does not support IPv6; skipping");
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    #[tokio::test]
+    async fn ipv6_two_socket_test() -> io::Result<()> {
+        match test(("::1", 0), Some(("::1", 0))).await {
+            Err(err) if err.kind() == io::ErrorKind::AddrNotAvailable => {
+                eprintln!("The current environment does not support IPv6; skipping");
+                Ok(())
+            }
+            other => other,
+        }
+    }
+}
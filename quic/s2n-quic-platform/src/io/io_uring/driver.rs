@@ -0,0 +1,304 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{rx, tx};
+use crate::message::{msg, queue};
+use core::mem::ManuallyDrop;
+use io_uring::{opcode, types, IoUring};
+use s2n_quic_core::{event, path::LocalAddress};
+use std::{
+    io,
+    net::UdpSocket,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// `user_data` for the multishot receive operation
+const RX: u64 = 0;
+/// `user_data` for cancellation operations
+const CANCEL: u64 = 1;
+/// `user_data` offset for transmit operations
+const TX: u64 = 2;
+
+/// Owns the ring along with all of the memory that is shared with the kernel
+///
+/// Dropping the driver waits for any in-flight operations to complete before releasing the
+/// queues. If that fails, the queues are leaked, since the kernel may still be using them.
+pub struct Driver {
+    ring: IoUring,
+    rx: ManuallyDrop<rx::Queue>,
+    tx: ManuallyDrop<tx::Queue>,
+    rx_socket: UdpSocket,
+    tx_socket: UdpSocket,
+}
+
+impl Driver {
+    pub fn new(
+        rx_socket: UdpSocket,
+        tx_socket: UdpSocket,
+        max_segments: usize,
+        local_address: LocalAddress,
+    ) -> io::Result<Self> {
+        let tx = tx::Queue::new(max_segments);
+        let mut rx = rx::Queue::new()?;
+        rx.set_local_address(local_address);
+
+        // leave room for the receive operation and its cancellation
+        let entries = (tx.capacity() + 2).next_power_of_two();
+        // every receive buffer and transmission can have a completion outstanding
+        let completions = (entries + rx.buffer_count()).next_power_of_two();
+
+        let ring = IoUring::builder()
+            .setup_cqsize(completions as u32)
+            .build(entries as u32)?;
+
+        rx.register(&ring.submitter())?;
+
+        Ok(Self {
+            ring,
+            rx: ManuallyDrop::new(rx),
+            tx: ManuallyDrop::new(tx),
+            rx_socket,
+            tx_socket,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.rx_socket.local_addr()
+    }
+
+    /// Processes all of the completed operations
+    pub fn complete<Publisher: event::EndpointPublisher>(
+        &mut self,
+        publisher: &mut Publisher,
+    ) -> io::Result<()> {
+        let mut rx_count = 0;
+        let mut tx_count = 0;
+
+        for entry in self.ring.completion() {
+            match entry.user_data() {
+                RX => match self.rx.on_completion(entry.result(), entry.flags()) {
+                    Ok(true) => rx_count += 1,
+                    Ok(false) => {}
+                    // the kernel doesn't support multishot receive operations
+                    Err(errno) if errno == libc::EINVAL => {
+                        return Err(io::Error::from_raw_os_error(errno));
+                    }
+                    // the operation will be re-armed once buffers are returned to the kernel
+                    Err(errno) if errno == libc::ENOBUFS => {}
+                    Err(errno) => {
+                        publisher.on_platform_rx_error(event::builder::PlatformRxError { errno });
+                    }
+                },
+                CANCEL => {}
+                user_data => {
+                    self.tx.on_completion(user_data - TX);
+
+                    match entry.result() {
+                        result if result >= 0 => tx_count += 1,
+                        result => self.tx.on_error(-result, publisher),
+                    }
+                }
+            }
+        }
+
+        if tx_count > 0 {
+            publisher.on_platform_tx(event::builder::PlatformTx { count: tx_count });
+        }
+
+        self.tx.finish();
+
+        if rx_count > 0 {
+            publisher.on_platform_rx(event::builder::PlatformRx { count: rx_count });
+        }
+
+        Ok(())
+    }
+
+    pub fn rx_queue(&mut self) -> &mut rx::Queue {
+        &mut self.rx
+    }
+
+    pub fn tx_queue(&mut self) -> queue::Free<'_, msg::Message> {
+        self.tx.tx_queue()
+    }
+
+    /// Submits the pending transmissions and re-arms the receive operation, if needed
+    ///
+    /// All of the operations are submitted to the kernel with a single syscall, unless the
+    /// submission queue fills up.
+    pub fn submit(&mut self) -> io::Result<()> {
+        let rx_fd = types::Fd(self.rx_socket.as_raw_fd());
+        let tx_fd = types::Fd(self.tx_socket.as_raw_fd());
+
+        loop {
+            let mut submission = self.ring.submission();
+
+            if let Some(entry) = self.rx.arm(rx_fd, RX) {
+                // Safety: the msghdr template and buffers live as long as the driver
+                if unsafe { submission.push(&entry) }.is_err() {
+                    self.rx.disarm();
+                }
+            }
+
+            let is_done = self.tx.push(&mut submission, tx_fd, TX);
+            let is_empty = submission.is_empty();
+            drop(submission);
+
+            if !is_empty {
+                match self.ring.submit() {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    // the completion queue is overflowing so we need to drain it before submitting
+                    // anything else. The ring will be readable so we'll be woken up to do so.
+                    Err(err) if err.raw_os_error() == Some(libc::EBUSY) => return Ok(()),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            if is_done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Cancels the receive operation and waits for all operations to complete
+    fn shutdown(&mut self) -> io::Result<()> {
+        if self.rx.is_armed() {
+            let entry = opcode::AsyncCancel::new(RX).build().user_data(CANCEL);
+            // Safety: the cancellation doesn't reference any memory
+            unsafe {
+                // the submission queue is drained on each submit so there will always be room
+                let _ = self.ring.submission().push(&entry);
+            }
+        }
+
+        while self.rx.is_armed() || self.tx.is_in_flight() {
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) if err.raw_os_error() == Some(libc::EBUSY) => {}
+                Err(err) => return Err(err),
+            }
+
+            for entry in self.ring.completion() {
+                match entry.user_data() {
+                    RX => {
+                        let _ = self.rx.on_completion(entry.result(), entry.flags());
+                    }
+                    CANCEL => {}
+                    user_data => self.tx.on_completion(user_data - TX),
+                }
+            }
+
+            self.tx.finish();
+        }
+
+        self.rx.unregister(&self.ring.submitter())
+    }
+}
+
+impl AsRawFd for Driver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.ring.as_raw_fd()
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        if self.shutdown().is_ok() {
+            unsafe {
+                // Safety: the kernel is no longer referencing the queues and they aren't used
+                // after this point
+                ManuallyDrop::drop(&mut self.rx);
+                ManuallyDrop::drop(&mut self.tx);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryInto;
+    use s2n_quic_core::io::rx::{Entry as _, Queue as _};
+    use std::collections::BTreeSet;
+
+    /// Waits for completions and returns the ids of the datagrams that were received
+    fn receive(driver: &mut Driver, publisher: &mut event::testing::Publisher) -> Vec<u32> {
+        driver.ring.submit_and_wait(1).unwrap();
+        driver.complete(publisher).unwrap();
+
+        let rx = driver.rx_queue();
+        let local_address = rx.local_address();
+        rx.as_slice_mut()
+            .iter_mut()
+            .map(|entry| {
+                let (_header, payload) = entry.read(&local_address).unwrap();
+                u32::from_be_bytes((&*payload).try_into().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rearm_test() -> io::Result<()> {
+        let rx_socket = UdpSocket::bind("127.0.0.1:0")?;
+        rx_socket.set_nonblocking(true)?;
+        let tx_socket = rx_socket.try_clone()?;
+
+        let mut driver = match Driver::new(rx_socket, tx_socket, 1, Default::default()) {
+            Ok(driver) => driver,
+            // io_uring may be disabled in the current environment
+            Err(err)
+                if matches!(
+                    err.raw_os_error(),
+                    Some(libc::ENOSYS) | Some(libc::EPERM) | Some(libc::EINVAL)
+                ) =>
+            {
+                eprintln!("The current environment does not support io_uring; skipping");
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let count = driver.rx.buffer_count();
+        let sender = UdpSocket::bind("127.0.0.1:0")?;
+        let addr = driver.local_addr()?;
+
+        // queue up more datagrams than there are buffers
+        for id in 0..count as u32 * 2 {
+            sender.send_to(&id.to_be_bytes(), addr)?;
+        }
+
+        let mut publisher = event::testing::Publisher::no_snapshot();
+        let mut received = BTreeSet::new();
+
+        driver.submit()?;
+        assert!(driver.rx.is_armed());
+
+        // the kernel terminates the operation once it runs out of buffers
+        while driver.rx.is_armed() {
+            received.extend(receive(&mut driver, &mut publisher));
+        }
+        assert_eq!(received.len(), count);
+        assert_eq!(publisher.platform_rx_error, 0);
+
+        // the operation isn't re-armed while the endpoint holds all of the buffers
+        driver.submit()?;
+        assert!(!driver.rx.is_armed());
+
+        driver.rx_queue().finish(count);
+        driver.submit()?;
+        assert!(driver.rx.is_armed());
+
+        while received.len() < count * 2 {
+            received.extend(receive(&mut driver, &mut publisher));
+            let rx = driver.rx_queue();
+            rx.finish(rx.len());
+            driver.submit()?;
+        }
+
+        assert_eq!(received, (0..count as u32 * 2).collect());
+
+        Ok(())
+    }
+}
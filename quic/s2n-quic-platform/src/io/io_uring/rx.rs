@@ -0,0 +1,559 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    buffer::{default as buffer, Buffer as _},
+    message::{cmsg, msg, Message as _},
+};
+use core::{
+    alloc::Layout,
+    mem::{align_of, size_of},
+    ptr::NonNull,
+    sync::atomic::{AtomicU16, Ordering},
+};
+use io_uring::{cqueue, opcode, squeue, types, Submitter};
+use s2n_quic_core::{io::rx, path::LocalAddress};
+use std::io;
+
+/// The buffer group id used for the provided receive buffers
+const BUFFER_GROUP: u16 = 0;
+
+/// The kernel limits the number of entries in a provided buffer ring to 2^15
+const MAX_BUFFERS: usize = 1 << 15;
+
+/// The alignment of each field the kernel writes into a buffer
+///
+/// The control messages are read in place so they need to be aligned to a `cmsghdr`.
+const ALIGNMENT: usize = align_of::<libc::cmsghdr>();
+
+/// Receives datagrams from a socket with a single multishot `recvmsg` operation
+///
+/// The kernel writes each datagram into one of the registered buffers in the provided buffer
+/// ring. Each completed buffer is exposed to the endpoint as a message which points into the
+/// buffer, so the `rx::Entry` implementation of the `msg` socket queue, including the decoding of
+/// the ECN and local address control messages, is used without copying the datagram. A buffer is
+/// handed back to the kernel once the endpoint has finished with its message.
+pub struct Queue {
+    buffers: Buffers,
+    /// The `msghdr` template for the multishot operation
+    ///
+    /// Only the `msg_namelen` and `msg_controllen` fields are used by the kernel to determine
+    /// the layout of each provided buffer.
+    msghdr: Box<libc::msghdr>,
+    /// The `iovec` for each buffer, which the messages point to
+    iovecs: Box<[libc::iovec]>,
+    /// Datagrams which have been received but not yet finished by the endpoint
+    messages: Vec<msg::Message>,
+    /// The buffer id of each received message
+    ids: Vec<u16>,
+    local_address: LocalAddress,
+    /// Set when the multishot operation is currently in-flight
+    armed: bool,
+}
+
+/// Even though `Queue` contains raw pointers, it owns all of the data
+/// and can be sent across threads safely.
+#[allow(unknown_lints, clippy::non_send_fields_in_send_ty)]
+unsafe impl Send for Queue {}
+
+impl Queue {
+    pub fn new() -> io::Result<Self> {
+        // use the same dimensions as the receive queue of the tokio provider
+        let payloads = buffer::Buffer::default();
+        let mtu = payloads.mtu();
+        let count = (payloads.len() / mtu).next_power_of_two().min(MAX_BUFFERS);
+        drop(payloads);
+
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { core::mem::zeroed() });
+        msghdr.msg_namelen = align(size_of::<libc::sockaddr_in6>()) as _;
+        msghdr.msg_controllen = align(cmsg::MAX_LEN) as _;
+
+        let buffer_len = align(
+            size_of::<io_uring_recvmsg_out>()
+                + msghdr.msg_namelen as usize
+                + msghdr.msg_controllen as usize
+                + mtu,
+        );
+
+        let buffers = Buffers::new(count, buffer_len)?;
+
+        Ok(Self {
+            buffers,
+            msghdr,
+            iovecs: vec![unsafe { core::mem::zeroed() }; count].into_boxed_slice(),
+            messages: Vec::with_capacity(count),
+            ids: Vec::with_capacity(count),
+            local_address: Default::default(),
+            armed: false,
+        })
+    }
+
+    pub fn set_local_address(&mut self, local_address: LocalAddress) {
+        self.local_address = local_address;
+    }
+
+    /// Returns the number of registered receive buffers
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.count
+    }
+
+    /// Registers the receive buffers with the ring
+    pub fn register(&self, submitter: &Submitter) -> io::Result<()> {
+        self.buffers.register(submitter)
+    }
+
+    pub fn unregister(&self, submitter: &Submitter) -> io::Result<()> {
+        submitter.unregister_buf_ring(BUFFER_GROUP)
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Returns the multishot `recvmsg` entry, if it needs to be submitted
+    pub fn arm(&mut self, fd: types::Fd, user_data: u64) -> Option<squeue::Entry> {
+        // if the endpoint holds all of the buffers then the operation would immediately fail
+        // with ENOBUFS
+        if self.armed || self.ids.len() == self.buffers.count {
+            return None;
+        }
+
+        self.armed = true;
+
+        let entry = opcode::RecvMsgMulti::new(fd, &*self.msghdr, BUFFER_GROUP)
+            .build()
+            .user_data(user_data);
+
+        Some(entry)
+    }
+
+    /// Marks the multishot operation as not being in-flight after it failed to be submitted
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    /// Records a completion for the multishot operation
+    ///
+    /// Returns `true` if a datagram was added to the queue, or the `errno` if the operation
+    /// failed.
+    pub fn on_completion(&mut self, result: i32, flags: u32) -> Result<bool, i32> {
+        // the kernel terminates the operation when it sets the `more` flag to false; usually
+        // because we ran out of buffers or the socket had an error. In either case the operation
+        // needs to be re-armed.
+        if !cqueue::more(flags) {
+            self.armed = false;
+        }
+
+        if result < 0 {
+            return Err(-result);
+        }
+
+        let id = if let Some(id) = cqueue::buffer_select(flags) {
+            id
+        } else {
+            return Ok(false);
+        };
+
+        if let Some(message) = self.read(id) {
+            self.messages.push(message);
+            self.ids.push(id);
+            Ok(true)
+        } else {
+            // the datagram is discarded so the buffer can be reused right away
+            self.buffers.push(id);
+            self.buffers.sync();
+            Ok(false)
+        }
+    }
+
+    /// Returns a message which points to the datagram in the buffer
+    ///
+    /// Returns `None` if the datagram was truncated and should be discarded.
+    fn read(&mut self, id: u16) -> Option<msg::Message> {
+        let buffer = self.buffers.get(id);
+        let out = types::RecvMsgOut::parse(buffer, &self.msghdr).ok()?;
+
+        if out.is_payload_truncated() || out.is_name_data_truncated() {
+            return None;
+        }
+
+        let offset_of = |field: &[u8]| field.as_ptr() as usize - buffer.as_ptr() as usize;
+        let name = (offset_of(out.name_data()), out.name_data().len());
+        let payload = (offset_of(out.payload_data()), out.payload_data().len());
+
+        // A truncated control message can't be decoded reliably, so the datagram is processed
+        // as if it didn't include any. The ECN markings and local address are only used as
+        // hints so this is preferred over dropping every datagram if the control messages no
+        // longer fit.
+        let is_control_data_truncated =
+            out.is_control_data_truncated() || out.flags() & libc::MSG_CTRUNC as u32 != 0;
+        let control = if is_control_data_truncated {
+            (offset_of(out.control_data()), 0)
+        } else {
+            (offset_of(out.control_data()), out.control_data().len())
+        };
+
+        // the template uses the same lengths as the message allocations
+        debug_assert!(name.1 <= size_of::<libc::sockaddr_in6>());
+        debug_assert_eq!(control.0 % ALIGNMENT, 0);
+
+        let buffer = self.buffers.get_mut_ptr(id);
+        let iovec = &mut self.iovecs[id as usize];
+
+        // Safety: the offsets were all derived from fields within the buffer
+        unsafe {
+            iovec.iov_base = buffer.add(payload.0) as _;
+            iovec.iov_len = payload.1;
+
+            let mut msghdr: libc::msghdr = core::mem::zeroed();
+            msghdr.msg_iov = iovec;
+            msghdr.msg_iovlen = 1;
+            msghdr.msg_name = buffer.add(name.0) as _;
+            msghdr.msg_namelen = name.1 as _;
+            msghdr.msg_control = buffer.add(control.0) as _;
+            msghdr.msg_controllen = control.1 as _;
+
+            Some(msg::Message(msghdr))
+        }
+    }
+}
+
+impl rx::Queue for Queue {
+    type Entry = msg::Message;
+    type Handle = msg::Handle;
+
+    #[inline]
+    fn local_address(&self) -> LocalAddress {
+        self.local_address
+    }
+
+    #[inline]
+    fn as_slice_mut(&mut self) -> &mut [msg::Message] {
+        &mut self.messages
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Hands the buffers of the first `count` messages back to the kernel
+    fn finish(&mut self, count: usize) {
+        debug_assert!(
+            count <= self.len(),
+            "cannot finish more messages than available"
+        );
+
+        // The payload could potentially contain sensitive data and should be zeroed out before
+        // the buffer is reused
+        #[cfg(feature = "wipe")]
+        for message in &mut self.messages[..count] {
+            zeroize::Zeroize::zeroize(&mut message.payload_mut().iter_mut());
+        }
+
+        self.messages.drain(..count);
+
+        for id in self.ids.drain(..count) {
+            self.buffers.push(id);
+        }
+
+        if count > 0 {
+            self.buffers.sync();
+        }
+    }
+}
+
+/// Rounds the length up to the alignment of the fields in each buffer
+const fn align(len: usize) -> usize {
+    (len + ALIGNMENT - 1) & !(ALIGNMENT - 1)
+}
+
+/// The header the kernel writes at the start of each multishot `recvmsg` buffer
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+struct io_uring_recvmsg_out {
+    namelen: u32,
+    controllen: u32,
+    payloadlen: u32,
+    flags: u32,
+}
+
+/// A ring of buffers that are registered with the kernel for receiving
+struct Buffers {
+    /// The page-aligned ring of buffer descriptors shared with the kernel
+    ring: NonNull<types::BufRingEntry>,
+    ring_layout: Layout,
+    /// The contiguous, page-aligned region which backs each of the buffers
+    region: NonNull<u8>,
+    region_layout: Layout,
+    buffer_len: usize,
+    count: usize,
+    tail: u16,
+}
+
+impl Buffers {
+    fn new(count: usize, buffer_len: usize) -> io::Result<Self> {
+        debug_assert!(count.is_power_of_two());
+        debug_assert!(count <= MAX_BUFFERS);
+
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let layout = |size| {
+            Layout::from_size_align(size, page_size)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))
+        };
+        let ring_layout = layout(count * size_of::<types::BufRingEntry>())?;
+        let region_layout = layout(count * buffer_len)?;
+
+        let ring = unsafe {
+            // Safety: the layout size is non-zero
+            std::alloc::alloc_zeroed(ring_layout)
+        };
+        let ring = NonNull::new(ring as *mut types::BufRingEntry)
+            .unwrap_or_else(|| std::alloc::handle_alloc_error(ring_layout));
+
+        let region = unsafe {
+            // Safety: the layout size is non-zero
+            std::alloc::alloc_zeroed(region_layout)
+        };
+        let region =
+            NonNull::new(region).unwrap_or_else(|| std::alloc::handle_alloc_error(region_layout));
+
+        let mut buffers = Self {
+            ring,
+            ring_layout,
+            region,
+            region_layout,
+            buffer_len,
+            count,
+            tail: 0,
+        };
+
+        for id in 0..count {
+            buffers.push(id as u16);
+        }
+        buffers.sync();
+
+        Ok(buffers)
+    }
+
+    fn register(&self, submitter: &Submitter) -> io::Result<()> {
+        submitter.register_buf_ring(self.ring.as_ptr() as u64, self.count as u16, BUFFER_GROUP)
+    }
+
+    fn get(&self, id: u16) -> &[u8] {
+        unsafe {
+            // Safety: the id is always less than the number of buffers in the region
+            core::slice::from_raw_parts(self.get_mut_ptr(id), self.buffer_len)
+        }
+    }
+
+    fn get_mut_ptr(&self, id: u16) -> *mut u8 {
+        debug_assert!((id as usize) < self.count);
+        unsafe {
+            // Safety: the id is always less than the number of buffers in the region
+            self.region.as_ptr().add(id as usize * self.buffer_len)
+        }
+    }
+
+    /// Adds a buffer to the tail of the ring
+    ///
+    /// The buffer will not be visible to the kernel until `sync` is called.
+    fn push(&mut self, id: u16) {
+        let index = self.tail as usize & (self.count - 1);
+        let addr = self.get_mut_ptr(id);
+
+        let entry = unsafe {
+            // Safety: the index is masked to the allocated number of entries
+            &mut *self.ring.as_ptr().add(index)
+        };
+        entry.set_addr(addr as u64);
+        entry.set_len(self.buffer_len as u32);
+        entry.set_bid(id);
+
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    /// Publishes the pushed buffers to the kernel
+    fn sync(&self) {
+        unsafe {
+            // Safety: the tail field lives in the first entry of the ring and is only written to
+            // by the application
+            let tail = types::BufRingEntry::tail(self.ring.as_ptr()) as *const AtomicU16;
+            (*tail).store(self.tail, Ordering::Release);
+        }
+    }
+}
+
+impl Drop for Buffers {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: the ring and region were allocated with the same layouts
+            std::alloc::dealloc(self.ring.as_ptr() as *mut u8, self.ring_layout);
+            std::alloc::dealloc(self.region.as_ptr(), self.region_layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rx::{Entry as _, Queue as _};
+    use s2n_quic_core::inet::{ExplicitCongestionNotification, SocketAddressV4};
+
+    // flags from `io_uring.h`
+    const F_BUFFER: u32 = 1 << 0;
+    const F_MORE: u32 = 1 << 1;
+    const BUFFER_SHIFT: u32 = 16;
+
+    const FD: types::Fd = types::Fd(-1);
+
+    /// Writes a datagram into the buffer the same way as the kernel and returns the completion
+    /// flags
+    fn write(queue: &mut Queue, id: u16, payload: &[u8], ecn: u8, flags: i32) -> u32 {
+        let name_field_len = queue.msghdr.msg_namelen as usize;
+        let control_field_len = queue.msghdr.msg_controllen as usize;
+        let buffer = queue.buffers.get_mut_ptr(id);
+
+        unsafe {
+            let name = buffer.add(size_of::<io_uring_recvmsg_out>());
+            let control = name.add(name_field_len);
+
+            let mut sockaddr: libc::sockaddr_in = core::mem::zeroed();
+            sockaddr.sin_family = libc::AF_INET as _;
+            sockaddr.sin_port = 4433u16.to_be();
+            sockaddr.sin_addr.s_addr = u32::from_ne_bytes([127, 0, 0, 1]);
+            core::ptr::write(name as *mut libc::sockaddr_in, sockaddr);
+
+            let mut msghdr: libc::msghdr = core::mem::zeroed();
+            msghdr.msg_control = control as _;
+            msghdr.msg_controllen = libc::CMSG_SPACE(size_of::<u8>() as _) as _;
+            let cmsg = &mut *libc::CMSG_FIRSTHDR(&msghdr);
+            cmsg.cmsg_level = libc::IPPROTO_IP;
+            cmsg.cmsg_type = libc::IP_TOS;
+            cmsg.cmsg_len = libc::CMSG_LEN(size_of::<u8>() as _) as _;
+            *libc::CMSG_DATA(cmsg) = ecn;
+
+            let header = io_uring_recvmsg_out {
+                namelen: size_of::<libc::sockaddr_in>() as _,
+                controllen: msghdr.msg_controllen as _,
+                payloadlen: payload.len() as _,
+                flags: flags as _,
+            };
+            core::ptr::write(buffer as *mut io_uring_recvmsg_out, header);
+
+            let payload_ptr = control.add(control_field_len);
+            core::ptr::copy_nonoverlapping(payload.as_ptr(), payload_ptr, payload.len());
+        }
+
+        F_BUFFER | (id as u32) << BUFFER_SHIFT
+    }
+
+    /// Reads the ECN markings and payload of the first message in the queue
+    fn read(queue: &mut Queue) -> (ExplicitCongestionNotification, Vec<u8>) {
+        let local_address = queue.local_address();
+        let (header, payload) = queue.as_slice_mut()[0].read(&local_address).unwrap();
+        let remote_address = SocketAddressV4::new([127, 0, 0, 1], 4433);
+        assert_eq!(header.path.remote_address.0, remote_address.into());
+        (header.ecn, payload.to_vec())
+    }
+
+    #[test]
+    fn rearm_test() {
+        let mut queue = Queue::new().unwrap();
+
+        assert!(queue.arm(FD, 0).is_some());
+        assert!(
+            queue.arm(FD, 0).is_none(),
+            "the operation is already in-flight"
+        );
+
+        // the kernel keeps the operation armed while it sets the `more` flag
+        let flags = write(&mut queue, 0, b"hello", 0, 0);
+        assert_eq!(queue.on_completion(5, flags | F_MORE), Ok(true));
+        assert!(queue.is_armed());
+        assert!(queue.arm(FD, 0).is_none());
+
+        // the operation terminates once the `more` flag is cleared
+        let flags = write(&mut queue, 1, b"world", 0, 0);
+        assert_eq!(queue.on_completion(5, flags), Ok(true));
+        assert!(!queue.is_armed());
+        assert!(queue.arm(FD, 0).is_some());
+
+        // errors also terminate the operation
+        assert_eq!(
+            queue.on_completion(-libc::ECONNREFUSED, 0),
+            Err(libc::ECONNREFUSED)
+        );
+        assert!(!queue.is_armed());
+        assert!(queue.arm(FD, 0).is_some());
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(read(&mut queue).1, b"hello");
+    }
+
+    #[test]
+    fn exhausted_buffers_test() {
+        let mut queue = Queue::new().unwrap();
+        let count = queue.buffer_count();
+
+        assert!(queue.arm(FD, 0).is_some());
+
+        for id in 0..count as u16 {
+            let flags = write(&mut queue, id, &id.to_be_bytes(), 0, 0);
+            assert_eq!(queue.on_completion(2, flags | F_MORE), Ok(true));
+        }
+
+        // the kernel terminates the operation when it runs out of buffers
+        assert_eq!(queue.on_completion(-libc::ENOBUFS, 0), Err(libc::ENOBUFS));
+
+        // re-arming would fail until the endpoint returns at least one buffer
+        assert!(queue.arm(FD, 0).is_none());
+        queue.finish(1);
+        assert!(queue.arm(FD, 0).is_some());
+
+        assert_eq!(queue.len(), count - 1);
+        assert_eq!(read(&mut queue).1, 1u16.to_be_bytes());
+    }
+
+    #[test]
+    fn zero_copy_test() {
+        let mut queue = Queue::new().unwrap();
+
+        let flags = write(&mut queue, 3, b"hello", 0b10, 0);
+        assert_eq!(queue.on_completion(5, flags), Ok(true));
+
+        // the payload is read from the buffer that the kernel wrote to
+        let buffer = queue.buffers.get(3).as_ptr_range();
+        let local_address = queue.local_address();
+        let (header, payload) = queue.as_slice_mut()[0].read(&local_address).unwrap();
+        assert!(buffer.contains(&payload.as_ptr()));
+        assert_eq!(payload, b"hello");
+        assert_eq!(header.ecn, ExplicitCongestionNotification::Ect0);
+
+        // the payload is wiped when the buffer is handed back to the kernel
+        queue.finish(1);
+        assert!(queue.is_empty());
+        if cfg!(feature = "wipe") {
+            let buffer = queue.buffers.get(3);
+            assert!(!buffer.windows(5).any(|window| window == b"hello"));
+        }
+    }
+
+    #[test]
+    fn truncated_test() {
+        let mut queue = Queue::new().unwrap();
+
+        // datagrams with truncated payloads are discarded
+        let flags = write(&mut queue, 0, b"hello", 0b10, libc::MSG_TRUNC);
+        assert_eq!(queue.on_completion(5, flags), Ok(false));
+        assert!(queue.is_empty());
+
+        // datagrams with truncated control messages are read without them
+        let flags = write(&mut queue, 1, b"hello", 0b10, libc::MSG_CTRUNC);
+        assert_eq!(queue.on_completion(5, flags), Ok(true));
+        assert_eq!(
+            read(&mut queue),
+            (ExplicitCongestionNotification::NotEct, b"hello".to_vec())
+        );
+    }
+}
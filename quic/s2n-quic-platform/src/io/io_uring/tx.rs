@@ -0,0 +1,166 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    buffer::default as buffer,
+    message::{msg, queue, Message as _},
+};
+use io_uring::{opcode, squeue::SubmissionQueue, types};
+use s2n_quic_core::event;
+use std::collections::VecDeque;
+
+/// Transmits the occupied messages in the queue with batches of `sendmsg` operations
+///
+/// The kernel reads the payloads directly from the message queue so each message stays
+/// occupied until its operation has completed. Since operations can complete out of order,
+/// messages are only released back to the free segment once all of the messages before them
+/// have also completed.
+pub struct Queue {
+    queue: queue::Queue<msg::Ring<buffer::Buffer>>,
+    /// The sequence number of the first occupied message
+    head: u64,
+    /// Tracks the completion state of each submitted message, starting at `head`
+    in_flight: VecDeque<bool>,
+}
+
+impl Queue {
+    pub fn new(max_segments: usize) -> Self {
+        let queue = queue::Queue::new(msg::Ring::new(buffer::Buffer::default(), max_segments));
+
+        Self {
+            queue,
+            head: 0,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    pub fn max_gso(&self) -> usize {
+        self.queue.max_gso()
+    }
+
+    pub fn disable_gso(&mut self) {
+        self.queue.disable_gso()
+    }
+
+    /// Returns `true` if any submitted operations have not yet completed
+    pub fn is_in_flight(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// Pushes a `sendmsg` operation for each occupied message that hasn't been submitted
+    ///
+    /// Each operation's `user_data` is the message's sequence number added to
+    /// `user_data_offset`. Returns `false` if the submission queue filled up before every
+    /// message was pushed.
+    pub fn push(
+        &mut self,
+        submission: &mut SubmissionQueue,
+        fd: types::Fd,
+        user_data_offset: u64,
+    ) -> bool {
+        let submitted = self.in_flight.len();
+        let mut entries = self.queue.occupied_mut();
+
+        for entry in entries[submitted..].iter_mut() {
+            let sequence = self.head + self.in_flight.len() as u64;
+            let sqe = opcode::SendMsg::new(fd, entry.as_mut_ptr() as *const libc::msghdr)
+                .build()
+                .user_data(user_data_offset + sequence);
+
+            // Safety: the message stays occupied, and therefore unmodified, until the
+            // operation has completed
+            if unsafe { submission.push(&sqe) }.is_err() {
+                return false;
+            }
+
+            self.in_flight.push_back(false);
+        }
+
+        true
+    }
+
+    /// Records the completion of the operation for the message with the `sequence` number
+    pub fn on_completion(&mut self, sequence: u64) {
+        let index = (sequence - self.head) as usize;
+        debug_assert!(!self.in_flight[index], "completed the same message twice");
+        self.in_flight[index] = true;
+    }
+
+    /// Records the failure of a completed operation
+    ///
+    /// Transmission errors are ignored, same as the `msg` socket queue, unless they indicate that
+    /// GSO isn't supported by the socket.
+    pub fn on_error<Pub: event::EndpointPublisher>(&mut self, errno: i32, publisher: &mut Pub) {
+        publisher.on_platform_tx_error(event::builder::PlatformTxError { errno });
+
+        // check to see if we need to disable GSO
+        if errno == libc::EIO && self.max_gso() > 1 {
+            // unfortunately we've already assembled GSO packets so any other in-flight messages
+            // will be dropped as well
+            self.disable_gso();
+
+            publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
+                configuration: event::builder::PlatformFeatureConfiguration::Gso {
+                    max_segments: self.max_gso(),
+                },
+            });
+        }
+    }
+
+    /// Releases all of the messages that have completed in order
+    pub fn finish(&mut self) {
+        let mut count = 0;
+
+        while let Some(true) = self.in_flight.front() {
+            self.in_flight.pop_front();
+            count += 1;
+        }
+
+        if count > 0 {
+            self.head += count as u64;
+            self.queue.occupied_mut().finish(count);
+        }
+    }
+
+    pub fn tx_queue(&mut self) -> queue::Free<'_, msg::Message> {
+        self.queue.free_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gso_error_test() {
+        let max_segments = crate::features::get().gso.max_segments();
+        if max_segments == 1 {
+            eprintln!("The current platform does not support GSO; skipping");
+            return;
+        }
+
+        let mut queue = Queue::new(max_segments);
+        let mut publisher = event::testing::Publisher::no_snapshot();
+
+        // other errors are ignored
+        queue.on_error(libc::ECONNREFUSED, &mut publisher);
+        assert_eq!(queue.max_gso(), max_segments);
+        assert_eq!(publisher.platform_tx_error, 1);
+        assert_eq!(publisher.platform_feature_configured, 0);
+
+        // EIO indicates the socket doesn't support GSO
+        queue.on_error(libc::EIO, &mut publisher);
+        assert_eq!(queue.max_gso(), 1);
+        assert_eq!(publisher.platform_tx_error, 2);
+        assert_eq!(publisher.platform_feature_configured, 1);
+
+        // GSO is only disabled once
+        queue.on_error(libc::EIO, &mut publisher);
+        assert_eq!(publisher.platform_tx_error, 3);
+        assert_eq!(publisher.platform_feature_configured, 1);
+    }
+}
@@ -0,0 +1,726 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An IO implementation which exchanges datagrams over a simulated, in-memory [`Network`]
+//!
+//! The event loop runs on the Tokio runtime and reads the time from the Tokio clock. On a
+//! current-thread runtime with a paused clock, tasks are scheduled in a fixed order and time jumps
+//! straight to the next timer or datagram arrival, so the simulation doesn't wait on the wall
+//! clock. Together with the seeded [`Network`], the same transmissions result in the same
+//! deliveries. Note that endpoints may still behave differently between runs if their own
+//! randomness, e.g. from the TLS provider, changes the size or number of datagrams they send.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::future::poll_fn;
+use s2n_quic_core::{
+    endpoint::Endpoint,
+    event::{self, EndpointPublisher as _},
+    inet::SocketAddress,
+    io::{rx, tx},
+    path::{self, Handle as _, LocalAddress, MaxMtu},
+    time::{self, Clock as ClockTrait, Timestamp},
+};
+use std::{convert::TryInto, io, io::ErrorKind, net::SocketAddr};
+use tokio::{
+    runtime::Handle,
+    time::{sleep_until, Instant, Sleep},
+};
+
+mod network;
+
+use network::Datagram;
+pub use network::{Link, Network};
+
+pub type PathHandle = path::Tuple;
+
+/// The number of datagrams the endpoint can transmit before they are handed to the network
+const TX_CAPACITY: usize = 64;
+
+#[derive(Debug)]
+pub struct Io {
+    builder: Builder,
+}
+
+impl Io {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Creates an IO provider which binds to `addr` on the given `network`
+    pub fn new<A: std::net::ToSocketAddrs>(network: &Network, addr: A) -> io::Result<Self> {
+        let address = addr.to_socket_addrs()?.next().expect("missing address");
+        let builder = Builder::default()
+            .with_network(network.clone())
+            .with_address(address)?;
+        Ok(Self { builder })
+    }
+
+    pub fn start<E: Endpoint<PathHandle = PathHandle>>(
+        self,
+        mut endpoint: E,
+    ) -> io::Result<(tokio::task::JoinHandle<()>, SocketAddress)> {
+        let Builder {
+            handle,
+            network,
+            addr,
+            max_mtu,
+        } = self.builder;
+
+        let network =
+            network.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "missing network"))?;
+        let addr =
+            addr.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "missing bind address"))?;
+
+        endpoint.set_max_mtu(max_mtu);
+
+        let handle = if let Some(handle) = handle {
+            handle
+        } else {
+            Handle::try_current().map_err(|err| io::Error::new(ErrorKind::InvalidInput, err))?
+        };
+
+        // the clock needs to be created inside of the runtime to read the Tokio time
+        let guard = handle.enter();
+        let clock = Clock::default();
+
+        let mut publisher = event::EndpointPublisherSubscriber::new(
+            event::builder::EndpointMeta {
+                endpoint_type: E::ENDPOINT_TYPE,
+                timestamp: clock.get_time(),
+            },
+            None,
+            endpoint.subscriber(),
+        );
+
+        publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
+            configuration: event::builder::PlatformFeatureConfiguration::MaxMtu {
+                mtu: max_mtu.into(),
+            },
+        });
+
+        let local_addr = network.bind(addr)?;
+
+        let instance = Instance {
+            clock,
+            binding: Binding {
+                network,
+                addrs: vec![local_addr],
+            },
+            max_mtu,
+            endpoint,
+        };
+
+        let task = handle.spawn(instance.event_loop());
+
+        drop(guard);
+
+        Ok((task, local_addr.into()))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    handle: Option<Handle>,
+    network: Option<Network>,
+    addr: Option<SocketAddr>,
+    max_mtu: MaxMtu,
+}
+
+impl Builder {
+    #[must_use]
+    pub fn with_handle(mut self, handle: Handle) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Sets the network the endpoint sends and receives datagrams on
+    #[must_use]
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Sets the address the endpoint binds to on the network
+    ///
+    /// An unspecified IP is replaced with a unique address and port 0 is replaced with an unused
+    /// port. The final address is returned from [`Io::start`].
+    pub fn with_address(mut self, addr: SocketAddr) -> io::Result<Self> {
+        self.addr = Some(addr);
+        Ok(self)
+    }
+
+    /// Sets the largest maximum transmission unit (MTU) that can be sent on a path
+    pub fn with_max_mtu(mut self, max_mtu: u16) -> io::Result<Self> {
+        self.max_mtu = max_mtu
+            .try_into()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidInput, format!("{}", err)))?;
+        Ok(self)
+    }
+
+    pub fn build(self) -> io::Result<Io> {
+        Ok(Io { builder: self })
+    }
+}
+
+/// The addresses the endpoint is bound to on the network
+///
+/// The first address is the one the endpoint was started with. Endpoints which send from another
+/// local IP, e.g. a client migrating to a new interface, are bound to that IP with the same port
+/// on first use. All of the addresses are released once the event loop has stopped.
+#[derive(Debug)]
+struct Binding {
+    network: Network,
+    addrs: Vec<SocketAddr>,
+}
+
+impl Binding {
+    /// Returns the address that datagrams for `local_address` are sent from
+    fn source(&mut self, local_address: LocalAddress) -> SocketAddr {
+        let primary = self.addrs[0];
+        let local_addr: SocketAddr = local_address.unmap().into();
+
+        if local_addr.ip().is_unspecified() || local_addr.ip() == primary.ip() {
+            return primary;
+        }
+
+        let addr = SocketAddr::new(local_addr.ip(), primary.port());
+        if !self.addrs.contains(&addr) && self.network.bind(addr).is_ok() {
+            self.addrs.push(addr);
+        }
+
+        addr
+    }
+
+    /// Moves all of the datagrams which have arrived at any of the addresses into the `queue`
+    ///
+    /// The time at which the next datagram arrives is returned.
+    fn receive(&self, queue: &mut Vec<Datagram>, cx: &mut Context<'_>) -> Option<Instant> {
+        self.addrs
+            .iter()
+            .filter_map(|addr| self.network.receive(*addr, queue, cx))
+            .min()
+    }
+}
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        for addr in &self.addrs {
+            self.network.unbind(*addr);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Instance<E> {
+    clock: Clock,
+    binding: Binding,
+    max_mtu: MaxMtu,
+    endpoint: E,
+}
+
+impl<E: Endpoint<PathHandle = PathHandle>> Instance<E> {
+    async fn event_loop(self) {
+        let Self {
+            clock,
+            binding,
+            max_mtu,
+            mut endpoint,
+        } = self;
+
+        let local_address: SocketAddress = binding.addrs[0].into();
+
+        let mut rx = RxQueue {
+            local_address: local_address.into(),
+            entries: vec![],
+        };
+        let mut tx = TxQueue {
+            binding,
+            max_mtu: u16::from(max_mtu) as usize,
+            entries: Vec::with_capacity(TX_CAPACITY),
+        };
+
+        let mut timer = Timer::new(&clock);
+        let mut arrival = Timer::new(&clock);
+
+        // wake up immediately so the endpoint can start transmitting
+        timer.update(Some(clock.0));
+
+        loop {
+            let mut timeout_expired = false;
+            let mut application_wakeup = false;
+
+            let is_open = poll_fn(|cx| {
+                let mut should_wake = false;
+
+                if let Poll::Ready(wakeup) = endpoint.poll_wakeups(cx, &clock) {
+                    if wakeup.is_err() {
+                        // The endpoint has shut down
+                        return Poll::Ready(false);
+                    }
+                    should_wake = true;
+                    application_wakeup = true;
+                }
+
+                if timer.poll(cx).is_ready() {
+                    should_wake = true;
+                    timeout_expired = true;
+                }
+
+                // the arrival timer only wakes the task; the datagrams are read from the network
+                arrival.update(tx.binding.receive(&mut rx.entries, cx));
+                let _ = arrival.poll(cx);
+
+                if !rx.entries.is_empty() {
+                    should_wake = true;
+                }
+
+                if should_wake {
+                    Poll::Ready(true)
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+
+            if !is_open {
+                return;
+            }
+
+            let subscriber = endpoint.subscriber();
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: E::ENDPOINT_TYPE,
+                    timestamp: clock.get_time(),
+                },
+                None,
+                subscriber,
+            );
+
+            publisher.on_platform_event_loop_wakeup(event::builder::PlatformEventLoopWakeup {
+                timeout_expired,
+                rx_ready: !rx.entries.is_empty(),
+                tx_ready: false,
+                application_wakeup,
+            });
+
+            if !rx.entries.is_empty() {
+                publisher.on_platform_rx(event::builder::PlatformRx {
+                    count: rx.entries.len(),
+                });
+                endpoint.receive(&mut rx, &clock);
+            }
+
+            // keep transmitting until the endpoint stops filling the queue
+            loop {
+                endpoint.transmit(&mut tx, &clock);
+
+                let is_full = !tx::Queue::has_capacity(&tx);
+                let count = tx.entries.len();
+
+                for datagram in tx.entries.drain(..) {
+                    tx.binding.network.send(datagram);
+                }
+
+                if count > 0 {
+                    let subscriber = endpoint.subscriber();
+                    let mut publisher = event::EndpointPublisherSubscriber::new(
+                        event::builder::EndpointMeta {
+                            endpoint_type: E::ENDPOINT_TYPE,
+                            timestamp: clock.get_time(),
+                        },
+                        None,
+                        subscriber,
+                    );
+                    publisher.on_platform_tx(event::builder::PlatformTx { count });
+                }
+
+                if !is_full {
+                    break;
+                }
+            }
+
+            timer.update(endpoint.timeout().map(|timeout| clock.instant(timeout)));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RxQueue {
+    local_address: LocalAddress,
+    entries: Vec<Datagram>,
+}
+
+impl rx::Queue for RxQueue {
+    type Entry = Datagram;
+    type Handle = PathHandle;
+
+    fn local_address(&self) -> LocalAddress {
+        self.local_address
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [Self::Entry] {
+        &mut self.entries
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn finish(&mut self, count: usize) {
+        self.entries.drain(..count);
+    }
+}
+
+#[derive(Debug)]
+struct TxQueue {
+    binding: Binding,
+    max_mtu: usize,
+    entries: Vec<Datagram>,
+}
+
+impl tx::Queue for TxQueue {
+    type Entry = Datagram;
+    type Handle = PathHandle;
+
+    const SUPPORTS_ECN: bool = true;
+
+    fn push<M: tx::Message<Handle = Self::Handle>>(
+        &mut self,
+        message: M,
+    ) -> Result<tx::Outcome, tx::Error> {
+        use tx::Entry as _;
+
+        if self.entries.len() == TX_CAPACITY {
+            return Err(tx::Error::AtCapacity);
+        }
+
+        let source = self.binding.source(message.path_handle().local_address());
+        let mut datagram = Datagram::new(source, self.max_mtu);
+        let len = datagram.set(message)?;
+        let index = self.entries.len();
+        self.entries.push(datagram);
+
+        Ok(tx::Outcome { len, index })
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [Self::Entry] {
+        &mut self.entries
+    }
+
+    fn capacity(&self) -> usize {
+        TX_CAPACITY - self.entries.len()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// A clock which reads the Tokio time
+#[derive(Clone, Debug)]
+struct Clock(Instant);
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Clock {
+    /// Converts a timestamp from this clock to a Tokio `Instant`
+    fn instant(&self, timestamp: Timestamp) -> Instant {
+        let duration = unsafe {
+            // Safety: the same clock epoch is being used
+            timestamp.as_duration()
+        };
+        self.0 + duration
+    }
+}
+
+impl ClockTrait for Clock {
+    fn get_time(&self) -> time::Timestamp {
+        let duration = self.0.elapsed();
+        unsafe {
+            // Safety: time duration is only derived from a single `Instant`
+            time::Timestamp::from_duration(duration)
+        }
+    }
+}
+
+/// A timer which expires at exactly the requested time
+///
+/// Unlike the timer for the socket-based IO, the target isn't rounded to milliseconds. With a
+/// paused clock, time doesn't advance while the task is busy, so a target that was rounded down
+/// would keep expiring without the endpoint making progress.
+#[derive(Debug)]
+struct Timer {
+    target: Option<Instant>,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl Timer {
+    fn new(clock: &Clock) -> Self {
+        Self {
+            target: None,
+            sleep: Box::pin(sleep_until(clock.0)),
+        }
+    }
+
+    fn update(&mut self, target: Option<Instant>) {
+        if let Some(target) = target {
+            if self.target != Some(target) {
+                self.sleep.as_mut().reset(target);
+            }
+        }
+        self.target = target;
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.target.is_none() {
+            return Poll::Pending;
+        }
+
+        let res = self.sleep.as_mut().poll(cx);
+
+        if res.is_ready() {
+            // clear the target after it fires, otherwise we'll endlessly wake up the task
+            self.target = None;
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{convert::TryInto, time::Duration};
+    use s2n_quic_core::{
+        endpoint::{self, CloseError},
+        io::rx::Entry as _,
+        path::Handle as _,
+    };
+    use std::collections::BTreeMap;
+
+    /// Sends numbered messages to the peer until each one has been echoed back
+    struct Sender {
+        peer: SocketAddress,
+        messages: BTreeMap<u32, Option<Timestamp>>,
+        now: Option<Timestamp>,
+        subscriber: NoopSubscriber,
+    }
+
+    /// Echoes every datagram back to where it came from
+    #[derive(Default)]
+    struct Echo {
+        pending: Vec<(PathHandle, Vec<u8>)>,
+        subscriber: NoopSubscriber,
+    }
+
+    #[derive(Debug, Default)]
+    struct NoopSubscriber;
+
+    impl event::Subscriber for NoopSubscriber {
+        type ConnectionContext = ();
+
+        fn create_connection_context(
+            &mut self,
+            _meta: &event::api::ConnectionMeta,
+            _info: &event::api::ConnectionInfo,
+        ) -> Self::ConnectionContext {
+        }
+    }
+
+    impl Endpoint for Sender {
+        type PathHandle = PathHandle;
+        type Subscriber = NoopSubscriber;
+
+        const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Client;
+
+        fn transmit<Tx: tx::Queue<Handle = PathHandle>, C: ClockTrait>(
+            &mut self,
+            queue: &mut Tx,
+            clock: &C,
+        ) {
+            let now = clock.get_time();
+            self.now = Some(now);
+
+            for (id, tx_time) in &mut self.messages {
+                match tx_time {
+                    Some(time)
+                        if now.saturating_duration_since(*time) < Duration::from_millis(50) =>
+                    {
+                        continue
+                    }
+                    _ => {
+                        let addr = PathHandle::from_remote_address(self.peer.into());
+                        if queue.push((addr, id.to_be_bytes())).is_ok() {
+                            *tx_time = Some(now);
+                        } else {
+                            // no more capacity
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        fn receive<Rx: rx::Queue<Handle = PathHandle>, C: ClockTrait>(
+            &mut self,
+            queue: &mut Rx,
+            _clock: &C,
+        ) {
+            let local_address = queue.local_address();
+            let entries = queue.as_slice_mut();
+            let len = entries.len();
+            for entry in entries {
+                if let Some((_header, payload)) = entry.read(&local_address) {
+                    let id = (&*payload).try_into().unwrap();
+                    self.messages.remove(&u32::from_be_bytes(id));
+                }
+            }
+            queue.finish(len);
+        }
+
+        fn poll_wakeups<C: ClockTrait>(
+            &mut self,
+            _cx: &mut Context<'_>,
+            _clock: &C,
+        ) -> Poll<Result<usize, CloseError>> {
+            if self.messages.is_empty() {
+                return Err(CloseError).into();
+            }
+
+            Poll::Pending
+        }
+
+        fn timeout(&self) -> Option<Timestamp> {
+            self.now.map(|now| now + Duration::from_millis(50))
+        }
+
+        fn set_max_mtu(&mut self, _max_mtu: MaxMtu) {
+            // noop
+        }
+
+        fn subscriber(&mut self) -> &mut Self::Subscriber {
+            &mut self.subscriber
+        }
+    }
+
+    impl Endpoint for Echo {
+        type PathHandle = PathHandle;
+        type Subscriber = NoopSubscriber;
+
+        const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Server;
+
+        fn transmit<Tx: tx::Queue<Handle = PathHandle>, C: ClockTrait>(
+            &mut self,
+            queue: &mut Tx,
+            _clock: &C,
+        ) {
+            while let Some(message) = self.pending.pop() {
+                if queue.push((message.0, &message.1)).is_err() {
+                    self.pending.push(message);
+                    return;
+                }
+            }
+        }
+
+        fn receive<Rx: rx::Queue<Handle = PathHandle>, C: ClockTrait>(
+            &mut self,
+            queue: &mut Rx,
+            _clock: &C,
+        ) {
+            let local_address = queue.local_address();
+            let entries = queue.as_slice_mut();
+            let len = entries.len();
+            for entry in entries {
+                if let Some((header, payload)) = entry.read(&local_address) {
+                    self.pending.push((header.path, payload.to_vec()));
+                }
+            }
+            queue.finish(len);
+        }
+
+        fn poll_wakeups<C: ClockTrait>(
+            &mut self,
+            _cx: &mut Context<'_>,
+            _clock: &C,
+        ) -> Poll<Result<usize, CloseError>> {
+            Poll::Pending
+        }
+
+        fn timeout(&self) -> Option<Timestamp> {
+            None
+        }
+
+        fn set_max_mtu(&mut self, _max_mtu: MaxMtu) {
+            // noop
+        }
+
+        fn subscriber(&mut self) -> &mut Self::Subscriber {
+            &mut self.subscriber
+        }
+    }
+
+    /// Sends messages over a lossy network and returns the number of datagrams that were sent
+    /// and how long it took
+    async fn test(seed: u64) -> io::Result<(u64, Duration)> {
+        let network = Network::new(seed);
+        network.set_default_link(
+            Link::default()
+                .with_latency(Duration::from_millis(10))
+                .with_jitter(Duration::from_millis(2))
+                .with_loss(0.3)
+                .with_bandwidth(10_000_000),
+        );
+
+        let (_server, server_addr) = Io::new(&network, "10.0.0.1:443")?.start(Echo::default())?;
+
+        let sender = Sender {
+            peer: server_addr,
+            messages: (0..1000).map(|id| (id, None)).collect(),
+            now: None,
+            subscriber: Default::default(),
+        };
+        let start = Instant::now();
+        let (client, _client_addr) = Io::new(&network, "0.0.0.0:0")?.start(sender)?;
+        client.await?;
+
+        Ok((network.sent(), start.elapsed()))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lossy_network_test() -> io::Result<()> {
+        let (sent, elapsed) = test(123).await?;
+
+        // each message is retransmitted every 50ms until the echo comes back
+        assert!(sent > 2000, "{}", sent);
+        assert!(elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+
+        // the same seed results in exactly the same outcome
+        assert_eq!(test(123).await?, (sent, elapsed));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn mtu_test() -> io::Result<()> {
+        let network = Network::new(0);
+        let io = Io::builder()
+            .with_network(network)
+            .with_address("10.0.0.1:443".parse().unwrap())?
+            .with_max_mtu(1300)?
+            .build()?;
+        let (_task, addr) = io.start(Echo::default())?;
+        assert_eq!(addr, "10.0.0.1:443".parse::<SocketAddr>().unwrap().into());
+
+        assert!(Io::builder().with_max_mtu(1000).is_err());
+
+        Ok(())
+    }
+}
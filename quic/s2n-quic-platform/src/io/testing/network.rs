@@ -0,0 +1,582 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::{
+    task::{Context, Waker},
+    time::Duration,
+};
+use s2n_quic_core::{
+    inet::{ExplicitCongestionNotification, SocketAddress},
+    io::{rx, tx},
+    path::{self, Handle as _, LocalAddress, UDP_HEADER_LEN},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::time::Instant;
+
+const IPV4_HEADER_LEN: u16 = 20;
+const IPV6_HEADER_LEN: u16 = 40;
+
+/// The first port handed out to hosts which bind to port 0
+const EPHEMERAL_PORT_START: u16 = 49152;
+
+/// The conditions applied to datagrams traveling in one direction between two hosts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Link {
+    latency: Duration,
+    jitter: Duration,
+    loss: f64,
+    reorder: f64,
+    mtu: u16,
+    bandwidth: Option<u64>,
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_secs(0),
+            jitter: Duration::from_secs(0),
+            loss: 0.0,
+            reorder: 0.0,
+            mtu: 1500,
+            bandwidth: None,
+        }
+    }
+}
+
+impl Link {
+    /// Sets the one-way delay of the link
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the maximum amount of time added to or removed from the latency of each datagram
+    ///
+    /// Jitter on its own does not reorder datagrams; use `with_reorder` for that.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the probability, between `0.0` and `1.0`, that a datagram is dropped
+    #[must_use]
+    pub fn with_loss(mut self, probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "loss probability must be between 0.0 and 1.0"
+        );
+        self.loss = probability;
+        self
+    }
+
+    /// Sets the probability, between `0.0` and `1.0`, that a datagram skips the latency of the
+    /// link and overtakes the datagrams which are already in flight
+    #[must_use]
+    pub fn with_reorder(mut self, probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "reorder probability must be between 0.0 and 1.0"
+        );
+        self.reorder = probability;
+        self
+    }
+
+    /// Sets the largest IP packet that can traverse the link
+    ///
+    /// Larger datagrams are dropped, as they would be with the Don't Fragment bit set.
+    #[must_use]
+    pub fn with_mtu(mut self, mtu: u16) -> Self {
+        self.mtu = mtu;
+        self
+    }
+
+    /// Limits the rate of the link, in bits per second
+    ///
+    /// Datagrams queue behind each other while the link is busy, without limit.
+    #[must_use]
+    pub fn with_bandwidth(mut self, bits_per_second: u64) -> Self {
+        assert_ne!(bits_per_second, 0, "bandwidth must be greater than 0");
+        self.bandwidth = Some(bits_per_second);
+        self
+    }
+
+    fn transmission_time(&self, ip_len: usize) -> Duration {
+        if let Some(bandwidth) = self.bandwidth {
+            let nanos = (ip_len as u128 * 8 * 1_000_000_000) / bandwidth as u128;
+            Duration::from_nanos(nanos as u64)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+}
+
+/// A simulated network connecting the hosts which are bound to it
+///
+/// All of the random decisions the network makes are drawn from a generator seeded by the
+/// caller, so the same sequence of transmissions always results in the same deliveries.
+///
+/// The network reads the time from the Tokio clock. When the runtime's clock is paused
+/// (see [`tokio::time::pause`]), time only advances while every task is idle, so simulated
+/// delays cost no wall-clock time.
+#[derive(Clone, Debug)]
+pub struct Network {
+    state: Arc<Mutex<State>>,
+}
+
+impl Network {
+    /// Creates a network which draws its random decisions from the given `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State::new(seed))),
+        }
+    }
+
+    /// Sets the link used between hosts which don't have a link configured with `set_link`
+    pub fn set_default_link(&self, link: Link) {
+        self.lock().default_link = link;
+    }
+
+    /// Sets the link used for datagrams sent from the `from` host to the `to` host
+    ///
+    /// Links are unidirectional; call this method twice to configure both directions. Changes
+    /// apply to datagrams sent afterwards, so a partition can be simulated by switching to a link
+    /// with a loss probability of `1.0`.
+    pub fn set_link(&self, from: IpAddr, to: IpAddr, link: Link) {
+        self.lock().links.insert((from, to), link);
+    }
+
+    /// Returns the number of datagrams that were sent on the network
+    pub fn sent(&self) -> u64 {
+        self.lock().sent
+    }
+
+    /// Returns the number of datagrams that were dropped by the network
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    pub(super) fn bind(&self, addr: SocketAddr) -> io::Result<SocketAddr> {
+        self.lock().bind(addr)
+    }
+
+    pub(super) fn unbind(&self, addr: SocketAddr) {
+        self.lock().hosts.remove(&addr);
+    }
+
+    pub(super) fn send(&self, datagram: Datagram) {
+        self.lock().send(datagram, Instant::now())
+    }
+
+    /// Moves all of the datagrams which have arrived at `addr` into the `queue`
+    ///
+    /// The time at which the next datagram arrives is returned.
+    pub(super) fn receive(
+        &self,
+        addr: SocketAddr,
+        queue: &mut Vec<Datagram>,
+        cx: &mut Context<'_>,
+    ) -> Option<Instant> {
+        let mut state = self.lock();
+        let host = state.hosts.get_mut(&addr)?;
+        let now = Instant::now();
+
+        while let Some(key) = host.inbox.keys().next().copied() {
+            if key.0 > now {
+                break;
+            }
+            queue.extend(host.inbox.remove(&key));
+        }
+
+        host.waker = Some(cx.waker().clone());
+
+        host.inbox.keys().next().map(|(arrival, _)| *arrival)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    rng: Rng,
+    default_link: Link,
+    links: HashMap<(IpAddr, IpAddr), Link>,
+    queues: HashMap<(IpAddr, IpAddr), LinkQueue>,
+    hosts: HashMap<SocketAddr, Host>,
+    next_port: u16,
+    next_host: u32,
+    /// Breaks ties between datagrams arriving at the same time
+    sequence: u64,
+    sent: u64,
+    dropped: u64,
+}
+
+impl State {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            default_link: Link::default(),
+            links: HashMap::new(),
+            queues: HashMap::new(),
+            hosts: HashMap::new(),
+            next_port: EPHEMERAL_PORT_START,
+            next_host: 1,
+            sequence: 0,
+            sent: 0,
+            dropped: 0,
+        }
+    }
+
+    fn bind(&mut self, mut addr: SocketAddr) -> io::Result<SocketAddr> {
+        // hosts don't have interfaces, so unspecified addresses are given a unique one
+        if addr.ip().is_unspecified() {
+            let id = self.next_host;
+            self.next_host += 1;
+            let ip: IpAddr = if addr.is_ipv4() {
+                // 10.0.0.0/8
+                (0x0a00_0000 | (id & 0x00ff_ffff)).to_be_bytes().into()
+            } else {
+                // fd00::/8
+                ((0xfd00u128 << 112) | id as u128).to_be_bytes().into()
+            };
+            addr.set_ip(ip);
+        }
+
+        if addr.port() == 0 {
+            for _ in EPHEMERAL_PORT_START..=u16::MAX {
+                addr.set_port(self.next_port);
+                self.next_port = self
+                    .next_port
+                    .checked_add(1)
+                    .unwrap_or(EPHEMERAL_PORT_START);
+
+                if !self.hosts.contains_key(&addr) {
+                    break;
+                }
+            }
+        }
+
+        if self.hosts.contains_key(&addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is already bound to the network", addr),
+            ));
+        }
+
+        self.hosts.insert(addr, Host::default());
+
+        Ok(addr)
+    }
+
+    fn send(&mut self, datagram: Datagram, now: Instant) {
+        self.sent += 1;
+
+        let key = (datagram.source.ip(), datagram.destination.ip());
+        let link = self.links.get(&key).copied().unwrap_or(self.default_link);
+
+        let ip_header_len = if datagram.destination.is_ipv4() {
+            IPV4_HEADER_LEN
+        } else {
+            IPV6_HEADER_LEN
+        };
+        let ip_len = datagram.payload.len() + (UDP_HEADER_LEN + ip_header_len) as usize;
+
+        if ip_len > link.mtu as usize || self.rng.gen_bool(link.loss) {
+            self.dropped += 1;
+            return;
+        }
+
+        let queue = self.queues.entry(key).or_insert(LinkQueue {
+            next_departure: now,
+            last_arrival: now,
+        });
+
+        // wait for the datagrams in front to finish transmitting
+        let departure = queue.next_departure.max(now);
+        let departure = departure + link.transmission_time(ip_len);
+        queue.next_departure = departure;
+
+        let arrival = if link.reorder > 0.0 && self.rng.gen_bool(link.reorder) {
+            departure
+        } else {
+            let jitter = link.jitter.as_nanos() as u64;
+            let offset = self.rng.gen_range(jitter * 2 + 1);
+            let latency = (link.latency + Duration::from_nanos(offset))
+                .checked_sub(link.jitter)
+                .unwrap_or_default();
+
+            // keep datagrams in order unless they were chosen to be reordered
+            let arrival = (departure + latency).max(queue.last_arrival);
+            queue.last_arrival = arrival;
+            arrival
+        };
+
+        // the destination may have been closed
+        let host = if let Some(host) = self.hosts.get_mut(&datagram.destination) {
+            host
+        } else {
+            self.dropped += 1;
+            return;
+        };
+
+        let sequence = self.sequence;
+        self.sequence += 1;
+        host.inbox.insert((arrival, sequence), datagram);
+
+        if let Some(waker) = host.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LinkQueue {
+    /// The time at which the link finishes transmitting the datagrams already sent
+    next_departure: Instant,
+    /// The arrival time of the last datagram that was delivered in order
+    last_arrival: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Host {
+    inbox: BTreeMap<(Instant, u64), Datagram>,
+    waker: Option<Waker>,
+}
+
+/// A datagram traveling through the network
+#[derive(Clone, Debug)]
+pub(super) struct Datagram {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub ecn: ExplicitCongestionNotification,
+    pub payload: Vec<u8>,
+}
+
+impl Datagram {
+    /// Creates an empty datagram which can hold `capacity` bytes
+    pub fn new(source: SocketAddr, capacity: usize) -> Self {
+        Self {
+            source,
+            destination: source,
+            ecn: ExplicitCongestionNotification::default(),
+            payload: vec![0; capacity],
+        }
+    }
+}
+
+impl rx::Entry for Datagram {
+    type Handle = path::Tuple;
+
+    fn read(
+        &mut self,
+        _local_address: &LocalAddress,
+    ) -> Option<(
+        s2n_quic_core::inet::datagram::Header<Self::Handle>,
+        &mut [u8],
+    )> {
+        let remote_address: SocketAddress = self.source.into();
+        // endpoints can be bound to several addresses, so the datagram's destination is used
+        let local_address: SocketAddress = self.destination.into();
+        let header = s2n_quic_core::inet::datagram::Header {
+            path: path::Tuple {
+                remote_address: remote_address.into(),
+                local_address: local_address.into(),
+            },
+            ecn: self.ecn,
+        };
+        Some((header, &mut self.payload[..]))
+    }
+}
+
+impl tx::Entry for Datagram {
+    type Handle = path::Tuple;
+
+    fn set<M: tx::Message<Handle = Self::Handle>>(
+        &mut self,
+        mut message: M,
+    ) -> Result<usize, tx::Error> {
+        let len = message.write_payload(&mut self.payload[..], 0);
+
+        if len == 0 {
+            return Err(tx::Error::EmptyPayload);
+        }
+
+        self.payload.truncate(len);
+        self.destination = message.path_handle().remote_address().unmap().into();
+        self.ecn = message.ecn();
+
+        Ok(len)
+    }
+
+    fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.payload
+    }
+}
+
+/// A small, seedable pseudo-random number generator (SplitMix64)
+///
+/// The network doesn't need cryptographic randomness, only a stream of numbers that is
+/// identical across runs and platforms for the same seed.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn gen_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns `true` with the given probability
+    fn gen_bool(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        // use the upper 53 bits to produce a uniform value in [0, 1)
+        let value = (self.gen_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        value < probability
+    }
+
+    /// Returns a value in `[0, bound)`, or `0` if `bound` is `0`
+    fn gen_range(&mut self, bound: u64) -> u64 {
+        if bound <= 1 {
+            return 0;
+        }
+        self.gen_u64() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    fn datagram(source: &str, destination: &str, len: usize) -> Datagram {
+        Datagram {
+            source: addr(source),
+            destination: addr(destination),
+            ecn: ExplicitCongestionNotification::default(),
+            payload: vec![0; len],
+        }
+    }
+
+    /// Returns the arrival times of the datagrams waiting for `addr`, relative to `now`
+    fn arrivals(state: &State, addr: &str, now: Instant) -> Vec<Duration> {
+        state.hosts[&self::addr(addr)]
+            .inbox
+            .keys()
+            .map(|(arrival, _)| *arrival - now)
+            .collect()
+    }
+
+    #[test]
+    fn bind_test() {
+        let mut state = State::new(0);
+
+        assert_eq!(
+            state.bind(addr("10.0.0.1:443")).unwrap(),
+            addr("10.0.0.1:443")
+        );
+        assert_eq!(
+            state.bind(addr("10.0.0.1:443")).unwrap_err().kind(),
+            io::ErrorKind::AddrInUse
+        );
+
+        assert_eq!(
+            state.bind(addr("10.0.0.1:0")).unwrap(),
+            addr("10.0.0.1:49152")
+        );
+        assert_eq!(
+            state.bind(addr("0.0.0.0:0")).unwrap(),
+            addr("10.0.0.1:49153")
+        );
+        assert_eq!(state.bind(addr("[::]:0")).unwrap(), addr("[fd00::2]:49154"));
+    }
+
+    #[test]
+    fn latency_test() {
+        let mut state = State::new(0);
+        state.bind(addr("10.0.0.2:443")).unwrap();
+        state.default_link = Link::default()
+            .with_latency(Duration::from_millis(50))
+            .with_bandwidth(8_000_000);
+
+        let now = Instant::now();
+        // 1000 bytes of payload + 28 bytes of headers take ~1ms on a 8Mbps link
+        state.send(datagram("10.0.0.1:1", "10.0.0.2:443", 972), now);
+        state.send(datagram("10.0.0.1:1", "10.0.0.2:443", 972), now);
+
+        assert_eq!(
+            arrivals(&state, "10.0.0.2:443", now),
+            vec![Duration::from_millis(51), Duration::from_millis(52)]
+        );
+    }
+
+    #[test]
+    fn mtu_test() {
+        let mut state = State::new(0);
+        state.bind(addr("10.0.0.2:443")).unwrap();
+        state.default_link = Link::default().with_mtu(1200);
+
+        let now = Instant::now();
+        state.send(datagram("10.0.0.1:1", "10.0.0.2:443", 1172), now);
+        state.send(datagram("10.0.0.1:1", "10.0.0.2:443", 1173), now);
+
+        assert_eq!(arrivals(&state, "10.0.0.2:443", now).len(), 1);
+        assert_eq!(state.dropped, 1);
+    }
+
+    #[test]
+    fn loss_test() {
+        fn run(seed: u64) -> Vec<Duration> {
+            let mut state = State::new(seed);
+            state.bind(addr("10.0.0.2:443")).unwrap();
+            state.links.insert(
+                ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()),
+                Link::default()
+                    .with_loss(0.25)
+                    .with_bandwidth(1_000_000)
+                    .with_jitter(Duration::from_millis(10))
+                    .with_reorder(0.1),
+            );
+
+            let now = Instant::now();
+            for _ in 0..1000 {
+                state.send(datagram("10.0.0.1:1", "10.0.0.2:443", 100), now);
+            }
+            arrivals(&state, "10.0.0.2:443", now)
+        }
+
+        let first = run(123);
+
+        // roughly a quarter of the datagrams were dropped
+        assert!((650..850).contains(&first.len()), "{}", first.len());
+
+        // the same seed results in the same deliveries
+        assert_eq!(first, run(123));
+        assert_ne!(first, run(456));
+    }
+}
@@ -22,7 +22,7 @@ use tokio::{net::UdpSocket, runtime::Handle};
 
 pub type PathHandle = socket::Handle;
 
-mod clock;
+pub(crate) mod clock;
 use clock::Clock;
 
 impl crate::socket::std::Socket for UdpSocket {
@@ -137,118 +137,7 @@ impl Io {
             rx_socket.set_recv_buffer_size(size)?;
         }
 
-        fn convert_addr_to_std(addr: socket2::SockAddr) -> io::Result<std::net::SocketAddr> {
-            addr.as_socket().ok_or_else(|| {
-                io::Error::new(io::ErrorKind::InvalidInput, "invalid domain for socket")
-            })
-        }
-
-        #[allow(unused_variables)] // some platform builds won't use these so ignore warnings
-        let (tx_addr, rx_addr) = (
-            convert_addr_to_std(tx_socket.local_addr()?)?,
-            convert_addr_to_std(rx_socket.local_addr()?)?,
-        );
-
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-14
-        //# UDP datagrams MUST NOT be fragmented at the IP layer.
-
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-14
-        //# In IPv4 [IPv4], the Don't Fragment (DF) bit MUST be set if possible, to
-        //# prevent fragmentation on the path.
-
-        //= https://www.rfc-editor.org/rfc/rfc8899#section-3
-        //# In IPv4, a probe packet MUST be sent with the Don't
-        //# Fragment (DF) bit set in the IP header and without network layer
-        //# endpoint fragmentation.
-
-        //= https://www.rfc-editor.org/rfc/rfc8899#section-4.5
-        //# A PL implementing this specification MUST suspend network layer
-        //# processing of outgoing packets that enforces a PMTU
-        //# [RFC1191][RFC8201] for each flow utilizing DPLPMTUD and instead use
-        //# DPLPMTUD to control the size of packets that are sent by a flow.
-        #[cfg(s2n_quic_platform_mtu_disc)]
-        {
-            use std::os::unix::io::AsRawFd;
-            if tx_addr.is_ipv4() {
-                // IP_PMTUDISC_PROBE setting will set the DF (Don't Fragment) flag
-                // while also ignoring the Path MTU. This means packets will not
-                // be fragmented, and the EMSGSIZE error will not be returned for
-                // packets larger than the Path MTU according to the kernel.
-                libc!(setsockopt(
-                    tx_socket.as_raw_fd(),
-                    libc::IPPROTO_IP,
-                    libc::IP_MTU_DISCOVER,
-                    &libc::IP_PMTUDISC_PROBE as *const _ as _,
-                    core::mem::size_of_val(&libc::IP_PMTUDISC_PROBE) as _,
-                ))?;
-            } else {
-                libc!(setsockopt(
-                    tx_socket.as_raw_fd(),
-                    libc::IPPROTO_IPV6,
-                    libc::IPV6_MTU_DISCOVER,
-                    &libc::IP_PMTUDISC_PROBE as *const _ as _,
-                    core::mem::size_of_val(&libc::IP_PMTUDISC_PROBE) as _,
-                ))?;
-            }
-        }
-
-        // Set up the RX socket to pass ECN information
-        #[cfg(s2n_quic_platform_tos)]
-        {
-            use std::os::unix::io::AsRawFd;
-            let enabled: libc::c_int = 1;
-
-            // This option needs to be enabled regardless of domain (IPv4 vs IPv6), except on mac
-            if rx_addr.is_ipv4() || !cfg!(any(target_os = "macos", target_os = "ios")) {
-                libc!(setsockopt(
-                    rx_socket.as_raw_fd(),
-                    libc::IPPROTO_IP,
-                    libc::IP_RECVTOS,
-                    &enabled as *const _ as _,
-                    core::mem::size_of_val(&enabled) as _,
-                ))?;
-            }
-
-            if rx_addr.is_ipv6() {
-                libc!(setsockopt(
-                    rx_socket.as_raw_fd(),
-                    libc::IPPROTO_IPV6,
-                    libc::IPV6_RECVTCLASS,
-                    &enabled as *const _ as _,
-                    core::mem::size_of_val(&enabled) as _,
-                ))?;
-            }
-        }
-        publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
-            configuration: event::builder::PlatformFeatureConfiguration::Ecn {
-                enabled: cfg!(s2n_quic_platform_tos),
-            },
-        });
-
-        // Set up the RX socket to pass information about the local address and interface
-        #[cfg(s2n_quic_platform_pktinfo)]
-        {
-            use std::os::unix::io::AsRawFd;
-            let enabled: libc::c_int = 1;
-
-            if rx_addr.is_ipv4() {
-                libc!(setsockopt(
-                    rx_socket.as_raw_fd(),
-                    libc::IPPROTO_IP,
-                    libc::IP_PKTINFO,
-                    &enabled as *const _ as _,
-                    core::mem::size_of_val(&enabled) as _,
-                ))?;
-            } else {
-                libc!(setsockopt(
-                    rx_socket.as_raw_fd(),
-                    libc::IPPROTO_IPV6,
-                    libc::IPV6_RECVPKTINFO,
-                    &enabled as *const _ as _,
-                    core::mem::size_of_val(&enabled) as _,
-                ))?;
-            }
-        }
+        let rx_addr = configure_sockets(&rx_socket, &tx_socket, &mut publisher)?;
 
         cfg_if! {
             if #[cfg(any(s2n_quic_platform_socket_msg, s2n_quic_platform_socket_mmsg))] {
@@ -294,7 +183,133 @@ impl Io {
     }
 }
 
-fn bind<A: std::net::ToSocketAddrs>(addr: A, reuse_port: bool) -> io::Result<socket2::Socket> {
+/// Configures the socket options required by the endpoint on the rx and tx sockets
+///
+/// Returns the local address of the rx socket.
+pub(crate) fn configure_sockets<P: event::EndpointPublisher>(
+    rx_socket: &socket2::Socket,
+    tx_socket: &socket2::Socket,
+    publisher: &mut P,
+) -> io::Result<std::net::SocketAddr> {
+    fn convert_addr_to_std(addr: socket2::SockAddr) -> io::Result<std::net::SocketAddr> {
+        addr.as_socket()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid domain for socket"))
+    }
+
+    #[allow(unused_variables)] // some platform builds won't use these so ignore warnings
+    let (tx_addr, rx_addr) = (
+        convert_addr_to_std(tx_socket.local_addr()?)?,
+        convert_addr_to_std(rx_socket.local_addr()?)?,
+    );
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-14
+    //# UDP datagrams MUST NOT be fragmented at the IP layer.
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-14
+    //# In IPv4 [IPv4], the Don't Fragment (DF) bit MUST be set if possible, to
+    //# prevent fragmentation on the path.
+
+    //= https://www.rfc-editor.org/rfc/rfc8899#section-3
+    //# In IPv4, a probe packet MUST be sent with the Don't
+    //# Fragment (DF) bit set in the IP header and without network layer
+    //# endpoint fragmentation.
+
+    //= https://www.rfc-editor.org/rfc/rfc8899#section-4.5
+    //# A PL implementing this specification MUST suspend network layer
+    //# processing of outgoing packets that enforces a PMTU
+    //# [RFC1191][RFC8201] for each flow utilizing DPLPMTUD and instead use
+    //# DPLPMTUD to control the size of packets that are sent by a flow.
+    #[cfg(s2n_quic_platform_mtu_disc)]
+    {
+        use std::os::unix::io::AsRawFd;
+        if tx_addr.is_ipv4() {
+            // IP_PMTUDISC_PROBE setting will set the DF (Don't Fragment) flag
+            // while also ignoring the Path MTU. This means packets will not
+            // be fragmented, and the EMSGSIZE error will not be returned for
+            // packets larger than the Path MTU according to the kernel.
+            libc!(setsockopt(
+                tx_socket.as_raw_fd(),
+                libc::IPPROTO_IP,
+                libc::IP_MTU_DISCOVER,
+                &libc::IP_PMTUDISC_PROBE as *const _ as _,
+                core::mem::size_of_val(&libc::IP_PMTUDISC_PROBE) as _,
+            ))?;
+        } else {
+            libc!(setsockopt(
+                tx_socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_MTU_DISCOVER,
+                &libc::IP_PMTUDISC_PROBE as *const _ as _,
+                core::mem::size_of_val(&libc::IP_PMTUDISC_PROBE) as _,
+            ))?;
+        }
+    }
+
+    // Set up the RX socket to pass ECN information
+    #[cfg(s2n_quic_platform_tos)]
+    {
+        use std::os::unix::io::AsRawFd;
+        let enabled: libc::c_int = 1;
+
+        // This option needs to be enabled regardless of domain (IPv4 vs IPv6), except on mac
+        if rx_addr.is_ipv4() || !cfg!(any(target_os = "macos", target_os = "ios")) {
+            libc!(setsockopt(
+                rx_socket.as_raw_fd(),
+                libc::IPPROTO_IP,
+                libc::IP_RECVTOS,
+                &enabled as *const _ as _,
+                core::mem::size_of_val(&enabled) as _,
+            ))?;
+        }
+
+        if rx_addr.is_ipv6() {
+            libc!(setsockopt(
+                rx_socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_RECVTCLASS,
+                &enabled as *const _ as _,
+                core::mem::size_of_val(&enabled) as _,
+            ))?;
+        }
+    }
+    publisher.on_platform_feature_configured(event::builder::PlatformFeatureConfigured {
+        configuration: event::builder::PlatformFeatureConfiguration::Ecn {
+            enabled: cfg!(s2n_quic_platform_tos),
+        },
+    });
+
+    // Set up the RX socket to pass information about the local address and interface
+    #[cfg(s2n_quic_platform_pktinfo)]
+    {
+        use std::os::unix::io::AsRawFd;
+        let enabled: libc::c_int = 1;
+
+        if rx_addr.is_ipv4() {
+            libc!(setsockopt(
+                rx_socket.as_raw_fd(),
+                libc::IPPROTO_IP,
+                libc::IP_PKTINFO,
+                &enabled as *const _ as _,
+                core::mem::size_of_val(&enabled) as _,
+            ))?;
+        } else {
+            libc!(setsockopt(
+                rx_socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_RECVPKTINFO,
+                &enabled as *const _ as _,
+                core::mem::size_of_val(&enabled) as _,
+            ))?;
+        }
+    }
+
+    Ok(rx_addr)
+}
+
+pub(crate) fn bind<A: std::net::ToSocketAddrs>(
+    addr: A,
+    reuse_port: bool,
+) -> io::Result<socket2::Socket> {
     use socket2::{Domain, Protocol, Socket, Type};
 
     let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
@@ -113,6 +113,16 @@ impl path::Handle for Handle {
         }
     }
 
+    #[inline]
+    fn set_local_address(&mut self, local_address: LocalAddress) {
+        #[cfg(s2n_quic_platform_pktinfo)]
+        {
+            self.local_address = local_address;
+        }
+
+        let _ = local_address;
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         let mut eq = true;
@@ -78,7 +78,28 @@ impl Interop {
         // Handshake Loss (multiconnect): Tests resilience of the handshake to high loss.
         // The client is expected to establish multiple connections, sequential or in parallel,
         // and use each connection to download a single file.
-        if let Some(Testcase::Multiconnect) = self.testcase {
+        if let Some(Testcase::Resumption) = self.testcase {
+            // https://github.com/marten-seemann/quic-interop-runner#test-cases
+            // Resumption: Tests QUIC session resumption. The client is expected to establish a
+            // connection and download the first file. It then closes the connection and uses
+            // the session ticket to establish a second connection to download the remaining files.
+            let (first, remaining) = self.requests.split_first().unwrap();
+
+            for requests in [vec![first], remaining.iter().collect::<Vec<_>>()] {
+                let connect = match requests.first() {
+                    Some(request) => endpoints.get(&request.host().unwrap()).unwrap().clone(),
+                    None => continue,
+                };
+                h09::create_connection(
+                    client.clone(),
+                    connect,
+                    requests,
+                    download_dir.clone(),
+                    self.keep_alive,
+                )
+                .await?;
+            }
+        } else if let Some(Testcase::Multiconnect) = self.testcase {
             for request in &self.requests {
                 let connect = endpoints.get(&request.host().unwrap()).unwrap().clone();
                 let requests = core::iter::once(request);
@@ -153,6 +174,7 @@ impl Interop {
                         self.application_protocols.iter().map(String::as_bytes),
                     )?
                     .with_key_logging()?
+                    .with_session_tickets()?
                     .build()?;
                 client.with_tls(tls)?.start().unwrap()
             }
@@ -165,6 +187,7 @@ impl Interop {
                         self.application_protocols.iter().map(String::as_bytes),
                     )?
                     .with_key_logging()?
+                    .with_session_tickets()?
                     .build()?;
                 client.with_tls(tls)?.start().unwrap()
             }
@@ -231,8 +254,7 @@ fn is_supported_testcase(testcase: Testcase) -> bool {
         // TODO add the ability to trigger a key update from the application
         KeyUpdate => false,
         Retry => true,
-        // TODO support storing tickets
-        Resumption => false,
+        Resumption => true,
         // TODO implement 0rtt
         ZeroRtt => false,
         Http3 => true,
@@ -151,7 +151,9 @@ impl Interop {
                 tls::s2n::private_key(self.private_key.as_ref())?,
             )?
             .with_application_protocols(self.application_protocols.iter().map(String::as_bytes))?
-            .with_key_logging()?;
+            .with_key_logging()?
+            // the interop runner only requires that tickets are resumable by the same instance
+            .with_session_ticket_key(b"qns", &SESSION_TICKET_KEY, std::time::SystemTime::now())?;
 
         cfg_if::cfg_if! {
             if #[cfg(all(
@@ -167,6 +169,13 @@ impl Interop {
     }
 }
 
+/// The key used to encrypt session tickets
+///
+/// This is only suitable for testing; production servers should use a secret, randomly-generated
+/// key that is rotated regularly.
+#[cfg(unix)]
+const SESSION_TICKET_KEY: [u8; 32] = [42; 32];
+
 fn is_supported_testcase(testcase: Testcase) -> bool {
     use Testcase::*;
     match testcase {
@@ -177,8 +186,7 @@ fn is_supported_testcase(testcase: Testcase) -> bool {
         // KeyUpdate is client only
         KeyUpdate => false,
         Retry => true,
-        // TODO support issuing tickets
-        Resumption => false,
+        Resumption => true,
         // TODO implement 0rtt
         ZeroRtt => false,
         Http3 => true,
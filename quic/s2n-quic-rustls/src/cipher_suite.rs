@@ -77,18 +77,23 @@ pub struct PacketKeys {
     opener: PacketKey,
 }
 
+/// Converts a rustls cipher suite into the s2n-quic representation
+pub(crate) fn cipher_suite(cipher_suite: CipherSuite) -> tls::CipherSuite {
+    match cipher_suite {
+        CipherSuite::TLS13_AES_128_GCM_SHA256 => tls::CipherSuite::TLS_AES_128_GCM_SHA256,
+        CipherSuite::TLS13_AES_256_GCM_SHA384 => tls::CipherSuite::TLS_AES_256_GCM_SHA384,
+        CipherSuite::TLS13_CHACHA20_POLY1305_SHA256 => {
+            tls::CipherSuite::TLS_CHACHA20_POLY1305_SHA256
+        }
+        _ => tls::CipherSuite::Unknown,
+    }
+}
+
 impl PacketKeys {
     pub(crate) fn new(keys: quic::Keys, cipher_suite: CipherSuite) -> (Self, HeaderProtectionKeys) {
         let quic::Keys { local, remote } = keys;
 
-        let cipher_suite = match cipher_suite {
-            CipherSuite::TLS13_AES_128_GCM_SHA256 => tls::CipherSuite::TLS_AES_128_GCM_SHA256,
-            CipherSuite::TLS13_AES_256_GCM_SHA384 => tls::CipherSuite::TLS_AES_256_GCM_SHA384,
-            CipherSuite::TLS13_CHACHA20_POLY1305_SHA256 => {
-                tls::CipherSuite::TLS_CHACHA20_POLY1305_SHA256
-            }
-            _ => tls::CipherSuite::Unknown,
-        };
+        let cipher_suite = self::cipher_suite(cipher_suite);
 
         let (sealer_packet, sealer_header) = PacketKey::new(local, cipher_suite);
         let (opener_packet, opener_header) = PacketKey::new(remote, cipher_suite);
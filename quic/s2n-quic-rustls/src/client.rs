@@ -3,19 +3,27 @@
 
 use crate::{certificate, encode_transport_parameters, session::Session};
 use core::convert::TryFrom;
-use rustls::{quic, ClientConfig};
+use rustls::{client::StoresClientSessions, quic, ClientConfig};
 use s2n_codec::EncoderValue;
-use s2n_quic_core::{application::ServerName, crypto::tls};
-use std::sync::Arc;
+use s2n_quic_core::{
+    application::ServerName,
+    crypto::tls::{self, session_ticket},
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 pub struct Client {
     config: Arc<ClientConfig>,
+    session_ticket_store: Option<Arc<dyn session_ticket::Store>>,
 }
 
 impl Client {
     pub fn new(config: ClientConfig) -> Self {
         Self {
             config: Arc::new(config),
+            session_ticket_store: None,
         }
     }
 
@@ -42,6 +50,8 @@ impl From<ClientConfig> for Client {
 impl tls::Endpoint for Client {
     type Session = Session;
 
+    const QUIC_VERSIONS: &'static [u32] = crate::QUIC_VERSIONS;
+
     fn new_server_session<Params: EncoderValue>(
         &mut self,
         _transport_parameters: &Params,
@@ -63,15 +73,32 @@ impl tls::Endpoint for Client {
         let rustls_server_name =
             rustls::ServerName::try_from(server_name.as_ref()).expect("invalid server name");
 
+        let (config, session_storage) = if let Some(store) = self.session_ticket_store.as_ref() {
+            // rustls looks up sessions by server name so a storage adapter is created for each
+            // connection to track when a ticket is received
+            let session_storage = Arc::new(SessionStorage::new(store.clone(), server_name.clone()));
+            let mut config = (*self.config).clone();
+            config.session_storage = session_storage.clone();
+            (Arc::new(config), Some(session_storage))
+        } else {
+            (self.config.clone(), None)
+        };
+
         let session = rustls::ClientConnection::new_quic(
-            self.config.clone(),
+            config,
             crate::QUIC_VERSION,
             rustls_server_name,
             transport_parameters,
         )
         .expect("could not create rustls client session");
 
-        Session::new(session.into(), Some(server_name))
+        let mut session = Session::new(session.into(), Some(server_name));
+
+        if let Some(session_storage) = session_storage {
+            session.receive_session_tickets(session_storage);
+        }
+
+        session
     }
 
     fn max_tag_length(&self) -> usize {
@@ -83,6 +110,9 @@ pub struct Builder {
     cert_store: rustls::RootCertStore,
     application_protocols: Vec<Vec<u8>>,
     key_log: Option<Arc<dyn rustls::KeyLog>>,
+    early_data: bool,
+    session_ticket_store: Option<Arc<dyn session_ticket::Store>>,
+    client_identity: Option<(certificate::Certificate, certificate::PrivateKey)>,
 }
 
 impl Default for Builder {
@@ -97,6 +127,9 @@ impl Builder {
             cert_store: rustls::RootCertStore::empty(),
             application_protocols: vec![b"h3".to_vec()],
             key_log: None,
+            early_data: false,
+            session_ticket_store: None,
+            client_identity: None,
         }
     }
 
@@ -114,6 +147,21 @@ impl Builder {
         Ok(self)
     }
 
+    /// Authenticates the client with the certificate chain when the server requests it
+    pub fn with_client_identity<
+        C: certificate::IntoCertificate,
+        PK: certificate::IntoPrivateKey,
+    >(
+        mut self,
+        certificate: C,
+        private_key: PK,
+    ) -> Result<Self, rustls::Error> {
+        let certificate = certificate.into_certificate()?;
+        let private_key = private_key.into_private_key()?;
+        self.client_identity = Some((certificate, private_key));
+        Ok(self)
+    }
+
     pub fn with_max_cert_chain_depth(self, len: u16) -> Result<Self, rustls::Error> {
         // TODO is there a way to configure this?
         let _ = len;
@@ -133,6 +181,37 @@ impl Builder {
         Ok(self)
     }
 
+    /// Passes the TLS secrets of each connection to `key_log`
+    pub fn with_key_log<K: 'static + rustls::KeyLog>(
+        mut self,
+        key_log: K,
+    ) -> Result<Self, rustls::Error> {
+        self.key_log = Some(Arc::new(key_log));
+        Ok(self)
+    }
+
+    /// Enables sending 0-RTT data when resuming a session
+    ///
+    /// Data sent before the handshake completes may be replayed by an attacker.
+    pub fn with_early_data(mut self, enabled: bool) -> Result<Self, rustls::Error> {
+        self.early_data = enabled;
+        Ok(self)
+    }
+
+    /// Stores session tickets issued by servers and offers them on later connections
+    pub fn with_session_ticket_store<S: session_ticket::Store>(
+        mut self,
+        store: S,
+    ) -> Result<Self, rustls::Error> {
+        self.session_ticket_store = Some(Arc::new(store));
+        Ok(self)
+    }
+
+    /// Uses an in-memory store for session tickets
+    pub fn with_session_tickets(self) -> Result<Self, rustls::Error> {
+        self.with_session_ticket_store(session_ticket::memory::Store::default())
+    }
+
     pub fn build(self) -> Result<Client, rustls::Error> {
         // TODO load system root store?
         if self.cert_store.is_empty() {
@@ -143,20 +222,77 @@ impl Builder {
             ));
         }
 
-        let mut config = ClientConfig::builder()
+        let builder = ClientConfig::builder()
             .with_cipher_suites(crate::cipher_suite::DEFAULT_CIPHERSUITES)
             .with_safe_default_kx_groups()
             .with_protocol_versions(crate::PROTOCOL_VERSIONS)?
-            .with_root_certificates(self.cert_store)
-            .with_no_client_auth();
+            .with_root_certificates(self.cert_store);
+
+        let mut config = if let Some((certificate, private_key)) = self.client_identity {
+            builder.with_single_cert(certificate.0, private_key.0)?
+        } else {
+            builder.with_no_client_auth()
+        };
 
         config.max_fragment_size = None;
         config.alpn_protocols = self.application_protocols;
 
+        config.enable_early_data = self.early_data;
+
         if let Some(key_log) = self.key_log {
             config.key_log = key_log;
         }
 
-        Ok(Client::new(config))
+        let mut client = Client::new(config);
+        client.session_ticket_store = self.session_ticket_store;
+        Ok(client)
+    }
+}
+
+/// Prefix of the keys rustls uses for session values
+const SESSION_KEY_PREFIX: &[u8] = b"session";
+
+/// Adapts a [`session_ticket::Store`] to the rustls session storage for a single connection
+pub(crate) struct SessionStorage {
+    store: Arc<dyn session_ticket::Store>,
+    server_name: ServerName,
+    has_received_ticket: AtomicBool,
+}
+
+impl SessionStorage {
+    fn new(store: Arc<dyn session_ticket::Store>, server_name: ServerName) -> Self {
+        Self {
+            store,
+            server_name,
+            has_received_ticket: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` if the server issued a ticket on the connection
+    pub(crate) fn has_received_ticket(&self) -> bool {
+        self.has_received_ticket.load(Ordering::Relaxed)
+    }
+}
+
+impl StoresClientSessions for SessionStorage {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        // other values, like key exchange hints, are not persisted
+        if !key.starts_with(SESSION_KEY_PREFIX) {
+            return false;
+        }
+
+        self.store.insert(&self.server_name, value.into());
+        self.has_received_ticket.store(true, Ordering::Relaxed);
+        true
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        if !key.starts_with(SESSION_KEY_PREFIX) {
+            return None;
+        }
+
+        self.store
+            .take(&self.server_name)
+            .map(|ticket| ticket.to_vec())
     }
 }
@@ -23,6 +23,12 @@ static PROTOCOL_VERSIONS: &[&rustls::SupportedProtocolVersion] = &[&rustls::vers
 /// The supported version of quic
 const QUIC_VERSION: rustls::quic::Version = rustls::quic::Version::V1;
 
+/// The QUIC versions that rustls is able to derive packet protection keys for
+///
+/// rustls derives the handshake and 1-RTT keys with the QUIC version 1 labels, so QUIC version 2
+/// is not supported.
+const QUIC_VERSIONS: &[u32] = &[s2n_quic_core::packet::long::VERSION_1];
+
 /// Encodes transport parameters into a byte vec
 pub(crate) fn encode_transport_parameters<Params: s2n_codec::EncoderValue>(
     params: &Params,
@@ -57,3 +63,44 @@ fn client_server_test() {
 
     pair.finish();
 }
+
+#[test]
+fn resumption_test() {
+    use s2n_quic_core::crypto::tls::{self, testing::certificates::*, Session as _};
+
+    let mut client = client::Builder::new()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_session_tickets()
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut server = server::Builder::new()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // the first connection receives a session ticket, which the second one uses
+    for &is_resumed in &[false, true] {
+        let mut pair = tls::testing::Pair::new(&mut server, &mut client, "localhost".into());
+
+        while pair.is_handshaking() {
+            pair.poll(None).unwrap();
+        }
+
+        pair.finish();
+
+        // deliver the session ticket to the client
+        pair.client.context.transfer(&mut pair.server.context);
+        let _ = pair.client.session.poll(&mut pair.client.context);
+
+        for info in &[
+            &pair.client.context.handshake_info,
+            &pair.server.context.handshake_info,
+        ] {
+            assert_eq!(info.as_ref().unwrap().is_resumed, is_resumed);
+        }
+    }
+}
@@ -40,6 +40,8 @@ impl From<ServerConfig> for Server {
 impl tls::Endpoint for Server {
     type Session = Session;
 
+    const QUIC_VERSIONS: &'static [u32] = crate::QUIC_VERSIONS;
+
     fn new_server_session<Params: EncoderValue>(
         &mut self,
         transport_parameters: &Params,
@@ -71,12 +73,19 @@ impl tls::Endpoint for Server {
     fn max_tag_length(&self) -> usize {
         s2n_quic_crypto::MAX_TAG_LEN
     }
+
+    fn supports_early_data(&self) -> bool {
+        self.config.max_early_data_size > 0
+    }
 }
 
 pub struct Builder {
     cert_resolver: Option<Arc<dyn rustls::server::ResolvesServerCert>>,
     application_protocols: Vec<Vec<u8>>,
     key_log: Option<Arc<dyn rustls::KeyLog>>,
+    early_data: bool,
+    client_authentication: bool,
+    trusted_certificates: rustls::RootCertStore,
 }
 
 impl Default for Builder {
@@ -91,6 +100,9 @@ impl Builder {
             cert_resolver: None,
             application_protocols: vec![b"h3".to_vec()],
             key_log: None,
+            early_data: false,
+            client_authentication: false,
+            trusted_certificates: rustls::RootCertStore::empty(),
         }
     }
 
@@ -106,6 +118,27 @@ impl Builder {
         self.with_cert_resolver(resolver)
     }
 
+    /// Adds a certificate which is trusted to issue client certificates
+    pub fn with_trusted_certificate<C: certificate::IntoCertificate>(
+        mut self,
+        certificate: C,
+    ) -> Result<Self, rustls::Error> {
+        let certificates = certificate.into_certificate()?;
+        let root_certificate = certificates.0.first().ok_or_else(|| {
+            rustls::Error::General("Certificate chain needs to have at least one entry".to_string())
+        })?;
+        self.trusted_certificates
+            .add(root_certificate)
+            .map_err(|err| rustls::Error::General(err.to_string()))?;
+        Ok(self)
+    }
+
+    /// Requires clients to authenticate with a certificate issued by a trusted certificate
+    pub fn with_client_authentication(mut self) -> Result<Self, rustls::Error> {
+        self.client_authentication = true;
+        Ok(self)
+    }
+
     pub fn with_cert_resolver(
         mut self,
         cert_resolver: Arc<dyn rustls::server::ResolvesServerCert>,
@@ -127,12 +160,42 @@ impl Builder {
         Ok(self)
     }
 
+    /// Passes the TLS secrets of each connection to `key_log`
+    pub fn with_key_log<K: 'static + rustls::KeyLog>(
+        mut self,
+        key_log: K,
+    ) -> Result<Self, rustls::Error> {
+        self.key_log = Some(Arc::new(key_log));
+        Ok(self)
+    }
+
+    /// Enables accepting 0-RTT data from resuming clients
+    ///
+    /// Early data is not protected against replay attacks. The server endpoint's `early_data`
+    /// provider makes the final decision for each connection.
+    pub fn with_early_data(mut self, enabled: bool) -> Result<Self, rustls::Error> {
+        self.early_data = enabled;
+        Ok(self)
+    }
+
     pub fn build(self) -> Result<Server, rustls::Error> {
         let builder = ServerConfig::builder()
             .with_cipher_suites(crate::cipher_suite::DEFAULT_CIPHERSUITES)
             .with_safe_default_kx_groups()
-            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?
-            .with_no_client_auth();
+            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?;
+
+        let builder = if self.client_authentication {
+            if self.trusted_certificates.is_empty() {
+                return Err(rustls::Error::General(
+                    "missing trusted certificate(s) for client authentication".to_string(),
+                ));
+            }
+            let verifier =
+                rustls::server::AllowAnyAuthenticatedClient::new(self.trusted_certificates);
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
 
         let mut config = if let Some(cert_resolver) = self.cert_resolver {
             builder.with_cert_resolver(cert_resolver)
@@ -146,6 +209,12 @@ impl Builder {
         config.max_fragment_size = None;
         config.alpn_protocols = self.application_protocols;
 
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-4.6.1
+        //# Servers MUST NOT send
+        //# the early_data extension with a max_early_data_size field set to any
+        //# value other than 0xffffffff.
+        config.max_early_data_size = if self.early_data { u32::MAX } else { 0 };
+
         if let Some(key_log) = self.key_log {
             config.key_log = key_log;
         }
@@ -1,8 +1,9 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::cipher_suite::{
-    HeaderProtectionKey, HeaderProtectionKeys, OneRttKey, PacketKey, PacketKeys,
+use crate::{
+    cipher_suite::{HeaderProtectionKey, HeaderProtectionKeys, OneRttKey, PacketKey, PacketKeys},
+    client::SessionStorage,
 };
 use bytes::Bytes;
 use core::{fmt, fmt::Debug, task::Poll};
@@ -10,11 +11,17 @@ use rustls::{
     quic::{self, QuicExt},
     Connection,
 };
+use s2n_codec::DecoderBuffer;
 use s2n_quic_core::{
     application::ServerName,
-    crypto::{self, tls, CryptoError},
+    crypto::{
+        self,
+        tls::{self, server_hello::ServerHello, HandshakeHeader, HandshakeType},
+        CryptoError,
+    },
     transport,
 };
+use std::sync::Arc;
 
 pub struct Session {
     connection: Connection,
@@ -25,6 +32,12 @@ pub struct Session {
     emitted_server_name: bool,
     emitted_application_protocol: bool,
     server_name: Option<ServerName>,
+    // Buffers the Initial CRYPTO data on clients until the ServerHello has been read
+    server_hello: Vec<u8>,
+    // Set on clients once the ServerHello has been read
+    is_resumed: Option<bool>,
+    // Set on clients that wait for the server to issue a session ticket
+    session_storage: Option<Arc<SessionStorage>>,
 }
 
 impl fmt::Debug for Session {
@@ -47,10 +60,28 @@ impl Session {
             emitted_server_name: false,
             emitted_application_protocol: false,
             server_name,
+            server_hello: Vec::new(),
+            is_resumed: None,
+            session_storage: None,
         }
     }
 
+    /// Keeps the session active after the handshake to receive a session ticket from the server
+    pub(crate) fn receive_session_tickets(&mut self, session_storage: Arc<SessionStorage>) {
+        self.session_storage = Some(session_storage);
+    }
+
+    fn is_awaiting_session_ticket(&self) -> bool {
+        self.session_storage
+            .as_ref()
+            .map_or(false, |storage| !storage.has_received_ticket())
+    }
+
     fn receive(&mut self, crypto_data: &[u8]) -> Result<(), transport::Error> {
+        if self.rx_phase == HandshakePhase::Initial {
+            self.read_server_hello(crypto_data);
+        }
+
         self.connection
             .read_hs(crypto_data)
             .map_err(crate::error::reason)
@@ -124,6 +155,87 @@ impl Session {
         }
     }
 
+    /// Reads the ServerHello on clients to determine if the session was resumed
+    ///
+    /// rustls doesn't expose this for clients, but servers only include the `pre_shared_key`
+    /// extension in the ServerHello when they accept the session ticket offered by the client.
+    fn read_server_hello(&mut self, crypto_data: &[u8]) {
+        if self.is_resumed.is_some() || matches!(self.connection, Connection::Server(_)) {
+            return;
+        }
+
+        self.server_hello.extend_from_slice(crypto_data);
+
+        loop {
+            let buffer = DecoderBuffer::new(&self.server_hello);
+            let (header, buffer) = match buffer.decode::<HandshakeHeader>() {
+                Ok(value) => value,
+                // wait for the rest of the header
+                Err(_) => return,
+            };
+            let (body, _) = match buffer.decode_slice(header.len()) {
+                Ok(value) => value,
+                // wait for the rest of the message
+                Err(_) => return,
+            };
+
+            // a malformed ServerHello fails the handshake in rustls
+            let server_hello = match header.msg_type() {
+                Some(HandshakeType::ServerHello) => ServerHello::decode(body).ok(),
+                _ => None,
+            };
+
+            match server_hello {
+                // a HelloRetryRequest is followed by another ServerHello
+                Some(server_hello) if server_hello.is_hello_retry_request() => {
+                    let len = core::mem::size_of::<HandshakeHeader>() + header.len();
+                    self.server_hello.drain(..len);
+                }
+                Some(server_hello) => {
+                    let is_resumed = server_hello
+                        .extension(tls::server_hello::PRE_SHARED_KEY)
+                        .map_or(false, |extension| extension.is_some());
+                    self.is_resumed = Some(is_resumed);
+                    self.server_hello = Vec::new();
+                    return;
+                }
+                None => {
+                    self.is_resumed = Some(false);
+                    self.server_hello = Vec::new();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handshake_info(&self) -> tls::HandshakeInfo {
+        let cipher_suite = self
+            .connection
+            .negotiated_cipher_suite()
+            .map_or(tls::CipherSuite::Unknown, |suite| {
+                crate::cipher_suite::cipher_suite(suite.suite())
+            });
+
+        let peer_certificate_chain = self
+            .connection
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .map(|certificate| Bytes::copy_from_slice(&certificate.0))
+            .collect();
+
+        let is_resumed = match &self.connection {
+            Connection::Client(_) => self.is_resumed.unwrap_or(false),
+            Connection::Server(server) => server.received_resumption_data().is_some(),
+        };
+
+        tls::HandshakeInfo {
+            cipher_suite,
+            peer_certificate_chain,
+            is_resumed,
+        }
+    }
+
     fn zero_rtt_keys(&mut self) -> Option<quic::DirectionalKeys> {
         if self.emitted_zero_rtt_keys {
             return None;
@@ -149,13 +261,15 @@ impl Session {
             // the handshake is complete!
             if !self.emitted_handshake_complete {
                 self.rx_phase.transition();
+                context.on_handshake_info(self.handshake_info())?;
                 context.on_handshake_complete()?;
             }
 
             self.emitted_handshake_complete = true;
         }
 
-        if self.emitted_handshake_complete {
+        // the server only issues tickets after the handshake has completed
+        if self.emitted_handshake_complete && !self.is_awaiting_session_ticket() {
             Poll::Ready(Ok(()))
         } else {
             Poll::Pending
@@ -184,10 +298,6 @@ impl Session {
                 // If there's nothing to receive then we're done for now
             }
 
-            if let Poll::Ready(()) = self.poll_complete_handshake(context)? {
-                return Poll::Ready(Ok(()));
-            }
-
             // mark that we tried to receive some data so we know next time we loop
             // to bail if nothing changed
             has_tried_receive = true;
@@ -251,6 +361,12 @@ impl Session {
                         quic::KeyChange::OneRtt { keys, next } => {
                             let (key, header_key) = OneRttKey::new(keys, next, cipher_suite);
 
+                            if let Connection::Client(client) = &self.connection {
+                                if self.emitted_zero_rtt_keys && !client.is_early_data_accepted() {
+                                    context.on_zero_rtt_rejected()?;
+                                }
+                            }
+
                             let application_parameters = self.application_parameters()?;
 
                             context.on_one_rtt_keys(key, header_key, application_parameters)?;
@@ -262,6 +378,12 @@ impl Session {
                     }
                 }
             }
+
+            // check for completion after writing so the server is able to send any
+            // post-handshake messages, like NewSessionTicket
+            if let Poll::Ready(()) = self.poll_complete_handshake(context)? {
+                return Poll::Ready(Ok(()));
+            }
         }
     }
 
@@ -304,12 +426,21 @@ impl tls::Session for Session {
         &mut self,
         context: &mut C,
     ) -> Poll<Result<(), transport::Error>> {
+        // the endpoint only establishes connections with the versions in `QUIC_VERSIONS`
+        debug_assert!(crate::QUIC_VERSIONS.contains(&context.quic_version()));
+
         let result = self.poll_impl(context);
         // attempt to emit server_name and application_protocol events prior to possibly
         // returning with an error
         self.emit_events(context)?;
         result
     }
+
+    fn reject_early_data(&mut self) {
+        if let Connection::Server(server) = &mut self.connection {
+            server.reject_early_data();
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
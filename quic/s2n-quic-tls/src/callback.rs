@@ -214,10 +214,14 @@ where
                 // Flush the send buffer before transitioning to the next phase
                 self.flush();
 
+                // the packet protection labels depend on the negotiated QUIC version
+                let version = self.context.quic_version();
+
                 match self.state.tx_phase {
                     HandshakePhase::Initial => {
-                        let (key, header_key) = HandshakeKey::new(self.endpoint, aead_algo, pair)
-                            .expect("invalid cipher");
+                        let (key, header_key) =
+                            HandshakeKey::new(self.endpoint, version, aead_algo, pair)
+                                .expect("invalid cipher");
 
                         self.context.on_handshake_keys(key, header_key)?;
                         self.state.tx_phase.transition();
@@ -225,7 +229,8 @@ where
                     }
                     _ => {
                         let (key, header_key) =
-                            OneRttKey::new(self.endpoint, aead_algo, pair).expect("invalid cipher");
+                            OneRttKey::new(self.endpoint, version, aead_algo, pair)
+                                .expect("invalid cipher");
 
                         let params = unsafe {
                             // Safety: conn needs to outlive params
@@ -303,6 +308,18 @@ where
         }
     }
 
+    /// Transitions to the application space once s2n-tls completes the handshake
+    pub fn on_handshake_complete(&mut self) {
+        // The client's Finished message is still buffered and belongs in the Handshake space.
+        // The server only has post-handshake messages buffered at this point, such as
+        // NewSessionTicket, which are sent in the application space.
+        if self.endpoint.is_client() {
+            self.flush();
+        }
+
+        self.state.on_handshake_complete();
+    }
+
     /// The function s2n-tls calls when it wants to receive data
     unsafe extern "C" fn recv_cb(
         context: *mut c_void,
@@ -398,36 +415,51 @@ impl Default for Secrets {
     }
 }
 
-fn get_algo_type(
-    connection: *mut s2n_connection,
-) -> Option<(hkdf::Algorithm, &'static aead::Algorithm)> {
+fn get_cipher_iana_value(connection: *mut s2n_connection) -> Option<[u8; 2]> {
     let mut cipher = [0, 0];
     unsafe {
         s2n_connection_get_cipher_iana_value(connection, &mut cipher[0], &mut cipher[1])
             .into_result()
             .ok()?;
     }
+    Some(cipher)
+}
+
+/// Returns the cipher suite negotiated by the connection
+pub fn get_cipher_suite(connection: *mut s2n_connection) -> tls::CipherSuite {
+    match get_cipher_iana_value(connection) {
+        Some(TLS_AES_128_GCM_SHA256) => tls::CipherSuite::TLS_AES_128_GCM_SHA256,
+        Some(TLS_AES_256_GCM_SHA384) => tls::CipherSuite::TLS_AES_256_GCM_SHA384,
+        Some(TLS_CHACHA20_POLY1305_SHA256) => tls::CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+        _ => tls::CipherSuite::Unknown,
+    }
+}
 
-    //= https://www.rfc-editor.org/rfc/rfc8446#appendix-B.4
-    //# This specification defines the following cipher suites for use with
-    //# TLS 1.3.
-    //#
-    //#              +------------------------------+-------------+
-    //#              | Description                  | Value       |
-    //#              +------------------------------+-------------+
-    //#              | TLS_AES_128_GCM_SHA256       | {0x13,0x01} |
-    //#              |                              |             |
-    //#              | TLS_AES_256_GCM_SHA384       | {0x13,0x02} |
-    //#              |                              |             |
-    //#              | TLS_CHACHA20_POLY1305_SHA256 | {0x13,0x03} |
-    //#              |                              |             |
-    //#              | TLS_AES_128_CCM_SHA256       | {0x13,0x04} |
-    //#              |                              |             |
-    //#              | TLS_AES_128_CCM_8_SHA256     | {0x13,0x05} |
-    //#              +------------------------------+-------------+
-    const TLS_AES_128_GCM_SHA256: [u8; 2] = [0x13, 0x01];
-    const TLS_AES_256_GCM_SHA384: [u8; 2] = [0x13, 0x02];
-    const TLS_CHACHA20_POLY1305_SHA256: [u8; 2] = [0x13, 0x03];
+//= https://www.rfc-editor.org/rfc/rfc8446#appendix-B.4
+//# This specification defines the following cipher suites for use with
+//# TLS 1.3.
+//#
+//#              +------------------------------+-------------+
+//#              | Description                  | Value       |
+//#              +------------------------------+-------------+
+//#              | TLS_AES_128_GCM_SHA256       | {0x13,0x01} |
+//#              |                              |             |
+//#              | TLS_AES_256_GCM_SHA384       | {0x13,0x02} |
+//#              |                              |             |
+//#              | TLS_CHACHA20_POLY1305_SHA256 | {0x13,0x03} |
+//#              |                              |             |
+//#              | TLS_AES_128_CCM_SHA256       | {0x13,0x04} |
+//#              |                              |             |
+//#              | TLS_AES_128_CCM_8_SHA256     | {0x13,0x05} |
+//#              +------------------------------+-------------+
+const TLS_AES_128_GCM_SHA256: [u8; 2] = [0x13, 0x01];
+const TLS_AES_256_GCM_SHA384: [u8; 2] = [0x13, 0x02];
+const TLS_CHACHA20_POLY1305_SHA256: [u8; 2] = [0x13, 0x03];
+
+fn get_algo_type(
+    connection: *mut s2n_connection,
+) -> Option<(hkdf::Algorithm, &'static aead::Algorithm)> {
+    let cipher = get_cipher_iana_value(connection)?;
 
     // NOTE: we don't have CCM support implemented currently
 
@@ -6,13 +6,20 @@ use crate::{
     keylog::KeyLogHandle,
     params::Params,
     session::Session,
+    session_ticket::{SessionTicketHandle, SessionTickets},
 };
 use s2n_codec::EncoderValue;
-use s2n_quic_core::{application::ServerName, crypto::tls, endpoint};
+use s2n_quic_core::{
+    application::ServerName,
+    crypto::tls::{self, session_ticket},
+    endpoint,
+};
 use s2n_tls::raw::{
     config::{self, Config},
-    error::Error,
-    ffi::s2n_cert_auth_type,
+    error::{Error, Fallible},
+    ffi::{
+        s2n_cert_auth_type, s2n_config_set_session_ticket_cb, s2n_config_set_session_tickets_onoff,
+    },
     security,
 };
 use std::sync::Arc;
@@ -21,6 +28,8 @@ pub struct Client {
     config: Config,
     #[allow(dead_code)] // we need to hold on to the handle to ensure it is cleaned up correctly
     keylog: Option<KeyLogHandle>,
+    #[allow(dead_code)] // we need to hold on to the handle to ensure it is cleaned up correctly
+    session_tickets: Option<SessionTicketHandle>,
     params: Params,
 }
 
@@ -41,6 +50,7 @@ impl Default for Client {
 pub struct Builder {
     config: config::Builder,
     keylog: Option<KeyLogHandle>,
+    session_tickets: Option<SessionTicketHandle>,
 }
 
 impl Default for Builder {
@@ -58,6 +68,7 @@ impl Default for Builder {
         Self {
             config,
             keylog: None,
+            session_tickets: None,
         }
     }
 }
@@ -127,10 +138,22 @@ impl Builder {
         Ok(self)
     }
 
-    pub fn with_key_logging(mut self) -> Result<Self, Error> {
+    pub fn with_key_logging(self) -> Result<Self, Error> {
+        self.set_key_log(crate::keylog::KeyLog::try_open())
+    }
+
+    /// Writes the TLS secrets of each connection to `writer`, in the NSS key log format
+    pub fn with_key_log<W: 'static + std::io::Write + Send>(
+        self,
+        writer: W,
+    ) -> Result<Self, Error> {
+        self.set_key_log(Some(crate::keylog::KeyLog::new(writer)))
+    }
+
+    fn set_key_log(mut self, keylog: Option<KeyLogHandle>) -> Result<Self, Error> {
         use crate::keylog::KeyLog;
 
-        self.keylog = KeyLog::try_open();
+        self.keylog = keylog;
 
         unsafe {
             // Safety: the KeyLog is stored on `self` to ensure it outlives `config`
@@ -147,10 +170,41 @@ impl Builder {
         Ok(self)
     }
 
+    /// Stores session tickets issued by servers and offers them on later connections
+    ///
+    /// Resuming a session skips certificate validation on the subsequent handshake.
+    pub fn with_session_ticket_store<S: session_ticket::Store>(
+        mut self,
+        store: S,
+    ) -> Result<Self, Error> {
+        let session_tickets = SessionTickets::new(store);
+
+        unsafe {
+            // Safety: the SessionTickets handle is stored on `self` to ensure it outlives `config`
+            let config = self.config.as_mut_ptr();
+            s2n_config_set_session_tickets_onoff(config, 1).into_result()?;
+            s2n_config_set_session_ticket_cb(
+                config,
+                Some(SessionTickets::callback),
+                Arc::as_ptr(&session_tickets) as *mut _,
+            )
+            .into_result()?;
+        }
+
+        self.session_tickets = Some(session_tickets);
+        Ok(self)
+    }
+
+    /// Uses an in-memory store for session tickets
+    pub fn with_session_tickets(self) -> Result<Self, Error> {
+        self.with_session_ticket_store(session_ticket::memory::Store::default())
+    }
+
     pub fn build(self) -> Result<Client, Error> {
         Ok(Client {
             config: self.config.build()?,
             keylog: self.keylog,
+            session_tickets: self.session_tickets,
             params: Default::default(),
         })
     }
@@ -169,8 +223,26 @@ impl tls::Endpoint for Client {
         server_name: ServerName,
     ) -> Self::Session {
         let config = self.config.clone();
+        let ticket = self
+            .session_tickets
+            .as_ref()
+            .and_then(|session_tickets| session_tickets.take(&server_name));
+        let receive_session_tickets = self.session_tickets.is_some();
+
         self.params.with(params, |params| {
-            Session::new(endpoint::Type::Client, config, params, Some(server_name)).unwrap()
+            let mut session =
+                Session::new(endpoint::Type::Client, config, params, Some(server_name)).unwrap();
+
+            if let Some(ticket) = ticket {
+                // a ticket that can't be used results in a full handshake
+                let _ = session.set_session_ticket(&ticket);
+            }
+
+            if receive_session_tickets {
+                session.receive_session_tickets();
+            }
+
+            session
         })
     }
 
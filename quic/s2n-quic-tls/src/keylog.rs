@@ -4,14 +4,14 @@
 use libc::{c_int, c_void};
 use s2n_tls::raw::ffi::*;
 use std::{
-    fs::{File, OpenOptions},
+    fs::OpenOptions,
     io::{BufWriter, Write},
     sync::{Arc, Mutex},
 };
 
 pub type KeyLogHandle = Arc<KeyLog>;
 
-pub struct KeyLog(Mutex<BufWriter<File>>);
+pub struct KeyLog(Mutex<Box<dyn Write + Send>>);
 
 impl KeyLog {
     pub fn try_open() -> Option<KeyLogHandle> {
@@ -22,10 +22,13 @@ impl KeyLog {
             .open(path)
             .ok()?;
         let file = BufWriter::new(file);
-        let file = Mutex::new(file);
-        let keylog = Self(file);
-        let keylog = Arc::new(keylog);
-        Some(keylog)
+        Some(Self::new(file))
+    }
+
+    /// Writes each key log line to `writer`, in the NSS key log format
+    pub fn new<W: 'static + Write + Send>(writer: W) -> KeyLogHandle {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        Arc::new(Self(Mutex::new(writer)))
     }
 
     pub unsafe extern "C" fn callback(
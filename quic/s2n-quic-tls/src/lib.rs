@@ -10,6 +10,7 @@ mod callback;
 mod keylog;
 mod params;
 mod session;
+mod session_ticket;
 
 pub mod certificate;
 pub mod client;
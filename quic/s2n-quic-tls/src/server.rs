@@ -13,11 +13,18 @@ use s2n_quic_core::{application::ServerName, crypto::tls, endpoint};
 use s2n_tls::raw::config::ClientHelloHandler;
 use s2n_tls::raw::{
     config::{self, Config, VerifyClientCertificateHandler},
-    error::Error,
-    ffi::s2n_cert_auth_type,
+    error::{Error, Fallible},
+    ffi::{
+        s2n_cert_auth_type, s2n_config_add_ticket_crypto_key, s2n_config_set_session_tickets_onoff,
+        s2n_config_set_ticket_decrypt_key_lifetime,
+        s2n_config_set_ticket_encrypt_decrypt_key_lifetime,
+    },
     security,
 };
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub struct Server {
     config: Config,
@@ -146,10 +153,81 @@ impl Builder {
         Ok(self)
     }
 
-    pub fn with_key_logging(mut self) -> Result<Self, Error> {
+    /// Adds a key used to encrypt and decrypt the session tickets issued to clients
+    ///
+    /// Session tickets are only issued once at least one key has been added. Keys are
+    /// identified by a unique `name` and are used for encryption starting at `intro_time`.
+    /// Keys can be rotated by adding several keys with increasing intro times; once the
+    /// encrypt-decrypt lifetime of a key has passed, it is only used to decrypt tickets until
+    /// the decrypt lifetime has also passed.
+    ///
+    /// Servers sharing the same keys are able to resume sessions issued by each other.
+    pub fn with_session_ticket_key(
+        mut self,
+        name: &[u8],
+        key: &[u8],
+        intro_time: SystemTime,
+    ) -> Result<Self, Error> {
+        // s2n-tls treats a time of 0 as the current time
+        let intro_time = intro_time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let mut key = key.to_vec();
+
+        unsafe {
+            // Safety: s2n-tls copies the name and key into the config
+            let config = self.config.as_mut_ptr();
+            s2n_config_set_session_tickets_onoff(config, 1).into_result()?;
+            s2n_config_add_ticket_crypto_key(
+                config,
+                name.as_ptr(),
+                name.len() as _,
+                key.as_mut_ptr(),
+                key.len() as _,
+                intro_time,
+            )
+            .into_result()?;
+        }
+
+        Ok(self)
+    }
+
+    /// Sets how long each session ticket key is used
+    ///
+    /// A key is used to both encrypt and decrypt tickets for `encrypt_decrypt`, after which it is
+    /// only used to decrypt tickets for `decrypt_only`.
+    pub fn with_session_ticket_key_lifetime(
+        mut self,
+        encrypt_decrypt: Duration,
+        decrypt_only: Duration,
+    ) -> Result<Self, Error> {
+        unsafe {
+            let config = self.config.as_mut_ptr();
+            s2n_config_set_ticket_encrypt_decrypt_key_lifetime(config, encrypt_decrypt.as_secs())
+                .into_result()?;
+            s2n_config_set_ticket_decrypt_key_lifetime(config, decrypt_only.as_secs())
+                .into_result()?;
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_key_logging(self) -> Result<Self, Error> {
+        self.set_key_log(crate::keylog::KeyLog::try_open())
+    }
+
+    /// Writes the TLS secrets of each connection to `writer`, in the NSS key log format
+    pub fn with_key_log<W: 'static + std::io::Write + Send>(
+        self,
+        writer: W,
+    ) -> Result<Self, Error> {
+        self.set_key_log(Some(crate::keylog::KeyLog::new(writer)))
+    }
+
+    fn set_key_log(mut self, keylog: Option<KeyLogHandle>) -> Result<Self, Error> {
         use crate::keylog::KeyLog;
 
-        self.keylog = KeyLog::try_open();
+        self.keylog = keylog;
 
         unsafe {
             // Safety: the KeyLog is stored on `self` to ensure it outlives `config`
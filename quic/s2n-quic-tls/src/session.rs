@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::callback::{self, Callback};
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use core::{marker::PhantomData, task::Poll};
+use s2n_codec::{EncoderBuffer, EncoderValue};
 use s2n_quic_core::{
     application::ServerName,
     crypto::{tls, CryptoError, CryptoSuite},
@@ -13,8 +14,13 @@ use s2n_quic_crypto::Suite;
 use s2n_tls::raw::{
     config::Config,
     connection::Connection,
-    error::Error,
-    ffi::{s2n_blinding, s2n_mode},
+    error::{Error, Fallible},
+    ffi::{
+        s2n_blinding, s2n_blocked_status, s2n_cert_chain_and_key_free, s2n_cert_chain_and_key_new,
+        s2n_cert_chain_get_cert, s2n_cert_chain_get_length, s2n_cert_get_der, s2n_connection,
+        s2n_connection_get_peer_cert_chain, s2n_connection_is_session_resumed,
+        s2n_connection_set_session, s2n_mode, s2n_recv_quic_post_handshake_message,
+    },
 };
 
 #[derive(Debug)]
@@ -27,6 +33,8 @@ pub struct Session {
     emitted_server_name: bool,
     // This is only set for the client to avoid an extra allocation
     server_name: Option<ServerName>,
+    // Set on clients that wait for the server to issue a session ticket
+    awaiting_session_ticket: bool,
 }
 
 impl Session {
@@ -61,8 +69,99 @@ impl Session {
             send_buffer: BytesMut::new(),
             emitted_server_name: false,
             server_name,
+            awaiting_session_ticket: false,
         })
     }
+
+    /// Offers a previously issued session ticket to the server
+    pub fn set_session_ticket(&mut self, ticket: &[u8]) -> Result<(), Error> {
+        unsafe {
+            // Safety: s2n-tls copies the ticket into the connection
+            s2n_connection_set_session(self.connection.as_ptr(), ticket.as_ptr(), ticket.len())
+                .into_result()?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the session active after the handshake to receive a session ticket from the server
+    pub fn receive_session_tickets(&mut self) {
+        self.awaiting_session_ticket = true;
+    }
+
+    fn handshake_info(&mut self) -> tls::HandshakeInfo {
+        let connection = self.connection.as_ptr();
+
+        unsafe {
+            // Safety: the connection pointer is valid for the lifetime of the session
+            tls::HandshakeInfo {
+                cipher_suite: callback::get_cipher_suite(connection),
+                peer_certificate_chain: get_peer_certificate_chain(connection)
+                    .unwrap_or_default()
+                    .into(),
+                is_resumed: s2n_connection_is_session_resumed(connection) == 1,
+            }
+        }
+    }
+
+    /// Processes a single post-handshake message, such as NewSessionTicket
+    fn recv_post_handshake_message(&mut self) -> Poll<Result<(), Error>> {
+        let mut blocked = s2n_blocked_status::NOT_BLOCKED;
+        let result = unsafe {
+            s2n_recv_quic_post_handshake_message(self.connection.as_ptr(), &mut blocked)
+                .into_result()
+        };
+
+        match result {
+            Ok(_) => Poll::Ready(Ok(())),
+            Err(_) if blocked != s2n_blocked_status::NOT_BLOCKED => Poll::Pending,
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+/// Copies the DER-encoded certificate chain presented by the peer
+///
+/// Returns `None` if the peer didn't present any certificates.
+unsafe fn get_peer_certificate_chain(connection: *mut s2n_connection) -> Option<Vec<Bytes>> {
+    // s2n-tls copies the peer's certificates into a separately allocated chain
+    let chain = s2n_cert_chain_and_key_new();
+    if chain.is_null() {
+        return None;
+    }
+
+    let certificates = (|| {
+        s2n_connection_get_peer_cert_chain(connection, chain)
+            .into_result()
+            .ok()?;
+
+        let mut len = 0;
+        s2n_cert_chain_get_length(chain, &mut len)
+            .into_result()
+            .ok()?;
+
+        let mut certificates = Vec::with_capacity(len as _);
+        for index in 0..len {
+            let mut certificate = core::ptr::null_mut();
+            s2n_cert_chain_get_cert(chain, &mut certificate, index)
+                .into_result()
+                .ok()?;
+
+            let mut der = core::ptr::null();
+            let mut der_len = 0;
+            s2n_cert_get_der(certificate, &mut der, &mut der_len)
+                .into_result()
+                .ok()?;
+
+            let der = core::slice::from_raw_parts(der, der_len as _);
+            certificates.push(Bytes::copy_from_slice(der));
+        }
+
+        Some(certificates)
+    })();
+
+    s2n_cert_chain_and_key_free(chain);
+
+    certificates
 }
 
 impl CryptoSuite for Session {
@@ -99,18 +198,36 @@ impl tls::Session for Session {
             callback.set(&mut self.connection);
         }
 
-        let result = self.connection.negotiate().map_ok(|_| ());
+        let result = if self.handshake_complete {
+            self.recv_post_handshake_message()
+        } else {
+            let result = self.connection.negotiate().map_ok(|_| ());
+
+            if let Poll::Ready(Ok(())) = result {
+                // s2n-tls has indicated that the handshake is complete
+                callback.on_handshake_complete();
+            }
+
+            result
+        };
 
         callback.unset(&mut self.connection)?;
 
         match result {
             Poll::Ready(Ok(())) => {
-                // s2n-tls has indicated that the handshake is complete
                 if !self.handshake_complete {
-                    self.state.on_handshake_complete();
+                    context.on_handshake_info(self.handshake_info())?;
                     context.on_handshake_complete()?;
                     self.handshake_complete = true;
+                } else {
+                    // the server only issues tickets after the handshake has completed
+                    self.awaiting_session_ticket = false;
                 }
+
+                if self.awaiting_session_ticket {
+                    return Poll::Pending;
+                }
+
                 Poll::Ready(Ok(()))
             }
             Poll::Ready(Err(e)) => Poll::Ready(Err(e
@@ -121,4 +238,12 @@ impl tls::Session for Session {
             Poll::Pending => Poll::Pending,
         }
     }
+
+    fn set_transport_parameters<Params: EncoderValue>(&mut self, params: &Params) -> bool {
+        let mut buffer = vec![0; params.encoding_size()];
+        params.encode(&mut EncoderBuffer::new(&mut buffer));
+        self.connection
+            .set_quic_transport_parameters(&buffer)
+            .is_ok()
+    }
 }
@@ -0,0 +1,60 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bytes::Bytes;
+use libc::{c_int, c_void};
+use s2n_quic_core::{application::ServerName, crypto::tls::session_ticket::Store};
+use s2n_tls::raw::{error::Fallible, ffi::*};
+use std::{ffi::CStr, sync::Arc};
+
+pub type SessionTicketHandle = Arc<SessionTickets>;
+
+/// Forwards session tickets received by s2n-tls client connections to a [`Store`]
+pub struct SessionTickets(Box<dyn Store>);
+
+impl SessionTickets {
+    pub fn new<S: Store>(store: S) -> SessionTicketHandle {
+        Arc::new(Self(Box::new(store)))
+    }
+
+    /// Returns a previously stored ticket for the server
+    pub fn take(&self, server_name: &ServerName) -> Option<Bytes> {
+        self.0.take(server_name)
+    }
+
+    pub unsafe extern "C" fn callback(
+        conn: *mut s2n_connection,
+        ctx: *mut c_void,
+        ticket: *mut s2n_session_ticket,
+    ) -> c_int {
+        let handle = &*(ctx as *const Self);
+
+        // ignore any errors; failing to store a ticket only prevents resumption
+        let _ = handle.on_ticket(conn, ticket);
+
+        0
+    }
+
+    unsafe fn on_ticket(
+        &self,
+        conn: *mut s2n_connection,
+        ticket: *mut s2n_session_ticket,
+    ) -> Option<()> {
+        let server_name = s2n_get_server_name(conn).into_result().ok()?;
+        let server_name = CStr::from_ptr(server_name).to_str().ok()?;
+
+        let mut len = 0;
+        s2n_session_ticket_get_data_len(ticket, &mut len)
+            .into_result()
+            .ok()?;
+
+        let mut data = vec![0; len];
+        s2n_session_ticket_get_data(ticket, len, data.as_mut_ptr())
+            .into_result()
+            .ok()?;
+
+        self.0.insert(&server_name.into(), data.into());
+
+        Some(())
+    }
+}
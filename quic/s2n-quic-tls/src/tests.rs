@@ -199,7 +199,12 @@ fn s2n_client_s2n_server_client_auth_test() {
     let mut client_endpoint = s2n_client_with_client_auth().unwrap();
     let mut server_endpoint = s2n_server_with_client_auth().unwrap();
 
-    run(&mut server_endpoint, &mut client_endpoint, None);
+    let pair = run(&mut server_endpoint, &mut client_endpoint, None);
+
+    // the server should have received the client certificate
+    let server_info = pair.server.context.handshake_info.as_ref().unwrap();
+    assert_eq!(server_info.peer_certificate_chain.len(), 1);
+    assert!(!server_info.is_resumed);
 }
 
 #[test]
@@ -267,7 +272,7 @@ fn run_result<S: Endpoint, C: Endpoint>(
     server: &mut S,
     client: &mut C,
     client_hello_cb_done: Option<Arc<AtomicBool>>,
-) -> Result<(), transport::Error> {
+) -> Result<tls::testing::Pair<S::Session, C::Session>, transport::Error> {
     let mut pair = tls::testing::Pair::new(server, client, "localhost".into());
 
     while pair.is_handshaking() {
@@ -275,7 +280,7 @@ fn run_result<S: Endpoint, C: Endpoint>(
     }
 
     pair.finish();
-    Ok(())
+    Ok(pair)
 }
 
 /// Executes the handshake to completion
@@ -283,6 +288,6 @@ fn run<S: Endpoint, C: Endpoint>(
     server: &mut S,
     client: &mut C,
     client_hello_cb_done: Option<Arc<AtomicBool>>,
-) {
-    run_result(server, client, client_hello_cb_done).unwrap();
+) -> tls::testing::Pair<S::Session, C::Session> {
+    run_result(server, client, client_hello_cb_done).unwrap()
 }
@@ -16,9 +16,13 @@ use core::{
 use s2n_quic_core::{
     application,
     application::ServerName,
+    crypto::tls,
+    datagram::DatagramError,
     event::query::{Query, QueryMut},
-    inet::SocketAddress,
+    inet::{IpAddress, SocketAddress},
+    path::migration::MigrationError,
     stream::StreamType,
+    transport::parameters::CustomTransportParameters,
 };
 
 /// A QUIC connection
@@ -165,6 +169,15 @@ impl Connection {
         self.api.close_connection(Some(error_code));
     }
 
+    /// Closes the Connection with the provided error code and reason phrase
+    ///
+    /// The reason is truncated if it doesn't fit in the packet carrying the CONNECTION_CLOSE
+    /// frame.
+    #[inline]
+    pub fn close_with_reason(&self, error_code: application::Error, reason: Bytes) {
+        self.api.close_connection_with_reason(error_code, reason);
+    }
+
     #[inline]
     pub fn server_name(&self) -> Result<Option<ServerName>, connection::Error> {
         self.api.server_name()
@@ -175,6 +188,18 @@ impl Connection {
         self.api.application_protocol()
     }
 
+    #[inline]
+    pub fn peer_custom_transport_parameters(
+        &self,
+    ) -> Result<CustomTransportParameters, connection::Error> {
+        self.api.peer_custom_transport_parameters()
+    }
+
+    #[inline]
+    pub fn handshake_info(&self) -> Result<Option<tls::HandshakeInfo>, connection::Error> {
+        self.api.handshake_info()
+    }
+
     #[inline]
     pub fn id(&self) -> u64 {
         self.api.id()
@@ -189,6 +214,56 @@ impl Connection {
         self.api.keep_alive(enabled)
     }
 
+    /// Changes the limits of the connection
+    pub fn update_limits(
+        &self,
+        update: connection::limits::Update,
+    ) -> Result<(), connection::Error> {
+        self.api.update_limits(update)
+    }
+
+    /// Initiates a 1-RTT key update
+    pub fn update_keys(&self) -> Result<(), connection::Error> {
+        self.api.update_keys()
+    }
+
+    /// Queues an unreliable datagram to be sent to the peer
+    ///
+    /// The method will return
+    /// - `Poll::Ready(Ok(()))` if the datagram was queued. In this case `data` is replaced
+    ///   with an empty buffer.
+    /// - `Poll::Ready(Err(datagram_error))` if the datagram could not be sent
+    /// - `Poll::Pending` if the send queue is currently full
+    #[inline]
+    pub fn poll_send_datagram(
+        &self,
+        data: &mut Bytes,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>> {
+        self.api.poll_send_datagram(data, context)
+    }
+
+    /// Receives an unreliable datagram from the peer
+    ///
+    /// The method will return
+    /// - `Poll::Ready(Ok(Some(datagram)))` if a datagram was received
+    /// - `Poll::Ready(Ok(None))` if the connection was closed without an error
+    /// - `Poll::Ready(Err(datagram_error))` if no datagram could be received due to an error
+    /// - `Poll::Pending` if no datagram has been received yet
+    #[inline]
+    pub fn poll_receive_datagram(
+        &self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>> {
+        self.api.poll_receive_datagram(context)
+    }
+
+    /// Returns the largest datagram payload that can currently be sent to the peer
+    #[inline]
+    pub fn max_datagram_size(&self) -> Result<usize, DatagramError> {
+        self.api.max_datagram_size()
+    }
+
     #[inline]
     pub fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api.local_address()
@@ -199,6 +274,18 @@ impl Connection {
         self.api.remote_address()
     }
 
+    /// Migrates the connection to a new local IP address
+    #[inline]
+    pub fn migrate(&mut self, local_ip: IpAddress) -> Result<(), MigrationError> {
+        self.api.migrate(local_ip)
+    }
+
+    /// Returns a snapshot of the connection statistics
+    #[inline]
+    pub fn stats(&self) -> Result<connection::Stats, connection::Error> {
+        self.api.stats()
+    }
+
     #[inline]
     pub fn query_event_context(&self, query: &mut dyn Query) -> Result<(), connection::Error> {
         self.api.query_event_context(query)
@@ -17,9 +17,13 @@ use core::{
 use s2n_quic_core::{
     application,
     application::ServerName,
+    crypto::tls,
+    datagram::DatagramError,
     event::query::{Query, QueryMut},
-    inet::SocketAddress,
+    inet::{IpAddress, SocketAddress},
+    path::migration::MigrationError,
     stream::{ops, StreamId, StreamType},
+    transport::parameters::CustomTransportParameters,
 };
 
 /// A dynamically dispatched connection API
@@ -54,20 +58,49 @@ pub(crate) trait ConnectionApiProvider: Sync + Send {
 
     fn close_connection(&self, code: Option<application::Error>);
 
+    fn close_connection_with_reason(&self, code: application::Error, reason: Bytes);
+
     fn server_name(&self) -> Result<Option<ServerName>, connection::Error>;
 
     fn application_protocol(&self) -> Result<Bytes, connection::Error>;
 
+    fn peer_custom_transport_parameters(
+        &self,
+    ) -> Result<CustomTransportParameters, connection::Error>;
+
+    fn handshake_info(&self) -> Result<Option<tls::HandshakeInfo>, connection::Error>;
+
     fn id(&self) -> u64;
 
     fn ping(&self) -> Result<(), connection::Error>;
 
     fn keep_alive(&self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn update_limits(&self, update: connection::limits::Update) -> Result<(), connection::Error>;
+
+    fn update_keys(&self) -> Result<(), connection::Error>;
+
+    fn poll_send_datagram(
+        &self,
+        data: &mut Bytes,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>>;
+
+    fn poll_receive_datagram(
+        &self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>>;
+
+    fn max_datagram_size(&self) -> Result<usize, DatagramError>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
 
+    fn migrate(&self, local_ip: IpAddress) -> Result<(), MigrationError>;
+
+    fn stats(&self) -> Result<connection::Stats, connection::Error>;
+
     fn query_event_context(&self, query: &mut dyn Query) -> Result<(), connection::Error>;
 
     fn query_event_context_mut(&self, query: &mut dyn QueryMut) -> Result<(), connection::Error>;
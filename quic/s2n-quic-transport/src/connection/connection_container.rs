@@ -17,7 +17,7 @@ use crate::{
     },
     stream,
 };
-use alloc::{collections::BTreeMap, sync::Arc};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
 use bytes::Bytes;
 use core::{
     cell::Cell,
@@ -33,14 +33,17 @@ use intrusive_collections::{
 use s2n_quic_core::{
     application,
     application::ServerName,
+    crypto::tls,
+    datagram::DatagramError,
     event::{
         query::{Query, QueryMut},
         supervisor,
     },
-    inet::SocketAddress,
+    inet::{IpAddress, SocketAddress},
+    path::migration::MigrationError,
     recovery::K_GRANULARITY,
     time::Timestamp,
-    transport,
+    transport::{self, parameters::CustomTransportParameters},
 };
 
 // Intrusive list adapter for managing the list of `done` connections
@@ -303,6 +306,13 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         });
     }
 
+    fn close_connection_with_reason(&self, error: application::Error, reason: Bytes) {
+        let _: Result<(), connection::Error> = self.api_write_call(|conn| {
+            conn.application_close_with_reason(error, reason);
+            Ok(())
+        });
+    }
+
     fn server_name(&self) -> Result<Option<ServerName>, connection::Error> {
         self.api_read_call(|conn| Ok(conn.server_name()))
     }
@@ -311,6 +321,16 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_read_call(|conn| Ok(conn.application_protocol()))
     }
 
+    fn peer_custom_transport_parameters(
+        &self,
+    ) -> Result<CustomTransportParameters, connection::Error> {
+        self.api_read_call(|conn| Ok(conn.peer_custom_transport_parameters()))
+    }
+
+    fn handshake_info(&self) -> Result<Option<tls::HandshakeInfo>, connection::Error> {
+        self.api_read_call(|conn| Ok(conn.handshake_info()))
+    }
+
     fn id(&self) -> u64 {
         self.internal_connection_id.into()
     }
@@ -323,6 +343,33 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_write_call(|conn| conn.keep_alive(enabled))
     }
 
+    fn update_limits(&self, update: connection::limits::Update) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.update_limits(update))
+    }
+
+    fn update_keys(&self) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.update_keys())
+    }
+
+    fn poll_send_datagram(
+        &self,
+        data: &mut Bytes,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>> {
+        self.api_poll_call(|conn| conn.poll_send_datagram(data, context))
+    }
+
+    fn poll_receive_datagram(
+        &self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>> {
+        self.api_poll_call(|conn| conn.poll_receive_datagram(context))
+    }
+
+    fn max_datagram_size(&self) -> Result<usize, DatagramError> {
+        self.api_read_call(|conn| conn.max_datagram_size())
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api_read_call(|conn| conn.local_address())
     }
@@ -331,6 +378,14 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_read_call(|conn| conn.remote_address())
     }
 
+    fn migrate(&self, local_ip: IpAddress) -> Result<(), MigrationError> {
+        self.api_write_call(|conn| conn.migrate(local_ip))
+    }
+
+    fn stats(&self) -> Result<connection::Stats, connection::Error> {
+        self.api_read_call(|conn| Ok(conn.stats()))
+    }
+
     #[inline]
     fn query_event_context(&self, query: &mut dyn Query) -> Result<(), connection::Error> {
         self.api_read_call(|conn| {
@@ -782,6 +837,10 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
         let connection = L::new(connection);
         let connection = Arc::new(ConnectionNode::new(connection, internal_connection_id));
 
+        // Increment the inflight handshakes before updating the interests, since a client
+        // with 0-RTT keys is handed over to the application as soon as it is created
+        self.interest_lists.handshake_connections += 1;
+
         if self
             .interest_lists
             .update_interests(
@@ -793,10 +852,11 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
             .is_ok()
         {
             self.connection_map.insert(connection);
-            // Increment the inflight handshakes and total connection counter because we have accepted a new connection
-            self.interest_lists.handshake_connections += 1;
+            // Increment the total connection counter because we have accepted a new connection
             self.interest_lists.connection_count += 1;
             self.ensure_counter_consistency();
+        } else {
+            self.interest_lists.handshake_connections = self.count_handshaking_connections();
         }
     }
 
@@ -867,6 +927,24 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
         Some((result, interests))
     }
 
+    /// Executes the provided function on every `Connection` in the container
+    ///
+    /// The interest lists are updated after each `Connection` has been visited.
+    pub fn iterate_all<F>(&mut self, mut func: F)
+    where
+        F: FnMut(&mut C),
+    {
+        let connection_ids: Vec<InternalConnectionId> = self
+            .connection_map
+            .iter()
+            .map(|node| node.internal_connection_id)
+            .collect();
+
+        for connection_id in connection_ids {
+            self.with_connection(connection_id, |conn| func(conn));
+        }
+    }
+
     /// Removes all Connections in the `done` state from the `ConnectionContainer`.
     pub fn finalize_done_connections(&mut self) {
         for connection in self.interest_lists.done_connections.take() {
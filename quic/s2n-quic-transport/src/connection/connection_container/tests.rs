@@ -19,7 +19,7 @@ use core::{
 use s2n_quic_core::{
     application, event,
     event::builder::DatagramDropReason,
-    inet::{DatagramInfo, SocketAddress},
+    inet::{DatagramInfo, IpAddress, SocketAddress},
     io::tx,
     packet::{
         handshake::ProtectedHandshake,
@@ -29,7 +29,7 @@ use s2n_quic_core::{
         version_negotiation::ProtectedVersionNegotiation,
         zero_rtt::ProtectedZeroRtt,
     },
-    path::MaxMtu,
+    path::{migration::MigrationError, MaxMtu},
     time::{Timer, Timestamp},
 };
 use std::sync::Mutex;
@@ -128,6 +128,8 @@ impl connection::Trait for TestConnection {
     fn on_wakeup(
         &mut self,
         _timestamp: Timestamp,
+        _random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
+        _congestion_controller_endpoint: &mut <Self::Config as endpoint::Config>::CongestionControllerEndpoint,
         _subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
     ) -> Result<(), connection::Error> {
         Ok(())
@@ -202,6 +204,7 @@ impl connection::Trait for TestConnection {
         _datagram: &DatagramInfo,
         _path_id: path::Id,
         _packet: ProtectedZeroRtt,
+        _random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
         _subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
         _packet_interceptor: &mut <Self::Config as endpoint::Config>::PacketInterceptor,
     ) -> Result<(), ProcessingError> {
@@ -243,6 +246,15 @@ impl connection::Trait for TestConnection {
         123
     }
 
+    fn on_compatible_version(
+        &mut self,
+        _timestamp: Timestamp,
+        _version: u32,
+        _subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
+    ) -> bool {
+        false
+    }
+
     fn poll_stream_request(
         &mut self,
         _stream_id: stream::StreamId,
@@ -273,6 +285,10 @@ impl connection::Trait for TestConnection {
         // no-op
     }
 
+    fn application_close_with_reason(&mut self, _error: application::Error, _reason: Bytes) {
+        // no-op
+    }
+
     fn server_name(&self) -> Option<ServerName> {
         todo!()
     }
@@ -281,6 +297,14 @@ impl connection::Trait for TestConnection {
         todo!()
     }
 
+    fn peer_custom_transport_parameters(&self) -> CustomTransportParameters {
+        todo!()
+    }
+
+    fn handshake_info(&self) -> Option<tls::HandshakeInfo> {
+        todo!()
+    }
+
     fn ping(&mut self) -> Result<(), connection::Error> {
         todo!()
     }
@@ -289,6 +313,36 @@ impl connection::Trait for TestConnection {
         todo!()
     }
 
+    fn update_limits(
+        &mut self,
+        _update: connection::limits::Update,
+    ) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn update_keys(&mut self) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn poll_send_datagram(
+        &mut self,
+        _data: &mut Bytes,
+        _context: &Context,
+    ) -> Poll<Result<(), DatagramError>> {
+        todo!()
+    }
+
+    fn poll_receive_datagram(
+        &mut self,
+        _context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>> {
+        todo!()
+    }
+
+    fn max_datagram_size(&self) -> Result<usize, DatagramError> {
+        todo!()
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         todo!()
     }
@@ -297,6 +351,14 @@ impl connection::Trait for TestConnection {
         Ok(SocketAddress::default())
     }
 
+    fn migrate(&mut self, _local_ip: IpAddress) -> Result<(), MigrationError> {
+        todo!()
+    }
+
+    fn stats(&self) -> connection::Stats {
+        todo!()
+    }
+
     fn error(&self) -> Option<connection::Error> {
         None
     }
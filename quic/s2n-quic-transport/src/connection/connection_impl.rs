@@ -8,7 +8,7 @@ use crate::{
         self,
         close_sender::CloseSender,
         id::{ConnectionInfo, Interest},
-        limits::Limits,
+        limits::{Limits, Update as LimitsUpdate},
         local_id_registry::LocalIdRegistrationError,
         ConnectionIdMapper, ConnectionInterests, ConnectionTimers, ConnectionTransmission,
         ConnectionTransmissionContext, InternalConnectionId, Parameters as ConnectionParameters,
@@ -19,7 +19,7 @@ use crate::{
     path::{self, path_event},
     processed_packet::ProcessedPacket,
     recovery::RttEstimator,
-    space::{PacketSpace, PacketSpaceManager},
+    space::{PacketSpace, PacketSpaceManager, ZeroRttReceiver},
     stream, transmission,
     transmission::interest::Provider,
     wakeup_queue::WakeupHandle,
@@ -36,12 +36,13 @@ use s2n_quic_core::{
     application::ServerName,
     connection::{id::Generator as _, InitialId, PeerId},
     crypto::{tls, CryptoSuite},
+    datagram::DatagramError,
     event::{
         self,
         builder::{DatagramDropReason, RxStreamProgress, TxStreamProgress},
         supervisor, ConnectionPublisher as _, IntoEvent as _, Subscriber,
     },
-    inet::{DatagramInfo, SocketAddress},
+    inet::{DatagramInfo, IpAddress, SocketAddress},
     io::tx,
     packet::{
         handshake::ProtectedHandshake,
@@ -52,11 +53,11 @@ use s2n_quic_core::{
         version_negotiation::ProtectedVersionNegotiation,
         zero_rtt::ProtectedZeroRtt,
     },
-    path::{Handle as _, MaxMtu},
+    path::{migration::MigrationError, Handle as _, MaxMtu},
     recovery::CongestionController,
     stateless_reset::token::Generator as _,
     time::{timer, Timestamp},
-    transport,
+    transport::{self, parameters::CustomTransportParameters},
 };
 
 /// Possible states for handing over a connection from the endpoint to the
@@ -168,6 +169,8 @@ pub struct ConnectionImpl<Config: endpoint::Config> {
     error: Result<(), connection::Error>,
     /// Sends CONNECTION_CLOSE close frames after the connection is closed
     close_sender: CloseSender,
+    /// The reason phrase the application provided when closing the connection
+    close_reason: Option<Bytes>,
     /// Manages all of the different packet spaces and their respective components
     space_manager: PacketSpaceManager<Config>,
     /// Holds the handle for waking up the endpoint from a application call
@@ -261,6 +264,26 @@ macro_rules! transmission_context {
 }
 
 impl<Config: endpoint::Config> ConnectionImpl<Config> {
+    /// Selects the QUIC version for the connection once the server received the ClientHello
+    fn negotiate_version(
+        &mut self,
+        timestamp: Timestamp,
+        subscriber: &mut Config::EventSubscriber,
+    ) {
+        let mut publisher = event::EndpointPublisherSubscriber::new(
+            event::builder::EndpointMeta {
+                endpoint_type: Config::ENDPOINT_TYPE,
+                timestamp,
+            },
+            Some(self.event_context.quic_version),
+            subscriber,
+        );
+
+        if let Some(version) = self.space_manager.negotiate_version(&mut publisher) {
+            self.event_context.quic_version = version;
+        }
+    }
+
     fn update_crypto_state(
         &mut self,
         timestamp: Timestamp,
@@ -269,16 +292,33 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
         let mut publisher = self.event_context.publisher(timestamp, subscriber);
         let space_manager = &mut self.space_manager;
 
-        match space_manager.poll_crypto(
+        let poll = space_manager.poll_crypto(
             &mut self.path_manager,
             &mut self.local_id_registry,
             &mut self.limits,
             timestamp,
             &self.waker,
             &mut publisher,
-        ) {
-            Poll::Ready(res) => res?,
-            Poll::Pending => return Ok(()),
+        );
+
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-4.6.1
+        //# A client that wishes to send 0-RTT packets uses the early_data
+        //# extension in the ClientHello message of a subsequent handshake; see
+        //# Section 4.2.10 of [TLS13].
+        //
+        // A client with 0-RTT keys is handed over to the application before the handshake
+        // completes so streams can be opened and written in 0-RTT packets.
+        if Config::ENDPOINT_TYPE.is_client()
+            && self.accept_state == AcceptState::Handshaking
+            && space_manager.zero_rtt().is_some()
+        {
+            self.accept_state = AcceptState::HandshakeCompleted;
+        }
+
+        // The TLS session may remain pending after the handshake completes in order to exchange
+        // post-handshake messages, such as session tickets
+        if let Poll::Ready(res) = poll {
+            res?;
         }
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-7.1
@@ -308,7 +348,11 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
         {
             // Move into the HandshakeCompleted state. This will signal the
             // necessary interest to hand over the connection to the application.
-            self.accept_state = AcceptState::HandshakeCompleted;
+            //
+            // Clients sending 0-RTT data may have already been handed over.
+            if self.accept_state == AcceptState::Handshaking {
+                self.accept_state = AcceptState::HandshakeCompleted;
+            }
             // Move the connection into the active state.
             self.state = ConnectionState::Active;
 
@@ -353,9 +397,12 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
 
     fn on_processed_packet(
         &mut self,
+        path_id: path::Id,
         packet: &ProcessedPacket,
         subscriber: &mut Config::EventSubscriber,
     ) -> Result<(), connection::Error> {
+        self.path_manager[path_id].counters.on_packet_received();
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.1
         //# An endpoint restarts its idle timer when a packet from its peer is
         //# received and processed successfully.
@@ -381,6 +428,11 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
             self.error?;
         }
 
+        // the path manager needs access to the endpoint to create new paths
+        if self.path_manager.has_pending_migration() {
+            self.wakeup_handle.wakeup();
+        }
+
         Ok(())
     }
 
@@ -519,15 +571,15 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
     /// Once all of the streams are finished, `Poll::Ready` will be returned
     fn poll_flush(&mut self) -> Poll<()> {
         if matches!(self.state, ConnectionState::Flushing) {
-            let is_finished = if let Some((space, _)) = self.space_manager.application_mut() {
-                space
-                    .stream_manager
+            let is_finished = if let Some(stream_manager) = self.space_manager.stream_manager_mut()
+            {
+                stream_manager
                     .flush(transport::Error::NO_ERROR.into())
                     .is_ready()
             } else {
                 debug_assert!(
                     false,
-                    "connection should only be flushing with application space"
+                    "connection should only be flushing with application or 0-RTT space"
                 );
                 true
             };
@@ -601,6 +653,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             limits: parameters.limits,
             error: Ok(()),
             close_sender: CloseSender::default(),
+            close_reason: None,
             space_manager: parameters.space_manager,
             wakeup_handle,
             waker,
@@ -661,6 +714,39 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         self.event_context.quic_version
     }
 
+    fn on_compatible_version(
+        &mut self,
+        timestamp: Timestamp,
+        version: u32,
+        subscriber: &mut Config::EventSubscriber,
+    ) -> bool {
+        if Config::ENDPOINT_TYPE.is_server() {
+            return self
+                .space_manager
+                .initial()
+                .map_or(false, |space| space.accepts_original_version(version));
+        }
+
+        let mut publisher = event::EndpointPublisherSubscriber::new(
+            event::builder::EndpointMeta {
+                endpoint_type: Config::ENDPOINT_TYPE,
+                timestamp,
+            },
+            Some(self.event_context.quic_version),
+            subscriber,
+        );
+
+        if !self
+            .space_manager
+            .on_server_version(version, &mut publisher)
+        {
+            return false;
+        }
+
+        self.event_context.quic_version = version;
+        true
+    }
+
     /// Initiates closing the connection as described in
     /// https://www.rfc-editor.org/rfc/rfc9000#section-10
     fn close(
@@ -697,7 +783,9 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         //# connection error MUST use a CONNECTION_CLOSE frame if it is able.
 
         let remote_address = self.path_manager.active_path().remote_address();
-        let close_context = s2n_quic_core::connection::close::Context::new(&remote_address);
+        let close_reason = self.close_reason.take();
+        let close_context = s2n_quic_core::connection::close::Context::new(&remote_address)
+            .with_reason(close_reason.as_deref());
         let active_path_id = self.path_manager.active_path_id();
 
         if let Some((early_connection_close, connection_close)) =
@@ -1003,6 +1091,16 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .poll_expiration(timestamp)
             .is_ready()
         {
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-10.1
+            //# By announcing a max_idle_timeout, an endpoint
+            //# commits to initiating an immediate close (Section 10.2) if it
+            //# abandons the connection prior to the effective value.
+            if self.limits.is_idle_timeout_reduced() {
+                return Err(transport::Error::NO_ERROR
+                    .with_reason("idle timeout")
+                    .into());
+            }
+
             return Err(connection::Error::idle_timer_expired());
         }
 
@@ -1047,6 +1145,8 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
     fn on_wakeup(
         &mut self,
         timestamp: Timestamp,
+        random_generator: &mut Config::RandomGenerator,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
         subscriber: &mut Config::EventSubscriber,
     ) -> Result<(), connection::Error> {
         // reset the queued state first so that new wakeup request are not missed
@@ -1058,6 +1158,14 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         // return an error if the application set one
         self.error?;
 
+        // migrate to a new local address if the application requested it
+        let mut publisher = self.event_context.publisher(timestamp, subscriber);
+        self.path_manager.migrate(
+            random_generator,
+            congestion_controller_endpoint,
+            &mut publisher,
+        );
+
         Ok(())
     }
 
@@ -1099,6 +1207,9 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         publisher.on_datagram_received(event::builder::DatagramReceived {
             len: datagram.payload_len as u16,
         });
+        self.path_manager[id]
+            .counters
+            .on_datagram_received(datagram.payload_len, datagram.ecn);
 
         if matches!(self.state, ConnectionState::Closing) {
             //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.1
@@ -1223,11 +1334,16 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
                 packet_interceptor,
             )?;
 
+            if Config::ENDPOINT_TYPE.is_server() {
+                // the version is negotiated before the ClientHello is passed to the TLS session
+                self.negotiate_version(datagram.timestamp, subscriber);
+            }
+
             // try to move the crypto state machine forward
             self.update_crypto_state(datagram.timestamp, subscriber)?;
 
             // notify the connection a packet was processed
-            self.on_processed_packet(&processed_packet, subscriber)?;
+            self.on_processed_packet(path_id, &processed_packet, subscriber)?;
         }
 
         Ok(())
@@ -1307,7 +1423,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             self.update_crypto_state(datagram.timestamp, subscriber)?;
 
             // notify the connection a packet was processed
-            self.on_processed_packet(&processed_packet, subscriber)?;
+            self.on_processed_packet(path_id, &processed_packet, subscriber)?;
         }
 
         Ok(())
@@ -1407,8 +1523,11 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
                 packet_interceptor,
             )?;
 
+            // try to move the crypto state machine forward with any post-handshake messages
+            self.update_crypto_state(datagram.timestamp, subscriber)?;
+
             // notify the connection a packet was processed
-            self.on_processed_packet(&processed_packet, subscriber)?;
+            self.on_processed_packet(path_id, &processed_packet, subscriber)?;
         }
 
         Ok(())
@@ -1459,20 +1578,14 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
     fn handle_zero_rtt_packet(
         &mut self,
         datagram: &DatagramInfo,
-        _path_id: path::Id,
-        _packet: ProtectedZeroRtt,
+        path_id: path::Id,
+        packet: ProtectedZeroRtt,
+        random_generator: &mut Config::RandomGenerator,
         subscriber: &mut Config::EventSubscriber,
-        _packet_interceptor: &mut Config::PacketInterceptor,
+        packet_interceptor: &mut Config::PacketInterceptor,
     ) -> Result<(), ProcessingError> {
         let mut publisher = self.event_context.publisher(datagram.timestamp, subscriber);
 
-        publisher.on_packet_received(event::builder::PacketReceived {
-            packet_header: event::builder::PacketHeader::ZeroRtt {
-                // FIXME: replace with PacketHeader::new when we support zero-rtt.
-                number: 0,
-                version: publisher.quic_version(),
-            },
-        });
         //= https://www.rfc-editor.org/rfc/rfc9000#section-5.2.2
         //= type=TODO
         //= tracking-issue=339
@@ -1480,7 +1593,42 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         //# number of these packets in anticipation of a late-arriving Initial
         //# packet.
 
-        // TODO
+        // 0-RTT keys are only available if the server accepted early data on this connection.
+        // Otherwise the packet is dropped and the client retransmits the data in 1-RTT packets.
+        if let Some((crypto, space, handshake_status)) = self.space_manager.zero_rtt_receiver_mut()
+        {
+            let packet = space.validate_and_decrypt_zero_rtt_packet(
+                packet,
+                crypto,
+                path_id,
+                &self.path_manager[path_id],
+                &mut publisher,
+            )?;
+
+            publisher.on_packet_received(event::builder::PacketReceived {
+                packet_header: event::builder::PacketHeader::ZeroRtt {
+                    number: packet.packet_number.as_u64(),
+                    version: publisher.quic_version(),
+                },
+            });
+
+            let processed_packet = ZeroRttReceiver { space }.handle_cleartext_payload(
+                packet.packet_number,
+                packet.payload,
+                datagram,
+                path_id,
+                &mut self.path_manager,
+                handshake_status,
+                &mut self.local_id_registry,
+                random_generator,
+                &mut publisher,
+                packet_interceptor,
+            )?;
+
+            // notify the connection a packet was processed
+            self.on_processed_packet(path_id, &processed_packet, subscriber)?;
+        }
+
         Ok(())
     }
 
@@ -1599,7 +1747,12 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .on_retry_packet(retry_source_connection_id);
 
         if let Some((space, _handshake_status)) = self.space_manager.initial_mut() {
-            space.on_retry_packet(path, &retry_source_connection_id, packet.retry_token);
+            space.on_retry_packet(
+                path,
+                self.event_context.quic_version,
+                &retry_source_connection_id,
+                packet.retry_token,
+            );
         }
 
         Ok(())
@@ -1681,16 +1834,14 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         // important for receive streams that may have buffered stream data that haven't been
         // consumed by the application.
 
-        let (space, _) = self
+        let stream_manager = self
             .space_manager
-            .application_mut()
+            .stream_manager_mut()
             .ok_or_else(connection::Error::unspecified)?;
 
         let mut api_context = ConnectionApiCallContext::from_wakeup_handle(&self.wakeup_handle);
 
-        space
-            .stream_manager
-            .poll_request(stream_id, &mut api_context, request, context)
+        stream_manager.poll_request(stream_id, &mut api_context, request, context)
     }
 
     fn poll_accept_stream(
@@ -1700,12 +1851,12 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
     ) -> Poll<Result<Option<stream::StreamId>, connection::Error>> {
         self.error?;
 
-        let (space, _) = self
+        let stream_manager = self
             .space_manager
-            .application_mut()
+            .stream_manager_mut()
             .ok_or_else(connection::Error::unspecified)?;
 
-        space.stream_manager.poll_accept(stream_type, context)
+        stream_manager.poll_accept(stream_type, context)
     }
 
     fn poll_open_stream(
@@ -1716,14 +1867,12 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
     ) -> Poll<Result<stream::StreamId, connection::Error>> {
         self.error?;
 
-        let (space, _) = self
+        let stream_manager = self
             .space_manager
-            .application_mut()
+            .stream_manager_mut()
             .ok_or_else(connection::Error::unspecified)?;
 
-        space
-            .stream_manager
-            .poll_open(stream_type, open_token, context)
+        stream_manager.poll_open(stream_type, open_token, context)
     }
 
     fn application_close(&mut self, error: Option<application::Error>) {
@@ -1743,6 +1892,15 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         self.wakeup_handle.wakeup();
     }
 
+    fn application_close_with_reason(&mut self, error: application::Error, reason: Bytes) {
+        if self.error.is_err() {
+            return;
+        }
+
+        self.close_reason = Some(reason);
+        self.application_close(Some(error));
+    }
+
     fn server_name(&self) -> Option<ServerName> {
         self.space_manager.server_name.clone()
     }
@@ -1751,6 +1909,14 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         self.space_manager.application_protocol.clone()
     }
 
+    fn peer_custom_transport_parameters(&self) -> CustomTransportParameters {
+        self.space_manager.peer_custom_transport_parameters.clone()
+    }
+
+    fn handshake_info(&self) -> Option<tls::HandshakeInfo> {
+        self.space_manager.handshake_info.clone()
+    }
+
     fn ping(&mut self) -> Result<(), connection::Error> {
         self.error?;
 
@@ -1759,8 +1925,10 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
 
             self.wakeup_handle.wakeup();
         } else {
+            // Clients sending 0-RTT data are handed to the application before 1-RTT keys are
+            // available
             debug_assert!(
-                false,
+                self.space_manager.zero_rtt().is_some(),
                 "applications can't interact with the connection until the application space is available"
             );
             return Err(connection::Error::unspecified());
@@ -1777,16 +1945,131 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
 
             self.wakeup_handle.wakeup();
         } else {
+            // Clients sending 0-RTT data are handed to the application before 1-RTT keys are
+            // available
             debug_assert!(
-                false,
+                self.space_manager.zero_rtt().is_some(),
+                "applications can't interact with the connection until the application space is available"
+            );
+            return Err(connection::Error::unspecified());
+        }
+
+        Ok(())
+    }
+
+    fn update_limits(&mut self, update: LimitsUpdate) -> Result<(), connection::Error> {
+        use timer::Provider as _;
+
+        self.error?;
+
+        if self.space_manager.application().is_none() {
+            // Clients sending 0-RTT data are handed to the application before 1-RTT keys are
+            // available
+            debug_assert!(
+                self.space_manager.zero_rtt().is_some(),
                 "applications can't interact with the connection until the application space is available"
             );
             return Err(connection::Error::unspecified());
         }
 
+        let idle_timer_duration = self.get_idle_timer_duration();
+        self.limits.update(&update);
+
+        if let Some((space, _)) = self.space_manager.application_mut() {
+            space.update_limits(&update, &self.limits);
+        }
+
+        // move the idle timer as if it had been set with the new timeout
+        if let Some(expiration) = self.timers.peer_idle_timer.next_expiration() {
+            match (idle_timer_duration, self.get_idle_timer_duration()) {
+                (Some(previous), Some(duration)) => {
+                    if let Some(reset_time) = expiration.checked_sub(previous) {
+                        self.timers.peer_idle_timer.set(reset_time + duration);
+                    }
+                }
+                (_, None) => self.timers.peer_idle_timer.cancel(),
+                (None, Some(_)) => {}
+            }
+        }
+
+        self.wakeup_handle.wakeup();
+
         Ok(())
     }
 
+    fn update_keys(&mut self) -> Result<(), connection::Error> {
+        self.error?;
+
+        if let Some((space, _)) = self.space_manager.application_mut() {
+            space.update_keys();
+
+            self.wakeup_handle.wakeup();
+        } else {
+            // Clients sending 0-RTT data are handed to the application before 1-RTT keys are
+            // available
+            debug_assert!(
+                self.space_manager.zero_rtt().is_some(),
+                "applications can't interact with the connection until the application space is available"
+            );
+            return Err(connection::Error::unspecified());
+        }
+
+        Ok(())
+    }
+
+    fn poll_send_datagram(
+        &mut self,
+        data: &mut Bytes,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>> {
+        self.error?;
+
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        let max_packet_payload = space.max_packet_payload(self.path_manager.active_path());
+        let result = space
+            .datagram_manager
+            .poll_send(data, max_packet_payload, context);
+
+        if let Poll::Ready(Ok(())) = result {
+            // notify the endpoint that the connection has a datagram to transmit
+            self.wakeup_handle.wakeup();
+        }
+
+        result
+    }
+
+    fn poll_receive_datagram(
+        &mut self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>> {
+        // Don't check the `self.error` here so datagrams received before the connection
+        // was closed can still be consumed by the application.
+
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        space.datagram_manager.poll_receive(context)
+    }
+
+    fn max_datagram_size(&self) -> Result<usize, DatagramError> {
+        self.error?;
+
+        let space = self
+            .space_manager
+            .application()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        let max_packet_payload = space.max_packet_payload(self.path_manager.active_path());
+
+        space.datagram_manager.max_datagram_size(max_packet_payload)
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         Ok(*self.path_manager.active_path().handle.local_address())
     }
@@ -1795,6 +2078,24 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         Ok(*self.path_manager.active_path().handle.remote_address())
     }
 
+    fn migrate(&mut self, local_ip: IpAddress) -> Result<(), MigrationError> {
+        self.error?;
+
+        let handshake_confirmed = self.space_manager.is_handshake_confirmed();
+        let peer_has_spare_id = self.local_id_registry.has_spare_active_id();
+        self.path_manager
+            .request_migration(local_ip, handshake_confirmed, peer_has_spare_id)?;
+
+        // the migration is performed on wakeup, where the endpoint state is available
+        self.wakeup_handle.wakeup();
+
+        Ok(())
+    }
+
+    fn stats(&self) -> connection::Stats {
+        self.path_manager.stats()
+    }
+
     fn error(&self) -> Option<connection::Error> {
         self.error.err()
     }
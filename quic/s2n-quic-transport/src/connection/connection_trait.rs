@@ -20,8 +20,10 @@ use s2n_codec::DecoderBufferMut;
 use s2n_quic_core::{
     application,
     application::ServerName,
+    crypto::tls,
+    datagram::DatagramError,
     event::{self, builder::DatagramDropReason, supervisor, ConnectionPublisher, IntoEvent},
-    inet::{DatagramInfo, SocketAddress},
+    inet::{DatagramInfo, IpAddress, SocketAddress},
     io::tx,
     packet::{
         handshake::ProtectedHandshake,
@@ -32,8 +34,9 @@ use s2n_quic_core::{
         zero_rtt::ProtectedZeroRtt,
         ProtectedPacket,
     },
-    path::{Handle as _, MaxMtu},
+    path::{migration::MigrationError, Handle as _, MaxMtu},
     time::Timestamp,
+    transport::parameters::CustomTransportParameters,
 };
 
 /// A trait which represents an internally used `Connection`
@@ -103,6 +106,8 @@ pub trait ConnectionTrait: 'static + Send + Sized {
     fn on_wakeup(
         &mut self,
         timestamp: Timestamp,
+        random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
+        congestion_controller_endpoint: &mut <Self::Config as endpoint::Config>::CongestionControllerEndpoint,
         subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
     ) -> Result<(), connection::Error>;
 
@@ -168,6 +173,7 @@ pub trait ConnectionTrait: 'static + Send + Sized {
         datagram: &DatagramInfo,
         path_id: path::Id,
         packet: ProtectedZeroRtt,
+        random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
         subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
         packet_interceptor: &mut <Self::Config as endpoint::Config>::PacketInterceptor,
     ) -> Result<(), ProcessingError>;
@@ -200,6 +206,19 @@ pub trait ConnectionTrait: 'static + Send + Sized {
     /// Returns the QUIC version selected for the current connection
     fn quic_version(&self) -> u32;
 
+    /// Called when an Initial packet uses a version other than the selected one
+    ///
+    /// On clients, returns `true` if the server negotiated a compatible version offered by the
+    /// client, in which case the connection switches to it. On servers, returns `true` if the
+    /// packet uses the original version and the client has not yet switched to the negotiated
+    /// version.
+    fn on_compatible_version(
+        &mut self,
+        timestamp: Timestamp,
+        version: u32,
+        subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
+    ) -> bool;
+
     /// Handles reception of a single QUIC packet
     fn handle_packet(
         &mut self,
@@ -214,7 +233,17 @@ pub trait ConnectionTrait: 'static + Send + Sized {
         //# If a client receives a packet that uses a different version than it
         //# initially selected, it MUST discard that packet.
         if let Some(version) = packet.version() {
-            if version != self.quic_version() {
+            // See <https://www.rfc-editor.org/rfc/rfc9368#section-2.3>
+            //
+            // Servers respond with the negotiated version, which can differ from the version
+            // the client started with. The client keeps sending Initial packets of the original
+            // version until it receives one from the server.
+            let is_initial = matches!(packet, ProtectedPacket::Initial(_));
+
+            if version != self.quic_version()
+                && !(is_initial
+                    && self.on_compatible_version(datagram.timestamp, version, subscriber))
+            {
                 self.with_event_publisher(
                     datagram.timestamp,
                     Some(path_id),
@@ -267,6 +296,7 @@ pub trait ConnectionTrait: 'static + Send + Sized {
                 datagram,
                 path_id,
                 packet,
+                random_generator,
                 subscriber,
                 packet_interceptor,
             ),
@@ -416,18 +446,48 @@ pub trait ConnectionTrait: 'static + Send + Sized {
 
     fn application_close(&mut self, error: Option<application::Error>);
 
+    fn application_close_with_reason(&mut self, error: application::Error, reason: Bytes);
+
     fn server_name(&self) -> Option<ServerName>;
 
     fn application_protocol(&self) -> Bytes;
 
+    fn peer_custom_transport_parameters(&self) -> CustomTransportParameters;
+
+    fn handshake_info(&self) -> Option<tls::HandshakeInfo>;
+
     fn ping(&mut self) -> Result<(), connection::Error>;
 
     fn keep_alive(&mut self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn update_limits(
+        &mut self,
+        update: connection::limits::Update,
+    ) -> Result<(), connection::Error>;
+
+    fn update_keys(&mut self) -> Result<(), connection::Error>;
+
+    fn poll_send_datagram(
+        &mut self,
+        data: &mut Bytes,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>>;
+
+    fn poll_receive_datagram(
+        &mut self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>>;
+
+    fn max_datagram_size(&self) -> Result<usize, DatagramError>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
 
+    fn migrate(&mut self, local_ip: IpAddress) -> Result<(), MigrationError>;
+
+    fn stats(&self) -> connection::Stats;
+
     fn error(&self) -> Option<connection::Error>;
 
     fn query_event_context(&self, query: &mut dyn event::query::Query);
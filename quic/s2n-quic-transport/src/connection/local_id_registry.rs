@@ -336,6 +336,40 @@ impl LocalIdRegistry {
         }
     }
 
+    /// Registers the connection ID advertised in the `preferred_address` transport parameter
+    ///
+    /// This must be called before any other connection IDs are registered after the
+    /// handshake connection ID.
+    pub fn register_preferred_address_connection_id(
+        &mut self,
+        id: &connection::LocalId,
+        expiration: Option<Timestamp>,
+        stateless_reset_token: stateless_reset::Token,
+    ) -> Result<(), LocalIdRegistrationError> {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
+        //# The value of the
+        //# active_connection_id_limit parameter MUST be at least 2.
+        self.active_connection_id_limit = self.active_connection_id_limit.max(2);
+
+        self.register_connection_id(id, expiration, stateless_reset_token)?;
+
+        let preferred_address_id_info = self
+            .registered_ids
+            .last_mut()
+            .expect("preferred address id added above");
+
+        // The preferred address connection ID is sent in the transport parameters,
+        // so it starts in the `Active` status.
+        preferred_address_id_info.status = Active;
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.1
+        //# If the preferred_address transport parameter is
+        //# sent, the sequence number of the supplied connection ID is 1.
+        debug_assert_eq!(preferred_address_id_info.sequence_number, 1);
+
+        Ok(())
+    }
+
     /// Unregisters connection IDs that have expired
     fn unregister_expired_ids(&mut self, timestamp: Timestamp) {
         {
@@ -457,6 +491,16 @@ impl LocalIdRegistry {
         }
     }
 
+    /// Returns true if the peer has acknowledged more than one connection ID,
+    /// leaving it an unused ID to address packets on a new path
+    pub fn has_spare_active_id(&self) -> bool {
+        self.registered_ids
+            .iter()
+            .filter(|id_info| id_info.status == Active)
+            .count()
+            > 1
+    }
+
     /// Handles timeouts on the registration
     ///
     /// `timestamp` passes the current time.
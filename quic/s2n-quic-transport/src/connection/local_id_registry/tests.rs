@@ -705,12 +705,14 @@ fn on_packet_ack_and_loss() {
     let packet_number = write_context.packet_number();
     reg1.on_transmit(&mut write_context);
 
+    assert!(!reg1.has_spare_active_id());
     reg1.on_packet_ack(&PacketNumberRange::new(packet_number, packet_number));
 
     assert_eq!(
         Active,
         reg1.get_connection_id_info(&ext_id_2).unwrap().status
     );
+    assert!(reg1.has_spare_active_id());
     assert_eq!(
         stateless_reset::Token::ZEROED,
         reg1.get_connection_id_info(&ext_id_2)
@@ -903,3 +905,52 @@ fn retire_handshake_connection_id() {
         }
     }
 }
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.1
+//= type=test
+//# If the preferred_address transport parameter is
+//# sent, the sequence number of the supplied connection ID is 1.
+#[test]
+fn register_preferred_address_connection_id() {
+    let ext_id_1 = id(b"id01");
+    let ext_id_2 = id(b"id02");
+    let ext_id_3 = id(b"id03");
+
+    let (_, mut reg1) = mapper(ext_id_1, None, TEST_TOKEN_1);
+
+    // The active connection ID limit is raised to the minimum the peer may send
+    assert!(reg1
+        .register_preferred_address_connection_id(&ext_id_2, None, TEST_TOKEN_2)
+        .is_ok());
+
+    let id_info = reg1.get_connection_id_info(&ext_id_2).unwrap();
+    assert_eq!(1, id_info.sequence_number);
+    assert_eq!(Active, id_info.status);
+
+    // The connection ID was sent in the transport parameters, so no NEW_CONNECTION_ID
+    // frame is needed
+    assert_eq!(
+        transmission::Interest::None,
+        reg1.get_transmission_interest()
+    );
+
+    reg1.set_active_connection_id_limit(3);
+    assert_eq!(
+        connection::id::Interest::New(1),
+        reg1.connection_id_interest()
+    );
+
+    // Retiring the handshake connection ID leaves the preferred address connection ID active
+    reg1.retire_handshake_connection_id();
+    assert!(!reg1.get_connection_id_info(&ext_id_2).unwrap().is_retired());
+
+    assert!(reg1
+        .register_connection_id(&ext_id_3, None, TEST_TOKEN_3)
+        .is_ok());
+    assert_eq!(
+        2,
+        reg1.get_connection_id_info(&ext_id_3)
+            .unwrap()
+            .sequence_number
+    );
+}
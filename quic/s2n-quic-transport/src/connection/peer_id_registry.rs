@@ -299,6 +299,53 @@ impl PeerIdRegistry {
             .insert(stateless_reset_token, self.internal_id);
     }
 
+    /// Used to register the connection ID the server supplied in the `preferred_address`
+    /// transport parameter.
+    ///
+    /// The connection ID is available for use on any path, but unlike a connection ID
+    /// received in a NEW_CONNECTION_ID frame, it does not cause the initial connection ID
+    /// to be retired.
+    pub(crate) fn register_preferred_address_connection_id(
+        &mut self,
+        peer_id: &connection::PeerId,
+        stateless_reset_token: &stateless_reset::Token,
+    ) -> Result<(), PeerIdRegistrationError> {
+        debug_assert!(!self.is_empty());
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.1
+        //# If the preferred_address transport parameter is
+        //# sent, the sequence number of the supplied connection ID is 1.
+        let sequence_number = 1;
+
+        for id_info in self.registered_ids.iter() {
+            if id_info.validate_new_connection_id(
+                peer_id,
+                stateless_reset_token,
+                sequence_number,
+            )? {
+                return Ok(());
+            }
+        }
+
+        self.registered_ids.push(PeerIdInfo {
+            id: *peer_id,
+            sequence_number,
+            stateless_reset_token: Some(*stateless_reset_token),
+            status: New,
+        });
+
+        let active_id_count = self
+            .registered_ids
+            .iter()
+            .filter(|id_info| id_info.status.is_active())
+            .count();
+        self.check_active_connection_id_limit(active_id_count)?;
+
+        self.ensure_no_duplicates();
+
+        Ok(())
+    }
+
     /// Check if registered_ids is empty.
     ///
     /// This is only expected to be true when an endpoint creates a new
@@ -471,6 +518,27 @@ impl PeerIdRegistry {
             .any(|id_info| peer_id == &id_info.id && id_info.status.is_active())
     }
 
+    /// Returns true if there is a peer_id that has not been used on any path
+    pub fn has_new_id(&self) -> bool {
+        self.registered_ids
+            .iter()
+            .any(|id_info| id_info.status == New)
+    }
+
+    /// Retires the given peer_id if it is in use
+    ///
+    /// A RETIRE_CONNECTION_ID frame will be sent, allowing the peer to issue a replacement
+    /// connection ID.
+    pub fn retire_id(&mut self, peer_id: &connection::PeerId) {
+        for id_info in self.registered_ids.iter_mut() {
+            if peer_id == &id_info.id
+                && matches!(id_info.status, InUse | InUsePendingNewConnectionId)
+            {
+                id_info.status = PendingRetirement;
+            }
+        }
+    }
+
     /// Tries to consume a new peer_id if one is available.
     ///
     /// Register the stateless reset token once a connection ID is in use.
@@ -440,6 +440,73 @@ pub fn consume_new_id_should_error_if_no_ids_are_available() {
     assert_eq!(None, reg.consume_new_id_inner());
 }
 
+#[test]
+pub fn retire_id_should_retire_in_use_id() {
+    let id_1 = id(b"id01");
+    let mut random_generator = random::testing::Generator(123);
+    let mut mapper = ConnectionIdMapper::new(&mut random_generator, endpoint::Type::Server);
+    let mut reg = mapper
+        .create_server_peer_id_registry(InternalConnectionIdGenerator::new().generate_id(), id_1);
+
+    let id_2 = id(b"id02");
+    assert!(reg.on_new_connection_id(&id_2, 1, 0, &TEST_TOKEN_2).is_ok());
+    assert!(reg.has_new_id());
+    assert_eq!(Some(id_2), reg.consume_new_id_for_new_path());
+    assert!(!reg.has_new_id());
+
+    // Unused ids are not retired
+    let id_3 = id(b"id03");
+    assert!(reg.on_new_connection_id(&id_3, 2, 0, &TEST_TOKEN_3).is_ok());
+    reg.retire_id(&id_3);
+    assert_eq!(New, reg.registered_ids[2].status);
+
+    reg.retire_id(&id_2);
+    assert_eq!(PendingRetirement, reg.registered_ids[1].status);
+    assert!(!reg.is_active(&id_2));
+    assert!(reg.has_new_id());
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.1
+//= type=test
+//# If the preferred_address transport parameter is
+//# sent, the sequence number of the supplied connection ID is 1.
+#[test]
+pub fn register_preferred_address_connection_id() {
+    let id_1 = id(b"id01");
+    let mut reg = peer_registry(id_1, Some(TEST_TOKEN_1));
+
+    // The stateless reset token must not repeat the token of the handshake connection ID
+    let id_2 = id(b"id02");
+    assert_eq!(
+        Some(InvalidNewConnectionId),
+        reg.register_preferred_address_connection_id(&id_2, &TEST_TOKEN_1)
+            .err()
+    );
+    assert_eq!(1, reg.registered_ids.len());
+
+    assert!(reg
+        .register_preferred_address_connection_id(&id_2, &TEST_TOKEN_2)
+        .is_ok());
+    assert_eq!(2, reg.registered_ids.len());
+    assert_eq!(1, reg.registered_ids[1].sequence_number);
+    assert_eq!(New, reg.registered_ids[1].status);
+
+    // The handshake connection ID remains in use
+    assert_eq!(InUsePendingNewConnectionId, reg.registered_ids[0].status);
+
+    // A NEW_CONNECTION_ID frame repeating the connection ID is a duplicate
+    assert!(reg.on_new_connection_id(&id_2, 1, 0, &TEST_TOKEN_2).is_ok());
+    assert_eq!(2, reg.registered_ids.len());
+
+    // A NEW_CONNECTION_ID frame retires the handshake connection ID as usual
+    let id_3 = id(b"id03");
+    assert!(reg.on_new_connection_id(&id_3, 2, 0, &TEST_TOKEN_3).is_ok());
+    assert_eq!(PendingRetirement, reg.registered_ids[0].status);
+    assert_eq!(New, reg.registered_ids[1].status);
+
+    assert_eq!(Some(id_2), reg.consume_new_id_for_new_path());
+}
+
 #[test]
 fn error_conversion() {
     //= https://www.rfc-editor.org/rfc/rfc9000#section-19.15
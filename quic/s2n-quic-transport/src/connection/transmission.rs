@@ -154,6 +154,9 @@ impl<'a, 'sub, Config: endpoint::Config> tx::Message for ConnectionTransmission<
                 Some(PacketNumberSpace::ApplicationData)
             } else if has_transmission(space_manager.handshake(), transmission_constraint) {
                 Some(PacketNumberSpace::Handshake)
+            } else if has_transmission(space_manager.zero_rtt(), transmission_constraint) {
+                // 0-RTT packets are coalesced after the Initial packet
+                Some(PacketNumberSpace::ApplicationData)
             } else {
                 //= https://www.rfc-editor.org/rfc/rfc9001#section-4.9
                 //# These packets MAY also include PADDING frames.
@@ -221,6 +224,39 @@ impl<'a, 'sub, Config: endpoint::Config> tx::Message for ConnectionTransmission<
             encoder
         };
 
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-12.2
+        //# Coalescing packets in order of increasing encryption levels (Initial,
+        //# 0-RTT, Handshake, 1-RTT; see Section 4.1.4 of [QUIC-TLS]) makes it
+        //# more likely that the receiver will be able to process all the packets
+        //# in a single pass.
+        let encoder = if let Some((space, handshake_status)) = space_manager
+            .zero_rtt_mut()
+            // MTU probes are only sent in the Application Space
+            .filter(|_| !is_mtu_probing)
+        {
+            self.context.min_packet_len = pn_space_to_pad
+                .filter(|pn_space| pn_space.is_application_data())
+                .map(|_| encoder.capacity());
+
+            match space.on_transmit(
+                &mut self.context,
+                transmission_constraint,
+                handshake_status,
+                encoder,
+            ) {
+                Ok((outcome, encoder)) => {
+                    *self.context.outcome += outcome;
+                    encoder
+                }
+                Err(err) => {
+                    // move to the next packet space
+                    err.take_buffer()
+                }
+            }
+        } else {
+            encoder
+        };
+
         let encoder = if let Some((space, handshake_status)) = space_manager
             .handshake_mut()
             // MTU probes are only sent in the Application Space
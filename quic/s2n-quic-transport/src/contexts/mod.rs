@@ -68,6 +68,9 @@ pub trait WriteContext {
 
     /// Returns the length of the authentication tag in bytes
     fn tag_len(&self) -> usize;
+
+    /// Called when data that was previously declared lost has been written to the packet
+    fn on_data_retransmitted(&mut self, len: usize);
 }
 
 /// Enumerates error values for `on_transmit` calls
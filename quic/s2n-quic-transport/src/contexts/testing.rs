@@ -58,6 +58,8 @@ pub struct OutgoingFrameBuffer {
     /// permitted before errors are returned on write. This can be used to simulate
     /// failing write calls.
     error_after_frames: Option<usize>,
+    /// The number of previously lost data bytes which have been written
+    pub bytes_retransmitted: usize,
 }
 
 impl Default for OutgoingFrameBuffer {
@@ -70,6 +72,7 @@ impl Default for OutgoingFrameBuffer {
             max_buffer_size: None,
             remaining_packet_space: 0,
             error_after_frames: None,
+            bytes_retransmitted: 0,
         }
     }
 }
@@ -314,4 +317,8 @@ impl<'a> WriteContext for MockWriteContext<'a> {
     fn tag_len(&self) -> usize {
         0
     }
+
+    fn on_data_retransmitted(&mut self, len: usize) {
+        self.frame_buffer.bytes_retransmitted += len;
+    }
 }
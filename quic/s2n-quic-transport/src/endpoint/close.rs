@@ -5,9 +5,33 @@ use super::handle::CloseReceiver;
 use crate::{connection, endpoint::handle::CloseSender};
 use alloc::sync::Arc;
 use core::{
+    pin::Pin,
     sync::atomic::{AtomicBool, Ordering},
     task::{Context, Poll, Waker},
+    time::Duration,
 };
+use futures_core::Stream;
+use s2n_quic_core::{
+    application,
+    time::{timer, Clock, Timer, Timestamp},
+};
+
+/// Bounds the amount of time connections are given to finish once the endpoint is closing
+///
+/// Connections that are still open after `duration` has elapsed are closed with `error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GracePeriod {
+    pub duration: Duration,
+    pub error: application::Error,
+}
+
+/// A request from the application to close the endpoint
+#[derive(Debug)]
+pub(crate) struct Request {
+    /// Woken once the endpoint has closed
+    waker: Waker,
+    grace_period: Option<GracePeriod>,
+}
 
 /// Held by library. Used to receive close attempts and track close state.
 #[derive(Debug)]
@@ -18,6 +42,10 @@ pub(crate) struct CloseHandle {
     close_receiver: CloseReceiver,
     /// Track the endpoint open state
     endpoint_state: EndpointState,
+    /// Armed when the first close request includes a grace period
+    grace_period_timer: Timer,
+    /// The error used to close connections once the grace period has elapsed
+    grace_period_error: Option<application::Error>,
 }
 
 impl CloseHandle {
@@ -26,21 +54,45 @@ impl CloseHandle {
             first_waker: None,
             close_receiver,
             endpoint_state,
+            grace_period_timer: Timer::default(),
+            grace_period_error: None,
         }
     }
 
     /// Returns `Poll::Ready` if there is interest in closing the endpoint.
-    pub fn poll_interest(&mut self) -> Poll<()> {
+    ///
+    /// The grace period of the first close request starts when the request is received.
+    pub fn poll_interest<C: Clock>(&mut self, cx: &mut Context, clock: &C) -> Poll<()> {
         if self.first_waker.is_some() {
-            Poll::Ready(())
-        } else {
-            match self.close_receiver.try_next() {
-                Ok(Some(waker)) => {
-                    self.first_waker = Some(waker);
-                    Poll::Ready(())
+            return Poll::Ready(());
+        }
+
+        match Stream::poll_next(Pin::new(&mut self.close_receiver), cx) {
+            Poll::Ready(Some(request)) => {
+                if let Some(grace_period) = request.grace_period {
+                    self.grace_period_timer
+                        .set(clock.get_time() + grace_period.duration);
+                    self.grace_period_error = Some(grace_period.error);
                 }
-                _ => Poll::Pending,
+                self.first_waker = Some(request.waker);
+                Poll::Ready(())
             }
+            _ => Poll::Pending,
+        }
+    }
+
+    /// Returns `true` if the application has requested the endpoint to close
+    pub fn is_closing(&self) -> bool {
+        self.first_waker.is_some() || !self.endpoint_state.is_open()
+    }
+
+    /// Returns the error to close the remaining connections with if the grace period
+    /// has elapsed
+    pub fn on_timeout(&mut self, now: Timestamp) -> Option<application::Error> {
+        if self.grace_period_timer.poll_expiration(now).is_ready() {
+            self.grace_period_error.take()
+        } else {
+            None
         }
     }
 
@@ -49,15 +101,24 @@ impl CloseHandle {
     pub fn close(&mut self) {
         self.endpoint_state.close();
 
+        self.grace_period_timer.cancel();
+
         if let Some(waker) = self.first_waker.take() {
             waker.wake();
         }
-        while let Ok(Some(waker)) = self.close_receiver.try_next() {
-            waker.wake_by_ref();
+        while let Ok(Some(request)) = self.close_receiver.try_next() {
+            request.waker.wake_by_ref();
         }
     }
 }
 
+impl timer::Provider for CloseHandle {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        query.on_timer(&self.grace_period_timer)
+    }
+}
+
 /// Track if the endpoint is still open and handling connections
 #[derive(Clone, Debug)]
 pub(crate) struct EndpointState(Arc<AtomicBool>);
@@ -98,9 +159,13 @@ impl Closer {
         }
     }
 
+    /// Polls for the endpoint to close
+    ///
+    /// The `grace_period` is only used by the first close request the endpoint receives.
     pub(crate) fn poll_close(
         &mut self,
         context: &mut Context,
+        grace_period: Option<GracePeriod>,
     ) -> Poll<Result<(), connection::Error>> {
         if !self.endpoint_state.is_open() {
             return Poll::Ready(Ok(()));
@@ -110,7 +175,11 @@ impl Closer {
             match self.close_sender.poll_ready(context) {
                 Poll::Ready(Ok(())) => {
                     // send a waker to the endpoint, which is woken once the endpoint has closed
-                    match self.close_sender.try_send(context.waker().clone()) {
+                    let request = Request {
+                        waker: context.waker().clone(),
+                        grace_period,
+                    };
+                    match self.close_sender.try_send(request) {
                         Ok(_) => {
                             self.request_sent = true;
                         }
@@ -139,3 +208,74 @@ impl Closer {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_channel::mpsc;
+    use futures_test::task::new_count_waker;
+    use s2n_quic_core::time::{testing::Clock as TestClock, timer::Provider as _};
+
+    fn close_pair() -> (CloseHandle, Closer) {
+        let (close_sender, close_receiver) = mpsc::channel(1);
+        let endpoint_state = EndpointState::default();
+        let closer = Closer::new(close_sender, endpoint_state.clone());
+        let handle = CloseHandle::new(close_receiver, endpoint_state);
+        (handle, closer)
+    }
+
+    #[test]
+    fn close_without_grace_period() {
+        let (mut handle, mut closer) = close_pair();
+        let (endpoint_waker, _) = new_count_waker();
+        let mut endpoint_cx = Context::from_waker(&endpoint_waker);
+        let (waker, wake_counter) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let clock = TestClock::default();
+
+        assert!(handle.poll_interest(&mut endpoint_cx, &clock).is_pending());
+        assert!(!handle.is_closing());
+
+        assert!(closer.poll_close(&mut cx, None).is_pending());
+        assert!(handle.poll_interest(&mut endpoint_cx, &clock).is_ready());
+        assert!(handle.is_closing());
+        assert_eq!(None, handle.next_expiration());
+        assert_eq!(wake_counter, 0);
+
+        handle.close();
+        assert_eq!(wake_counter, 1);
+        assert!(handle.is_closing());
+        assert!(closer.poll_close(&mut cx, None).is_ready());
+    }
+
+    #[test]
+    fn close_with_grace_period() {
+        let (mut handle, mut closer) = close_pair();
+        let (waker, _wake_counter) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut clock = TestClock::default();
+
+        let grace_period = GracePeriod {
+            duration: Duration::from_secs(5),
+            error: application::Error::from(7u8),
+        };
+        assert!(closer.poll_close(&mut cx, Some(grace_period)).is_pending());
+        assert!(handle.poll_interest(&mut cx, &clock).is_ready());
+
+        let deadline = clock.get_time() + grace_period.duration;
+        assert_eq!(Some(deadline), handle.next_expiration());
+
+        clock.inc_by(Duration::from_secs(4));
+        assert_eq!(None, handle.on_timeout(clock.get_time()));
+
+        clock.inc_by(Duration::from_secs(1));
+        assert_eq!(
+            Some(grace_period.error),
+            handle.on_timeout(clock.get_time())
+        );
+
+        // The error is only returned once
+        assert_eq!(None, handle.on_timeout(clock.get_time()));
+        assert_eq!(None, handle.next_expiration());
+    }
+}
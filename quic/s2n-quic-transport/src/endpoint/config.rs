@@ -5,8 +5,9 @@
 
 use crate::{connection, stream};
 use s2n_quic_core::{
-    crypto::tls, endpoint, event, packet, path, random, recovery::congestion_controller,
-    stateless_reset,
+    connection::early_data, crypto::tls, endpoint, event, packet, path, random,
+    recovery::congestion_controller, stateless_reset,
+    transport::parameters::CustomTransportParameters,
 };
 
 /// Configuration parameters for a QUIC endpoint
@@ -42,6 +43,8 @@ pub trait Config: 'static + Send + Sized + core::fmt::Debug {
     type PathMigrationValidator: path::migration::Validator;
     /// The packet_interceptor implementation for the endpoint
     type PacketInterceptor: packet::interceptor::Interceptor;
+    /// The policy for accepting 0-RTT data on the endpoint
+    type EarlyDataPolicy: early_data::Policy;
 
     /// The type of the local endpoint
     const ENDPOINT_TYPE: endpoint::Type;
@@ -83,4 +86,9 @@ pub struct Context<'a, Cfg: Config> {
     pub path_migration: &'a mut Cfg::PathMigrationValidator,
 
     pub packet_interceptor: &'a mut Cfg::PacketInterceptor,
+
+    pub early_data: &'a mut Cfg::EarlyDataPolicy,
+
+    /// The transport parameters defined by the application
+    pub custom_transport_parameters: &'a CustomTransportParameters,
 }
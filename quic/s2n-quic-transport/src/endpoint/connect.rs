@@ -12,7 +12,9 @@ use core::{
     task::{Context, Poll},
 };
 use futures_channel::oneshot;
-use s2n_quic_core::{application::ServerName, inet::SocketAddress, path::RemoteAddress};
+use s2n_quic_core::{
+    application::ServerName, inet::SocketAddress, packet::long::VERSION_1, path::RemoteAddress,
+};
 
 /// Held by connection Attempt future. Used to receive the actual connection.
 pub(crate) type ConnectionReceiver = oneshot::Receiver<Result<Connection, connection::Error>>;
@@ -25,6 +27,8 @@ pub(crate) type ConnectionSender = oneshot::Sender<Result<Connection, connection
 pub struct Connect {
     pub(crate) remote_address: RemoteAddress,
     pub(crate) server_name: Option<ServerName>,
+    pub(crate) quic_version: u32,
+    pub(crate) compatible_quic_version: Option<u32>,
 }
 
 impl fmt::Display for Connect {
@@ -49,6 +53,8 @@ impl Connect {
         Self {
             remote_address: addr.into().into(),
             server_name: None,
+            quic_version: VERSION_1,
+            compatible_quic_version: None,
         }
     }
 
@@ -60,6 +66,34 @@ impl Connect {
             ..self
         }
     }
+
+    /// Specifies the QUIC version to offer in the first Initial packet
+    ///
+    /// Defaults to QUIC version 1 (`0x00000001`). QUIC version 2 (`0x6b3343cf`) can be offered
+    /// when the TLS provider supports it. If the version is not supported by the endpoint, the
+    /// connection attempt will fail.
+    #[must_use]
+    pub fn with_quic_version(self, quic_version: u32) -> Self {
+        Self {
+            quic_version,
+            ..self
+        }
+    }
+
+    /// Specifies a QUIC version the server may upgrade the connection to during the handshake
+    ///
+    /// The client starts the connection with the version passed to
+    /// [`with_quic_version`](Self::with_quic_version) and advertises this version as its
+    /// preferred one. A server supporting it switches to the version without an additional
+    /// round trip. If the version is not supported by the endpoint, the connection attempt
+    /// will fail.
+    #[must_use]
+    pub fn with_compatible_quic_version(self, quic_version: u32) -> Self {
+        Self {
+            compatible_quic_version: Some(quic_version),
+            ..self
+        }
+    }
 }
 
 /// Make it easy for applications to create a connection attempt without importing the `Connect` struct
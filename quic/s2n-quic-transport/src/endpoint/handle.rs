@@ -10,7 +10,7 @@ use crate::{
 };
 use core::{
     pin::Pin,
-    task::{Context, Poll, Waker},
+    task::{Context, Poll},
 };
 use futures_channel::mpsc;
 use futures_core::Stream;
@@ -26,9 +26,9 @@ pub(crate) type ConnectorReceiver = mpsc::Receiver<connect::Request>;
 pub(crate) type ConnectorSender = mpsc::Sender<connect::Request>;
 
 /// Held by library. Used to receive close attempts from the application.
-pub(crate) type CloseReceiver = mpsc::Receiver<Waker>;
+pub(crate) type CloseReceiver = mpsc::Receiver<close::Request>;
 /// Held by the application. Used to submit connection close attempts to the library.
-pub(crate) type CloseSender = mpsc::Sender<Waker>;
+pub(crate) type CloseSender = mpsc::Sender<close::Request>;
 
 /// The [`Handle`] allows applications to accept and open QUIC connections on an `Endpoint`.
 #[derive(Debug)]
@@ -52,6 +52,7 @@ impl Handle {
         let handle = Self {
             acceptor: Acceptor {
                 acceptor: acceptor_receiver,
+                closer: closer.clone(),
             },
             connector: Connector {
                 connector: connector_sender,
@@ -70,6 +71,7 @@ impl Handle {
 #[derive(Debug)]
 pub struct Acceptor {
     acceptor: AcceptorReceiver,
+    closer: close::Closer,
 }
 
 impl Acceptor {
@@ -92,6 +94,20 @@ impl Acceptor {
             Poll::Pending => Poll::Pending,
         }
     }
+
+    /// Polls to close the endpoint
+    ///
+    /// Once called, the endpoint stops accepting new connections. The method returns
+    /// `Poll::Ready` once all of the existing connections have closed. If a `grace_period`
+    /// is provided, connections that are still open once it has elapsed are closed with the
+    /// grace period's error.
+    pub fn poll_close(
+        &mut self,
+        context: &mut Context,
+        grace_period: Option<close::GracePeriod>,
+    ) -> Poll<Result<(), connection::Error>> {
+        self.closer.poll_close(context, grace_period)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -108,6 +124,6 @@ impl Connector {
 
     /// Polls to close the endpoint
     pub fn poll_close(&mut self, context: &mut Context) -> Poll<Result<(), connection::Error>> {
-        self.closer.poll_close(context)
+        self.closer.poll_close(context, None)
     }
 }
@@ -10,18 +10,25 @@ use crate::{
     },
     endpoint,
     recovery::congestion_controller::{self, Endpoint as _},
-    space::PacketSpaceManager,
+    space::{PacketSpaceManager, VersionNegotiation},
 };
 use core::convert::TryInto;
 use s2n_codec::DecoderBufferMut;
 use s2n_quic_core::{
-    crypto::{tls, tls::Endpoint as TLSEndpoint, CryptoSuite, InitialKey},
+    connection::early_data::{self, Policy as _},
+    crypto::{
+        tls::{self, Endpoint as TLSEndpoint, Session as _},
+        CryptoSuite, InitialKey,
+    },
     event::{self, supervisor, ConnectionPublisher, IntoEvent, Subscriber as _},
     inet::{datagram, DatagramInfo},
     packet::initial::ProtectedInitial,
     path::Handle as _,
     stateless_reset::token::Generator as _,
-    transport::{self, parameters::ServerTransportParameters},
+    transport::{
+        self,
+        parameters::{PreferredAddress, ServerTransportParameters},
+    },
 };
 
 impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
@@ -105,6 +112,7 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
         //# a change to the keys used to protect the Initial packet.
         let (initial_key, initial_header_key) =
             <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_server(
+                packet.version,
                 datagram.destination_connection_id.as_bytes(),
             );
 
@@ -129,7 +137,7 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
             .stateless_reset_token_generator
             .generate(initial_connection_id.as_bytes());
 
-        let local_id_registry = self.connection_id_mapper.create_local_id_registry(
+        let mut local_id_registry = self.connection_id_mapper.create_local_id_registry(
             internal_connection_id,
             &initial_connection_id,
             initial_connection_id_expiration_time,
@@ -200,12 +208,75 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
         .try_into()
         .unwrap();
 
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+        //# A server conveys a preferred address by including the
+        //# preferred_address transport parameter in the TLS handshake.
+        if limits.preferred_ipv4_address().is_some() || limits.preferred_ipv6_address().is_some() {
+            let connection_info = ConnectionInfo::new(&remote_address);
+            let connection_id = self
+                .config
+                .context()
+                .connection_id_format
+                .generate(&connection_info);
+            let stateless_reset_token = self
+                .config
+                .context()
+                .stateless_reset_token_generator
+                .generate(connection_id.as_bytes());
+
+            // If the connection ID can't be registered, the preferred address is not advertised
+            // and the connection remains on the handshake address.
+            if local_id_registry
+                .register_preferred_address_connection_id(
+                    &connection_id,
+                    initial_connection_id_expiration_time,
+                    stateless_reset_token,
+                )
+                .is_ok()
+            {
+                transport_parameters.preferred_address = Some(PreferredAddress {
+                    ipv4_address: limits.preferred_ipv4_address(),
+                    ipv6_address: limits.preferred_ipv6_address(),
+                    connection_id: connection_id
+                        .as_bytes()
+                        .try_into()
+                        .expect("connection ID already validated"),
+                    stateless_reset_token,
+                });
+            }
+        }
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-3>
+        //
+        // Servers always include their version information, which the client uses to detect
+        // downgrades. It is updated if a compatible version is negotiated with the client.
+        transport_parameters.version_information = VersionNegotiation::server_information(
+            packet.version,
+            endpoint::version::supported_versions::<Config>(),
+        );
+
         let endpoint_context = self.config.context();
 
-        let tls_session = endpoint_context
+        transport_parameters.custom_parameters =
+            endpoint_context.custom_transport_parameters.clone();
+
+        let mut tls_session = endpoint_context
             .tls
             .new_server_session(&transport_parameters);
 
+        let early_data_attempt =
+            early_data::Attempt::new(&remote_address, retry_token_dcid.is_some());
+        if !endpoint_context
+            .early_data
+            .on_early_data(&early_data_attempt)
+            .is_accept()
+        {
+            //= https://www.rfc-editor.org/rfc/rfc9001#section-4.6.2
+            //# A server rejects 0-RTT by rejecting 0-RTT at the TLS layer.  This
+            //# also prevents QUIC from sending 0-RTT data.
+            tls_session.reject_early_data();
+        }
+
         let path_info = congestion_controller::PathInfo::new(&remote_address);
         let congestion_controller = endpoint_context
             .congestion_controller
@@ -243,6 +314,11 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
             tls_session,
             initial_key,
             initial_header_key,
+            VersionNegotiation::server(
+                quic_version,
+                datagram.destination_connection_id,
+                transport_parameters,
+            ),
             datagram.timestamp,
             &mut publisher,
         );
@@ -13,7 +13,7 @@ use crate::{
     endpoint,
     endpoint::close::CloseHandle,
     recovery::congestion_controller::{self, Endpoint as _},
-    space::PacketSpaceManager,
+    space::{PacketSpaceManager, VersionNegotiation},
     wakeup_queue::WakeupQueue,
 };
 use alloc::collections::VecDeque;
@@ -39,7 +39,7 @@ use s2n_quic_core::{
     path::{Handle as _, MaxMtu},
     random::Generator as _,
     stateless_reset::token::{Generator as _, LEN as StatelessResetTokenLen},
-    time::{Clock, Timestamp},
+    time::{timer::Provider as _, Clock, Timestamp},
     token::{self, Format},
     transport::parameters::ClientTransportParameters,
 };
@@ -52,7 +52,7 @@ mod initial;
 mod packet_buffer;
 mod retry;
 mod stateless_reset;
-mod version;
+pub(crate) mod version;
 
 // exports
 pub use config::{Config, Context};
@@ -172,7 +172,7 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
         cx: &mut task::Context<'_>,
         clock: &C,
     ) -> Poll<Result<usize, s2n_quic_core::endpoint::CloseError>> {
-        if self.close_handle.poll_interest().is_ready() // poll for close interest
+        if self.close_handle.poll_interest(cx, clock).is_ready() // poll for close interest
             && self.connections.is_empty() // wait for all connections to close gracefully
             && self.connections.is_open()
         {
@@ -207,7 +207,12 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
                     }
                 };
 
-                if let Err(error) = conn.on_wakeup(timestamp, endpoint_context.event_subscriber) {
+                if let Err(error) = conn.on_wakeup(
+                    timestamp,
+                    endpoint_context.random_generator,
+                    endpoint_context.congestion_controller,
+                    endpoint_context.event_subscriber,
+                ) {
                     conn.close(
                         error,
                         endpoint_context.connection_close_formatter,
@@ -252,7 +257,13 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
 
     #[inline]
     fn timeout(&self) -> Option<Timestamp> {
-        self.connections.next_expiration()
+        let connections = self.connections.next_expiration();
+        let close = self.close_handle.next_expiration();
+
+        match (connections, close) {
+            (Some(connections), Some(close)) => Some(connections.min(close)),
+            (connections, close) => connections.or(close),
+        }
     }
 
     #[inline]
@@ -326,6 +337,23 @@ impl<Cfg: Config> Endpoint<Cfg> {
             return None;
         }
 
+        if self.close_handle.is_closing() {
+            // The application is shutting down the endpoint so no new connections are accepted
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: Cfg::ENDPOINT_TYPE,
+                    timestamp,
+                },
+                None,
+                self.config.context().event_subscriber,
+            );
+            publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
+                len: payload_len as u16,
+                reason: event::builder::DatagramDropReason::RejectedConnectionAttempt,
+            });
+            return None;
+        }
+
         let remote_address = header.path.remote_address();
 
         let attempt = s2n_quic_core::endpoint::limits::ConnectionAttempt::new(
@@ -830,6 +858,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
             max_tag_length,
             triggering_packet_len,
             self.config.context().random_generator,
+            datagram.timestamp,
         );
     }
 
@@ -895,6 +924,21 @@ impl<Cfg: Config> Endpoint<Cfg> {
         let close_packet_buffer = &mut self.close_packet_buffer;
         let endpoint_context = self.config.context();
 
+        if let Some(error) = self.close_handle.on_timeout(timestamp) {
+            // The grace period for closing the endpoint has elapsed so close any connections
+            // that are still open
+            self.connections.iterate_all(|conn| {
+                conn.close(
+                    connection::Error::application(error),
+                    endpoint_context.connection_close_formatter,
+                    close_packet_buffer,
+                    timestamp,
+                    endpoint_context.event_subscriber,
+                    endpoint_context.packet_interceptor,
+                );
+            });
+        }
+
         self.connections
             .iterate_timeout_list(timestamp, |conn, supervisor_context| {
                 if let Err(error) = conn.on_timeout(
@@ -943,10 +987,19 @@ impl<Cfg: Config> Endpoint<Cfg> {
                 endpoint::connect::Connect {
                     remote_address,
                     server_name: hostname,
+                    quic_version,
+                    compatible_quic_version,
                 },
             sender,
         } = request;
 
+        if !core::iter::once(quic_version)
+            .chain(compatible_quic_version)
+            .all(version::is_supported::<Cfg>)
+        {
+            return Err(connection::Error::unspecified());
+        }
+
         let internal_connection_id = self.connection_id_generator.generate_id();
         let local_connection_id = self
             .config
@@ -1012,11 +1065,6 @@ impl<Cfg: Config> Endpoint<Cfg> {
                 .new_congestion_controller(path_info)
         };
 
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-15
-        //# This version of the specification is identified by the number
-        //# 0x00000001.
-        let quic_version = 0x00000001;
-
         let meta = event::builder::ConnectionMeta {
             endpoint_type: Cfg::ENDPOINT_TYPE,
             id: internal_connection_id.into(),
@@ -1054,6 +1102,11 @@ impl<Cfg: Config> Endpoint<Cfg> {
         .try_into()
         .unwrap();
 
+        let version_negotiation = VersionNegotiation::client(quic_version, compatible_quic_version);
+        transport_parameters.version_information = version_negotiation.client_information();
+        transport_parameters.custom_parameters =
+            endpoint_context.custom_transport_parameters.clone();
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-7.2
         //# The Destination Connection ID field from the first Initial packet
         //# sent by a client is used to determine packet protection keys for
@@ -1063,6 +1116,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
         // protection keys.
         let (initial_key, initial_header_key) =
             <<Cfg::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_client(
+                quic_version,
                 original_destination_connection_id.as_bytes(),
             );
         let tls_session = endpoint_context
@@ -1078,6 +1132,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
             tls_session,
             initial_key,
             initial_header_key,
+            version_negotiation,
             timestamp,
             &mut publisher,
         );
@@ -1142,6 +1197,7 @@ pub mod testing {
         type EventSubscriber = Subscriber;
         type PathMigrationValidator = path::migration::default::Validator;
         type PacketInterceptor = s2n_quic_core::packet::interceptor::Disabled;
+        type EarlyDataPolicy = s2n_quic_core::connection::early_data::default::Policy;
 
         fn context(&mut self) -> super::Context<Self> {
             todo!()
@@ -1171,6 +1227,7 @@ pub mod testing {
         type EventSubscriber = Subscriber;
         type PathMigrationValidator = path::migration::default::Validator;
         type PacketInterceptor = s2n_quic_core::packet::interceptor::Disabled;
+        type EarlyDataPolicy = s2n_quic_core::connection::early_data::default::Policy;
 
         fn context(&mut self) -> super::Context<Self> {
             todo!()
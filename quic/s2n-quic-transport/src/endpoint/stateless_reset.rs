@@ -3,14 +3,29 @@
 
 use crate::endpoint;
 use alloc::collections::VecDeque;
+use core::time::Duration;
 use s2n_quic_core::{
-    event, inet::ExplicitCongestionNotification, io::tx, packet, path, path::MINIMUM_MTU, random,
-    stateless_reset, time,
+    event,
+    inet::ExplicitCongestionNotification,
+    io::tx,
+    packet, path,
+    path::MINIMUM_MTU,
+    random, stateless_reset,
+    time::{self, Timestamp},
 };
 
+/// The interval over which the number of sent stateless resets is limited
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 pub struct Dispatch<Path: path::Handle> {
     transmissions: VecDeque<Transmission<Path>>,
+    /// The maximum number of stateless resets that will be queued within a `RATE_LIMIT_INTERVAL`
+    max_peers: usize,
+    /// The start of the current rate limiting interval
+    interval_start: Option<Timestamp>,
+    /// The number of stateless resets queued during the current interval
+    interval_count: usize,
 }
 
 impl<Path: path::Handle> Default for Dispatch<Path> {
@@ -23,6 +38,9 @@ impl<Path: path::Handle> Dispatch<Path> {
     pub fn new(max_peers: usize) -> Self {
         Self {
             transmissions: VecDeque::with_capacity(max_peers),
+            max_peers,
+            interval_start: None,
+            interval_count: 0,
         }
     }
 
@@ -33,7 +51,16 @@ impl<Path: path::Handle> Dispatch<Path> {
         max_tag_len: usize,
         triggering_packet_len: usize,
         random_generator: &mut R,
+        timestamp: Timestamp,
     ) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.3
+        //# An endpoint can remember the number of Stateless Resets that it has
+        //# sent and stop generating new Stateless Resets once a limit is
+        //# reached.
+        if !self.check_rate_limit(timestamp) {
+            return;
+        }
+
         if let Some(transmission) = Transmission::new(
             path,
             token,
@@ -42,7 +69,22 @@ impl<Path: path::Handle> Dispatch<Path> {
             random_generator,
         ) {
             self.transmissions.push_back(transmission);
+            self.interval_count += 1;
+        }
+    }
+
+    /// Returns `true` if another stateless reset can be queued at the given `timestamp`
+    fn check_rate_limit(&mut self, timestamp: Timestamp) -> bool {
+        let interval_expired = self.interval_start.map_or(true, |start| {
+            timestamp.saturating_duration_since(start) >= RATE_LIMIT_INTERVAL
+        });
+
+        if interval_expired {
+            self.interval_start = Some(timestamp);
+            self.interval_count = 0;
         }
+
+        self.interval_count < self.max_peers && self.transmissions.len() < self.max_peers
     }
 
     pub fn on_transmit<Tx: tx::Queue<Handle = Path>, Pub: event::EndpointPublisher>(
@@ -155,3 +197,110 @@ impl<Path: path::Handle> tx::Message for &Transmission<Path> {
         packet.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        inet::SocketAddress,
+        stateless_reset::token::testing::TEST_TOKEN_1,
+        time::{Clock, NoopClock},
+    };
+
+    const MAX_TAG_LEN: usize = 16;
+    const TRIGGERING_PACKET_LEN: usize = 600;
+
+    fn queue(
+        dispatch: &mut Dispatch<path::RemoteAddress>,
+        random: &mut random::testing::Generator,
+        timestamp: Timestamp,
+    ) {
+        let path = path::RemoteAddress(SocketAddress::default());
+        dispatch.queue(
+            path,
+            TEST_TOKEN_1,
+            MAX_TAG_LEN,
+            TRIGGERING_PACKET_LEN,
+            random,
+            timestamp,
+        );
+    }
+
+    #[test]
+    fn rate_limit_test() {
+        let max_peers = 3;
+        let mut dispatch = Dispatch::new(max_peers);
+        let mut random = random::testing::Generator::default();
+        let now = NoopClock.get_time();
+
+        for _ in 0..max_peers + 1 {
+            queue(&mut dispatch, &mut random, now);
+        }
+        assert_eq!(dispatch.transmissions.len(), max_peers);
+
+        // Draining the queue does not reset the limit within the same interval
+        dispatch.transmissions.clear();
+        queue(
+            &mut dispatch,
+            &mut random,
+            now + RATE_LIMIT_INTERVAL - Duration::from_millis(1),
+        );
+        assert!(dispatch.transmissions.is_empty());
+
+        // The limit is reset once the interval expires
+        queue(&mut dispatch, &mut random, now + RATE_LIMIT_INTERVAL);
+        assert_eq!(dispatch.transmissions.len(), 1);
+    }
+
+    #[test]
+    fn queue_limit_test() {
+        let max_peers = 2;
+        let mut dispatch = Dispatch::new(max_peers);
+        let mut random = random::testing::Generator::default();
+        let now = NoopClock.get_time();
+
+        for i in 0..max_peers as u32 + 1 {
+            queue(&mut dispatch, &mut random, now + RATE_LIMIT_INTERVAL * i);
+        }
+
+        // Pending transmissions are capped even if the rate limit has been reset
+        assert_eq!(dispatch.transmissions.len(), max_peers);
+    }
+
+    #[test]
+    fn small_triggering_packet_test() {
+        let mut dispatch = Dispatch::new(1);
+        let mut random = random::testing::Generator::default();
+        let path = path::RemoteAddress(SocketAddress::default());
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.3
+        //= type=test
+        //# An endpoint MUST ensure that every Stateless Reset that it sends is
+        //# smaller than the packet that triggered it, unless it maintains state
+        //# sufficient to prevent looping.
+        let min_len = packet::stateless_reset::min_indistinguishable_packet_len(MAX_TAG_LEN);
+        dispatch.queue(
+            path,
+            TEST_TOKEN_1,
+            MAX_TAG_LEN,
+            min_len,
+            &mut random,
+            NoopClock.get_time(),
+        );
+        assert!(dispatch.transmissions.is_empty());
+
+        dispatch.queue(
+            path,
+            TEST_TOKEN_1,
+            MAX_TAG_LEN,
+            min_len + 1,
+            &mut random,
+            NoopClock.get_time(),
+        );
+        assert_eq!(dispatch.transmissions.len(), 1);
+        assert!(dispatch.transmissions[0].as_ref().len() <= min_len);
+        assert!(dispatch.transmissions[0]
+            .as_ref()
+            .ends_with(TEST_TOKEN_1.as_ref()));
+    }
+}
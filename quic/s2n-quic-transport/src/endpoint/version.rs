@@ -6,6 +6,7 @@ use alloc::collections::VecDeque;
 use core::time::Duration;
 use s2n_codec::{Encoder, EncoderBuffer, EncoderValue};
 use s2n_quic_core::{
+    crypto::tls,
     event,
     inet::ExplicitCongestionNotification,
     io::tx,
@@ -23,23 +24,41 @@ pub struct Negotiator<Config: endpoint::Config> {
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Error;
 
-const SUPPORTED_VERSIONS: &[u32] = &[
-    0x1, // Draft 34 / Version 1 (https://github.com/quicwg/base-drafts/wiki/21st-Implementation-Draft)
+/// The versions the transport is able to process packets for
+pub(crate) const SUPPORTED_VERSIONS: &[u32] = &[
+    packet::long::VERSION_1, // Draft 34 / Version 1 (https://github.com/quicwg/base-drafts/wiki/21st-Implementation-Draft)
+    packet::long::VERSION_2, // Version 2 (https://www.rfc-editor.org/rfc/rfc9369)
 ];
 
+/// Returns the versions the endpoint is able to establish connections with, in order of
+/// preference
+///
+/// Each version requires packet protection keys from the TLS provider, so these are the versions
+/// the provider supports.
+pub fn supported_versions<Config: endpoint::Config>() -> &'static [u32] {
+    let versions = <Config::TLSEndpoint as tls::Endpoint>::QUIC_VERSIONS;
+    debug_assert!(
+        versions.iter().all(|v| SUPPORTED_VERSIONS.contains(v)),
+        "the TLS provider supports versions unknown to the transport"
+    );
+    versions
+}
+
+/// Returns `true` if the endpoint is able to establish connections with the given version
+pub fn is_supported<Config: endpoint::Config>(version: u32) -> bool {
+    supported_versions::<Config>().contains(&version)
+}
+
 macro_rules! is_supported {
     ($packet:ident, $publisher:ident) => {{
-        let supported = SUPPORTED_VERSIONS
-            .iter()
-            .cloned()
-            .any(|v| v == $packet.version);
+        let supported = is_supported::<Config>($packet.version);
 
         if supported {
             //= https://tools.ietf.org/id/draft-marx-qlog-event-definitions-quic-h3-02#5.3.1
             //# Upon receiving a client initial with a supported version, the
             //# server logs this event with server_versions and chosen_version set
             $publisher.on_version_information(event::builder::VersionInformation {
-                server_versions: &SUPPORTED_VERSIONS,
+                server_versions: supported_versions::<Config>(),
                 client_versions: &[],
                 chosen_version: Some($packet.version),
             });
@@ -51,7 +70,7 @@ macro_rules! is_supported {
             //# client's attempted version.  The absence of chosen_version implies
             //# no overlap was found.
             $publisher.on_version_information(event::builder::VersionInformation {
-                server_versions: &SUPPORTED_VERSIONS,
+                server_versions: supported_versions::<Config>(),
                 client_versions: &[$packet.version],
                 chosen_version: None,
             });
@@ -150,8 +169,11 @@ impl<Config: endpoint::Config> Negotiator<Config> {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-5.2.2
                 //# Servers SHOULD respond with a Version
                 //# Negotiation packet, provided that the datagram is sufficiently long.
-                self.transmissions
-                    .push_back(Transmission::new(*path, packet));
+                self.transmissions.push_back(Transmission::new(
+                    *path,
+                    packet,
+                    supported_versions::<Config>(),
+                ));
             }
         }
 
@@ -211,11 +233,15 @@ impl<Path: path::Handle> core::fmt::Debug for Transmission<Path> {
 }
 
 impl<Path: path::Handle> Transmission<Path> {
-    pub fn new(path: Path, initial_packet: &packet::initial::ProtectedInitial) -> Self {
+    pub fn new(
+        path: Path,
+        initial_packet: &packet::initial::ProtectedInitial,
+        supported_versions: &'static [u32],
+    ) -> Self {
         let mut packet_buf = [0u8; MINIMUM_MTU as usize];
         let version_packet = packet::version_negotiation::VersionNegotiation::from_initial(
             initial_packet,
-            SupportedVersions,
+            SupportedVersions(supported_versions),
         );
 
         let mut buffer = EncoderBuffer::new(&mut packet_buf);
@@ -272,11 +298,11 @@ impl<Path: path::Handle> tx::Message for &Transmission<Path> {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct SupportedVersions;
+pub struct SupportedVersions(&'static [u32]);
 
 impl EncoderValue for SupportedVersions {
     fn encode<E: Encoder>(&self, encoder: &mut E) {
-        for version in SUPPORTED_VERSIONS {
+        for version in self.0 {
             encoder.encode(version);
         }
 
@@ -473,7 +499,7 @@ mod tests {
                 tag: 0,
                 destination_connection_id: &[1u8, 2, 3][..],
                 source_connection_id: &[4u8, 5, 6][..],
-                supported_versions: SupportedVersions,
+                supported_versions: SupportedVersions(SUPPORTED_VERSIONS),
             }
         )
     }
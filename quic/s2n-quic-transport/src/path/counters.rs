@@ -0,0 +1,128 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_quic_core::{
+    connection::Stats, frame::ack::EcnCounts, inet::ExplicitCongestionNotification,
+};
+
+/// Tracks the packets and bytes exchanged on a path
+///
+/// The counters of all paths are summed when reporting connection [`Stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counters {
+    packets_sent: u64,
+    bytes_sent: u64,
+    packets_received: u64,
+    bytes_received: u64,
+    packets_lost: u64,
+    bytes_lost: u64,
+    packets_retransmitted: u64,
+    bytes_retransmitted: u64,
+    ecn_sent: EcnCounts,
+    ecn_received: EcnCounts,
+}
+
+impl Counters {
+    /// Called when a packet has been sent on the path
+    #[inline]
+    pub fn on_packet_sent(&mut self, bytes: usize, ecn: ExplicitCongestionNotification) {
+        self.packets_sent = self.packets_sent.saturating_add(1);
+        self.bytes_sent = self.bytes_sent.saturating_add(bytes as u64);
+        self.ecn_sent.increment(ecn);
+    }
+
+    /// Called when a datagram has been received on the path
+    #[inline]
+    pub fn on_datagram_received(&mut self, bytes: usize, ecn: ExplicitCongestionNotification) {
+        self.bytes_received = self.bytes_received.saturating_add(bytes as u64);
+        self.ecn_received.increment(ecn);
+    }
+
+    /// Called when a packet received on the path has been successfully processed
+    #[inline]
+    pub fn on_packet_received(&mut self) {
+        self.packets_received = self.packets_received.saturating_add(1);
+    }
+
+    /// Called when a packet sent on the path has been declared lost
+    #[inline]
+    pub fn on_packet_lost(&mut self, bytes: usize) {
+        self.packets_lost = self.packets_lost.saturating_add(1);
+        self.bytes_lost = self.bytes_lost.saturating_add(bytes as u64);
+    }
+
+    /// Called when a packet carrying previously lost stream or crypto data has been sent on
+    /// the path
+    #[inline]
+    pub fn on_packet_retransmitted(&mut self, bytes: usize) {
+        self.packets_retransmitted = self.packets_retransmitted.saturating_add(1);
+        self.bytes_retransmitted = self.bytes_retransmitted.saturating_add(bytes as u64);
+    }
+
+    /// Adds the counters to the totals in `stats`
+    #[inline]
+    pub fn add_to(&self, stats: &mut Stats) {
+        stats.packets_sent = stats.packets_sent.saturating_add(self.packets_sent);
+        stats.bytes_sent = stats.bytes_sent.saturating_add(self.bytes_sent);
+        stats.packets_received = stats.packets_received.saturating_add(self.packets_received);
+        stats.bytes_received = stats.bytes_received.saturating_add(self.bytes_received);
+        stats.packets_lost = stats.packets_lost.saturating_add(self.packets_lost);
+        stats.bytes_lost = stats.bytes_lost.saturating_add(self.bytes_lost);
+        stats.packets_retransmitted = stats
+            .packets_retransmitted
+            .saturating_add(self.packets_retransmitted);
+        stats.bytes_retransmitted = stats
+            .bytes_retransmitted
+            .saturating_add(self.bytes_retransmitted);
+        add_ecn_counts(&mut stats.ecn_sent, self.ecn_sent);
+        add_ecn_counts(&mut stats.ecn_received, self.ecn_received);
+    }
+}
+
+#[inline]
+fn add_ecn_counts(total: &mut EcnCounts, counts: EcnCounts) {
+    total.ect_0_count = total.ect_0_count.saturating_add(counts.ect_0_count);
+    total.ect_1_count = total.ect_1_count.saturating_add(counts.ect_1_count);
+    total.ce_count = total.ce_count.saturating_add(counts.ce_count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::varint::VarInt;
+
+    #[test]
+    fn add_to_stats() {
+        let mut first = Counters::default();
+        first.on_packet_sent(1200, ExplicitCongestionNotification::Ect0);
+        first.on_packet_sent(50, ExplicitCongestionNotification::NotEct);
+        first.on_datagram_received(1200, ExplicitCongestionNotification::Ce);
+        first.on_packet_received();
+        first.on_packet_received();
+        first.on_packet_lost(1200);
+        first.on_packet_retransmitted(1000);
+
+        let mut second = Counters::default();
+        second.on_packet_sent(1000, ExplicitCongestionNotification::Ect0);
+        second.on_datagram_received(500, ExplicitCongestionNotification::Ect0);
+        second.on_packet_received();
+        second.on_packet_retransmitted(200);
+
+        let mut stats = Stats::default();
+        first.add_to(&mut stats);
+        second.add_to(&mut stats);
+
+        assert_eq!(3, stats.packets_sent);
+        assert_eq!(2250, stats.bytes_sent);
+        assert_eq!(3, stats.packets_received);
+        assert_eq!(1700, stats.bytes_received);
+        assert_eq!(1, stats.packets_lost);
+        assert_eq!(1200, stats.bytes_lost);
+        assert_eq!(2, stats.packets_retransmitted);
+        assert_eq!(1200, stats.bytes_retransmitted);
+        assert_eq!(VarInt::from_u8(2), stats.ecn_sent.ect_0_count);
+        assert_eq!(VarInt::from_u8(0), stats.ecn_sent.ce_count);
+        assert_eq!(VarInt::from_u8(1), stats.ecn_received.ect_0_count);
+        assert_eq!(VarInt::from_u8(1), stats.ecn_received.ce_count);
+    }
+}
@@ -15,20 +15,20 @@ use s2n_quic_core::{
     event::{self, builder::DatagramDropReason, IntoEvent},
     frame,
     frame::path_validation,
-    inet::DatagramInfo,
+    inet::{DatagramInfo, IpAddress, SocketAddress},
     packet::number::PacketNumberSpace,
     path::{
-        migration::{self, Validator as _},
-        Handle as _, Id, MaxMtu,
+        migration::{self, MigrationError, Validator as _},
+        Handle as _, Id, LocalAddress, MaxMtu, RemoteAddress,
     },
     random::Generator as _,
     recovery::{
         congestion_controller::{self, Endpoint as _},
-        RttEstimator,
+        CongestionController as _, RttEstimator,
     },
     stateless_reset,
     time::{timer, Timestamp},
-    transport,
+    transport::{self, parameters::PreferredAddress},
 };
 use smallvec::SmallVec;
 
@@ -68,6 +68,26 @@ pub struct Manager<Config: endpoint::Config> {
     /// The `paths` data structure will need to be enhanced to include garbage collection
     /// of old paths to overcome this limitation.
     pending_packet_authentication: Option<u8>,
+
+    /// The path handle a client application has requested to migrate to
+    ///
+    /// The migration is performed in [`Self::migrate`], which has access to the
+    /// endpoint state needed to create a new path.
+    pending_migration: Option<Config::PathHandle>,
+
+    /// True if the peer sent the disable_active_migration transport parameter
+    active_migration_disabled: bool,
+
+    /// The server's preferred address selected from the `preferred_address` transport parameter
+    ///
+    /// The address is probed once the handshake is confirmed.
+    preferred_address: Option<RemoteAddress>,
+
+    /// True if the handshake is confirmed and the preferred address is ready to be probed
+    pending_preferred_address_probe: bool,
+
+    /// Index of the path to the server's preferred address while it is being validated
+    preferred_address_path: Option<u8>,
 }
 
 impl<Config: endpoint::Config> Manager<Config> {
@@ -78,6 +98,11 @@ impl<Config: endpoint::Config> Manager<Config> {
             active: 0,
             last_known_active_validated_path: None,
             pending_packet_authentication: None,
+            pending_migration: None,
+            active_migration_disabled: false,
+            preferred_address: None,
+            pending_preferred_address_probe: false,
+            preferred_address_path: None,
         };
         manager.paths[0].activated = true;
         manager.paths[0].is_active = true;
@@ -194,21 +219,34 @@ impl<Config: endpoint::Config> Manager<Config> {
     /// Returns the Path for the provided address if the PathManager knows about it
     #[inline]
     pub fn path(&self, handle: &Config::PathHandle) -> Option<(Id, &Path<Config>)> {
-        self.paths
-            .iter()
-            .enumerate()
-            .find(|(_id, path)| Path::eq_by_handle(path, handle))
-            .map(|(id, path)| (path_id(id as u8), path))
+        let idx = self.path_index(handle)?;
+        Some((path_id(idx as u8), &self.paths[idx]))
     }
 
     /// Returns the Path for the provided address if the PathManager knows about it
     #[inline]
     pub fn path_mut(&mut self, handle: &Config::PathHandle) -> Option<(Id, &mut Path<Config>)> {
+        let idx = self.path_index(handle)?;
+        Some((path_id(idx as u8), &mut self.paths[idx]))
+    }
+
+    #[inline]
+    fn path_index(&self, handle: &Config::PathHandle) -> Option<usize> {
+        if Config::ENDPOINT_TYPE.is_client() {
+            // Paths the client migrated to are bound to a local address, so they take
+            // precedence over the initial path, which matches any local address.
+            let idx = self
+                .paths
+                .iter()
+                .position(|path| path.eq_by_local_address(handle));
+            if idx.is_some() {
+                return idx;
+            }
+        }
+
         self.paths
-            .iter_mut()
-            .enumerate()
-            .find(|(_id, path)| Path::eq_by_handle(path, handle))
-            .map(|(id, path)| (path_id(id as u8), path))
+            .iter()
+            .position(|path| Path::eq_by_handle(path, handle))
     }
 
     /// Returns an iterator over all paths pending path_challenge or path_response
@@ -452,6 +490,355 @@ impl<Config: endpoint::Config> Manager<Config> {
         Ok((new_path_id, unblocked))
     }
 
+    /// Called when the application requests the connection migrate to a new local IP address
+    ///
+    /// `peer_has_spare_id` indicates the peer acknowledged more than one of the local
+    /// connection IDs. The request is validated and stored until [`Self::migrate`] is called.
+    pub fn request_migration(
+        &mut self,
+        local_ip: IpAddress,
+        handshake_confirmed: bool,
+        peer_has_spare_id: bool,
+    ) -> Result<(), MigrationError> {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# Clients are responsible for initiating all migrations.
+        if Config::ENDPOINT_TYPE.is_server() {
+            return Err(MigrationError::Unsupported);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# An endpoint MUST NOT initiate
+        //# connection migration before the handshake is confirmed, as defined
+        //# in section 4.1.2 of [QUIC-TLS].
+        if !handshake_confirmed {
+            return Err(MigrationError::HandshakeNotConfirmed);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# If the peer sent the disable_active_migration transport parameter, an
+        //# endpoint also MUST NOT send packets (including probing packets; see
+        //# Section 9.1) from a different local address to the address the peer
+        //# used during the handshake, unless the endpoint has acted on a
+        //# preferred_address transport parameter from the peer.
+        if self.active_migration_disabled {
+            return Err(MigrationError::DisabledByPeer);
+        }
+
+        // Only the source IP is selected; the port is determined by the socket the IO
+        // provider sends from.
+        let local_address: LocalAddress = local_ip.with_port(0).into();
+        let mut handle = self.active_path().handle;
+        handle.set_local_address(local_address);
+
+        // The handle is unable to send from the requested address
+        if handle.local_address() != local_address {
+            return Err(MigrationError::Unsupported);
+        }
+
+        let existing_path = self
+            .paths
+            .iter()
+            .position(|path| path.eq_by_local_address(&handle));
+
+        if existing_path == Some(self.active as usize) {
+            // The connection is already using the local address
+            self.pending_migration = None;
+            return Ok(());
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+        //# An endpoint MUST NOT reuse a connection ID when sending from more
+        //# than one local address -- for example, when initiating connection
+        //# migration as described in Section 9.2 or when probing a new network
+        //# path as described in Section 9.1.
+        if !self.peer_id_registry.has_new_id() {
+            return Err(MigrationError::InsufficientConnectionIds);
+        }
+
+        // The peer drops packets on the new path unless it also holds an unused
+        // connection ID of ours to respond with
+        if !peer_has_spare_id {
+            return Err(MigrationError::InsufficientConnectionIds);
+        }
+
+        if existing_path.is_none() && self.paths.len() >= MAX_ALLOWED_PATHS {
+            return Err(MigrationError::PathLimitExceeded);
+        }
+
+        self.pending_migration = Some(handle);
+
+        Ok(())
+    }
+
+    /// Returns true if a migration or a probe of the server's preferred address is waiting
+    /// for [`Self::migrate`] to be called
+    pub fn has_pending_migration(&self) -> bool {
+        self.pending_migration.is_some() || self.pending_preferred_address_probe
+    }
+
+    /// Migrates to the local address requested by the application, if any
+    ///
+    /// The new path becomes the active path immediately and is validated with a
+    /// PATH_CHALLENGE. If validation fails, the connection reverts to the last validated path.
+    ///
+    /// The server's preferred address is also probed here once the handshake is confirmed.
+    pub fn migrate<Pub: event::ConnectionPublisher>(
+        &mut self,
+        random_generator: &mut Config::RandomGenerator,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
+        publisher: &mut Pub,
+    ) {
+        if core::mem::take(&mut self.pending_preferred_address_probe) {
+            self.probe_preferred_address(
+                random_generator,
+                congestion_controller_endpoint,
+                publisher,
+            );
+        }
+
+        let handle = if let Some(handle) = self.pending_migration.take() {
+            handle
+        } else {
+            return;
+        };
+
+        let existing_path = self
+            .paths
+            .iter()
+            .position(|path| path.eq_by_local_address(&handle));
+
+        if existing_path == Some(self.active as usize) {
+            return;
+        }
+
+        let new_path_idx = existing_path.unwrap_or_else(|| self.paths.len());
+        if new_path_idx >= MAX_ALLOWED_PATHS {
+            return;
+        }
+        let new_path_id = path_id(new_path_idx as u8);
+
+        if let Some(idx) = existing_path {
+            let is_last_validated_path = self.last_known_active_validated_path == Some(idx as u8);
+            if is_last_validated_path
+                && !self.active_path().is_validated()
+                && self
+                    .peer_id_registry
+                    .is_active(&self.paths[idx].peer_connection_id)
+            {
+                // The application returned to the last validated path before the migration
+                // away from it was validated, so the path is used as it was.
+                let prev_path_id = self.active_path_id();
+                self.activate_path(publisher, prev_path_id, new_path_id);
+                self.last_known_active_validated_path = None;
+                return;
+            }
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+        //# An endpoint MUST NOT reuse a connection ID when sending from more
+        //# than one local address -- for example, when initiating connection
+        //# migration as described in Section 9.2 or when probing a new network
+        //# path as described in Section 9.1.
+        let peer_connection_id = if let Some(idx) = existing_path {
+            let current_peer_connection_id = self.paths[idx].peer_connection_id;
+            self.peer_id_registry.consume_new_id_for_existing_path(
+                new_path_id,
+                current_peer_connection_id,
+                publisher,
+            )
+        } else {
+            self.peer_id_registry.consume_new_id_for_new_path()
+        };
+
+        let peer_connection_id = if let Some(peer_connection_id) = peer_connection_id {
+            peer_connection_id
+        } else {
+            // The peer retired the unused connection IDs since the migration was requested
+            return;
+        };
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+        //# When migrating, the new path might not support the endpoint's current
+        //# sending rate.  Therefore, the endpoint resets its congestion
+        //# controller and RTT estimate, as described in Section 9.4.
+        let remote_address = handle.remote_address();
+        let rtt = RttEstimator::new(self.active_path().rtt_estimator.max_ack_delay());
+        let path_info = congestion_controller::PathInfo::new(&remote_address);
+        let cc = congestion_controller_endpoint.new_congestion_controller(path_info);
+
+        let mut path = Path::new(
+            handle,
+            peer_connection_id,
+            self.active_path().local_connection_id,
+            rtt,
+            cc,
+            true,
+            self.max_mtu(),
+        );
+        path.on_client_migration();
+
+        let active_path = self.active_path();
+        let active_path_id = self.active_path_id();
+        publisher.on_path_created(event::builder::PathCreated {
+            active: path_event!(active_path, active_path_id),
+            new: path_event!(path, new_path_id),
+        });
+
+        if let Some(idx) = existing_path {
+            // Keep the counters so the connection statistics include the previous use of the path
+            path.counters = self.paths[idx].counters;
+            self.paths[idx] = path;
+        } else {
+            self.paths.push(path);
+        }
+
+        if self.active_path().is_validated() {
+            self.last_known_active_validated_path = Some(self.active);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+        //# To establish reachability on the new path, an endpoint initiates path
+        //# validation (Section 8.2) on the new path.
+        self.set_challenge(new_path_id, random_generator);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+        //# Each endpoint validates its peer's address during connection
+        //# establishment.  Therefore, a migrating endpoint can send to its peer
+        //# knowing that the peer is willing to receive at the peer's current
+        //# address.  Thus, an endpoint can migrate to a new local address
+        //# without first validating the peer's address.
+        let prev_path_id = self.active_path_id();
+        self.activate_path(publisher, prev_path_id, new_path_id);
+    }
+
+    /// Called when the peer sent the disable_active_migration transport parameter
+    pub fn on_active_migration_disabled(&mut self) {
+        self.active_migration_disabled = true;
+        self.pending_migration = None;
+    }
+
+    /// Called when the server sent the preferred_address transport parameter
+    ///
+    /// The connection ID is registered and an address is selected to probe once the
+    /// handshake is confirmed.
+    pub fn on_preferred_address(
+        &mut self,
+        preferred_address: &PreferredAddress,
+    ) -> Result<(), transport::Error> {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        let peer_connection_id = PeerId::try_from_bytes(preferred_address.connection_id.as_bytes())
+            .ok_or_else(|| {
+                transport::Error::TRANSPORT_PARAMETER_ERROR
+                    .with_reason("invalid preferred_address connection ID")
+            })?;
+
+        self.peer_id_registry
+            .register_preferred_address_connection_id(
+                &peer_connection_id,
+                &preferred_address.stateless_reset_token,
+            )
+            .map_err(|_| {
+                transport::Error::TRANSPORT_PARAMETER_ERROR
+                    .with_reason("invalid preferred_address connection ID")
+            })?;
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+        //# Servers MAY communicate a preferred address of each address family
+        //# (IPv4 and IPv6) to allow clients to pick the one most suited to their
+        //# network attachment.
+        let remote_address = self.active_path().remote_address();
+        self.preferred_address = match remote_address.unmap() {
+            SocketAddress::IpV4(_) => preferred_address.ipv4_address.map(|address| {
+                // Keep using mapped addresses if the socket is dual-stack
+                if let SocketAddress::IpV6(_) = *remote_address {
+                    address.to_ipv6_mapped().into()
+                } else {
+                    address.into()
+                }
+            }),
+            SocketAddress::IpV6(_) => preferred_address.ipv6_address.map(RemoteAddress::from),
+        };
+
+        Ok(())
+    }
+
+    /// Called when the handshake is confirmed
+    pub fn on_handshake_confirmed(&mut self) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+        //# Once the handshake is confirmed, the client SHOULD select one of the
+        //# two addresses provided by the server and initiate path validation
+        //# (see Section 8.2).
+        self.pending_preferred_address_probe = self.preferred_address.is_some();
+    }
+
+    /// Creates a path to the server's preferred address and initiates path validation
+    ///
+    /// The active path is unchanged until the new path has been validated.
+    fn probe_preferred_address<Pub: event::ConnectionPublisher>(
+        &mut self,
+        random_generator: &mut Config::RandomGenerator,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
+        publisher: &mut Pub,
+    ) {
+        let remote_address = if let Some(remote_address) = self.preferred_address.take() {
+            remote_address
+        } else {
+            return;
+        };
+
+        let mut handle = Config::PathHandle::from_remote_address(remote_address);
+        handle.set_local_address(self.active_path().local_address());
+
+        // The server advertised the address already in use
+        if self.path(&handle).is_some() || self.paths.len() >= MAX_ALLOWED_PATHS {
+            return;
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+        //# A client constructs packets using any previously
+        //# unused active connection ID, taken from either the preferred_address
+        //# transport parameter or a NEW_CONNECTION_ID frame.
+        let peer_connection_id =
+            if let Some(peer_connection_id) = self.peer_id_registry.consume_new_id_for_new_path() {
+                peer_connection_id
+            } else {
+                return;
+            };
+
+        let rtt = RttEstimator::new(self.active_path().rtt_estimator.max_ack_delay());
+        let path_info = congestion_controller::PathInfo::new(&remote_address);
+        let cc = congestion_controller_endpoint.new_congestion_controller(path_info);
+
+        let mut path = Path::new(
+            handle,
+            peer_connection_id,
+            self.active_path().local_connection_id,
+            rtt,
+            cc,
+            true,
+            self.max_mtu(),
+        );
+        path.on_client_migration();
+
+        let new_path_idx = self.paths.len() as u8;
+        let new_path_id = path_id(new_path_idx);
+        let active_path = self.active_path();
+        let active_path_id = self.active_path_id();
+        publisher.on_path_created(event::builder::PathCreated {
+            active: path_event!(active_path, active_path_id),
+            new: path_event!(path, new_path_id),
+        });
+
+        self.paths.push(path);
+        self.preferred_address_path = Some(new_path_idx);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.2
+        //# A client that migrates to a preferred address MUST validate the
+        //# address it chooses before migrating; see Section 21.5.3.
+        self.set_challenge(new_path_id, random_generator);
+    }
+
     fn set_challenge(&mut self, path_id: Id, random_generator: &mut Config::RandomGenerator) {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.2.1
         //# The endpoint MUST use unpredictable data in every PATH_CHALLENGE
@@ -561,8 +948,12 @@ impl<Config: endpoint::Config> Manager<Config> {
         //# A PATH_RESPONSE frame received on any network path validates the path
         //# on which the PATH_CHALLENGE was sent.
 
+        let mut local_migration_validated = false;
+        let mut validated_path = None;
+
         for (id, path) in self.paths.iter_mut().enumerate() {
             if path.on_path_response(response.data) {
+                validated_path = Some(id as u8);
                 let id = id as u64;
                 publisher.on_path_challenge_updated(event::builder::PathChallengeUpdated {
                     path_challenge_status: event::builder::PathChallengeStatus::Validated,
@@ -574,9 +965,43 @@ impl<Config: endpoint::Config> Manager<Config> {
                 if path.is_activated() {
                     self.last_known_active_validated_path = Some(id as u8);
                 }
+                local_migration_validated = Config::ENDPOINT_TYPE.is_client() && path.is_active();
                 break;
             }
         }
+
+        if let Some(idx) = validated_path.filter(|idx| Some(*idx) == self.preferred_address_path) {
+            self.preferred_address_path = None;
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+            //# As soon as path validation succeeds, the client SHOULD begin sending
+            //# all future packets to the new server address using the new connection
+            //# ID and discontinue use of the old server address.
+            let prev_path_id = self.active_path_id();
+            self.activate_path(publisher, prev_path_id, path_id(idx));
+            self.last_known_active_validated_path = Some(idx);
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
+            //# This transport
+            //# parameter does not prohibit connection migration after a client
+            //# has acted on a preferred_address transport parameter.
+            self.active_migration_disabled = false;
+
+            local_migration_validated = true;
+        }
+
+        if local_migration_validated {
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.2
+            //# Endpoints SHOULD retire connection IDs when
+            //# they are no longer actively using either the local or destination
+            //# address for which the connection ID was used.
+            let active_connection_id = self.active_path().peer_connection_id;
+            for path in self.paths.iter().filter(|path| !path.is_active()) {
+                if path.peer_connection_id != active_connection_id {
+                    self.peer_id_registry.retire_id(&path.peer_connection_id);
+                }
+            }
+        }
     }
 
     /// Process a packet and update internal state.
@@ -622,7 +1047,16 @@ impl<Config: endpoint::Config> Manager<Config> {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
         //# An endpoint can migrate a connection to a new local address by
         //# sending packets containing non-probing frames from that address.
-        if !path_validation_probing.is_probing() && self.active_path_id() != path_id {
+        //
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# Clients are responsible for initiating all migrations.
+        //
+        // A client changes its active path when the application migrates to a new local
+        // address, so packets received on other paths do not change it.
+        if Config::ENDPOINT_TYPE.is_server()
+            && !path_validation_probing.is_probing()
+            && self.active_path_id() != path_id
+        {
             self.update_active_path(path_id, random_generator, publisher)?;
 
             //= https://www.rfc-editor.org/rfc/rfc9000#section-9.3
@@ -716,6 +1150,19 @@ impl<Config: endpoint::Config> Manager<Config> {
             path.on_timeout(timestamp, path_id(id as u8), random_generator, publisher);
         }
 
+        if let Some(idx) = self.preferred_address_path {
+            let path = &self.paths[idx as usize];
+            if path.failed_validation() {
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+                //# If path validation
+                //# fails, the client MUST continue sending all future packets to the
+                //# server's original IP address.
+                let peer_connection_id = path.peer_connection_id;
+                self.peer_id_registry.retire_id(&peer_connection_id);
+                self.preferred_address_path = None;
+            }
+        }
+
         if self.active_path().failed_validation() {
             match self.last_known_active_validated_path {
                 Some(last_known_active_validated_path) => {
@@ -803,6 +1250,37 @@ impl<Config: endpoint::Config> Manager<Config> {
 
         value
     }
+
+    /// Returns a snapshot of the connection statistics
+    ///
+    /// The RTT, congestion control and MTU values are taken from the active path, while the
+    /// packet and byte counters are summed over all of the paths.
+    pub fn stats(&self) -> connection::Stats {
+        let path = self.active_path();
+        let mut stats = connection::Stats::default();
+
+        stats.local_address = *path.handle.local_address();
+        stats.remote_address = *path.handle.remote_address();
+
+        stats.min_rtt = path.rtt_estimator.min_rtt();
+        stats.smoothed_rtt = path.rtt_estimator.smoothed_rtt();
+        stats.latest_rtt = path.rtt_estimator.latest_rtt();
+        stats.rtt_variance = path.rtt_estimator.rttvar();
+        stats.max_ack_delay = path.rtt_estimator.max_ack_delay();
+        // the backoff doubles on each PTO so the count is its exponent
+        stats.pto_count = path.pto_backoff.trailing_zeros();
+
+        stats.congestion_window = path.congestion_controller.congestion_window();
+        stats.bytes_in_flight = path.congestion_controller.bytes_in_flight();
+        stats.pacing_rate = path.congestion_controller.pacing_rate(&path.rtt_estimator);
+        stats.max_datagram_size = path.mtu_controller.mtu() as u16;
+
+        for path in self.paths.iter() {
+            path.counters.add_to(&mut stats);
+        }
+
+        stats
+    }
 }
 
 #[inline]
@@ -12,7 +12,7 @@ use crate::{
     path,
     path::DEFAULT_MAX_MTU,
 };
-use core::time::Duration;
+use core::{convert::TryInto, time::Duration};
 use s2n_quic_core::{
     event::testing::Publisher,
     inet::{DatagramInfo, ExplicitCongestionNotification, SocketAddress},
@@ -1808,3 +1808,458 @@ pub struct Helper {
     pub second_path_id: Id,
     pub manager: ServerManager,
 }
+
+// A client with path handles that track the local address, which is required for migrations
+#[derive(Debug)]
+struct MigratingClient;
+
+impl endpoint::Config for MigratingClient {
+    type CongestionControllerEndpoint =
+        crate::recovery::congestion_controller::testing::mock::Endpoint;
+    type TLSEndpoint = s2n_quic_core::crypto::tls::testing::Endpoint;
+    type PathHandle = s2n_quic_core::path::Tuple;
+    type Connection = crate::connection::Implementation<Self>;
+    type ConnectionLock = std::sync::Mutex<Self::Connection>;
+    type EndpointLimits = endpoint::testing::Limits;
+    type ConnectionIdFormat = connection::id::testing::Format;
+    type StatelessResetTokenGenerator = stateless_reset::token::testing::Generator;
+    type RandomGenerator = random::testing::Generator;
+    type TokenFormat = s2n_quic_core::token::testing::Format;
+    type ConnectionLimits = s2n_quic_core::connection::limits::Limits;
+    type Stream = crate::stream::StreamImpl;
+    type ConnectionCloseFormatter = s2n_quic_core::connection::close::Development;
+    type EventSubscriber = s2n_quic_core::event::testing::Subscriber;
+    type PathMigrationValidator = migration::default::Validator;
+    type PacketInterceptor = s2n_quic_core::packet::interceptor::Disabled;
+    type EarlyDataPolicy = s2n_quic_core::connection::early_data::default::Policy;
+
+    fn context(&mut self) -> endpoint::Context<Self> {
+        todo!()
+    }
+
+    const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Client;
+}
+
+// Helper function to create a migrating client PathManager with one spare peer connection ID
+fn manager_migrating_client(publisher: &mut Publisher) -> super::Manager<MigratingClient> {
+    let mut manager = manager_migrating_client_handshake("127.0.0.1:443");
+
+    // The handshake connection ID is rotated when the first new connection ID arrives
+    let id_1 = connection::PeerId::try_from_bytes(b"id01").unwrap();
+    manager
+        .on_new_connection_id(&id_1, 1, 0, &TEST_TOKEN_1, publisher)
+        .unwrap();
+    assert_eq!(manager.active_path().peer_connection_id, id_1);
+
+    manager
+}
+
+// Helper function to create a migrating client PathManager with only the handshake connection ID
+fn manager_migrating_client_handshake(remote_address: &str) -> super::Manager<MigratingClient> {
+    let initial_cid = connection::PeerId::try_from_bytes(&[0, 0]).unwrap();
+    let remote_address: SocketAddr = remote_address.parse().unwrap();
+    let handle =
+        s2n_quic_core::path::Tuple::from_remote_address(SocketAddress::from(remote_address).into());
+    let first_path = super::Path::<MigratingClient>::new(
+        handle,
+        initial_cid,
+        connection::LocalId::TEST_ID,
+        RttEstimator::new(Duration::from_millis(30)),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+
+    let mut random_generator = random::testing::Generator(123);
+    let mut peer_id_registry =
+        ConnectionIdMapper::new(&mut random_generator, endpoint::Type::Client)
+            .create_client_peer_id_registry(InternalConnectionIdGenerator::new().generate_id());
+    peer_id_registry.register_initial_connection_id(initial_cid);
+    super::Manager::new(first_path, peer_id_registry)
+}
+
+fn local_address(address: &str) -> LocalAddress {
+    let address: SocketAddr = address.parse().unwrap();
+    SocketAddress::from(address).into()
+}
+
+fn local_ip(ip: &str) -> IpAddress {
+    let ip: std::net::IpAddr = ip.parse().unwrap();
+    ip.into()
+}
+
+#[test]
+fn request_migration_errors() {
+    let mut publisher = Publisher::no_snapshot();
+
+    // Servers never initiate migrations
+    let first_path = ServerPath::new(
+        Default::default(),
+        connection::PeerId::try_from_bytes(&[1]).unwrap(),
+        connection::LocalId::TEST_ID,
+        RttEstimator::new(Duration::from_millis(30)),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+    let mut manager = manager_server(first_path);
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, true),
+        Err(MigrationError::Unsupported)
+    );
+
+    // The handle of the client is unable to represent a local address
+    let first_path = ClientPath::new(
+        Default::default(),
+        connection::PeerId::try_from_bytes(&[1]).unwrap(),
+        connection::LocalId::TEST_ID,
+        RttEstimator::new(Duration::from_millis(30)),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+    let mut manager = manager_client(first_path);
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, true),
+        Err(MigrationError::Unsupported)
+    );
+
+    let mut manager = manager_migrating_client(&mut publisher);
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), false, true),
+        Err(MigrationError::HandshakeNotConfirmed)
+    );
+
+    // The only connection ID the peer has issued is in use
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, true),
+        Err(MigrationError::InsufficientConnectionIds)
+    );
+
+    // The peer hasn't acknowledged an unused local connection ID to respond with
+    let id_2 = connection::PeerId::try_from_bytes(b"id02").unwrap();
+    manager
+        .on_new_connection_id(&id_2, 2, 0, &TEST_TOKEN_2, &mut publisher)
+        .unwrap();
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, false),
+        Err(MigrationError::InsufficientConnectionIds)
+    );
+
+    manager.on_active_migration_disabled();
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, true),
+        Err(MigrationError::DisabledByPeer)
+    );
+    assert!(manager.pending_migration.is_none());
+}
+
+#[test]
+//= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+//= type=test
+//# To establish reachability on the new path, an endpoint initiates path
+//# validation (Section 8.2) on the new path.
+fn client_migration_validates_new_path() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = manager_migrating_client(&mut publisher);
+    let id_1 = manager.active_path().peer_connection_id;
+    let id_2 = connection::PeerId::try_from_bytes(b"id02").unwrap();
+    manager
+        .on_new_connection_id(&id_2, 2, 0, &TEST_TOKEN_2, &mut publisher)
+        .unwrap();
+
+    // Trigger:
+    assert_eq!(
+        manager.request_migration(local_ip("10.0.0.2"), true, true),
+        Ok(())
+    );
+    assert_eq!(manager.paths.len(), 1);
+    manager.migrate(
+        &mut random::testing::Generator(123),
+        &mut Default::default(),
+        &mut publisher,
+    );
+
+    // Expectation:
+    assert_eq!(manager.paths.len(), 2);
+    assert_eq!(manager.active, 1);
+    assert_eq!(manager.last_known_active_validated_path, Some(0));
+    assert_eq!(
+        manager.active_path().local_address(),
+        local_address("10.0.0.2:0")
+    );
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+    //= type=test
+    //# An endpoint MUST NOT reuse a connection ID when sending from more
+    //# than one local address -- for example, when initiating connection
+    //# migration as described in Section 9.2 or when probing a new network
+    //# path as described in Section 9.1.
+    assert_eq!(manager.active_path().peer_connection_id, id_2);
+    assert!(!manager.active_path().is_validated());
+    assert!(manager.active_path().is_challenge_pending());
+
+    // Trigger:
+    let data: challenge::Data = manager
+        .active_path()
+        .challenge
+        .challenge_data()
+        .try_into()
+        .unwrap();
+    manager.on_path_response(&frame::PathResponse { data: &data }, &mut publisher);
+
+    // Expectation:
+    assert!(manager.active_path().is_validated());
+    assert!(manager.peer_id_registry.is_active(&id_2));
+    assert!(!manager.peer_id_registry.is_active(&id_1));
+}
+
+#[test]
+fn client_migration_falls_back_on_failed_validation() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = manager_migrating_client(&mut publisher);
+    let id_2 = connection::PeerId::try_from_bytes(b"id02").unwrap();
+    manager
+        .on_new_connection_id(&id_2, 2, 0, &TEST_TOKEN_2, &mut publisher)
+        .unwrap();
+    manager
+        .request_migration(local_ip("10.0.0.2"), true, true)
+        .unwrap();
+    manager.migrate(
+        &mut random::testing::Generator(123),
+        &mut Default::default(),
+        &mut publisher,
+    );
+    assert_eq!(manager.active, 1);
+
+    // send challenge and arm abandon timer
+    let now = NoopClock {}.get_time();
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let mut context = MockWriteContext::new(
+        now,
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Client,
+    );
+    manager[path_id(1)].on_transmit(&mut context);
+
+    // Trigger:
+    manager
+        .on_timeout(
+            now + Duration::from_secs(60),
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+
+    // Expectation:
+    assert_eq!(manager.active, 0);
+    assert!(manager.last_known_active_validated_path.is_none());
+    assert_eq!(
+        manager.active_path().local_address(),
+        local_address("0.0.0.0:0")
+    );
+}
+
+fn preferred_address() -> PreferredAddress {
+    let ipv4_address: std::net::SocketAddrV4 = "127.0.0.2:4433".parse().unwrap();
+    let ipv6_address: std::net::SocketAddrV6 = "[::2]:4433".parse().unwrap();
+    PreferredAddress {
+        ipv4_address: Some(ipv4_address.into()),
+        ipv6_address: Some(ipv6_address.into()),
+        connection_id: connection::UnboundedId::try_from_bytes(b"id01").unwrap(),
+        stateless_reset_token: TEST_TOKEN_1,
+    }
+}
+
+fn remote_address(address: &str) -> RemoteAddress {
+    let address: SocketAddr = address.parse().unwrap();
+    SocketAddress::from(address).into()
+}
+
+#[test]
+fn preferred_address_matches_address_family() {
+    let mut manager = manager_migrating_client_handshake("127.0.0.1:443");
+    manager.on_preferred_address(&preferred_address()).unwrap();
+    assert_eq!(
+        manager.preferred_address,
+        Some(remote_address("127.0.0.2:4433"))
+    );
+
+    let mut manager = manager_migrating_client_handshake("[::1]:443");
+    manager.on_preferred_address(&preferred_address()).unwrap();
+    assert_eq!(
+        manager.preferred_address,
+        Some(remote_address("[::2]:4433"))
+    );
+
+    // Dual-stack sockets keep using IPv4-mapped addresses
+    let mut manager = manager_migrating_client_handshake("[::ffff:127.0.0.1]:443");
+    manager.on_preferred_address(&preferred_address()).unwrap();
+    assert_eq!(
+        manager.preferred_address,
+        Some(remote_address("[::ffff:127.0.0.2]:4433"))
+    );
+
+    // The server did not provide an address of the same family
+    let mut manager = manager_migrating_client_handshake("[::1]:443");
+    let preferred_address = PreferredAddress {
+        ipv6_address: None,
+        ..preferred_address()
+    };
+    manager.on_preferred_address(&preferred_address).unwrap();
+    assert_eq!(manager.preferred_address, None);
+
+    // The connection ID is available for use on any path
+    assert!(manager.peer_id_registry.has_new_id());
+}
+
+#[test]
+//= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+//= type=test
+//# Once the handshake is confirmed, the client SHOULD select one of the
+//# two addresses provided by the server and initiate path validation
+//# (see Section 8.2).
+fn preferred_address_is_validated_after_handshake_confirmed() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = manager_migrating_client_handshake("127.0.0.1:443");
+    let handshake_id = manager.active_path().peer_connection_id;
+    let id_1 = connection::PeerId::try_from_bytes(b"id01").unwrap();
+    manager.on_preferred_address(&preferred_address()).unwrap();
+    manager.on_active_migration_disabled();
+
+    // The preferred address is not probed before the handshake is confirmed
+    assert!(!manager.has_pending_migration());
+    manager.migrate(
+        &mut random::testing::Generator(123),
+        &mut Default::default(),
+        &mut publisher,
+    );
+    assert_eq!(manager.paths.len(), 1);
+
+    // Trigger:
+    manager.on_handshake_confirmed();
+    assert!(manager.has_pending_migration());
+    manager.migrate(
+        &mut random::testing::Generator(123),
+        &mut Default::default(),
+        &mut publisher,
+    );
+
+    // Expectation:
+    assert!(!manager.has_pending_migration());
+    assert_eq!(manager.paths.len(), 2);
+    assert_eq!(manager.active, 0);
+    assert_eq!(
+        manager[path_id(1)].remote_address(),
+        remote_address("127.0.0.2:4433")
+    );
+    assert_eq!(manager[path_id(1)].peer_connection_id, id_1);
+    assert!(manager[path_id(1)].is_challenge_pending());
+    assert!(!manager[path_id(1)].is_validated());
+
+    // Trigger:
+    let data: challenge::Data = manager[path_id(1)]
+        .challenge
+        .challenge_data()
+        .try_into()
+        .unwrap();
+    manager.on_path_response(&frame::PathResponse { data: &data }, &mut publisher);
+
+    // Expectation:
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+    //= type=test
+    //# As soon as path validation succeeds, the client SHOULD begin sending
+    //# all future packets to the new server address using the new connection
+    //# ID and discontinue use of the old server address.
+    assert_eq!(manager.active, 1);
+    assert_eq!(manager.last_known_active_validated_path, Some(1));
+    assert!(manager.active_path().is_validated());
+    assert!(manager.peer_id_registry.is_active(&id_1));
+    assert!(!manager.peer_id_registry.is_active(&handshake_id));
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
+    //= type=test
+    //# This transport
+    //# parameter does not prohibit connection migration after a client
+    //# has acted on a preferred_address transport parameter.
+    assert!(!manager.active_migration_disabled);
+}
+
+#[test]
+//= https://www.rfc-editor.org/rfc/rfc9000#section-9.6.1
+//= type=test
+//# If path validation
+//# fails, the client MUST continue sending all future packets to the
+//# server's original IP address.
+fn preferred_address_failed_validation_keeps_original_path() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = manager_migrating_client_handshake("127.0.0.1:443");
+    let id_1 = connection::PeerId::try_from_bytes(b"id01").unwrap();
+    manager.on_preferred_address(&preferred_address()).unwrap();
+    manager.on_handshake_confirmed();
+    manager.migrate(
+        &mut random::testing::Generator(123),
+        &mut Default::default(),
+        &mut publisher,
+    );
+    assert_eq!(manager.paths.len(), 2);
+
+    // send challenge and arm abandon timer
+    let now = NoopClock {}.get_time();
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let mut context = MockWriteContext::new(
+        now,
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::PathValidationOnly,
+        endpoint::Type::Client,
+    );
+    manager[path_id(1)].on_transmit(&mut context);
+
+    // Trigger:
+    manager
+        .on_timeout(
+            now + Duration::from_secs(60),
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+
+    // Expectation:
+    assert_eq!(manager.active, 0);
+    assert!(manager.preferred_address_path.is_none());
+    assert_eq!(
+        manager.active_path().remote_address(),
+        remote_address("127.0.0.1:443")
+    );
+    assert!(!manager.peer_id_registry.is_active(&id_1));
+}
+
+#[test]
+fn stats_pto_count() {
+    let first_path = ServerPath::new(
+        Default::default(),
+        connection::PeerId::try_from_bytes(&[1]).unwrap(),
+        connection::LocalId::TEST_ID,
+        RttEstimator::new(Duration::from_millis(30)),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+    let mut manager = manager_server(first_path);
+    assert_eq!(manager.stats().pto_count, 0);
+
+    // the backoff doubles with each consecutive PTO
+    for pto_count in 1..=31 {
+        manager.active_path_mut().pto_backoff *= 2;
+        assert_eq!(manager.stats().pto_count, pto_count);
+    }
+
+    manager.active_path_mut().reset_pto_backoff();
+    assert_eq!(manager.stats().pto_count, 0);
+}
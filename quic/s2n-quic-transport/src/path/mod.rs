@@ -19,11 +19,13 @@ use s2n_quic_core::{
 };
 
 mod challenge;
+mod counters;
 pub(crate) mod ecn;
 mod manager;
 pub(crate) mod mtu;
 
 pub use challenge::*;
+pub use counters::Counters;
 pub use manager::*;
 
 /// re-export core
@@ -34,6 +36,12 @@ enum State {
     /// Path has no transmission limitations
     Validated,
 
+    /// Path has not been validated, but is not subject to amplification limits
+    ///
+    /// A client migrating to a new local address or probing the server's preferred address
+    /// uses this state until the new path has been validated.
+    Pending,
+
     /// Path has not been validated and is subject to amplification limits
     AmplificationLimited {
         tx_allowance: Counter<u32, Saturating>,
@@ -60,6 +68,8 @@ pub struct Path<Config: endpoint::Config> {
     pub mtu_controller: mtu::Controller,
     /// Controller for determining the ECN capability of the path
     pub ecn_controller: ecn::Controller,
+    /// Packet and byte counters for the path
+    pub counters: Counters,
 
     /// True if the path has been validated by the peer
     peer_validated: bool,
@@ -94,6 +104,7 @@ impl<Config: endpoint::Config> Clone for Path<Config> {
             state: self.state,
             mtu_controller: self.mtu_controller.clone(),
             ecn_controller: self.ecn_controller.clone(),
+            counters: self.counters,
             peer_validated: self.peer_validated,
             challenge: self.challenge.clone(),
             response_data: self.response_data,
@@ -141,6 +152,7 @@ impl<Config: endpoint::Config> Path<Config> {
             state,
             mtu_controller: mtu::Controller::new(max_mtu, &peer_socket_address),
             ecn_controller: ecn::Controller::default(),
+            counters: Counters::default(),
             peer_validated,
             challenge: Challenge::disabled(),
             response_data: None,
@@ -371,6 +383,17 @@ impl<Config: endpoint::Config> Path<Config> {
         self.state == State::Validated
     }
 
+    /// Called when a client has migrated to the path from a new local address or is probing
+    /// the server's preferred address
+    ///
+    /// The client is not limited by the amount of data received on the path, but the path
+    /// must pass validation to remain in use.
+    #[inline]
+    pub fn on_client_migration(&mut self) {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+        self.state = State::Pending;
+    }
+
     /// The path received a non-path-validation-probing packet so mark it as activated.
     #[inline]
     pub fn on_activated(&mut self) {
@@ -440,7 +463,7 @@ impl<Config: endpoint::Config> Path<Config> {
         let mtu = self.mtu(transmission_mode);
 
         match self.state {
-            State::Validated => requested_size.min(mtu),
+            State::Validated | State::Pending => requested_size.min(mtu),
 
             // https://github.com/aws/s2n-quic/issues/695
             // Note: while a 3X check if performed, the `limit` value is not used
@@ -525,7 +548,7 @@ impl<Config: endpoint::Config> Path<Config> {
         match &self.state {
             // keep the current amplification limits
             State::AmplificationLimited { .. } => {}
-            State::Validated => {
+            State::Validated | State::Pending => {
                 self.state = State::AmplificationLimited {
                     tx_allowance: Counter::new(MINIMUM_MTU as u32 * 3),
                 };
@@ -552,6 +575,21 @@ impl<Config: endpoint::Config> Path<Config> {
             self.handle.eq(handle)
         }
     }
+
+    // Compare a client Path based on the remote address and local IP of the PathHandle.
+    //
+    // Clients only migrate by switching the source IP, so paths are created with the
+    // requested IP and an unspecified port. The local port is determined by the socket the
+    // IO provider receives the datagram on and is not compared.
+    fn eq_by_local_address(&self, handle: &Config::PathHandle) -> bool {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        self.handle.local_address().ip().unmap() == handle.local_address().ip().unmap()
+            && s2n_quic_core::path::Handle::eq(
+                &self.handle.remote_address(),
+                &handle.remote_address(),
+            )
+    }
 }
 
 impl<Config: endpoint::Config> timer::Provider for Path<Config> {
@@ -1176,7 +1214,9 @@ mod tests {
 
         // Expectation:
         match path.state {
-            path::State::Validated => panic!("transition to AmplificationLimited when closing"),
+            path::State::Validated | path::State::Pending => {
+                panic!("transition to AmplificationLimited when closing")
+            }
             path::State::AmplificationLimited { tx_allowance } => {
                 assert_eq!(*tx_allowance, (MINIMUM_MTU * 3) as u32)
             }
@@ -1194,7 +1234,9 @@ mod tests {
 
         // Expectation:
         match path.state {
-            path::State::Validated => panic!("transition to AmplificationLimited when closing"),
+            path::State::Validated | path::State::Pending => {
+                panic!("transition to AmplificationLimited when closing")
+            }
             path::State::AmplificationLimited { tx_allowance } => {
                 assert_eq!(*tx_allowance, 0)
             }
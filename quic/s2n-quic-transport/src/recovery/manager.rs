@@ -144,6 +144,41 @@ impl<Config: endpoint::Config> Manager<Config> {
         *self = Self::new(self.space);
     }
 
+    //= https://www.rfc-editor.org/rfc/rfc9002#section-6.4
+    //# When 0-RTT is rejected, recovery state for all in-flight 0-RTT
+    //# packets is discarded.
+    /// Invoked when the Client learns the server rejected its 0-RTT packets.
+    ///
+    /// The rejected packets are removed from the congestion controller without a congestion
+    /// response and the frames they carried are declared lost so they are retransmitted in
+    /// 1-RTT packets.
+    pub fn on_zero_rtt_rejected<Ctx: Context<Config>, Pub: event::ConnectionPublisher>(
+        &mut self,
+        context: &mut Ctx,
+        publisher: &mut Pub,
+    ) {
+        debug_assert!(
+            Config::ENDPOINT_TYPE.is_client(),
+            "only a Client sends 0-RTT packets"
+        );
+        debug_assert_eq!(self.space, PacketNumberSpace::ApplicationData);
+
+        let mut discarded_bytes = 0;
+        for (packet_number, unacked_sent_info) in self.sent_packets.iter() {
+            discarded_bytes += unacked_sent_info.sent_bytes as usize;
+            context.on_packet_loss(
+                &PacketNumberRange::new(packet_number, packet_number),
+                publisher,
+            );
+        }
+        context
+            .path_mut()
+            .congestion_controller
+            .on_packet_discarded(discarded_bytes);
+
+        *self = Self::new(self.space);
+    }
+
     pub fn on_timeout<Ctx: Context<Config>, Pub: event::ConnectionPublisher>(
         &mut self,
         timestamp: Timestamp,
@@ -227,6 +262,11 @@ impl<Config: endpoint::Config> Manager<Config> {
         );
         path.ecn_controller
             .on_packet_sent(ecn, path_event!(path, path_id), publisher);
+        path.counters.on_packet_sent(outcome.bytes_sent, ecn);
+        if outcome.bytes_retransmitted > 0 {
+            path.counters
+                .on_packet_retransmitted(outcome.bytes_retransmitted);
+        }
         self.sent_packet_ecn_counts.increment(ecn);
 
         if outcome.is_congestion_controlled {
@@ -864,6 +904,8 @@ impl<Config: endpoint::Config> Manager<Config> {
                 is_congestion_event = true;
             }
 
+            path.counters.on_packet_lost(sent_info.sent_bytes as usize);
+
             publisher.on_packet_lost(event::builder::PacketLost {
                 packet_header: event::builder::PacketHeader::new(
                     packet_number,
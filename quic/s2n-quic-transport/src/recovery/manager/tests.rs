@@ -109,6 +109,7 @@ fn on_packet_sent() {
             is_congestion_controlled: i % 3 == 0,
             bytes_sent: (2 * i) as usize,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         };
 
         manager.on_packet_sent(
@@ -240,6 +241,7 @@ fn on_packet_sent_across_multiple_paths() {
         is_congestion_controlled: true,
         bytes_sent: packet_bytes,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     manager.on_packet_sent(
@@ -280,6 +282,7 @@ fn on_packet_sent_across_multiple_paths() {
         is_congestion_controlled: true,
         bytes_sent: packet_bytes,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // Reset the timer so we can confirm it was set correctly
@@ -343,6 +346,7 @@ fn on_ack_frame() {
                 is_congestion_controlled: true,
                 bytes_sent: packet_bytes,
                 bytes_progressed: 0,
+                bytes_retransmitted: 0,
             },
             time_sent,
             ecn,
@@ -481,6 +485,7 @@ fn on_ack_frame() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -550,6 +555,7 @@ fn process_new_acked_packets_update_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -565,6 +571,7 @@ fn process_new_acked_packets_update_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -663,6 +670,7 @@ fn process_new_acked_packets_congestion_controller() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -678,6 +686,7 @@ fn process_new_acked_packets_congestion_controller() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -787,6 +796,7 @@ fn process_new_acked_packets_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -802,6 +812,7 @@ fn process_new_acked_packets_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -837,6 +848,7 @@ fn process_new_acked_packets_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -899,6 +911,7 @@ fn process_new_acked_packets_process_ecn() {
                 is_congestion_controlled: true,
                 bytes_sent: packet_bytes,
                 bytes_progressed: 0,
+                bytes_retransmitted: 0,
             },
             time_sent,
             ExplicitCongestionNotification::Ect0,
@@ -999,6 +1012,7 @@ fn process_new_acked_packets_failed_ecn_validation_does_not_cause_congestion_eve
                 is_congestion_controlled: true,
                 bytes_sent: packet_bytes,
                 bytes_progressed: 0,
+                bytes_retransmitted: 0,
             },
             time_sent,
             ExplicitCongestionNotification::Ect0,
@@ -1055,6 +1069,7 @@ fn no_rtt_update_when_not_acknowledging_the_largest_acknowledged_packet() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -1068,6 +1083,7 @@ fn no_rtt_update_when_not_acknowledging_the_largest_acknowledged_packet() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -1144,6 +1160,7 @@ fn no_rtt_update_when_receiving_packet_on_different_path() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -1157,6 +1174,7 @@ fn no_rtt_update_when_receiving_packet_on_different_path() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         time_sent,
         ecn,
@@ -1258,6 +1276,7 @@ fn rtt_update_when_receiving_ack_from_multiple_paths() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         sent_time,
         ecn,
@@ -1274,6 +1293,7 @@ fn rtt_update_when_receiving_ack_from_multiple_paths() {
             is_congestion_controlled: true,
             bytes_sent: packet_bytes,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         sent_time,
         ecn,
@@ -1340,6 +1360,7 @@ fn detect_and_remove_lost_packets() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // Send a packet that was sent too long ago (lost)
@@ -1514,6 +1535,7 @@ fn detect_lost_packets_persistent_congestion_path_aware() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // Send a packet that was sent too long ago (lost)
@@ -1734,6 +1756,7 @@ fn detect_and_remove_lost_packets_nothing_lost() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // Send a packet that is less than the largest acked but not lost
@@ -1784,6 +1807,7 @@ fn detect_and_remove_lost_packets_mtu_probe() {
         is_congestion_controlled: true,
         bytes_sent: MINIMUM_MTU as usize + 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // Send an MTU probe packet
@@ -1843,6 +1867,7 @@ fn persistent_congestion() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // t=0: Send packet #1 (app data)
@@ -1994,6 +2019,7 @@ fn persistent_congestion_multiple_periods() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // t=0: Send packet #1 (app data)
@@ -2117,6 +2143,7 @@ fn persistent_congestion_period_does_not_start_until_rtt_sample() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // t=0: Send packet #1 (app data)
@@ -2197,6 +2224,7 @@ fn persistent_congestion_not_ack_eliciting() {
         is_congestion_controlled: true,
         bytes_sent: 1,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
 
     // t=0: Send packet #1 (app data)
@@ -2341,6 +2369,7 @@ fn update_pto_timer() {
             is_congestion_controlled: true,
             bytes_sent: 1,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         now,
         ecn,
@@ -2477,6 +2506,7 @@ fn on_timeout() {
             is_congestion_controlled: true,
             bytes_sent: 1,
             bytes_progressed: 0,
+            bytes_retransmitted: 0,
         },
         now - Duration::from_secs(5),
         ecn,
@@ -2779,6 +2809,7 @@ fn probe_packets_count_towards_bytes_in_flight() {
         is_congestion_controlled: true,
         bytes_sent: 100,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
     manager.on_packet_sent(
         space.new_packet_number(VarInt::from_u8(1)),
@@ -2869,6 +2900,7 @@ fn packet_declared_lost_less_than_1_ms_from_loss_threshold() {
         is_congestion_controlled: true,
         bytes_sent: 100,
         bytes_progressed: 0,
+        bytes_retransmitted: 0,
     };
     manager.on_packet_sent(
         space.new_packet_number(VarInt::from_u8(1)),
@@ -8,8 +8,8 @@ use crate::{
     processed_packet::ProcessedPacket,
     recovery,
     space::{
-        keep_alive::KeepAlive, rx_packet_numbers::AckManager, HandshakeStatus, PacketSpace,
-        TxPacketNumbers,
+        datagram, keep_alive::KeepAlive, rx_packet_numbers::AckManager, CryptoStream,
+        HandshakeStatus, PacketSpace, TxPacketNumbers, ZeroRttCrypto,
     },
     stream::AbstractStreamManager,
     sync::flag,
@@ -19,25 +19,31 @@ use core::{convert::TryInto, fmt, marker::PhantomData};
 use once_cell::sync::OnceCell;
 use s2n_codec::EncoderBuffer;
 use s2n_quic_core::{
-    crypto::{application::KeySet, limited, tls, CryptoSuite},
+    crypto::{application::KeySet, limited, tls, CryptoSuite, Key as _},
     event::{self, ConnectionPublisher as _, IntoEvent},
     frame::{
-        ack::AckRanges, crypto::CryptoRef, stream::StreamRef, Ack, ConnectionClose, DataBlocked,
-        HandshakeDone, MaxData, MaxStreamData, MaxStreams, NewConnectionId, NewToken,
-        PathChallenge, PathResponse, ResetStream, RetireConnectionId, StopSending,
-        StreamDataBlocked, StreamsBlocked,
+        ack::AckRanges, crypto::CryptoRef, datagram::DatagramRef, stream::StreamRef, Ack,
+        ConnectionClose, DataBlocked, HandshakeDone, MaxData, MaxStreamData, MaxStreams,
+        NewConnectionId, NewToken, PathChallenge, PathResponse, ResetStream, RetireConnectionId,
+        StopSending, StreamDataBlocked, StreamsBlocked,
     },
     inet::DatagramInfo,
     packet::{
         encoding::{PacketEncoder, PacketEncodingError},
         number::{PacketNumber, PacketNumberRange, PacketNumberSpace, SlidingWindow},
         short::{CleartextShort, ProtectedShort, Short, SpinBit},
+        zero_rtt::{CleartextZeroRtt, ProtectedZeroRtt},
     },
     path::MaxMtu,
     time::{timer, Timestamp},
     transport,
 };
 
+//= https://www.rfc-editor.org/rfc/rfc9000#section-7.5
+//# Implementations MUST support buffering at least 4096 bytes of data
+//# received in out-of-order CRYPTO frames.
+const POST_HANDSHAKE_CRYPTO_BUFFER_LEN: u64 = 4096;
+
 pub struct ApplicationSpace<Config: endpoint::Config> {
     /// Transmission Packet numbers
     pub tx_packet_numbers: TxPacketNumbers,
@@ -45,11 +51,18 @@ pub struct ApplicationSpace<Config: endpoint::Config> {
     pub ack_manager: AckManager,
     /// All streams that are managed through this connection
     pub stream_manager: AbstractStreamManager<Config::Stream>,
+    /// Unreliable datagrams which are exchanged through this connection
+    pub datagram_manager: datagram::Manager,
+    /// Post-handshake TLS messages, such as session tickets
+    pub crypto_stream: CryptoStream,
+    /// Set once the TLS session no longer reads post-handshake messages
+    is_crypto_rx_closed: bool,
     /// The current state of the Spin bit
     /// TODO: Spin me
     pub spin_bit: SpinBit,
     /// The crypto suite for application data
-    /// TODO: What about ZeroRtt?
+    ///
+    /// 0-RTT keys are held separately by the `PacketSpaceManager`.
     //= https://www.rfc-editor.org/rfc/rfc9001#section-6.3
     //# For this reason, endpoints MUST be able to retain two sets of packet
     //# protection keys for receiving packets: the current and the next.
@@ -62,6 +75,8 @@ pub struct ApplicationSpace<Config: endpoint::Config> {
 
     ping: flag::Ping,
     keep_alive: KeepAlive,
+    /// Set when the application requested a key update that has not been initiated yet
+    key_update_requested: bool,
     processed_packet_numbers: SlidingWindow,
     recovery_manager: recovery::Manager<Config>,
 }
@@ -70,6 +85,8 @@ impl<Config: endpoint::Config> fmt::Debug for ApplicationSpace<Config> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ApplicationSpace")
             .field("ack_manager", &self.ack_manager)
+            .field("crypto_stream", &self.crypto_stream)
+            .field("datagram_manager", &self.datagram_manager)
             .field("ping", &self.ping)
             .field("processed_packet_numbers", &self.processed_packet_numbers)
             .field("recovery_manager", &self.recovery_manager)
@@ -86,21 +103,27 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::OneRttHeaderKey,
         now: Timestamp,
         stream_manager: AbstractStreamManager<Config::Stream>,
+        datagram_manager: datagram::Manager,
         ack_manager: AckManager,
         keep_alive: KeepAlive,
         max_mtu: MaxMtu,
+        limits: &connection::Limits,
     ) -> Self {
-        let key_set = KeySet::new(key, Self::key_limits(max_mtu));
+        let key_set = KeySet::new(key, Self::key_limits(max_mtu, limits));
 
         Self {
             tx_packet_numbers: TxPacketNumbers::new(PacketNumberSpace::ApplicationData, now),
             ack_manager,
             spin_bit: SpinBit::Zero,
             stream_manager,
+            datagram_manager,
+            crypto_stream: CryptoStream::new(),
+            is_crypto_rx_closed: false,
             key_set,
             header_key,
             ping: flag::Ping::default(),
             keep_alive,
+            key_update_requested: false,
             processed_packet_numbers: SlidingWindow::default(),
             recovery_manager: recovery::Manager::new(PacketNumberSpace::ApplicationData),
         }
@@ -150,6 +173,14 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             packet_number = packet_number.next().unwrap();
         }
 
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-6.1
+        //# An endpoint MUST NOT initiate a key update prior to having confirmed
+        //# the handshake (Section 4.1.2).
+        if self.key_update_requested && handshake_status.is_confirmed() {
+            self.key_update_requested = false;
+            self.key_set.request_key_update();
+        }
+
         let packet_number_encoder = self.packet_number_encoder();
 
         let mut outcome = transmission::Outcome::default();
@@ -172,7 +203,9 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
                 &mut self.ack_manager,
                 handshake_status,
                 &mut self.ping,
+                &mut self.crypto_stream,
                 &mut self.stream_manager,
+                &mut self.datagram_manager,
                 &mut self.recovery_manager,
             ),
             timestamp,
@@ -400,6 +433,38 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         self.keep_alive.update(enabled);
     }
 
+    /// Applies an update to the limits of the connection
+    pub fn update_limits(
+        &mut self,
+        update: &connection::limits::Update,
+        limits: &connection::Limits,
+    ) {
+        self.stream_manager.update_limits(update);
+        self.keep_alive
+            .update_period(limits.max_idle_timeout(), limits.max_keep_alive_period());
+    }
+
+    /// Requests that the 1-RTT keys are updated
+    ///
+    /// The update is initiated with the next packet sent after the handshake is confirmed. A
+    /// PING is sent so that the update happens even if the connection is otherwise idle.
+    pub fn update_keys(&mut self) {
+        self.key_update_requested = true;
+        self.ping.send();
+    }
+
+    /// Returns the number of bytes available for frames in a packet sent on the given path
+    pub fn max_packet_payload(&self, path: &Path<Config>) -> usize {
+        // A short header consists of a single byte of flags, the destination connection ID,
+        // and up to 4 bytes of packet number
+        let header_len = 1 + path.peer_connection_id.len() + 4;
+        let tag_len = self.key_set.active_key().key().tag_len();
+
+        path.mtu_controller
+            .mtu()
+            .saturating_sub(header_len + tag_len)
+    }
+
     /// Returns the Packet Number to be used when encoding outgoing packets
     fn packet_number_encoder(&self) -> PacketNumber {
         self.tx_packet_numbers.largest_sent_packet_number_acked()
@@ -421,6 +486,7 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
                 ack_manager: &mut self.ack_manager,
                 handshake_status,
                 ping: &mut self.ping,
+                crypto_stream: &mut self.crypto_stream,
                 stream_manager: &mut self.stream_manager,
                 local_id_registry,
                 path_id,
@@ -511,10 +577,82 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         decrypted.map(|x| x.0)
     }
 
-    fn key_limits(max_mtu: MaxMtu) -> limited::Limits {
+    /// Validate 0-RTT packets sent by the client before the handshake completed
+    pub fn validate_and_decrypt_zero_rtt_packet<'a, Pub: event::ConnectionPublisher>(
+        &mut self,
+        protected: ProtectedZeroRtt<'a>,
+        crypto: &ZeroRttCrypto<Config>,
+        path_id: path::Id,
+        path: &path::Path<Config>,
+        publisher: &mut Pub,
+    ) -> Result<CleartextZeroRtt<'a>, ProcessingError> {
+        debug_assert!(Config::ENDPOINT_TYPE.is_server());
+
+        let largest_acked = self.ack_manager.largest_received_packet_number_acked();
+        let packet = protected
+            .unprotect(&crypto.header_key, largest_acked)
+            .map_err(|err| {
+                publisher.on_packet_dropped(event::builder::PacketDropped {
+                    reason: event::builder::PacketDropReason::UnprotectFailed {
+                        space: event::builder::KeySpace::ZeroRtt,
+                        path: path_event!(path, path_id),
+                    },
+                });
+                err
+            })?;
+
+        let packet_number = packet.packet_number;
+        let packet_header = event::builder::PacketHeader::ZeroRtt {
+            number: packet_number.as_u64(),
+            version: publisher.quic_version(),
+        };
+        let decrypted = packet.decrypt(&crypto.key);
+
+        if decrypted.is_err() {
+            publisher.on_packet_dropped(event::builder::PacketDropped {
+                reason: event::builder::PacketDropReason::DecryptionFailed {
+                    packet_header,
+                    path: path_event!(path, path_id),
+                },
+            });
+        }
+
+        // We perform decryption prior to checking for duplicate to avoid short-circuiting
+        // and maintain constant-time operation.
+        if self.is_duplicate(packet_number, path_id, path, publisher) {
+            return Err(ProcessingError::DuplicatePacket);
+        }
+
+        Ok(decrypted?)
+    }
+
+    /// Stops buffering post-handshake CRYPTO data
+    ///
+    /// This is called once the TLS session has been dropped, since nothing would read the data.
+    pub fn close_crypto_rx(&mut self) {
+        self.is_crypto_rx_closed = true;
+        self.crypto_stream.rx.reset();
+    }
+
+    /// Continues the packet number space and in-flight packets of the 0-RTT packets sent by
+    /// the client
+    pub fn on_zero_rtt_space(
+        &mut self,
+        tx_packet_numbers: TxPacketNumbers,
+        recovery_manager: recovery::Manager<Config>,
+    ) {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        self.tx_packet_numbers = tx_packet_numbers;
+        self.recovery_manager = recovery_manager;
+    }
+
+    fn key_limits(max_mtu: MaxMtu, connection_limits: &connection::Limits) -> limited::Limits {
         let mut limits = limited::Limits::default();
 
         limits.max_mtu = max_mtu;
+        limits.key_update_window = connection_limits.key_update_window();
+        limits.max_packets_per_key = connection_limits.max_packets_per_key();
 
         // AEAD optimizations are currently in the testing phase so make them opt-in at runtime
         limits.sealer_optimization_threshold = {
@@ -565,7 +703,9 @@ impl<Config: endpoint::Config> transmission::interest::Provider for ApplicationS
         self.ack_manager.transmission_interest(query)?;
         self.ping.transmission_interest(query)?;
         self.recovery_manager.transmission_interest(query)?;
+        self.crypto_stream.transmission_interest(query)?;
         self.stream_manager.transmission_interest(query)?;
+        self.datagram_manager.transmission_interest(query)?;
         Ok(())
     }
 }
@@ -580,6 +720,7 @@ struct RecoveryContext<'a, Config: endpoint::Config> {
     ack_manager: &'a mut AckManager,
     handshake_status: &'a mut HandshakeStatus,
     ping: &'a mut flag::Ping,
+    crypto_stream: &'a mut CryptoStream,
     stream_manager: &'a mut AbstractStreamManager<Config::Stream>,
     local_id_registry: &'a mut connection::LocalIdRegistry,
     path_id: path::Id,
@@ -631,6 +772,7 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
         self.handshake_status
             .on_packet_ack(packet_number_range, publisher);
         self.ping.on_packet_ack(packet_number_range);
+        self.crypto_stream.on_packet_ack(packet_number_range);
         self.stream_manager.on_packet_ack(packet_number_range);
         self.local_id_registry.on_packet_ack(packet_number_range);
         self.path_manager.on_packet_ack(packet_number_range);
@@ -650,6 +792,7 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
         self.handshake_status
             .on_packet_loss(packet_number_range, publisher);
         self.ping.on_packet_loss(packet_number_range);
+        self.crypto_stream.on_packet_loss(packet_number_range);
         self.stream_manager.on_packet_loss(packet_number_range);
         self.local_id_registry.on_packet_loss(packet_number_range);
         self.path_manager.on_packet_loss(packet_number_range);
@@ -669,7 +812,7 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
 
     fn handle_crypto_frame<Pub: event::ConnectionPublisher>(
         &mut self,
-        _frame: CryptoRef,
+        frame: CryptoRef,
         _datagram: &DatagramInfo,
         _path: &mut Path<Config>,
         _publisher: &mut Pub,
@@ -679,9 +822,17 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         //# data in a CRYPTO frame, it MAY discard that CRYPTO frame and all
         //# CRYPTO frames received in the future, or it MAY close the connection
         //# with a CRYPTO_BUFFER_EXCEEDED error code.
+        if self.is_crypto_rx_closed {
+            return Ok(());
+        }
 
-        // we currently just discard CRYPTO frames post-handshake
-        Ok(())
+        let end_offset = frame.offset.as_u64() + frame.data.len() as u64;
+        if end_offset > self.crypto_stream.rx.consumed_len() + POST_HANDSHAKE_CRYPTO_BUFFER_LEN {
+            self.close_crypto_rx();
+            return Ok(());
+        }
+
+        self.crypto_stream.on_crypto_frame(frame)
     }
 
     fn handle_ack_frame<A: AckRanges, Pub: event::ConnectionPublisher>(
@@ -725,6 +876,14 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         Ok(())
     }
 
+    fn handle_datagram_frame(
+        &mut self,
+        frame: DatagramRef,
+        _packet: &mut ProcessedPacket,
+    ) -> Result<(), transport::Error> {
+        self.datagram_manager.on_datagram_frame(&frame)
+    }
+
     fn handle_data_blocked_frame(&mut self, frame: DataBlocked) -> Result<(), transport::Error> {
         self.stream_manager.on_data_blocked(frame)
     }
@@ -879,7 +1038,8 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         &mut self,
         frame: HandshakeDone,
         datagram: &DatagramInfo,
-        path: &mut Path<Config>,
+        path_id: path::Id,
+        path_manager: &mut path::Manager<Config>,
         local_id_registry: &mut connection::LocalIdRegistry,
         handshake_status: &mut HandshakeStatus,
         publisher: &mut Pub,
@@ -901,7 +1061,12 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         //# At the
         //# client, the handshake is considered confirmed when a HANDSHAKE_DONE
         //# frame is received.
-        self.on_handshake_confirmed(path, local_id_registry, datagram.timestamp);
+        self.on_handshake_confirmed(
+            &path_manager[path_id],
+            local_id_registry,
+            datagram.timestamp,
+        );
+        path_manager.on_handshake_confirmed();
 
         Ok(())
     }
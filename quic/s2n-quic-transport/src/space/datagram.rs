@@ -0,0 +1,534 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Queues unreliable datagrams between the application and the peer
+//!
+//! Outgoing datagrams are packed into application packets as space allows. Since they are not
+//! retransmitted, nothing is tracked once a datagram has been written to a packet.
+
+use crate::{contexts::WriteContext, transmission};
+use alloc::collections::VecDeque;
+use bytes::Bytes;
+use core::{
+    convert::TryFrom,
+    task::{Context, Poll, Waker},
+};
+use s2n_codec::EncoderValue;
+use s2n_quic_core::{
+    connection::{self, limits::Limits},
+    datagram::DatagramError,
+    frame::{datagram::DatagramRef, Datagram},
+    transport,
+    varint::VarInt,
+};
+
+#[derive(Debug)]
+pub struct Manager {
+    /// The maximum DATAGRAM frame size advertised to the peer
+    local_max_frame_size: u64,
+    /// The maximum DATAGRAM frame size the peer is willing to receive
+    peer_max_frame_size: u64,
+    /// Datagrams waiting to be transmitted
+    send_queue: VecDeque<Bytes>,
+    send_queue_capacity: usize,
+    /// The `Waker` for the task blocked on a full send queue
+    send_waker: Option<Waker>,
+    /// Datagrams waiting to be consumed by the application
+    receive_queue: VecDeque<Bytes>,
+    receive_queue_capacity: usize,
+    /// The `Waker` for the task blocked on an empty receive queue
+    receive_waker: Option<Waker>,
+    close_reason: Option<connection::Error>,
+}
+
+impl Manager {
+    pub fn new(limits: &Limits, peer_max_frame_size: u64) -> Self {
+        Self {
+            local_max_frame_size: limits.max_datagram_frame_size(),
+            peer_max_frame_size,
+            send_queue: VecDeque::new(),
+            send_queue_capacity: limits.datagram_send_queue_capacity(),
+            send_waker: None,
+            receive_queue: VecDeque::new(),
+            receive_queue_capacity: limits.datagram_receive_queue_capacity(),
+            receive_waker: None,
+            close_reason: None,
+        }
+    }
+
+    /// Returns the largest datagram payload that can currently be sent to the peer
+    ///
+    /// `max_packet_payload` is the number of bytes available for frames in a single packet
+    /// on the active path.
+    ///
+    /// Returns an error if the peer did not advertise support for receiving datagrams.
+    #[inline]
+    pub fn max_datagram_size(&self, max_packet_payload: usize) -> Result<usize, DatagramError> {
+        if self.peer_max_frame_size == 0 {
+            return Err(DatagramError::not_supported());
+        }
+
+        Ok(max_payload_len(self.max_frame_len(max_packet_payload)))
+    }
+
+    /// Queues a datagram to be sent to the peer
+    ///
+    /// On success, `data` is taken and replaced with an empty buffer. If the send queue is full
+    /// the `Waker` is stored and notified once space is available.
+    pub fn poll_send(
+        &mut self,
+        data: &mut Bytes,
+        max_packet_payload: usize,
+        context: &Context,
+    ) -> Poll<Result<(), DatagramError>> {
+        if let Some(error) = self.close_reason {
+            return Err(error.into()).into();
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9221#section-3
+        //# An endpoint MUST NOT send DATAGRAM frames until it has received the
+        //# max_datagram_frame_size transport parameter with a non-zero value
+        //# during the handshake (or during a previous handshake if 0-RTT is
+        //# used).
+        //
+        //= https://www.rfc-editor.org/rfc/rfc9221#section-3
+        //# An endpoint MUST NOT send DATAGRAM frames that are larger than the
+        //# max_datagram_frame_size value it has received from its peer.
+        let max_size = match self.max_datagram_size(max_packet_payload) {
+            Ok(max_size) => max_size,
+            Err(error) => return Err(error).into(),
+        };
+
+        if data.len() > max_size {
+            return Err(DatagramError::too_large(max_size)).into();
+        }
+
+        if self.send_queue.len() >= self.send_queue_capacity {
+            self.send_waker = Some(context.waker().clone());
+            return Poll::Pending;
+        }
+
+        self.send_queue.push_back(core::mem::take(data));
+
+        Ok(()).into()
+    }
+
+    /// Returns the next datagram received from the peer
+    ///
+    /// The method will return
+    /// - `Poll::Ready(Ok(Some(datagram)))` if a datagram was received
+    /// - `Poll::Ready(Ok(None))` if the connection was closed without an error
+    /// - `Poll::Ready(Err(error))` if no datagram could be received due to an error
+    /// - `Poll::Pending` if no datagram has been received yet
+    pub fn poll_receive(
+        &mut self,
+        context: &Context,
+    ) -> Poll<Result<Option<Bytes>, DatagramError>> {
+        self.receive_waker = None;
+
+        // Datagrams which were received before the connection was closed are still delivered
+        if let Some(datagram) = self.receive_queue.pop_front() {
+            return Ok(Some(datagram)).into();
+        }
+
+        match self.close_reason {
+            // The connection closed without an error
+            Some(connection::Error::Closed { .. }) => return Ok(None).into(),
+            // Translate application closes to the end of the datagram flow
+            Some(connection::Error::Transport { code, .. })
+                if code == transport::Error::APPLICATION_ERROR.code =>
+            {
+                return Ok(None).into()
+            }
+            // Translate idle timer expiration to the end of the datagram flow
+            Some(connection::Error::IdleTimerExpired { .. }) => return Ok(None).into(),
+            Some(reason) => return Err(reason.into()).into(),
+            None => {}
+        }
+
+        if self.local_max_frame_size == 0 {
+            return Err(DatagramError::disabled()).into();
+        }
+
+        self.receive_waker = Some(context.waker().clone());
+
+        Poll::Pending
+    }
+
+    /// Called when a DATAGRAM frame is received from the peer
+    pub fn on_datagram_frame(&mut self, frame: &DatagramRef) -> Result<(), transport::Error> {
+        //= https://www.rfc-editor.org/rfc/rfc9221#section-3
+        //# An endpoint that receives a DATAGRAM frame when it has not indicated
+        //# support via the transport parameter MUST terminate the connection
+        //# with an error of type PROTOCOL_VIOLATION.
+        if self.local_max_frame_size == 0 {
+            return Err(transport::Error::PROTOCOL_VIOLATION
+                .with_reason("received a DATAGRAM frame without advertising support"));
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9221#section-3
+        //# Similarly, an endpoint that
+        //# receives a DATAGRAM frame that is larger than the value it sent in
+        //# its max_datagram_frame_size transport parameter MUST terminate the
+        //# connection with an error of type PROTOCOL_VIOLATION.
+        if frame.encoding_size() as u64 > self.local_max_frame_size {
+            return Err(transport::Error::PROTOCOL_VIOLATION
+                .with_reason("DATAGRAM frame exceeds max_datagram_frame_size"));
+        }
+
+        // Datagrams are unreliable so if the application isn't keeping up, make room by
+        // dropping the oldest datagram rather than blocking the connection.
+        if self.receive_queue.len() >= self.receive_queue_capacity {
+            self.receive_queue.pop_front();
+        }
+
+        if self.receive_queue_capacity > 0 {
+            self.receive_queue
+                .push_back(Bytes::copy_from_slice(frame.data));
+        }
+
+        if let Some(waker) = self.receive_waker.take() {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    /// Writes queued datagrams into the outgoing packet
+    ///
+    /// `max_packet_payload` is the number of bytes available for frames in a single packet
+    /// on the active path. Any datagram that can no longer fit within this limit is dropped.
+    pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W, max_packet_payload: usize) {
+        //= https://www.rfc-editor.org/rfc/rfc9221#section-5
+        //# When an application sends a datagram over a QUIC connection, QUIC
+        //# will generate a new DATAGRAM frame and send it in the first available
+        //# packet.
+        if !context.transmission_constraint().can_transmit() {
+            return;
+        }
+
+        let max_frame_len = self.max_frame_len(max_packet_payload);
+        let mut did_dequeue = false;
+
+        while let Some(data) = self.send_queue.front() {
+            let frame = Datagram {
+                is_last_frame: false,
+                data: data.as_ref(),
+            };
+
+            if frame.encoding_size() <= max_frame_len && context.write_frame(&frame).is_none() {
+                // the current packet is full so try again with the next one
+                break;
+            }
+
+            // The datagram was either written or can no longer be sent after the path MTU
+            // decreased. Either way it's removed from the queue.
+            self.send_queue.pop_front();
+            did_dequeue = true;
+        }
+
+        if did_dequeue {
+            if let Some(waker) = self.send_waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Closes the manager and notifies any blocked tasks
+    pub fn close(&mut self, error: connection::Error) {
+        if self.close_reason.is_some() {
+            return;
+        }
+
+        self.close_reason = Some(error);
+        self.send_queue.clear();
+
+        if let Some(waker) = self.send_waker.take() {
+            waker.wake();
+        }
+
+        if let Some(waker) = self.receive_waker.take() {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    fn max_frame_len(&self, max_packet_payload: usize) -> usize {
+        self.peer_max_frame_size.min(max_packet_payload as u64) as usize
+    }
+}
+
+impl transmission::interest::Provider for Manager {
+    #[inline]
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        if !self.send_queue.is_empty() {
+            query.on_new_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the largest datagram payload that fits in a DATAGRAM frame of `frame_len` bytes
+#[inline]
+fn max_payload_len(frame_len: usize) -> usize {
+    // The frame tag is a single byte
+    let capacity = frame_len.saturating_sub(1);
+
+    // Conservatively reserve enough space for the length prefix of the largest possible payload
+    let len_prefix = VarInt::try_from(capacity)
+        .map(|len| len.encoding_size())
+        .unwrap_or(8);
+
+    capacity.saturating_sub(len_prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contexts::testing::*, transmission::interest::Provider as _};
+    use futures_test::task::new_count_waker;
+    use s2n_quic_core::endpoint;
+    use s2n_quic_platform::time;
+
+    fn limits(max_frame_size: u64, queue_capacity: usize) -> Limits {
+        Limits::new()
+            .with_max_datagram_frame_size(max_frame_size)
+            .unwrap()
+            .with_datagram_send_queue_capacity(queue_capacity)
+            .unwrap()
+            .with_datagram_receive_queue_capacity(queue_capacity)
+            .unwrap()
+    }
+
+    #[test]
+    fn max_payload_len_test() {
+        assert_eq!(max_payload_len(0), 0);
+        assert_eq!(max_payload_len(1), 0);
+        assert_eq!(max_payload_len(2), 0);
+        assert_eq!(max_payload_len(3), 1);
+        assert_eq!(max_payload_len(64), 62);
+        assert_eq!(max_payload_len(65), 62);
+        assert_eq!(max_payload_len(1200), 1197);
+
+        // make sure the calculated payload length always fits in the frame
+        for frame_len in 0..20_000 {
+            let payload = vec![0u8; max_payload_len(frame_len)];
+            let frame = Datagram {
+                is_last_frame: false,
+                data: &payload[..],
+            };
+            assert!(frame.encoding_size() <= frame_len.max(2));
+        }
+    }
+
+    #[test]
+    fn send_not_supported_test() {
+        let (waker, _) = new_count_waker();
+        let mut manager = Manager::new(&limits(65535, 4), 0);
+
+        assert!(matches!(
+            manager.max_datagram_size(1200),
+            Err(DatagramError::NotSupported { .. })
+        ));
+
+        let mut data = Bytes::from_static(&[1, 2, 3]);
+        assert!(matches!(
+            manager.poll_send(&mut data, 1200, &Context::from_waker(&waker)),
+            Poll::Ready(Err(DatagramError::NotSupported { .. }))
+        ));
+    }
+
+    #[test]
+    fn send_too_large_test() {
+        let (waker, _) = new_count_waker();
+        let mut manager = Manager::new(&limits(0, 4), 100);
+        let max_size = manager.max_datagram_size(1200).unwrap();
+        assert_eq!(max_size, 97);
+
+        let mut data = Bytes::from(vec![0u8; max_size + 1]);
+        assert!(matches!(
+            manager.poll_send(&mut data, 1200, &Context::from_waker(&waker)),
+            Poll::Ready(Err(DatagramError::TooLarge { max_size: 97, .. }))
+        ));
+
+        // the packet size also limits the datagram size
+        assert_eq!(manager.max_datagram_size(50), Ok(48));
+    }
+
+    #[test]
+    fn send_queue_test() {
+        let (waker, wake_count) = new_count_waker();
+        let cx = Context::from_waker(&waker);
+        let mut manager = Manager::new(&limits(0, 2), 65535);
+
+        assert!(!manager.has_transmission_interest());
+
+        for i in 0..2u8 {
+            let mut data = Bytes::from(vec![i; 10]);
+            assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Ready(Ok(())));
+            assert!(data.is_empty());
+        }
+
+        assert_eq!(
+            manager.get_transmission_interest(),
+            transmission::Interest::NewData
+        );
+
+        // the queue is full
+        let mut data = Bytes::from(vec![2u8; 10]);
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Pending);
+        assert_eq!(data.len(), 10);
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Client,
+        );
+
+        manager.on_transmit(&mut write_context, 1200);
+
+        assert_eq!(write_context.frame_buffer.len(), 2);
+        assert!(!manager.has_transmission_interest());
+        assert_eq!(wake_count, 1);
+
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    fn transmit_constraint_test() {
+        let (waker, _) = new_count_waker();
+        let cx = Context::from_waker(&waker);
+        let mut manager = Manager::new(&limits(0, 2), 65535);
+
+        let mut data = Bytes::from_static(&[1, 2, 3]);
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Ready(Ok(())));
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::CongestionLimited,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
+        );
+
+        manager.on_transmit(&mut write_context, 1200);
+        assert!(write_context.frame_buffer.is_empty());
+        assert!(manager.has_transmission_interest());
+
+        // datagrams are never retransmitted so they shouldn't be sent in this phase
+        write_context.transmission_constraint = transmission::Constraint::RetransmissionOnly;
+        manager.on_transmit(&mut write_context, 1200);
+        assert!(write_context.frame_buffer.is_empty());
+
+        write_context.transmission_constraint = transmission::Constraint::None;
+        manager.on_transmit(&mut write_context, 1200);
+        assert_eq!(write_context.frame_buffer.len(), 1);
+    }
+
+    #[test]
+    fn transmit_drops_oversized_test() {
+        let (waker, _) = new_count_waker();
+        let cx = Context::from_waker(&waker);
+        let mut manager = Manager::new(&limits(0, 2), 65535);
+
+        let mut data = Bytes::from(vec![0u8; 1000]);
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Ready(Ok(())));
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
+        );
+
+        // the path MTU decreased after the datagram was queued
+        manager.on_transmit(&mut write_context, 500);
+        assert!(write_context.frame_buffer.is_empty());
+        assert!(!manager.has_transmission_interest());
+    }
+
+    #[test]
+    fn receive_test() {
+        let (waker, wake_count) = new_count_waker();
+        let cx = Context::from_waker(&waker);
+        let mut manager = Manager::new(&limits(100, 2), 0);
+
+        assert_eq!(manager.poll_receive(&cx), Poll::Pending);
+
+        for i in 0..3u8 {
+            let data = [i; 10];
+            let frame = Datagram {
+                is_last_frame: true,
+                data: &data[..],
+            };
+            manager.on_datagram_frame(&frame).unwrap();
+        }
+
+        assert_eq!(wake_count, 1);
+
+        // the oldest datagram was dropped since the queue was full
+        assert_eq!(
+            manager.poll_receive(&cx),
+            Poll::Ready(Ok(Some(Bytes::from(vec![1u8; 10]))))
+        );
+        assert_eq!(
+            manager.poll_receive(&cx),
+            Poll::Ready(Ok(Some(Bytes::from(vec![2u8; 10]))))
+        );
+        assert_eq!(manager.poll_receive(&cx), Poll::Pending);
+
+        manager.close(connection::Error::idle_timer_expired());
+        assert_eq!(wake_count, 2);
+        assert_eq!(manager.poll_receive(&cx), Poll::Ready(Ok(None)));
+    }
+
+    #[test]
+    fn receive_protocol_violation_test() {
+        let data = [0u8; 100];
+        let frame = Datagram {
+            is_last_frame: true,
+            data: &data[..],
+        };
+
+        let mut manager = Manager::new(&limits(0, 2), 65535);
+        assert!(manager.on_datagram_frame(&frame).is_err());
+
+        let mut manager = Manager::new(&limits(100, 2), 65535);
+        assert!(manager.on_datagram_frame(&frame).is_err());
+
+        let mut manager = Manager::new(&limits(101, 2), 65535);
+        assert!(manager.on_datagram_frame(&frame).is_ok());
+    }
+
+    #[test]
+    fn close_test() {
+        let (waker, wake_count) = new_count_waker();
+        let cx = Context::from_waker(&waker);
+        let mut manager = Manager::new(&limits(0, 1), 65535);
+
+        let mut data = Bytes::from_static(&[1]);
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Ready(Ok(())));
+        let mut data = Bytes::from_static(&[2]);
+        assert_eq!(manager.poll_send(&mut data, 1200, &cx), Poll::Pending);
+
+        let error = connection::Error::unspecified();
+        manager.close(error);
+        assert_eq!(wake_count, 1);
+        assert!(!manager.has_transmission_interest());
+
+        assert_eq!(
+            manager.poll_send(&mut data, 1200, &cx),
+            Poll::Ready(Err(DatagramError::from(error)))
+        );
+    }
+}
@@ -13,10 +13,16 @@ use crate::{
     transmission,
 };
 use core::{fmt, marker::PhantomData};
-use s2n_codec::EncoderBuffer;
+use s2n_codec::{DecoderBuffer, EncoderBuffer};
 use s2n_quic_core::{
     connection::PeerId,
-    crypto::{tls, CryptoSuite, InitialKey},
+    crypto::{
+        tls::{
+            self,
+            client_hello::{self, ClientHello},
+        },
+        CryptoSuite, InitialKey,
+    },
     event::{self, ConnectionPublisher as _, IntoEvent},
     frame::{ack::AckRanges, crypto::CryptoRef, Ack, ConnectionClose},
     inet::DatagramInfo,
@@ -26,10 +32,43 @@ use s2n_quic_core::{
         number::{PacketNumber, PacketNumberRange, PacketNumberSpace, SlidingWindow},
     },
     time::{timer, Timestamp},
-    transport,
+    transport::{
+        self,
+        parameters::{ClientTransportParameters, VersionInformation},
+    },
 };
 use smallvec::SmallVec;
 
+/// Reads the version information from the transport parameters in the ClientHello payload
+///
+/// Servers don't negotiate a version when the client attempts 0-RTT, since the early data is
+/// protected with the keys of the original version.
+fn client_version_information(payload: &[&[u8]]) -> Option<VersionInformation> {
+    let owned;
+    let payload = match payload {
+        [payload] => *payload,
+        _ => {
+            owned = payload.concat();
+            &owned[..]
+        }
+    };
+
+    let hello = ClientHello::decode(DecoderBuffer::new(payload)).ok()?;
+
+    if hello.extension(client_hello::EARLY_DATA).ok()?.is_some() {
+        return None;
+    }
+
+    let parameters = hello
+        .extension(client_hello::QUIC_TRANSPORT_PARAMETERS)
+        .ok()??;
+    let (parameters, _) = DecoderBuffer::new(parameters)
+        .decode::<ClientTransportParameters>()
+        .ok()?;
+
+    parameters.version_information
+}
+
 pub struct InitialSpace<Config: endpoint::Config> {
     pub ack_manager: AckManager,
     //= https://www.rfc-editor.org/rfc/rfc9001#section-4
@@ -45,6 +84,12 @@ pub struct InitialSpace<Config: endpoint::Config> {
     pub crypto_stream: CryptoStream,
     pub tx_packet_numbers: TxPacketNumbers,
     pub received_hello_message: bool,
+    /// The version information from the ClientHello, which the server uses to negotiate a
+    /// compatible version
+    pub client_version_information: Option<VersionInformation>,
+    /// The Initial keys of the version the client started with, which the server keeps after
+    /// negotiating a compatible version
+    original_keys: Option<Box<OriginalKeys<Config>>>,
     //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.3
     //# Subsequent Initial packets from the client include the connection ID
     //# and token values from the Retry packet.
@@ -53,6 +98,12 @@ pub struct InitialSpace<Config: endpoint::Config> {
     recovery_manager: recovery::Manager<Config>,
 }
 
+struct OriginalKeys<Config: endpoint::Config> {
+    version: u32,
+    key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
+    header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialHeaderKey,
+}
+
 impl<Config: endpoint::Config> fmt::Debug for InitialSpace<Config> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("InitialSpace")
@@ -78,6 +129,8 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
             crypto_stream: CryptoStream::new(),
             tx_packet_numbers: TxPacketNumbers::new(PacketNumberSpace::Initial, now),
             received_hello_message: false,
+            client_version_information: None,
+            original_keys: None,
             retry_token: Vec::new(),
             processed_packet_numbers: SlidingWindow::default(),
             recovery_manager: recovery::Manager::new(PacketNumberSpace::Initial),
@@ -91,6 +144,7 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
     pub fn on_retry_packet(
         &mut self,
         path: &mut path::Path<Config>,
+        quic_version: u32,
         retry_source_connection_id: &PeerId,
         retry_token: &[u8],
     ) {
@@ -102,6 +156,7 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
         //# a change to the keys used to protect the Initial packet.
         let (initial_key, initial_header_key) =
                             <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_client(
+                                quic_version,
                                 retry_source_connection_id.as_bytes(),
                             );
 
@@ -120,6 +175,39 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
         self.recovery_manager.on_retry_packet(path);
     }
 
+    /// This method gets called on servers when a compatible version was negotiated.
+    ///
+    /// The space switches to the keys of the negotiated version for all packets it sends, but
+    /// keeps the keys of the original version.
+    pub fn on_version_negotiated(
+        &mut self,
+        original_version: u32,
+        key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
+        header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialHeaderKey,
+    ) {
+        debug_assert!(Config::ENDPOINT_TYPE.is_server());
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-2.3>
+        //
+        // Until the client sends a packet of the negotiated version, it can still send Initial
+        // packets of the original version, such as a retransmitted ClientHello or an ACK of the
+        // server's Initial packets.
+        let key = core::mem::replace(&mut self.key, key);
+        let header_key = core::mem::replace(&mut self.header_key, header_key);
+        self.original_keys = Some(Box::new(OriginalKeys {
+            version: original_version,
+            key,
+            header_key,
+        }));
+    }
+
+    /// Returns true if Initial packets of the given original version are still accepted
+    pub fn accepts_original_version(&self, version: u32) -> bool {
+        self.original_keys
+            .as_ref()
+            .map_or(false, |keys| keys.version == version)
+    }
+
     /// Returns true if the packet number has already been processed
     pub fn is_duplicate<Pub: event::ConnectionPublisher>(
         &self,
@@ -378,15 +466,21 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
 
     /// Validate packets in the Initial packet space
     pub fn validate_and_decrypt_packet<'a, Pub: event::ConnectionPublisher>(
-        &self,
+        &mut self,
         protected: ProtectedInitial<'a>,
         path_id: path::Id,
         path: &path::Path<Config>,
         publisher: &mut Pub,
     ) -> Result<CleartextInitial<'a>, ProcessingError> {
+        let is_original_version = self.accepts_original_version(protected.version);
+        let (key, header_key) = match self.original_keys.as_deref() {
+            Some(original) if is_original_version => (&original.key, &original.header_key),
+            _ => (&self.key, &self.header_key),
+        };
+
         let packet_number_decoder = self.packet_number_decoder();
         let packet = protected
-            .unprotect(&self.header_key, packet_number_decoder)
+            .unprotect(header_key, packet_number_decoder)
             .map_err(|err| {
                 publisher.on_packet_dropped(event::builder::PacketDropped {
                     reason: event::builder::PacketDropReason::UnprotectFailed {
@@ -403,7 +497,7 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
 
         let packet_header =
             event::builder::PacketHeader::new(packet.packet_number, publisher.quic_version());
-        let decrypted = packet.decrypt(&self.key).map_err(|err| {
+        let decrypted = packet.decrypt(key).map_err(|err| {
             publisher.on_packet_dropped(event::builder::PacketDropped {
                 reason: event::builder::PacketDropReason::DecryptionFailed {
                     packet_header,
@@ -427,6 +521,12 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
             return Err(ProcessingError::NonEmptyRetryToken);
         }
 
+        // Once the client sends a packet of the negotiated version, it no longer uses the
+        // original version.
+        if !is_original_version {
+            self.original_keys = None;
+        }
+
         Ok(decrypted)
     }
 
@@ -435,9 +535,14 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
         publisher: &mut Pub,
     ) -> Result<(), transport::Error> {
         debug_assert!(Config::ENDPOINT_TYPE.is_server());
-        if let Some(payload) = self.parse_hello(tls::HandshakeType::ClientHello)? {
-            publisher.on_tls_client_hello(event::builder::TlsClientHello { payload: &payload });
-        }
+        let version_information =
+            if let Some(payload) = self.parse_hello(tls::HandshakeType::ClientHello)? {
+                publisher.on_tls_client_hello(event::builder::TlsClientHello { payload: &payload });
+                client_version_information(&payload)
+            } else {
+                None
+            };
+        self.client_version_information = version_information;
         Ok(())
     }
 
@@ -684,3 +789,100 @@ impl<Config: endpoint::Config> PacketSpace<Config> for InitialSpace<Config> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint::testing::Server, path::testing::helper_path_server};
+    use s2n_codec::DecoderBufferMut;
+    use s2n_quic_core::{
+        ack,
+        connection::id::ConnectionInfo,
+        crypto::key::testing::{HeaderKey, Key},
+        event::testing::Publisher,
+        inet::SocketAddress,
+        packet::{
+            long::{VERSION_1, VERSION_2},
+            ProtectedPacket,
+        },
+    };
+    use s2n_quic_platform::time;
+
+    /// Encodes an Initial packet, which the testing keys leave unprotected
+    fn initial_packet(version: u32, packet_number: u8) -> Vec<u8> {
+        // The Initial long packet type is 0b00 in version 1 and 0b01 in version 2
+        let tag = if version == VERSION_2 { 0xd0 } else { 0xc0 };
+        let mut packet = vec![tag];
+        packet.extend_from_slice(&version.to_be_bytes());
+        // destination connection id
+        packet.push(8);
+        packet.extend_from_slice(&[1; 8]);
+        // empty source connection id and token
+        packet.extend_from_slice(&[0, 0]);
+        // the length of the packet number and the PADDING frames
+        packet.push(33);
+        packet.push(packet_number);
+        packet.extend_from_slice(&[0; 32]);
+        packet
+    }
+
+    fn receive(space: &mut InitialSpace<Server>, version: u32, packet_number: u8) -> bool {
+        let mut packet = initial_packet(version, packet_number);
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let decoder = DecoderBufferMut::new(&mut packet);
+        let packet = match ProtectedPacket::decode(decoder, &connection_info, &8)
+            .unwrap()
+            .0
+        {
+            ProtectedPacket::Initial(packet) => packet,
+            _ => panic!("expected an Initial packet"),
+        };
+
+        space
+            .validate_and_decrypt_packet(
+                packet,
+                path::Id::test_id(),
+                &helper_path_server(),
+                &mut Publisher::no_snapshot(),
+            )
+            .is_ok()
+    }
+
+    #[test]
+    fn original_version_keys_test() {
+        let mut space = InitialSpace::<Server>::new(
+            Key::new(),
+            HeaderKey::new(),
+            time::now(),
+            AckManager::new(PacketNumberSpace::Initial, ack::Settings::EARLY),
+        );
+        assert!(!space.accepts_original_version(VERSION_1));
+
+        // the negotiated keys fail to open packets so the keys in use can be told apart
+        let negotiated_key = Key {
+            fail_on_decrypt: true,
+            ..Key::new()
+        };
+        space.on_version_negotiated(VERSION_1, negotiated_key, HeaderKey::new());
+        assert!(space.accepts_original_version(VERSION_1));
+        assert!(!space.accepts_original_version(VERSION_2));
+
+        // the client can still send packets of the original version, such as a retransmitted
+        // ClientHello
+        assert!(receive(&mut space, VERSION_1, 1));
+        assert!(receive(&mut space, VERSION_1, 2));
+        assert!(!receive(&mut space, VERSION_2, 3));
+
+        // packets that fail to open don't confirm that the client switched versions
+        assert!(space.accepts_original_version(VERSION_1));
+
+        // the original keys are discarded once the client sends a packet of the negotiated version
+        space.key.fail_on_decrypt = false;
+        assert!(receive(&mut space, VERSION_2, 4));
+        assert!(!space.accepts_original_version(VERSION_1));
+
+        space.key.fail_on_decrypt = true;
+        assert!(!receive(&mut space, VERSION_1, 5));
+    }
+}
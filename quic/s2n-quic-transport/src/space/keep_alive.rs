@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use core::{task::Poll, time::Duration};
-use s2n_quic_core::time::{timer, Timer, Timestamp};
+use s2n_quic_core::time::{timer, timer::Provider as _, Timer, Timestamp};
 
 #[derive(Debug)]
 pub struct KeepAlive {
@@ -13,7 +13,15 @@ pub struct KeepAlive {
 
 impl KeepAlive {
     pub fn new(max_idle_timeout: Option<Duration>, max_period: Duration) -> Self {
-        let period = if let Some(max_idle_timeout) = max_idle_timeout {
+        Self {
+            enabled: false,
+            period: Self::compute_period(max_idle_timeout, max_period),
+            timer: Timer::default(),
+        }
+    }
+
+    fn compute_period(max_idle_timeout: Option<Duration>, max_period: Duration) -> Duration {
+        if let Some(max_idle_timeout) = max_idle_timeout {
             // send a ping frame at 3/4 max idle timeout to ensure it is delivered in time
             (max_idle_timeout * 3 / 4).min(max_period)
         } else {
@@ -29,13 +37,25 @@ impl KeepAlive {
             // Even though we don't have an idle timeout, we should still have a default
             // keep-alive period to ensure middleboxes don't drop their UDP flow
             max_period
-        };
+        }
+    }
 
-        Self {
-            enabled: false,
-            period,
-            timer: Timer::default(),
+    /// Recomputes the period after the limits of the connection have changed
+    ///
+    /// An armed timer is moved to expire as if it had been set with the new period.
+    #[inline]
+    pub fn update_period(&mut self, max_idle_timeout: Option<Duration>, max_period: Duration) {
+        let period = Self::compute_period(max_idle_timeout, max_period);
+
+        if let Some(reset_time) = self
+            .timer
+            .next_expiration()
+            .and_then(|expiration| expiration.checked_sub(self.period))
+        {
+            self.timer.set(reset_time + period);
         }
+
+        self.period = period;
     }
 
     #[inline]
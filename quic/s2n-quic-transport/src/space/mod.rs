@@ -6,6 +6,7 @@ use crate::{
     path::{path_event, Path},
     processed_packet::ProcessedPacket,
     space::rx_packet_numbers::AckManager,
+    stream::AbstractStreamManager,
     transmission,
 };
 use bytes::Bytes;
@@ -18,7 +19,7 @@ use s2n_quic_core::{
     ack,
     application::ServerName,
     connection::{limits::Limits, InitialId, PeerId},
-    crypto::{tls, tls::Session, CryptoSuite, Key},
+    crypto::{tls, tls::Session, CryptoSuite, InitialKey, Key},
     event::{self, IntoEvent},
     frame::{
         ack::AckRanges, crypto::CryptoRef, datagram::DatagramRef, stream::StreamRef, Ack,
@@ -29,11 +30,12 @@ use s2n_quic_core::{
     inet::DatagramInfo,
     packet::number::{PacketNumber, PacketNumberSpace},
     time::{timer, Timestamp},
-    transport,
+    transport::{self, parameters::CustomTransportParameters},
 };
 
 mod application;
 mod crypto_stream;
+pub(crate) mod datagram;
 mod handshake;
 mod handshake_status;
 mod initial;
@@ -41,6 +43,8 @@ mod keep_alive;
 pub(crate) mod rx_packet_numbers;
 mod session_context;
 mod tx_packet_numbers;
+mod version_negotiation;
+mod zero_rtt;
 
 pub(crate) use application::ApplicationSpace;
 pub(crate) use crypto_stream::CryptoStream;
@@ -49,10 +53,14 @@ pub(crate) use handshake_status::HandshakeStatus;
 pub(crate) use initial::InitialSpace;
 pub(crate) use session_context::SessionContext;
 pub(crate) use tx_packet_numbers::TxPacketNumbers;
+pub(crate) use version_negotiation::VersionNegotiation;
+pub(crate) use zero_rtt::{ZeroRttCrypto, ZeroRttReceiver, ZeroRttSpace};
 
 struct SessionInfo<Config: endpoint::Config> {
     session: <Config::TLSEndpoint as tls::Endpoint>::Session,
     initial_cid: InitialId,
+    /// Bounds how long the session is kept after the handshake to read post-handshake messages
+    post_handshake_timer: timer::Timer,
 }
 
 pub struct PacketSpaceManager<Config: endpoint::Config> {
@@ -61,9 +69,12 @@ pub struct PacketSpaceManager<Config: endpoint::Config> {
     initial: Option<Box<InitialSpace<Config>>>,
     handshake: Option<Box<HandshakeSpace<Config>>>,
     application: Option<Box<ApplicationSpace<Config>>>,
-    zero_rtt_crypto:
-        Option<Box<<<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey>>,
+    /// The 0-RTT keys used by a server to read early data
+    zero_rtt_crypto: Option<Box<ZeroRttCrypto<Config>>>,
+    /// The space used by a client to send early data
+    zero_rtt: Option<Box<ZeroRttSpace<Config>>>,
     handshake_status: HandshakeStatus,
+    version_negotiation: VersionNegotiation,
     /// Server Name Indication
     pub server_name: Option<ServerName>,
     //= https://www.rfc-editor.org/rfc/rfc9000#section-7
@@ -74,6 +85,10 @@ pub struct PacketSpaceManager<Config: endpoint::Config> {
     //# another mechanism is used for agreeing on an application protocol,
     //# endpoints MUST use ALPN for this purpose.
     pub application_protocol: Bytes,
+    /// The details of the TLS session, which are available once the handshake completes
+    pub handshake_info: Option<tls::HandshakeInfo>,
+    /// The transport parameters defined by the peer's application
+    pub peer_custom_transport_parameters: CustomTransportParameters,
 }
 
 impl<Config: endpoint::Config> fmt::Debug for PacketSpaceManager<Config> {
@@ -82,6 +97,7 @@ impl<Config: endpoint::Config> fmt::Debug for PacketSpaceManager<Config> {
             .field("initial", &self.initial)
             .field("handshake", &self.handshake)
             .field("application", &self.application)
+            .field("zero_rtt", &self.zero_rtt)
             .field("handshake_status", &self.handshake_status)
             .finish()
     }
@@ -141,6 +157,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
         session: <Config::TLSEndpoint as tls::Endpoint>::Session,
         initial_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
         header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialHeaderKey,
+        version_negotiation: VersionNegotiation,
         now: Timestamp,
         publisher: &mut Pub,
     ) -> Self {
@@ -154,6 +171,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
             session_info: Some(SessionInfo {
                 session,
                 initial_cid,
+                post_handshake_timer: Default::default(),
             }),
             retry_cid: None,
             initial: Some(Box::new(InitialSpace::new(
@@ -165,9 +183,13 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
             handshake: None,
             application: None,
             zero_rtt_crypto: None,
+            zero_rtt: None,
             handshake_status: HandshakeStatus::default(),
+            version_negotiation,
             server_name: None,
             application_protocol: Bytes::new(),
+            handshake_info: None,
+            peer_custom_transport_parameters: CustomTransportParameters::default(),
         }
     }
 
@@ -183,17 +205,40 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
 
     packet_space_api!(ApplicationSpace<Config>, application, application_mut);
 
-    #[allow(dead_code)] // 0RTT hasn't been started yet
-    pub fn zero_rtt_crypto(
-        &self,
-    ) -> Option<&<<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey> {
-        self.zero_rtt_crypto.as_ref().map(Box::as_ref)
+    packet_space_api!(ZeroRttSpace<Config>, zero_rtt, zero_rtt_mut);
+
+    /// Returns the application space along with the 0-RTT keys, if the server accepted early
+    /// data on the connection
+    pub fn zero_rtt_receiver_mut(
+        &mut self,
+    ) -> Option<(
+        &ZeroRttCrypto<Config>,
+        &mut ApplicationSpace<Config>,
+        &mut HandshakeStatus,
+    )> {
+        let crypto = self.zero_rtt_crypto.as_deref()?;
+        let space = self.application.as_deref_mut()?;
+        Some((crypto, space, &mut self.handshake_status))
     }
 
     pub fn discard_zero_rtt_crypto(&mut self) {
         self.zero_rtt_crypto = None;
     }
 
+    /// Returns the stream manager of the space currently used to exchange application data
+    ///
+    /// Before the handshake completes, a client sending early data opens streams in the 0-RTT
+    /// space.
+    pub fn stream_manager_mut(&mut self) -> Option<&mut AbstractStreamManager<Config::Stream>> {
+        if let Some(space) = self.application.as_deref_mut() {
+            return Some(&mut space.stream_manager);
+        }
+
+        self.zero_rtt
+            .as_deref_mut()
+            .map(|space| &mut space.stream_manager)
+    }
+
     pub fn poll_crypto<Pub: event::ConnectionPublisher>(
         &mut self,
         path_manager: &mut path::Manager<Config>,
@@ -212,29 +257,55 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
                 handshake: &mut self.handshake,
                 application: &mut self.application,
                 zero_rtt_crypto: &mut self.zero_rtt_crypto,
+                zero_rtt: &mut self.zero_rtt,
                 path_manager,
                 handshake_status: &mut self.handshake_status,
+                version_negotiation: &self.version_negotiation,
                 local_id_registry,
                 limits,
                 server_name: &mut self.server_name,
                 application_protocol: &mut self.application_protocol,
+                handshake_info: &mut self.handshake_info,
+                peer_custom_transport_parameters: &mut self.peer_custom_transport_parameters,
                 waker,
                 publisher,
             };
 
             match session_info.session.poll(&mut context)? {
-                Poll::Ready(_success) => {
-                    // The TLS session and retry_cid is no longer needed
-                    self.session_info = None;
-                    self.retry_cid = None;
+                Poll::Ready(_success) => self.discard_session(),
+                Poll::Pending => {
+                    // The session is kept after the handshake while it waits for post-handshake
+                    // messages, like a session ticket. The peer isn't required to send any so
+                    // the wait is bounded.
+                    if self.handshake_status.is_complete()
+                        && !session_info.post_handshake_timer.is_armed()
+                    {
+                        let pto = path_manager
+                            .active_path()
+                            .pto_period(PacketNumberSpace::ApplicationData);
+                        session_info.post_handshake_timer.set(now + 3 * pto);
+                    }
+
+                    return Poll::Pending;
                 }
-                Poll::Pending => return Poll::Pending,
             };
         }
 
         Poll::Ready(Ok(()))
     }
 
+    /// Drops the TLS session once it has nothing left to exchange
+    fn discard_session(&mut self) {
+        // The TLS session and retry_cid is no longer needed
+        self.session_info = None;
+        self.retry_cid = None;
+
+        // Nothing is left to read any post-handshake messages
+        if let Some(application) = self.application.as_mut() {
+            application.close_crypto_rx();
+        }
+    }
+
     /// Called when the connection timer expired
     pub fn on_timeout<Pub: event::ConnectionPublisher>(
         &mut self,
@@ -276,6 +347,21 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
                 publisher,
             )
         }
+        if let Some((space, _handshake_status)) = self.zero_rtt_mut() {
+            space.on_timeout(timestamp)
+        }
+        if self.zero_rtt_crypto.as_mut().map_or(false, |crypto| {
+            crypto.discard_timer.poll_expiration(timestamp).is_ready()
+        }) {
+            self.discard_zero_rtt_crypto();
+        }
+        if self.session_info.as_mut().map_or(false, |info| {
+            info.post_handshake_timer
+                .poll_expiration(timestamp)
+                .is_ready()
+        }) {
+            self.discard_session();
+        }
 
         let path = path_manager.active_path_mut();
         path.pto_backoff = path.pto_backoff.min(max_backoff);
@@ -421,6 +507,10 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
         self.discard_handshake(path, path_id, publisher);
         self.discard_zero_rtt_crypto();
 
+        if let Some((zero_rtt, _handshake_status)) = self.zero_rtt_mut() {
+            zero_rtt.stream_manager.close(error);
+        }
+
         // Don't discard the application space until the application has read the error
         if let Some((application, _handshake_status)) = self.application_mut() {
             //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2
@@ -430,6 +520,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
 
             // Close all streams with the derived error
             application.stream_manager.close(error);
+            application.datagram_manager.close(error);
         }
     }
 
@@ -441,6 +532,107 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
     pub fn retry_cid(&self) -> Option<&PeerId> {
         self.retry_cid.as_deref()
     }
+
+    /// Called on servers once the ClientHello may have been received
+    ///
+    /// Returns the negotiated version if the connection switched to a version other than the
+    /// one the client started with.
+    pub fn negotiate_version<Pub: event::EndpointPublisher>(
+        &mut self,
+        publisher: &mut Pub,
+    ) -> Option<u32> {
+        debug_assert!(Config::ENDPOINT_TYPE.is_server());
+
+        let initial = self.initial.as_deref_mut()?;
+        if !initial.received_hello_message {
+            return None;
+        }
+
+        let client = initial.client_version_information.take()?;
+        let session_info = self.session_info.as_mut()?;
+        let original_version = self.version_negotiation.original_version();
+
+        let supported_versions = endpoint::version::supported_versions::<Config>();
+        let negotiated = match self
+            .version_negotiation
+            .on_client_hello(&client, supported_versions)
+        {
+            // The TLS provider may not be able to change the transport parameters after the
+            // session was created, in which case the original version is kept.
+            Some(negotiated)
+                if negotiated.version != original_version
+                    && session_info
+                        .session
+                        .set_transport_parameters(&negotiated.parameters) =>
+            {
+                Some(negotiated)
+            }
+            _ => None,
+        };
+
+        publisher.on_version_information(event::builder::VersionInformation {
+            server_versions: supported_versions,
+            client_versions: client.available_versions(),
+            chosen_version: Some(negotiated.as_ref().map_or(original_version, |n| n.version)),
+        });
+
+        let negotiated = negotiated?;
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-2.3>
+        //
+        // The server responds with Initial packets of the negotiated version, which are
+        // protected with the keys of that version.
+        let (key, header_key) =
+            <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_server(
+                negotiated.version,
+                negotiated.initial_destination_connection_id.as_bytes(),
+            );
+        initial.on_version_negotiated(original_version, key, header_key);
+
+        Some(negotiated.version)
+    }
+
+    /// Called on clients when the server responds with Initial packets of another version
+    ///
+    /// Returns `true` if the server selected the compatible version offered by the client, in
+    /// which case the connection switches to it.
+    pub fn on_server_version<Pub: event::EndpointPublisher>(
+        &mut self,
+        version: u32,
+        publisher: &mut Pub,
+    ) -> bool {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        let (initial, session_info) = match (self.initial.as_deref_mut(), &self.session_info) {
+            (Some(initial), Some(session_info)) => (initial, session_info),
+            _ => return false,
+        };
+
+        // The version can't change once the ServerHello was processed with the original keys
+        if initial.received_hello_message || !self.version_negotiation.on_server_version(version) {
+            return false;
+        }
+
+        publisher.on_version_information(event::builder::VersionInformation {
+            server_versions: &[],
+            client_versions: &[version, self.version_negotiation.original_version()],
+            chosen_version: Some(version),
+        });
+
+        let destination_connection_id = self
+            .retry_cid
+            .as_deref()
+            .map_or(session_info.initial_cid.as_bytes(), PeerId::as_bytes);
+        let (key, header_key) =
+            <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_client(
+                version,
+                destination_connection_id,
+            );
+        initial.key = key;
+        initial.header_key = header_key;
+
+        true
+    }
 }
 
 impl<Config: endpoint::Config> timer::Provider for PacketSpaceManager<Config> {
@@ -454,6 +646,15 @@ impl<Config: endpoint::Config> timer::Provider for PacketSpaceManager<Config> {
         if let Some(space) = self.application.as_ref() {
             space.timers(query)?;
         }
+        if let Some(space) = self.zero_rtt.as_ref() {
+            space.timers(query)?;
+        }
+        if let Some(crypto) = self.zero_rtt_crypto.as_ref() {
+            crypto.timers(query)?;
+        }
+        if let Some(session_info) = self.session_info.as_ref() {
+            session_info.post_handshake_timer.timers(query)?;
+        }
         if let Some(space) = self.handshake.as_ref() {
             space.timers(query)?;
         }
@@ -484,6 +685,10 @@ impl<Config: endpoint::Config> transmission::interest::Provider for PacketSpaceM
             space.transmission_interest(query)?;
         }
 
+        if let Some(space) = self.zero_rtt.as_ref() {
+            space.transmission_interest(query)?;
+        }
+
         Ok(())
     }
 }
@@ -502,6 +707,11 @@ impl<Config: endpoint::Config> connection::finalization::Provider for PacketSpac
                     .iter()
                     .map(|space| space.finalization_status()),
             )
+            .chain(
+                self.zero_rtt
+                    .iter()
+                    .map(|space| space.finalization_status()),
+            )
             .sum()
     }
 }
@@ -550,7 +760,8 @@ pub trait PacketSpace<Config: endpoint::Config> {
         &mut self,
         frame: HandshakeDone,
         _datagram: &DatagramInfo,
-        _path: &mut Path<Config>,
+        _path_id: path::Id,
+        _path_manager: &mut path::Manager<Config>,
         _local_id_registry: &mut connection::LocalIdRegistry,
         _handshake_status: &mut HandshakeStatus,
         _publisher: &mut Pub,
@@ -837,7 +1048,8 @@ pub trait PacketSpace<Config: endpoint::Config> {
                     self.handle_handshake_done_frame(
                         frame,
                         datagram,
-                        &mut path_manager[path_id],
+                        path_id,
+                        path_manager,
                         local_id_registry,
                         handshake_status,
                         publisher,
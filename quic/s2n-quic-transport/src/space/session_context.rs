@@ -5,8 +5,9 @@ use crate::{
     connection::{self, limits::Limits},
     endpoint, path,
     space::{
-        keep_alive::KeepAlive, rx_packet_numbers::AckManager, ApplicationSpace, HandshakeSpace,
-        HandshakeStatus, InitialSpace,
+        datagram, keep_alive::KeepAlive, rx_packet_numbers::AckManager, ApplicationSpace,
+        HandshakeSpace, HandshakeStatus, InitialSpace, VersionNegotiation, ZeroRttCrypto,
+        ZeroRttSpace,
     },
     stream::AbstractStreamManager,
 };
@@ -27,8 +28,9 @@ use s2n_quic_core::{
     transport::{
         self,
         parameters::{
-            ActiveConnectionIdLimit, ClientTransportParameters, InitialFlowControlLimits,
-            InitialSourceConnectionId, ServerTransportParameters,
+            ActiveConnectionIdLimit, ClientTransportParameters, CustomTransportParameters,
+            InitialFlowControlLimits, InitialSourceConnectionId, MaxDatagramFrameSize,
+            MigrationSupport, ServerTransportParameters,
         },
     },
 };
@@ -41,14 +43,16 @@ pub struct SessionContext<'a, Config: endpoint::Config, Pub: event::ConnectionPu
     pub initial: &'a mut Option<Box<InitialSpace<Config>>>,
     pub handshake: &'a mut Option<Box<HandshakeSpace<Config>>>,
     pub application: &'a mut Option<Box<ApplicationSpace<Config>>>,
-    pub zero_rtt_crypto: &'a mut Option<
-        Box<<<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey>,
-    >,
+    pub zero_rtt_crypto: &'a mut Option<Box<ZeroRttCrypto<Config>>>,
+    pub zero_rtt: &'a mut Option<Box<ZeroRttSpace<Config>>>,
     pub handshake_status: &'a mut HandshakeStatus,
+    pub version_negotiation: &'a VersionNegotiation,
     pub local_id_registry: &'a mut connection::LocalIdRegistry,
     pub limits: &'a mut Limits,
     pub server_name: &'a mut Option<ServerName>,
     pub application_protocol: &'a mut Bytes,
+    pub handshake_info: &'a mut Option<tls::HandshakeInfo>,
+    pub peer_custom_transport_parameters: &'a mut CustomTransportParameters,
     pub waker: &'a Waker,
     pub publisher: &'a mut Pub,
 }
@@ -60,7 +64,14 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
     fn on_server_params(
         &mut self,
         decoder: DecoderBuffer,
-    ) -> Result<(InitialFlowControlLimits, ActiveConnectionIdLimit), transport::Error> {
+    ) -> Result<
+        (
+            InitialFlowControlLimits,
+            ActiveConnectionIdLimit,
+            MaxDatagramFrameSize,
+        ),
+        transport::Error,
+    > {
         debug_assert!(Config::ENDPOINT_TYPE.is_client());
 
         let (peer_parameters, remaining) =
@@ -151,6 +162,11 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .with_reason("missing original_destination_connection_id"));
         }
 
+        self.version_negotiation.validate_server_information(
+            self.publisher.quic_version(),
+            peer_parameters.version_information.as_ref(),
+        )?;
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3
         //# Servers can also issue a stateless_reset_token transport parameter during the
         //# handshake that applies to the connection ID that it selected during
@@ -164,20 +180,55 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .register_initial_stateless_reset_token(stateless_reset_token);
         }
 
+        if peer_parameters.migration_support == MigrationSupport::Disabled {
+            self.path_manager.on_active_migration_disabled();
+        }
+
+        if let Some(preferred_address) = peer_parameters.preferred_address.as_ref() {
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
+            //# A server
+            //# that chooses a zero-length connection ID MUST NOT provide a
+            //# preferred address.
+            if self
+                .path_manager
+                .active_path()
+                .peer_connection_id
+                .is_empty()
+            {
+                return Err(transport::Error::TRANSPORT_PARAMETER_ERROR
+                    .with_reason("preferred_address provided with a zero-length connection ID"));
+            }
+
+            self.path_manager.on_preferred_address(preferred_address)?;
+        }
+
         // Load the peer's transport parameters into the connection's limits
         self.limits.load_peer(&peer_parameters);
 
         let initial_flow_control_limits = peer_parameters.flow_control_limits();
         let active_connection_id_limit = peer_parameters.active_connection_id_limit;
-
-        Ok((initial_flow_control_limits, active_connection_id_limit))
+        let max_datagram_frame_size = peer_parameters.max_datagram_frame_size;
+        *self.peer_custom_transport_parameters = peer_parameters.custom_parameters;
+
+        Ok((
+            initial_flow_control_limits,
+            active_connection_id_limit,
+            max_datagram_frame_size,
+        ))
     }
 
     // This is called by the server
     fn on_client_params(
         &mut self,
         decoder: DecoderBuffer,
-    ) -> Result<(InitialFlowControlLimits, ActiveConnectionIdLimit), transport::Error> {
+    ) -> Result<
+        (
+            InitialFlowControlLimits,
+            ActiveConnectionIdLimit,
+            MaxDatagramFrameSize,
+        ),
+        transport::Error,
+    > {
         debug_assert!(Config::ENDPOINT_TYPE.is_server());
 
         let (peer_parameters, remaining) =
@@ -208,13 +259,22 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .as_bytes(),
         )?;
 
+        self.version_negotiation
+            .validate_client_information(peer_parameters.version_information.as_ref())?;
+
         // Load the peer's transport parameters into the connection's limits
         self.limits.load_peer(&peer_parameters);
 
         let initial_flow_control_limits = peer_parameters.flow_control_limits();
         let active_connection_id_limit = peer_parameters.active_connection_id_limit;
-
-        Ok((initial_flow_control_limits, active_connection_id_limit))
+        let max_datagram_frame_size = peer_parameters.max_datagram_frame_size;
+        *self.peer_custom_transport_parameters = peer_parameters.custom_parameters;
+
+        Ok((
+            initial_flow_control_limits,
+            active_connection_id_limit,
+            max_datagram_frame_size,
+        ))
     }
 
     //= https://www.rfc-editor.org/rfc/rfc9000#section-7.3
@@ -300,18 +360,47 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
     fn on_zero_rtt_keys(
         &mut self,
         key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey,
-        _header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
-        _application_parameters: tls::ApplicationParameters,
+        header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
+        application_parameters: tls::ApplicationParameters,
     ) -> Result<(), transport::Error> {
-        if self.zero_rtt_crypto.is_some() {
+        if self.zero_rtt_crypto.is_some() || self.zero_rtt.is_some() {
             return Err(transport::Error::INTERNAL_ERROR
                 .with_reason("zero rtt keys initialized more than once"));
         }
 
         let cipher_suite = key.cipher_suite().into_event();
 
-        // TODO: also store the header_key https://github.com/aws/s2n-quic/issues/319
-        *self.zero_rtt_crypto = Some(Box::new(key));
+        match Config::ENDPOINT_TYPE {
+            endpoint::Type::Client => {
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+                //# Remembered transport parameters apply to the new
+                //# connection until the handshake completes and the client starts
+                //# sending 1-RTT packets.
+                let param_decoder = DecoderBuffer::new(application_parameters.transport_parameters);
+                let (remembered_parameters, _remaining) =
+                    ServerTransportParameters::decode(param_decoder).map_err(|_| {
+                        transport::Error::TRANSPORT_PARAMETER_ERROR
+                            .with_reason("Invalid remembered transport parameters")
+                    })?;
+
+                let stream_manager = AbstractStreamManager::new(
+                    self.limits,
+                    Config::ENDPOINT_TYPE,
+                    self.limits.initial_flow_control_limits(),
+                    remembered_parameters.flow_control_limits(),
+                );
+
+                *self.zero_rtt = Some(Box::new(ZeroRttSpace::new(
+                    key,
+                    header_key,
+                    self.now,
+                    stream_manager,
+                )));
+            }
+            endpoint::Type::Server => {
+                *self.zero_rtt_crypto = Some(Box::new(ZeroRttCrypto::new(key, header_key)));
+            }
+        }
 
         self.publisher.on_key_update(event::builder::KeyUpdate {
             key_type: event::builder::KeyType::ZeroRtt,
@@ -331,29 +420,47 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .with_reason("application keys initialized more than once"));
         }
 
-        if Config::ENDPOINT_TYPE.is_client() {
-            //= https://www.rfc-editor.org/rfc/rfc9001#section-4.9.3
-            //# Therefore, a client SHOULD discard 0-RTT keys as soon as it installs
-            //# 1-RTT keys as they have no use after that moment.
-
-            *self.zero_rtt_crypto = None;
-        }
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-4.9.3
+        //# Therefore, a client SHOULD discard 0-RTT keys as soon as it installs
+        //# 1-RTT keys as they have no use after that moment.
+        let zero_rtt = self.zero_rtt.take();
 
         // Parse transport parameters
         let param_decoder = DecoderBuffer::new(application_parameters.transport_parameters);
-        let (peer_flow_control_limits, active_connection_id_limit) = match Config::ENDPOINT_TYPE {
-            endpoint::Type::Client => self.on_server_params(param_decoder)?,
-            endpoint::Type::Server => self.on_client_params(param_decoder)?,
-        };
+        let (peer_flow_control_limits, active_connection_id_limit, peer_max_datagram_frame_size) =
+            match Config::ENDPOINT_TYPE {
+                endpoint::Type::Client => self.on_server_params(param_decoder)?,
+                endpoint::Type::Server => self.on_client_params(param_decoder)?,
+            };
 
         self.local_id_registry
             .set_active_connection_id_limit(active_connection_id_limit.as_u64());
 
-        let stream_manager = AbstractStreamManager::new(
+        // Streams opened while sending early data are continued in the application space
+        let (stream_manager, zero_rtt) = if let Some(zero_rtt) = zero_rtt {
+            let is_rejected = zero_rtt.is_rejected();
+            let (tx_packet_numbers, mut stream_manager, recovery_manager) = zero_rtt.into_parts();
+            if is_rejected {
+                // Nothing sent in 0-RTT was processed, so the streams start over with
+                // the limits of this connection, which may be lower
+                stream_manager.on_zero_rtt_rejected(peer_flow_control_limits);
+            } else {
+                stream_manager.on_peer_limits(peer_flow_control_limits)?;
+            }
+            (stream_manager, Some((tx_packet_numbers, recovery_manager)))
+        } else {
+            let stream_manager = AbstractStreamManager::new(
+                self.limits,
+                Config::ENDPOINT_TYPE,
+                self.limits.initial_flow_control_limits(),
+                peer_flow_control_limits,
+            );
+            (stream_manager, None)
+        };
+
+        let datagram_manager = datagram::Manager::new(
             self.limits,
-            Config::ENDPOINT_TYPE,
-            self.limits.initial_flow_control_limits(),
-            peer_flow_control_limits,
+            peer_max_datagram_frame_size.as_varint().as_u64(),
         );
 
         let ack_manager = AckManager::new(
@@ -368,15 +475,21 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
 
         let cipher_suite = key.cipher_suite().into_event();
         let max_mtu = self.path_manager.max_mtu();
-        *self.application = Some(Box::new(ApplicationSpace::new(
+        let mut application = Box::new(ApplicationSpace::new(
             key,
             header_key,
             self.now,
             stream_manager,
+            datagram_manager,
             ack_manager,
             keep_alive,
             max_mtu,
-        )));
+            self.limits,
+        ));
+        if let Some((tx_packet_numbers, recovery_manager)) = zero_rtt {
+            application.on_zero_rtt_space(tx_packet_numbers, recovery_manager);
+        }
+        *self.application = Some(application);
         self.publisher.on_key_update(event::builder::KeyUpdate {
             key_type: event::builder::KeyType::OneRtt { generation: 0 },
             cipher_suite,
@@ -385,6 +498,16 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
         Ok(())
     }
 
+    fn on_zero_rtt_rejected(&mut self) -> Result<(), transport::Error> {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        if let Some(space) = self.zero_rtt.as_mut() {
+            space.on_rejected(self.handshake_status, self.path_manager, self.publisher);
+        }
+
+        Ok(())
+    }
+
     fn on_server_name(&mut self, server_name: ServerName) -> Result<(), transport::Error> {
         self.publisher
             .on_server_name_information(event::builder::ServerNameInformation {
@@ -409,6 +532,12 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
         Ok(())
     }
 
+    fn on_handshake_info(&mut self, info: tls::HandshakeInfo) -> Result<(), transport::Error> {
+        *self.handshake_info = Some(info);
+
+        Ok(())
+    }
+
     fn on_handshake_complete(&mut self) -> Result<(), transport::Error> {
         // After the handshake is complete, the handshake crypto stream should be completely
         // finished
@@ -436,6 +565,21 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
         self.handshake_status
             .on_handshake_complete(Config::ENDPOINT_TYPE, self.publisher);
 
+        if let Some(crypto) = self.zero_rtt_crypto.as_mut() {
+            //= https://www.rfc-editor.org/rfc/rfc9001#section-4.9.3
+            //# After receiving a 1-RTT packet, servers MUST discard 0-RTT keys
+            //# within a short time; the RECOMMENDED time period is three times the
+            //# PTO.
+            //
+            // The keys are kept around so 0-RTT packets that were reordered behind the client's
+            // Finished message can still be processed.
+            let pto = self
+                .path_manager
+                .active_path()
+                .pto_period(PacketNumberSpace::ApplicationData);
+            crypto.discard_timer.set(self.now + 3 * pto);
+        }
+
         if let Some(application) = self.application.as_mut() {
             if Config::ENDPOINT_TYPE.is_server() {
                 // All of the other spaces are discarded by the time the handshake is complete so
@@ -481,9 +625,13 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             .map(|bytes| bytes.freeze())
     }
 
-    fn receive_application(&mut self, _max_len: Option<usize>) -> Option<Bytes> {
-        // Application doesn't currently have a buffer
-        None
+    fn receive_application(&mut self, max_len: Option<usize>) -> Option<Bytes> {
+        self.application
+            .as_deref_mut()?
+            .crypto_stream
+            .rx
+            .pop_watermarked(max_len.unwrap_or(usize::MAX))
+            .map(|bytes| bytes.freeze())
     }
 
     fn can_send_initial(&self) -> bool {
@@ -519,14 +667,26 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
     }
 
     fn can_send_application(&self) -> bool {
-        false
+        self.application
+            .as_ref()
+            .map(|space| space.crypto_stream.can_send())
+            .unwrap_or_default()
     }
 
-    fn send_application(&mut self, _transmission: Bytes) {
-        unimplemented!("application level crypto frames cannot currently be sent")
+    fn send_application(&mut self, transmission: Bytes) {
+        self.application
+            .as_mut()
+            .expect("can_send_application should be called before sending")
+            .crypto_stream
+            .tx
+            .push(transmission);
     }
 
     fn waker(&self) -> &Waker {
         self.waker
     }
+
+    fn quic_version(&self) -> u32 {
+        self.publisher.quic_version()
+    }
 }
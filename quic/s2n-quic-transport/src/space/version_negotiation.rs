@@ -0,0 +1,329 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compatible version negotiation
+//!
+//! Endpoints exchange the versions they support in the `version_information` transport
+//! parameter, which allows the server to move a connection to a version preferred by the
+//! client without an additional round trip.
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc9368>.
+
+use s2n_quic_core::{
+    connection::LocalId,
+    transport::{
+        self,
+        parameters::{ServerTransportParameters, VersionInformation},
+    },
+};
+
+#[derive(Debug)]
+pub struct VersionNegotiation {
+    /// The version of the first Initial packet sent by the client
+    original_version: u32,
+    /// The version a client offered to upgrade to, until the server responds with it
+    compatible_version: Option<u32>,
+    /// The state a server retains until the ClientHello is received
+    server: Option<Box<ServerState>>,
+}
+
+#[derive(Debug)]
+struct ServerState {
+    /// The Destination Connection ID that the client's Initial keys are derived from
+    initial_destination_connection_id: LocalId,
+    /// The transport parameters, which advertise the negotiated version
+    parameters: ServerTransportParameters,
+}
+
+impl VersionNegotiation {
+    /// Creates the negotiation state for a client starting the connection with `original_version`
+    pub fn client(original_version: u32, compatible_version: Option<u32>) -> Self {
+        Self {
+            original_version,
+            compatible_version: compatible_version.filter(|v| *v != original_version),
+            server: None,
+        }
+    }
+
+    /// Creates the negotiation state for a server accepting a connection with `original_version`
+    pub fn server(
+        original_version: u32,
+        initial_destination_connection_id: LocalId,
+        parameters: ServerTransportParameters,
+    ) -> Self {
+        Self {
+            original_version,
+            compatible_version: None,
+            server: Some(Box::new(ServerState {
+                initial_destination_connection_id,
+                parameters,
+            })),
+        }
+    }
+
+    /// Returns the version of the first Initial packet sent by the client
+    pub fn original_version(&self) -> u32 {
+        self.original_version
+    }
+
+    /// Returns the version information a client includes in its transport parameters
+    ///
+    /// The available versions are listed in order of preference, so an offered compatible
+    /// version comes first.
+    pub fn client_information(&self) -> Option<VersionInformation> {
+        match self.compatible_version {
+            Some(compatible_version) => VersionInformation::new(
+                self.original_version,
+                &[compatible_version, self.original_version],
+            ),
+            None => VersionInformation::new(self.original_version, &[self.original_version]),
+        }
+    }
+
+    /// Returns the version information a server includes in its transport parameters
+    pub fn server_information(
+        chosen_version: u32,
+        supported_versions: &[u32],
+    ) -> Option<VersionInformation> {
+        VersionInformation::new(chosen_version, supported_versions)
+    }
+
+    /// Called on clients when the server responds with a version other than the original one
+    ///
+    /// Returns `true` if the client offered the version and the connection should switch to it.
+    pub fn on_server_version(&mut self, version: u32) -> bool {
+        if self.compatible_version == Some(version) {
+            self.compatible_version = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Called on servers once the ClientHello is received
+    ///
+    /// Returns the version selected from the client's version information, out of the
+    /// `supported_versions` of the server. Servers only negotiate a version once.
+    pub fn on_client_hello(
+        &mut self,
+        client: &VersionInformation,
+        supported_versions: &[u32],
+    ) -> Option<Negotiated> {
+        let mut server = self.server.take()?;
+
+        // The client's version information is validated with the rest of its transport
+        // parameters, after which the connection is closed if it's inconsistent.
+        if client.chosen_version() != self.original_version
+            || !client.available_versions().contains(&self.original_version)
+        {
+            return None;
+        }
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-2.3>
+        //
+        // Clients list their available versions in order of preference. Every version supported
+        // by the endpoint is compatible with every other one, so the first supported version
+        // is selected.
+        let version = client
+            .available_versions()
+            .iter()
+            .copied()
+            .find(|v| supported_versions.contains(v))?;
+
+        server.parameters.version_information =
+            Self::server_information(version, supported_versions);
+
+        Some(Negotiated {
+            version,
+            initial_destination_connection_id: server.initial_destination_connection_id,
+            parameters: server.parameters,
+        })
+    }
+
+    /// Called on servers when the transport parameters of the client are received
+    pub fn validate_client_information(
+        &self,
+        client: Option<&VersionInformation>,
+    ) -> Result<(), transport::Error> {
+        let client = if let Some(client) = client {
+            client
+        } else {
+            return Ok(());
+        };
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-4>
+        //
+        // The client's chosen version has to match the version of its first Initial packet,
+        // otherwise an attacker may have modified the packet.
+        if client.chosen_version() != self.original_version {
+            return Err(transport::Error::VERSION_NEGOTIATION_ERROR
+                .with_reason("client chosen version mismatch"));
+        }
+
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-3>
+        //
+        // A chosen version that isn't one of the available versions is a parsing failure.
+        if !client
+            .available_versions()
+            .contains(&client.chosen_version())
+        {
+            return Err(transport::Error::TRANSPORT_PARAMETER_ERROR
+                .with_reason("client chosen version is not available"));
+        }
+
+        Ok(())
+    }
+
+    /// Called on clients when the transport parameters of the server are received
+    pub fn validate_server_information(
+        &self,
+        negotiated_version: u32,
+        server: Option<&VersionInformation>,
+    ) -> Result<(), transport::Error> {
+        // See <https://www.rfc-editor.org/rfc/rfc9368#section-4>
+        //
+        // The server's version information is authenticated by the handshake, which prevents
+        // an attacker from downgrading the connection.
+        match server {
+            Some(server) if server.chosen_version() != negotiated_version => {
+                Err(transport::Error::VERSION_NEGOTIATION_ERROR
+                    .with_reason("server chosen version mismatch"))
+            }
+            None if negotiated_version != self.original_version => {
+                Err(transport::Error::VERSION_NEGOTIATION_ERROR
+                    .with_reason("missing server version information"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The result of compatible version negotiation on a server
+#[derive(Debug)]
+pub struct Negotiated {
+    pub version: u32,
+    /// The Destination Connection ID that the Initial keys of the version are derived from
+    pub initial_destination_connection_id: LocalId,
+    /// The transport parameters to send to the client, which advertise the version
+    pub parameters: ServerTransportParameters,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::version::SUPPORTED_VERSIONS;
+    use s2n_quic_core::packet::long::{VERSION_1, VERSION_2};
+
+    fn info(chosen_version: u32, available_versions: &[u32]) -> VersionInformation {
+        VersionInformation::new(chosen_version, available_versions).unwrap()
+    }
+
+    #[test]
+    fn client_information_test() {
+        let negotiation = VersionNegotiation::client(VERSION_1, None);
+        assert_eq!(
+            negotiation.client_information(),
+            Some(info(VERSION_1, &[VERSION_1]))
+        );
+
+        let negotiation = VersionNegotiation::client(VERSION_1, Some(VERSION_1));
+        assert_eq!(
+            negotiation.client_information(),
+            Some(info(VERSION_1, &[VERSION_1]))
+        );
+
+        let negotiation = VersionNegotiation::client(VERSION_1, Some(VERSION_2));
+        assert_eq!(
+            negotiation.client_information(),
+            Some(info(VERSION_1, &[VERSION_2, VERSION_1]))
+        );
+    }
+
+    #[test]
+    fn client_upgrade_test() {
+        let mut negotiation = VersionNegotiation::client(VERSION_1, Some(VERSION_2));
+
+        assert!(!negotiation.on_server_version(0xabcd_0000));
+        assert!(negotiation.on_server_version(VERSION_2));
+        // the client only switches versions once
+        assert!(!negotiation.on_server_version(VERSION_2));
+
+        assert!(negotiation
+            .validate_server_information(VERSION_2, Some(&info(VERSION_2, SUPPORTED_VERSIONS)))
+            .is_ok());
+        assert!(negotiation
+            .validate_server_information(VERSION_2, Some(&info(VERSION_1, SUPPORTED_VERSIONS)))
+            .is_err());
+        assert!(negotiation
+            .validate_server_information(VERSION_2, None)
+            .is_err());
+        assert!(negotiation
+            .validate_server_information(VERSION_1, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn server_selection_test() {
+        let id = LocalId::try_from_bytes(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let server = || VersionNegotiation::server(VERSION_1, id, Default::default());
+
+        let mut negotiation = server();
+        let negotiated = negotiation
+            .on_client_hello(
+                &info(VERSION_1, &[VERSION_2, VERSION_1]),
+                SUPPORTED_VERSIONS,
+            )
+            .unwrap();
+        assert_eq!(negotiated.version, VERSION_2);
+        assert_eq!(negotiated.initial_destination_connection_id, id);
+        assert_eq!(
+            negotiated.parameters.version_information,
+            Some(info(VERSION_2, SUPPORTED_VERSIONS))
+        );
+        // the version is only negotiated once
+        assert!(negotiation
+            .on_client_hello(
+                &info(VERSION_1, &[VERSION_2, VERSION_1]),
+                SUPPORTED_VERSIONS
+            )
+            .is_none());
+
+        // unsupported versions are skipped
+        let negotiated = server()
+            .on_client_hello(
+                &info(VERSION_1, &[0xabcd_0000, VERSION_1, VERSION_2]),
+                SUPPORTED_VERSIONS,
+            )
+            .unwrap();
+        assert_eq!(negotiated.version, VERSION_1);
+
+        // versions the TLS provider doesn't support are skipped
+        let negotiated = server()
+            .on_client_hello(&info(VERSION_1, &[VERSION_2, VERSION_1]), &[VERSION_1])
+            .unwrap();
+        assert_eq!(negotiated.version, VERSION_1);
+        assert_eq!(
+            negotiated.parameters.version_information,
+            Some(info(VERSION_1, &[VERSION_1]))
+        );
+
+        // inconsistent version information isn't negotiated
+        let mut negotiation = server();
+        assert!(negotiation
+            .on_client_hello(
+                &info(VERSION_2, &[VERSION_2, VERSION_1]),
+                SUPPORTED_VERSIONS
+            )
+            .is_none());
+        assert!(negotiation
+            .validate_client_information(Some(&info(VERSION_2, &[VERSION_2, VERSION_1])))
+            .is_err());
+        assert!(negotiation
+            .validate_client_information(Some(&info(VERSION_1, &[VERSION_2])))
+            .is_err());
+        assert!(negotiation
+            .validate_client_information(Some(&info(VERSION_1, &[VERSION_2, VERSION_1])))
+            .is_ok());
+        assert!(negotiation.validate_client_information(None).is_ok());
+    }
+}
@@ -0,0 +1,491 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    connection::{self, ConnectionTransmissionContext},
+    endpoint, path,
+    path::Path,
+    processed_packet::ProcessedPacket,
+    recovery,
+    space::{ApplicationSpace, HandshakeStatus, PacketSpace, TxPacketNumbers},
+    stream::AbstractStreamManager,
+    transmission,
+};
+use core::{fmt, marker::PhantomData};
+use s2n_codec::EncoderBuffer;
+use s2n_quic_core::{
+    crypto::{tls, CryptoSuite},
+    event::{self, ConnectionPublisher as _},
+    frame::{
+        ack::AckRanges, crypto::CryptoRef, datagram::DatagramRef, stream::StreamRef, Ack,
+        ConnectionClose, DataBlocked, MaxData, MaxStreamData, MaxStreams, NewConnectionId,
+        PathChallenge, ResetStream, StopSending, StreamDataBlocked, StreamsBlocked,
+    },
+    inet::DatagramInfo,
+    packet::{
+        encoding::{PacketEncoder, PacketEncodingError},
+        number::{PacketNumber, PacketNumberRange, PacketNumberSpace},
+        zero_rtt::ZeroRtt,
+    },
+    time::{timer, Timestamp},
+    transport,
+};
+
+/// The 0-RTT keys installed by a server that accepted early data
+pub struct ZeroRttCrypto<Config: endpoint::Config> {
+    pub key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey,
+    pub header_key:
+        <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
+    /// Armed once the handshake completes to discard the keys after 0-RTT packets that were
+    /// reordered behind 1-RTT packets have had a chance to arrive
+    pub discard_timer: timer::Timer,
+}
+
+impl<Config: endpoint::Config> ZeroRttCrypto<Config> {
+    pub fn new(
+        key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey,
+        header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
+    ) -> Self {
+        Self {
+            key,
+            header_key,
+            discard_timer: Default::default(),
+        }
+    }
+}
+
+impl<Config: endpoint::Config> timer::Provider for ZeroRttCrypto<Config> {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.discard_timer.timers(query)
+    }
+}
+
+/// Holds the state for a client sending 0-RTT data before the handshake completes
+///
+/// 0-RTT packets share the application data packet number space. Once the 1-RTT keys are
+/// available, the packet numbers, streams and in-flight packets are moved into the
+/// [`ApplicationSpace`](super::ApplicationSpace) where they are acknowledged.
+pub struct ZeroRttSpace<Config: endpoint::Config> {
+    pub tx_packet_numbers: TxPacketNumbers,
+    /// All streams that were opened while sending 0-RTT data
+    pub stream_manager: AbstractStreamManager<Config::Stream>,
+    key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey,
+    header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
+    recovery_manager: recovery::Manager<Config>,
+    /// Set when the server rejected the 0-RTT packets
+    is_rejected: bool,
+}
+
+impl<Config: endpoint::Config> fmt::Debug for ZeroRttSpace<Config> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZeroRttSpace")
+            .field("recovery_manager", &self.recovery_manager)
+            .field("stream_manager", &self.stream_manager)
+            .field("tx_packet_numbers", &self.tx_packet_numbers)
+            .field("is_rejected", &self.is_rejected)
+            .finish()
+    }
+}
+
+impl<Config: endpoint::Config> ZeroRttSpace<Config> {
+    pub fn new(
+        key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttKey,
+        header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::ZeroRttHeaderKey,
+        now: Timestamp,
+        stream_manager: AbstractStreamManager<Config::Stream>,
+    ) -> Self {
+        debug_assert!(
+            Config::ENDPOINT_TYPE.is_client(),
+            "only a Client sends 0-RTT packets"
+        );
+
+        Self {
+            tx_packet_numbers: TxPacketNumbers::new(PacketNumberSpace::ApplicationData, now),
+            stream_manager,
+            key,
+            header_key,
+            recovery_manager: recovery::Manager::new(PacketNumberSpace::ApplicationData),
+            is_rejected: false,
+        }
+    }
+
+    pub fn on_transmit<'a>(
+        &mut self,
+        context: &mut ConnectionTransmissionContext<Config>,
+        transmission_constraint: transmission::Constraint,
+        handshake_status: &HandshakeStatus,
+        buffer: EncoderBuffer<'a>,
+    ) -> Result<(transmission::Outcome, EncoderBuffer<'a>), PacketEncodingError<'a>> {
+        let packet_number = self.tx_packet_numbers.next();
+        let packet_number_encoder = self.packet_number_encoder();
+        let mut outcome = transmission::Outcome::default();
+
+        let bytes_progressed = self.stream_manager.outgoing_bytes_progressed();
+        let destination_connection_id = context.path().peer_connection_id;
+        let payload = transmission::Transmission {
+            config: <PhantomData<Config>>::default(),
+            outcome: &mut outcome,
+            packet_number,
+            payload: transmission::zero_rtt::Payload {
+                stream_manager: &mut self.stream_manager,
+                recovery_manager: &mut self.recovery_manager,
+            },
+            timestamp: context.timestamp,
+            transmission_constraint,
+            transmission_mode: context.transmission_mode,
+            tx_packet_numbers: &mut self.tx_packet_numbers,
+            path_id: context.path_id,
+            publisher: context.publisher,
+            packet_interceptor: context.packet_interceptor,
+        };
+
+        let packet = ZeroRtt {
+            version: context.quic_version,
+            destination_connection_id,
+            source_connection_id: context.path_manager[context.path_id].local_connection_id,
+            packet_number,
+            payload,
+        };
+
+        let (_protected_packet, buffer) = packet.encode_packet(
+            &self.key,
+            &self.header_key,
+            packet_number_encoder,
+            context.min_packet_len,
+            buffer,
+        )?;
+
+        outcome.bytes_progressed +=
+            (self.stream_manager.outgoing_bytes_progressed() - bytes_progressed).as_u64() as usize;
+
+        let time_sent = context.timestamp;
+        let path_id = context.path_id;
+        let (recovery_manager, mut recovery_context) =
+            self.recovery(handshake_status, path_id, context.path_manager);
+        recovery_manager.on_packet_sent(
+            packet_number,
+            outcome,
+            time_sent,
+            context.ecn,
+            &mut recovery_context,
+            context.publisher,
+        );
+
+        context
+            .publisher
+            .on_packet_sent(event::builder::PacketSent {
+                packet_header: event::builder::PacketHeader::ZeroRtt {
+                    number: packet_number.as_u64(),
+                    version: context.publisher.quic_version(),
+                },
+            });
+
+        Ok((outcome, buffer))
+    }
+
+    /// Called when the connection timer expired
+    pub fn on_timeout(&mut self, timestamp: Timestamp) {
+        self.stream_manager.on_timeout(timestamp);
+    }
+
+    /// Called when the server rejected the 0-RTT packets
+    ///
+    /// All of the data sent in 0-RTT packets is queued for retransmission in 1-RTT packets.
+    pub fn on_rejected<Pub: event::ConnectionPublisher>(
+        &mut self,
+        handshake_status: &HandshakeStatus,
+        path_manager: &mut path::Manager<Config>,
+        publisher: &mut Pub,
+    ) {
+        let path_id = path_manager.active_path_id();
+        let (recovery_manager, mut context) =
+            self.recovery(handshake_status, path_id, path_manager);
+        recovery_manager.on_zero_rtt_rejected(&mut context, publisher);
+        self.is_rejected = true;
+    }
+
+    /// Returns `true` if the server rejected the 0-RTT packets
+    pub fn is_rejected(&self) -> bool {
+        self.is_rejected
+    }
+
+    /// Splits the space into the components that are continued by the application space
+    pub fn into_parts(
+        self,
+    ) -> (
+        TxPacketNumbers,
+        AbstractStreamManager<Config::Stream>,
+        recovery::Manager<Config>,
+    ) {
+        (
+            self.tx_packet_numbers,
+            self.stream_manager,
+            self.recovery_manager,
+        )
+    }
+
+    /// Returns the Packet Number to be used when encoding outgoing packets
+    fn packet_number_encoder(&self) -> PacketNumber {
+        self.tx_packet_numbers.largest_sent_packet_number_acked()
+    }
+
+    fn recovery<'a>(
+        &'a mut self,
+        handshake_status: &'a HandshakeStatus,
+        path_id: path::Id,
+        path_manager: &'a mut path::Manager<Config>,
+    ) -> (
+        &'a mut recovery::Manager<Config>,
+        RecoveryContext<'a, Config>,
+    ) {
+        (
+            &mut self.recovery_manager,
+            RecoveryContext {
+                stream_manager: &mut self.stream_manager,
+                tx_packet_numbers: &mut self.tx_packet_numbers,
+                handshake_status,
+                path_id,
+                path_manager,
+            },
+        )
+    }
+}
+
+impl<Config: endpoint::Config> timer::Provider for ZeroRttSpace<Config> {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.recovery_manager.timers(query)?;
+        self.stream_manager.timers(query)?;
+
+        Ok(())
+    }
+}
+
+impl<Config: endpoint::Config> transmission::interest::Provider for ZeroRttSpace<Config> {
+    #[inline]
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        self.recovery_manager.transmission_interest(query)?;
+        self.stream_manager.transmission_interest(query)?;
+        Ok(())
+    }
+}
+
+impl<Config: endpoint::Config> connection::finalization::Provider for ZeroRttSpace<Config> {
+    fn finalization_status(&self) -> connection::finalization::Status {
+        self.stream_manager.finalization_status()
+    }
+}
+
+struct RecoveryContext<'a, Config: endpoint::Config> {
+    stream_manager: &'a mut AbstractStreamManager<Config::Stream>,
+    tx_packet_numbers: &'a mut TxPacketNumbers,
+    handshake_status: &'a HandshakeStatus,
+    path_id: path::Id,
+    path_manager: &'a mut path::Manager<Config>,
+}
+
+impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext<'a, Config> {
+    const ENDPOINT_TYPE: endpoint::Type = Config::ENDPOINT_TYPE;
+
+    fn is_handshake_confirmed(&self) -> bool {
+        self.handshake_status.is_confirmed()
+    }
+
+    fn path(&self) -> &Path<Config> {
+        &self.path_manager[self.path_id]
+    }
+
+    fn path_mut(&mut self) -> &mut Path<Config> {
+        &mut self.path_manager[self.path_id]
+    }
+
+    fn path_by_id(&self, path_id: path::Id) -> &path::Path<Config> {
+        &self.path_manager[path_id]
+    }
+
+    fn path_mut_by_id(&mut self, path_id: path::Id) -> &mut path::Path<Config> {
+        &mut self.path_manager[path_id]
+    }
+
+    fn path_id(&self) -> path::Id {
+        self.path_id
+    }
+
+    fn validate_packet_ack(
+        &mut self,
+        datagram: &DatagramInfo,
+        packet_number_range: &PacketNumberRange,
+    ) -> Result<(), transport::Error> {
+        self.tx_packet_numbers
+            .on_packet_ack(datagram, packet_number_range)
+    }
+
+    fn on_new_packet_ack<Pub: event::ConnectionPublisher>(
+        &mut self,
+        packet_number_range: &PacketNumberRange,
+        _publisher: &mut Pub,
+    ) {
+        self.stream_manager.on_packet_ack(packet_number_range);
+    }
+
+    fn on_packet_ack(
+        &mut self,
+        _datagram: &DatagramInfo,
+        _packet_number_range: &PacketNumberRange,
+    ) {
+    }
+
+    fn on_packet_loss<Pub: event::ConnectionPublisher>(
+        &mut self,
+        packet_number_range: &PacketNumberRange,
+        _publisher: &mut Pub,
+    ) {
+        self.stream_manager.on_packet_loss(packet_number_range);
+    }
+
+    fn on_rtt_update(&mut self) {}
+}
+
+/// Processes the payload of 0-RTT packets received by the server
+///
+/// Frames are applied to the application space, except for those which are not permitted in
+/// 0-RTT packets.
+pub struct ZeroRttReceiver<'a, Config: endpoint::Config> {
+    pub space: &'a mut ApplicationSpace<Config>,
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-12.5
+//# Note that it is not possible to send the following frames in 0-RTT
+//# packets for various reasons: ACK, CRYPTO, HANDSHAKE_DONE, NEW_TOKEN,
+//# PATH_RESPONSE, and RETIRE_CONNECTION_ID.  A server MAY treat receipt
+//# of these frames in 0-RTT packets as a connection error of type
+//# PROTOCOL_VIOLATION.
+impl<'a, Config: endpoint::Config> PacketSpace<Config> for ZeroRttReceiver<'a, Config> {
+    const INVALID_FRAME_ERROR: &'static str = "invalid frame in zero rtt space";
+
+    fn handle_crypto_frame<Pub: event::ConnectionPublisher>(
+        &mut self,
+        frame: CryptoRef,
+        _datagram: &DatagramInfo,
+        _path: &mut Path<Config>,
+        _publisher: &mut Pub,
+    ) -> Result<(), transport::Error> {
+        Err(transport::Error::PROTOCOL_VIOLATION
+            .with_reason(Self::INVALID_FRAME_ERROR)
+            .with_frame_type(frame.tag().into()))
+    }
+
+    fn handle_ack_frame<A: AckRanges, Pub: event::ConnectionPublisher>(
+        &mut self,
+        frame: Ack<A>,
+        _datagram: &DatagramInfo,
+        _path_id: path::Id,
+        _path_manager: &mut path::Manager<Config>,
+        _handshake_status: &mut HandshakeStatus,
+        _local_id_registry: &mut connection::LocalIdRegistry,
+        _publisher: &mut Pub,
+    ) -> Result<(), transport::Error> {
+        Err(transport::Error::PROTOCOL_VIOLATION
+            .with_reason(Self::INVALID_FRAME_ERROR)
+            .with_frame_type(frame.tag().into()))
+    }
+
+    fn handle_connection_close_frame(
+        &mut self,
+        frame: ConnectionClose,
+        datagram: &DatagramInfo,
+        path: &mut Path<Config>,
+    ) -> Result<(), transport::Error> {
+        self.space
+            .handle_connection_close_frame(frame, datagram, path)
+    }
+
+    fn handle_new_connection_id_frame<Pub: event::ConnectionPublisher>(
+        &mut self,
+        frame: NewConnectionId,
+        datagram: &DatagramInfo,
+        path_manager: &mut path::Manager<Config>,
+        publisher: &mut Pub,
+    ) -> Result<(), transport::Error> {
+        self.space
+            .handle_new_connection_id_frame(frame, datagram, path_manager, publisher)
+    }
+
+    fn handle_path_challenge_frame(
+        &mut self,
+        frame: PathChallenge,
+        path_id: path::Id,
+        path_manager: &mut path::Manager<Config>,
+    ) -> Result<(), transport::Error> {
+        self.space
+            .handle_path_challenge_frame(frame, path_id, path_manager)
+    }
+
+    fn handle_stream_frame(
+        &mut self,
+        frame: StreamRef,
+        packet: &mut ProcessedPacket,
+    ) -> Result<(), transport::Error> {
+        self.space.handle_stream_frame(frame, packet)
+    }
+
+    fn handle_datagram_frame(
+        &mut self,
+        frame: DatagramRef,
+        packet: &mut ProcessedPacket,
+    ) -> Result<(), transport::Error> {
+        self.space.handle_datagram_frame(frame, packet)
+    }
+
+    fn handle_data_blocked_frame(&mut self, frame: DataBlocked) -> Result<(), transport::Error> {
+        self.space.handle_data_blocked_frame(frame)
+    }
+
+    fn handle_max_data_frame(&mut self, frame: MaxData) -> Result<(), transport::Error> {
+        self.space.handle_max_data_frame(frame)
+    }
+
+    fn handle_max_stream_data_frame(
+        &mut self,
+        frame: MaxStreamData,
+    ) -> Result<(), transport::Error> {
+        self.space.handle_max_stream_data_frame(frame)
+    }
+
+    fn handle_max_streams_frame(&mut self, frame: MaxStreams) -> Result<(), transport::Error> {
+        self.space.handle_max_streams_frame(frame)
+    }
+
+    fn handle_reset_stream_frame(&mut self, frame: ResetStream) -> Result<(), transport::Error> {
+        self.space.handle_reset_stream_frame(frame)
+    }
+
+    fn handle_stop_sending_frame(&mut self, frame: StopSending) -> Result<(), transport::Error> {
+        self.space.handle_stop_sending_frame(frame)
+    }
+
+    fn handle_stream_data_blocked_frame(
+        &mut self,
+        frame: StreamDataBlocked,
+    ) -> Result<(), transport::Error> {
+        self.space.handle_stream_data_blocked_frame(frame)
+    }
+
+    fn handle_streams_blocked_frame(
+        &mut self,
+        frame: StreamsBlocked,
+    ) -> Result<(), transport::Error> {
+        self.space.handle_streams_blocked_frame(frame)
+    }
+
+    fn on_processed_packet(
+        &mut self,
+        processed_packet: ProcessedPacket,
+    ) -> Result<(), transport::Error> {
+        self.space.on_processed_packet(processed_packet)
+    }
+}
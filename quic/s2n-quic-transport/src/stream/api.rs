@@ -13,7 +13,7 @@ use core::{
 };
 pub use s2n_quic_core::{
     application,
-    stream::{ops, StreamError, StreamId, StreamType},
+    stream::{ops, Priority, StreamError, StreamId, StreamType},
 };
 
 #[derive(Clone)]
@@ -222,6 +222,14 @@ macro_rules! tx_stream_apis {
             self.tx_request()?.reset(error_code).poll(None)?;
             Ok(())
         }
+
+        /// Sets the priority used to schedule the transmission of the stream
+        ///
+        /// The priority may be changed at any time while the stream is sending data.
+        pub fn set_priority(&mut self, priority: Priority) -> Result<(), StreamError> {
+            self.tx_request()?.set_priority(priority).poll(None)?;
+            Ok(())
+        }
     };
 }
 
@@ -492,6 +500,11 @@ macro_rules! tx_request_apis {
             self.request.flush();
             self
         }
+
+        pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+            self.request.set_priority(priority);
+            self
+        }
     };
 }
 
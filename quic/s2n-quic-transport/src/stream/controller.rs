@@ -95,6 +95,26 @@ impl Controller {
         }
     }
 
+    /// This method is called on the client when the server rejected 0-RTT data
+    ///
+    /// The limits of the new connection replace the remembered limits, even if they are lower.
+    pub fn on_zero_rtt_rejected(&mut self, limits: &InitialFlowControlLimits) {
+        self.bidi_controller
+            .outgoing
+            .reset_peer_limit(limits.max_streams_bidi);
+        self.uni_controller
+            .outgoing
+            .reset_peer_limit(limits.max_streams_uni);
+    }
+
+    /// Updates the number of streams of the given type the peer can have open concurrently
+    pub fn update_remote_stream_limit(&mut self, stream_type: StreamType, limit: VarInt) {
+        match stream_type {
+            StreamType::Bidirectional => self.bidi_controller.incoming.update_stream_limit(limit),
+            StreamType::Unidirectional => self.uni_controller.incoming.update_stream_limit(limit),
+        }
+    }
+
     /// This method is called when the local application wishes to open a new stream.
     ///
     /// `Poll::Pending` is returned when there isn't available capacity to open a stream,
@@ -366,6 +386,13 @@ impl OutgoingController {
         self.wake_unblocked();
     }
 
+    fn reset_peer_limit(&mut self, maximum_streams: VarInt) {
+        self.peer_cumulative_stream_limit = maximum_streams;
+        self.streams_blocked_sync.reset();
+
+        self.wake_unblocked();
+    }
+
     fn poll_open_stream(
         &mut self,
         open_token: &mut open_token::Token,
@@ -629,13 +656,32 @@ impl IncomingController {
     fn on_close_stream(&mut self) {
         self.closed_streams += 1;
 
+        self.update_max_streams();
+
+        self.check_integrity();
+    }
+
+    /// Updates the number of streams the peer can have open concurrently
+    ///
+    /// The maximum number of streams sent to the peer can't decrease, so a lower limit only
+    /// takes effect once enough streams have been closed.
+    fn update_stream_limit(&mut self, limit: VarInt) {
+        self.peer_initiated_concurrent_stream_limit = limit;
+        self.max_streams_sync
+            .set_threshold(limit / MAX_STREAMS_SYNC_FRACTION);
+
+        self.update_max_streams();
+
+        self.check_integrity();
+    }
+
+    fn update_max_streams(&mut self) {
         let max_streams = self
             .closed_streams
             .saturating_add(self.peer_initiated_concurrent_stream_limit)
-            .min(MAX_STREAMS_MAX_VALUE);
+            .min(MAX_STREAMS_MAX_VALUE)
+            .max(self.max_streams_sync.latest_value());
         self.max_streams_sync.update_latest_value(max_streams);
-
-        self.check_integrity();
     }
 
     /// Returns the number of streams currently open
@@ -673,10 +719,11 @@ impl IncomingController {
                 self.closed_streams <= self.opened_streams,
                 "Cannot close more streams than previously opened"
             );
+            // the limit can be lowered below the number of open streams, but the peer can never
+            // open more streams than were allowed
             assert!(
-                self.open_stream_count() <= self.peer_initiated_concurrent_stream_limit,
-                "Cannot have more incoming streams open concurrently than
-                the peer_initiated_concurrent_stream_limit"
+                self.opened_streams <= self.max_streams_sync.latest_value(),
+                "Cannot have more incoming streams open than the max_streams value"
             );
         }
     }
@@ -3,13 +3,14 @@
 
 //! Manages the per-connection flow-control window
 
+use super::receive_window_tuner::ReceiveWindowTuner;
 use crate::{
     contexts::{OnTransmitError, WriteContext},
     sync::{IncrementalValueSync, ValueToFrameWriter},
     transmission,
 };
 use alloc::rc::Rc;
-use core::cell::RefCell;
+use core::{cell::RefCell, time::Duration};
 use s2n_quic_core::{
     ack, frame::max_data::MaxData, packet::number::PacketNumber, stream::StreamId, transport,
     varint::VarInt,
@@ -46,10 +47,18 @@ struct IncomingConnectionFlowControllerImpl {
     /// The amount of flow control credits which had been acquired and where the
     /// data had already been consumed by the application
     pub(super) consumed_window: VarInt,
+    /// Grows the desired flow control window if it limits the peer
+    tuner: ReceiveWindowTuner,
+    /// The smoothed RTT of the active path, once it has been sampled
+    smoothed_rtt: Option<Duration>,
 }
 
 impl IncomingConnectionFlowControllerImpl {
-    pub fn new(initial_window_size: VarInt, desired_flow_control_window: u32) -> Self {
+    pub fn new(
+        initial_window_size: VarInt,
+        desired_flow_control_window: u32,
+        max_flow_control_window: u32,
+    ) -> Self {
         Self {
             read_window_sync: IncrementalValueSync::new(
                 VarInt::from_u32(desired_flow_control_window),
@@ -59,6 +68,8 @@ impl IncomingConnectionFlowControllerImpl {
             desired_flow_control_window,
             acquired_window: VarInt::from_u32(0),
             consumed_window: VarInt::from_u32(0),
+            tuner: ReceiveWindowTuner::new(max_flow_control_window),
+            smoothed_rtt: None,
         }
     }
 
@@ -78,10 +89,36 @@ impl IncomingConnectionFlowControllerImpl {
             "Can not consume more window than previously acquired"
         );
 
-        self.read_window_sync.update_latest_value(
-            self.consumed_window
-                .saturating_add(VarInt::from_u32(self.desired_flow_control_window)),
-        );
+        self.update_read_window();
+    }
+
+    pub fn set_desired_flow_control_window(&mut self, desired_flow_control_window: u32) {
+        self.desired_flow_control_window = desired_flow_control_window;
+        self.read_window_sync
+            .set_threshold(VarInt::from_u32(desired_flow_control_window / 10));
+        self.update_read_window();
+    }
+
+    pub fn on_stream_window_update(&mut self, stream_window: u32) {
+        // Keep the connection window larger than the stream windows, so a single stream
+        // isn't limited by the connection window
+        let window = stream_window
+            .saturating_add(stream_window / 2)
+            .min(self.tuner.max_window());
+
+        if window > self.desired_flow_control_window {
+            self.set_desired_flow_control_window(window);
+        }
+    }
+
+    fn update_read_window(&mut self) {
+        // The window can be reduced, but credits which were already sent to the peer can't
+        // be taken back
+        let read_window = self
+            .consumed_window
+            .saturating_add(VarInt::from_u32(self.desired_flow_control_window))
+            .max(self.read_window_sync.latest_value());
+        self.read_window_sync.update_latest_value(read_window);
     }
 
     pub fn acquire_window(&mut self, desired: VarInt) -> Result<(), transport::Error> {
@@ -107,6 +144,16 @@ impl IncomingConnectionFlowControllerImpl {
 
     #[inline]
     pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) -> Result<(), OnTransmitError> {
+        let window = self.tuner.on_window_update(
+            self.desired_flow_control_window,
+            self.consumed_window,
+            context.current_time(),
+            self.smoothed_rtt,
+        );
+        if window > self.desired_flow_control_window {
+            self.set_desired_flow_control_window(window);
+        }
+
         // Stream ID does not matter here, since it does not get transmitted
         self.read_window_sync
             .on_transmit(StreamId::from_varint(VarInt::from_u32(0)), context)
@@ -134,11 +181,19 @@ impl IncomingConnectionFlowController {
     /// `desired_flow_control_window`. This means if the window which is indicated
     /// to the peer is lower than this value the new value will be communicated
     /// to the peer.
-    pub fn new(initial_window_size: VarInt, desired_flow_control_window: u32) -> Self {
+    ///
+    /// If the peer is limited by the window, it is grown up to
+    /// `max_flow_control_window`.
+    pub fn new(
+        initial_window_size: VarInt,
+        desired_flow_control_window: u32,
+        max_flow_control_window: u32,
+    ) -> Self {
         Self {
             inner: Rc::new(RefCell::new(IncomingConnectionFlowControllerImpl::new(
                 initial_window_size,
                 desired_flow_control_window,
+                max_flow_control_window,
             ))),
         }
     }
@@ -157,6 +212,32 @@ impl IncomingConnectionFlowController {
         self.inner.borrow_mut().release_window(amount)
     }
 
+    /// Changes the flow control window the controller tries to maintain
+    ///
+    /// Larger windows are sent to the peer in a MAX_DATA frame.
+    pub fn set_desired_flow_control_window(&mut self, desired_flow_control_window: u32) {
+        self.inner
+            .borrow_mut()
+            .set_desired_flow_control_window(desired_flow_control_window)
+    }
+
+    /// Grows the window along with the receive window of a Stream
+    pub fn on_stream_window_update(&mut self, stream_window: u32) {
+        self.inner
+            .borrow_mut()
+            .on_stream_window_update(stream_window)
+    }
+
+    /// This method gets called when the RTT estimate is updated for the active path
+    pub fn on_rtt_update(&mut self, smoothed_rtt: Duration) {
+        self.inner.borrow_mut().smoothed_rtt = Some(smoothed_rtt);
+    }
+
+    /// Returns the smoothed RTT of the active path, once it has been sampled
+    pub fn smoothed_rtt(&self) -> Option<Duration> {
+        self.inner.borrow().smoothed_rtt
+    }
+
     /// This method gets called when a packet delivery got acknowledged
     pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
         self.inner.borrow_mut().on_packet_ack(ack_set)
@@ -8,6 +8,7 @@ mod controller;
 mod incoming_connection_flow_controller;
 mod outgoing_connection_flow_controller;
 mod receive_stream;
+mod receive_window_tuner;
 mod send_stream;
 mod stream_container;
 mod stream_events;
@@ -82,6 +82,12 @@ impl OutgoingConnectionFlowControllerImpl {
         // We now have more capacity from the peer so stop sending DATA_BLOCKED frames
         self.data_blocked_sync.stop_sync();
     }
+
+    pub fn reset(&mut self, maximum_data: VarInt) {
+        self.total_available_window = maximum_data;
+        self.available_window = maximum_data;
+        self.data_blocked_sync.reset();
+    }
 }
 
 /// Writes the `DATA_BLOCKED` frames.
@@ -154,6 +160,15 @@ impl OutgoingConnectionFlowController {
         self.inner.borrow_mut().on_max_data(frame)
     }
 
+    /// Replaces the flow control window after the peer discarded all of the data
+    /// which was sent on the connection.
+    ///
+    /// In contrast to `on_max_data`, the new limit may be lower than the current one.
+    /// Streams are expected to release their acquired window at the same time.
+    pub fn reset(&mut self, maximum_data: VarInt) {
+        self.inner.borrow_mut().reset(maximum_data)
+    }
+
     /// This method is called when a packet delivery got acknowledged
     pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
         self.inner
@@ -6,6 +6,7 @@ use crate::{
     contexts::{OnTransmitError, WriteContext},
     stream::{
         incoming_connection_flow_controller::IncomingConnectionFlowController,
+        receive_window_tuner::ReceiveWindowTuner,
         stream_events::StreamEvents,
         stream_interests::{StreamInterestProvider, StreamInterests},
         StreamError,
@@ -22,6 +23,7 @@ use s2n_quic_core::{
     frame::{stream::StreamRef, MaxStreamData, ResetStream, StopSending, StreamDataBlocked},
     packet::number::PacketNumber,
     stream::{ops, StreamId},
+    time::Timestamp,
     transport,
     varint::VarInt,
 };
@@ -181,6 +183,8 @@ pub(super) struct ReceiveStreamFlowController {
     pub(super) acquired_connection_window: VarInt,
     /// The amount of credits which had been released in total
     pub(super) released_connection_window: VarInt,
+    /// Grows the desired flow control window if it limits the peer
+    tuner: ReceiveWindowTuner,
 }
 
 impl ReceiveStreamFlowController {
@@ -188,6 +192,7 @@ impl ReceiveStreamFlowController {
         connection_flow_controller: IncomingConnectionFlowController,
         initial_window: VarInt,
         desired_flow_control_window: u32,
+        max_flow_control_window: u32,
     ) -> Self {
         Self {
             connection_flow_controller,
@@ -199,6 +204,7 @@ impl ReceiveStreamFlowController {
             acquired_connection_window: VarInt::from_u32(0),
             released_connection_window: VarInt::from_u32(0),
             desired_flow_control_window,
+            tuner: ReceiveWindowTuner::new(max_flow_control_window),
         }
     }
 
@@ -290,6 +296,38 @@ impl ReceiveStreamFlowController {
         self.connection_flow_controller.release_window(amount);
     }
 
+    /// Grows the flow control window if the peer is limited by it
+    ///
+    /// This is called before window updates are transmitted.
+    fn tune_window(&mut self, now: Timestamp) {
+        // there's no need to grow the window once the final size is known
+        if self.read_window_sync.is_cancelled() {
+            return;
+        }
+
+        let window = self.tuner.on_window_update(
+            self.desired_flow_control_window,
+            self.released_connection_window,
+            now,
+            self.connection_flow_controller.smoothed_rtt(),
+        );
+
+        if window <= self.desired_flow_control_window {
+            return;
+        }
+
+        self.desired_flow_control_window = window;
+        self.read_window_sync
+            .set_threshold(VarInt::from_u32(window / 10));
+        self.read_window_sync.update_latest_value(
+            self.released_connection_window
+                .saturating_add(VarInt::from_u32(window))
+                .max(self.read_window_sync.latest_value()),
+        );
+        self.connection_flow_controller
+            .on_stream_window_update(window);
+    }
+
     /// Releases all flow credits which had been acquired but not yet released
     /// through previous [`release_window`] calls.
     fn release_outstanding_window(&mut self) {
@@ -353,6 +391,7 @@ impl ReceiveStream {
         connection_flow_controller: IncomingConnectionFlowController,
         initial_window: VarInt,
         desired_flow_control_window: u32,
+        max_flow_control_window: u32,
     ) -> ReceiveStream {
         // If the stream is created in closed state directly move into the
         // terminal state.
@@ -369,6 +408,7 @@ impl ReceiveStream {
                 connection_flow_controller,
                 initial_window,
                 desired_flow_control_window,
+                max_flow_control_window,
             ),
             stop_sending_sync: OnceSync::new(),
             read_waiter: None,
@@ -721,6 +761,7 @@ impl ReceiveStream {
         //# To avoid blocking a sender, a receiver MAY send a MAX_STREAM_DATA or
         //# MAX_DATA frame multiple times within a round trip or send it early
         //# enough to allow time for loss of the frame and subsequent recovery.
+        self.flow_controller.tune_window(context.current_time());
         self.flow_controller
             .read_window_sync
             .on_transmit(stream_id, context)
@@ -0,0 +1,131 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Grows receive windows which limit the throughput of the peer
+//!
+//! Similar to the receive buffer auto-tuning in Linux, the amount of data the application
+//! consumes is measured against the round trip time. If a full window is consumed within two
+//! round trips, the peer was likely waiting on flow control credits instead of the network,
+//! and the window is doubled. This settles the window at roughly twice the bandwidth-delay
+//! product, without exceeding the configured maximum.
+
+use core::time::Duration;
+use s2n_quic_core::{time::Timestamp, varint::VarInt};
+
+#[derive(Debug)]
+pub(super) struct ReceiveWindowTuner {
+    /// The size the window can be grown to
+    max_window: u32,
+    /// The time and amount of consumed data at the start of the current measurement
+    epoch: Option<(Timestamp, VarInt)>,
+}
+
+impl ReceiveWindowTuner {
+    pub fn new(max_window: u32) -> Self {
+        Self {
+            max_window,
+            epoch: None,
+        }
+    }
+
+    /// Returns the size the window can be grown to
+    pub fn max_window(&self) -> u32 {
+        self.max_window
+    }
+
+    /// Returns the window to maintain after `consumed` bytes have been read by the application
+    ///
+    /// This is called when a window update is about to be transmitted. `rtt` is `None` until
+    /// the first RTT sample is available.
+    pub fn on_window_update(
+        &mut self,
+        window: u32,
+        consumed: VarInt,
+        now: Timestamp,
+        rtt: Option<Duration>,
+    ) -> u32 {
+        // auto-tuning is disabled or the window has already reached the maximum
+        if window >= self.max_window {
+            return window;
+        }
+
+        let rtt = if let Some(rtt) = rtt {
+            rtt
+        } else {
+            return window;
+        };
+
+        let (start, start_consumed) = if let Some(epoch) = self.epoch {
+            epoch
+        } else {
+            self.epoch = Some((now, consumed));
+            return window;
+        };
+
+        // wait until a full window has been consumed to measure the rate
+        if consumed.saturating_sub(start_consumed) < VarInt::from_u32(window) {
+            return window;
+        }
+
+        self.epoch = Some((now, consumed));
+
+        if now.saturating_duration_since(start) < rtt * 2 {
+            window.saturating_mul(2).min(self.max_window)
+        } else {
+            window
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RTT: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn disabled_test() {
+        let now = s2n_quic_platform::time::now();
+        let mut tuner = ReceiveWindowTuner::new(0);
+
+        for i in 0..10u32 {
+            let consumed = VarInt::from_u32(i * 1000);
+            assert_eq!(tuner.on_window_update(1000, consumed, now, Some(RTT)), 1000);
+        }
+    }
+
+    #[test]
+    fn fast_consumption_test() {
+        let mut now = s2n_quic_platform::time::now();
+        let mut tuner = ReceiveWindowTuner::new(5000);
+        let mut window = 1000;
+        let mut consumed = VarInt::from_u32(0);
+
+        // no RTT sample yet
+        assert_eq!(tuner.on_window_update(window, consumed, now, None), window);
+
+        for &expected in &[1000, 2000, 4000, 5000, 5000] {
+            window = tuner.on_window_update(window, consumed, now, Some(RTT));
+            assert_eq!(window, expected);
+
+            // the peer sends a full window every round trip
+            consumed += VarInt::from_u32(window);
+            now += RTT;
+        }
+    }
+
+    #[test]
+    fn slow_consumption_test() {
+        let mut now = s2n_quic_platform::time::now();
+        let mut tuner = ReceiveWindowTuner::new(5000);
+        let mut consumed = VarInt::from_u32(0);
+
+        for _ in 0..5 {
+            assert_eq!(tuner.on_window_update(1000, consumed, now, Some(RTT)), 1000);
+
+            // the peer sends a full window every 3 round trips
+            consumed += VarInt::from_u32(1000);
+            now += RTT * 3;
+        }
+    }
+}
@@ -26,7 +26,7 @@ use s2n_quic_core::{
     ack, application,
     frame::{MaxStreamData, ResetStream, StopSending, StreamDataBlocked},
     packet::number::PacketNumber,
-    stream::{ops, StreamId},
+    stream::{ops, Priority, StreamId},
     time::{timer, Timestamp},
     transport,
     varint::VarInt,
@@ -232,6 +232,18 @@ impl StreamFlowController {
         }
     }
 
+    /// Releases the connection window which was acquired for data the peer never received
+    /// and replaces the `MAXIMUM_STREAM_DATA` value, even if the new value is lower
+    pub fn reset(&mut self, max_stream_data: VarInt) {
+        self.acquired_connection_flow_controller_window = VarInt::from_u32(0);
+        self.highest_requested_connection_flow_control_window = VarInt::from_u32(0);
+        self.max_stream_data = max_stream_data;
+        if self.state != StreamFlowControllerState::Finished {
+            self.state = StreamFlowControllerState::Ready;
+        }
+        self.stream_data_blocked_sync.reset();
+    }
+
     /// Tries to acquire as much window from the connection flow control window
     /// as possible.
     pub fn try_acquire_connection_window(&mut self) {
@@ -455,6 +467,8 @@ pub struct SendStream {
     pub(super) write_waiter: Option<(Waker, bool)>,
     /// Whether the final state had already been observed by the application
     final_state_observed: bool,
+    /// The priority used to schedule the transmission of the stream
+    priority: Priority,
 }
 
 impl SendStream {
@@ -482,6 +496,7 @@ impl SendStream {
             reset_sync: OnceSync::new(),
             write_waiter: None,
             final_state_observed: is_closed,
+            priority: Priority::default(),
         };
 
         if is_closed {
@@ -493,6 +508,30 @@ impl SendStream {
 
     // These functions are called from the packet delivery thread
 
+    /// This is called on the client when the server rejected the 0-RTT data
+    /// which was sent on this stream
+    ///
+    /// The server did not receive anything for the stream, so all of the data is
+    /// transmitted again using the `max_stream_data` of the new connection.
+    pub fn on_zero_rtt_rejected(&mut self, max_stream_data: VarInt) {
+        self.data_sender
+            .flow_controller_mut()
+            .reset(max_stream_data);
+
+        match self.state {
+            SendStreamState::Sending => self.data_sender.rewind(),
+            SendStreamState::ResetSent(StreamError::StreamReset { error, .. }) => {
+                // No window was acquired on the new connection, so the final size is 0
+                self.reset_sync = OnceSync::new();
+                self.reset_sync.request_delivery(OutgoingResetData {
+                    application_error_code: error,
+                    final_size: VarInt::from_u32(0),
+                });
+            }
+            SendStreamState::ResetSent(_) | SendStreamState::ResetAcknowledged(_) => {}
+        }
+    }
+
     /// This is called when a `MAX_STREAM_DATA` frame had been received for
     /// this stream
     pub fn on_max_stream_data(
@@ -717,6 +756,12 @@ impl SendStream {
         }
     }
 
+    /// Returns the priority used to schedule the transmission of the stream
+    #[inline]
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
     // These functions are called from the client API
 
     /// Tries to enqueue data for transmission on the `Stream`.
@@ -744,6 +789,10 @@ impl SendStream {
     ) -> Result<ops::tx::Response, StreamError> {
         let mut response = ops::tx::Response::default();
 
+        if let Some(priority) = request.priority {
+            self.priority = priority;
+        }
+
         macro_rules! store_waker {
             ($should_flush:expr) => {
                 // Store the waker, in order to be able to wakeup the caller
@@ -13,11 +13,17 @@ use crate::{
     transmission,
 };
 use alloc::rc::Rc;
-use core::{cell::RefCell, ops::Deref};
+use core::{
+    cell::{Cell, RefCell},
+    ops::Deref,
+};
 use intrusive_collections::{
     intrusive_adapter, KeyAdapter, LinkedList, LinkedListLink, RBTree, RBTreeLink,
 };
-use s2n_quic_core::{stream::StreamId, time::timer};
+use s2n_quic_core::{
+    stream::{priority::URGENCY_LEVELS, Priority, StreamId},
+    time::timer,
+};
 
 // Intrusive list adapter for managing the list of `done` streams
 intrusive_adapter!(DoneStreamsAdapter<S> = Rc<StreamNode<S>>: StreamNode<S> {
@@ -79,11 +85,13 @@ struct StreamNode<S> {
     waiting_for_connection_flow_control_credits_link: LinkedListLink,
     /// Allows the Stream to be part of the `waiting_for_stream_flow_control_credits` collection
     waiting_for_stream_flow_control_credits_link: LinkedListLink,
+    /// The priority under which the Stream is stored in the prioritized collections
+    priority: Cell<Priority>,
 }
 
 impl<S> StreamNode<S> {
     /// Creates a new `StreamNode` which wraps the given Stream implementation of type `S`
-    pub fn new(stream_impl: S) -> StreamNode<S> {
+    pub fn new(stream_impl: S, priority: Priority) -> StreamNode<S> {
         StreamNode {
             inner: RefCell::new(stream_impl),
             tree_link: RBTreeLink::new(),
@@ -93,6 +101,7 @@ impl<S> StreamNode<S> {
             waiting_for_retransmission_link: LinkedListLink::new(),
             waiting_for_connection_flow_control_credits_link: LinkedListLink::new(),
             waiting_for_stream_flow_control_credits_link: LinkedListLink::new(),
+            priority: Cell::new(priority),
         }
     }
 }
@@ -123,10 +132,32 @@ unsafe fn stream_node_rc_from_ref<S>(stream_node: &StreamNode<S>) -> Rc<StreamNo
     temp_node_ptr.deref().clone()
 }
 
+/// A list for each urgency level, indexed by the urgency of the contained Streams
+type PrioritizedList<A> = [LinkedList<A>; URGENCY_LEVELS];
+
+/// Creates an empty list for each urgency level
+macro_rules! prioritized_list {
+    ($adapter:ident) => {
+        [
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+            LinkedList::new($adapter::new()),
+        ]
+    };
+}
+
 /// Contains all secondary lists of Streams.
 ///
 /// A Stream can be a member in any of those, in addition to being a member of
 /// `StreamContainer::stream_map`.
+///
+/// The lists which are used to schedule transmissions are split by urgency, so
+/// that more urgent Streams are always visited first.
 struct InterestLists<S> {
     /// Streams which have been finalized
     done_streams: LinkedList<DoneStreamsAdapter<S>>,
@@ -134,13 +165,13 @@ struct InterestLists<S> {
     /// packet loss notifications
     waiting_for_frame_delivery: LinkedList<WaitingForFrameDeliveryAdapter<S>>,
     /// Streams which need to transmit data
-    waiting_for_transmission: LinkedList<WaitingForTransmissionAdapter<S>>,
+    waiting_for_transmission: PrioritizedList<WaitingForTransmissionAdapter<S>>,
     /// Streams which need to transmit data
-    waiting_for_retransmission: LinkedList<WaitingForRetransmissionAdapter<S>>,
+    waiting_for_retransmission: PrioritizedList<WaitingForRetransmissionAdapter<S>>,
     /// Streams which are blocked on transmission due to waiting on the
     /// connection flow control window to increase
     waiting_for_connection_flow_control_credits:
-        LinkedList<WaitingForConnectionFlowControlCreditsAdapter<S>>,
+        PrioritizedList<WaitingForConnectionFlowControlCreditsAdapter<S>>,
     /// Streams which are blocked on transmission due to waiting on the
     /// stream flow control window to increase
     waiting_for_stream_flow_control_credits:
@@ -152,10 +183,10 @@ impl<S: StreamTrait> InterestLists<S> {
         Self {
             done_streams: LinkedList::new(DoneStreamsAdapter::new()),
             waiting_for_frame_delivery: LinkedList::new(WaitingForFrameDeliveryAdapter::new()),
-            waiting_for_transmission: LinkedList::new(WaitingForTransmissionAdapter::new()),
-            waiting_for_retransmission: LinkedList::new(WaitingForRetransmissionAdapter::new()),
-            waiting_for_connection_flow_control_credits: LinkedList::new(
-                WaitingForConnectionFlowControlCreditsAdapter::new(),
+            waiting_for_transmission: prioritized_list!(WaitingForTransmissionAdapter),
+            waiting_for_retransmission: prioritized_list!(WaitingForRetransmissionAdapter),
+            waiting_for_connection_flow_control_credits: prioritized_list!(
+                WaitingForConnectionFlowControlCreditsAdapter
             ),
             waiting_for_stream_flow_control_credits: LinkedList::new(
                 WaitingForStreamFlowControlCreditsAdapter::new(),
@@ -168,6 +199,7 @@ impl<S: StreamTrait> InterestLists<S> {
         &mut self,
         node: &Rc<StreamNode<S>>,
         interests: StreamInterests,
+        priority: Priority,
         result: StreamContainerIterationResult,
     ) -> bool {
         // Note that all comparisons start by checking whether the stream is
@@ -177,24 +209,57 @@ impl<S: StreamTrait> InterestLists<S> {
         // an element from a list while it is not actually part of the list
         // is undefined.
 
+        macro_rules! remove_from_list {
+            ($link_name:ident, $list:expr) => {
+                if node.$link_name.is_linked() {
+                    // Safety: We know that the node is only ever part of this list.
+                    // While elements are in temporary lists, they always get unlinked
+                    // from those temporary lists while their interest is updated.
+                    let mut cursor =
+                        unsafe { $list.cursor_mut_from_ptr(node.deref() as *const StreamNode<S>) };
+                    cursor.remove();
+                }
+            };
+        }
+
+        // Move the stream out of the lists for its previous urgency. It is inserted
+        // into the lists for the new urgency below.
+        let previous_urgency = node.priority.get().urgency() as usize;
+        if previous_urgency != priority.urgency() as usize {
+            remove_from_list!(
+                waiting_for_transmission_link,
+                self.waiting_for_transmission[previous_urgency]
+            );
+            remove_from_list!(
+                waiting_for_retransmission_link,
+                self.waiting_for_retransmission[previous_urgency]
+            );
+            remove_from_list!(
+                waiting_for_connection_flow_control_credits_link,
+                self.waiting_for_connection_flow_control_credits[previous_urgency]
+            );
+        }
+        node.priority.set(priority);
+
+        let urgency = priority.urgency() as usize;
+
+        // An interrupted stream is placed at the front of the list in order to
+        // continue where it left off
+        let is_continue = matches!(result, StreamContainerIterationResult::Continue);
+        // Incremental streams instead yield to the other streams of the same urgency
+        let is_prioritized_continue = is_continue || priority.is_incremental();
+
         macro_rules! sync_interests {
-            ($interest:expr, $link_name:ident, $list_name:ident) => {
+            ($interest:expr, $link_name:ident, $list:expr, $push_back:expr) => {
                 if $interest != node.$link_name.is_linked() {
                     if $interest {
-                        if matches!(result, StreamContainerIterationResult::Continue) {
-                            self.$list_name.push_back(node.clone());
+                        if $push_back {
+                            $list.push_back(node.clone());
                         } else {
-                            self.$list_name.push_front(node.clone());
+                            $list.push_front(node.clone());
                         }
                     } else {
-                        // Safety: We know that the node is only ever part of this list.
-                        // While elements are in temporary lists, they always get unlinked
-                        // from those temporary lists while their interest is updated.
-                        let mut cursor = unsafe {
-                            self.$list_name
-                                .cursor_mut_from_ptr(node.deref() as *const StreamNode<S>)
-                        };
-                        cursor.remove();
+                        remove_from_list!($link_name, $list);
                     }
                 }
                 debug_assert_eq!($interest, node.$link_name.is_linked());
@@ -204,27 +269,32 @@ impl<S: StreamTrait> InterestLists<S> {
         sync_interests!(
             interests.delivery_notifications,
             waiting_for_frame_delivery_link,
-            waiting_for_frame_delivery
+            self.waiting_for_frame_delivery,
+            is_continue
         );
         sync_interests!(
             matches!(interests.transmission, transmission::Interest::NewData),
             waiting_for_transmission_link,
-            waiting_for_transmission
+            self.waiting_for_transmission[urgency],
+            is_prioritized_continue
         );
         sync_interests!(
             matches!(interests.transmission, transmission::Interest::LostData),
             waiting_for_retransmission_link,
-            waiting_for_retransmission
+            self.waiting_for_retransmission[urgency],
+            is_prioritized_continue
         );
         sync_interests!(
             interests.connection_flow_control_credits,
             waiting_for_connection_flow_control_credits_link,
-            waiting_for_connection_flow_control_credits
+            self.waiting_for_connection_flow_control_credits[urgency],
+            is_prioritized_continue
         );
         sync_interests!(
             interests.stream_flow_control_credits,
             waiting_for_stream_flow_control_credits_link,
-            waiting_for_stream_flow_control_credits
+            self.waiting_for_stream_flow_control_credits,
+            is_continue
         );
 
         if !interests.retained != node.done_streams_link.is_linked() {
@@ -276,15 +346,16 @@ macro_rules! iterate_uninterruptible {
         for stream in $sel.interest_lists.$list_name.take() {
             debug_assert!(!stream.$link_name.is_linked());
 
-            let interests = {
+            let (interests, priority) = {
                 let mut mut_stream = stream.inner.borrow_mut();
                 $func(&mut *mut_stream);
-                mut_stream.get_stream_interests()
+                (mut_stream.get_stream_interests(), mut_stream.priority())
             };
 
             did_finalize |= $sel.interest_lists.update_interests(
                 &stream,
                 interests,
+                priority,
                 StreamContainerIterationResult::Continue,
             );
         }
@@ -311,9 +382,10 @@ macro_rules! iterate_interruptible {
 
             // Update the interests after the interaction
             let interests = mut_stream.get_stream_interests();
+            let priority = mut_stream.priority();
             did_finalize |= $sel
                 .interest_lists
-                .update_interests(&stream, interests, result);
+                .update_interests(&stream, interests, priority, result);
 
             match result {
                 StreamContainerIterationResult::BreakAndInsertAtBack => {
@@ -333,6 +405,60 @@ macro_rules! iterate_interruptible {
     };
 }
 
+macro_rules! iterate_prioritized {
+    ($sel:ident, $list_name:tt, $link_name:ident, $controller:ident, $func:ident) => {
+        let mut did_finalize = false;
+
+        // Visit the lists in order of urgency, so less urgent Streams only get to
+        // transmit once all of the more urgent Streams are done.
+        'urgency: for urgency in 0..URGENCY_LEVELS {
+            let mut extracted_list = $sel.interest_lists.$list_name[urgency].take();
+            let mut cursor = extracted_list.front_mut();
+
+            while let Some(stream) = cursor.remove() {
+                // Note that while we iterate over the intrusive lists here
+                // `stream` is part of no list anymore, since it also got dropped
+                // from list that is described by the `cursor`.
+                debug_assert!(!stream.$link_name.is_linked());
+                let mut mut_stream = stream.inner.borrow_mut();
+                let result = $func(&mut *mut_stream);
+
+                // Update the interests after the interaction
+                let interests = mut_stream.get_stream_interests();
+                let priority = mut_stream.priority();
+                did_finalize |= $sel
+                    .interest_lists
+                    .update_interests(&stream, interests, priority, result);
+
+                match result {
+                    StreamContainerIterationResult::BreakAndInsertAtBack => {
+                        let list = &mut $sel.interest_lists.$list_name[urgency];
+                        let is_front = list
+                            .front()
+                            .get()
+                            .map_or(false, |front| core::ptr::eq(front, &*stream));
+
+                        if is_front && !priority.is_incremental() {
+                            // The interrupted stream continues first, followed by
+                            // the streams which were not visited yet
+                            list.front_mut().splice_after(extracted_list);
+                        } else {
+                            // The streams which were not visited yet go first
+                            list.cursor_mut().splice_after(extracted_list);
+                        }
+                        break 'urgency;
+                    }
+                    StreamContainerIterationResult::Continue => {}
+                }
+            }
+        }
+
+        if did_finalize {
+            $sel.finalize_done_streams($controller);
+        }
+    };
+}
+
 impl<S: StreamTrait> StreamContainer<S> {
     /// Creates a new `StreamContainer`
     pub fn new() -> Self {
@@ -348,12 +474,14 @@ impl<S: StreamTrait> StreamContainer<S> {
         // Even though it likely might have none, it seems like it
         // would be better to avoid future bugs
         let interests = stream.get_stream_interests();
+        let priority = stream.priority();
 
-        let new_stream = Rc::new(StreamNode::new(stream));
+        let new_stream = Rc::new(StreamNode::new(stream, priority));
 
         self.interest_lists.update_interests(
             &new_stream,
             interests,
+            priority,
             StreamContainerIterationResult::Continue,
         );
         self.stream_map.insert(new_stream);
@@ -394,6 +522,7 @@ impl<S: StreamTrait> StreamContainer<S> {
         let node_ptr: Rc<StreamNode<S>>;
         let result: R;
         let interests;
+        let priority;
 
         // This block is required since we mutably borrow `self` inside the
         // block in order to obtain a Stream reference and to executing the
@@ -413,6 +542,7 @@ impl<S: StreamTrait> StreamContainer<S> {
             let stream: &mut S = &mut *node.inner.borrow_mut();
             result = func(stream);
             interests = stream.get_stream_interests();
+            priority = stream.priority();
         }
 
         // Update the interest lists after the interactions and then remove
@@ -420,6 +550,7 @@ impl<S: StreamTrait> StreamContainer<S> {
         if self.interest_lists.update_interests(
             &node_ptr,
             interests,
+            priority,
             StreamContainerIterationResult::Continue,
         ) {
             self.finalize_done_streams(controller);
@@ -444,32 +575,40 @@ impl<S: StreamTrait> StreamContainer<S> {
             // part of.
             let stream_ptr = &*stream as *const StreamNode<S>;
 
+            let urgency = stream.priority.get().urgency() as usize;
+
             macro_rules! remove_stream_from_list {
-                ($list_name:ident, $link_name:ident) => {
+                ($list:expr, $link_name:ident) => {
                     if stream.$link_name.is_linked() {
                         // Safety: We know that the Stream is part of the list,
                         // because it is linked, and we never place Streams in
                         // other lists when `finalize_done_streams` is called.
-                        let mut cursor = unsafe {
-                            self.interest_lists
-                                .$list_name
-                                .cursor_mut_from_ptr(stream_ptr)
-                        };
+                        let mut cursor = unsafe { $list.cursor_mut_from_ptr(stream_ptr) };
                         let remove_result = cursor.remove();
                         debug_assert!(remove_result.is_some());
                     }
                 };
             }
 
-            remove_stream_from_list!(waiting_for_frame_delivery, waiting_for_frame_delivery_link);
-            remove_stream_from_list!(waiting_for_transmission, waiting_for_transmission_link);
-            remove_stream_from_list!(waiting_for_retransmission, waiting_for_retransmission_link);
             remove_stream_from_list!(
-                waiting_for_connection_flow_control_credits,
+                self.interest_lists.waiting_for_frame_delivery,
+                waiting_for_frame_delivery_link
+            );
+            remove_stream_from_list!(
+                self.interest_lists.waiting_for_transmission[urgency],
+                waiting_for_transmission_link
+            );
+            remove_stream_from_list!(
+                self.interest_lists.waiting_for_retransmission[urgency],
+                waiting_for_retransmission_link
+            );
+            remove_stream_from_list!(
+                self.interest_lists
+                    .waiting_for_connection_flow_control_credits[urgency],
                 waiting_for_connection_flow_control_credits_link
             );
             remove_stream_from_list!(
-                waiting_for_stream_flow_control_credits,
+                self.interest_lists.waiting_for_stream_flow_control_credits,
                 waiting_for_stream_flow_control_credits_link
             );
 
@@ -510,7 +649,7 @@ impl<S: StreamTrait> StreamContainer<S> {
     ) where
         F: FnMut(&mut S) -> StreamContainerIterationResult,
     {
-        iterate_interruptible!(
+        iterate_prioritized!(
             self,
             waiting_for_connection_flow_control_credits,
             waiting_for_connection_flow_control_credits_link,
@@ -549,7 +688,7 @@ impl<S: StreamTrait> StreamContainer<S> {
     where
         F: FnMut(&mut S) -> StreamContainerIterationResult,
     {
-        iterate_interruptible!(
+        iterate_prioritized!(
             self,
             waiting_for_transmission,
             waiting_for_transmission_link,
@@ -570,7 +709,7 @@ impl<S: StreamTrait> StreamContainer<S> {
     ) where
         F: FnMut(&mut S) -> StreamContainerIterationResult,
     {
-        iterate_interruptible!(
+        iterate_prioritized!(
             self,
             waiting_for_retransmission,
             waiting_for_retransmission_link,
@@ -600,6 +739,7 @@ impl<S: StreamTrait> StreamContainer<S> {
             let mut mut_stream = stream.inner.borrow_mut();
             func(&mut *mut_stream);
             let interests = mut_stream.get_stream_interests();
+            let priority = mut_stream.priority();
 
             // Update the interest lists here
             // Safety: The stream reference is obtained from the RBTree, which
@@ -608,6 +748,7 @@ impl<S: StreamTrait> StreamContainer<S> {
             did_finalize |= self.interest_lists.update_interests(
                 &stream_node_rc,
                 interests,
+                priority,
                 StreamContainerIterationResult::Continue,
             );
         }
@@ -641,9 +782,19 @@ impl<S: StreamTrait> transmission::interest::Provider for StreamContainer<S> {
         &self,
         query: &mut Q,
     ) -> transmission::interest::Result {
-        if !self.interest_lists.waiting_for_retransmission.is_empty() {
+        if self
+            .interest_lists
+            .waiting_for_retransmission
+            .iter()
+            .any(|list| !list.is_empty())
+        {
             query.on_lost_data()?;
-        } else if !self.interest_lists.waiting_for_transmission.is_empty() {
+        } else if self
+            .interest_lists
+            .waiting_for_transmission
+            .iter()
+            .any(|list| !list.is_empty())
+        {
             query.on_new_data()?;
         }
 
@@ -17,7 +17,7 @@ use core::{task::Context, time::Duration};
 use s2n_quic_core::{
     ack, endpoint,
     frame::{stream::StreamRef, MaxStreamData, ResetStream, StopSending, StreamDataBlocked},
-    stream::{ops, StreamId},
+    stream::{ops, Priority, StreamId},
     time::{timer, Timestamp},
     transport,
     varint::VarInt,
@@ -38,6 +38,8 @@ pub struct StreamConfig {
     pub initial_receive_window: VarInt,
     /// The desired flow control window that we want to maintain on the receiving side
     pub desired_flow_control_window: u32,
+    /// The size the receive window can be grown to by auto-tuning
+    pub max_receive_window: u32,
     /// The initial flow control window for sending data
     pub initial_send_window: VarInt,
     /// The maximum buffered amount of data on the sending side
@@ -52,6 +54,9 @@ pub trait StreamTrait: StreamInterestProvider + timer::Provider + core::fmt::Deb
     /// Returns the Streams ID
     fn stream_id(&self) -> StreamId;
 
+    /// Returns the priority used to schedule the transmission of the Stream
+    fn priority(&self) -> Priority;
+
     // These functions are called from the packet delivery thread
 
     /// This is called when a `STREAM_DATA` frame had been received for
@@ -111,6 +116,12 @@ pub trait StreamTrait: StreamInterestProvider + timer::Provider + core::fmt::Deb
     /// not related to a frame. E.g. due to a connection failure.
     fn on_internal_reset(&mut self, error: StreamError, events: &mut StreamEvents);
 
+    /// This method gets called on the client when the server rejected the 0-RTT data
+    /// which was sent on the stream
+    ///
+    /// All data is transmitted again using the `max_stream_data` of the new connection.
+    fn on_zero_rtt_rejected(&mut self, max_stream_data: VarInt);
+
     /// This method is called when the application drops the connection
     ///
     /// The stream should finish any pending operations and close
@@ -204,6 +215,7 @@ impl StreamTrait for StreamImpl {
                 config.incoming_connection_flow_controller,
                 config.initial_receive_window,
                 config.desired_flow_control_window,
+                config.max_receive_window,
             ),
             has_send: !send_is_closed,
             send_stream: SendStream::new(
@@ -220,6 +232,11 @@ impl StreamTrait for StreamImpl {
         self.stream_id
     }
 
+    #[inline]
+    fn priority(&self) -> Priority {
+        self.send_stream.priority()
+    }
+
     // These functions are called from the packet delivery thread
 
     #[inline]
@@ -305,6 +322,13 @@ impl StreamTrait for StreamImpl {
         self.send_stream.on_internal_reset(error, events);
     }
 
+    #[inline]
+    fn on_zero_rtt_rejected(&mut self, max_stream_data: VarInt) {
+        if self.has_send {
+            self.send_stream.on_zero_rtt_rejected(max_stream_data);
+        }
+    }
+
     #[inline]
     fn on_flush(&mut self, error: StreamError, events: &mut StreamEvents) {
         // flushing a receive stream is the same as resetting it
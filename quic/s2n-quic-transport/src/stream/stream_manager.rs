@@ -31,7 +31,10 @@ use s2n_quic_core::{
     packet::number::PacketNumberSpace,
     stream::{ops, StreamId, StreamType},
     time::{timer, Timestamp},
-    transport::{self, parameters::InitialFlowControlLimits},
+    transport::{
+        self,
+        parameters::{InitialFlowControlLimits, InitialStreamLimits},
+    },
     varint::VarInt,
 };
 
@@ -177,6 +180,10 @@ pub struct StreamManagerState<S> {
     /// The initial flow control limits we received from the peer via transport
     /// parameters
     initial_peer_limits: InitialFlowControlLimits,
+    /// The receive windows which are maintained for new streams
+    ///
+    /// These start out as the initial limits, but can be changed while the connection is open.
+    receive_windows: InitialStreamLimits,
     /// If the `StreamManager` was closed, this contains the error which was
     /// passed to the `close()` call
     close_reason: Option<connection::Error>,
@@ -219,17 +226,20 @@ impl<S: StreamTrait> StreamManagerState<S> {
             .stream_limits
             .max_data(self.local_endpoint_type.peer_type(), stream_id);
 
-        // We pass the initial_receive_window also as the desired flow control
-        // window. Thereby we will maintain the same flow control window over
-        // the lifetime of the Stream.
-        // If we would want to have another limit, we would need to have various
-        // limits for the various combinations of unidirectional/bidirectional
-        // Streams. Those would bloat up the config, and essentially just
-        // duplicate the transport parameters.
+        // The stream starts with the window announced to the peer, and maintains the
+        // current receive window over its lifetime. These are the same unless the limits
+        // of the connection were updated.
+        let desired_flow_control_window = self
+            .receive_windows
+            .max_data(self.local_endpoint_type, stream_id);
         debug_assert!(
             initial_receive_window <= VarInt::from_u32(core::u32::MAX),
             "Receive window must not exceed 32bit range"
         );
+        debug_assert!(
+            desired_flow_control_window <= VarInt::from_u32(core::u32::MAX),
+            "Receive window must not exceed 32bit range"
+        );
 
         self.stream_controller.on_open_stream(stream_id);
 
@@ -239,7 +249,8 @@ impl<S: StreamTrait> StreamManagerState<S> {
             local_endpoint_type: self.local_endpoint_type,
             stream_id,
             initial_receive_window,
-            desired_flow_control_window: initial_receive_window.as_u64() as u32,
+            desired_flow_control_window: desired_flow_control_window.as_u64() as u32,
+            max_receive_window: self.stream_limits.max_receive_window,
             initial_send_window,
             max_send_buffer_size: self.stream_limits.max_send_buffer_size,
         }));
@@ -416,6 +427,7 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 incoming_connection_flow_controller: IncomingConnectionFlowController::new(
                     initial_local_limits.max_data,
                     initial_local_limits.max_data.as_u64() as u32,
+                    connection_limits.max_data_window(),
                 ),
                 outgoing_connection_flow_controller: OutgoingConnectionFlowController::new(
                     initial_peer_limits.max_data,
@@ -431,6 +443,7 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 local_endpoint_type,
                 initial_local_limits,
                 initial_peer_limits,
+                receive_windows: initial_local_limits.stream_limits,
                 close_reason: None,
                 accept_state: AcceptState::new(local_endpoint_type),
                 stream_limits: connection_limits.stream_limits(),
@@ -625,8 +638,31 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
         );
     }
 
+    /// Applies the stream and flow control limits of an update to the connection limits
+    pub fn update_limits(&mut self, update: &connection::limits::Update) {
+        for &stream_type in &[StreamType::Bidirectional, StreamType::Unidirectional] {
+            if let Some(limit) = update.max_open_remote_streams(stream_type) {
+                self.inner
+                    .stream_controller
+                    .update_remote_stream_limit(stream_type, limit);
+            }
+        }
+
+        if let Some(window) = update.data_window() {
+            self.inner
+                .incoming_connection_flow_controller
+                .set_desired_flow_control_window(window);
+        }
+
+        self.inner.receive_windows = update.stream_receive_windows(self.inner.receive_windows);
+    }
+
     /// This method gets called when the RTT estimate is updated for the active path
     pub fn on_rtt_update(&mut self, rtt_estimator: &RttEstimator) {
+        self.inner
+            .incoming_connection_flow_controller
+            .on_rtt_update(rtt_estimator.smoothed_rtt());
+
         let blocked_sync_period = self.blocked_sync_period(rtt_estimator);
         self.inner
             .stream_controller
@@ -921,6 +957,110 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
         Ok(())
     }
 
+    /// This is called on the client when the peer's transport parameters are received after
+    /// streams were already opened with the limits remembered for 0-RTT
+    pub fn on_peer_limits(
+        &mut self,
+        limits: InitialFlowControlLimits,
+    ) -> Result<(), transport::Error> {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+        //# If 0-RTT data is accepted by the server, the server MUST NOT reduce
+        //# any limits or alter any values that might be violated by the client
+        //# with its 0-RTT data.
+        let remembered = &self.inner.initial_peer_limits;
+        let remembered_stream_limits = &remembered.stream_limits;
+        if limits.max_data < remembered.max_data
+            || limits.max_streams_bidi < remembered.max_streams_bidi
+            || limits.max_streams_uni < remembered.max_streams_uni
+            || limits.stream_limits.max_data_bidi_local
+                < remembered_stream_limits.max_data_bidi_local
+            || limits.stream_limits.max_data_bidi_remote
+                < remembered_stream_limits.max_data_bidi_remote
+            || limits.stream_limits.max_data_uni < remembered_stream_limits.max_data_uni
+        {
+            return Err(transport::Error::PROTOCOL_VIOLATION
+                .with_reason("server reduced the limits remembered for 0-RTT"));
+        }
+
+        self.on_max_data(MaxData {
+            maximum_data: limits.max_data,
+        })?;
+        self.on_max_streams(&MaxStreams {
+            stream_type: StreamType::Bidirectional,
+            maximum_streams: limits.max_streams_bidi,
+        })?;
+        self.on_max_streams(&MaxStreams {
+            stream_type: StreamType::Unidirectional,
+            maximum_streams: limits.max_streams_uni,
+        })?;
+
+        let mut stream_ids = Vec::new();
+        self.inner
+            .streams
+            .iterate_streams(&mut self.inner.stream_controller, |stream| {
+                stream_ids.push(stream.stream_id())
+            });
+
+        let peer_type = self.inner.local_endpoint_type.peer_type();
+        for stream_id in stream_ids {
+            self.on_max_stream_data(&MaxStreamData {
+                stream_id: stream_id.into(),
+                maximum_stream_data: limits.stream_limits.max_data(peer_type, stream_id),
+            })?;
+        }
+
+        // Streams opened from now on use the limits of the current connection
+        self.inner.initial_peer_limits = limits;
+
+        Ok(())
+    }
+
+    /// This is called on the client when the server rejected the 0-RTT data
+    ///
+    /// The server did not process any of the streams, so all of the data is transmitted
+    /// again using the limits of the current connection.
+    pub fn on_zero_rtt_rejected(&mut self, limits: InitialFlowControlLimits) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+        //# Remembered transport parameters apply to the new connection until the
+        //# handshake completes and the client starts sending 1-RTT packets.
+        //# Once the handshake completes, the client uses the transport
+        //# parameters established in the handshake.
+        self.inner
+            .outgoing_connection_flow_controller
+            .reset(limits.max_data);
+        self.inner.stream_controller.on_zero_rtt_rejected(&limits);
+
+        let local_endpoint_type = self.inner.local_endpoint_type;
+        let peer_type = local_endpoint_type.peer_type();
+        self.inner
+            .streams
+            .iterate_streams(&mut self.inner.stream_controller, |stream| {
+                let stream_id = stream.stream_id();
+                let max_streams = match stream_id.stream_type() {
+                    StreamType::Bidirectional => limits.max_streams_bidi,
+                    StreamType::Unidirectional => limits.max_streams_uni,
+                };
+
+                // Stream IDs encode the stream type in the lowest 2 bits
+                let stream_index = stream_id.as_varint().as_u64() >> 2;
+
+                if stream_id.initiator() == local_endpoint_type
+                    && stream_index >= max_streams.as_u64()
+                {
+                    // The stream is no longer allowed by the peer and is closed
+                    let mut events = StreamEvents::new();
+                    stream.on_internal_reset(StreamError::zero_rtt_rejected(), &mut events);
+                    events.wake_all();
+                } else {
+                    stream
+                        .on_zero_rtt_rejected(limits.stream_limits.max_data(peer_type, stream_id));
+                }
+            });
+
+        // Streams opened from now on use the limits of the current connection
+        self.inner.initial_peer_limits = limits;
+    }
+
     // User APIs
 
     /// Executes an application API call on the given Stream if the Stream exists
@@ -2554,3 +2554,62 @@ fn receiving_into_non_empty_buffers_returns_an_error() {
         "data should not be lost when returning an error"
     );
 }
+
+#[test]
+fn receive_window_auto_tuning() {
+    let mut test_env_config = TestEnvironmentConfig::default();
+    test_env_config.initial_receive_window = 1000;
+    test_env_config.desired_flow_control_window = 1000;
+    test_env_config.max_receive_window = 4000;
+    test_env_config.initial_connection_receive_window_size = 2000;
+    test_env_config.desired_connection_flow_control_window = 2000;
+    test_env_config.max_connection_flow_control_window = 1_000_000;
+    let mut test_env = setup_stream_test_env_with_config(test_env_config);
+
+    let rtt = core::time::Duration::from_millis(100);
+    test_env.rx_connection_flow_controller.on_rtt_update(rtt);
+
+    let mut offset = VarInt::from_u32(0);
+
+    for &(expected_window, expected_max_stream_data) in
+        &[(1000, 2000), (2000, 4000), (4000, 8000), (4000, 12000)]
+    {
+        // The peer sends a full window every round trip
+        let window = test_env
+            .stream
+            .receive_stream
+            .flow_controller
+            .current_stream_receive_window()
+            - offset;
+        test_env.feed_data(offset, window.as_u64() as usize);
+        offset += window;
+        assert_eq!(window.as_u64() as usize, test_env.consume_all_data());
+
+        let mut frame = test_env.transmit().expect("Frame is written");
+        assert_eq!(
+            Frame::MaxStreamData(MaxStreamData {
+                stream_id: test_env.stream.stream_id.into(),
+                maximum_stream_data: VarInt::from_u32(expected_max_stream_data),
+            }),
+            frame.as_frame()
+        );
+        assert_eq!(
+            expected_window,
+            test_env
+                .stream
+                .receive_stream
+                .flow_controller
+                .desired_flow_control_window
+        );
+
+        test_env.current_time += rtt;
+    }
+
+    // The connection window is grown along with the stream window
+    assert_eq!(
+        6000,
+        test_env
+            .rx_connection_flow_controller
+            .desired_flow_control_window()
+    );
+}
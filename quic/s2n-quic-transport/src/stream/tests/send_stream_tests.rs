@@ -120,6 +120,11 @@ enum Instruction {
     AckPacket(PacketNumber, ExpectWakeup),
     /// Declares a packet with a given packet number as lost
     NackPacket(PacketNumber),
+    /// Rejects the 0-RTT data and applies the new connection and stream limits
+    ZeroRttRejected(
+        VarInt, // max data
+        VarInt, // max stream data
+    ),
 }
 
 fn execute_instructions(test_env: &mut TestEnvironment, instructions: &[Instruction]) {
@@ -251,6 +256,10 @@ fn execute_instructions(test_env: &mut TestEnvironment, instructions: &[Instruct
             Instruction::NackPacket(packet_number) => {
                 test_env.nack_packet(*packet_number);
             }
+            Instruction::ZeroRttRejected(max_data, max_stream_data) => {
+                test_env.tx_connection_flow_controller.reset(*max_data);
+                test_env.stream.on_zero_rtt_rejected(*max_stream_data);
+            }
         }
     }
 }
@@ -682,7 +691,12 @@ fn lost_data_is_retransmitted() {
         ][..],
     ];
 
-    for test_config in test_configs.iter() {
+    // Only the data of the lost packets is counted as retransmitted
+    let retransmitted_bytes = [900, 2 * 996];
+
+    for (test_config, retransmitted_bytes) in
+        test_configs.iter().zip(retransmitted_bytes.iter().copied())
+    {
         let test_env_config = TestEnvironmentConfig {
             max_send_buffer_size: 10 * MAX_PACKET_SIZE,
             initial_send_window: 10 * MAX_PACKET_SIZE as u64,
@@ -694,6 +708,64 @@ fn lost_data_is_retransmitted() {
             .set_max_packet_size(Some(MAX_PACKET_SIZE));
 
         execute_instructions(&mut test_env, &test_config[..]);
+        assert_eq!(
+            test_env.sent_frames.bytes_retransmitted,
+            retransmitted_bytes
+        );
+    }
+}
+
+#[test]
+fn zero_rtt_rejected_retransmits_with_new_limits() {
+    let test_configs = &[
+        &[
+            Instruction::EnqueueData(VarInt::from_u32(0), 500, true),
+            Instruction::Finish(false),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 500, true, false, pn(0)),
+            Instruction::CheckInterests(stream_interests(&["ack"])),
+            // All 0-RTT packets are declared lost when the server rejects them
+            Instruction::NackPacket(pn(0)),
+            Instruction::CheckInterests(stream_interests(&["lost"])),
+            // The data is transmitted again, respecting the lowered stream limit
+            Instruction::ZeroRttRejected(VarInt::from_u32(1000), VarInt::from_u32(200)),
+            Instruction::CheckInterests(stream_interests(&["tx"])),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 200, false, false, pn(1)),
+            Instruction::CheckStreamDataBlockedTx(VarInt::from_u32(200), pn(1)),
+            Instruction::CheckInterests(stream_interests(&["ack", "sf"])),
+            Instruction::SetMaxStreamData(VarInt::from_u32(1000), ExpectWakeup(Some(false))),
+            Instruction::CheckDataTx(VarInt::from_u32(200), 300, true, false, pn(2)),
+            Instruction::CheckInterests(stream_interests(&["ack"])),
+            Instruction::AckPacket(pn(1), ExpectWakeup(Some(false))),
+            Instruction::AckPacket(pn(2), ExpectWakeup(Some(true))),
+            Instruction::CheckInterests(stream_interests(&[])),
+            Instruction::CheckNoTx,
+        ][..],
+        &[
+            // A stream reset in 0-RTT is reset again with a final size of 0
+            Instruction::EnqueueData(VarInt::from_u32(0), 500, true),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 500, false, false, pn(0)),
+            Instruction::Reset(ApplicationErrorCode::new(1).unwrap(), true),
+            Instruction::CheckResetTx(
+                ApplicationErrorCode::new(1).unwrap(),
+                pn(1),
+                VarInt::from_u32(500),
+            ),
+            Instruction::NackPacket(pn(0)),
+            Instruction::NackPacket(pn(1)),
+            Instruction::ZeroRttRejected(VarInt::from_u32(300), VarInt::from_u32(200)),
+            Instruction::CheckResetTx(
+                ApplicationErrorCode::new(1).unwrap(),
+                pn(2),
+                VarInt::from_u32(0),
+            ),
+            Instruction::CheckNoTx,
+        ][..],
+    ];
+
+    for test_config in test_configs.iter() {
+        let mut test_env = setup_send_only_test_env();
+        test_env.sent_frames.set_max_packet_size(Some(1000));
+        execute_instructions(&mut test_env, &test_config[..]);
     }
 }
 
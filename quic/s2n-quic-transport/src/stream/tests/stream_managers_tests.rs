@@ -38,7 +38,7 @@ use s2n_quic_core::{
         StopSending, Stream as StreamFrame, StreamDataBlocked, StreamsBlocked,
     },
     packet::number::{PacketNumberRange, PacketNumberSpace},
-    stream::{ops, StreamId, StreamType},
+    stream::{ops, Priority, StreamId, StreamType},
     time::{
         timer::{self, Provider as _},
         Timestamp,
@@ -67,6 +67,7 @@ struct MockStream {
     update_blocked_sync_period_count: usize,
     on_timeout_count: usize,
     on_internal_reset_count: usize,
+    last_zero_rtt_rejected: Option<VarInt>,
     on_transmit_try_write_frames: usize,
     on_transmit_count: usize,
     on_transmit_limit: Option<usize>,
@@ -85,6 +86,7 @@ struct MockStream {
     poll_push_count: usize,
     poll_finish_count: usize,
     reset_count: usize,
+    priority: Priority,
 }
 
 impl MockStream {
@@ -138,6 +140,7 @@ impl StreamTrait for MockStream {
             update_blocked_sync_period_count: 0,
             on_timeout_count: 0,
             on_internal_reset_count: 0,
+            last_zero_rtt_rejected: None,
             on_data_count: 0,
             on_reset_count: 0,
             on_stream_data_blocked_count: 0,
@@ -158,6 +161,7 @@ impl StreamTrait for MockStream {
             poll_push_count: 0,
             poll_finish_count: 0,
             reset_count: 0,
+            priority: Priority::default(),
         }
     }
 
@@ -165,6 +169,10 @@ impl StreamTrait for MockStream {
         self.config.stream_id
     }
 
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+
     fn on_data(
         &mut self,
         frame: &StreamRef,
@@ -265,6 +273,10 @@ impl StreamTrait for MockStream {
         self.store_wakers(events);
     }
 
+    fn on_zero_rtt_rejected(&mut self, max_stream_data: VarInt) {
+        self.last_zero_rtt_rejected = Some(max_stream_data);
+    }
+
     fn on_flush(&mut self, error: StreamError, events: &mut StreamEvents) {
         // for testing purposes, it's the same as a reset
         self.on_internal_reset(error, events);
@@ -320,6 +332,10 @@ impl StreamTrait for MockStream {
                 self.reset_count += 1;
             }
 
+            if let Some(priority) = tx.priority {
+                self.priority = priority;
+            }
+
             response.tx = Some(ops::tx::Response::default());
         }
 
@@ -699,6 +715,112 @@ fn max_streams_replenishes_stream_control_capacity() {
     }
 }
 
+#[test]
+fn peer_limits_update_opened_streams() {
+    let mut manager = create_stream_manager(endpoint::Type::Client);
+
+    let bidi = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let uni = try_open(&mut manager, StreamType::Unidirectional).unwrap();
+
+    let mut limits = create_default_initial_flow_control_limits();
+    limits.stream_limits.max_data_bidi_remote = VarInt::from_u32(8192);
+    limits.stream_limits.max_data_uni = VarInt::from_u32(16384);
+    limits.max_data = VarInt::from_u32(128 * 1024);
+
+    assert!(manager.on_peer_limits(limits).is_ok());
+
+    assert_eq!(
+        limits.max_data,
+        manager.with_outgoing_connection_flow_controller(|ctrl| ctrl.total_window())
+    );
+
+    for (stream_id, expected) in [
+        (bidi, VarInt::from_u32(8192)),
+        (uni, VarInt::from_u32(16384)),
+    ] {
+        manager.with_asserted_stream(stream_id, |stream| {
+            assert_eq!(1, stream.on_max_stream_data_count);
+            assert_eq!(
+                Some(expected),
+                stream
+                    .last_max_stream_data
+                    .map(|frame| frame.maximum_stream_data)
+            );
+        });
+    }
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+//= type=test
+//# If 0-RTT data is accepted by the server, the server MUST NOT reduce
+//# any limits or alter any values that might be violated by the client
+//# with its 0-RTT data.
+#[test]
+fn peer_limits_must_not_be_reduced() {
+    let mut manager = create_stream_manager(endpoint::Type::Client);
+    try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    let mut limits = create_default_initial_flow_control_limits();
+    limits.stream_limits.max_data_bidi_remote = VarInt::from_u32(1024);
+
+    assert_eq!(
+        Err(TransportError::PROTOCOL_VIOLATION.code),
+        manager.on_peer_limits(limits).map_err(|err| err.code)
+    );
+}
+
+#[test]
+fn zero_rtt_rejected_applies_lower_limits() {
+    let mut manager = create_stream_manager(endpoint::Type::Client);
+
+    let bidi_streams: Vec<_> = (0..3)
+        .map(|_| try_open(&mut manager, StreamType::Bidirectional).unwrap())
+        .collect();
+    let uni = try_open(&mut manager, StreamType::Unidirectional).unwrap();
+
+    let mut limits = create_default_initial_flow_control_limits();
+    limits.stream_limits.max_data_bidi_remote = VarInt::from_u32(1000);
+    limits.stream_limits.max_data_uni = VarInt::from_u32(2000);
+    limits.max_data = VarInt::from_u32(1024);
+    limits.max_streams_bidi = VarInt::from_u32(2);
+
+    manager.on_zero_rtt_rejected(limits);
+
+    assert_eq!(
+        limits.max_data,
+        manager.with_outgoing_connection_flow_controller(|ctrl| ctrl.total_window())
+    );
+    assert_eq!(
+        VarInt::from_u32(0),
+        manager.with_outgoing_connection_flow_controller(|ctrl| ctrl.acquired_window())
+    );
+
+    // Streams within the new limit start over with the new stream limits
+    for stream_id in &bidi_streams[..2] {
+        manager.with_asserted_stream(*stream_id, |stream| {
+            assert_eq!(Some(VarInt::from_u32(1000)), stream.last_zero_rtt_rejected);
+            assert_eq!(0, stream.on_internal_reset_count);
+        });
+    }
+    manager.with_asserted_stream(uni, |stream| {
+        assert_eq!(Some(VarInt::from_u32(2000)), stream.last_zero_rtt_rejected);
+    });
+
+    // The stream which exceeds the new limit is reset
+    manager.with_asserted_stream(bidi_streams[2], |stream| {
+        assert_eq!(None, stream.last_zero_rtt_rejected);
+        assert_eq!(1, stream.on_internal_reset_count);
+    });
+
+    // No more bidirectional streams can be opened
+    assert_eq!(
+        VarInt::from_u32(0),
+        manager.with_stream_controller(
+            |ctrl| ctrl.available_outgoing_stream_capacity(StreamType::Bidirectional)
+        )
+    );
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-4.6
 //= type=test
 //# An endpoint MUST NOT wait
@@ -787,6 +909,68 @@ fn peer_closing_streams_transmits_max_streams() {
     }
 }
 
+#[test]
+fn update_limits_transmits_max_streams_and_max_data() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    let update = connection::limits::Update::new()
+        .with_max_open_remote_bidirectional_streams(200)
+        .unwrap()
+        .with_max_open_remote_unidirectional_streams(300)
+        .unwrap()
+        .with_data_window(128 * 1024)
+        .unwrap();
+    manager.update_limits(&update);
+
+    assert_eq!(
+        transmission::Interest::NewData,
+        manager.get_transmission_interest()
+    );
+
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let mut write_context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+    assert!(manager.on_transmit(&mut write_context).is_ok());
+
+    for expected_frame in [
+        Frame::MaxData(MaxData {
+            maximum_data: VarInt::from_u32(128 * 1024),
+        }),
+        Frame::MaxStreams(MaxStreams {
+            stream_type: StreamType::Bidirectional,
+            maximum_streams: VarInt::from_u32(200),
+        }),
+        Frame::MaxStreams(MaxStreams {
+            stream_type: StreamType::Unidirectional,
+            maximum_streams: VarInt::from_u32(300),
+        }),
+    ] {
+        assert_eq!(
+            expected_frame,
+            write_context.frame_buffer.pop_front().unwrap().as_frame()
+        );
+    }
+    assert!(write_context.frame_buffer.is_empty());
+
+    // lower limits can't be retracted, so nothing is sent
+    let update = connection::limits::Update::new()
+        .with_max_open_remote_bidirectional_streams(10)
+        .unwrap()
+        .with_data_window(1024)
+        .unwrap();
+    manager.update_limits(&update);
+
+    assert_eq!(
+        transmission::Interest::None,
+        manager.get_transmission_interest()
+    );
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-4.6
 //= type=test
 //# An endpoint that is unable to open a new stream due to the peer's
@@ -2370,6 +2554,123 @@ fn on_transmit_queries_streams_for_data() {
     assert!(manager.streams_waiting_for_transmission().is_empty());
 }
 
+#[test]
+fn on_transmit_honors_stream_urgency() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+
+    let stream_1 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_2 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_3 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    for (stream_id, urgency) in [(stream_1, 5), (stream_2, 0), (stream_3, 3)] {
+        manager.with_asserted_stream(stream_id, |stream| {
+            stream.priority = Priority::new(urgency, false);
+            stream.on_transmit_try_write_frames = 10;
+        });
+    }
+
+    // streams are ordered by urgency, regardless of when they became interested
+    assert_eq!(
+        [stream_2, stream_3, stream_1],
+        *manager.streams_waiting_for_transmission()
+    );
+
+    frame_buffer.set_error_write_after_n_frames(15);
+    let mut write_context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+
+    assert_eq!(
+        Err(OnTransmitError::CouldNotWriteFrame),
+        manager.on_transmit(&mut write_context)
+    );
+
+    // the least urgent stream is not queried until the others are done
+    manager.with_asserted_stream(stream_2, |stream| {
+        assert_eq!(1, stream.on_transmit_count);
+        assert_eq!(0, stream.on_transmit_try_write_frames);
+    });
+    manager.with_asserted_stream(stream_3, |stream| {
+        assert_eq!(1, stream.on_transmit_count);
+        assert_eq!(5, stream.on_transmit_try_write_frames);
+    });
+    manager.with_asserted_stream(stream_1, |stream| {
+        assert_eq!(0, stream.on_transmit_count);
+        assert_eq!(10, stream.on_transmit_try_write_frames);
+    });
+
+    frame_buffer.clear();
+    assert_eq!(
+        [stream_3, stream_1],
+        *manager.streams_waiting_for_transmission()
+    );
+
+    // raising the urgency of a waiting stream moves it ahead of the others
+    let (_wakeup_queue, wakeup_handle) = create_wakeup_queue_and_handle();
+    assert!(manager
+        .poll_request(
+            stream_1,
+            &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+            ops::Request::default().set_priority(Priority::new(0, false)),
+            None,
+        )
+        .is_ok());
+
+    assert_eq!(
+        [stream_1, stream_3],
+        *manager.streams_waiting_for_transmission()
+    );
+}
+
+#[test]
+fn on_transmit_interleaves_incremental_streams() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+
+    let stream_1 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_2 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    for stream_id in [stream_1, stream_2] {
+        manager.with_asserted_stream(stream_id, |stream| {
+            stream.priority = Priority::default().with_incremental(true);
+            stream.on_transmit_try_write_frames = 10;
+        });
+    }
+
+    // each packet is filled by the stream which has waited the longest
+    for expected in [[stream_2, stream_1], [stream_1, stream_2]] {
+        frame_buffer.set_error_write_after_n_frames(5);
+        let mut write_context = MockWriteContext::new(
+            s2n_quic_platform::time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
+        );
+
+        assert_eq!(
+            Err(OnTransmitError::CouldNotWriteFrame),
+            manager.on_transmit(&mut write_context)
+        );
+        assert_eq!(5, frame_buffer.len());
+        frame_buffer.clear();
+
+        assert_eq!(expected, *manager.streams_waiting_for_transmission());
+    }
+
+    for stream_id in [stream_1, stream_2] {
+        manager.with_asserted_stream(stream_id, |stream| {
+            assert_eq!(1, stream.on_transmit_count);
+            assert_eq!(5, stream.on_transmit_try_write_frames);
+        });
+    }
+}
+
 fn invalid_stream_id(local_ep_type: endpoint::Type) -> StreamId {
     StreamId::nth(local_ep_type, StreamType::Bidirectional, 100_000).unwrap()
 }
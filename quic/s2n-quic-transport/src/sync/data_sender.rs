@@ -161,6 +161,29 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         result
     }
 
+    /// Queues all enqueued data for transmission as if it had never been sent.
+    ///
+    /// This is used when the peer discarded all packets that were sent for the stream,
+    /// which happens when a server rejects 0-RTT data.
+    pub fn rewind(&mut self) {
+        if matches!(self.state, State::Finished | State::Cancelled(_)) {
+            return;
+        }
+
+        // None of the data was acknowledged, so all of it is still pending
+        self.transmissions.clear();
+        self.lost.clear();
+        self.transmission_offset = self.buffer.head();
+
+        if let Some(fin_state) = self.state.fin_state_mut() {
+            if matches!(fin_state, FinState::InFlight(_) | FinState::Lost) {
+                *fin_state = FinState::Pending;
+            }
+        }
+
+        self.check_integrity();
+    }
+
     /// Returns the flow controller for this `DataSender`
     pub fn flow_controller(&self) -> &FlowController {
         &self.transmissions.flow_controller
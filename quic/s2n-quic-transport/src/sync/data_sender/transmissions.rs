@@ -66,6 +66,7 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         changed
     }
 
+    /// Transmits the ranges in `set`, which were previously declared lost
     #[inline]
     pub fn transmit_set<W: WriteContext>(
         &mut self,
@@ -88,6 +89,7 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
                 Ok(transmitted) => {
                     has_transmitted = true;
                     let len = transmitted.len();
+                    context.on_data_retransmitted(len);
                     if len != interval.len() {
                         // only a part of the range was written so push back what wasn't
                         interval.start += len;
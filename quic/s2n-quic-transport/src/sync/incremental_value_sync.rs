@@ -73,6 +73,12 @@ impl<
         self.request_delivery_if_necessary();
     }
 
+    /// Sets the minimum increase of the value that is sent to the peer
+    pub fn set_threshold(&mut self, threshold: T) {
+        self.threshold = threshold;
+        self.request_delivery_if_necessary();
+    }
+
     /// Stop to synchronize the value to the peer
     pub fn stop_sync(&mut self) {
         self.delivery.cancel();
@@ -94,6 +94,14 @@ impl<T: Copy + Clone + Default + Eq + PartialEq + PartialOrd, S: ValueToFrameWri
         self.delivered = false;
     }
 
+    /// Stop to synchronize the value and forget the latest requested value
+    ///
+    /// This allows a lower value to be requested afterwards.
+    pub fn reset(&mut self) {
+        self.stop_sync();
+        self.latest_value = T::default();
+    }
+
     /// This method gets called when a packet delivery got acknowledged
     pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
         // If the packet containing the frame gets acknowledged, schedule a delivery for the
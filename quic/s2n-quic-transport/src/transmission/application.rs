@@ -7,7 +7,7 @@ use crate::{
     endpoint, path,
     path::mtu,
     recovery,
-    space::{rx_packet_numbers::AckManager, HandshakeStatus},
+    space::{datagram, rx_packet_numbers::AckManager, CryptoStream, HandshakeStatus},
     stream::{AbstractStreamManager, StreamTrait as Stream},
     sync::{flag, flag::Ping},
     transmission::{self, Mode},
@@ -34,7 +34,9 @@ impl<'a, Config: endpoint::Config> Payload<'a, Config> {
         ack_manager: &'a mut AckManager,
         handshake_status: &'a mut HandshakeStatus,
         ping: &'a mut flag::Ping,
+        crypto_stream: &'a mut CryptoStream,
         stream_manager: &'a mut AbstractStreamManager<Config::Stream>,
+        datagram_manager: &'a mut datagram::Manager,
         recovery_manager: &'a mut recovery::Manager<Config>,
     ) -> Self {
         if transmission_mode != Mode::PathValidationOnly {
@@ -47,7 +49,9 @@ impl<'a, Config: endpoint::Config> Payload<'a, Config> {
                     ack_manager,
                     handshake_status,
                     ping,
+                    crypto_stream,
                     stream_manager,
+                    datagram_manager,
                     local_id_registry,
                     path_manager,
                     recovery_manager,
@@ -101,7 +105,9 @@ pub struct Normal<'a, S: Stream, Config: endpoint::Config> {
     ack_manager: &'a mut AckManager,
     handshake_status: &'a mut HandshakeStatus,
     ping: &'a mut Ping,
+    crypto_stream: &'a mut CryptoStream,
     stream_manager: &'a mut AbstractStreamManager<S>,
+    datagram_manager: &'a mut datagram::Manager,
     local_id_registry: &'a mut connection::LocalIdRegistry,
     path_manager: &'a mut path::Manager<Config>,
     recovery_manager: &'a mut recovery::Manager<Config>,
@@ -119,6 +125,9 @@ impl<'a, S: Stream, Config: endpoint::Config> Normal<'a, S, Config> {
             // soon as possible
             self.handshake_status.on_transmit(context);
 
+            // post-handshake messages, such as session tickets, are sent ahead of application data
+            let _ = self.crypto_stream.tx.on_transmit((), context);
+
             //= https://www.rfc-editor.org/rfc/rfc9000#section-8.2
             //# An endpoint MAY include other frames with the PATH_CHALLENGE and
             //# PATH_RESPONSE frames used for path validation.
@@ -129,6 +138,17 @@ impl<'a, S: Stream, Config: endpoint::Config> Normal<'a, S, Config> {
 
             self.path_manager.on_transmit(context);
 
+            // Datagrams are written ahead of stream data since they are typically used for
+            // latency-sensitive application data
+            let max_packet_payload = self
+                .path_manager
+                .active_path()
+                .mtu_controller
+                .mtu()
+                .saturating_sub(context.header_len() + context.tag_len());
+            self.datagram_manager
+                .on_transmit(context, max_packet_payload);
+
             let _ = self.stream_manager.on_transmit(context);
 
             // send PINGs last, since they might not actually be needed if there's an ack-eliciting
@@ -153,7 +173,9 @@ impl<'a, S: Stream, Config: endpoint::Config> transmission::interest::Provider
     ) -> transmission::interest::Result {
         self.ack_manager.transmission_interest(query)?;
         self.handshake_status.transmission_interest(query)?;
+        self.crypto_stream.transmission_interest(query)?;
         self.stream_manager.transmission_interest(query)?;
+        self.datagram_manager.transmission_interest(query)?;
         self.local_id_registry.transmission_interest(query)?;
         self.path_manager.transmission_interest(query)?;
         self.recovery_manager.transmission_interest(query)?;
@@ -2,22 +2,67 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::transmission::{self, WriteContext};
-use core::ops::RangeInclusive;
+use core::{convert::TryFrom, ops::RangeInclusive};
 use s2n_codec::EncoderValue;
-use s2n_quic_core::{frame, packet::number::PacketNumberSpace};
+use s2n_quic_core::{frame, packet::number::PacketNumberSpace, varint::VarInt};
 
 pub struct Payload<'a> {
     pub connection_close: &'a frame::ConnectionClose<'a>,
     pub packet_number_space: PacketNumberSpace,
 }
 
+impl<'a> Payload<'a> {
+    /// Returns the frame with the reason phrase truncated to fit in `capacity`
+    fn fitted_frame(&self, capacity: usize) -> frame::ConnectionClose<'a> {
+        let mut frame = *self.connection_close;
+
+        let reason = if let Some(reason) = frame.reason {
+            reason
+        } else {
+            return frame;
+        };
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-19.19
+        //# Because a CONNECTION_CLOSE
+        //# frame cannot be split between packets, any limits on packet size
+        //# will also limit the space available for a reason phrase.
+        let overhead = frame::ConnectionClose {
+            reason: None,
+            ..frame
+        }
+        .encoding_size();
+        // the overhead includes a single byte for the length prefix
+        let available = capacity.saturating_sub(overhead - 1);
+        let prefix_len = VarInt::try_from(available).map_or(8, |len| len.encoding_size());
+        let len = reason.len().min(available.saturating_sub(prefix_len));
+
+        if len == reason.len() {
+            return frame;
+        }
+
+        let mut reason = &reason[..len];
+
+        // avoid splitting a UTF-8 character at the end of the reason
+        if let Err(error) = core::str::from_utf8(reason) {
+            if error.error_len().is_none() {
+                reason = &reason[..error.valid_up_to()];
+            }
+        }
+
+        frame.reason = Some(reason).filter(|reason| !reason.is_empty());
+        frame
+    }
+}
+
 impl<'a> super::Payload for Payload<'a> {
     fn size_hint(&self, range: RangeInclusive<usize>) -> usize {
-        (*range.start()).max(self.connection_close.encoding_size())
+        let frame = self.fitted_frame(*range.end());
+        (*range.start()).max(frame.encoding_size())
     }
 
     fn on_transmit<W: WriteContext>(&mut self, context: &mut W) {
-        context.write_frame(self.connection_close);
+        let frame = self.fitted_frame(context.remaining_capacity());
+        context.write_frame(&frame);
     }
 
     fn packet_number_space(&self) -> PacketNumberSpace {
@@ -40,3 +85,37 @@ impl<'a> transmission::interest::Provider for Payload<'a> {
         query.on_forced()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reason_truncation_test() {
+        let reason = "closing connection: ünexpected".as_bytes();
+        let connection_close = frame::ConnectionClose {
+            error_code: VarInt::from_u8(1),
+            frame_type: None,
+            reason: Some(reason),
+        };
+        let payload = Payload {
+            connection_close: &connection_close,
+            packet_number_space: PacketNumberSpace::ApplicationData,
+        };
+
+        let len = connection_close.encoding_size();
+        assert_eq!(payload.fitted_frame(len), connection_close);
+        assert_eq!(payload.fitted_frame(len + 100), connection_close);
+
+        for capacity in 0..len {
+            let frame = payload.fitted_frame(capacity);
+            let truncated = frame.reason.unwrap_or_default();
+
+            assert!(reason.starts_with(truncated));
+            assert!(core::str::from_utf8(truncated).is_ok());
+            assert_eq!(frame.error_code, connection_close.error_code);
+            // frames only exceed the capacity if the error code doesn't fit
+            assert!(frame.encoding_size() <= capacity.max(3));
+        }
+    }
+}
@@ -163,6 +163,11 @@ impl<'a, 'b, 'sub, Config: endpoint::Config> WriteContext for Context<'a, 'b, 's
     fn tag_len(&self) -> usize {
         self.tag_len
     }
+
+    #[inline]
+    fn on_data_retransmitted(&mut self, len: usize) {
+        self.outcome.bytes_retransmitted += len;
+    }
 }
 
 // Overrides a context's transmission constraint to allow only retransmissions to be written to
@@ -253,4 +258,9 @@ impl<'a, C: WriteContext> WriteContext for RetransmissionContext<'a, C> {
     fn tag_len(&self) -> usize {
         self.context.tag_len()
     }
+
+    #[inline]
+    fn on_data_retransmitted(&mut self, len: usize) {
+        self.context.on_data_retransmitted(len)
+    }
 }
@@ -8,6 +8,7 @@ pub mod application;
 pub mod connection_close;
 pub mod early;
 pub mod interest;
+pub mod zero_rtt;
 
 pub use crate::contexts::WriteContext;
 pub use interest::Interest;
@@ -0,0 +1,60 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    contexts::WriteContext,
+    endpoint, recovery,
+    stream::{AbstractStreamManager, StreamTrait as Stream},
+    transmission,
+};
+use core::ops::RangeInclusive;
+use s2n_quic_core::packet::number::PacketNumberSpace;
+
+pub struct Payload<'a, S: Stream, Config: endpoint::Config> {
+    pub stream_manager: &'a mut AbstractStreamManager<S>,
+    pub recovery_manager: &'a mut recovery::Manager<Config>,
+}
+
+impl<'a, S: Stream, Config: endpoint::Config> super::Payload for Payload<'a, S, Config> {
+    fn size_hint(&self, range: RangeInclusive<usize>) -> usize {
+        (*range.start()).max(1)
+    }
+
+    fn on_transmit<W: WriteContext>(&mut self, context: &mut W) {
+        debug_assert!(
+            !context.transmission_mode().is_mtu_probing(),
+            "0-RTT transmissions should not be used for MTU probing"
+        );
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-12.5
+        //# Note that it is not possible to send the following frames in 0-RTT
+        //# packets for various reasons: ACK, CRYPTO, HANDSHAKE_DONE, NEW_TOKEN,
+        //# PATH_RESPONSE, and RETIRE_CONNECTION_ID.
+        if context.transmission_constraint().can_transmit()
+            || context.transmission_constraint().can_retransmit()
+        {
+            let _ = self.stream_manager.on_transmit(context);
+
+            // send PINGs last, since they might not actually be needed if there's an ack-eliciting
+            // frame already present in the payload
+            self.recovery_manager.on_transmit(context);
+        }
+    }
+
+    fn packet_number_space(&self) -> PacketNumberSpace {
+        PacketNumberSpace::ApplicationData
+    }
+}
+
+impl<'a, S: Stream, Config: endpoint::Config> transmission::interest::Provider
+    for Payload<'a, S, Config>
+{
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        self.stream_manager.transmission_interest(query)?;
+        self.recovery_manager.transmission_interest(query)?;
+        Ok(())
+    }
+}
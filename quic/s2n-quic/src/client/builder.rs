@@ -126,6 +126,123 @@ impl<Providers: ClientProviders> Builder<Providers> {
         ClientProviders
     );
 
+    impl_provider_method!(
+        /// Sets the custom transport parameters provider for the [`Client`]
+        ///
+        /// The parameters are sent to the peer during the handshake of every connection. The
+        /// values sent by the peer can be read with
+        /// [`Connection::peer_custom_transport_parameters`](crate::Connection::peer_custom_transport_parameters).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{Client, provider::custom_transport_parameters::CustomTransportParameters};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let parameters = CustomTransportParameters::default().with(0x7a3c, &b"feature-x"[..])?;
+        ///
+        /// let client = Client::builder()
+        ///     .with_custom_transport_parameters(parameters)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_custom_transport_parameters,
+        custom_transport_parameters,
+        ClientProviders
+    );
+
+    impl_provider_method!(
+        /// Sets the connection close formatter provider for the [`Client`]
+        ///
+        /// The formatter controls the error codes and reasons sent to the peer in
+        /// CONNECTION_CLOSE frames. The default formatter removes details that could be
+        /// sensitive, such as the reasons of transport errors.
+        ///
+        /// # Examples
+        ///
+        /// Sends errors to the peer unmodified, which can help while developing an application.
+        ///
+        /// ```rust,no_run
+        /// # use std::{error::Error, path::Path};
+        /// use s2n_quic::{Client, provider::connection_close_formatter};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let client = Client::builder()
+        ///     .with_tls(Path::new("./certs/cert.pem"))?
+        ///     .with_connection_close_formatter(connection_close_formatter::Development)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        ///
+        /// Hides the reasons of all errors
+        ///
+        /// ```rust,no_run
+        /// # use std::{error::Error, path::Path};
+        /// use s2n_quic::{
+        ///     provider::connection_close_formatter::{
+        ///         ApplicationError, ConnectionClose, Context, Formatter, Production, TransportError,
+        ///     },
+        ///     Client,
+        /// };
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// struct MyFormatter;
+        ///
+        /// impl Formatter for MyFormatter {
+        ///     fn format_transport_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: TransportError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_transport_error(context, error)
+        ///     }
+        ///
+        ///     fn format_application_error<'a>(
+        ///         &'a self,
+        ///         _context: &Context<'a>,
+        ///         error: ApplicationError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         error.into()
+        ///     }
+        ///
+        ///     fn format_early_transport_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: TransportError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_early_transport_error(context, error)
+        ///     }
+        ///
+        ///     fn format_early_application_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: ApplicationError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_early_application_error(context, error)
+        ///     }
+        /// }
+        ///
+        /// let client = Client::builder()
+        ///     .with_tls(Path::new("./certs/cert.pem"))?
+        ///     .with_connection_close_formatter(MyFormatter)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_connection_close_formatter,
+        connection_close_formatter,
+        ClientProviders
+    );
+
     impl_provider_method!(
         /// Sets the event provider for the [`Client`]
         ///
@@ -151,6 +268,47 @@ impl<Providers: ClientProviders> Builder<Providers> {
         ClientProviders
     );
 
+    impl_provider_method!(
+        /// Sets the congestion controller provider for the [`Client`]
+        ///
+        /// # Examples
+        ///
+        /// Uses the BBRv2 congestion controller instead of the default CUBIC controller
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{Client, provider::congestion_controller};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let client = Client::builder()
+        ///     .with_congestion_controller(congestion_controller::bbr::Provider::default())?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        ///
+        /// Sets a custom congestion controller for the client
+        ///
+        /// ```rust,ignore
+        /// # use std::error::Error;
+        /// use s2n_quic::Client;
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let client = Client::builder()
+        ///     .with_congestion_controller(MyCongestionControllerEndpoint::new())?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_congestion_controller,
+        congestion_controller,
+        ClientProviders
+    );
+
     impl_provider_method!(
         /// Sets the IO provider for the [`Client`]
         ///
@@ -250,15 +408,15 @@ impl<Providers: ClientProviders> Builder<Providers> {
         /// Sets the packet interceptor provider for the [`Client`]
         with_packet_interceptor,
         packet_interceptor,
-        ServerProviders
+        ClientProviders
     );
 
-    #[cfg(all(s2n_quic_unstable, feature = "unstable-provider-random"))]
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable-provider-random")))]
     impl_provider_method!(
         /// Sets the random provider for the [`Client`]
         with_random,
         random,
-        ServerProviders
+        ClientProviders
     );
 
     /// Starts the [`Client`] with the configured providers
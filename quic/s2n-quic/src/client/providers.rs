@@ -12,6 +12,7 @@ impl_providers_state! {
         congestion_controller: CongestionController,
         connection_close_formatter: ConnectionCloseFormatter,
         connection_id: ConnectionID,
+        custom_transport_parameters: CustomTransportParameters,
         packet_interceptor: PacketInterceptor,
         stateless_reset_token: StatelessResetToken,
         random: Random,
@@ -30,6 +31,7 @@ impl<
         CongestionController: congestion_controller::Provider,
         ConnectionCloseFormatter: connection_close_formatter::Provider,
         ConnectionID: connection_id::Provider,
+        CustomTransportParameters: custom_transport_parameters::Provider,
         PacketInterceptor: packet_interceptor::Provider,
         StatelessResetToken: stateless_reset_token::Provider,
         Random: random::Provider,
@@ -43,6 +45,7 @@ impl<
         CongestionController,
         ConnectionCloseFormatter,
         ConnectionID,
+        CustomTransportParameters,
         PacketInterceptor,
         StatelessResetToken,
         Random,
@@ -58,6 +61,7 @@ impl<
             congestion_controller,
             connection_close_formatter,
             connection_id,
+            custom_transport_parameters,
             packet_interceptor,
             stateless_reset_token,
             random,
@@ -73,6 +77,9 @@ impl<
             .start()
             .map_err(StartError::new)?;
         let connection_id = connection_id.start().map_err(StartError::new)?;
+        let custom_transport_parameters = custom_transport_parameters
+            .start()
+            .map_err(StartError::new)?;
         let packet_interceptor = packet_interceptor.start().map_err(StartError::new)?;
         let stateless_reset_token = stateless_reset_token.start().map_err(StartError::new)?;
         let random = random.start().map_err(StartError::new)?;
@@ -82,6 +89,7 @@ impl<
         let token = Token;
         let sync = sync.start().map_err(StartError::new)?;
         let path_migration = PathMigration;
+        let early_data = EarlyData;
         let tls = tls.start_client().map_err(StartError::new)?;
 
         // Validate providers
@@ -100,6 +108,7 @@ impl<
             congestion_controller,
             connection_close_formatter,
             connection_id,
+            custom_transport_parameters,
             packet_interceptor,
             stateless_reset_token,
             random,
@@ -111,6 +120,7 @@ impl<
             token,
             path_handle: PhantomData,
             path_migration,
+            early_data,
         };
 
         let (endpoint, connector) = endpoint::Endpoint::new_client(endpoint_config);
@@ -182,6 +192,18 @@ impl crate::provider::path_migration::Validator for PathMigration {
     }
 }
 
+#[derive(Debug)]
+struct EarlyData;
+
+impl crate::provider::early_data::Policy for EarlyData {
+    fn on_early_data(
+        &mut self,
+        _attempt: &crate::provider::early_data::Attempt,
+    ) -> crate::provider::early_data::Outcome {
+        unreachable!("early data policies should not be used with clients")
+    }
+}
+
 #[allow(dead_code)] // don't warn on unused providers for now
 struct EndpointConfig<
     CongestionController,
@@ -199,6 +221,7 @@ struct EndpointConfig<
     congestion_controller: CongestionController,
     connection_close_formatter: ConnectionCloseFormatter,
     connection_id: ConnectionID,
+    custom_transport_parameters: custom_transport_parameters::CustomTransportParameters,
     packet_interceptor: PacketInterceptor,
     stateless_reset_token: StatelessResetToken,
     random: Random,
@@ -210,6 +233,7 @@ struct EndpointConfig<
     token: Token,
     path_handle: PhantomData<PathHandle>,
     path_migration: PathMigration,
+    early_data: EarlyData,
 }
 
 impl<
@@ -288,6 +312,7 @@ impl<
     type Stream = stream::StreamImpl;
     type PathMigrationValidator = PathMigration;
     type PacketInterceptor = PacketInterceptor;
+    type EarlyDataPolicy = EarlyData;
 
     const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Client;
 
@@ -296,6 +321,7 @@ impl<
             congestion_controller: &mut self.congestion_controller,
             connection_close_formatter: &mut self.connection_close_formatter,
             connection_id_format: &mut self.connection_id,
+            custom_transport_parameters: &self.custom_transport_parameters,
             packet_interceptor: &mut self.packet_interceptor,
             stateless_reset_token_generator: &mut self.stateless_reset_token,
             random_generator: &mut self.random,
@@ -305,6 +331,7 @@ impl<
             connection_limits: &mut self.limits,
             event_subscriber: &mut self.event,
             path_migration: &mut self.path_migration,
+            early_data: &mut self.early_data,
         }
     }
 }
@@ -11,7 +11,10 @@ mod handle;
 
 pub use acceptor::*;
 pub use handle::*;
-pub use s2n_quic_core::connection::Error;
+pub use s2n_quic_core::{
+    connection::{Error, Stats},
+    path::migration::MigrationError,
+};
 
 pub mod error {
     pub use s2n_quic_core::transport::error::Code;
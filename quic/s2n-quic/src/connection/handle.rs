@@ -149,6 +149,39 @@ macro_rules! impl_handle_api {
             self.0.remote_address().map(std::net::SocketAddr::from)
         }
 
+        /// Migrates the connection to a new source IP address
+        ///
+        /// This allows a client to move the connection to another network interface, e.g.
+        /// when switching from Wi-Fi to a cellular network. Once the handshake is confirmed,
+        /// the connection starts sending from `local_ip` using an unused connection ID
+        /// provided by the peer, and validates the new path with a PATH_CHALLENGE. If the
+        /// path fails validation, the connection falls back to the last validated path.
+        ///
+        /// Only the source IP address of the packets changes; they are still sent and received
+        /// on the socket, and therefore the port, of the IO provider. The default provider
+        /// selects the source IP address of each packet on platforms that support
+        /// `IP_PKTINFO`.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # fn test() -> Result<(), s2n_quic::connection::MigrationError> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// // move the connection to the address of the cellular interface
+        /// connection.migrate("10.0.0.2".parse().unwrap())?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn migrate(
+            &mut self,
+            local_ip: std::net::IpAddr,
+        ) -> Result<(), $crate::connection::MigrationError> {
+            self.0.migrate(local_ip.into())
+        }
+
         /// Returns the negotiated server name the connection is using.
         #[inline]
         pub fn server_name(&self) -> $crate::connection::Result<Option<$crate::server::Name>> {
@@ -161,6 +194,89 @@ macro_rules! impl_handle_api {
             self.0.application_protocol()
         }
 
+        /// Returns the custom transport parameters sent by the peer
+        ///
+        /// The parameters are available once the peer's transport parameters have been received
+        /// during the handshake. Until then, the returned set is empty.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// let parameters = connection.peer_custom_transport_parameters()?;
+        ///
+        /// if let Some(value) = parameters.get(0x7a3c) {
+        ///     println!("peer supports feature-x: {:?}", value);
+        /// }
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn peer_custom_transport_parameters(
+            &self,
+        ) -> $crate::connection::Result<
+            $crate::provider::custom_transport_parameters::CustomTransportParameters,
+        > {
+            self.0.peer_custom_transport_parameters()
+        }
+
+        /// Returns the DER-encoded certificate chain presented by the peer
+        ///
+        /// The chain starts with the peer's own certificate. It is empty until the handshake
+        /// completes, and for clients that don't authenticate with a certificate. A server
+        /// that requires client authentication can use this to identify the client.
+        ///
+        /// The chain is also empty for resumed sessions with TLS providers that don't store the
+        /// peer's certificates in session tickets, such as s2n-tls. See
+        /// [`is_resumed`](Self::is_resumed).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// if let Some(client_certificate) = connection.peer_certificate_chain()?.first() {
+        ///     println!("client certificate: {} bytes", client_certificate.len());
+        /// }
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn peer_certificate_chain(
+            &self,
+        ) -> $crate::connection::Result<::std::sync::Arc<[::bytes::Bytes]>> {
+            Ok(self.0.handshake_info()?.map_or_else(
+                || ::std::sync::Arc::from(&[][..]),
+                |info| info.peer_certificate_chain,
+            ))
+        }
+
+        /// Returns the negotiated cipher suite, once the handshake has completed
+        #[inline]
+        pub fn cipher_suite(
+            &self,
+        ) -> $crate::connection::Result<Option<$crate::provider::tls::CipherSuite>> {
+            Ok(self.0.handshake_info()?.map(|info| info.cipher_suite))
+        }
+
+        /// Returns `true` if the TLS session was resumed with a session ticket
+        ///
+        /// This is `false` until the handshake has completed. The peer doesn't present its
+        /// certificates when resuming, so s2n-tls returns an empty
+        /// [`peer_certificate_chain`](Self::peer_certificate_chain) for resumed sessions.
+        #[inline]
+        pub fn is_resumed(&self) -> $crate::connection::Result<bool> {
+            Ok(self
+                .0
+                .handshake_info()?
+                .map_or(false, |info| info.is_resumed))
+        }
+
         /// Returns the internal identifier for the [`Connection`](`crate::Connection`)
         ///
         /// Note: This internal identifier is not the same as the connection ID included in packet
@@ -186,6 +302,180 @@ macro_rules! impl_handle_api {
             self.0.keep_alive(enabled)
         }
 
+        /// Changes the limits of the connection
+        ///
+        /// The limits set with [`Limits`](crate::provider::limits::Limits) apply when the
+        /// connection is created. This allows them to be adjusted afterwards, for example to let
+        /// a client open more streams once the application has authenticated it. Only the values
+        /// set on the [`Update`](crate::provider::limits::Update) are changed.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # fn test() -> Result<(), Box<dyn std::error::Error>> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// use s2n_quic::provider::limits::Update;
+        ///
+        /// let update = Update::new()
+        ///     .with_max_open_remote_bidirectional_streams(1000)?
+        ///     .with_data_window(64 * 1024 * 1024)?;
+        /// connection.update_limits(update)?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn update_limits(
+            &mut self,
+            update: $crate::provider::limits::Update,
+        ) -> $crate::connection::Result<()> {
+            self.0.update_limits(update)
+        }
+
+        /// Initiates an update of the 1-RTT packet protection keys
+        ///
+        /// Keys are also updated automatically as they approach the limits configured with
+        /// [`Limits::with_max_packets_per_key`](crate::provider::limits::Limits::with_max_packets_per_key).
+        /// This can be used to rotate keys on a schedule, for example on connections that stay
+        /// open for days.
+        ///
+        /// The update is initiated with the next packet sent once the handshake is confirmed,
+        /// and a PING is sent to ensure it happens promptly. Completion is reported through the
+        /// [`KeyUpdate`](crate::provider::event::events::KeyUpdate) event once the peer responds
+        /// with the new keys. Requests made while a previous update is in progress are deferred
+        /// until that update completes.
+        #[inline]
+        pub fn update_keys(&mut self) -> $crate::connection::Result<()> {
+            self.0.update_keys()
+        }
+
+        /// Returns a snapshot of the connection statistics
+        ///
+        /// The snapshot includes the RTT estimates, congestion window, pacing rate and MTU of the
+        /// active path, along with packet, byte and ECN counters for the lifetime of the
+        /// connection.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// let stats = connection.stats()?;
+        /// println!(
+        ///     "rtt: {:?}, cwnd: {}, lost: {}",
+        ///     stats.smoothed_rtt, stats.congestion_window, stats.packets_lost
+        /// );
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn stats(&self) -> $crate::connection::Result<$crate::connection::Stats> {
+            self.0.stats()
+        }
+
+        /// Sends an unreliable datagram to the peer
+        ///
+        /// The datagram is queued and sent in the next available packet. Datagrams are not
+        /// retransmitted if lost and may be delivered out of order. If the send queue is full,
+        /// the call will wait until the connection is able to queue the datagram.
+        ///
+        /// The method will return
+        /// - `Ok(())` if the datagram was queued for sending
+        /// - `Err(datagram_error)` if the datagram could not be sent, either because the peer
+        ///   does not support datagrams, the datagram is too large, or the connection was closed
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::datagram::Result<()> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// connection.send_datagram(bytes::Bytes::from_static(&[1, 2, 3])).await?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub async fn send_datagram(
+            &mut self,
+            data: ::bytes::Bytes,
+        ) -> $crate::datagram::Result<()> {
+            let mut data = data;
+            futures::future::poll_fn(|cx| self.poll_send_datagram(&mut data, cx)).await
+        }
+
+        /// Polls sending an unreliable datagram to the peer
+        ///
+        /// The method will return
+        /// - `Poll::Ready(Ok(()))` if the datagram was queued for sending. In this case `data`
+        ///   is replaced with an empty buffer.
+        /// - `Poll::Ready(Err(datagram_error))` if the datagram could not be sent
+        /// - `Poll::Pending` if the send queue is currently full
+        #[inline]
+        pub fn poll_send_datagram(
+            &mut self,
+            data: &mut ::bytes::Bytes,
+            cx: &mut core::task::Context,
+        ) -> core::task::Poll<$crate::datagram::Result<()>> {
+            self.0.poll_send_datagram(data, cx)
+        }
+
+        /// Receives an unreliable datagram from the peer
+        ///
+        /// The method will return
+        /// - `Ok(Some(datagram))` if a datagram was received
+        /// - `Ok(None)` if the connection was closed without an error
+        /// - `Err(datagram_error)` if no datagram could be received due to an error
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::datagram::Result<()> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// while let Some(datagram) = connection.receive_datagram().await? {
+        ///     println!("received datagram of {} bytes", datagram.len());
+        /// }
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub async fn receive_datagram(
+            &mut self,
+        ) -> $crate::datagram::Result<Option<::bytes::Bytes>> {
+            futures::future::poll_fn(|cx| self.poll_receive_datagram(cx)).await
+        }
+
+        /// Polls receiving an unreliable datagram from the peer
+        ///
+        /// The method will return
+        /// - `Poll::Ready(Ok(Some(datagram)))` if a datagram was received
+        /// - `Poll::Ready(Ok(None))` if the connection was closed without an error
+        /// - `Poll::Ready(Err(datagram_error))` if no datagram could be received due to an error
+        /// - `Poll::Pending` if no datagram has been received yet
+        #[inline]
+        pub fn poll_receive_datagram(
+            &mut self,
+            cx: &mut core::task::Context,
+        ) -> core::task::Poll<$crate::datagram::Result<Option<::bytes::Bytes>>> {
+            self.0.poll_receive_datagram(cx)
+        }
+
+        /// Returns the largest datagram payload, in bytes, that can currently be sent to the peer
+        ///
+        /// This value is bounded by the peer's `max_datagram_frame_size` transport parameter as
+        /// well as the MTU of the active path, so it may change over the lifetime of the
+        /// connection. If the peer did not advertise support for datagrams,
+        /// [`Error::NotSupported`]($crate::datagram::Error::NotSupported) is returned.
+        #[inline]
+        pub fn max_datagram_size(&self) -> $crate::datagram::Result<usize> {
+            self.0.max_datagram_size()
+        }
+
         /// Closes the Connection with the provided error code
         ///
         /// This will immediately terminate all outstanding streams.
@@ -207,6 +497,35 @@ macro_rules! impl_handle_api {
             self.0.close(error_code)
         }
 
+        /// Closes the Connection with the provided error code and reason phrase
+        ///
+        /// The reason is sent to the peer in the CONNECTION_CLOSE frame, which can help with
+        /// debugging why the connection was closed. It is truncated if it doesn't fit in the
+        /// packet, and can be modified or removed by the configured
+        /// [`connection_close_formatter`](crate::provider::connection_close_formatter).
+        ///
+        /// This will immediately terminate all outstanding streams.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// const MY_ERROR_CODE:u32 = 99;
+        /// connection.close_with_reason(MY_ERROR_CODE.into(), "unauthorized client");
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn close_with_reason(&self, error_code: $crate::application::Error, reason: &str) {
+            self.0.close_with_reason(
+                error_code,
+                ::bytes::Bytes::copy_from_slice(reason.as_bytes()),
+            )
+        }
+
         /// API for querying the connection's
         /// [`Subscriber::ConnectionContext`](crate::provider::event::Subscriber::ConnectionContext).
         ///
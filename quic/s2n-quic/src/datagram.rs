@@ -0,0 +1,12 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Unreliable datagrams, as defined in [RFC 9221](https://www.rfc-editor.org/rfc/rfc9221.html)
+//!
+//! Datagrams are only exchanged once both endpoints advertise support for them. The local
+//! endpoint enables receiving datagrams by configuring a maximum frame size with
+//! [`Limits::with_max_datagram_frame_size`](crate::provider::limits::Limits::with_max_datagram_frame_size).
+
+pub use s2n_quic_core::datagram::DatagramError as Error;
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -22,6 +22,20 @@
 //! that multiple servers handle address tokens, this provider should not be used. Instead, a custom
 //! implementation of [`provider::address_token::Format`] should be specified.
 //!
+//! ### `provider-connection-id-quic-lb`
+//!
+//! Enables the [QUIC-LB](https://datatracker.ietf.org/doc/draft-ietf-quic-load-balancers/)
+//! connection ID provider, which embeds a server ID in each connection ID so a stateless
+//! load balancer can route packets to the correct server after a client migrates to a new
+//! address. The provider will be available at [`provider::connection_id::quic_lb`].
+//!
+//! ### `provider-event-qlog`
+//!
+//! Enables the [qlog](https://datatracker.ietf.org/doc/draft-ietf-quic-qlog-main-schema/) event
+//! provider, which writes a JSON-SEQ trace for each connection that can be loaded into
+//! [qvis](https://qvis.quictools.info). The provider will be available at
+//! [`provider::event::qlog`].
+//!
 //! ### `provider-event-tracing`
 //!
 //! Enables event integration with [`tracing`](https://docs.rs/tracing). The
@@ -29,6 +43,20 @@
 //! endpoint and connection events to the application's configured
 //! [`tracing::Subscriber`](https://docs.rs/tracing/latest/tracing/trait.Subscriber.html).
 //!
+//! ### `provider-io-uring`
+//!
+//! Enables the [io_uring](https://kernel.dk/io_uring.pdf) IO provider on Linux, which keeps
+//! receive and transmit operations in flight on a ring shared with the kernel rather than calling
+//! `recvmmsg`/`sendmmsg` on each wakeup. The provider requires Linux 6.0 or later and will be
+//! available at [`provider::io::io_uring`].
+//!
+//! ### `provider-stateless-reset-token-hmac`
+//!
+//! Enables the stateless reset token provider which derives each token from a static key and
+//! the local connection ID with HMAC-SHA256. Servers sharing the key generate the same token for
+//! a connection ID, so any of them can reset a connection after losing its state. The provider
+//! will be available at [`provider::stateless_reset_token::hmac`].
+//!
 //! ### `provider-tls-default`
 //!
 //! _Enabled by default_
@@ -58,6 +86,7 @@ pub mod provider;
 
 pub mod client;
 pub mod connection;
+pub mod datagram;
 pub mod server;
 pub mod stream;
 
@@ -69,6 +98,9 @@ pub use client::Client;
 pub use connection::Connection;
 pub use server::Server;
 
+#[cfg(test)]
+mod tests;
+
 // Require `--cfg s2n_quic_unstable` is set when using unstable features
 #[cfg(
     all(
@@ -8,7 +8,11 @@ use core::fmt;
 mod macros;
 
 pub mod address_token;
+pub mod congestion_controller;
+pub mod connection_close_formatter;
 pub mod connection_id;
+pub mod custom_transport_parameters;
+pub mod early_data;
 pub mod endpoint_limits;
 pub mod event;
 pub mod io;
@@ -17,8 +21,6 @@ pub mod stateless_reset_token;
 pub mod tls;
 
 // These providers are not currently exposed to applications
-pub(crate) mod congestion_controller;
-pub(crate) mod connection_close_formatter;
 pub(crate) mod path_migration;
 pub(crate) mod sync;
 
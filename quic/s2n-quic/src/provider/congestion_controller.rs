@@ -1,8 +1,62 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-pub use s2n_quic_core::recovery::congestion_controller::{
-    CongestionController, Endpoint, PathInfo,
+//! Provides congestion controller support for an endpoint
+//!
+//! The endpoint calls [`Endpoint::new_congestion_controller`] for each new path, including the
+//! initial path of a connection. Each [`CongestionController`] is then driven by the connection
+//! that owns the path:
+//!
+//! * [`on_packet_sent`](CongestionController::on_packet_sent) is called for every packet sent on
+//!   the path. Packets containing only ACK frames report `0` sent bytes and are not congestion
+//!   controlled. The returned `PacketInfo` is stored with the packet until it is acknowledged,
+//!   declared lost or discarded.
+//! * When an ACK frame is received on the active path, the following are called in order:
+//!   1. [`on_rtt_update`](CongestionController::on_rtt_update), if the ACK frame produced a new
+//!      RTT sample.
+//!   2. [`on_packet_lost`](CongestionController::on_packet_lost) for each packet newly declared
+//!      lost.
+//!   3. [`on_congestion_event`](CongestionController::on_congestion_event), if the peer reported
+//!      an increase in ECN Congestion Experienced markings.
+//!   4. [`on_ack`](CongestionController::on_ack) with the bytes newly acknowledged on the path,
+//!      along with the `PacketInfo` of the newest acknowledged packet.
+//!
+//!   The ACK frame can also acknowledge packets that were sent on other paths, for example before
+//!   a migration. The controllers of those paths receive `on_ack` for each of their acknowledged
+//!   packets between steps 2 and 3, rather than after the active path. They do not receive
+//!   `on_rtt_update` or `on_congestion_event` for the ACK frame.
+//! * [`on_packet_lost`](CongestionController::on_packet_lost) is also called when the loss
+//!   timer expires. Lost packets are not followed by a call to
+//!   [`on_congestion_event`](CongestionController::on_congestion_event); controllers should
+//!   apply their congestion response from `on_packet_lost`.
+//! * [`on_packet_discarded`](CongestionController::on_packet_discarded) is called for packets
+//!   that no longer count toward bytes in flight without indicating congestion. This happens when
+//!   Initial or Handshake keys are discarded, when 0-RTT is rejected, and when an MTU probe is
+//!   lost.
+//! * [`on_mtu_update`](CongestionController::on_mtu_update) is called when the maximum datagram
+//!   size of the path changes.
+//!
+//! Before transmitting, the connection checks
+//! [`is_congestion_limited`](CongestionController::is_congestion_limited) and
+//! [`requires_fast_retransmission`](CongestionController::requires_fast_retransmission), and
+//! waits until [`earliest_departure_time`](CongestionController::earliest_departure_time) to pace
+//! packets.
+//!
+//! [`congestion_window`](CongestionController::congestion_window),
+//! [`bytes_in_flight`](CongestionController::bytes_in_flight) and
+//! [`pacing_rate`](CongestionController::pacing_rate) are also read when an application requests
+//! the connection statistics.
+//!
+//! All callbacks are invoked from the task driving the connection, so implementations do not
+//! need to be thread-safe beyond being `Send`.
+
+pub use s2n_quic_core::{
+    recovery::{
+        bandwidth::Bandwidth,
+        congestion_controller::{CongestionController, Endpoint, PathInfo},
+        RttEstimator,
+    },
+    time::Timestamp,
 };
 
 /// Provides congestion controller support for an endpoint
@@ -18,6 +72,15 @@ pub use default::Provider as Default;
 
 impl_provider_utils!();
 
+impl<T: Endpoint> Provider for T {
+    type Endpoint = T;
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<Self::Endpoint, Self::Error> {
+        Ok(self)
+    }
+}
+
 pub mod cubic {
     use s2n_quic_core::recovery::cubic::Endpoint;
 
@@ -33,3 +96,19 @@ pub mod cubic {
         }
     }
 }
+
+pub mod bbr {
+    use s2n_quic_core::recovery::bbr::Endpoint;
+
+    #[derive(Debug, Default)]
+    pub struct Provider(());
+
+    impl super::Provider for Provider {
+        type Endpoint = Endpoint;
+        type Error = core::convert::Infallible;
+
+        fn start(self) -> Result<Self::Endpoint, Self::Error> {
+            Ok(Endpoint::default())
+        }
+    }
+}
@@ -2,15 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Provider for formatting CONNECTION_CLOSE frames
+//!
+//! The formatter decides which error codes and reasons are sent to the peer when a connection
+//! is closed. [`Production`] is used by default and removes details which could be sensitive,
+//! while [`Development`] sends errors unmodified. Applications can also implement
+//! [`Formatter`] to apply their own policy.
 
-pub use s2n_quic_core::connection::close::*;
+pub use s2n_quic_core::{
+    application::Error as ApplicationError, connection::close::*,
+    transport::Error as TransportError,
+};
 
 /// Provider for formatting CONNECTION_CLOSE frames
 pub trait Provider: 'static {
     type Formatter: 'static + Formatter;
     type Error: core::fmt::Display;
 
-    /// Starts the token provider
+    /// Starts the formatter provider
     fn start(self) -> Result<Self::Formatter, Self::Error>;
 }
 
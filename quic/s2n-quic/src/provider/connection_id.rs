@@ -25,6 +25,10 @@ impl<T: 'static + Format> Provider for T {
     }
 }
 
+/// Routable connection Ids for QUIC-LB load balancers
+#[cfg(feature = "provider-connection-id-quic-lb")]
+pub mod quic_lb;
+
 pub mod default {
     use core::{
         convert::{Infallible, TryInto},
@@ -0,0 +1,726 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routable connection Ids, as specified in
+//! [QUIC-LB](https://datatracker.ietf.org/doc/draft-ietf-quic-load-balancers/)
+//!
+//! Each connection Id embeds a server Id, which allows a stateless load balancer to route
+//! packets to the correct server even after the client migrates to a new address or is
+//! subject to a NAT rebinding. The server Id can either be sent in plaintext or encrypted
+//! with a key shared between the servers and the load balancer.
+
+use aes::{Aes128, Block, BlockDecrypt as _, BlockEncrypt as _, NewBlockCipher as _};
+use core::{convert::TryInto, fmt, time::Duration};
+use rand::prelude::*;
+use s2n_quic_core::connection::{
+    self,
+    id::{ConnectionInfo, Generator, Validator},
+};
+
+/// The maximum length of a server Id
+pub const MAX_SERVER_ID_LEN: usize = 15;
+
+/// The minimum length of the nonce following the server Id
+pub const MIN_NONCE_LEN: usize = 4;
+
+/// The maximum value of the config rotation bits
+///
+/// `0b111` is reserved for connection Ids that are not routable by the load balancer.
+pub const MAX_CONFIG_ROTATION: u8 = 0b110;
+
+/// The length of the first octet, which carries the config rotation and length bits
+const FIRST_OCTET_LEN: usize = 1;
+
+/// The maximum length of the encoded server Id and nonce, excluding the first octet
+const MAX_PLAINTEXT_LEN: usize = connection::id::MAX_LEN - FIRST_OCTET_LEN;
+
+const CONFIG_ROTATION_SHIFT: u8 = 5;
+const LENGTH_MASK: u8 = 0b0001_1111;
+
+/// The length of a block that is encrypted with a single AES-128-ECB pass
+const SINGLE_PASS_LEN: usize = 16;
+
+/// The largest half of a four-pass encrypted connection Id
+const MAX_HALF_LEN: usize = (MAX_PLAINTEXT_LEN + 1) / 2;
+
+#[derive(Debug, Default)]
+pub struct Provider(Format);
+
+impl super::Provider for Provider {
+    type Format = Format;
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<Self::Format, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+/// An error returned when configuring a [`Format`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidConfigRotation,
+    InvalidServerId,
+    InvalidNonceLength,
+    InvalidLifetime,
+}
+
+impl Error {
+    fn message(&self) -> &'static str {
+        match self {
+            Error::InvalidConfigRotation => "invalid config rotation bits",
+            Error::InvalidServerId => "invalid server id length",
+            Error::InvalidNonceLength => "invalid nonce length",
+            Error::InvalidLifetime => "invalid connection id lifetime",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A server Id embedded in a routable connection Id
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ServerId {
+    bytes: [u8; MAX_SERVER_ID_LEN],
+    len: u8,
+}
+
+impl ServerId {
+    /// Creates a server Id from a slice of 1 to 15 bytes
+    pub fn new(bytes: &[u8]) -> Result<Self, Error> {
+        if !(1..=MAX_SERVER_ID_LEN).contains(&bytes.len()) {
+            return Err(Error::InvalidServerId);
+        }
+
+        let mut server_id = Self {
+            len: bytes.len() as u8,
+            ..Default::default()
+        };
+        server_id.bytes[..bytes.len()].copy_from_slice(bytes);
+        Ok(server_id)
+    }
+
+    /// Returns the length of the server Id
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+}
+
+impl AsRef<[u8]> for ServerId {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len()]
+    }
+}
+
+impl fmt::Debug for ServerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ServerId").field(&self.as_ref()).finish()
+    }
+}
+
+/// Connection Id format that embeds a server Id for load balancer routing
+///
+/// By default, connection Ids carry a single zero byte server Id in plaintext, followed
+/// by an 8 byte random nonce.
+pub struct Format {
+    config_rotation: u8,
+    server_id: ServerId,
+    nonce_len: usize,
+    cipher: Option<Aes128>,
+    lifetime: Option<Duration>,
+}
+
+impl fmt::Debug for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Format")
+            .field("config_rotation", &self.config_rotation)
+            .field("server_id", &self.server_id)
+            .field("nonce_len", &self.nonce_len)
+            .field("encrypted", &self.cipher.is_some())
+            .field("lifetime", &self.lifetime)
+            .finish()
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Builder::default()
+            .build()
+            .expect("default builder should be valid")
+    }
+}
+
+impl Format {
+    /// Creates a builder for the format
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Returns the server Id that this format embeds in generated connection Ids
+    pub fn server_id(&self) -> &ServerId {
+        &self.server_id
+    }
+
+    /// Returns the length of connection Ids generated by this format
+    #[inline]
+    fn len(&self) -> usize {
+        FIRST_OCTET_LEN + self.server_id.len() + self.nonce_len
+    }
+
+    /// Decodes the server Id from a connection Id generated with the same configuration
+    ///
+    /// This is the operation a load balancer performs to route a packet. `None` is returned
+    /// if the connection Id was not generated with the current config rotation or length.
+    pub fn decode_server_id(&self, connection_id: &[u8]) -> Option<ServerId> {
+        let len = self.validate_len(connection_id)?;
+
+        let first_octet = connection_id[0];
+        if first_octet >> CONFIG_ROTATION_SHIFT != self.config_rotation || len != self.len() {
+            return None;
+        }
+
+        let mut plaintext = [0u8; MAX_PLAINTEXT_LEN];
+        let plaintext = &mut plaintext[..len - FIRST_OCTET_LEN];
+        plaintext.copy_from_slice(&connection_id[FIRST_OCTET_LEN..len]);
+
+        if let Some(cipher) = self.cipher.as_ref() {
+            decrypt(cipher, plaintext);
+        }
+
+        ServerId::new(&plaintext[..self.server_id.len()]).ok()
+    }
+
+    /// Reads the connection Id length that is self-encoded in the first octet
+    #[inline]
+    fn validate_len(&self, buffer: &[u8]) -> Option<usize> {
+        let first_octet = buffer.first()?;
+        let len = (first_octet & LENGTH_MASK) as usize + FIRST_OCTET_LEN;
+
+        if (connection::LocalId::MIN_LEN..=connection::id::MAX_LEN).contains(&len)
+            && buffer.len() >= len
+        {
+            Some(len)
+        } else {
+            None
+        }
+    }
+}
+
+/// A builder for [`Format`] providers
+#[derive(Debug)]
+pub struct Builder {
+    config_rotation: u8,
+    server_id: ServerId,
+    nonce_len: usize,
+    key: Option<Key>,
+    lifetime: Option<Duration>,
+}
+
+/// An AES-128 key that is redacted from debug output
+struct Key([u8; 16]);
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            config_rotation: 0,
+            server_id: ServerId::new(&[0]).expect("valid server id"),
+            nonce_len: 8,
+            key: None,
+            lifetime: None,
+        }
+    }
+}
+
+impl Builder {
+    /// Sets the config rotation bits that are encoded in the first octet of each connection Id
+    ///
+    /// Load balancers use these bits to distinguish connection Ids generated with an
+    /// older configuration during a key or server Id rotation. Values must be in the
+    /// range `0..=6`.
+    pub fn with_config_rotation(mut self, config_rotation: u8) -> Result<Self, Error> {
+        if config_rotation > MAX_CONFIG_ROTATION {
+            return Err(Error::InvalidConfigRotation);
+        }
+        self.config_rotation = config_rotation;
+        Ok(self)
+    }
+
+    /// Sets the server Id that is embedded in each connection Id
+    pub fn with_server_id(mut self, server_id: &[u8]) -> Result<Self, Error> {
+        self.server_id = ServerId::new(server_id)?;
+        Ok(self)
+    }
+
+    /// Sets the length of the nonce that follows the server Id
+    ///
+    /// The nonce is randomly generated and must be at least 4 bytes.
+    pub fn with_nonce_len(mut self, nonce_len: usize) -> Result<Self, Error> {
+        if !(MIN_NONCE_LEN..=MAX_PLAINTEXT_LEN - 1).contains(&nonce_len) {
+            return Err(Error::InvalidNonceLength);
+        }
+        self.nonce_len = nonce_len;
+        Ok(self)
+    }
+
+    /// Sets the AES-128 key shared with the load balancer
+    ///
+    /// When a key is configured, the server Id and nonce are encrypted so they
+    /// cannot be linked by observers. Otherwise, they are sent in plaintext.
+    pub fn with_key(mut self, key: [u8; 16]) -> Self {
+        self.key = Some(Key(key));
+        self
+    }
+
+    /// Sets the lifetime of each generated connection Id
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Result<Self, Error> {
+        if !(connection::id::MIN_LIFETIME..=connection::id::MAX_LIFETIME).contains(&lifetime) {
+            return Err(Error::InvalidLifetime);
+        }
+        self.lifetime = Some(lifetime);
+        Ok(self)
+    }
+
+    /// Builds the [`Format`] into a provider
+    pub fn build(self) -> Result<Format, Error> {
+        // The server Id and nonce must fit in a connection Id along with the first octet
+        if self.server_id.len() + self.nonce_len > MAX_PLAINTEXT_LEN {
+            return Err(Error::InvalidNonceLength);
+        }
+
+        Ok(Format {
+            config_rotation: self.config_rotation,
+            server_id: self.server_id,
+            nonce_len: self.nonce_len,
+            cipher: self.key.map(|key| Aes128::new(&key.0.into())),
+            lifetime: self.lifetime,
+        })
+    }
+}
+
+impl Generator for Format {
+    fn generate(&mut self, _connection_info: &ConnectionInfo) -> connection::LocalId {
+        let mut id = [0u8; connection::id::MAX_LEN];
+        let id = &mut id[..self.len()];
+
+        let (first_octet, plaintext) = id.split_at_mut(FIRST_OCTET_LEN);
+        let plaintext_len = plaintext.len();
+
+        first_octet[0] =
+            (self.config_rotation << CONFIG_ROTATION_SHIFT) | (plaintext_len as u8 & LENGTH_MASK);
+
+        let (server_id, nonce) = plaintext.split_at_mut(self.server_id.len());
+        server_id.copy_from_slice(self.server_id.as_ref());
+        rand::thread_rng().fill_bytes(nonce);
+
+        if let Some(cipher) = self.cipher.as_ref() {
+            encrypt(cipher, plaintext);
+        }
+
+        (&*id).try_into().expect("length already checked")
+    }
+
+    fn lifetime(&self) -> Option<Duration> {
+        self.lifetime
+    }
+}
+
+impl Validator for Format {
+    fn validate(&self, _connection_info: &ConnectionInfo, buffer: &[u8]) -> Option<usize> {
+        self.validate_len(buffer)
+    }
+}
+
+/// Encrypts the server Id and nonce in place
+///
+/// 16 byte inputs are encrypted with a single AES-128-ECB pass. All other lengths use
+/// a four-pass Feistel network with AES-128-ECB as the round function.
+fn encrypt(cipher: &Aes128, plaintext: &mut [u8]) {
+    if plaintext.len() == SINGLE_PASS_LEN {
+        let mut block = Block::default();
+        block.copy_from_slice(plaintext);
+        cipher.encrypt_block(&mut block);
+        plaintext.copy_from_slice(&block);
+        return;
+    }
+
+    let mut halves = Halves::new(plaintext);
+    halves.right_pass(cipher, 1);
+    halves.left_pass(cipher, 2);
+    halves.right_pass(cipher, 3);
+    halves.left_pass(cipher, 4);
+    halves.write(plaintext);
+}
+
+/// Decrypts the server Id and nonce in place
+fn decrypt(cipher: &Aes128, ciphertext: &mut [u8]) {
+    if ciphertext.len() == SINGLE_PASS_LEN {
+        let mut block = Block::default();
+        block.copy_from_slice(ciphertext);
+        cipher.decrypt_block(&mut block);
+        ciphertext.copy_from_slice(&block);
+        return;
+    }
+
+    let mut halves = Halves::new(ciphertext);
+    halves.left_pass(cipher, 4);
+    halves.right_pass(cipher, 3);
+    halves.left_pass(cipher, 2);
+    halves.right_pass(cipher, 1);
+    halves.write(ciphertext);
+}
+
+/// The two halves of a four-pass encrypted connection Id
+///
+/// For odd lengths, the middle octet is split between the halves: the left half
+/// holds the most significant 4 bits and the right half holds the least significant 4 bits.
+struct Halves {
+    left: [u8; MAX_HALF_LEN],
+    right: [u8; MAX_HALF_LEN],
+    half_len: usize,
+    plaintext_len: usize,
+}
+
+impl Halves {
+    fn new(input: &[u8]) -> Self {
+        let plaintext_len = input.len();
+        let half_len = (plaintext_len + 1) / 2;
+
+        let mut halves = Self {
+            left: [0; MAX_HALF_LEN],
+            right: [0; MAX_HALF_LEN],
+            half_len,
+            plaintext_len,
+        };
+
+        halves.left[..half_len].copy_from_slice(&input[..half_len]);
+        halves.right[..half_len].copy_from_slice(&input[plaintext_len - half_len..]);
+
+        if halves.is_odd() {
+            halves.left[half_len - 1] &= 0xf0;
+            halves.right[0] &= 0x0f;
+        }
+
+        halves
+    }
+
+    #[inline]
+    fn is_odd(&self) -> bool {
+        self.plaintext_len % 2 == 1
+    }
+
+    /// Combines the halves into the output, joining the middle octet for odd lengths
+    fn write(&self, output: &mut [u8]) {
+        let half_len = self.half_len;
+        let plaintext_len = self.plaintext_len;
+
+        output[..half_len].copy_from_slice(&self.left[..half_len]);
+        if self.is_odd() {
+            output[half_len - 1] |= self.right[0];
+            output[half_len..].copy_from_slice(&self.right[1..half_len]);
+        } else {
+            output[half_len..plaintext_len].copy_from_slice(&self.right[..half_len]);
+        }
+    }
+
+    /// XORs the right half with the AES output of the expanded left half
+    fn right_pass(&mut self, cipher: &Aes128, pass: u8) {
+        let mask = self.round(cipher, &self.left, pass);
+        for (right, mask) in self.right[..self.half_len].iter_mut().zip(mask.iter()) {
+            *right ^= mask;
+        }
+        if self.is_odd() {
+            self.right[0] &= 0x0f;
+        }
+    }
+
+    /// XORs the left half with the AES output of the expanded right half
+    fn left_pass(&mut self, cipher: &Aes128, pass: u8) {
+        let mask = self.round(cipher, &self.right, pass);
+        for (left, mask) in self.left[..self.half_len].iter_mut().zip(mask.iter()) {
+            *left ^= mask;
+        }
+        if self.is_odd() {
+            self.left[self.half_len - 1] &= 0xf0;
+        }
+    }
+
+    /// Encrypts a half, padded with zeros and followed by the plaintext length and pass index
+    fn round(&self, cipher: &Aes128, half: &[u8; MAX_HALF_LEN], pass: u8) -> Block {
+        let mut block = Block::default();
+        block[..self.half_len].copy_from_slice(&half[..self.half_len]);
+        block[14] = self.plaintext_len as u8;
+        block[15] = pass;
+        cipher.encrypt_block(&mut block);
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generator_test() {
+        let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+        let connection_info = ConnectionInfo::new(remote_address);
+        let server_id = [0x31, 0x44, 0x1a, 0x9c, 0x69, 0xc2, 0x75, 0xa2, 0x9b];
+
+        for key in [None, Some([0x8f; 16])].iter().copied() {
+            for config_rotation in 0..=MAX_CONFIG_ROTATION {
+                for server_id_len in 1..=server_id.len() {
+                    for nonce_len in MIN_NONCE_LEN..=MAX_PLAINTEXT_LEN - server_id_len {
+                        let mut builder = Format::builder()
+                            .with_config_rotation(config_rotation)
+                            .unwrap()
+                            .with_server_id(&server_id[..server_id_len])
+                            .unwrap()
+                            .with_nonce_len(nonce_len)
+                            .unwrap();
+                        if let Some(key) = key {
+                            builder = builder.with_key(key);
+                        }
+                        let mut format = builder.build().unwrap();
+
+                        let id = format.generate(&connection_info);
+                        let len = 1 + server_id_len + nonce_len;
+                        assert_eq!(id.len(), len);
+
+                        let first_octet = id.as_bytes()[0];
+                        assert_eq!(first_octet >> 5, config_rotation);
+                        assert_eq!((first_octet & 0x1f) as usize, len - 1);
+
+                        // the length is recovered from the first octet, even with trailing data
+                        let mut buffer = id.as_bytes().to_vec();
+                        buffer.extend_from_slice(&[0xff; 8]);
+                        assert_eq!(format.validate(&connection_info, &buffer), Some(len));
+
+                        assert_eq!(
+                            format.decode_server_id(id.as_bytes()).unwrap().as_ref(),
+                            &server_id[..server_id_len]
+                        );
+
+                        if key.is_none() {
+                            assert_eq!(
+                                &id.as_bytes()[1..1 + server_id_len],
+                                &server_id[..server_id_len]
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encryption_round_trip_test() {
+        let cipher = Aes128::new(&[0x4d; 16].into());
+
+        for len in 5..=MAX_PLAINTEXT_LEN {
+            let mut input = [0u8; MAX_PLAINTEXT_LEN];
+            let input = &mut input[..len];
+            rand::thread_rng().fill_bytes(input);
+
+            let mut output = [0u8; MAX_PLAINTEXT_LEN];
+            let output = &mut output[..len];
+            output.copy_from_slice(input);
+
+            encrypt(&cipher, output);
+            assert_ne!(input, output);
+            decrypt(&cipher, output);
+            assert_eq!(input, output);
+        }
+    }
+
+    struct TestVector {
+        config_rotation: u8,
+        server_id: &'static [u8],
+        nonce: &'static [u8],
+        key: Option<[u8; 16]>,
+        connection_id: &'static [u8],
+    }
+
+    const TEST_VECTORS: &[TestVector] = &[
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-load-balancers#appendix-B.1
+        TestVector {
+            config_rotation: 0,
+            server_id: &[0xc4, 0x60, 0x5e],
+            nonce: &[0x45, 0x04, 0xcc, 0x4f],
+            key: None,
+            connection_id: &[0x07, 0xc4, 0x60, 0x5e, 0x45, 0x04, 0xcc, 0x4f],
+        },
+        // A 16 byte server Id and nonce is encrypted with a single AES-128-ECB pass, so the
+        // connection Id carries the AES-128 known answer from FIPS-197 Appendix C.1
+        TestVector {
+            config_rotation: 1,
+            server_id: &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77],
+            nonce: &[0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff],
+            key: Some([
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                0x0e, 0x0f,
+            ]),
+            connection_id: &[
+                0x30, 0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70,
+                0xb4, 0xc5, 0x5a,
+            ],
+        },
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for vector in TEST_VECTORS {
+            let mut builder = Format::builder()
+                .with_config_rotation(vector.config_rotation)
+                .unwrap()
+                .with_server_id(vector.server_id)
+                .unwrap()
+                .with_nonce_len(vector.nonce.len())
+                .unwrap();
+            if let Some(key) = vector.key {
+                builder = builder.with_key(key);
+            }
+            let format = builder.build().unwrap();
+
+            let mut plaintext = vector.server_id.to_vec();
+            plaintext.extend_from_slice(vector.nonce);
+            if let Some(cipher) = format.cipher.as_ref() {
+                encrypt(cipher, &mut plaintext);
+            }
+            assert_eq!(&plaintext[..], &vector.connection_id[FIRST_OCTET_LEN..]);
+
+            let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+            let connection_info = ConnectionInfo::new(remote_address);
+            assert_eq!(
+                format.validate(&connection_info, vector.connection_id),
+                Some(vector.connection_id.len())
+            );
+            assert_eq!(
+                format
+                    .decode_server_id(vector.connection_id)
+                    .unwrap()
+                    .as_ref(),
+                vector.server_id
+            );
+        }
+    }
+
+    #[test]
+    fn validator_test() {
+        let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+        let connection_info = ConnectionInfo::new(remote_address);
+        let format = Format::default();
+
+        assert_eq!(format.validate(&connection_info, &[]), None);
+        // the encoded length is longer than the buffer
+        assert_eq!(format.validate(&connection_info, &[0x05, 1, 2, 3]), None);
+        // the encoded length is shorter than the minimum connection id length
+        assert_eq!(format.validate(&connection_info, &[0x02, 1, 2, 3]), None);
+        // the encoded length is longer than the maximum connection id length
+        assert_eq!(format.validate(&connection_info, &[0x1f; 32]), None);
+        assert_eq!(format.validate(&connection_info, &[0x13; 32]), Some(20));
+        assert_eq!(format.validate(&connection_info, &[0xc5; 6]), Some(6));
+    }
+
+    #[test]
+    fn decode_test() {
+        let mut format = Format::builder()
+            .with_config_rotation(1)
+            .unwrap()
+            .with_server_id(&[1, 2, 3])
+            .unwrap()
+            .with_key([7; 16])
+            .build()
+            .unwrap();
+        let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+        let id = format.generate(&ConnectionInfo::new(remote_address));
+
+        let other_config = Format::builder()
+            .with_config_rotation(2)
+            .unwrap()
+            .with_server_id(&[1, 2, 3])
+            .unwrap()
+            .with_key([7; 16])
+            .build()
+            .unwrap();
+        assert_eq!(other_config.decode_server_id(id.as_bytes()), None);
+
+        let other_key = Format::builder()
+            .with_config_rotation(1)
+            .unwrap()
+            .with_server_id(&[1, 2, 3])
+            .unwrap()
+            .with_key([8; 16])
+            .build()
+            .unwrap();
+        assert_ne!(
+            other_key
+                .decode_server_id(id.as_bytes())
+                .map(|id| id.as_ref().to_vec()),
+            Some(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn builder_test() {
+        assert_eq!(
+            Some(Error::InvalidConfigRotation),
+            Format::builder().with_config_rotation(0b111).err()
+        );
+
+        assert_eq!(
+            Some(Error::InvalidServerId),
+            Format::builder().with_server_id(&[]).err()
+        );
+
+        assert_eq!(
+            Some(Error::InvalidServerId),
+            Format::builder()
+                .with_server_id(&[0; MAX_SERVER_ID_LEN + 1])
+                .err()
+        );
+
+        assert_eq!(
+            Some(Error::InvalidNonceLength),
+            Format::builder().with_nonce_len(MIN_NONCE_LEN - 1).err()
+        );
+
+        // the server id and nonce don't fit in a connection id
+        assert_eq!(
+            Some(Error::InvalidNonceLength),
+            Format::builder()
+                .with_server_id(&[0; 4])
+                .unwrap()
+                .with_nonce_len(16)
+                .unwrap()
+                .build()
+                .err()
+        );
+
+        let lifetime = Duration::from_secs(1000);
+        let format = Format::builder()
+            .with_lifetime(lifetime)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(Some(lifetime), format.lifetime());
+
+        assert_eq!(
+            Some(Error::InvalidLifetime),
+            Format::builder()
+                .with_lifetime(connection::id::MIN_LIFETIME - Duration::from_millis(1))
+                .err()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Provides application-defined transport parameters for an endpoint
+//!
+//! The parameters are included in the transport parameters of every connection the endpoint
+//! creates. The values sent by the peer can be read from the connection
+//! [`Handle`](crate::connection::Handle) once the handshake completes.
+
+pub use s2n_quic_core::transport::parameters::{
+    CustomTransportParameter, CustomTransportParameters, ValidationError,
+};
+
+/// Provides custom transport parameters for an endpoint
+pub trait Provider {
+    type Error: 'static + core::fmt::Display;
+
+    fn start(self) -> Result<CustomTransportParameters, Self::Error>;
+}
+
+/// The default provider doesn't include any custom transport parameters
+pub type Default = CustomTransportParameters;
+
+impl_provider_utils!();
+
+impl Provider for CustomTransportParameters {
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<CustomTransportParameters, Self::Error> {
+        Ok(self)
+    }
+}
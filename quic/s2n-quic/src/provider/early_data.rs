@@ -0,0 +1,32 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Provides 0-RTT support for an endpoint
+//!
+//! Servers reject early data by default. Before enabling it, see the replay considerations in
+//! [`s2n_quic_core::connection::early_data`].
+
+pub use s2n_quic_core::connection::early_data::{
+    accept_all,
+    default::{self, Policy as Default},
+    Attempt, Error, Outcome, Policy,
+};
+
+/// Provides early data support for an endpoint
+pub trait Provider {
+    type Policy: 'static + Send + Policy;
+    type Error: 'static + core::fmt::Display;
+
+    fn start(self) -> Result<Self::Policy, Self::Error>;
+}
+
+impl_provider_utils!();
+
+impl<T: 'static + Send + Policy> Provider for T {
+    type Policy = T;
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<Self::Policy, Self::Error> {
+        Ok(self)
+    }
+}
@@ -0,0 +1,862 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Aggregates endpoint and connection events into counters and histograms
+//!
+//! The aggregates are shared between the subscriber and any number of [`Metrics`] handles, which
+//! can take a [`Snapshot`] at any time. Snapshots can be rendered in the
+//! [Prometheus text exposition format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! use s2n_quic::{provider::event::metrics, Server};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let provider = metrics::Provider::default();
+//! let metrics = provider.metrics();
+//!
+//! let server = Server::builder()
+//!     .with_event(provider)?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//!
+//! // serve `metrics.snapshot().to_prometheus()` from the application's metrics endpoint
+//! println!("{}", metrics.snapshot().to_prometheus());
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Timestamp};
+use core::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+#[derive(Debug, Default)]
+pub struct Provider {
+    metrics: Metrics,
+}
+
+impl Provider {
+    /// Returns a handle to the metrics which are aggregated by the subscriber
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+}
+
+impl super::Provider for Provider {
+    type Subscriber = Subscriber;
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<Self::Subscriber, Self::Error> {
+        Ok(Subscriber {
+            metrics: self.metrics,
+        })
+    }
+}
+
+/// A handle to the aggregated metrics of an endpoint
+#[derive(Clone, Debug, Default)]
+pub struct Metrics(Arc<Registry>);
+
+impl Metrics {
+    /// Returns the current value of each metric
+    pub fn snapshot(&self) -> Snapshot {
+        let registry = &self.0;
+
+        Snapshot {
+            handshakes_completed: registry.handshakes_completed.load(Ordering::Relaxed),
+            handshakes_failed: registry.handshakes_failed.load(Ordering::Relaxed),
+            connection_attempts_failed: registry.connection_attempts_failed.load(Ordering::Relaxed),
+            packets_dropped: registry.packets_dropped.snapshot(),
+            datagrams_dropped: registry.datagrams_dropped.snapshot(),
+            migrations_denied: registry.migrations_denied.snapshot(),
+            keys_updated: registry.keys_updated.load(Ordering::Relaxed),
+            handshake_duration: registry.handshake_duration.snapshot(),
+            smoothed_rtt: registry.smoothed_rtt.snapshot(),
+            connection_lifetime: registry.connection_lifetime.snapshot(),
+            connection_bytes_sent: registry.connection_bytes_sent.snapshot(),
+            connection_bytes_received: registry.connection_bytes_received.snapshot(),
+        }
+    }
+}
+
+/// The value of each metric at the time the snapshot was taken
+///
+/// Durations are reported in seconds and sizes in bytes.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Snapshot {
+    /// The number of connections which completed the handshake
+    pub handshakes_completed: u64,
+    /// The number of connections which closed before completing the handshake
+    pub handshakes_failed: u64,
+    /// The number of Initial packets which were rejected before a connection was created
+    pub connection_attempts_failed: u64,
+    /// The number of packets dropped by connections, by reason
+    pub packets_dropped: BTreeMap<&'static str, u64>,
+    /// The number of datagrams dropped by the endpoint or its connections, by reason
+    pub datagrams_dropped: BTreeMap<&'static str, u64>,
+    /// The number of peer migrations which were denied, by reason
+    pub migrations_denied: BTreeMap<&'static str, u64>,
+    /// The number of 1-RTT key phase changes, initiated by either peer
+    pub keys_updated: u64,
+    /// The time from creating a connection until the handshake completed
+    pub handshake_duration: HistogramSnapshot,
+    /// The last smoothed RTT of each connection, recorded when the connection is closed
+    pub smoothed_rtt: HistogramSnapshot,
+    /// The time from creating a connection until it was closed
+    pub connection_lifetime: HistogramSnapshot,
+    /// The number of bytes sent by each connection over its lifetime
+    pub connection_bytes_sent: HistogramSnapshot,
+    /// The number of bytes received by each connection over its lifetime
+    pub connection_bytes_received: HistogramSnapshot,
+}
+
+impl Snapshot {
+    /// Renders the snapshot in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        // writing to a `String` can't fail
+        let _ = self.write_prometheus(&mut out);
+        out
+    }
+
+    fn write_prometheus<W: Write>(&self, out: &mut W) -> fmt::Result {
+        write_counter(
+            out,
+            "handshakes_completed_total",
+            "Connections which completed the handshake",
+            self.handshakes_completed,
+        )?;
+        write_counter(
+            out,
+            "handshakes_failed_total",
+            "Connections which closed before completing the handshake",
+            self.handshakes_failed,
+        )?;
+        write_counter(
+            out,
+            "connection_attempts_failed_total",
+            "Initial packets which were rejected before a connection was created",
+            self.connection_attempts_failed,
+        )?;
+        write_labeled_counter(
+            out,
+            "packets_dropped_total",
+            "Packets dropped by connections",
+            "reason",
+            &self.packets_dropped,
+        )?;
+        write_labeled_counter(
+            out,
+            "datagrams_dropped_total",
+            "Datagrams dropped by the endpoint or its connections",
+            "reason",
+            &self.datagrams_dropped,
+        )?;
+        write_labeled_counter(
+            out,
+            "migrations_denied_total",
+            "Peer migrations which were denied",
+            "reason",
+            &self.migrations_denied,
+        )?;
+        write_counter(
+            out,
+            "keys_updated_total",
+            "1-RTT key phase changes",
+            self.keys_updated,
+        )?;
+        write_histogram(
+            out,
+            "handshake_duration_seconds",
+            "Time from creating a connection until the handshake completed",
+            &self.handshake_duration,
+        )?;
+        write_histogram(
+            out,
+            "smoothed_rtt_seconds",
+            "Smoothed RTT of each connection when it was closed",
+            &self.smoothed_rtt,
+        )?;
+        write_histogram(
+            out,
+            "connection_lifetime_seconds",
+            "Time from creating a connection until it was closed",
+            &self.connection_lifetime,
+        )?;
+        write_histogram(
+            out,
+            "connection_sent_bytes",
+            "Bytes sent by each connection",
+            &self.connection_bytes_sent,
+        )?;
+        write_histogram(
+            out,
+            "connection_received_bytes",
+            "Bytes received by each connection",
+            &self.connection_bytes_received,
+        )?;
+        Ok(())
+    }
+}
+
+/// The distribution of a metric at the time the snapshot was taken
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct HistogramSnapshot {
+    /// The cumulative number of observations less than or equal to each upper bound
+    pub buckets: Vec<(f64, u64)>,
+    /// The total number of observations, including those above the largest upper bound
+    pub count: u64,
+    /// The sum of all observations
+    pub sum: f64,
+}
+
+/// Prefix of each metric name in the Prometheus output
+const NAMESPACE: &str = "s2n_quic";
+
+fn write_counter<W: Write>(out: &mut W, name: &str, help: &str, value: u64) -> fmt::Result {
+    writeln!(out, "# HELP {}_{} {}", NAMESPACE, name, help)?;
+    writeln!(out, "# TYPE {}_{} counter", NAMESPACE, name)?;
+    writeln!(out, "{}_{} {}", NAMESPACE, name, value)
+}
+
+fn write_labeled_counter<W: Write>(
+    out: &mut W,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: &BTreeMap<&'static str, u64>,
+) -> fmt::Result {
+    writeln!(out, "# HELP {}_{} {}", NAMESPACE, name, help)?;
+    writeln!(out, "# TYPE {}_{} counter", NAMESPACE, name)?;
+    for (value_label, value) in values {
+        writeln!(
+            out,
+            "{}_{}{{{}=\"{}\"}} {}",
+            NAMESPACE, name, label, value_label, value
+        )?;
+    }
+    Ok(())
+}
+
+fn write_histogram<W: Write>(
+    out: &mut W,
+    name: &str,
+    help: &str,
+    histogram: &HistogramSnapshot,
+) -> fmt::Result {
+    writeln!(out, "# HELP {}_{} {}", NAMESPACE, name, help)?;
+    writeln!(out, "# TYPE {}_{} histogram", NAMESPACE, name)?;
+    for (upper_bound, count) in &histogram.buckets {
+        writeln!(
+            out,
+            "{}_{}_bucket{{le=\"{}\"}} {}",
+            NAMESPACE, name, upper_bound, count
+        )?;
+    }
+    writeln!(
+        out,
+        "{}_{}_bucket{{le=\"+Inf\"}} {}",
+        NAMESPACE, name, histogram.count
+    )?;
+    writeln!(out, "{}_{}_sum {}", NAMESPACE, name, histogram.sum)?;
+    writeln!(out, "{}_{}_count {}", NAMESPACE, name, histogram.count)
+}
+
+const PACKET_DROP_REASONS: &[&str] = &[
+    "connection_error",
+    "handshake_not_complete",
+    "version_mismatch",
+    "connection_id_mismatch",
+    "unprotect_failed",
+    "decryption_failed",
+    "decoding_failed",
+    "non_empty_retry_token",
+    "retry_discarded",
+    "other",
+];
+
+fn packet_drop_reason(reason: &events::PacketDropReason) -> usize {
+    use events::PacketDropReason as Reason;
+
+    match reason {
+        Reason::ConnectionError { .. } => 0,
+        Reason::HandshakeNotComplete { .. } => 1,
+        Reason::VersionMismatch { .. } => 2,
+        Reason::ConnectionIdMismatch { .. } => 3,
+        Reason::UnprotectFailed { .. } => 4,
+        Reason::DecryptionFailed { .. } => 5,
+        Reason::DecodingFailed { .. } => 6,
+        Reason::NonEmptyRetryToken { .. } => 7,
+        Reason::RetryDiscarded { .. } => 8,
+        _ => 9,
+    }
+}
+
+const DATAGRAM_DROP_REASONS: &[&str] = &[
+    "decoding_failed",
+    "invalid_retry_token",
+    "unsupported_version",
+    "invalid_destination_connection_id",
+    "invalid_source_connection_id",
+    "unknown_destination_connection_id",
+    "rejected_connection_attempt",
+    "unknown_server_address",
+    "connection_migration_during_handshake",
+    "rejected_connection_migration",
+    "path_limit_exceeded",
+    "insufficient_connection_ids",
+    "other",
+];
+
+fn datagram_drop_reason(reason: &events::DatagramDropReason) -> usize {
+    use events::DatagramDropReason as Reason;
+
+    match reason {
+        Reason::DecodingFailed { .. } => 0,
+        Reason::InvalidRetryToken { .. } => 1,
+        Reason::UnsupportedVersion { .. } => 2,
+        Reason::InvalidDestinationConnectionId { .. } => 3,
+        Reason::InvalidSourceConnectionId { .. } => 4,
+        Reason::UnknownDestinationConnectionId { .. } => 5,
+        Reason::RejectedConnectionAttempt { .. } => 6,
+        Reason::UnknownServerAddress { .. } => 7,
+        Reason::ConnectionMigrationDuringHandshake { .. } => 8,
+        Reason::RejectedConnectionMigration { .. } => 9,
+        Reason::PathLimitExceeded { .. } => 10,
+        Reason::InsufficientConnectionIds { .. } => 11,
+        _ => 12,
+    }
+}
+
+const MIGRATION_DENY_REASONS: &[&str] = &[
+    "blocked_port",
+    "port_scope_changed",
+    "ip_scope_change",
+    "connection_migration_disabled",
+    "other",
+];
+
+fn migration_deny_reason(reason: &events::MigrationDenyReason) -> usize {
+    use events::MigrationDenyReason as Reason;
+
+    match reason {
+        Reason::BlockedPort { .. } => 0,
+        Reason::PortScopeChanged { .. } => 1,
+        Reason::IpScopeChange { .. } => 2,
+        Reason::ConnectionMigrationDisabled { .. } => 3,
+        _ => 4,
+    }
+}
+
+const MICROS_PER_SECOND: f64 = 1_000_000.0;
+
+/// Upper bounds for handshake durations, in microseconds
+const HANDSHAKE_DURATION_BOUNDS: &[u64] = &[
+    1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000, 2_500_000,
+    5_000_000, 10_000_000,
+];
+
+/// Upper bounds for round trip times, in microseconds
+const RTT_BOUNDS: &[u64] = &[
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000,
+];
+
+/// Upper bounds for connection lifetimes, in microseconds
+const LIFETIME_BOUNDS: &[u64] = &[
+    100_000,
+    1_000_000,
+    10_000_000,
+    60_000_000,
+    300_000_000,
+    1_800_000_000,
+    3_600_000_000,
+    21_600_000_000,
+];
+
+/// Upper bounds for the number of bytes transferred by a connection
+const BYTES_BOUNDS: &[u64] = &[
+    1 << 10,
+    1 << 12,
+    1 << 14,
+    1 << 16,
+    1 << 18,
+    1 << 20,
+    1 << 22,
+    1 << 24,
+    1 << 26,
+    1 << 28,
+    1 << 30,
+];
+
+#[derive(Debug)]
+struct Registry {
+    handshakes_completed: AtomicU64,
+    handshakes_failed: AtomicU64,
+    connection_attempts_failed: AtomicU64,
+    packets_dropped: LabeledCounter,
+    datagrams_dropped: LabeledCounter,
+    migrations_denied: LabeledCounter,
+    keys_updated: AtomicU64,
+    handshake_duration: Histogram,
+    smoothed_rtt: Histogram,
+    connection_lifetime: Histogram,
+    connection_bytes_sent: Histogram,
+    connection_bytes_received: Histogram,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            handshakes_completed: AtomicU64::new(0),
+            handshakes_failed: AtomicU64::new(0),
+            connection_attempts_failed: AtomicU64::new(0),
+            packets_dropped: LabeledCounter::new(PACKET_DROP_REASONS),
+            datagrams_dropped: LabeledCounter::new(DATAGRAM_DROP_REASONS),
+            migrations_denied: LabeledCounter::new(MIGRATION_DENY_REASONS),
+            keys_updated: AtomicU64::new(0),
+            handshake_duration: Histogram::new(HANDSHAKE_DURATION_BOUNDS, MICROS_PER_SECOND),
+            smoothed_rtt: Histogram::new(RTT_BOUNDS, MICROS_PER_SECOND),
+            connection_lifetime: Histogram::new(LIFETIME_BOUNDS, MICROS_PER_SECOND),
+            connection_bytes_sent: Histogram::new(BYTES_BOUNDS, 1.0),
+            connection_bytes_received: Histogram::new(BYTES_BOUNDS, 1.0),
+        }
+    }
+}
+
+/// A set of counters which are distinguished by a single label
+#[derive(Debug)]
+struct LabeledCounter {
+    labels: &'static [&'static str],
+    values: Box<[AtomicU64]>,
+}
+
+impl LabeledCounter {
+    fn new(labels: &'static [&'static str]) -> Self {
+        Self {
+            labels,
+            values: labels.iter().map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn increment(&self, index: usize) {
+        self.values[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> BTreeMap<&'static str, u64> {
+        self.labels
+            .iter()
+            .zip(self.values.iter())
+            .map(|(label, value)| (*label, value.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+/// A histogram with fixed bucket boundaries
+///
+/// Observations are recorded as integers and divided by `scale` when a snapshot is taken.
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [u64],
+    scale: f64,
+    /// The number of observations in each bucket, with a final bucket for values above the
+    /// largest bound
+    buckets: Box<[AtomicU64]>,
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [u64], scale: f64) -> Self {
+        Self {
+            bounds,
+            scale,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, value: u64) {
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn record_duration(&self, value: Duration) {
+        self.record(value.as_micros().min(u64::MAX as u128) as u64)
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds
+            .iter()
+            .zip(self.buckets.iter())
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (*bound as f64 / self.scale, cumulative)
+            })
+            .collect();
+
+        // derive the count from the same bucket loads so the `+Inf` bucket is never less than
+        // the bounded ones, even if observations are recorded while taking the snapshot
+        let overflow = self.buckets[self.bounds.len()].load(Ordering::Relaxed);
+
+        HistogramSnapshot {
+            buckets,
+            count: cumulative + overflow,
+            sum: self.sum.load(Ordering::Relaxed) as f64 / self.scale,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Subscriber {
+    metrics: Metrics,
+}
+
+/// The per-connection state needed to record the connection's metrics
+#[derive(Debug)]
+pub struct ConnectionContext {
+    created: Timestamp,
+    is_handshake_complete: bool,
+    smoothed_rtt: Option<Duration>,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl super::Subscriber for Subscriber {
+    type ConnectionContext = ConnectionContext;
+
+    fn create_connection_context(
+        &mut self,
+        meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        ConnectionContext {
+            created: meta.timestamp,
+            is_handshake_complete: false,
+            smoothed_rtt: None,
+            bytes_sent: 0,
+            bytes_received: 0,
+        }
+    }
+
+    fn on_handshake_status_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::HandshakeStatusUpdated,
+    ) {
+        if let events::HandshakeStatus::Complete { .. } = event.status {
+            if !context.is_handshake_complete {
+                context.is_handshake_complete = true;
+
+                let registry = &self.metrics.0;
+                registry
+                    .handshakes_completed
+                    .fetch_add(1, Ordering::Relaxed);
+                registry
+                    .handshake_duration
+                    .record_duration(elapsed(context.created, meta.timestamp));
+            }
+        }
+    }
+
+    fn on_recovery_metrics(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::RecoveryMetrics,
+    ) {
+        if event.path.is_active {
+            context.smoothed_rtt = Some(event.smoothed_rtt);
+        }
+    }
+
+    fn on_datagram_sent(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::DatagramSent,
+    ) {
+        context.bytes_sent += event.len as u64;
+    }
+
+    fn on_datagram_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::DatagramReceived,
+    ) {
+        context.bytes_received += event.len as u64;
+    }
+
+    fn on_packet_dropped(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::PacketDropped,
+    ) {
+        self.metrics
+            .0
+            .packets_dropped
+            .increment(packet_drop_reason(&event.reason));
+    }
+
+    fn on_datagram_dropped(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::DatagramDropped,
+    ) {
+        self.metrics
+            .0
+            .datagrams_dropped
+            .increment(datagram_drop_reason(&event.reason));
+    }
+
+    fn on_connection_migration_denied(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::ConnectionMigrationDenied,
+    ) {
+        self.metrics
+            .0
+            .migrations_denied
+            .increment(migration_deny_reason(&event.reason));
+    }
+
+    fn on_key_update(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::KeyUpdate,
+    ) {
+        // the first 1-RTT keys are installed by the handshake rather than a key update
+        if let events::KeyType::OneRtt { generation, .. } = event.key_type {
+            if generation > 0 {
+                self.metrics.0.keys_updated.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        _event: &events::ConnectionClosed,
+    ) {
+        let registry = &self.metrics.0;
+
+        if !context.is_handshake_complete {
+            registry.handshakes_failed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if let Some(smoothed_rtt) = context.smoothed_rtt {
+            registry.smoothed_rtt.record_duration(smoothed_rtt);
+        }
+
+        registry
+            .connection_lifetime
+            .record_duration(elapsed(context.created, meta.timestamp));
+        registry.connection_bytes_sent.record(context.bytes_sent);
+        registry
+            .connection_bytes_received
+            .record(context.bytes_received);
+    }
+
+    fn on_endpoint_datagram_dropped(
+        &mut self,
+        _meta: &events::EndpointMeta,
+        event: &events::EndpointDatagramDropped,
+    ) {
+        self.metrics
+            .0
+            .datagrams_dropped
+            .increment(datagram_drop_reason(&event.reason));
+    }
+
+    fn on_endpoint_connection_attempt_failed(
+        &mut self,
+        _meta: &events::EndpointMeta,
+        _event: &events::EndpointConnectionAttemptFailed,
+    ) {
+        self.metrics
+            .0
+            .connection_attempts_failed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn elapsed(start: Timestamp, end: Timestamp) -> Duration {
+    end.duration_since_start()
+        .saturating_sub(start.duration_since_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::event::{Provider as _, Subscriber as _};
+    use s2n_quic_core::{
+        endpoint,
+        event::{builder, IntoEvent},
+        time,
+    };
+
+    fn meta(ms: u64) -> ConnectionMeta {
+        builder::ConnectionMeta {
+            endpoint_type: endpoint::Type::Server,
+            id: 0,
+            timestamp: unsafe { time::Timestamp::from_duration(Duration::from_millis(ms)) },
+        }
+        .into_event()
+    }
+
+    #[test]
+    fn connection_test() {
+        let provider = Provider::default();
+        let metrics = provider.metrics();
+        let mut subscriber = provider.start().unwrap();
+
+        let info = builder::ConnectionInfo {}.into_event();
+        let mut context = subscriber.create_connection_context(&meta(1_000), &info);
+
+        subscriber.on_datagram_received(
+            &mut context,
+            &meta(1_000),
+            &builder::DatagramReceived { len: 1200 }.into_event(),
+        );
+        subscriber.on_datagram_sent(
+            &mut context,
+            &meta(1_001),
+            &builder::DatagramSent {
+                len: 1200,
+                gso_offset: 0,
+            }
+            .into_event(),
+        );
+        for key_type in [
+            builder::KeyType::Handshake,
+            builder::KeyType::OneRtt { generation: 0 },
+            builder::KeyType::OneRtt { generation: 1 },
+        ] {
+            subscriber.on_key_update(
+                &mut context,
+                &meta(1_002),
+                &builder::KeyUpdate {
+                    key_type,
+                    cipher_suite: builder::CipherSuite::TLS_AES_128_GCM_SHA256,
+                }
+                .into_event(),
+            );
+        }
+        subscriber.on_handshake_status_updated(
+            &mut context,
+            &meta(1_020),
+            &builder::HandshakeStatusUpdated {
+                status: builder::HandshakeStatus::Complete,
+            }
+            .into_event(),
+        );
+        subscriber.on_datagram_dropped(
+            &mut context,
+            &meta(1_030),
+            &builder::DatagramDropped {
+                len: 100,
+                reason: builder::DatagramDropReason::DecodingFailed,
+            }
+            .into_event(),
+        );
+        subscriber.on_connection_closed(
+            &mut context,
+            &meta(6_000),
+            &builder::ConnectionClosed {
+                error: s2n_quic_core::connection::Error::idle_timer_expired(),
+            }
+            .into_event(),
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.handshakes_completed, 1);
+        assert_eq!(snapshot.handshakes_failed, 0);
+        assert_eq!(snapshot.keys_updated, 1);
+        assert_eq!(snapshot.datagrams_dropped["decoding_failed"], 1);
+
+        assert_eq!(snapshot.handshake_duration.count, 1);
+        assert!((snapshot.handshake_duration.sum - 0.02).abs() < f64::EPSILON);
+        assert_eq!(snapshot.handshake_duration.buckets[3], (0.01, 0));
+        assert_eq!(snapshot.handshake_duration.buckets[4], (0.025, 1));
+
+        // no RTT sample was taken
+        assert_eq!(snapshot.smoothed_rtt.count, 0);
+
+        assert!((snapshot.connection_lifetime.sum - 5.0).abs() < f64::EPSILON);
+        assert_eq!(snapshot.connection_bytes_sent.sum, 1200.0);
+        assert_eq!(snapshot.connection_bytes_received.sum, 1200.0);
+
+        let prometheus = snapshot.to_prometheus();
+        assert!(prometheus.contains("s2n_quic_handshakes_completed_total 1\n"));
+        assert!(prometheus.contains("s2n_quic_keys_updated_total 1\n"));
+        assert!(prometheus.contains("s2n_quic_handshake_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(prometheus.contains("s2n_quic_handshake_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(prometheus.contains("s2n_quic_connection_lifetime_seconds_sum 5\n"));
+    }
+
+    #[test]
+    fn handshake_failure_test() {
+        let provider = Provider::default();
+        let metrics = provider.metrics();
+        let mut subscriber = provider.start().unwrap();
+
+        let info = builder::ConnectionInfo {}.into_event();
+        let mut context = subscriber.create_connection_context(&meta(0), &info);
+        subscriber.on_connection_closed(
+            &mut context,
+            &meta(10),
+            &builder::ConnectionClosed {
+                error: s2n_quic_core::connection::Error::max_handshake_duration_exceeded(
+                    Duration::from_millis(10),
+                ),
+            }
+            .into_event(),
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.handshakes_completed, 0);
+        assert_eq!(snapshot.handshakes_failed, 1);
+        assert_eq!(snapshot.handshake_duration.count, 0);
+        assert_eq!(snapshot.connection_lifetime.count, 1);
+    }
+
+    #[test]
+    fn histogram_test() {
+        let histogram = Histogram::new(&[10, 100], 1.0);
+        for value in [5, 50, 500, 5_000] {
+            histogram.record(value);
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets, vec![(10.0, 1), (100.0, 2)]);
+        // the observations above the largest bound are only counted by the `+Inf` bucket
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.sum, 5_555.0);
+
+        let mut prometheus = String::new();
+        write_histogram(&mut prometheus, "test", "Test", &snapshot).unwrap();
+        assert!(prometheus.contains("s2n_quic_test_bucket{le=\"100\"} 2\n"));
+        assert!(prometheus.contains("s2n_quic_test_bucket{le=\"+Inf\"} 4\n"));
+        assert!(prometheus.contains("s2n_quic_test_count 4\n"));
+    }
+}
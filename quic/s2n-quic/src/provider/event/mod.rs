@@ -24,6 +24,13 @@ pub trait Provider {
 /// Provides an implementation to disable all events
 pub mod disabled;
 
+/// Aggregates events into counters and histograms
+pub mod metrics;
+
+/// Writes per-connection [qlog](https://datatracker.ietf.org/doc/draft-ietf-quic-qlog-main-schema/) traces
+#[cfg(feature = "provider-event-qlog")]
+pub mod qlog;
+
 /// This module contains event integration with [`tracing`](https://docs.rs/tracing)
 #[cfg(feature = "provider-event-tracing")]
 pub mod tracing;
@@ -0,0 +1,1165 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writes a [qlog](https://datatracker.ietf.org/doc/draft-ietf-quic-qlog-main-schema/) trace for
+//! each connection
+//!
+//! Traces use the JSON-SEQ serialization of the main schema and can be loaded into tools such as
+//! [qvis](https://qvis.quictools.info). Events are named after the qlog QUIC event definitions,
+//! and events which have no qlog equivalent use the names from [`events`].
+//!
+//! Records are serialized on the endpoint's thread and handed to a background thread in chunks,
+//! which creates the trace outputs and writes to them so the endpoint never blocks on I/O.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! use s2n_quic::{provider::event::qlog, Server};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let server = Server::builder()
+//!     .with_event(qlog::Provider::new("/var/log/s2n-quic"))?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use crate::provider::event::{events, ConnectionInfo, ConnectionMeta};
+use core::time::Duration;
+use s2n_quic_core::endpoint::Location;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufWriter, Write},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+type MakeWriter = Box<dyn FnMut(&ConnectionMeta) -> io::Result<Box<dyn Write + Send>> + Send>;
+
+/// The record separator which starts each record in a JSON-SEQ stream
+const RECORD_SEPARATOR: &[u8] = b"\x1e";
+
+/// The number of serialized bytes a trace buffers before handing them to the writer thread
+const CHUNK_LEN: usize = 16 * 1024;
+
+pub struct Provider {
+    output: Output,
+}
+
+enum Output {
+    Directory(PathBuf),
+    Writer(MakeWriter),
+}
+
+impl Provider {
+    /// Writes the trace of each connection to a `.sqlog` file in the given directory
+    ///
+    /// The directory is created if it doesn't exist. Files are named after the time the
+    /// connection was created, the connection's internal ID and the endpoint type.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            output: Output::Directory(directory.into()),
+        }
+    }
+
+    /// Writes the trace of each connection to the writer returned by `make_writer`
+    ///
+    /// If `make_writer` returns an error, the connection isn't traced.
+    pub fn with_writer<F, W>(mut make_writer: F) -> Self
+    where
+        F: 'static + Send + FnMut(&ConnectionMeta) -> io::Result<W>,
+        W: 'static + Send + Write,
+    {
+        Self {
+            output: Output::Writer(Box::new(move |meta| {
+                let writer: Box<dyn Write + Send> = Box::new(make_writer(meta)?);
+                Ok(writer)
+            })),
+        }
+    }
+}
+
+impl super::Provider for Provider {
+    type Subscriber = Subscriber;
+    type Error = io::Error;
+
+    fn start(self) -> Result<Self::Subscriber, Self::Error> {
+        let make_writer = match self.output {
+            Output::Directory(directory) => {
+                fs::create_dir_all(&directory)?;
+
+                Box::new(move |meta: &ConnectionMeta| {
+                    let name = format!(
+                        "{}-{}-{}.sqlog",
+                        unix_time(SystemTime::now()).as_millis(),
+                        meta.id,
+                        meta.endpoint_type
+                    );
+                    let writer: Box<dyn Write + Send> = Box::new(TraceFile {
+                        path: directory.join(name),
+                        file: None,
+                    });
+                    Ok(writer)
+                })
+            }
+            Output::Writer(make_writer) => make_writer,
+        };
+
+        let (commands, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("s2n-quic-qlog".into())
+            .spawn(move || write_traces(receiver))?;
+
+        Ok(Subscriber {
+            make_writer,
+            commands,
+        })
+    }
+}
+
+pub struct Subscriber {
+    make_writer: MakeWriter,
+    commands: mpsc::Sender<Command>,
+}
+
+/// A request to the writer thread for the trace of a connection
+enum Command {
+    /// Starts writing a trace to the given output
+    Open(u64, Box<dyn Write + Send>),
+    /// Appends serialized records to a trace
+    Write(u64, Vec<u8>),
+    /// Flushes the output of a trace
+    Flush(u64),
+    /// Flushes and closes the output of a trace
+    Close(u64),
+}
+
+/// Writes the traces of an endpoint's connections until the endpoint has been dropped
+fn write_traces(commands: mpsc::Receiver<Command>) {
+    let mut outputs = HashMap::new();
+
+    for command in commands {
+        match command {
+            Command::Open(id, writer) => {
+                outputs.insert(id, BufWriter::new(writer));
+            }
+            Command::Write(id, records) => {
+                if let Some(output) = outputs.get_mut(&id) {
+                    // stop tracing the connection rather than writing incomplete records
+                    if output.write_all(&records).is_err() {
+                        outputs.remove(&id);
+                    }
+                }
+            }
+            Command::Flush(id) => {
+                if let Some(output) = outputs.get_mut(&id) {
+                    if output.flush().is_err() {
+                        outputs.remove(&id);
+                    }
+                }
+            }
+            Command::Close(id) => {
+                if let Some(mut output) = outputs.remove(&id) {
+                    let _ = output.flush();
+                }
+            }
+        }
+    }
+}
+
+/// A trace file which is created on the first write, so the writer thread creates it rather
+/// than the endpoint
+struct TraceFile {
+    path: PathBuf,
+    file: Option<fs::File>,
+}
+
+impl Write for TraceFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.is_none() {
+            self.file = Some(fs::File::create(&self.path)?);
+        }
+        self.file.as_mut().expect("file was created").write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The trace's handle to the writer thread
+struct Handle {
+    id: u64,
+    commands: mpsc::Sender<Command>,
+}
+
+/// The qlog trace of a single connection
+pub struct Trace {
+    /// The output of the trace, which is cleared if the writer thread has exited
+    output: Option<Handle>,
+    /// The serialized records which haven't been handed to the writer thread yet
+    buffer: Vec<u8>,
+    /// The time at which the connection was created, which event times are relative to
+    start: Duration,
+    /// The frames written into packets that are currently being sent
+    sent_frames: Vec<(Value, Value)>,
+    /// The last received packet, which is written once its frames have been processed
+    received_packet: Option<ReceivedPacket>,
+}
+
+struct ReceivedPacket {
+    time: f64,
+    header: Value,
+    frames: Vec<Value>,
+}
+
+impl Trace {
+    fn new(output: Option<Handle>, meta: &ConnectionMeta) -> Self {
+        let mut trace = Self {
+            output,
+            buffer: vec![],
+            start: meta.timestamp.duration_since_start(),
+            sent_frames: vec![],
+            received_packet: None,
+        };
+
+        //= https://datatracker.ietf.org/doc/html/draft-ietf-quic-qlog-main-schema-03#section-6.2
+        //# When using JSON-SEQ serialization, the QlogFileSeq top-level
+        //# element is included as the first record in the file
+        trace.write_record(&json!({
+            "qlog_version": "0.3",
+            "qlog_format": "JSON-SEQ",
+            "title": "s2n-quic",
+            "trace": {
+                "vantage_point": {
+                    "name": "s2n-quic",
+                    "type": meta.endpoint_type.to_string(),
+                },
+                "common_fields": {
+                    "group_id": meta.id.to_string(),
+                    "time_format": "relative",
+                    "reference_time": milliseconds(unix_time(SystemTime::now())),
+                },
+            },
+        }));
+
+        trace
+    }
+
+    fn time(&self, meta: &ConnectionMeta) -> f64 {
+        milliseconds(
+            meta.timestamp
+                .duration_since_start()
+                .saturating_sub(self.start),
+        )
+    }
+
+    fn write_event(&mut self, meta: &ConnectionMeta, name: &str, data: Value) {
+        self.flush_received_packet();
+        let time = self.time(meta);
+        self.write_event_at(time, name, data);
+    }
+
+    fn write_event_at(&mut self, time: f64, name: &str, data: Value) {
+        self.write_record(&json!({
+            "time": time,
+            "name": name,
+            "data": data,
+        }));
+    }
+
+    fn write_record(&mut self, record: &Value) {
+        if self.output.is_none() {
+            return;
+        }
+
+        let len = self.buffer.len();
+        self.buffer.extend_from_slice(RECORD_SEPARATOR);
+        if serde_json::to_writer(&mut self.buffer, record).is_err() {
+            // drop the record rather than writing an incomplete one
+            self.buffer.truncate(len);
+            return;
+        }
+        self.buffer.push(b'\n');
+
+        if self.buffer.len() >= CHUNK_LEN {
+            self.send_records();
+        }
+    }
+
+    /// Hands the buffered records to the writer thread
+    fn send_records(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let records = core::mem::take(&mut self.buffer);
+        self.send(|id| Command::Write(id, records));
+    }
+
+    fn send<F: FnOnce(u64) -> Command>(&mut self, command: F) {
+        if let Some(output) = self.output.as_ref() {
+            if output.commands.send(command(output.id)).is_err() {
+                self.output = None;
+            }
+        }
+    }
+
+    fn flush_received_packet(&mut self) {
+        if let Some(packet) = self.received_packet.take() {
+            self.write_event_at(
+                packet.time,
+                "transport:packet_received",
+                json!({
+                    "header": packet.header,
+                    "frames": packet.frames,
+                }),
+            );
+        }
+    }
+
+    fn flush(&mut self) {
+        self.flush_received_packet();
+        self.send_records();
+        self.send(Command::Flush);
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        self.flush_received_packet();
+        self.send_records();
+        self.send(Command::Close);
+    }
+}
+
+impl super::Subscriber for Subscriber {
+    type ConnectionContext = Trace;
+
+    fn create_connection_context(
+        &mut self,
+        meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        let output = (self.make_writer)(meta).ok().and_then(|writer| {
+            let output = Handle {
+                id: meta.id,
+                commands: self.commands.clone(),
+            };
+            output.commands.send(Command::Open(meta.id, writer)).ok()?;
+            Some(output)
+        });
+
+        Trace::new(output, meta)
+    }
+
+    fn on_connection_started(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionStarted,
+    ) {
+        let local = SocketAddr::from(&event.path.local_addr);
+        let remote = SocketAddr::from(&event.path.remote_addr);
+
+        context.write_event(
+            meta,
+            "connectivity:connection_started",
+            json!({
+                "ip_version": if local.is_ipv4() { "ipv4" } else { "ipv6" },
+                "src_ip": local.ip().to_string(),
+                "src_port": local.port(),
+                "dst_ip": remote.ip().to_string(),
+                "dst_port": remote.port(),
+                "src_cid": hex(event.path.local_cid.bytes),
+                "dst_cid": hex(event.path.remote_cid.bytes),
+            }),
+        );
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionClosed,
+    ) {
+        use s2n_quic_core::connection::Error;
+
+        let mut data = Map::new();
+
+        let (owner, trigger) = match event.error {
+            Error::Closed { initiator, .. } => (Some(initiator), "clean"),
+            Error::Transport {
+                code, initiator, ..
+            } => {
+                data.insert("connection_code".into(), code.as_u64().into());
+                (Some(initiator), "error")
+            }
+            Error::Application {
+                error, initiator, ..
+            } => {
+                data.insert("application_code".into(), (*error).into());
+                (Some(initiator), "application")
+            }
+            Error::StatelessReset { .. } => (Some(Location::Remote), "stateless_reset"),
+            Error::IdleTimerExpired { .. } => (Some(Location::Local), "idle_timeout"),
+            Error::MaxHandshakeDurationExceeded { .. } => {
+                (Some(Location::Local), "handshake_timeout")
+            }
+            _ => (None, "error"),
+        };
+
+        if let Some(owner) = owner {
+            data.insert("owner".into(), location(owner).into());
+        }
+        data.insert("trigger".into(), trigger.into());
+        data.insert("reason".into(), event.error.to_string().into());
+
+        context.write_event(meta, "connectivity:connection_closed", data.into());
+        context.flush();
+    }
+
+    fn on_packet_sent(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::PacketSent,
+    ) {
+        let header = packet_header(&event.packet_header);
+
+        // any frames that don't belong to this packet were written into packets that were
+        // never sent
+        let frames: Vec<_> = core::mem::take(&mut context.sent_frames)
+            .into_iter()
+            .filter(|(frame_header, _)| *frame_header == header)
+            .map(|(_, frame)| frame)
+            .collect();
+
+        context.write_event(
+            meta,
+            "transport:packet_sent",
+            json!({
+                "header": header,
+                "frames": frames,
+            }),
+        );
+    }
+
+    fn on_frame_sent(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::FrameSent,
+    ) {
+        context
+            .sent_frames
+            .push((packet_header(&event.packet_header), frame(&event.frame)));
+    }
+
+    fn on_packet_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::PacketReceived,
+    ) {
+        context.flush_received_packet();
+        context.received_packet = Some(ReceivedPacket {
+            time: context.time(meta),
+            header: packet_header(&event.packet_header),
+            frames: vec![],
+        });
+    }
+
+    fn on_frame_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::FrameReceived,
+    ) {
+        let header = packet_header(&event.packet_header);
+
+        if let Some(packet) = context.received_packet.as_mut() {
+            if packet.header == header {
+                packet.frames.push(frame(&event.frame));
+            }
+        }
+    }
+
+    fn on_packet_lost(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::PacketLost,
+    ) {
+        context.write_event(
+            meta,
+            "recovery:packet_lost",
+            json!({
+                "header": packet_header(&event.packet_header),
+                "path_id": event.path.id,
+                "bytes_lost": event.bytes_lost,
+                "is_mtu_probe": event.is_mtu_probe,
+            }),
+        );
+    }
+
+    fn on_packet_dropped(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::PacketDropped,
+    ) {
+        use events::PacketDropReason as Reason;
+
+        let mut data = Map::new();
+
+        let trigger = match &event.reason {
+            Reason::ConnectionError { .. } => "general",
+            Reason::HandshakeNotComplete { .. } => "key_unavailable",
+            Reason::VersionMismatch { version, .. } => {
+                data.insert("version".into(), format!("{:08x}", version).into());
+                "unexpected_version"
+            }
+            Reason::ConnectionIdMismatch { .. } => "unknown_connection_id",
+            Reason::UnprotectFailed { .. } => "key_unavailable",
+            Reason::DecryptionFailed {
+                packet_header: header,
+                ..
+            } => {
+                data.insert("header".into(), packet_header(header));
+                "payload_decrypt_error"
+            }
+            Reason::DecodingFailed { .. } => "header_parse_error",
+            Reason::NonEmptyRetryToken { .. } => "unexpected_packet",
+            Reason::RetryDiscarded { .. } => "unexpected_packet",
+            _ => "general",
+        };
+
+        data.insert("trigger".into(), trigger.into());
+        data.insert("details".into(), format!("{:?}", event.reason).into());
+
+        context.write_event(meta, "transport:packet_dropped", data.into());
+    }
+
+    fn on_recovery_metrics(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::RecoveryMetrics,
+    ) {
+        context.write_event(
+            meta,
+            "recovery:metrics_updated",
+            json!({
+                "min_rtt": milliseconds(event.min_rtt),
+                "smoothed_rtt": milliseconds(event.smoothed_rtt),
+                "latest_rtt": milliseconds(event.latest_rtt),
+                "rtt_variance": milliseconds(event.rtt_variance),
+                "pto_count": event.pto_count,
+                "congestion_window": event.congestion_window,
+                "bytes_in_flight": event.bytes_in_flight,
+            }),
+        );
+    }
+
+    fn on_congestion(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::Congestion,
+    ) {
+        let trigger = match event.source {
+            events::CongestionSource::Ecn { .. } => "ECN",
+            _ => "packet_loss",
+        };
+
+        context.write_event(
+            meta,
+            "recovery:congestion_state_updated",
+            json!({
+                "new": "recovery",
+                "trigger": trigger,
+            }),
+        );
+    }
+
+    fn on_key_update(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::KeyUpdate,
+    ) {
+        let generation = match event.key_type {
+            events::KeyType::OneRtt { generation, .. } => Some(generation),
+            _ => None,
+        };
+
+        for key_type in key_types(&event.key_type) {
+            let mut data = Map::new();
+            data.insert("key_type".into(), (*key_type).into());
+            if let Some(generation) = generation {
+                data.insert("generation".into(), generation.into());
+            }
+            context.write_event(meta, "security:key_updated", data.into());
+        }
+    }
+
+    fn on_key_space_discarded(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::KeySpaceDiscarded,
+    ) {
+        use events::KeySpace;
+
+        let key_types: &[&str] = match event.space {
+            KeySpace::Initial { .. } => &["client_initial_secret", "server_initial_secret"],
+            KeySpace::Handshake { .. } => &["client_handshake_secret", "server_handshake_secret"],
+            KeySpace::ZeroRtt { .. } => &["client_0rtt_secret"],
+            _ => &["client_1rtt_secret", "server_1rtt_secret"],
+        };
+
+        for key_type in key_types {
+            context.write_event(
+                meta,
+                "security:key_discarded",
+                json!({
+                    "key_type": key_type,
+                    "trigger": "tls",
+                }),
+            );
+        }
+    }
+
+    fn on_path_created(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::PathCreated,
+    ) {
+        context.write_event(
+            meta,
+            "transport:path_created",
+            json!({
+                "active": path(&event.active),
+                "new": path(&event.new),
+            }),
+        );
+    }
+
+    fn on_active_path_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ActivePathUpdated,
+    ) {
+        context.write_event(
+            meta,
+            "connectivity:active_path_updated",
+            json!({
+                "previous": path(&event.previous),
+                "active": path(&event.active),
+            }),
+        );
+    }
+
+    fn on_connection_id_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionIdUpdated,
+    ) {
+        context.write_event(
+            meta,
+            "connectivity:connection_id_updated",
+            json!({
+                "owner": location(event.cid_consumer),
+                "old": hex(event.previous.bytes),
+                "new": hex(event.current.bytes),
+            }),
+        );
+    }
+
+    fn on_handshake_status_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::HandshakeStatusUpdated,
+    ) {
+        use events::HandshakeStatus;
+
+        let state = match event.status {
+            HandshakeStatus::Complete { .. } => "handshake_complete",
+            HandshakeStatus::Confirmed { .. } => "handshake_confirmed",
+            _ => return,
+        };
+
+        context.write_event(
+            meta,
+            "connectivity:connection_state_updated",
+            json!({ "new": state }),
+        );
+    }
+
+    fn on_transport_parameters_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::TransportParametersReceived,
+    ) {
+        let parameters = &event.transport_parameters;
+        let mut data = Map::new();
+
+        data.insert("owner".into(), "remote".into());
+
+        for (name, id) in [
+            (
+                "original_destination_connection_id",
+                &parameters.original_destination_connection_id,
+            ),
+            (
+                "initial_source_connection_id",
+                &parameters.initial_source_connection_id,
+            ),
+            (
+                "retry_source_connection_id",
+                &parameters.retry_source_connection_id,
+            ),
+        ] {
+            if let Some(id) = id {
+                data.insert(name.into(), hex(id.bytes).into());
+            }
+        }
+
+        if let Some(token) = parameters.stateless_reset_token {
+            data.insert("stateless_reset_token".into(), hex(token).into());
+        }
+
+        data.insert(
+            "disable_active_migration".into(),
+            (!parameters.migration_support).into(),
+        );
+        data.insert(
+            "max_idle_timeout".into(),
+            (parameters.max_idle_timeout.as_millis() as u64).into(),
+        );
+        data.insert(
+            "max_udp_payload_size".into(),
+            parameters.max_udp_payload_size.into(),
+        );
+        data.insert(
+            "ack_delay_exponent".into(),
+            parameters.ack_delay_exponent.into(),
+        );
+        data.insert(
+            "max_ack_delay".into(),
+            (parameters.max_ack_delay.as_millis() as u64).into(),
+        );
+        data.insert(
+            "active_connection_id_limit".into(),
+            parameters.active_connection_id_limit.into(),
+        );
+        data.insert(
+            "initial_max_stream_data_bidi_local".into(),
+            parameters.initial_max_stream_data_bidi_local.into(),
+        );
+        data.insert(
+            "initial_max_stream_data_bidi_remote".into(),
+            parameters.initial_max_stream_data_bidi_remote.into(),
+        );
+        data.insert(
+            "initial_max_stream_data_uni".into(),
+            parameters.initial_max_stream_data_uni.into(),
+        );
+        data.insert(
+            "initial_max_streams_bidi".into(),
+            parameters.initial_max_streams_bidi.into(),
+        );
+        data.insert(
+            "initial_max_streams_uni".into(),
+            parameters.initial_max_streams_uni.into(),
+        );
+        data.insert(
+            "max_datagram_frame_size".into(),
+            parameters.max_datagram_frame_size.into(),
+        );
+
+        context.write_event(meta, "transport:parameters_set", data.into());
+    }
+}
+
+fn location(location: Location) -> &'static str {
+    match location {
+        Location::Local => "local",
+        Location::Remote => "remote",
+    }
+}
+
+fn packet_header(header: &events::PacketHeader) -> Value {
+    use events::PacketHeader;
+
+    let (packet_type, number, version) = match *header {
+        PacketHeader::Initial {
+            number, version, ..
+        } => ("initial", Some(number), Some(version)),
+        PacketHeader::Handshake {
+            number, version, ..
+        } => ("handshake", Some(number), Some(version)),
+        PacketHeader::ZeroRtt {
+            number, version, ..
+        } => ("0RTT", Some(number), Some(version)),
+        PacketHeader::OneRtt { number, .. } => ("1RTT", Some(number), None),
+        PacketHeader::Retry { version, .. } => ("retry", None, Some(version)),
+        PacketHeader::VersionNegotiation { .. } => ("version_negotiation", None, None),
+        PacketHeader::StatelessReset { .. } => ("stateless_reset", None, None),
+        _ => ("unknown", None, None),
+    };
+
+    let mut value = Map::new();
+    value.insert("packet_type".into(), packet_type.into());
+    if let Some(number) = number {
+        value.insert("packet_number".into(), number.into());
+    }
+    if let Some(version) = version {
+        value.insert("version".into(), format!("{:08x}", version).into());
+    }
+    value.into()
+}
+
+fn frame(frame: &events::Frame) -> Value {
+    use events::Frame;
+
+    match *frame {
+        Frame::Padding { .. } => json!({ "frame_type": "padding" }),
+        Frame::Ping { .. } => json!({ "frame_type": "ping" }),
+        Frame::Ack { .. } => json!({ "frame_type": "ack" }),
+        Frame::ResetStream { .. } => json!({ "frame_type": "reset_stream" }),
+        Frame::StopSending { .. } => json!({ "frame_type": "stop_sending" }),
+        Frame::Crypto { offset, len, .. } => json!({
+            "frame_type": "crypto",
+            "offset": offset,
+            "length": len,
+        }),
+        Frame::NewToken { .. } => json!({ "frame_type": "new_token" }),
+        Frame::Stream {
+            id,
+            offset,
+            len,
+            is_fin,
+            ..
+        } => json!({
+            "frame_type": "stream",
+            "stream_id": id,
+            "offset": offset,
+            "length": len,
+            "fin": is_fin,
+        }),
+        Frame::MaxData { .. } => json!({ "frame_type": "max_data" }),
+        Frame::MaxStreamData { .. } => json!({ "frame_type": "max_stream_data" }),
+        Frame::MaxStreams {
+            ref stream_type, ..
+        } => json!({
+            "frame_type": "max_streams",
+            "stream_type": stream_type_name(stream_type),
+        }),
+        Frame::DataBlocked { .. } => json!({ "frame_type": "data_blocked" }),
+        Frame::StreamDataBlocked { .. } => json!({ "frame_type": "stream_data_blocked" }),
+        Frame::StreamsBlocked {
+            ref stream_type, ..
+        } => json!({
+            "frame_type": "streams_blocked",
+            "stream_type": stream_type_name(stream_type),
+        }),
+        Frame::NewConnectionId { .. } => json!({ "frame_type": "new_connection_id" }),
+        Frame::RetireConnectionId { .. } => json!({ "frame_type": "retire_connection_id" }),
+        Frame::PathChallenge { .. } => json!({ "frame_type": "path_challenge" }),
+        Frame::PathResponse { .. } => json!({ "frame_type": "path_response" }),
+        Frame::ConnectionClose { .. } => json!({ "frame_type": "connection_close" }),
+        Frame::HandshakeDone { .. } => json!({ "frame_type": "handshake_done" }),
+        Frame::Datagram { len, .. } => json!({
+            "frame_type": "datagram",
+            "length": len,
+        }),
+        _ => json!({ "frame_type": "unknown" }),
+    }
+}
+
+fn stream_type_name(stream_type: &events::StreamType) -> &'static str {
+    match stream_type {
+        events::StreamType::Bidirectional { .. } => "bidirectional",
+        _ => "unidirectional",
+    }
+}
+
+fn key_types(key_type: &events::KeyType) -> &'static [&'static str] {
+    use events::KeyType;
+
+    match key_type {
+        KeyType::Initial { .. } => &["client_initial_secret", "server_initial_secret"],
+        KeyType::Handshake { .. } => &["client_handshake_secret", "server_handshake_secret"],
+        KeyType::ZeroRtt { .. } => &["client_0rtt_secret"],
+        _ => &["client_1rtt_secret", "server_1rtt_secret"],
+    }
+}
+
+fn path(path: &events::Path) -> Value {
+    json!({
+        "path_id": path.id,
+        "local_addr": SocketAddr::from(&path.local_addr).to_string(),
+        "local_cid": hex(path.local_cid.bytes),
+        "remote_addr": SocketAddr::from(&path.remote_addr).to_string(),
+        "remote_cid": hex(path.remote_cid.bytes),
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use core::fmt::Write as _;
+
+    let mut value = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(value, "{:02x}", byte);
+    }
+    value
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn unix_time(time: SystemTime) -> Duration {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::event::{Provider as _, Subscriber as _};
+    use s2n_quic_core::{
+        connection, endpoint,
+        event::{builder, IntoEvent},
+        time::{Duration, Timestamp},
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// A trace output which reports each flush
+    #[derive(Clone)]
+    struct Output {
+        data: Arc<Mutex<Vec<u8>>>,
+        flushes: mpsc::Sender<()>,
+    }
+
+    impl Output {
+        fn new() -> (Self, mpsc::Receiver<()>) {
+            let (flushes, receiver) = mpsc::channel();
+            let output = Self {
+                data: Default::default(),
+                flushes,
+            };
+            (output, receiver)
+        }
+
+        fn subscriber(&self) -> Subscriber {
+            let output = self.clone();
+            Provider::with_writer(move |_meta| Ok(output.clone()))
+                .start()
+                .unwrap()
+        }
+
+        /// Parses the records written so far, checking the JSON-SEQ framing of each one
+        fn records(&self) -> Vec<Value> {
+            let data = self.data.lock().unwrap();
+
+            //= https://www.rfc-editor.org/rfc/rfc7464#section-2.2
+            //# Each JSON text MUST be preceded by an ASCII RS and followed by an
+            //# ASCII LF
+            assert_eq!(data.first(), Some(&RECORD_SEPARATOR[0]));
+            data[1..]
+                .split(|byte| *byte == RECORD_SEPARATOR[0])
+                .map(|record| {
+                    assert_eq!(record.last(), Some(&b'\n'));
+                    serde_json::from_slice(record).unwrap()
+                })
+                .collect()
+        }
+    }
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            let _ = self.flushes.send(());
+            Ok(())
+        }
+    }
+
+    /// Waits for the writer thread to flush the output
+    fn wait_for_flush(flushes: &mpsc::Receiver<()>) {
+        flushes
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the output should be flushed");
+    }
+
+    fn meta(ms: u64) -> ConnectionMeta {
+        builder::ConnectionMeta {
+            endpoint_type: endpoint::Type::Server,
+            id: 7,
+            timestamp: unsafe { Timestamp::from_duration(Duration::from_millis(ms)) },
+        }
+        .into_event()
+    }
+
+    fn packet_received(number: u64) -> events::PacketReceived {
+        builder::PacketReceived {
+            packet_header: builder::PacketHeader::Initial { number, version: 1 },
+        }
+        .into_event()
+    }
+
+    #[test]
+    fn packet_test() {
+        let (output, flushes) = Output::new();
+        let mut subscriber = output.subscriber();
+
+        let info = builder::ConnectionInfo {}.into_event();
+        let mut context = subscriber.create_connection_context(&meta(1000), &info);
+
+        let header = || builder::PacketHeader::OneRtt { number: 3 };
+
+        subscriber.on_frame_sent(
+            &mut context,
+            &meta(1001),
+            &builder::FrameSent {
+                packet_header: header(),
+                path_id: 0,
+                frame: builder::Frame::Ping,
+            }
+            .into_event(),
+        );
+        subscriber.on_packet_sent(
+            &mut context,
+            &meta(1002),
+            &builder::PacketSent {
+                packet_header: header(),
+            }
+            .into_event(),
+        );
+        subscriber.on_packet_received(&mut context, &meta(1010), &packet_received(1));
+        drop(context);
+        wait_for_flush(&flushes);
+
+        let records = output.records();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["qlog_format"], "JSON-SEQ");
+        assert_eq!(records[0]["trace"]["vantage_point"]["type"], "server");
+
+        assert_eq!(
+            records[1],
+            json!({
+                "time": 2.0,
+                "name": "transport:packet_sent",
+                "data": {
+                    "header": { "packet_type": "1RTT", "packet_number": 3 },
+                    "frames": [{ "frame_type": "ping" }],
+                },
+            })
+        );
+
+        // the received packet is written once the trace is dropped
+        assert_eq!(
+            records[2],
+            json!({
+                "time": 10.0,
+                "name": "transport:packet_received",
+                "data": {
+                    "header": {
+                        "packet_type": "initial",
+                        "packet_number": 1,
+                        "version": "00000001",
+                    },
+                    "frames": [],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn json_seq_test() {
+        let (output, flushes) = Output::new();
+        let mut subscriber = output.subscriber();
+
+        let info = builder::ConnectionInfo {}.into_event();
+        let mut context = subscriber.create_connection_context(&meta(1000), &info);
+
+        // write enough records to be handed to the writer thread in several chunks
+        let count = 1000;
+        for number in 0..count {
+            subscriber.on_packet_received(
+                &mut context,
+                &meta(1000 + number),
+                &packet_received(number),
+            );
+        }
+        drop(context);
+        wait_for_flush(&flushes);
+
+        let records = output.records();
+        assert_eq!(records.len(), 1 + count as usize);
+        for (number, record) in records[1..].iter().enumerate() {
+            assert_eq!(record["name"], "transport:packet_received");
+            assert_eq!(record["data"]["header"]["packet_number"], number);
+        }
+    }
+
+    #[test]
+    fn connection_closed_test() {
+        let (output, flushes) = Output::new();
+        let mut subscriber = output.subscriber();
+
+        let info = builder::ConnectionInfo {}.into_event();
+        let mut context = subscriber.create_connection_context(&meta(1000), &info);
+
+        subscriber.on_packet_received(&mut context, &meta(1001), &packet_received(1));
+        subscriber.on_connection_closed(
+            &mut context,
+            &meta(1002),
+            &builder::ConnectionClosed {
+                error: connection::Error::idle_timer_expired(),
+            }
+            .into_event(),
+        );
+
+        // the trace is flushed when the connection closes, before the context is dropped
+        wait_for_flush(&flushes);
+        let records = output.records();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1]["name"], "transport:packet_received");
+        assert_eq!(
+            records[2],
+            json!({
+                "time": 2.0,
+                "name": "connectivity:connection_closed",
+                "data": {
+                    "owner": "local",
+                    "trigger": "idle_timeout",
+                    "reason": connection::Error::idle_timer_expired().to_string(),
+                },
+            })
+        );
+
+        drop(context);
+        wait_for_flush(&flushes);
+        assert_eq!(output.records(), records);
+    }
+}
@@ -16,6 +16,11 @@ pub trait Provider: 'static {
     ) -> Result<SocketAddress, Self::Error>;
 }
 
+#[cfg(all(feature = "provider-io-uring", target_os = "linux"))]
+pub mod io_uring;
+
+pub mod pcapng;
+pub mod testing;
 pub mod tokio;
 
 pub use self::tokio as default;
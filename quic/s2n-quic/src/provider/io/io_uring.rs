@@ -0,0 +1,44 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Provides an implementation of the [`io::Provider`](crate::provider::io::Provider)
+//! using [io_uring](https://kernel.dk/io_uring.pdf) on a
+//! [`Tokio runtime`](https://docs.rs/tokio/latest/tokio/runtime/index.html)
+//!
+//! The provider is configured the same way as the default Tokio provider:
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! use s2n_quic::{provider::io::io_uring, Server};
+//!
+//! # async fn example() -> Result<(), Box<dyn Error>> {
+//! let io = io_uring::Provider::builder()
+//!     .with_receive_address("0.0.0.0:443".parse()?)?
+//!     .build()?;
+//!
+//! let server = Server::builder()
+//!     .with_io(io)?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use s2n_quic_core::{endpoint::Endpoint, inet::SocketAddress};
+use s2n_quic_platform::io::io_uring;
+use std::io;
+
+pub use self::io_uring::{Builder, Io as Provider};
+
+impl super::Provider for Provider {
+    type PathHandle = io_uring::PathHandle;
+    type Error = io::Error;
+
+    fn start<E: Endpoint<PathHandle = Self::PathHandle>>(
+        self,
+        endpoint: E,
+    ) -> Result<SocketAddress, Self::Error> {
+        let (_join_handle, local_addr) = Provider::start(self, endpoint)?;
+        Ok(local_addr)
+    }
+}
@@ -0,0 +1,1407 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Captures the datagrams of an IO provider into a [pcapng](https://datatracker.ietf.org/doc/draft-ietf-opsawg-pcapng/)
+//! file
+//!
+//! Each datagram is written with synthesized IP and UDP headers as it is sent or received, after
+//! encryption and before decryption. TLS secrets which are passed to [`Secrets`] are embedded in
+//! the capture as a Decryption Secrets Block, which allows tools such as Wireshark to decrypt
+//! the traffic without a separate key log file.
+//!
+//! The [`Subscriber`] associates the secrets with their connections, so the secrets of closed
+//! connections aren't embedded in later capture files, and limits the capture to the connections
+//! accepted by the filter. The files are written by a background thread, so the endpoint never
+//! blocks on the capture.
+//!
+//! ```rust,no_run
+//! # use std::{error::Error, path::Path};
+//! use s2n_quic::{
+//!     provider::{io, io::pcapng, tls},
+//!     Server,
+//! };
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let capture = pcapng::Builder::default()
+//!     .with_file("/var/log/s2n-quic/capture.pcapng")?
+//!     .with_max_file_size(100 * 1024 * 1024)?
+//!     .with_max_files(10)?;
+//!
+//! let tls = tls::default::Server::builder()
+//!     .with_certificate(Path::new("./certs/cert.pem"), Path::new("./certs/key.pem"))?
+//!     .with_key_log(capture.secrets())?
+//!     .build()?;
+//!
+//! let event = capture.subscriber();
+//! let io = capture.build(io::Default::new("127.0.0.1:443")?)?;
+//!
+//! let server = Server::builder()
+//!     .with_tls(tls)?
+//!     .with_io(io)?
+//!     .with_event(event)?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use crate::provider::event::{self, events, ConnectionInfo, ConnectionMeta};
+use s2n_quic_core::{
+    endpoint::{self, CloseError},
+    inet::SocketAddress,
+    io::{rx, tx},
+    path::{self, MaxMtu},
+    time::{Clock, Timestamp},
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    iter,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    task::{Context, Poll},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A connection which is about to be captured
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Connection<'a> {
+    pub endpoint_type: events::EndpointType,
+    /// The internal ID of the connection, as reported in [`ConnectionMeta`]
+    pub id: u64,
+    pub local_address: SocketAddr,
+    pub remote_address: SocketAddr,
+    /// The connection ID which the peer uses to address the endpoint
+    pub local_connection_id: &'a [u8],
+    /// The connection ID which the endpoint uses to address the peer
+    pub remote_connection_id: &'a [u8],
+}
+
+type Filter = Box<dyn FnMut(&Connection) -> bool + Send>;
+
+/// The direction of a captured datagram
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Sent,
+    Received,
+}
+
+/// A datagram which is about to be captured
+#[derive(Debug)]
+struct Datagram<'a> {
+    direction: Direction,
+    local_address: SocketAddr,
+    remote_address: SocketAddr,
+    /// The UDP payload of the datagram
+    payload: &'a [u8],
+}
+
+pub struct Provider<Io> {
+    io: Io,
+    state: Arc<Mutex<State>>,
+}
+
+impl<Io: super::Provider> super::Provider for Provider<Io> {
+    type PathHandle = Io::PathHandle;
+    type Error = Io::Error;
+
+    fn start<E: endpoint::Endpoint<PathHandle = Self::PathHandle>>(
+        self,
+        endpoint: E,
+    ) -> Result<SocketAddress, Self::Error> {
+        self.io.start(Endpoint {
+            inner: endpoint,
+            state: self.state,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Builder {
+    path: Option<PathBuf>,
+    max_file_size: Option<u64>,
+    max_files: Option<u64>,
+    filter: Option<Filter>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Builder {
+    /// Sets the path of the capture file
+    ///
+    /// When the capture is rotated, later files are named after this path with an increasing
+    /// index before the extension, e.g. `capture.1.pcapng`.
+    pub fn with_file<P: Into<PathBuf>>(mut self, path: P) -> io::Result<Self> {
+        self.path = Some(path.into());
+        Ok(self)
+    }
+
+    /// Starts a new capture file once the current file exceeds `max_file_size` bytes
+    ///
+    /// Each new file starts with the secrets of the connections which are still open.
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> io::Result<Self> {
+        if max_file_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "max_file_size must be greater than 0",
+            ));
+        }
+        self.max_file_size = Some(max_file_size);
+        Ok(self)
+    }
+
+    /// Removes the oldest capture file when rotating would keep more than `max_files` files
+    pub fn with_max_files(mut self, max_files: u64) -> io::Result<Self> {
+        if max_files == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "max_files must be greater than 0",
+            ));
+        }
+        self.max_files = Some(max_files);
+        Ok(self)
+    }
+
+    /// Only captures the connections for which `filter` returns `true`
+    ///
+    /// The filter is called once when each connection is created. Every datagram the connection
+    /// sends or receives is then captured, even after the connection migrates to another path.
+    /// Datagrams which don't belong to a connection, such as Version Negotiation packets, aren't
+    /// captured. The filter requires the [`Subscriber`] to be registered with the endpoint.
+    pub fn with_filter<F>(mut self, filter: F) -> io::Result<Self>
+    where
+        F: 'static + Send + FnMut(&Connection) -> bool,
+    {
+        self.filter = Some(Box::new(filter));
+        Ok(self)
+    }
+
+    /// Returns the key log which embeds TLS secrets into the capture
+    ///
+    /// The key log should be passed to the TLS provider of the endpoint.
+    pub fn secrets(&self) -> Secrets {
+        Secrets(self.state.clone())
+    }
+
+    /// Returns the event subscriber which tracks the connections of the endpoint
+    ///
+    /// The subscriber should be registered with the endpoint. Without it, the secrets of every
+    /// connection are kept for the lifetime of the capture.
+    pub fn subscriber(&self) -> Subscriber {
+        Subscriber(self.state.clone())
+    }
+
+    /// Captures the datagrams of `io`
+    pub fn build<Io>(mut self, io: Io) -> io::Result<Provider<Io>> {
+        let (file, commands) = self.start()?;
+
+        thread::Builder::new()
+            .name("s2n-quic-pcapng".into())
+            .spawn(move || write_files(file, commands))?;
+
+        Ok(Provider {
+            io,
+            state: self.state,
+        })
+    }
+
+    /// Creates the first capture file and the channel which hands blocks to the writer thread
+    fn start(&mut self) -> io::Result<(File, mpsc::Receiver<Command>)> {
+        let path = self.path.take().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "missing capture file path")
+        })?;
+
+        let file = File::create(&path)?;
+        let (commands, receiver) = mpsc::channel();
+
+        let mut output = Output {
+            path,
+            max_file_size: self.max_file_size,
+            max_files: self.max_files,
+            file_index: 0,
+            len: 0,
+            commands,
+        };
+        // the receiver is still open
+        let _ = output.write(file_header());
+
+        if let Ok(mut state) = self.state.lock() {
+            state.filter = self.filter.take();
+            state.output = Some(output);
+        }
+
+        Ok((file, receiver))
+    }
+}
+
+/// Collects TLS secrets in the NSS key log format
+///
+/// The secrets are embedded in the capture before the next captured datagram.
+#[derive(Clone)]
+pub struct Secrets(Arc<Mutex<State>>);
+
+impl Write for Secrets {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Ok(mut state) = self.0.lock() {
+            state.key_log.write(buf);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "s2n-quic-rustls")]
+impl s2n_quic_rustls::rustls::KeyLog for Secrets {
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        let mut line = String::from(label);
+        for value in [client_random, secret].iter() {
+            line.push(' ');
+            for byte in value.iter() {
+                line.push_str(&format!("{:02x}", byte));
+            }
+        }
+        line.push('\n');
+
+        if let Ok(mut state) = self.0.lock() {
+            state.key_log.write(line.as_bytes());
+        }
+    }
+}
+
+/// Associates the logged secrets with their connections and applies the capture's filter
+#[derive(Clone)]
+pub struct Subscriber(Arc<Mutex<State>>);
+
+/// The capture state of a connection
+#[derive(Debug)]
+pub struct ConnectionContext {
+    is_captured: bool,
+}
+
+impl Subscriber {
+    fn on_datagram(&self, context: &ConnectionContext) {
+        if context.is_captured {
+            if let Ok(mut state) = self.0.lock() {
+                state.is_captured = true;
+            }
+        }
+    }
+}
+
+impl event::Subscriber for Subscriber {
+    type ConnectionContext = ConnectionContext;
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        // the filter is applied once the connection's path is known
+        ConnectionContext { is_captured: false }
+    }
+
+    fn on_connection_started(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionStarted,
+    ) {
+        if let Ok(mut state) = self.0.lock() {
+            context.is_captured = match state.filter.as_mut() {
+                Some(filter) => filter(&Connection {
+                    endpoint_type: meta.endpoint_type.clone(),
+                    id: meta.id,
+                    local_address: SocketAddr::from(&event.path.local_addr),
+                    remote_address: SocketAddr::from(&event.path.remote_addr),
+                    local_connection_id: event.path.local_cid.bytes,
+                    remote_connection_id: event.path.remote_cid.bytes,
+                }),
+                None => true,
+            };
+        }
+    }
+
+    fn on_datagram_sent(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        _event: &events::DatagramSent,
+    ) {
+        self.on_datagram(context);
+    }
+
+    fn on_datagram_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        _event: &events::DatagramReceived,
+    ) {
+        self.on_datagram(context);
+    }
+
+    fn on_key_update(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        _event: &events::KeyUpdate,
+    ) {
+        if let Ok(mut state) = self.0.lock() {
+            state.key_log.claim(meta.id, context.is_captured);
+        }
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        _event: &events::ConnectionClosed,
+    ) {
+        if let Ok(mut state) = self.0.lock() {
+            state.key_log.remove(meta.id);
+        }
+    }
+}
+
+struct Endpoint<E> {
+    inner: E,
+    state: Arc<Mutex<State>>,
+}
+
+impl<E: endpoint::Endpoint> endpoint::Endpoint for Endpoint<E> {
+    type PathHandle = E::PathHandle;
+    type Subscriber = E::Subscriber;
+
+    const ENDPOINT_TYPE: endpoint::Type = E::ENDPOINT_TYPE;
+
+    fn receive<Rx, C>(&mut self, rx: &mut Rx, clock: &C)
+    where
+        Rx: rx::Queue<Handle = Self::PathHandle>,
+        C: Clock,
+    {
+        use rx::Entry as _;
+
+        let local_address = rx.local_address();
+        let is_filtered = self
+            .state
+            .lock()
+            .map_or(false, |state| state.filter.is_some());
+
+        // capture the datagrams before they are decrypted in place
+        if !is_filtered {
+            if let Ok(mut state) = self.state.lock() {
+                for entry in rx.as_slice_mut() {
+                    if let Some((header, payload)) = entry.read(&local_address) {
+                        state.write_packet(packet_block(
+                            Direction::Received,
+                            &header.path,
+                            payload,
+                        ));
+                    }
+                }
+            }
+
+            return self.inner.receive(rx, clock);
+        }
+
+        // the endpoint receives one datagram at a time, so each one can be matched with the
+        // connection which received it
+        let len = rx.len();
+        for index in 0..len {
+            let packet = rx.as_slice_mut()[index]
+                .read(&local_address)
+                .map(|(header, payload)| packet_block(Direction::Received, &header.path, payload));
+
+            self.inner.receive(&mut RxEntry { queue: rx, index }, clock);
+
+            if let Some(packet) = packet {
+                if let Ok(mut state) = self.state.lock() {
+                    if state.take_is_captured() {
+                        state.write_packet(packet);
+                    }
+                }
+            }
+        }
+        rx.finish(len);
+    }
+
+    fn transmit<Tx, C>(&mut self, tx: &mut Tx, clock: &C)
+    where
+        Tx: tx::Queue<Handle = Self::PathHandle>,
+        C: Clock,
+    {
+        let mut queue = TxQueue {
+            inner: tx,
+            state: &self.state,
+        };
+        self.inner.transmit(&mut queue, clock);
+    }
+
+    fn poll_wakeups<C: Clock>(
+        &mut self,
+        cx: &mut Context<'_>,
+        clock: &C,
+    ) -> Poll<Result<usize, CloseError>> {
+        self.inner.poll_wakeups(cx, clock)
+    }
+
+    fn timeout(&self) -> Option<Timestamp> {
+        self.inner.timeout()
+    }
+
+    fn set_max_mtu(&mut self, max_mtu: MaxMtu) {
+        self.inner.set_max_mtu(max_mtu)
+    }
+
+    fn subscriber(&mut self) -> &mut Self::Subscriber {
+        self.inner.subscriber()
+    }
+}
+
+/// Exposes a single entry of the inner queue
+struct RxEntry<'a, Q> {
+    queue: &'a mut Q,
+    index: usize,
+}
+
+impl<'a, Q: rx::Queue> rx::Queue for RxEntry<'a, Q> {
+    type Entry = Q::Entry;
+    type Handle = Q::Handle;
+
+    fn local_address(&self) -> path::LocalAddress {
+        self.queue.local_address()
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [Self::Entry] {
+        let index = self.index;
+        &mut self.queue.as_slice_mut()[index..=index]
+    }
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn finish(&mut self, _count: usize) {
+        // the inner queue is finished once all of its entries have been received
+    }
+}
+
+/// Captures each message as it is written into the inner queue
+struct TxQueue<'a, Q> {
+    inner: &'a mut Q,
+    state: &'a Mutex<State>,
+}
+
+impl<'a, Q: tx::Queue> tx::Queue for TxQueue<'a, Q> {
+    type Entry = Q::Entry;
+    type Handle = Q::Handle;
+
+    const SUPPORTS_ECN: bool = Q::SUPPORTS_ECN;
+    const SUPPORTS_PACING: bool = Q::SUPPORTS_PACING;
+    const SUPPORTS_FLOW_LABELS: bool = Q::SUPPORTS_FLOW_LABELS;
+
+    fn push<M: tx::Message<Handle = Self::Handle>>(
+        &mut self,
+        message: M,
+    ) -> Result<tx::Outcome, tx::Error> {
+        self.inner.push(TxMessage {
+            inner: message,
+            state: self.state,
+        })
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [Self::Entry] {
+        self.inner.as_slice_mut()
+    }
+
+    fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+struct TxMessage<'a, M> {
+    inner: M,
+    state: &'a Mutex<State>,
+}
+
+impl<'a, M: tx::Message> tx::Message for TxMessage<'a, M> {
+    type Handle = M::Handle;
+
+    fn path_handle(&self) -> &Self::Handle {
+        self.inner.path_handle()
+    }
+
+    fn ecn(&mut self) -> s2n_quic_core::inet::ExplicitCongestionNotification {
+        self.inner.ecn()
+    }
+
+    fn delay(&mut self) -> core::time::Duration {
+        self.inner.delay()
+    }
+
+    fn ipv6_flow_label(&mut self) -> u32 {
+        self.inner.ipv6_flow_label()
+    }
+
+    fn can_gso(&self) -> bool {
+        self.inner.can_gso()
+    }
+
+    fn write_payload(&mut self, buffer: &mut [u8], gso_offset: usize) -> usize {
+        // the connection reports the datagram to the subscriber while writing it
+        let len = self.inner.write_payload(buffer, gso_offset);
+
+        // the payload is encrypted by the time it's written
+        if len > 0 {
+            if let Ok(mut state) = self.state.lock() {
+                if state.take_is_captured() {
+                    let handle = *self.inner.path_handle();
+                    state.write_packet(packet_block(Direction::Sent, &handle, &buffer[..len]));
+                }
+            }
+        }
+
+        len
+    }
+}
+
+/// The capture state which is shared by the IO provider, the subscriber and the key log
+///
+/// The state is only used on the endpoint's thread, while the capture files are written by a
+/// separate thread.
+#[derive(Default)]
+struct State {
+    filter: Option<Filter>,
+    key_log: KeyLog,
+    /// Set by the subscriber when the datagram which is being sent or received belongs to a
+    /// captured connection
+    is_captured: bool,
+    /// The output of the capture, which is cleared once the writer thread has exited
+    output: Option<Output>,
+}
+
+impl State {
+    /// Returns `true` if the datagram which was just sent or received should be captured
+    fn take_is_captured(&mut self) -> bool {
+        let is_captured = core::mem::take(&mut self.is_captured);
+        // without a filter, the datagrams which don't belong to a connection are captured too
+        self.filter.is_none() || is_captured
+    }
+
+    fn write_packet(&mut self, packet: Vec<u8>) {
+        let output = if let Some(output) = self.output.as_mut() {
+            output
+        } else {
+            return;
+        };
+
+        let mut result = Ok(());
+
+        if output.is_full() {
+            result = output.rotate();
+            self.key_log.embed_all();
+        }
+
+        // embed any new secrets before the packets which need them
+        let secrets = self.key_log.take_unembedded();
+        let mut blocks = if secrets.is_empty() {
+            vec![]
+        } else {
+            decryption_secrets_block(&secrets)
+        };
+        blocks.extend(packet);
+
+        // stop capturing once the writer thread has exited
+        if result.and_then(|_| output.write(blocks)).is_err() {
+            self.output = None;
+        }
+    }
+}
+
+/// The TLS secrets which are embedded in the capture
+#[derive(Debug, Default)]
+struct KeyLog {
+    /// A line which hasn't been terminated yet
+    pending: Vec<u8>,
+    /// Complete lines which haven't been claimed by a connection yet
+    unclaimed: Vec<u8>,
+    /// The number of bytes of `unclaimed` which have been embedded in the current file
+    unclaimed_embedded: usize,
+    /// The secrets of each open connection which is captured
+    connections: HashMap<u64, ConnectionSecrets>,
+    /// The connections which have secrets that haven't been embedded in the current file
+    unembedded: Vec<u64>,
+}
+
+#[derive(Debug, Default)]
+struct ConnectionSecrets {
+    lines: Vec<u8>,
+    /// The number of bytes of `lines` which have been embedded in the current file
+    embedded: usize,
+}
+
+impl KeyLog {
+    fn write(&mut self, buf: &[u8]) {
+        self.pending.extend_from_slice(buf);
+
+        // only complete lines are embedded so a secret isn't split across blocks
+        if let Some(end) = self.pending.iter().rposition(|byte| *byte == b'\n') {
+            self.unclaimed.extend(self.pending.drain(..=end));
+        }
+    }
+
+    /// Associates the unclaimed secrets with a connection which has just installed keys
+    ///
+    /// The TLS provider logs secrets while the endpoint processes the connection which derives
+    /// them, right before the connection installs the keys.
+    fn claim(&mut self, connection: u64, is_captured: bool) {
+        let lines = core::mem::take(&mut self.unclaimed);
+        let embedded = core::mem::take(&mut self.unclaimed_embedded);
+
+        if !is_captured || lines.is_empty() {
+            return;
+        }
+
+        let secrets = self.connections.entry(connection).or_default();
+        if secrets.embedded == secrets.lines.len() {
+            secrets.embedded += embedded;
+        }
+        secrets.lines.extend(lines);
+        self.unembedded.push(connection);
+    }
+
+    /// Drops the secrets of a connection which has closed
+    fn remove(&mut self, connection: u64) {
+        self.connections.remove(&connection);
+    }
+
+    /// Embeds all of the secrets of the open connections into the next file
+    fn embed_all(&mut self) {
+        self.unembedded.clear();
+        for (connection, secrets) in self.connections.iter_mut() {
+            secrets.embedded = 0;
+            self.unembedded.push(*connection);
+        }
+        self.unclaimed_embedded = 0;
+    }
+
+    /// Returns the secrets which haven't been embedded in the current file yet
+    fn take_unembedded(&mut self) -> Vec<u8> {
+        let mut lines = vec![];
+
+        for connection in self.unembedded.drain(..) {
+            if let Some(secrets) = self.connections.get_mut(&connection) {
+                lines.extend_from_slice(&secrets.lines[secrets.embedded..]);
+                secrets.embedded = secrets.lines.len();
+            }
+        }
+
+        lines.extend_from_slice(&self.unclaimed[self.unclaimed_embedded..]);
+        self.unclaimed_embedded = self.unclaimed.len();
+
+        lines
+    }
+}
+
+/// The endpoint's side of the capture files, which hands encoded blocks to the writer thread
+struct Output {
+    path: PathBuf,
+    max_file_size: Option<u64>,
+    max_files: Option<u64>,
+    file_index: u64,
+    /// The number of bytes which have been written to the current file
+    len: u64,
+    commands: mpsc::Sender<Command>,
+}
+
+impl Output {
+    fn is_full(&self) -> bool {
+        self.max_file_size
+            .map_or(false, |max_file_size| self.len >= max_file_size)
+    }
+
+    /// Starts the next capture file, removing the oldest file if there are too many
+    fn rotate(&mut self) -> Result<(), mpsc::SendError<Command>> {
+        self.file_index += 1;
+
+        let remove = self
+            .max_files
+            .and_then(|max_files| self.file_index.checked_sub(max_files))
+            .map(|index| file_path(&self.path, index));
+
+        self.commands.send(Command::Open {
+            path: file_path(&self.path, self.file_index),
+            remove,
+        })?;
+        self.len = 0;
+
+        self.write(file_header())
+    }
+
+    fn write(&mut self, blocks: Vec<u8>) -> Result<(), mpsc::SendError<Command>> {
+        self.len += blocks.len() as u64;
+        self.commands.send(Command::Write(blocks))
+    }
+}
+
+/// A request to the writer thread
+enum Command {
+    /// Closes the current file and creates the file at `path`, after removing the file at
+    /// `remove`
+    Open {
+        path: PathBuf,
+        remove: Option<PathBuf>,
+    },
+    /// Appends encoded blocks to the current file
+    Write(Vec<u8>),
+}
+
+/// Writes the capture files until the endpoint has been dropped or writing fails
+fn write_files(file: File, commands: mpsc::Receiver<Command>) -> io::Result<()> {
+    let mut file = BufWriter::new(file);
+
+    while let Ok(command) = commands.recv() {
+        for command in iter::once(command).chain(commands.try_iter()) {
+            file = execute(file, command)?;
+        }
+
+        // flush once the queued blocks have been written
+        file.flush()?;
+    }
+
+    Ok(())
+}
+
+fn execute(mut file: BufWriter<File>, command: Command) -> io::Result<BufWriter<File>> {
+    match command {
+        Command::Open { path, remove } => {
+            file.flush()?;
+
+            if let Some(remove) = remove {
+                // the file may have already been removed
+                let _ = fs::remove_file(remove);
+            }
+
+            Ok(BufWriter::new(File::create(path)?))
+        }
+        Command::Write(blocks) => {
+            file.write_all(&blocks)?;
+            Ok(file)
+        }
+    }
+}
+
+fn file_path(path: &Path, index: u64) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    path.with_file_name(name)
+}
+
+fn packet_block<H: path::Handle>(direction: Direction, handle: &H, payload: &[u8]) -> Vec<u8> {
+    let (local_address, remote_address) = addresses(handle);
+
+    let datagram = Datagram {
+        direction,
+        local_address,
+        remote_address,
+        payload,
+    };
+
+    enhanced_packet_block(&datagram, SystemTime::now())
+}
+
+/// Returns the local and remote address of the path in the same address family
+fn addresses<H: path::Handle>(handle: &H) -> (SocketAddr, SocketAddr) {
+    let local: SocketAddr = handle.local_address().unmap().into();
+    let remote: SocketAddr = handle.remote_address().unmap().into();
+
+    match (local, remote) {
+        (SocketAddr::V4(_), SocketAddr::V4(_)) | (SocketAddr::V6(_), SocketAddr::V6(_)) => {
+            (local, remote)
+        }
+        // sockets bound to an unspecified address don't know the local address
+        (local, remote) if local.ip().is_unspecified() => {
+            let ip = match remote {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            (SocketAddr::new(ip, local.port()), remote)
+        }
+        (local, remote) => (to_ipv6(local), to_ipv6(remote)),
+    }
+}
+
+fn to_ipv6(address: SocketAddr) -> SocketAddr {
+    match address {
+        SocketAddr::V4(v4) => SocketAddr::new(IpAddr::V6(v4.ip().to_ipv6_mapped()), v4.port()),
+        address => address,
+    }
+}
+
+//= https://www.ietf.org/archive/id/draft-ietf-opsawg-pcapng-03.html#section-4.1
+//# The Section Header Block (SHB) is mandatory.  It identifies the
+//# beginning of a section of the capture file.
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const DECRYPTION_SECRETS_BLOCK: u32 = 0x0000_000A;
+
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+/// Packets begin with an IPv4 or IPv6 header
+const LINKTYPE_RAW: u16 = 101;
+/// The secrets are in the NSS key log format
+const TLS_KEY_LOG: u32 = 0x544c_534b;
+
+const OPT_ENDOFOPT: u16 = 0;
+const EPB_FLAGS: u16 = 2;
+const EPB_FLAGS_INBOUND: u32 = 0b01;
+const EPB_FLAGS_OUTBOUND: u32 = 0b10;
+
+/// Encodes a block with the given body, adding the type and lengths
+fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    let len = (12 + body.len()) as u32;
+    let mut block = Vec::with_capacity(len as usize);
+    block.extend_from_slice(&block_type.to_le_bytes());
+    block.extend_from_slice(&len.to_le_bytes());
+    block.extend_from_slice(body);
+    block.extend_from_slice(&len.to_le_bytes());
+    block
+}
+
+/// Appends `data` followed by padding to a 32-bit boundary
+fn extend_padded(body: &mut Vec<u8>, data: &[u8]) {
+    body.extend_from_slice(data);
+    body.resize(body.len() + (4 - data.len() % 4) % 4, 0);
+}
+
+fn section_header_block() -> Vec<u8> {
+    let mut body = vec![];
+    body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+    // version 1.0
+    body.extend_from_slice(&1u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    // the section length isn't known ahead of time
+    body.extend_from_slice(&(-1i64).to_le_bytes());
+    block(SECTION_HEADER_BLOCK, &body)
+}
+
+fn interface_description_block() -> Vec<u8> {
+    let mut body = vec![];
+    body.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    // no snap length
+    body.extend_from_slice(&0u32.to_le_bytes());
+    block(INTERFACE_DESCRIPTION_BLOCK, &body)
+}
+
+/// The blocks which start each capture file
+fn file_header() -> Vec<u8> {
+    let mut blocks = section_header_block();
+    blocks.extend(interface_description_block());
+    blocks
+}
+
+fn decryption_secrets_block(secrets: &[u8]) -> Vec<u8> {
+    let mut body = vec![];
+    body.extend_from_slice(&TLS_KEY_LOG.to_le_bytes());
+    body.extend_from_slice(&(secrets.len() as u32).to_le_bytes());
+    extend_padded(&mut body, secrets);
+    block(DECRYPTION_SECRETS_BLOCK, &body)
+}
+
+fn enhanced_packet_block(datagram: &Datagram, time: SystemTime) -> Vec<u8> {
+    let packet = ip_packet(datagram);
+
+    // timestamps default to microsecond resolution
+    let timestamp = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+
+    let mut body = vec![];
+    // interface ID
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+    body.extend_from_slice(&(timestamp as u32).to_le_bytes());
+    body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+    body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+    extend_padded(&mut body, &packet);
+
+    let flags = match datagram.direction {
+        Direction::Received => EPB_FLAGS_INBOUND,
+        Direction::Sent => EPB_FLAGS_OUTBOUND,
+    };
+    body.extend_from_slice(&EPB_FLAGS.to_le_bytes());
+    body.extend_from_slice(&4u16.to_le_bytes());
+    body.extend_from_slice(&flags.to_le_bytes());
+    body.extend_from_slice(&OPT_ENDOFOPT.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+
+    block(ENHANCED_PACKET_BLOCK, &body)
+}
+
+const UDP_PROTOCOL: u8 = 17;
+const UDP_HEADER_LEN: usize = 8;
+
+/// Synthesizes the IP and UDP headers of the datagram
+fn ip_packet(datagram: &Datagram) -> Vec<u8> {
+    let (source, destination) = match datagram.direction {
+        Direction::Sent => (datagram.local_address, datagram.remote_address),
+        Direction::Received => (datagram.remote_address, datagram.local_address),
+    };
+
+    let udp_len = (UDP_HEADER_LEN + datagram.payload.len()) as u16;
+    let mut udp = Vec::with_capacity(udp_len as usize);
+    udp.extend_from_slice(&source.port().to_be_bytes());
+    udp.extend_from_slice(&destination.port().to_be_bytes());
+    udp.extend_from_slice(&udp_len.to_be_bytes());
+    udp.extend_from_slice(&[0, 0]);
+    udp.extend_from_slice(datagram.payload);
+
+    let mut packet = vec![];
+
+    let pseudo_header = match (source.ip(), destination.ip()) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            let total_len = 20 + udp_len;
+            let mut header = vec![
+                0x45, // version 4, 5 words
+                0,
+                (total_len >> 8) as u8,
+                total_len as u8,
+                0,
+                0,
+                0x40, // don't fragment
+                0,
+                64, // TTL
+                UDP_PROTOCOL,
+                0,
+                0,
+            ];
+            header.extend_from_slice(&source.octets());
+            header.extend_from_slice(&destination.octets());
+            let checksum = checksum(&[&header]);
+            header[10..12].copy_from_slice(&checksum.to_be_bytes());
+            packet.extend_from_slice(&header);
+
+            let mut pseudo_header = vec![];
+            pseudo_header.extend_from_slice(&source.octets());
+            pseudo_header.extend_from_slice(&destination.octets());
+            pseudo_header.extend_from_slice(&[0, UDP_PROTOCOL]);
+            pseudo_header.extend_from_slice(&udp_len.to_be_bytes());
+            pseudo_header
+        }
+        (source, destination) => {
+            let source = to_ipv6_addr(source);
+            let destination = to_ipv6_addr(destination);
+
+            packet.extend_from_slice(&[0x60, 0, 0, 0]);
+            packet.extend_from_slice(&udp_len.to_be_bytes());
+            packet.extend_from_slice(&[UDP_PROTOCOL, 64]);
+            packet.extend_from_slice(&source.octets());
+            packet.extend_from_slice(&destination.octets());
+
+            let mut pseudo_header = vec![];
+            pseudo_header.extend_from_slice(&source.octets());
+            pseudo_header.extend_from_slice(&destination.octets());
+            pseudo_header.extend_from_slice(&(udp_len as u32).to_be_bytes());
+            pseudo_header.extend_from_slice(&[0, 0, 0, UDP_PROTOCOL]);
+            pseudo_header
+        }
+    };
+
+    let checksum = match checksum(&[&pseudo_header, &udp]) {
+        // a zero checksum is transmitted as all ones
+        0 => 0xffff,
+        checksum => checksum,
+    };
+    udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+
+    packet.extend_from_slice(&udp);
+    packet
+}
+
+fn to_ipv6_addr(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+/// Computes the internet checksum of the concatenated chunks
+///
+/// Each chunk other than the last must have an even length.
+fn checksum(chunks: &[&[u8]]) -> u16 {
+    let mut sum = 0u32;
+
+    for chunk in chunks {
+        for word in chunk.chunks(2) {
+            let word = match *word {
+                [a, b] => u16::from_be_bytes([a, b]),
+                [a] => u16::from_be_bytes([a, 0]),
+                _ => 0,
+            };
+            sum += word as u32;
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+    }
+
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    fn datagram(direction: Direction, payload: &[u8]) -> Datagram<'_> {
+        Datagram {
+            direction,
+            local_address: "192.168.0.1:443".parse().unwrap(),
+            remote_address: "10.0.0.1:5000".parse().unwrap(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn ip_packet_test() {
+        let packet = ip_packet(&datagram(Direction::Received, &[1, 2, 3]));
+
+        assert_eq!(packet.len(), 20 + 8 + 3);
+        // the IPv4 and UDP checksums verify
+        assert_eq!(checksum(&[&packet[..20]]), 0);
+        assert_eq!(&packet[12..16], &[10, 0, 0, 1]);
+        assert_eq!(&packet[16..20], &[192, 168, 0, 1]);
+        assert_eq!(&packet[20..24], &[0x13, 0x88, 0x01, 0xbb]);
+
+        let mut pseudo_header = vec![];
+        pseudo_header.extend_from_slice(&packet[12..20]);
+        pseudo_header.extend_from_slice(&[0, UDP_PROTOCOL, 0, 11]);
+        assert_eq!(checksum(&[&pseudo_header, &packet[20..]]), 0);
+    }
+
+    #[test]
+    fn addresses_test() {
+        let local: SocketAddr = "[::]:443".parse().unwrap();
+        let remote: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+
+        let handle = path::Tuple {
+            local_address: SocketAddress::from(local).into(),
+            remote_address: SocketAddress::from(remote).into(),
+        };
+        assert_eq!(addresses(&handle), ("0.0.0.0:443".parse().unwrap(), remote));
+
+        let packet = ip_packet(&Datagram {
+            direction: Direction::Sent,
+            local_address: "[::1]:443".parse().unwrap(),
+            remote_address: "[::2]:5000".parse().unwrap(),
+            payload: &[0; 5],
+        });
+        assert_eq!(packet.len(), 40 + 8 + 5);
+        assert_eq!(packet[0] >> 4, 6);
+    }
+
+    /// Starts a capture without the writer thread
+    ///
+    /// Returns a function which writes the files once the test is done.
+    fn start(mut builder: Builder) -> (Arc<Mutex<State>>, impl FnOnce()) {
+        let (file, commands) = builder.start().unwrap();
+        let state = builder.state.clone();
+
+        let finish = {
+            let state = state.clone();
+            move || {
+                // the writer returns once the output has been dropped
+                state.lock().unwrap().output = None;
+                write_files(file, commands).unwrap();
+            }
+        };
+
+        (state, finish)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("s2n-quic-pcapng-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Captures a datagram in the same way as the endpoint, once the connection has sent it
+    fn capture(state: &Mutex<State>) {
+        let mut state = state.lock().unwrap();
+        if state.take_is_captured() {
+            let datagram = datagram(Direction::Sent, &[0; 10]);
+            state.write_packet(enhanced_packet_block(&datagram, SystemTime::now()));
+        }
+    }
+
+    /// Returns the type and body of each block in the file
+    fn blocks(path: &Path) -> Vec<(u32, Vec<u8>)> {
+        let contents = fs::read(path).unwrap();
+        let mut blocks = vec![];
+        let mut offset = 0;
+        while offset < contents.len() {
+            let block_type = u32::from_le_bytes(contents[offset..offset + 4].try_into().unwrap());
+            let len = u32::from_le_bytes(contents[offset + 4..offset + 8].try_into().unwrap());
+            let end = offset + len as usize;
+            blocks.push((block_type, contents[offset + 8..end - 4].to_vec()));
+            offset = end;
+        }
+        assert_eq!(offset, contents.len());
+        blocks
+    }
+
+    fn block_types(blocks: &[(u32, Vec<u8>)]) -> Vec<u32> {
+        blocks.iter().map(|(block_type, _)| *block_type).collect()
+    }
+
+    /// Returns the secrets of a Decryption Secrets Block
+    fn secrets(body: &[u8]) -> &[u8] {
+        let len = u32::from_le_bytes(body[4..8].try_into().unwrap());
+        &body[8..8 + len as usize]
+    }
+
+    #[test]
+    fn secrets_test() {
+        let dir = temp_dir("secrets");
+        let path = dir.join("capture.pcapng");
+
+        let builder = Builder::default()
+            .with_file(&path)
+            .unwrap()
+            .with_max_file_size(400)
+            .unwrap();
+        let mut key_log = builder.secrets();
+        let (state, finish) = start(builder);
+
+        let first = b"CLIENT_TRAFFIC_SECRET_0 01 01\n";
+        let second = b"CLIENT_TRAFFIC_SECRET_0 02 02\n";
+
+        // the incomplete line is embedded once it has been terminated
+        key_log.write_all(&first[..10]).unwrap();
+        capture(&state);
+        key_log.write_all(&first[10..]).unwrap();
+        state.lock().unwrap().key_log.claim(1, true);
+        capture(&state);
+
+        key_log.write_all(second).unwrap();
+        state.lock().unwrap().key_log.claim(2, true);
+        capture(&state);
+
+        // the first connection closes before the file exceeds the maximum size
+        state.lock().unwrap().key_log.remove(1);
+        capture(&state);
+        assert_eq!(state.lock().unwrap().output.as_ref().unwrap().file_index, 1);
+        finish();
+
+        let blocks_0 = blocks(&path);
+        assert_eq!(
+            block_types(&blocks_0),
+            [
+                SECTION_HEADER_BLOCK,
+                INTERFACE_DESCRIPTION_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                DECRYPTION_SECRETS_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                DECRYPTION_SECRETS_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+            ]
+        );
+        assert_eq!(secrets(&blocks_0[3].1), first);
+        assert_eq!(secrets(&blocks_0[5].1), second);
+
+        // the rotated file only includes the secrets of the open connection
+        let blocks_1 = blocks(&dir.join("capture.1.pcapng"));
+        assert_eq!(
+            block_types(&blocks_1),
+            [
+                SECTION_HEADER_BLOCK,
+                INTERFACE_DESCRIPTION_BLOCK,
+                DECRYPTION_SECRETS_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+            ]
+        );
+        assert_eq!(secrets(&blocks_1[2].1), second);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn max_files_test() {
+        let dir = temp_dir("max-files");
+        let path = dir.join("capture.pcapng");
+
+        let builder = Builder::default()
+            .with_file(&path)
+            .unwrap()
+            .with_max_file_size(1)
+            .unwrap()
+            .with_max_files(2)
+            .unwrap();
+        let (state, finish) = start(builder);
+
+        // the file header already exceeds the maximum size, so each datagram starts a new file
+        for _ in 0..3 {
+            capture(&state);
+        }
+        finish();
+
+        // only the latest 2 files are kept
+        for index in 0..=1 {
+            assert!(!file_path(&path, index).exists());
+        }
+        for index in 2..=3 {
+            let blocks = blocks(&file_path(&path, index));
+            assert_eq!(
+                block_types(&blocks),
+                [
+                    SECTION_HEADER_BLOCK,
+                    INTERFACE_DESCRIPTION_BLOCK,
+                    ENHANCED_PACKET_BLOCK,
+                ]
+            );
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn filter_test() {
+        use crate::provider::event::Subscriber as _;
+        use s2n_quic_core::{
+            event::{builder, IntoEvent},
+            time::Duration,
+        };
+
+        let dir = temp_dir("filter");
+        let path = dir.join("capture.pcapng");
+
+        let builder = Builder::default()
+            .with_file(&path)
+            .unwrap()
+            .with_filter(|connection| connection.remote_connection_id == [2])
+            .unwrap();
+        let mut key_log = builder.secrets();
+        let mut subscriber = builder.subscriber();
+        let (state, finish) = start(builder);
+
+        let meta = |id: u64| -> ConnectionMeta {
+            builder::ConnectionMeta {
+                endpoint_type: endpoint::Type::Server,
+                id,
+                timestamp: unsafe { Timestamp::from_duration(Duration::from_millis(id)) },
+            }
+            .into_event()
+        };
+        let info = builder::ConnectionInfo {}.into_event();
+
+        let mut connections = vec![];
+        for id in 1..=2u8 {
+            let meta = meta(id as u64);
+            let mut context = subscriber.create_connection_context(&meta, &info);
+
+            // both connections share the same addresses
+            subscriber.on_connection_started(
+                &mut context,
+                &meta,
+                &builder::ConnectionStarted {
+                    path: builder::Path {
+                        local_addr: builder::SocketAddress::IpV4 {
+                            ip: &[192, 168, 0, 1],
+                            port: 443,
+                        },
+                        local_cid: builder::ConnectionId { bytes: &[id + 10] },
+                        remote_addr: builder::SocketAddress::IpV4 {
+                            ip: &[10, 0, 0, 1],
+                            port: 5000,
+                        },
+                        remote_cid: builder::ConnectionId { bytes: &[id] },
+                        id: 0,
+                        is_active: true,
+                    },
+                }
+                .into_event(),
+            );
+
+            writeln!(key_log, "CLIENT_TRAFFIC_SECRET_0 0{} 0{}", id, id).unwrap();
+            subscriber.on_key_update(
+                &mut context,
+                &meta,
+                &builder::KeyUpdate {
+                    key_type: builder::KeyType::OneRtt { generation: 0 },
+                    cipher_suite: builder::CipherSuite::TLS_AES_128_GCM_SHA256,
+                }
+                .into_event(),
+            );
+
+            connections.push((meta, context));
+        }
+
+        for (meta, context) in connections.iter_mut() {
+            subscriber.on_datagram_sent(
+                context,
+                meta,
+                &builder::DatagramSent {
+                    len: 10,
+                    gso_offset: 0,
+                }
+                .into_event(),
+            );
+            capture(&state);
+            subscriber.on_datagram_received(
+                context,
+                meta,
+                &builder::DatagramReceived { len: 10 }.into_event(),
+            );
+            capture(&state);
+        }
+
+        // datagrams which don't belong to a connection aren't captured
+        capture(&state);
+
+        // the secrets of connections which aren't captured are dropped
+        assert_eq!(
+            state
+                .lock()
+                .unwrap()
+                .key_log
+                .connections
+                .keys()
+                .collect::<Vec<_>>(),
+            [&2]
+        );
+        finish();
+
+        let blocks = blocks(&path);
+        assert_eq!(
+            block_types(&blocks),
+            [
+                SECTION_HEADER_BLOCK,
+                INTERFACE_DESCRIPTION_BLOCK,
+                DECRYPTION_SECRETS_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+                ENHANCED_PACKET_BLOCK,
+            ]
+        );
+        assert_eq!(secrets(&blocks[2].1), b"CLIENT_TRAFFIC_SECRET_0 02 02\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
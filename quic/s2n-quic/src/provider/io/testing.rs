@@ -0,0 +1,294 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Provides an implementation of the [`io::Provider`](crate::provider::io::Provider) which
+//! exchanges datagrams over a simulated, in-memory [`Network`]
+//!
+//! Each endpoint binds to an address on a shared [`Network`]. The [`Link`] between two hosts
+//! controls the latency, jitter, loss, reordering, MTU and bandwidth that datagrams experience.
+//! The network's random decisions are drawn from a seeded generator so a failing scenario can
+//! be replayed. A scenario only repeats exactly if the endpoints send the same datagrams in each
+//! run. Giving each endpoint a seeded [`Random`] generator with the unstable `with_random` builder
+//! method keeps values like the client's initial destination connection ID and path challenges the
+//! same; randomness in the TLS handshake can still change packet sizes, which shifts the decisions
+//! made for the packets that follow.
+//!
+//! The provider reads the time from the Tokio clock. Running on a current-thread runtime with a
+//! paused clock lets simulated time skip ahead whenever every task is idle, so a test covering
+//! several seconds of timeouts and retransmissions finishes in milliseconds. This requires the
+//! `test-util` feature of Tokio.
+//!
+//! ```rust,no_run
+//! # use std::{error::Error, net::SocketAddr, time::Duration};
+//! use s2n_quic::{
+//!     client::Connect,
+//!     provider::io::testing::{Link, Network, Provider},
+//!     Client, Server,
+//! };
+//!
+//! # static CERT_PEM: &str = "";
+//! # static KEY_PEM: &str = "";
+//! // called from a `#[tokio::test(start_paused = true)]`
+//! async fn lossy_handshake() -> Result<(), Box<dyn Error>> {
+//!     let network = Network::new(123);
+//!     network.set_default_link(
+//!         Link::default()
+//!             .with_latency(Duration::from_millis(50))
+//!             .with_loss(0.1),
+//!     );
+//!
+//!     let mut server = Server::builder()
+//!         .with_tls((CERT_PEM, KEY_PEM))?
+//!         .with_io(Provider::new(&network, "10.0.0.1:443")?)?
+//!         .start()?;
+//!
+//!     let client = Client::builder()
+//!         .with_tls(CERT_PEM)?
+//!         .with_io(Provider::new(&network, "10.0.0.2:0")?)?
+//!         .start()?;
+//!
+//!     let addr: SocketAddr = "10.0.0.1:443".parse()?;
+//!     let connect = Connect::new(addr).with_server_name("localhost");
+//!     let mut connection = client.connect(connect).await?;
+//!     let mut stream = connection.open_bidirectional_stream().await?;
+//!     stream.send("hello".into()).await?;
+//!
+//!     let mut connection = server.accept().await.expect("server is open");
+//!     let mut stream = connection
+//!         .accept_bidirectional_stream()
+//!         .await?
+//!         .expect("connection is open");
+//!     assert_eq!(stream.receive().await?.as_deref(), Some(&b"hello"[..]));
+//!     Ok(())
+//! }
+//! ```
+
+use crate::provider::random;
+use core::convert::Infallible;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use s2n_quic_core::{endpoint::Endpoint, inet::SocketAddress};
+use s2n_quic_platform::io::testing;
+use std::io;
+
+pub use self::testing::{Builder, Io as Provider, Link, Network};
+
+impl super::Provider for Provider {
+    type PathHandle = testing::PathHandle;
+    type Error = io::Error;
+
+    fn start<E: Endpoint<PathHandle = Self::PathHandle>>(
+        self,
+        endpoint: E,
+    ) -> Result<SocketAddress, Self::Error> {
+        let (_join_handle, local_addr) = Provider::start(self, endpoint)?;
+        Ok(local_addr)
+    }
+}
+
+/// A random generator which produces the same bytes in each run for a given seed
+///
+/// The output is predictable so the generator must only be used for tests.
+#[derive(Clone, Debug)]
+pub struct Random {
+    public: ChaCha8Rng,
+    private: ChaCha8Rng,
+}
+
+impl Random {
+    /// Creates a generator from the given seed
+    pub fn new(seed: u64) -> Self {
+        let public = ChaCha8Rng::seed_from_u64(seed);
+        let mut private = public.clone();
+        private.set_stream(1);
+        Self { public, private }
+    }
+}
+
+impl random::Provider for Random {
+    type Generator = Self;
+    type Error = Infallible;
+
+    fn start(self) -> Result<Self::Generator, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl s2n_quic_core::random::Generator for Random {
+    fn public_random_fill(&mut self, dest: &mut [u8]) {
+        self.public.fill_bytes(dest)
+    }
+
+    fn private_random_fill(&mut self, dest: &mut [u8]) {
+        self.private.fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Connect,
+        provider::event::{self, events, ConnectionInfo, ConnectionMeta},
+        Client, Server,
+    };
+    use bytes::Bytes;
+    use core::time::Duration;
+    use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+    use std::{
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+
+    const SEED: u64 = 123;
+
+    /// Records the original destination connection ID which the server echoes back to the client
+    #[derive(Clone, Default)]
+    struct OriginalConnectionId(Arc<Mutex<Option<Vec<u8>>>>);
+
+    impl event::Subscriber for OriginalConnectionId {
+        type ConnectionContext = ();
+
+        fn create_connection_context(
+            &mut self,
+            _meta: &ConnectionMeta,
+            _info: &ConnectionInfo,
+        ) -> Self::ConnectionContext {
+        }
+
+        fn on_transport_parameters_received(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::TransportParametersReceived,
+        ) {
+            let id = &event
+                .transport_parameters
+                .original_destination_connection_id;
+            *self.0.lock().unwrap() = id.as_ref().map(|id| id.bytes.to_vec());
+        }
+    }
+
+    /// The outcome of a scenario which should be the same in each run
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        sent: u64,
+        dropped: u64,
+        original_destination_connection_id: Option<Vec<u8>>,
+    }
+
+    /// Echoes a message over a lossy network and returns what happened along the way
+    async fn echo_over_lossy_link() -> Outcome {
+        let network = Network::new(SEED);
+        network.set_default_link(
+            Link::default()
+                .with_latency(Duration::from_millis(50))
+                .with_loss(0.2),
+        );
+
+        let mut server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))
+            .unwrap()
+            .with_io(Provider::new(&network, "10.0.0.1:443").unwrap())
+            .unwrap()
+            .with_random(Random::new(SEED))
+            .unwrap()
+            .start()
+            .unwrap();
+        tokio::spawn(async move {
+            let mut connection = server.accept().await.expect("server is open");
+            let mut stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .expect("connection is open");
+            tokio::spawn(async move {
+                while let Some(chunk) = stream.receive().await.unwrap() {
+                    stream.send(chunk).await.unwrap();
+                }
+                stream.finish().unwrap();
+            });
+            // keep the connection open until the client closes it
+            while let Ok(Some(_)) = connection.accept_bidirectional_stream().await {}
+        });
+
+        let original_connection_id = OriginalConnectionId::default();
+        let client = Client::builder()
+            .with_tls(CERT_PEM)
+            .unwrap()
+            .with_event(original_connection_id.clone())
+            .unwrap()
+            .with_io(Provider::new(&network, "10.0.0.2:0").unwrap())
+            .unwrap()
+            .with_random(Random::new(SEED + 1))
+            .unwrap()
+            .start()
+            .unwrap();
+
+        let addr: SocketAddr = "10.0.0.1:443".parse().unwrap();
+        let connect = Connect::new(addr).with_server_name("localhost");
+        let mut connection = client.connect(connect).await.unwrap();
+        let mut stream = connection.open_bidirectional_stream().await.unwrap();
+        let message = Bytes::from(vec![42; 10_000]);
+        stream.send(message.clone()).await.unwrap();
+        stream.finish().unwrap();
+
+        let mut received = vec![];
+        while let Some(chunk) = stream.receive().await.unwrap() {
+            received.extend_from_slice(&chunk);
+        }
+        assert_eq!(received, message);
+
+        // the client draws the original destination connection ID from the random provider
+        let original_destination_connection_id = original_connection_id.0.lock().unwrap().take();
+        Outcome {
+            sent: network.sent(),
+            dropped: network.dropped(),
+            original_destination_connection_id,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handshake_and_stream_test() {
+        let outcome = echo_over_lossy_link().await;
+        assert!(outcome.dropped > 0, "the link should drop datagrams");
+        assert!(outcome.sent > outcome.dropped);
+        assert!(outcome.original_destination_connection_id.is_some());
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let run = || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .start_paused(true)
+                .build()
+                .unwrap()
+                .block_on(echo_over_lossy_link())
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn random_test() {
+        use s2n_quic_core::random::Generator;
+
+        let mut a = Random::new(SEED);
+        let mut b = Random::new(SEED);
+        let mut dest_a = [0; 20];
+        let mut dest_b = [0; 20];
+
+        a.public_random_fill(&mut dest_a);
+        b.public_random_fill(&mut dest_b);
+        assert_eq!(dest_a, dest_b);
+
+        a.private_random_fill(&mut dest_a);
+        b.private_random_fill(&mut dest_b);
+        assert_eq!(dest_a, dest_b);
+
+        // the public and private streams are independent
+        let mut public = [0; 20];
+        Random::new(SEED).public_random_fill(&mut public);
+        assert_ne!(public, dest_a);
+    }
+}
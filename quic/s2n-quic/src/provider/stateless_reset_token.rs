@@ -16,6 +16,112 @@ pub use random::Provider as Default;
 
 impl_provider_utils!();
 
+/// Stateless reset tokens derived from a static key
+#[cfg(feature = "provider-stateless-reset-token-hmac")]
+pub mod hmac {
+    use core::convert::Infallible;
+    use ring::hmac;
+    use s2n_quic_core::{frame::new_connection_id::STATELESS_RESET_TOKEN_LEN, stateless_reset};
+
+    #[derive(Debug)]
+    pub struct Provider(Generator);
+
+    impl super::Provider for Provider {
+        type Generator = Generator;
+        type Error = Infallible;
+
+        fn start(self) -> Result<Self::Generator, Self::Error> {
+            Ok(self.0)
+        }
+    }
+
+    impl super::TryInto for Generator {
+        type Provider = Provider;
+        type Error = Infallible;
+
+        fn try_into(self) -> Result<Self::Provider, Self::Error> {
+            Ok(Provider(self))
+        }
+    }
+
+    /// Stateless reset token generator keyed with a static secret.
+    ///
+    /// Tokens are computed as HMAC-SHA256(secret, local_connection_id), truncated
+    /// to 16 bytes. As long as the same secret is used, the token for a connection ID
+    /// can be recomputed after the endpoint loses its connection state, such as after
+    /// a restart. This allows the endpoint to send a stateless reset to peers of
+    /// connections it no longer knows about, instead of leaving them to wait for
+    /// their idle timeout.
+    ///
+    /// The secret must be kept private and should be at least 32 bytes of
+    /// cryptographically random data. Endpoints that share a secret must not
+    /// issue the same connection IDs.
+    #[derive(Debug)]
+    pub struct Generator {
+        key: hmac::Key,
+    }
+
+    impl Generator {
+        /// Creates a new generator with the given static secret
+        pub fn new(secret: &[u8]) -> Self {
+            Self {
+                key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            }
+        }
+    }
+
+    impl stateless_reset::token::Generator for Generator {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.2
+        //# A single static key can be used across all connections to the same
+        //# endpoint by generating the proof using a pseudorandom function that
+        //# takes a static key and the connection ID chosen by the endpoint (see
+        //# Section 5.1) as input.
+        fn generate(&mut self, local_connection_id: &[u8]) -> stateless_reset::Token {
+            let tag = hmac::sign(&self.key, local_connection_id);
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.2
+            //# The
+            //# output of this function is truncated to 16 bytes to produce the
+            //# stateless reset token for that connection.
+            let mut token = [0u8; STATELESS_RESET_TOKEN_LEN];
+            token.copy_from_slice(&tag.as_ref()[..STATELESS_RESET_TOKEN_LEN]);
+            token.into()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use s2n_quic_core::{connection, stateless_reset::token::Generator as _};
+
+        #[test]
+        fn stateless_reset_token_test() {
+            let mut generator = Generator::new(&[1; 32]);
+            let id_1 = connection::LocalId::try_from_bytes(b"id01").unwrap();
+            let id_2 = connection::LocalId::try_from_bytes(b"id02").unwrap();
+
+            let token_1 = generator.generate(id_1.as_bytes());
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.2
+            //= type=test
+            //# An endpoint that loses state can use the same method to generate a
+            //# valid stateless reset token.
+            let mut restarted = Generator::new(&[1; 32]);
+            assert_eq!(token_1, restarted.generate(id_1.as_bytes()));
+            assert_eq!(token_1, generator.generate(id_1.as_bytes()));
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3.2
+            //= type=test
+            //# The same stateless reset token MUST NOT be used for multiple
+            //# connection IDs.
+            assert_ne!(token_1, generator.generate(id_2.as_bytes()));
+
+            let mut other_key = Generator::new(&[2; 32]);
+            assert_ne!(token_1, other_key.generate(id_1.as_bytes()));
+        }
+    }
+}
+
 mod random {
     use core::convert::Infallible;
     use rand::prelude::*;
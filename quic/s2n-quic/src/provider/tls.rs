@@ -6,6 +6,11 @@
 use cfg_if::cfg_if;
 use s2n_quic_core::crypto;
 
+/// Provides storage for session tickets used to resume TLS sessions
+pub use s2n_quic_core::crypto::tls::session_ticket;
+
+pub use s2n_quic_core::crypto::tls::CipherSuite;
+
 pub trait Provider {
     type Server: 'static + crypto::tls::Endpoint;
     type Client: 'static + crypto::tls::Endpoint;
@@ -1,12 +1,17 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{connection::Connection, provider::*};
+use crate::{
+    application,
+    connection::{self, Connection},
+    provider::*,
+};
 use core::{
     fmt,
     task::{Context, Poll},
+    time::Duration,
 };
-use s2n_quic_transport::endpoint::handle::Acceptor;
+use s2n_quic_transport::endpoint::{close::GracePeriod, handle::Acceptor};
 
 mod builder;
 mod providers;
@@ -101,6 +106,77 @@ impl Server {
         }
     }
 
+    /// Stops accepting new connections and waits for the existing connections to close
+    ///
+    /// Once called, Initial packets from new clients are rejected. Connections that complete
+    /// their handshake before the call may still be returned by [`Server::accept`].
+    ///
+    /// The method returns once all of the connections have closed and the endpoint has stopped
+    /// processing packets. Since this may never happen for long-lived connections,
+    /// [`Server::shutdown_with_grace_period`] can be used to bound the amount of time spent
+    /// waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::{error::Error, path::Path};
+    /// # use s2n_quic::Server;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut server = Server::builder()
+    ///     .with_tls((Path::new("./certs/cert.pem"), Path::new("./certs/key.pem")))?
+    ///     .with_io("127.0.0.1:443")?
+    ///     .start()?;
+    ///
+    /// server.shutdown().await?;
+    /// #
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(&mut self) -> Result<(), connection::Error> {
+        futures::future::poll_fn(|cx| self.acceptor.poll_close(cx, None)).await
+    }
+
+    /// Stops accepting new connections and closes any remaining connections after a grace period
+    ///
+    /// This behaves like [`Server::shutdown`], except that connections that are still open
+    /// once `grace_period` has elapsed are closed with the provided application `error`.
+    /// The method returns after the closed connections have finished draining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::{error::Error, path::Path, time::Duration};
+    /// # use s2n_quic::Server;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut server = Server::builder()
+    ///     .with_tls((Path::new("./certs/cert.pem"), Path::new("./certs/key.pem")))?
+    ///     .with_io("127.0.0.1:443")?
+    ///     .start()?;
+    ///
+    /// const SHUTTING_DOWN: u32 = 1;
+    /// server
+    ///     .shutdown_with_grace_period(Duration::from_secs(30), SHUTTING_DOWN.into())
+    ///     .await?;
+    /// #
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown_with_grace_period(
+        &mut self,
+        grace_period: Duration,
+        error: application::Error,
+    ) -> Result<(), connection::Error> {
+        let grace_period = GracePeriod {
+            duration: grace_period,
+            error,
+        };
+        futures::future::poll_fn(|cx| self.acceptor.poll_close(cx, Some(grace_period))).await
+    }
+
     /// Returns the local address that this listener is bound to.
     ///
     /// This can be useful, for example, when binding to port `0` to figure out which
@@ -126,6 +126,124 @@ impl<Providers: ServerProviders> Builder<Providers> {
         ServerProviders
     );
 
+    impl_provider_method!(
+        /// Sets the custom transport parameters provider for the [`Server`]
+        ///
+        /// The parameters are sent to the peer during the handshake of every connection. The
+        /// values sent by the peer can be read with
+        /// [`Connection::peer_custom_transport_parameters`](crate::Connection::peer_custom_transport_parameters).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # use std::{error::Error, path::Path};
+        /// use s2n_quic::{Server, provider::custom_transport_parameters::CustomTransportParameters};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let parameters = CustomTransportParameters::default().with(0x7a3c, &b"feature-x"[..])?;
+        ///
+        /// let server = Server::builder()
+        ///     .with_tls((Path::new("./certs/cert.pem"), Path::new("./certs/key.pem")))?
+        ///     .with_custom_transport_parameters(parameters)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_custom_transport_parameters,
+        custom_transport_parameters,
+        ServerProviders
+    );
+
+    impl_provider_method!(
+        /// Sets the connection close formatter provider for the [`Server`]
+        ///
+        /// The formatter controls the error codes and reasons sent to the peer in
+        /// CONNECTION_CLOSE frames. The default formatter removes details that could be
+        /// sensitive, such as the reasons of transport errors.
+        ///
+        /// # Examples
+        ///
+        /// Sends errors to the peer unmodified, which can help while developing an application.
+        ///
+        /// ```rust,no_run
+        /// # use std::{error::Error, path::Path};
+        /// use s2n_quic::{Server, provider::connection_close_formatter};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let server = Server::builder()
+        ///     .with_tls((Path::new("./certs/cert.pem"), Path::new("./certs/key.pem")))?
+        ///     .with_connection_close_formatter(connection_close_formatter::Development)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        ///
+        /// Hides the reasons of all errors
+        ///
+        /// ```rust,no_run
+        /// # use std::{error::Error, path::Path};
+        /// use s2n_quic::{
+        ///     provider::connection_close_formatter::{
+        ///         ApplicationError, ConnectionClose, Context, Formatter, Production, TransportError,
+        ///     },
+        ///     Server,
+        /// };
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// struct MyFormatter;
+        ///
+        /// impl Formatter for MyFormatter {
+        ///     fn format_transport_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: TransportError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_transport_error(context, error)
+        ///     }
+        ///
+        ///     fn format_application_error<'a>(
+        ///         &'a self,
+        ///         _context: &Context<'a>,
+        ///         error: ApplicationError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         error.into()
+        ///     }
+        ///
+        ///     fn format_early_transport_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: TransportError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_early_transport_error(context, error)
+        ///     }
+        ///
+        ///     fn format_early_application_error<'a>(
+        ///         &'a self,
+        ///         context: &Context<'a>,
+        ///         error: ApplicationError,
+        ///     ) -> ConnectionClose<'a> {
+        ///         Production.format_early_application_error(context, error)
+        ///     }
+        /// }
+        ///
+        /// let server = Server::builder()
+        ///     .with_tls((Path::new("./certs/cert.pem"), Path::new("./certs/key.pem")))?
+        ///     .with_connection_close_formatter(MyFormatter)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_connection_close_formatter,
+        connection_close_formatter,
+        ServerProviders
+    );
+
     impl_provider_method!(
         /// Sets the endpoint limits provider for the [`Server`]
         ///
@@ -201,6 +319,47 @@ impl<Providers: ServerProviders> Builder<Providers> {
         ServerProviders
     );
 
+    impl_provider_method!(
+        /// Sets the congestion controller provider for the [`Server`]
+        ///
+        /// # Examples
+        ///
+        /// Uses the BBRv2 congestion controller instead of the default CUBIC controller
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{Server, provider::congestion_controller};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let server = Server::builder()
+        ///     .with_congestion_controller(congestion_controller::bbr::Provider::default())?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        ///
+        /// Sets a custom congestion controller for the server
+        ///
+        /// ```rust,ignore
+        /// # use std::error::Error;
+        /// use s2n_quic::Server;
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let server = Server::builder()
+        ///     .with_congestion_controller(MyCongestionControllerEndpoint::new())?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_congestion_controller,
+        congestion_controller,
+        ServerProviders
+    );
+
     impl_provider_method!(
         /// Sets the IO provider for the [`Server`]
         ///
@@ -295,6 +454,45 @@ impl<Providers: ServerProviders> Builder<Providers> {
         ServerProviders
     );
 
+    impl_provider_method!(
+        /// Sets the early data provider for the [`Server`]
+        ///
+        /// The provider decides which connections may have their 0-RTT data processed. Early
+        /// data is rejected by default since it can be replayed by an attacker.
+        ///
+        /// The TLS provider must be configured to accept early data, otherwise starting the
+        /// [`Server`] fails. Only the `rustls` provider currently supports early data.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// # #[cfg(feature = "provider-tls-rustls")]
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// # use std::path::Path;
+        /// use s2n_quic::{Server, provider::{early_data, tls::rustls}};
+        ///
+        /// let tls = rustls::Server::builder()
+        ///     .with_certificate(Path::new("./certs/cert.pem"), Path::new("./certs/key.pem"))?
+        ///     .with_early_data(true)?
+        ///     .build()?;
+        ///
+        /// let server = Server::builder()
+        ///     .with_tls(tls)?
+        ///     .with_early_data(early_data::accept_all::Policy)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// # #[cfg(not(feature = "provider-tls-rustls"))]
+        /// # fn main() {}
+        /// ```
+        with_early_data,
+        early_data,
+        ServerProviders
+    );
+
     #[cfg(all(s2n_quic_unstable, feature = "unstable-provider-packet-interceptor"))]
     impl_provider_method!(
         /// Sets the packet interceptor provider for the [`Server`]
@@ -303,7 +501,7 @@ impl<Providers: ServerProviders> Builder<Providers> {
         ServerProviders
     );
 
-    #[cfg(all(s2n_quic_unstable, feature = "unstable-provider-random"))]
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable-provider-random")))]
     impl_provider_method!(
         /// Sets the random provider for the [`Server`]
         with_random,
@@ -3,7 +3,11 @@
 
 use super::*;
 use core::marker::PhantomData;
-use s2n_quic_core::{connection::id::Generator, crypto, path};
+use s2n_quic_core::{
+    connection::{early_data::Policy as _, id::Generator},
+    crypto::{self, tls::Endpoint as _},
+    path,
+};
 use s2n_quic_transport::{connection, endpoint, stream};
 
 impl_providers_state! {
@@ -12,6 +16,7 @@ impl_providers_state! {
         congestion_controller: CongestionController,
         connection_close_formatter: ConnectionCloseFormatter,
         connection_id: ConnectionID,
+        custom_transport_parameters: CustomTransportParameters,
         packet_interceptor: PacketInterceptor,
         stateless_reset_token: StatelessResetToken,
         random: Random,
@@ -20,6 +25,7 @@ impl_providers_state! {
         limits: Limits,
         io: IO,
         path_migration: PathMigration,
+        early_data: EarlyData,
         sync: Sync,
         tls: Tls,
         address_token: AddressToken,
@@ -33,6 +39,7 @@ impl<
         CongestionController: congestion_controller::Provider,
         ConnectionCloseFormatter: connection_close_formatter::Provider,
         ConnectionID: connection_id::Provider,
+        CustomTransportParameters: custom_transport_parameters::Provider,
         PacketInterceptor: packet_interceptor::Provider,
         StatelessResetToken: stateless_reset_token::Provider,
         Random: random::Provider,
@@ -41,6 +48,7 @@ impl<
         Limits: limits::Provider,
         IO: io::Provider,
         PathMigration: path_migration::Provider,
+        EarlyData: early_data::Provider,
         Sync: sync::Provider,
         Tls: tls::Provider,
         AddressToken: address_token::Provider,
@@ -49,6 +57,7 @@ impl<
         CongestionController,
         ConnectionCloseFormatter,
         ConnectionID,
+        CustomTransportParameters,
         PacketInterceptor,
         StatelessResetToken,
         Random,
@@ -57,6 +66,7 @@ impl<
         Limits,
         IO,
         PathMigration,
+        EarlyData,
         Sync,
         Tls,
         AddressToken,
@@ -67,6 +77,7 @@ impl<
             congestion_controller,
             connection_close_formatter,
             connection_id,
+            custom_transport_parameters,
             packet_interceptor,
             stateless_reset_token,
             random,
@@ -76,6 +87,7 @@ impl<
             address_token,
             io,
             path_migration,
+            early_data,
             sync,
             tls,
         } = self;
@@ -85,6 +97,9 @@ impl<
             .start()
             .map_err(StartError::new)?;
         let connection_id = connection_id.start().map_err(StartError::new)?;
+        let custom_transport_parameters = custom_transport_parameters
+            .start()
+            .map_err(StartError::new)?;
         let packet_interceptor = packet_interceptor.start().map_err(StartError::new)?;
         let stateless_reset_token = stateless_reset_token.start().map_err(StartError::new)?;
         let random = random.start().map_err(StartError::new)?;
@@ -94,6 +109,7 @@ impl<
         let address_token = address_token.start().map_err(StartError::new)?;
         let sync = sync.start().map_err(StartError::new)?;
         let path_migration = path_migration.start().map_err(StartError::new)?;
+        let early_data = early_data.start().map_err(StartError::new)?;
         let tls = tls.start_server().map_err(StartError::new)?;
 
         // Validate providers
@@ -108,10 +124,19 @@ impl<
             return Err(StartError::new(connection::id::Error::InvalidLifetime));
         };
 
+        // 0-RTT keys are derived by the TLS provider, so a policy accepting early data would
+        // otherwise be ignored without an error
+        if early_data.may_accept() && !tls.supports_early_data() {
+            return Err(StartError::new(
+                crate::provider::early_data::Error::UnsupportedTls,
+            ));
+        }
+
         let endpoint_config = EndpointConfig {
             congestion_controller,
             connection_close_formatter,
             connection_id,
+            custom_transport_parameters,
             packet_interceptor,
             stateless_reset_token,
             random,
@@ -123,6 +148,7 @@ impl<
             address_token,
             path_handle: PhantomData,
             path_migration,
+            early_data,
         };
 
         let (endpoint, acceptor) = endpoint::Endpoint::new_server(endpoint_config);
@@ -145,6 +171,7 @@ struct EndpointConfig<
     PacketInterceptor,
     PathHandle,
     PathMigration,
+    EarlyData,
     StatelessResetToken,
     Random,
     EndpointLimits,
@@ -157,6 +184,7 @@ struct EndpointConfig<
     congestion_controller: CongestionController,
     connection_close_formatter: ConnectionCloseFormatter,
     connection_id: ConnectionID,
+    custom_transport_parameters: custom_transport_parameters::CustomTransportParameters,
     packet_interceptor: PacketInterceptor,
     stateless_reset_token: StatelessResetToken,
     random: Random,
@@ -168,6 +196,7 @@ struct EndpointConfig<
     address_token: AddressToken,
     path_handle: PhantomData<PathHandle>,
     path_migration: PathMigration,
+    early_data: EarlyData,
 }
 
 impl<
@@ -176,6 +205,7 @@ impl<
         ConnectionID: connection::id::Format,
         PacketInterceptor: packet_interceptor::PacketInterceptor,
         PathMigration: path_migration::Validator,
+        EarlyData: early_data::Policy,
         PathHandle: path::Handle,
         StatelessResetToken: stateless_reset_token::Generator,
         Random: s2n_quic_core::random::Generator,
@@ -193,6 +223,7 @@ impl<
         PacketInterceptor,
         PathHandle,
         PathMigration,
+        EarlyData,
         StatelessResetToken,
         Random,
         EndpointLimits,
@@ -215,6 +246,7 @@ impl<
         PacketInterceptor: packet_interceptor::PacketInterceptor,
         PathHandle: path::Handle,
         PathMigration: path_migration::Validator,
+        EarlyData: early_data::Policy,
         StatelessResetToken: stateless_reset_token::Generator,
         Random: s2n_quic_core::random::Generator,
         EndpointLimits: s2n_quic_core::endpoint::Limiter,
@@ -231,6 +263,7 @@ impl<
         PacketInterceptor,
         PathHandle,
         PathMigration,
+        EarlyData,
         StatelessResetToken,
         Random,
         EndpointLimits,
@@ -258,6 +291,7 @@ impl<
     type Stream = stream::StreamImpl;
     type PathMigrationValidator = PathMigration;
     type PacketInterceptor = PacketInterceptor;
+    type EarlyDataPolicy = EarlyData;
 
     const ENDPOINT_TYPE: endpoint::Type = endpoint::Type::Server;
 
@@ -266,6 +300,7 @@ impl<
             congestion_controller: &mut self.congestion_controller,
             connection_close_formatter: &mut self.connection_close_formatter,
             connection_id_format: &mut self.connection_id,
+            custom_transport_parameters: &self.custom_transport_parameters,
             packet_interceptor: &mut self.packet_interceptor,
             stateless_reset_token_generator: &mut self.stateless_reset_token,
             random_generator: &mut self.random,
@@ -275,6 +310,7 @@ impl<
             connection_limits: &mut self.limits,
             event_subscriber: &mut self.event,
             path_migration: &mut self.path_migration,
+            early_data: &mut self.early_data,
         }
     }
 }
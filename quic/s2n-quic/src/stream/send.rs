@@ -376,6 +376,51 @@ macro_rules! impl_send_stream_api {
             let $stream = self;
             $dispatch_body
         }
+
+        /// Sets the [priority](crate::stream::Priority) used to schedule the transmission of the
+        /// stream.
+        ///
+        /// Streams with a lower urgency are sent before streams with a higher urgency, even when
+        /// the connection is limited by congestion or flow control. The priority may be changed
+        /// at any time; it applies to any data that has not been transmitted yet.
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        /// - `Ok(())` if the priority was updated.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::stream::Result<()> {
+        /// #   let mut stream: s2n_quic::stream::SendStream = todo!();
+        /// #
+        /// use s2n_quic::stream::Priority;
+        ///
+        /// // send control messages ahead of any bulk transfers
+        /// stream.set_priority(Priority::new(0, false))?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn set_priority(
+            &mut self,
+            priority: $crate::stream::Priority,
+        ) -> $crate::stream::Result<()> {
+            macro_rules! $dispatch {
+                () => {
+                    Err($crate::stream::Error::non_writable())
+                };
+                ($variant: expr) => {
+                    $variant.set_priority(priority)
+                };
+            }
+
+            let $stream = self;
+            $dispatch_body
+        }
     };
 }
 
@@ -0,0 +1,99 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end tests which run clients and servers over the in-memory testing network
+
+use crate::{
+    client::Connect,
+    provider::io::testing::{Link, Network, Provider as IoProvider},
+    Connection, Server,
+};
+use bytes::Bytes;
+use core::{future::Future, time::Duration};
+use std::net::SocketAddr;
+
+mod congestion_controller;
+mod custom_transport_parameters;
+#[cfg(feature = "provider-tls-rustls")]
+mod early_data;
+#[cfg(any(feature = "provider-tls-rustls", feature = "provider-tls-s2n"))]
+mod handshake_info;
+mod migration;
+#[cfg(feature = "provider-tls-rustls")]
+mod pcapng;
+#[cfg(any(feature = "provider-tls-rustls", feature = "provider-tls-s2n"))]
+mod resumption;
+mod shutdown;
+mod stats;
+mod version;
+
+type Error = Box<dyn std::error::Error>;
+type Result<T = (), E = Error> = core::result::Result<T, E>;
+
+const SERVER_ADDR: &str = "10.0.0.1:443";
+const CLIENT_ADDR: &str = "10.0.0.2:0";
+
+/// Runs the test on a current-thread runtime with a paused clock
+///
+/// The clock skips ahead whenever all tasks are idle so timeouts complete instantly.
+fn run<F: Future<Output = Result>>(test: F) {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+        .unwrap()
+        .block_on(test)
+        .unwrap();
+}
+
+fn server_io(network: &Network) -> Result<IoProvider> {
+    Ok(IoProvider::new(network, SERVER_ADDR)?)
+}
+
+fn client_io(network: &Network) -> Result<IoProvider> {
+    Ok(IoProvider::new(network, CLIENT_ADDR)?)
+}
+
+fn connect() -> Connect {
+    let addr: SocketAddr = SERVER_ADDR.parse().unwrap();
+    Connect::new(addr).with_server_name("localhost")
+}
+
+/// Accepts connections and echoes the data received on each bidirectional stream
+fn spawn_echo_server(mut server: Server) {
+    tokio::spawn(async move {
+        while let Some(connection) = server.accept().await {
+            spawn_echo_connection(connection);
+        }
+    });
+}
+
+/// Echoes the data received on each bidirectional stream of the connection
+fn spawn_echo_connection(mut connection: Connection) {
+    tokio::spawn(async move {
+        while let Ok(Some(mut stream)) = connection.accept_bidirectional_stream().await {
+            tokio::spawn(async move {
+                while let Ok(Some(chunk)) = stream.receive().await {
+                    if stream.send(chunk).await.is_err() {
+                        return;
+                    }
+                }
+                let _ = stream.finish();
+            });
+        }
+    });
+}
+
+/// Sends `data` on a new bidirectional stream and returns the response
+async fn echo(connection: &mut Connection, data: &'static [u8]) -> Result<Bytes> {
+    let mut stream = connection.open_bidirectional_stream().await?;
+    stream.send(Bytes::from_static(data)).await?;
+    stream.finish()?;
+
+    let mut response = vec![];
+    while let Some(chunk) = stream.receive().await? {
+        response.extend_from_slice(&chunk);
+    }
+
+    Ok(response.into())
+}
@@ -0,0 +1,181 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::congestion_controller::{
+        CongestionController, Endpoint, PathInfo, RttEstimator, Timestamp,
+    },
+    Client,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use s2n_quic_core::{
+    crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM},
+    recovery::{cubic, CubicCongestionController},
+};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct Calls {
+    new_congestion_controller: AtomicUsize,
+    on_packet_sent: AtomicUsize,
+    on_rtt_update: AtomicUsize,
+    on_ack: AtomicUsize,
+}
+
+impl Calls {
+    fn get(counter: &AtomicUsize) -> usize {
+        counter.load(Ordering::Relaxed)
+    }
+
+    fn increment(counter: &AtomicUsize) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Creates CUBIC congestion controllers which record the callbacks made by the connection
+#[derive(Debug, Default)]
+struct Recorder(Arc<Calls>);
+
+impl Endpoint for Recorder {
+    type CongestionController = Recording;
+
+    fn new_congestion_controller(&mut self, path_info: PathInfo) -> Self::CongestionController {
+        Calls::increment(&self.0.new_congestion_controller);
+        Recording {
+            inner: cubic::Endpoint::default().new_congestion_controller(path_info),
+            calls: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Recording {
+    inner: CubicCongestionController,
+    calls: Arc<Calls>,
+}
+
+impl CongestionController for Recording {
+    type PacketInfo = <CubicCongestionController as CongestionController>::PacketInfo;
+
+    fn congestion_window(&self) -> u32 {
+        self.inner.congestion_window()
+    }
+
+    fn bytes_in_flight(&self) -> u32 {
+        self.inner.bytes_in_flight()
+    }
+
+    fn is_congestion_limited(&self) -> bool {
+        self.inner.is_congestion_limited()
+    }
+
+    fn requires_fast_retransmission(&self) -> bool {
+        self.inner.requires_fast_retransmission()
+    }
+
+    fn on_packet_sent(
+        &mut self,
+        time_sent: Timestamp,
+        sent_bytes: usize,
+        rtt_estimator: &RttEstimator,
+    ) -> Self::PacketInfo {
+        Calls::increment(&self.calls.on_packet_sent);
+        self.inner
+            .on_packet_sent(time_sent, sent_bytes, rtt_estimator)
+    }
+
+    fn on_rtt_update(&mut self, time_sent: Timestamp, rtt_estimator: &RttEstimator) {
+        Calls::increment(&self.calls.on_rtt_update);
+        self.inner.on_rtt_update(time_sent, rtt_estimator)
+    }
+
+    fn on_ack(
+        &mut self,
+        newest_acked_time_sent: Timestamp,
+        bytes_acknowledged: usize,
+        newest_acked_packet_info: Self::PacketInfo,
+        rtt_estimator: &RttEstimator,
+        ack_receive_time: Timestamp,
+    ) {
+        Calls::increment(&self.calls.on_ack);
+        self.inner.on_ack(
+            newest_acked_time_sent,
+            bytes_acknowledged,
+            newest_acked_packet_info,
+            rtt_estimator,
+            ack_receive_time,
+        )
+    }
+
+    fn on_packet_lost(
+        &mut self,
+        lost_bytes: u32,
+        packet_info: Self::PacketInfo,
+        persistent_congestion: bool,
+        new_loss_burst: bool,
+        timestamp: Timestamp,
+    ) {
+        self.inner.on_packet_lost(
+            lost_bytes,
+            packet_info,
+            persistent_congestion,
+            new_loss_burst,
+            timestamp,
+        )
+    }
+
+    fn on_congestion_event(&mut self, event_time: Timestamp) {
+        self.inner.on_congestion_event(event_time)
+    }
+
+    fn on_mtu_update(&mut self, max_data_size: u16) {
+        self.inner.on_mtu_update(max_data_size)
+    }
+
+    fn on_packet_discarded(&mut self, bytes_sent: usize) {
+        self.inner.on_packet_discarded(bytes_sent)
+    }
+
+    fn earliest_departure_time(&self) -> Option<Timestamp> {
+        self.inner.earliest_departure_time()
+    }
+}
+
+#[test]
+fn custom_congestion_controller() {
+    static DATA: [u8; 100_000] = [1; 100_000];
+
+    run(async {
+        let network = Network::new(1);
+        network.set_default_link(Link::default().with_latency(Duration::from_millis(10)));
+
+        let server_calls = Arc::new(Calls::default());
+        let server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .with_congestion_controller(Recorder(server_calls.clone()))?
+            .start()?;
+        spawn_echo_server(server);
+
+        let client_calls = Arc::new(Calls::default());
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .with_congestion_controller(Recorder(client_calls.clone()))?
+            .start()?;
+
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, &DATA).await?[..], &DATA[..]);
+
+        // each endpoint uses the configured controller for its single path
+        for calls in [&server_calls, &client_calls].iter() {
+            assert_eq!(Calls::get(&calls.new_congestion_controller), 1);
+            assert!(Calls::get(&calls.on_packet_sent) > 0);
+            assert!(Calls::get(&calls.on_rtt_update) > 0);
+            assert!(Calls::get(&calls.on_ack) > 0);
+        }
+
+        Ok(())
+    });
+}
@@ -0,0 +1,112 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::{
+        custom_transport_parameters::CustomTransportParameters,
+        event::{self, events, ConnectionInfo, ConnectionMeta},
+    },
+    Client,
+};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// The `(id, value)` pairs of the custom parameters in a `TransportParametersReceived` event
+type Parameters = Vec<(u64, Bytes)>;
+
+/// Records the custom parameters of each `TransportParametersReceived` event
+#[derive(Clone, Default)]
+struct Received(Arc<Mutex<Vec<Parameters>>>);
+
+impl event::Subscriber for Received {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_transport_parameters_received(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::TransportParametersReceived,
+    ) {
+        let parameters = event
+            .transport_parameters
+            .custom_parameters
+            .iter()
+            .map(|parameter| (parameter.id(), parameter.value().clone()))
+            .collect();
+        self.0.lock().unwrap().push(parameters);
+    }
+}
+
+#[test]
+fn custom_transport_parameters() {
+    run(async {
+        let network = Network::new(1);
+
+        // the server only knows about one of the parameters the client sends
+        let server_parameters =
+            CustomTransportParameters::default().with(0x7a3c, &b"server"[..])?;
+        let client_parameters = CustomTransportParameters::default()
+            .with(0x7a3c, &b"client"[..])?
+            .with(0x7a3d, &b""[..])?;
+        // each ID can only be sent once
+        assert!(client_parameters
+            .clone()
+            .with(0x7a3d, &b"duplicate"[..])
+            .is_err());
+
+        let server_events = Received::default();
+        let mut server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .with_custom_transport_parameters(server_parameters)?
+            .with_event(server_events.clone())?
+            .start()?;
+        let (server_received, received) = mpsc::channel();
+        tokio::spawn(async move {
+            while let Some(connection) = server.accept().await {
+                server_received
+                    .send(connection.peer_custom_transport_parameters().unwrap())
+                    .unwrap();
+                spawn_echo_connection(connection);
+            }
+        });
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .with_custom_transport_parameters(client_parameters.clone())?
+            .start()?;
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+
+        // the server receives all of the client's parameters, including the unknown one
+        let server_received = received.try_recv()?;
+        assert_eq!(server_received, client_parameters);
+        assert_eq!(server_received.get(0x7a3c).unwrap(), &b"client"[..]);
+        assert_eq!(server_received.get(0x7a3d).unwrap(), &b""[..]);
+
+        let server_events = server_events.0.lock().unwrap();
+        assert_eq!(
+            *server_events,
+            [vec![
+                (0x7a3c, Bytes::from_static(b"client")),
+                (0x7a3d, Bytes::new()),
+            ]]
+        );
+
+        // the client receives the server's value rather than its own
+        let client_received = connection.peer_custom_transport_parameters()?;
+        assert_eq!(client_received.get(0x7a3c).unwrap(), &b"server"[..]);
+        assert_eq!(client_received.get(0x7a3d), None);
+
+        Ok(())
+    });
+}
@@ -0,0 +1,137 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::{
+        early_data::{self, Policy},
+        event::{self, events, ConnectionInfo, ConnectionMeta},
+        tls::rustls,
+    },
+    Client,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+use std::sync::Arc;
+
+/// Counts the 0-RTT packets processed by the server
+#[derive(Clone, Default)]
+struct ZeroRttCounter(Arc<AtomicUsize>);
+
+impl ZeroRttCounter {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl event::Subscriber for ZeroRttCounter {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_packet_received(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::PacketReceived,
+    ) {
+        if matches!(event.packet_header, events::PacketHeader::ZeroRtt { .. }) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Resumes a session and sends a request in 0-RTT packets
+///
+/// Returns the number of 0-RTT packets the server processed.
+async fn resume_with_early_data<P: Policy>(policy: P) -> Result<usize> {
+    let network = Network::new(1);
+    // the request is sent while the handshake is still in flight
+    network.set_default_link(Link::default().with_latency(Duration::from_millis(50)));
+    let counter = ZeroRttCounter::default();
+
+    let tls = rustls::Server::builder()
+        .with_certificate(CERT_PEM, KEY_PEM)?
+        .with_early_data(true)?
+        .build()?;
+    let server = Server::builder()
+        .with_tls(tls)?
+        .with_io(server_io(&network)?)?
+        .with_event(counter.clone())?
+        .with_early_data(policy)?
+        .start()?;
+    spawn_echo_server(server);
+
+    let tls = rustls::Client::builder()
+        .with_certificate(CERT_PEM)?
+        .with_session_tickets()?
+        .with_early_data(true)?
+        .build()?;
+    let client = Client::builder()
+        .with_tls(tls)?
+        .with_io(client_io(&network)?)?
+        .start()?;
+
+    // the first connection receives a session ticket from the server
+    let mut connection = client.connect(connect()).await?;
+    assert_eq!(
+        &echo(&mut connection, b"full handshake").await?[..],
+        b"full handshake"
+    );
+    drop(connection);
+    assert_eq!(0, counter.get());
+
+    // the resumed connection is handed out before the handshake completes so the request is
+    // sent in 0-RTT packets
+    let mut connection = client.connect(connect()).await?;
+    assert_eq!(
+        &echo(&mut connection, b"early data").await?[..],
+        b"early data"
+    );
+
+    Ok(counter.get())
+}
+
+#[test]
+fn early_data_accepted() {
+    run(async {
+        let zero_rtt_packets = resume_with_early_data(early_data::accept_all::Policy).await?;
+        assert!(zero_rtt_packets > 0);
+        Ok(())
+    });
+}
+
+#[test]
+fn early_data_rejected() {
+    run(async {
+        // the data sent in 0-RTT packets is retransmitted in 1-RTT packets
+        let zero_rtt_packets = resume_with_early_data(early_data::Default::default()).await?;
+        assert_eq!(0, zero_rtt_packets);
+        Ok(())
+    });
+}
+
+#[test]
+fn early_data_requires_tls_support() {
+    run(async {
+        let network = Network::new(1);
+
+        // early data is not enabled in the TLS provider
+        let tls = rustls::Server::builder()
+            .with_certificate(CERT_PEM, KEY_PEM)?
+            .build()?;
+        let result = Server::builder()
+            .with_tls(tls)?
+            .with_io(server_io(&network)?)?
+            .with_early_data(early_data::accept_all::Policy)?
+            .start();
+        assert!(result.is_err());
+
+        Ok(())
+    });
+}
@@ -0,0 +1,158 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::tls::{self, CipherSuite},
+    Client,
+};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+use std::sync::{mpsc, Arc};
+
+/// The details of the TLS session, as reported by a connection
+#[derive(Debug)]
+struct Info {
+    peer_certificate_chain: Arc<[Bytes]>,
+    cipher_suite: Option<CipherSuite>,
+    is_resumed: bool,
+}
+
+impl Info {
+    fn new(connection: &Connection) -> Result<Self> {
+        Ok(Self {
+            peer_certificate_chain: connection.peer_certificate_chain()?,
+            cipher_suite: connection.cipher_suite()?,
+            is_resumed: connection.is_resumed()?,
+        })
+    }
+}
+
+/// Opens two connections to a server which requires client authentication
+///
+/// The second connection resumes the session of the first one. Returns the server and client
+/// info of each connection.
+async fn connect_twice<S, C>(server_tls: S, client_tls: C) -> Result<[(Info, Info); 2]>
+where
+    S: 'static + tls::Provider,
+    C: 'static + tls::Provider,
+{
+    let network = Network::new(1);
+
+    let mut server = Server::builder()
+        .with_tls(server_tls)?
+        .with_io(server_io(&network)?)?
+        .start()?;
+    let (server_info, received) = mpsc::channel();
+    tokio::spawn(async move {
+        while let Some(connection) = server.accept().await {
+            server_info.send(Info::new(&connection).unwrap()).unwrap();
+            spawn_echo_connection(connection);
+        }
+    });
+
+    let client = Client::builder()
+        .with_tls(client_tls)?
+        .with_io(client_io(&network)?)?
+        .start()?;
+
+    let mut info = vec![];
+    for _ in 0..2 {
+        let mut connection = client.connect(connect()).await?;
+        // the session ticket is issued after the handshake so wait for a round trip
+        assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+        info.push((received.try_recv()?, Info::new(&connection)?));
+    }
+
+    let second = info.pop().unwrap();
+    let first = info.pop().unwrap();
+    Ok([first, second])
+}
+
+/// Checks the info of a full handshake, in which both peers present the same certificate
+fn assert_full_handshake((server, client): &(Info, Info)) {
+    assert_eq!(server.peer_certificate_chain.len(), 1);
+    assert_eq!(server.peer_certificate_chain, client.peer_certificate_chain);
+
+    assert!(server.cipher_suite.is_some());
+    assert_ne!(server.cipher_suite, Some(CipherSuite::Unknown));
+    assert_eq!(server.cipher_suite, client.cipher_suite);
+
+    assert!(!server.is_resumed);
+    assert!(!client.is_resumed);
+}
+
+#[cfg(feature = "provider-tls-rustls")]
+#[test]
+fn rustls_mtls() {
+    use tls::rustls;
+
+    run(async {
+        let server = rustls::Server::builder()
+            .with_client_authentication()?
+            .with_certificate(CERT_PEM, KEY_PEM)?
+            .with_trusted_certificate(CERT_PEM)?
+            .build()?;
+        let client = rustls::Client::builder()
+            .with_certificate(CERT_PEM)?
+            .with_client_identity(CERT_PEM, KEY_PEM)?
+            .with_session_tickets()?
+            .build()?;
+
+        let [first, second] = connect_twice(server, client).await?;
+
+        assert_full_handshake(&first);
+
+        // rustls stores the peer's certificates with the session
+        let (server, client) = &second;
+        assert!(server.is_resumed);
+        assert!(client.is_resumed);
+        assert_eq!(
+            server.peer_certificate_chain,
+            first.0.peer_certificate_chain
+        );
+        assert_eq!(
+            client.peer_certificate_chain,
+            first.1.peer_certificate_chain
+        );
+        assert_eq!(server.cipher_suite, first.0.cipher_suite);
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "provider-tls-s2n")]
+#[test]
+fn s2n_tls_mtls() {
+    use std::time::SystemTime;
+    use tls::s2n_tls;
+
+    run(async {
+        let server = s2n_tls::Server::builder()
+            .with_empty_trust_store()?
+            .with_client_authentication()?
+            .with_certificate(CERT_PEM, KEY_PEM)?
+            .with_trusted_certificate(CERT_PEM)?
+            .with_session_ticket_key(b"key", &[1; 32], SystemTime::now())?
+            .build()?;
+        let client = s2n_tls::Client::builder()
+            .with_empty_trust_store()?
+            .with_certificate(CERT_PEM)?
+            .with_client_identity(CERT_PEM, KEY_PEM)?
+            .with_session_tickets()?
+            .build()?;
+
+        let [first, second] = connect_twice(server, client).await?;
+
+        assert_full_handshake(&first);
+
+        // s2n-tls doesn't store the peer's certificates with the session
+        let (server, client) = &second;
+        assert!(server.is_resumed);
+        assert!(client.is_resumed);
+        assert!(server.peer_certificate_chain.is_empty());
+        assert!(client.peer_certificate_chain.is_empty());
+        assert_eq!(server.cipher_suite, first.0.cipher_suite);
+
+        Ok(())
+    });
+}
@@ -0,0 +1,205 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::event::{self, events, ConnectionInfo, ConnectionMeta},
+    Client,
+};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+use std::{
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+/// The IP of the interface the client migrates to
+const MIGRATED_IP: &str = "10.0.1.2";
+
+#[derive(Clone, Debug, PartialEq)]
+struct PathInfo {
+    id: u64,
+    local_ip: Vec<u8>,
+    remote_ip: Vec<u8>,
+    remote_cid: Vec<u8>,
+}
+
+impl<'a> From<&events::Path<'a>> for PathInfo {
+    fn from(path: &events::Path<'a>) -> Self {
+        Self {
+            id: path.id,
+            local_ip: path.local_addr.ip().to_vec(),
+            remote_ip: path.remote_addr.ip().to_vec(),
+            remote_cid: path.remote_cid.bytes.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Recorded {
+    /// The previous and new active path of each update
+    active_path_updates: Vec<(PathInfo, PathInfo)>,
+    validated_paths: Vec<u64>,
+    abandoned_paths: Vec<u64>,
+}
+
+/// Records the path changes of the connections on an endpoint
+#[derive(Clone, Default)]
+struct Paths(Arc<Mutex<Recorded>>);
+
+impl event::Subscriber for Paths {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_active_path_updated(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::ActivePathUpdated,
+    ) {
+        let update = ((&event.previous).into(), (&event.active).into());
+        self.0.lock().unwrap().active_path_updates.push(update);
+    }
+
+    fn on_path_challenge_updated(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::PathChallengeUpdated,
+    ) {
+        let mut recorded = self.0.lock().unwrap();
+        match event.path_challenge_status {
+            events::PathChallengeStatus::Validated { .. } => {
+                recorded.validated_paths.push(event.path.id)
+            }
+            events::PathChallengeStatus::Abandoned { .. } => {
+                recorded.abandoned_paths.push(event.path.id)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn ip_octets(ip: &str) -> Vec<u8> {
+    match ip.parse::<IpAddr>().unwrap() {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec(),
+    }
+}
+
+/// Starts an echo server and returns a client connection which completed a request
+async fn connect_client(
+    network: &Network,
+    server_paths: &Paths,
+    client_paths: &Paths,
+) -> Result<Connection> {
+    let server = Server::builder()
+        .with_tls((CERT_PEM, KEY_PEM))?
+        .with_io(server_io(network)?)?
+        .with_event(server_paths.clone())?
+        .start()?;
+    spawn_echo_server(server);
+
+    let client = Client::builder()
+        .with_tls(CERT_PEM)?
+        .with_io(client_io(network)?)?
+        .with_event(client_paths.clone())?
+        .start()?;
+
+    let mut connection = client.connect(connect()).await?;
+    // the requests complete the handshake and exchange connection IDs for the new path
+    for _ in 0..2 {
+        assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+    }
+
+    Ok(connection)
+}
+
+#[test]
+fn client_migration() {
+    run(async {
+        let network = Network::new(1);
+        network.set_default_link(Link::default().with_latency(Duration::from_millis(10)));
+        let server_paths = Paths::default();
+        let client_paths = Paths::default();
+        let mut connection = connect_client(&network, &server_paths, &client_paths).await?;
+
+        connection.migrate(MIGRATED_IP.parse()?)?;
+        assert_eq!(&echo(&mut connection, b"migrated").await?[..], b"migrated");
+
+        let stats = connection.stats()?;
+        assert_eq!(
+            stats.local_address.ip(),
+            MIGRATED_IP.parse::<IpAddr>()?.into()
+        );
+
+        let client = client_paths.0.lock().unwrap();
+        // the client switched to the new path immediately and validated it with a PATH_CHALLENGE
+        assert_eq!(client.active_path_updates.len(), 1);
+        let (previous, active) = &client.active_path_updates[0];
+        assert_eq!((previous.id, active.id), (0, 1));
+        assert_eq!(active.local_ip, ip_octets(MIGRATED_IP));
+        assert_eq!(client.validated_paths, [1]);
+        assert!(client.abandoned_paths.is_empty());
+        // the new path uses a connection ID the peer hasn't seen on the previous path
+        assert_ne!(previous.remote_cid, active.remote_cid);
+
+        // the server followed the client to the new address
+        let server = server_paths.0.lock().unwrap();
+        let (_, active) = server.active_path_updates.last().expect("server migrated");
+        assert_eq!(active.remote_ip, ip_octets(MIGRATED_IP));
+        assert_eq!(
+            active.local_ip,
+            ip_octets(SERVER_ADDR.split(':').next().unwrap())
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn client_migration_fallback() {
+    run(async {
+        let network = Network::new(1);
+        let link = Link::default().with_latency(Duration::from_millis(10));
+        network.set_default_link(link);
+        let server_paths = Paths::default();
+        let client_paths = Paths::default();
+        let mut connection = connect_client(&network, &server_paths, &client_paths).await?;
+
+        // nothing the client sends from the new interface reaches the server
+        let server_ip = SERVER_ADDR.parse::<SocketAddr>()?.ip();
+        network.set_link(MIGRATED_IP.parse()?, server_ip, link.with_loss(1.0));
+
+        connection.migrate(MIGRATED_IP.parse()?)?;
+        // the request is retransmitted on the previous path once validation fails
+        assert_eq!(&echo(&mut connection, b"migrated").await?[..], b"migrated");
+
+        let stats = connection.stats()?;
+        assert_ne!(
+            stats.local_address.ip(),
+            MIGRATED_IP.parse::<IpAddr>()?.into()
+        );
+
+        let client = client_paths.0.lock().unwrap();
+        assert_eq!(client.abandoned_paths, [1]);
+        assert!(client.validated_paths.is_empty());
+        let ids: Vec<_> = client
+            .active_path_updates
+            .iter()
+            .map(|(previous, active)| (previous.id, active.id))
+            .collect();
+        assert_eq!(ids, [(0, 1), (1, 0)]);
+
+        // the server never saw the new address
+        let server = server_paths.0.lock().unwrap();
+        assert!(server.active_path_updates.is_empty());
+
+        Ok(())
+    });
+}
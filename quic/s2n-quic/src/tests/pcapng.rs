@@ -0,0 +1,113 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    provider::{io::pcapng, tls::rustls},
+    Client,
+};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+use std::{collections::HashSet, convert::TryInto, fs, path::Path, time::Instant};
+
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+const DECRYPTION_SECRETS_BLOCK: u32 = 10;
+
+/// Returns the type and body of each complete block which has been written to the file
+fn blocks(path: &Path) -> Vec<(u32, Vec<u8>)> {
+    let contents = fs::read(path).unwrap_or_default();
+    let mut blocks = vec![];
+    let mut offset = 0;
+    while let Some(header) = contents.get(offset..offset + 8) {
+        let block_type = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let end = offset + u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        match contents.get(offset + 8..end - 4) {
+            Some(body) => blocks.push((block_type, body.to_vec())),
+            None => break,
+        }
+        offset = end;
+    }
+    blocks
+}
+
+#[test]
+fn capture_filter() {
+    let dir = std::env::temp_dir().join(format!("s2n-quic-capture-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("capture.pcapng");
+
+    run(async {
+        let network = Network::new(1);
+
+        // only capture the first connection
+        let mut is_first = true;
+        let capture = pcapng::Builder::default()
+            .with_file(&path)?
+            .with_filter(move |_connection| core::mem::replace(&mut is_first, false))?;
+
+        let server = Server::builder()
+            .with_tls(
+                rustls::Server::builder()
+                    .with_certificate(CERT_PEM, KEY_PEM)?
+                    .with_key_log(capture.secrets())?
+                    .build()?,
+            )?
+            .with_event(capture.subscriber())?
+            .with_io(capture.build(server_io(&network)?)?)?
+            .start()?;
+        spawn_echo_server(server);
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .start()?;
+
+        // both connections come from the same address
+        for _ in 0..2 {
+            let mut connection = client.connect(connect()).await?;
+            assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+        }
+
+        Ok(())
+    });
+
+    // wait for the writer thread to flush the capture
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut blocks = blocks(&path);
+    while !blocks
+        .iter()
+        .any(|(block_type, _)| *block_type == ENHANCED_PACKET_BLOCK)
+        && Instant::now() < deadline
+    {
+        std::thread::sleep(Duration::from_millis(10));
+        blocks = self::blocks(&path);
+    }
+
+    // the datagram which created the connection is captured
+    let (_, first_packet) = blocks
+        .iter()
+        .find(|(block_type, _)| *block_type == ENHANCED_PACKET_BLOCK)
+        .expect("the capture should include packets");
+    let flags = u32::from_le_bytes(
+        first_packet[first_packet.len() - 8..][..4]
+            .try_into()
+            .unwrap(),
+    );
+    assert_eq!(flags, 0b01, "the first packet should be inbound");
+
+    // only the secrets of the captured connection are embedded
+    let client_randoms: HashSet<_> = blocks
+        .iter()
+        .filter(|(block_type, _)| *block_type == DECRYPTION_SECRETS_BLOCK)
+        .flat_map(|(_, body)| {
+            let len = u32::from_le_bytes(body[4..8].try_into().unwrap()) as usize;
+            String::from_utf8(body[8..8 + len].to_vec())
+                .unwrap()
+                .lines()
+                .map(|line| line.split(' ').nth(1).unwrap().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(client_randoms.len(), 1);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,76 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{provider::tls, Client};
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+
+/// Opens two connections to the server and returns whether each one resumed a session
+async fn connect_twice<S, C>(server_tls: S, client_tls: C) -> Result<[bool; 2]>
+where
+    S: 'static + tls::Provider,
+    C: 'static + tls::Provider,
+{
+    let network = Network::new(1);
+
+    let server = Server::builder()
+        .with_tls(server_tls)?
+        .with_io(server_io(&network)?)?
+        .start()?;
+    spawn_echo_server(server);
+
+    let client = Client::builder()
+        .with_tls(client_tls)?
+        .with_io(client_io(&network)?)?
+        .start()?;
+
+    let mut is_resumed = [false; 2];
+    for is_resumed in is_resumed.iter_mut() {
+        let mut connection = client.connect(connect()).await?;
+        // the session ticket is issued after the handshake so wait for a round trip
+        assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+        *is_resumed = connection.is_resumed()?;
+    }
+
+    Ok(is_resumed)
+}
+
+#[cfg(feature = "provider-tls-rustls")]
+#[test]
+fn rustls_resumption() {
+    use tls::rustls;
+
+    run(async {
+        let server = rustls::Server::builder()
+            .with_certificate(CERT_PEM, KEY_PEM)?
+            .build()?;
+        let client = rustls::Client::builder()
+            .with_certificate(CERT_PEM)?
+            .with_session_tickets()?
+            .build()?;
+
+        assert_eq!(connect_twice(server, client).await?, [false, true]);
+        Ok(())
+    });
+}
+
+#[cfg(feature = "provider-tls-s2n")]
+#[test]
+fn s2n_tls_resumption() {
+    use std::time::SystemTime;
+    use tls::s2n_tls;
+
+    run(async {
+        let server = s2n_tls::Server::builder()
+            .with_certificate(CERT_PEM, KEY_PEM)?
+            .with_session_ticket_key(b"key", &[1; 32], SystemTime::now())?
+            .build()?;
+        let client = s2n_tls::Client::builder()
+            .with_certificate(CERT_PEM)?
+            .with_session_tickets()?
+            .build()?;
+
+        assert_eq!(connect_twice(server, client).await?, [false, true]);
+        Ok(())
+    });
+}
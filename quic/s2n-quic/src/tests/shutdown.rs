@@ -0,0 +1,134 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{application, connection, Client};
+use core::sync::atomic::{AtomicBool, Ordering};
+use s2n_quic_core::{
+    crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM},
+    endpoint,
+};
+use std::sync::Arc;
+
+static DATA: [u8; 100_000] = [1; 100_000];
+
+#[test]
+fn shutdown_drains_connections() {
+    run(async {
+        let network = Network::new(1);
+        network.set_default_link(Link::default().with_latency(Duration::from_millis(10)));
+
+        let mut server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .start()?;
+
+        let is_shut_down = Arc::new(AtomicBool::new(false));
+        let server = tokio::spawn({
+            let is_shut_down = is_shut_down.clone();
+            async move {
+                let connection = server
+                    .accept()
+                    .await
+                    .expect("connection should be accepted");
+                spawn_echo_connection(connection);
+
+                // start shutting down while the connection is still in use
+                server.shutdown().await?;
+                is_shut_down.store(true, Ordering::Relaxed);
+
+                // the accept loop ends once the endpoint has shut down
+                assert!(server.accept().await.is_none());
+
+                <core::result::Result<_, connection::Error>>::Ok(())
+            }
+        });
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .start()?;
+
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, &DATA).await?[..], &DATA[..]);
+
+        // the existing connection keeps working while the server is shutting down
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!is_shut_down.load(Ordering::Relaxed));
+        assert_eq!(&echo(&mut connection, &DATA).await?[..], &DATA[..]);
+
+        // new connections are no longer accepted
+        assert!(client.connect(connect()).await.is_err());
+
+        // the shutdown completes once the remaining connection has closed and drained
+        assert!(!is_shut_down.load(Ordering::Relaxed));
+        connection.close(application::Error::from(0u8));
+        drop(connection);
+        server.await??;
+        assert!(is_shut_down.load(Ordering::Relaxed));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn shutdown_with_grace_period() {
+    const SHUTTING_DOWN: u8 = 7;
+
+    run(async {
+        let network = Network::new(1);
+        network.set_default_link(Link::default().with_latency(Duration::from_millis(10)));
+
+        let mut server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .start()?;
+
+        let server = tokio::spawn(async move {
+            let connection = server
+                .accept()
+                .await
+                .expect("connection should be accepted");
+            spawn_echo_connection(connection);
+
+            server
+                .shutdown_with_grace_period(Duration::from_secs(5), SHUTTING_DOWN.into())
+                .await?;
+
+            assert!(server.accept().await.is_none());
+
+            <core::result::Result<_, connection::Error>>::Ok(())
+        });
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .start()?;
+
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, &DATA).await?[..], &DATA[..]);
+        connection.keep_alive(true)?;
+
+        // the client never closes the connection so the server closes it after the grace period
+        let error = connection
+            .accept_bidirectional_stream()
+            .await
+            .expect_err("the server should close the connection");
+        assert!(
+            matches!(
+                error,
+                connection::Error::Application {
+                    error,
+                    initiator: endpoint::Location::Remote,
+                    ..
+                } if error == SHUTTING_DOWN.into()
+            ),
+            "{:?}",
+            error
+        );
+
+        server.await??;
+
+        Ok(())
+    });
+}
@@ -0,0 +1,64 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::Client;
+use s2n_quic_core::crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM};
+
+#[test]
+fn connection_stats() {
+    static DATA: [u8; 100_000] = [1; 100_000];
+
+    run(async {
+        let network = Network::new(1);
+        let link = Link::default().with_latency(Duration::from_millis(50));
+        network.set_default_link(link);
+        // drop some of the packets sent by the client
+        let client_ip = CLIENT_ADDR.parse::<SocketAddr>()?.ip();
+        let server_ip = SERVER_ADDR.parse::<SocketAddr>()?.ip();
+        network.set_link(client_ip, server_ip, link.with_loss(0.1));
+
+        let server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .start()?;
+        spawn_echo_server(server);
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .start()?;
+
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, &DATA).await?[..], &DATA[..]);
+
+        let stats = connection.stats()?;
+
+        assert_eq!(stats.remote_address, connection.remote_addr()?.into());
+        assert_eq!(stats.local_address, connection.local_addr()?.into());
+
+        // the link adds 50ms in each direction, less the timer granularity
+        assert!(stats.min_rtt >= Duration::from_millis(99));
+        assert!(stats.smoothed_rtt >= stats.min_rtt);
+        assert!(stats.latest_rtt >= stats.min_rtt);
+        // the last packet sent by the client has been acknowledged
+        assert_eq!(stats.pto_count, 0);
+
+        assert!(stats.congestion_window > 0);
+        assert!(stats.max_datagram_size >= 1200);
+
+        // the request and response are each larger than the data they carry
+        assert!(stats.bytes_sent > DATA.len() as u64);
+        assert!(stats.bytes_received > DATA.len() as u64);
+        assert!(stats.packets_sent > 0);
+        assert!(stats.packets_received > 0);
+        assert!(stats.packets_lost > 0);
+        assert!(stats.bytes_lost > 0);
+        // the lost stream data was sent again
+        assert!(stats.packets_retransmitted > 0);
+        assert!(stats.packets_retransmitted < stats.packets_sent);
+        assert!(stats.bytes_retransmitted > 0);
+
+        Ok(())
+    });
+}
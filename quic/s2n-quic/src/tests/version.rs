@@ -0,0 +1,172 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::Client;
+use s2n_quic_core::{
+    crypto::tls::testing::certificates::{CERT_PEM, KEY_PEM},
+    packet::long::VERSION_2,
+};
+
+/// rustls only derives keys with the QUIC version 1 labels, so it never negotiates version 2
+#[cfg(feature = "provider-tls-rustls")]
+#[test]
+fn rustls_version_1_only() {
+    run(async {
+        let network = Network::new(1);
+
+        let server = Server::builder()
+            .with_tls((CERT_PEM, KEY_PEM))?
+            .with_io(server_io(&network)?)?
+            .start()?;
+        spawn_echo_server(server);
+
+        let client = Client::builder()
+            .with_tls(CERT_PEM)?
+            .with_io(client_io(&network)?)?
+            .start()?;
+
+        let mut connection = client.connect(connect()).await?;
+        assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+
+        // the client refuses to start or offer a version 2 connection
+        assert!(client
+            .connect(connect().with_quic_version(VERSION_2))
+            .await
+            .is_err());
+        assert!(client
+            .connect(connect().with_compatible_quic_version(VERSION_2))
+            .await
+            .is_err());
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "provider-tls-s2n")]
+mod compatible_version {
+    use super::*;
+    use crate::provider::{
+        event::{self, events, ConnectionInfo, ConnectionMeta},
+        tls::s2n_tls,
+    };
+    use s2n_quic_core::packet::long::VERSION_1;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Recorded {
+        initial_versions: Vec<u32>,
+        handshake_versions: Vec<u32>,
+        version_mismatches: usize,
+    }
+
+    /// Records the versions of the long header packets processed by the server
+    #[derive(Clone, Default)]
+    struct PacketVersions(Arc<Mutex<Recorded>>);
+
+    impl event::Subscriber for PacketVersions {
+        type ConnectionContext = ();
+
+        fn create_connection_context(
+            &mut self,
+            _meta: &ConnectionMeta,
+            _info: &ConnectionInfo,
+        ) -> Self::ConnectionContext {
+        }
+
+        fn on_packet_received(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::PacketReceived,
+        ) {
+            let mut recorded = self.0.lock().unwrap();
+            match event.packet_header {
+                events::PacketHeader::Initial { version, .. } => {
+                    recorded.initial_versions.push(version)
+                }
+                events::PacketHeader::Handshake { version, .. } => {
+                    recorded.handshake_versions.push(version)
+                }
+                _ => {}
+            }
+        }
+
+        fn on_packet_dropped(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::PacketDropped,
+        ) {
+            if let events::PacketDropReason::VersionMismatch { .. } = event.reason {
+                self.0.lock().unwrap().version_mismatches += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn s2n_tls_with_loss() {
+        run(async {
+            let network = Network::new(1);
+            let link = Link::default().with_latency(Duration::from_millis(50));
+            network.set_default_link(link);
+            // drop the server's first flight so the client retransmits its ClientHello in Initial
+            // packets of the original version after the server negotiated version 2
+            let client_ip = CLIENT_ADDR.parse::<SocketAddr>()?.ip();
+            let server_ip = SERVER_ADDR.parse::<SocketAddr>()?.ip();
+            network.set_link(server_ip, client_ip, link.with_loss(1.0));
+            tokio::spawn({
+                let network = network.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    network.set_link(server_ip, client_ip, link);
+                }
+            });
+
+            let versions = PacketVersions::default();
+            let tls = s2n_tls::Server::builder()
+                .with_certificate(CERT_PEM, KEY_PEM)?
+                .build()?;
+            let server = Server::builder()
+                .with_tls(tls)?
+                .with_io(server_io(&network)?)?
+                .with_event(versions.clone())?
+                .start()?;
+            spawn_echo_server(server);
+
+            let tls = s2n_tls::Client::builder()
+                .with_certificate(CERT_PEM)?
+                .build()?;
+            let client = Client::builder()
+                .with_tls(tls)?
+                .with_io(client_io(&network)?)?
+                .start()?;
+
+            let connect = connect().with_compatible_quic_version(VERSION_2);
+            let mut connection = client.connect(connect).await?;
+            assert_eq!(&echo(&mut connection, b"hello").await?[..], b"hello");
+
+            let recorded = versions.0.lock().unwrap();
+            let original = recorded
+                .initial_versions
+                .iter()
+                .filter(|version| **version == VERSION_1)
+                .count();
+            // the first ClientHello and at least one retransmission
+            assert!(original >= 2, "{:?}", recorded);
+            // the client switched to the negotiated version once the server's flight arrived
+            assert!(!recorded.handshake_versions.is_empty(), "{:?}", recorded);
+            assert!(
+                recorded
+                    .handshake_versions
+                    .iter()
+                    .all(|version| *version == VERSION_2),
+                "{:?}",
+                recorded
+            );
+            assert_eq!(recorded.version_mismatches, 0);
+
+            Ok(())
+        });
+    }
+}